@@ -6,7 +6,9 @@ use meilisearch_types::error::ResponseError;
 use meilisearch_types::keys::Key;
 use meilisearch_types::milli::update::IndexDocumentsMethod;
 use meilisearch_types::settings::Unchecked;
-use meilisearch_types::tasks::{Details, IndexSwap, KindWithContent, Status, Task, TaskId};
+use meilisearch_types::tasks::{
+    Details, IndexSwap, KindWithContent, Status, Task, TaskId, TaskPriority,
+};
 use meilisearch_types::InstanceUid;
 use roaring::RoaringBitmap;
 use serde::{Deserialize, Serialize};
@@ -66,6 +68,9 @@ pub struct TaskDump {
     pub status: Status,
     #[serde(rename = "type")]
     pub kind: KindDump,
+    // The priorities were introduced in v1.15, everything prior to this version is `Normal`.
+    #[serde(default, skip_serializing_if = "TaskPriority::is_normal")]
+    pub priority: TaskPriority,
 
     #[serde(skip_serializing_if = "Option::is_none")]
     pub canceled_by: Option<TaskId>,
@@ -89,6 +94,22 @@ pub struct TaskDump {
         default
     )]
     pub finished_at: Option<OffsetDateTime>,
+    // The scheduled tasks were introduced in v1.15, everything prior to this version is `None`.
+    #[serde(
+        with = "time::serde::rfc3339::option",
+        skip_serializing_if = "Option::is_none",
+        default
+    )]
+    pub run_after: Option<OffsetDateTime>,
+    // The task groups were introduced in v1.15, everything prior to this version is `None`.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub group_uid: Option<TaskId>,
+    // The task retries were introduced in v1.15, everything prior to this version is `0`.
+    #[serde(skip_serializing_if = "meilisearch_types::tasks::is_zero", default)]
+    pub retries: u32,
+    // The interrupted steps were introduced in v1.15, everything prior to this version is `None`.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub interrupted_step: Option<String>,
 }
 
 // A `Kind` specific version made for the dump. If modified you may break the dump.
@@ -154,12 +175,17 @@ impl From<Task> for TaskDump {
             index_uid: task.index_uid().map(|uid| uid.to_string()),
             status: task.status,
             kind: task.kind.into(),
+            priority: task.priority,
             canceled_by: task.canceled_by,
             details: task.details,
             error: task.error,
             enqueued_at: task.enqueued_at,
             started_at: task.started_at,
             finished_at: task.finished_at,
+            run_after: task.run_after,
+            group_uid: task.group_uid,
+            retries: task.retries,
+            interrupted_step: task.interrupted_step,
         }
     }
 }
@@ -237,7 +263,7 @@ pub(crate) mod test {
     use meilisearch_types::milli::{self, FilterableAttributesRule};
     use meilisearch_types::settings::{Checked, FacetingSettings, Settings};
     use meilisearch_types::task_view::DetailsView;
-    use meilisearch_types::tasks::{BatchStopReason, Details, Kind, Status};
+    use meilisearch_types::tasks::{BatchStopReason, Details, Kind, Status, TaskPriority};
     use serde_json::{json, Map, Value};
     use time::macros::datetime;
     use uuid::Uuid;
@@ -305,6 +331,11 @@ pub(crate) mod test {
             localized_attributes: Setting::NotSet,
             facet_search: Setting::NotSet,
             prefix_search: Setting::NotSet,
+            stemming: Setting::NotSet,
+            synonym_rules: Setting::NotSet,
+            searchable_attributes_weights: Setting::NotSet,
+            ngram_attributes: Setting::NotSet,
+            query_rules: Setting::NotSet,
             _kind: std::marker::PhantomData,
         };
         settings.check()
@@ -352,6 +383,7 @@ pub(crate) mod test {
                         primary_key: Some(S("bone")),
                         documents_count: 12,
                     },
+                    priority: TaskPriority::Normal,
                     canceled_by: None,
                     details: Some(Details::DocumentAdditionOrUpdate {
                         received_documents: 12,
@@ -361,6 +393,10 @@ pub(crate) mod test {
                     enqueued_at: datetime!(2022-11-11 0:00 UTC),
                     started_at: Some(datetime!(2022-11-20 0:00 UTC)),
                     finished_at: Some(datetime!(2022-11-21 0:00 UTC)),
+                    run_after: None,
+                    group_uid: None,
+                    retries: 0,
+                    interrupted_step: None,
                 },
                 None,
             ),
@@ -376,6 +412,7 @@ pub(crate) mod test {
                         primary_key: None,
                         documents_count: 2,
                     },
+                    priority: TaskPriority::Normal,
                     canceled_by: None,
                     details: Some(Details::DocumentAdditionOrUpdate {
                         received_documents: 2,
@@ -385,6 +422,10 @@ pub(crate) mod test {
                     enqueued_at: datetime!(2022-11-11 0:00 UTC),
                     started_at: None,
                     finished_at: None,
+                    run_after: None,
+                    group_uid: None,
+                    retries: 0,
+                    interrupted_step: None,
                 },
                 Some(vec![
                     json!({ "id": 4, "race": "leonberg" }).as_object().unwrap().clone(),
@@ -398,12 +439,17 @@ pub(crate) mod test {
                     index_uid: Some(S("catto")),
                     status: Status::Enqueued,
                     kind: KindDump::IndexDeletion,
+                    priority: TaskPriority::Normal,
                     canceled_by: None,
                     details: None,
                     error: None,
                     enqueued_at: datetime!(2022-11-15 0:00 UTC),
                     started_at: None,
                     finished_at: None,
+                    run_after: None,
+                    group_uid: None,
+                    retries: 0,
+                    interrupted_step: None,
                 },
                 None,
             ),
@@ -421,6 +467,7 @@ pub(crate) mod test {
                 expires_at: Some(datetime!(4130-03-14 12:21 UTC)),
                 created_at: datetime!(1960-11-15 0:00 UTC),
                 updated_at: datetime!(2022-11-10 0:00 UTC),
+                task_priority: None,
             },
             Key {
                 description: Some(S("The master key for everything and even the doggos")),
@@ -431,6 +478,7 @@ pub(crate) mod test {
                 expires_at: None,
                 created_at: datetime!(0000-01-01 00:01 UTC),
                 updated_at: datetime!(1964-05-04 17:25 UTC),
+                task_priority: None,
             },
             Key {
                 description: Some(S("The useless key to for nothing nor the doggos")),
@@ -441,6 +489,7 @@ pub(crate) mod test {
                 expires_at: None,
                 created_at: datetime!(400-02-29 0:00 UTC),
                 updated_at: datetime!(1024-02-29 0:00 UTC),
+                task_priority: None,
             },
         ]
     }
@@ -127,6 +127,7 @@ impl CompatV5ToV6 {
                             v6::Kind::DumpCreation { keys: keys.clone(), instance_uid }
                         }
                     },
+                    priority: v6::TaskPriority::Normal,
                     canceled_by: None,
                     details: task_view.details.map(|details| match details {
                         v5::Details::DocumentAddition { received_documents, indexed_documents } => {
@@ -159,6 +160,10 @@ impl CompatV5ToV6 {
                     enqueued_at: task_view.enqueued_at,
                     started_at: task_view.started_at,
                     finished_at: task_view.finished_at,
+                    run_after: None,
+                    group_uid: None,
+                    retries: 0,
+                    interrupted_step: None,
                 };
 
                 (task, content_file)
@@ -189,6 +194,7 @@ impl CompatV5ToV6 {
                 expires_at: key.expires_at,
                 created_at: key.created_at,
                 updated_at: key.updated_at,
+                task_priority: None,
             })
         })))
     }
@@ -374,6 +380,7 @@ impl<T> From<v5::Settings<T>> for v6::Settings<v6::Unchecked> {
                     disable_on_words: typo.disable_on_words.into(),
                     disable_on_attributes: typo.disable_on_attributes.into(),
                     disable_on_numbers: v6::Setting::NotSet,
+                    per_attribute: v6::Setting::NotSet,
                 }),
                 v5::Setting::Reset => v6::Setting::Reset,
                 v5::Setting::NotSet => v6::Setting::NotSet,
@@ -398,6 +405,11 @@ impl<T> From<v5::Settings<T>> for v6::Settings<v6::Unchecked> {
             search_cutoff_ms: v6::Setting::NotSet,
             facet_search: v6::Setting::NotSet,
             prefix_search: v6::Setting::NotSet,
+            stemming: v6::Setting::NotSet,
+            synonym_rules: v6::Setting::NotSet,
+            searchable_attributes_weights: v6::Setting::NotSet,
+            ngram_attributes: v6::Setting::NotSet,
+            query_rules: v6::Setting::NotSet,
             _kind: std::marker::PhantomData,
         }
     }
@@ -29,6 +29,7 @@ pub type Network = meilisearch_types::features::Network;
 pub type Status = meilisearch_types::tasks::Status;
 pub type Kind = crate::KindDump;
 pub type Details = meilisearch_types::tasks::Details;
+pub type TaskPriority = meilisearch_types::tasks::TaskPriority;
 
 // everything related to the settings
 pub type Setting<T> = meilisearch_types::milli::update::Setting<T>;
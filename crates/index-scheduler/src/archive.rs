@@ -0,0 +1,84 @@
+//! The archival of the tasks and batches deleted from the task queue.
+//!
+//! When an archive directory is configured, every task deletion, including the ones automatically
+//! registered when the task queue is full, first writes the deleted tasks and batches in their
+//! public API format to gzip-compressed NDJSON files named
+//! `tasks-{first}-{last}-{timestamp}.ndjson.gz` and `batches-{first}-{last}-{timestamp}.ndjson.gz`,
+//! where `first` and `last` are the lowest and highest archived uids and `timestamp` is the
+//! archival date in milliseconds since the epoch.
+//!
+//! The uids restart from zero when the database is recreated, so the existing archives are never
+//! overwritten: a numeric suffix is appended to the name of an archive if it is already taken.
+
+use std::fs;
+use std::io::{BufWriter, ErrorKind, Write};
+use std::path::{Path, PathBuf};
+
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use serde::Serialize;
+use tempfile::NamedTempFile;
+use time::OffsetDateTime;
+
+use crate::Result;
+
+/// Writes the entries of an archive file in a temporary file that is only moved
+/// to its final place once all the entries have been written.
+pub(crate) struct ArchiveWriter {
+    dir: PathBuf,
+    name: &'static str,
+    file: Option<GzEncoder<BufWriter<NamedTempFile>>>,
+    uids: Option<(u32, u32)>,
+}
+
+impl ArchiveWriter {
+    /// Prepares an archive, nothing is written to disk until an entry is pushed.
+    pub(crate) fn new(dir: &Path, name: &'static str) -> Self {
+        ArchiveWriter { dir: dir.to_path_buf(), name, file: None, uids: None }
+    }
+
+    pub(crate) fn push(&mut self, uid: u32, entry: &impl Serialize) -> Result<()> {
+        if self.file.is_none() {
+            fs::create_dir_all(&self.dir)?;
+            let file = NamedTempFile::new_in(&self.dir)?;
+            self.file = Some(GzEncoder::new(BufWriter::new(file), Compression::default()));
+        }
+        // safe to unwrap, the file was created above if it didn't exist yet
+        let file = self.file.as_mut().unwrap();
+        serde_json::to_writer(&mut *file, entry).map_err(std::io::Error::from)?;
+        file.write_all(b"\n")?;
+
+        self.uids = Some(match self.uids {
+            Some((first, last)) => (first.min(uid), last.max(uid)),
+            None => (uid, uid),
+        });
+        Ok(())
+    }
+
+    /// Syncs the archive to disk and returns its path, or `None` if no entry was pushed.
+    pub(crate) fn persist(self) -> Result<Option<PathBuf>> {
+        let (Some(file), Some((first, last))) = (self.file, self.uids) else {
+            return Ok(None);
+        };
+        let mut file = file.finish()?.into_inner().map_err(|e| e.into_error())?;
+        file.as_file().sync_all()?;
+
+        let timestamp = OffsetDateTime::now_utc().unix_timestamp_nanos() / 1_000_000;
+        let mut suffix = 0;
+        loop {
+            let name = match suffix {
+                0 => format!("{}-{first}-{last}-{timestamp}.ndjson.gz", self.name),
+                suffix => format!("{}-{first}-{last}-{timestamp}-{suffix}.ndjson.gz", self.name),
+            };
+            let path = self.dir.join(name);
+            match file.persist_noclobber(&path) {
+                Ok(_) => return Ok(Some(path)),
+                Err(e) if e.error.kind() == ErrorKind::AlreadyExists => {
+                    file = e.file;
+                    suffix += 1;
+                }
+                Err(e) => return Err(e.into()),
+            }
+        }
+    }
+}
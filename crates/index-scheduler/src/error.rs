@@ -112,12 +112,24 @@ pub enum Error {
     TaskNotFound(TaskId),
     #[error("Task `{0}` does not contain any documents. Only `documentAdditionOrUpdate` tasks with the statuses `enqueued` or `processing` contain documents")]
     TaskFileNotFound(TaskId),
+    #[error("Task `{0}` cannot be retried because its documents were deleted. Only the document additions that failed because of a transient error keep their documents.")]
+    TaskNotRetriable(TaskId),
     #[error("Batch `{0}` not found.")]
     BatchNotFound(BatchId),
+    #[error("Webhook `{0}` not found.")]
+    WebhookNotFound(String),
     #[error("Query parameters to filter the tasks to delete are missing. Available query parameters are: `uids`, `indexUids`, `statuses`, `types`, `canceledBy`, `beforeEnqueuedAt`, `afterEnqueuedAt`, `beforeStartedAt`, `afterStartedAt`, `beforeFinishedAt`, `afterFinishedAt`.")]
     TaskDeletionWithEmptyQuery,
     #[error("Query parameters to filter the tasks to cancel are missing. Available query parameters are: `uids`, `indexUids`, `statuses`, `types`, `canceledBy`, `beforeEnqueuedAt`, `afterEnqueuedAt`, `beforeStartedAt`, `afterStartedAt`, `beforeFinishedAt`, `afterFinishedAt`.")]
     TaskCancelationWithEmptyQuery,
+    #[error("Query parameters to filter the tasks to retry are missing. Available query parameters are: `uids`, `indexUids`, `statuses`, `types`, `canceledBy`, `beforeEnqueuedAt`, `afterEnqueuedAt`, `beforeStartedAt`, `afterStartedAt`, `beforeFinishedAt`, `afterFinishedAt`.")]
+    TaskRetryWithEmptyQuery,
+    #[error("Tasks of type `{0}` cannot be part of a task group. Only document operations and settings updates can be grouped.")]
+    InvalidTaskGroupKind(Kind),
+    #[error("All the tasks of a task group must operate on the same index.")]
+    TaskGroupWithMultipleIndexes,
+    #[error("The task group was rolled back because task `{task}` failed: {message}")]
+    TaskGroupFailed { task: TaskId, message: String, internal: bool },
     #[error("Aborted task")]
     AbortedTask,
 
@@ -209,9 +221,15 @@ impl Error {
             | Error::InvalidIndexUid { .. }
             | Error::TaskNotFound(_)
             | Error::TaskFileNotFound(_)
+            | Error::TaskNotRetriable(_)
             | Error::BatchNotFound(_)
+            | Error::WebhookNotFound(_)
             | Error::TaskDeletionWithEmptyQuery
             | Error::TaskCancelationWithEmptyQuery
+            | Error::TaskRetryWithEmptyQuery
+            | Error::InvalidTaskGroupKind(_)
+            | Error::TaskGroupWithMultipleIndexes
+            | Error::TaskGroupFailed { .. }
             | Error::AbortedTask
             | Error::Dump(_)
             | Error::Heed(_)
@@ -235,6 +253,22 @@ impl Error {
         }
     }
 
+    /// Whether the error is likely to disappear by itself, e.g. an outage of a remote embedder,
+    /// in which case the failed tasks can be retried later.
+    pub fn is_transient(&self) -> bool {
+        match self {
+            Error::WithCustomErrorCode(_, error) => error.is_transient(),
+            Error::Milli {
+                error:
+                    milli::Error::UserError(milli::UserError::DocumentEmbeddingError {
+                        transient, ..
+                    }),
+                ..
+            } => *transient,
+            _ => false,
+        }
+    }
+
     pub fn with_custom_error_code(self, code: Code) -> Self {
         Self::WithCustomErrorCode(code, Box::new(self))
     }
@@ -274,9 +308,16 @@ impl ErrorCode for Error {
             Error::InvalidIndexUid { .. } => Code::InvalidIndexUid,
             Error::TaskNotFound(_) => Code::TaskNotFound,
             Error::TaskFileNotFound(_) => Code::TaskFileNotFound,
+            Error::TaskNotRetriable(_) => Code::TaskNotRetriable,
             Error::BatchNotFound(_) => Code::BatchNotFound,
+            Error::WebhookNotFound(_) => Code::WebhookNotFound,
             Error::TaskDeletionWithEmptyQuery => Code::MissingTaskFilters,
             Error::TaskCancelationWithEmptyQuery => Code::MissingTaskFilters,
+            Error::TaskRetryWithEmptyQuery => Code::MissingTaskFilters,
+            Error::InvalidTaskGroupKind(_) => Code::InvalidTaskGroupOperations,
+            Error::TaskGroupWithMultipleIndexes => Code::InvalidTaskGroupOperations,
+            Error::TaskGroupFailed { internal: false, .. } => Code::TaskGroupFailed,
+            Error::TaskGroupFailed { internal: true, .. } => Code::TaskGroupInternalError,
             // TODO: not sure of the Code to use
             Error::NoSpaceLeftInTaskQueue => Code::NoSpaceLeftOnDevice,
             Error::Dump(e) => e.error_code(),
@@ -0,0 +1,42 @@
+//! The subscribers to the changes of the tasks.
+//!
+//! Every time a task is registered, starts being processed or finishes being processed, it is
+//! sent to all the subscribers. It is used to push the task changes to the clients as they happen.
+
+use meilisearch_types::tasks::Task;
+use tokio::sync::broadcast;
+
+/// The number of task changes a subscriber can lag behind before the oldest changes are dropped.
+const SUBSCRIBER_CAPACITY: usize = 1024;
+
+#[derive(Clone)]
+pub(crate) struct TaskEventSubscribers(broadcast::Sender<Task>);
+
+impl Default for TaskEventSubscribers {
+    fn default() -> Self {
+        let (sender, _receiver) = broadcast::channel(SUBSCRIBER_CAPACITY);
+        Self(sender)
+    }
+}
+
+impl TaskEventSubscribers {
+    pub fn subscribe(&self) -> broadcast::Receiver<Task> {
+        self.0.subscribe()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.receiver_count() == 0
+    }
+
+    /// Send the tasks to every subscriber, a subscription ends when its receiver is dropped.
+    pub fn broadcast(&self, tasks: &[Task]) {
+        if self.is_empty() {
+            return;
+        }
+
+        for task in tasks {
+            // There is no error as long as there is a subscriber.
+            let _ = self.0.send(task.clone());
+        }
+    }
+}
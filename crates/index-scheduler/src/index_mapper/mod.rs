@@ -80,7 +80,8 @@ pub struct IndexMapper {
 
     /// A few types of long running batches of tasks that act on a single index set this field
     /// so that a handle to the index is available from other threads (search) in an optimized manner.
-    currently_updating_index: Arc<RwLock<Option<(String, Index)>>>,
+    /// There are several of them when batches of different indexes are processed in parallel.
+    currently_updating_indexes: Arc<RwLock<Vec<(String, Index)>>>,
 }
 
 /// Whether the index is available for use or is forbidden to be inserted back in the index map
@@ -179,7 +180,7 @@ impl IndexMapper {
             index_growth_amount: options.index_growth_amount,
             enable_mdb_writemap: options.enable_mdb_writemap,
             indexer_config: options.indexer_config.clone(),
-            currently_updating_index: Default::default(),
+            currently_updating_indexes: Default::default(),
         })
     }
 
@@ -343,12 +344,14 @@ impl IndexMapper {
 
     /// Return an index, may open it if it wasn't already opened.
     pub fn index(&self, rtxn: &RoTxn, name: &str) -> Result<Index> {
-        if let Some((current_name, current_index)) =
-            self.currently_updating_index.read().unwrap().as_ref()
+        if let Some((_, current_index)) = self
+            .currently_updating_indexes
+            .read()
+            .unwrap()
+            .iter()
+            .find(|(current_name, _)| current_name == name)
         {
-            if current_name == name {
-                return Ok(current_index.clone());
-            }
+            return Ok(current_index.clone());
         }
 
         let uuid = self
@@ -439,7 +442,7 @@ impl IndexMapper {
         to: (u32, u32, u32),
     ) -> Result<RollbackOutcome> {
         // remove any currently updating index to make sure that we aren't keeping a reference to the index somewhere
-        drop(self.currently_updating_index.write().unwrap().take());
+        self.clear_currently_updating_indexes();
 
         let uuid = self
             .index_mapping
@@ -574,7 +577,11 @@ impl IndexMapper {
         &self.indexer_config
     }
 
-    pub fn set_currently_updating_index(&self, index: Option<(String, Index)>) {
-        *self.currently_updating_index.write().unwrap() = index;
+    pub fn add_currently_updating_index(&self, name: String, index: Index) {
+        self.currently_updating_indexes.write().unwrap().push((name, index));
+    }
+
+    pub fn clear_currently_updating_indexes(&self) {
+        self.currently_updating_indexes.write().unwrap().clear();
     }
 }
@@ -28,12 +28,16 @@ pub fn snapshot_index_scheduler(scheduler: &IndexScheduler) -> String {
 
         index_mapper,
         features: _,
+        pause: _,
         webhook_url: _,
         webhook_authorization_header: _,
+        webhooks: _,
+        task_events: _,
         test_breakpoint_sdr: _,
         planned_failures: _,
         run_loop_iteration: _,
         embedders: _,
+        rerankers: _,
     } = scheduler;
 
     let rtxn = env.read_txn().unwrap();
@@ -211,6 +215,11 @@ pub fn snapshot_task(task: &Task) -> String {
         details,
         status,
         kind,
+        priority,
+        run_after,
+        group_uid,
+        retries,
+        interrupted_step,
     } = task;
     snap.push('{');
     snap.push_str(&format!("uid: {uid}, "));
@@ -218,9 +227,24 @@ pub fn snapshot_task(task: &Task) -> String {
         snap.push_str(&format!("batch_uid: {batch_uid}, "));
     }
     snap.push_str(&format!("status: {status}, "));
+    if !priority.is_normal() {
+        snap.push_str(&format!("priority: {priority}, "));
+    }
+    if let Some(run_after) = run_after {
+        snap.push_str(&format!("run_after: {run_after}, "));
+    }
+    if let Some(group_uid) = group_uid {
+        snap.push_str(&format!("group_uid: {group_uid}, "));
+    }
+    if *retries != 0 {
+        snap.push_str(&format!("retries: {retries}, "));
+    }
     if let Some(canceled_by) = canceled_by {
         snap.push_str(&format!("canceled_by: {canceled_by}, "));
     }
+    if let Some(interrupted_step) = interrupted_step {
+        snap.push_str(&format!("interrupted_step: {interrupted_step:?}, "));
+    }
     if let Some(error) = error {
         snap.push_str(&format!("error: {error:?}, "));
     }
@@ -18,13 +18,16 @@ called asynchronously from any thread. These methods can either query the
 content of the scheduler or enqueue new tasks.
 */
 
+mod archive;
 mod dump;
 pub mod error;
+mod events;
 mod features;
 mod index_mapper;
 #[cfg(test)]
 mod insta_snapshot;
 mod lru;
+mod pause;
 mod processing;
 mod queue;
 mod scheduler;
@@ -34,11 +37,12 @@ pub mod upgrade;
 mod utils;
 pub mod uuid_codec;
 pub mod versioning;
+mod webhooks;
 
 pub type Result<T, E = Error> = std::result::Result<T, E>;
 pub type TaskId = u32;
 
-use std::collections::{BTreeMap, HashMap};
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::io::{self, BufReader, Read};
 use std::panic::{catch_unwind, AssertUnwindSafe};
 use std::path::{Path, PathBuf};
@@ -47,27 +51,36 @@ use std::time::Duration;
 
 use dump::Dump;
 pub use error::Error;
+use events::TaskEventSubscribers;
 pub use features::RoFeatures;
 use flate2::bufread::GzEncoder;
 use flate2::Compression;
-use meilisearch_types::batches::Batch;
+use meilisearch_types::batches::{Batch, BatchId};
 use meilisearch_types::features::{InstanceTogglableFeatures, Network, RuntimeTogglableFeatures};
 use meilisearch_types::heed::byteorder::BE;
 use meilisearch_types::heed::types::I128;
 use meilisearch_types::heed::{self, Env, RoTxn, WithoutTls};
 use meilisearch_types::milli::index::IndexEmbeddingConfig;
+use meilisearch_types::milli::progress::ProgressView;
+use meilisearch_types::milli::prompt::Prompt;
 use meilisearch_types::milli::update::IndexerConfig;
+use meilisearch_types::milli::vector::rerank::{Reranker, RerankerSettings};
 use meilisearch_types::milli::vector::{Embedder, EmbedderOptions, EmbeddingConfigs};
 use meilisearch_types::milli::{self, Index};
 use meilisearch_types::task_view::TaskView;
-use meilisearch_types::tasks::{KindWithContent, Task};
+use meilisearch_types::tasks::{KindWithContent, QueuePause, Status, Task};
+use meilisearch_types::webhooks::{Webhook, WebhookDelivery};
+use pause::PauseState;
 use processing::ProcessingTasks;
-pub use queue::Query;
 use queue::Queue;
+pub use queue::{Query, TaskOptions};
 use roaring::RoaringBitmap;
 use scheduler::Scheduler;
 use time::OffsetDateTime;
+use uuid::Uuid;
 use versioning::Versioning;
+use webhooks::WebhookStore;
+pub use webhooks::{sign_webhook_payload, WEBHOOK_SIGNATURE_HEADER};
 
 use crate::index_mapper::IndexMapper;
 use crate::utils::clamp_to_page_size;
@@ -131,6 +144,22 @@ pub struct IndexSchedulerOptions {
     ///
     /// 0 disables the cache.
     pub embedding_cache_cap: usize,
+    /// The maximum number of batches targeting different indexes that are processed in parallel.
+    ///
+    /// 1 processes the batches one after the other.
+    pub max_parallel_batches: usize,
+    /// The maximum number of times a task that failed because of a transient error
+    /// is automatically enqueued again.
+    ///
+    /// 0 disables the automatic retries.
+    pub max_task_retries: u32,
+    /// The delay before the first automatic retry of a task, doubled at each new attempt.
+    pub task_retry_delay: std::time::Duration,
+    /// The path to the folder in which the finished tasks and batches are archived before
+    /// being deleted from the task queue.
+    ///
+    /// `None` deletes them without archiving them.
+    pub tasks_archive_path: Option<PathBuf>,
 }
 
 /// Structure which holds meilisearch's indexes and schedules the tasks
@@ -151,6 +180,9 @@ pub struct IndexScheduler {
     /// In charge of fetching and setting the status of experimental features.
     features: features::FeatureData,
 
+    /// Whether the task queue is paused, globally or for some indexes.
+    pause: PauseState,
+
     /// Everything related to the processing of the tasks
     pub scheduler: scheduler::Scheduler,
 
@@ -161,6 +193,11 @@ pub struct IndexScheduler {
     pub(crate) webhook_url: Option<String>,
     /// The Authorization header to send to the webhook URL.
     pub(crate) webhook_authorization_header: Option<String>,
+    /// The webhooks registered through the `/webhooks` route and their delivery log.
+    webhooks: WebhookStore,
+
+    /// The subscribers to the changes of the tasks.
+    pub(crate) task_events: TaskEventSubscribers,
 
     /// A map to retrieve the runtime representation of an embedder depending on its configuration.
     ///
@@ -169,6 +206,9 @@ pub struct IndexScheduler {
     /// to the same embeddings for the same input text.
     embedders: Arc<RwLock<HashMap<EmbedderOptions, Arc<Embedder>>>>,
 
+    /// A map to retrieve the runtime representation of a reranker depending on its configuration.
+    rerankers: Arc<RwLock<HashMap<RerankerSettings, Arc<Reranker>>>>,
+
     // ================= test
     // The next entry is dedicated to the tests.
     /// Provide a way to set a breakpoint in multiple part of the scheduler.
@@ -201,7 +241,10 @@ impl IndexScheduler {
             cleanup_enabled: self.cleanup_enabled,
             webhook_url: self.webhook_url.clone(),
             webhook_authorization_header: self.webhook_authorization_header.clone(),
+            webhooks: self.webhooks.clone(),
+            task_events: self.task_events.clone(),
             embedders: self.embedders.clone(),
+            rerankers: self.rerankers.clone(),
             #[cfg(test)]
             test_breakpoint_sdr: self.test_breakpoint_sdr.clone(),
             #[cfg(test)]
@@ -209,11 +252,17 @@ impl IndexScheduler {
             #[cfg(test)]
             run_loop_iteration: self.run_loop_iteration.clone(),
             features: self.features.clone(),
+            pause: self.pause.clone(),
         }
     }
 
     pub(crate) const fn nb_db() -> u32 {
-        Versioning::nb_db() + Queue::nb_db() + IndexMapper::nb_db() + features::FeatureData::nb_db()
+        Versioning::nb_db()
+            + Queue::nb_db()
+            + IndexMapper::nb_db()
+            + features::FeatureData::nb_db()
+            + WebhookStore::nb_db()
+            + PauseState::nb_db()
     }
 
     /// Create an index scheduler and start its run loop.
@@ -267,6 +316,8 @@ impl IndexScheduler {
         let features = features::FeatureData::new(&env, &mut wtxn, options.instance_features)?;
         let queue = Queue::new(&env, &mut wtxn, &options)?;
         let index_mapper = IndexMapper::new(&env, &mut wtxn, &options, budget)?;
+        let webhooks = WebhookStore::new(&env, &mut wtxn)?;
+        let pause = PauseState::new(&env, &mut wtxn)?;
         wtxn.commit()?;
 
         // allow unreachable_code to get rids of the warning in the case of a test build.
@@ -281,7 +332,10 @@ impl IndexScheduler {
             cleanup_enabled: options.cleanup_enabled,
             webhook_url: options.webhook_url,
             webhook_authorization_header: options.webhook_authorization_header,
+            webhooks,
+            task_events: TaskEventSubscribers::default(),
             embedders: Default::default(),
+            rerankers: Default::default(),
 
             #[cfg(test)]
             test_breakpoint_sdr,
@@ -290,6 +344,7 @@ impl IndexScheduler {
             #[cfg(test)]
             run_loop_iteration: Arc::new(RwLock::new(0)),
             features,
+            pause,
         };
 
         this.run();
@@ -395,6 +450,10 @@ impl IndexScheduler {
                     match ret {
                         Ok(Ok(TickOutcome::TickAgain(_))) => (),
                         Ok(Ok(TickOutcome::WaitForSignal)) => run.scheduler.wake_up.wait(),
+                        Ok(Ok(TickOutcome::WaitUntil(date))) => {
+                            let timeout = date - OffsetDateTime::now_utc();
+                            run.scheduler.wake_up.wait_timeout(timeout.try_into().unwrap_or_default());
+                        }
                         Ok(Ok(TickOutcome::StopProcessingForever)) => break,
                         Ok(Err(e)) => {
                             tracing::error!("{e}");
@@ -637,6 +696,19 @@ impl IndexScheduler {
         kind: KindWithContent,
         task_id: Option<TaskId>,
         dry_run: bool,
+    ) -> Result<Task> {
+        self.register_with_options(kind, task_id, dry_run, TaskOptions::default())
+    }
+
+    /// Register a new task in the scheduler with the given [`TaskOptions`].
+    ///
+    /// If it fails and data was associated with the task, it tries to delete the associated data.
+    pub fn register_with_options(
+        &self,
+        kind: KindWithContent,
+        task_id: Option<TaskId>,
+        dry_run: bool,
+        options: TaskOptions,
     ) -> Result<Task> {
         // if the task doesn't delete or cancel anything and 40% of the task queue is full, we must refuse to enqueue the incoming task
         if !matches!(&kind, KindWithContent::TaskDeletion { tasks, .. } | KindWithContent::TaskCancelation { tasks, .. } if !tasks.is_empty())
@@ -647,7 +719,7 @@ impl IndexScheduler {
         }
 
         let mut wtxn = self.env.write_txn()?;
-        let task = self.queue.register(&mut wtxn, &kind, task_id, dry_run)?;
+        let task = self.queue.register(&mut wtxn, &kind, task_id, dry_run, options, None)?;
 
         // If the registered task is a task cancelation
         // we inform the processing tasks to stop (if necessary).
@@ -664,11 +736,158 @@ impl IndexScheduler {
             return Err(e.into());
         }
 
+        if !dry_run {
+            self.task_events.broadcast(std::slice::from_ref(&task));
+        }
+
         // notify the scheduler loop to execute a new tick
         self.scheduler.wake_up.signal();
         Ok(task)
     }
 
+    /// Register the given operations as a task group.
+    ///
+    /// The tasks of a group are registered with consecutive uids and processed in a single
+    /// batch, their changes are committed in the same write transaction: either all of them
+    /// succeed or all of them fail. Only document operations and settings updates of the
+    /// same index can be grouped.
+    ///
+    /// If it fails and data was associated with the tasks, it tries to delete the associated data.
+    pub fn register_group(
+        &self,
+        kinds: Vec<KindWithContent>,
+        dry_run: bool,
+        options: TaskOptions,
+    ) -> Result<Vec<Task>> {
+        let mut index_uid = None;
+        for kind in &kinds {
+            if !matches!(
+                kind,
+                KindWithContent::DocumentAdditionOrUpdate { .. }
+                    | KindWithContent::DocumentDeletion { .. }
+                    | KindWithContent::DocumentDeletionByFilter { .. }
+                    | KindWithContent::DocumentClear { .. }
+                    | KindWithContent::SettingsUpdate { .. }
+            ) {
+                return Err(Error::InvalidTaskGroupKind(kind.as_kind()));
+            }
+            let indexes = kind.indexes();
+            if *index_uid.get_or_insert(indexes[0]) != indexes[0] {
+                return Err(Error::TaskGroupWithMultipleIndexes);
+            }
+        }
+
+        if (self.env.non_free_pages_size()? * 100) / self.env.info().map_size as u64
+            > TASK_SCHEDULER_SIZE_THRESHOLD_PERCENT_INT
+        {
+            return Err(Error::NoSpaceLeftInTaskQueue);
+        }
+
+        let mut wtxn = self.env.write_txn()?;
+        // The uid of the first task identifies the group.
+        let group_uid = self.queue.tasks.next_task_id(&wtxn)?;
+        let mut tasks = Vec::with_capacity(kinds.len());
+        for kind in &kinds {
+            let task = self.queue.register(
+                &mut wtxn,
+                kind,
+                None,
+                dry_run,
+                options.clone(),
+                Some(group_uid),
+            )?;
+            tasks.push(task);
+        }
+
+        if let Err(e) = wtxn.commit() {
+            for task in &tasks {
+                self.queue.delete_persisted_task_data(task)?;
+            }
+            return Err(e.into());
+        }
+
+        if !dry_run {
+            self.task_events.broadcast(&tasks);
+        }
+
+        // notify the scheduler loop to execute a new tick
+        self.scheduler.wake_up.signal();
+        Ok(tasks)
+    }
+
+    /// Enqueue the given failed tasks again as new tasks, keeping their priority.
+    ///
+    /// The tasks that are not failed and the database upgrades are ignored. The content of a
+    /// document addition is kept only when it failed because of a transient error, retrying a
+    /// document addition whose content was deleted is an error. The retried tasks of a task
+    /// group are registered as a new task group.
+    pub fn retry_tasks(&self, tasks: &RoaringBitmap, dry_run: bool) -> Result<Vec<Task>> {
+        if (self.env.non_free_pages_size()? * 100) / self.env.info().map_size as u64
+            > TASK_SCHEDULER_SIZE_THRESHOLD_PERCENT_INT
+        {
+            return Err(Error::NoSpaceLeftInTaskQueue);
+        }
+
+        let mut wtxn = self.env.write_txn()?;
+        let failed = self.queue.tasks.get_status(&wtxn, Status::Failed)? & tasks;
+        let failed = self.queue.tasks.get_existing_tasks(&wtxn, failed)?;
+
+        let mut retried = Vec::new();
+        let mut retried_content_files = Vec::new();
+        let register = || -> Result<()> {
+            // The retried tasks of a group are registered with the new uid of its first task.
+            let mut groups = HashMap::new();
+            for task in failed {
+                if matches!(task.kind, KindWithContent::UpgradeDatabase { .. }) {
+                    continue;
+                }
+                let mut kind = task.kind.clone();
+                if let KindWithContent::DocumentAdditionOrUpdate { content_file, .. } = &mut kind {
+                    if !self.queue.update_file_exists(*content_file) {
+                        return Err(Error::TaskNotRetriable(task.uid));
+                    }
+                    if !dry_run {
+                        retried_content_files.push(*content_file);
+                        *content_file = self.queue.copy_update_file(*content_file)?;
+                    }
+                }
+                let group_uid = match task.group_uid {
+                    Some(group_uid) => Some(
+                        *groups.entry(group_uid).or_insert(self.queue.tasks.next_task_id(&wtxn)?),
+                    ),
+                    None => None,
+                };
+                let options = TaskOptions { priority: task.priority, run_after: None };
+                let new_task =
+                    self.queue.register(&mut wtxn, &kind, None, dry_run, options, group_uid)?;
+                retried.push(new_task);
+            }
+            Ok(())
+        };
+
+        if let Err(e) = register().and_then(|()| Ok(wtxn.commit()?)) {
+            for task in &retried {
+                self.queue.delete_persisted_task_data(task)?;
+            }
+            return Err(e);
+        }
+
+        // The content of the failed tasks now belongs to the retried tasks.
+        for content_file in retried_content_files {
+            if let Err(e) = self.queue.delete_update_file(content_file) {
+                tracing::error!("Failure to delete the content file {content_file}. Error: {e}");
+            }
+        }
+
+        if !dry_run {
+            self.task_events.broadcast(&retried);
+        }
+
+        // notify the scheduler loop to execute a new tick
+        self.scheduler.wake_up.signal();
+        Ok(retried)
+    }
+
     /// Register a new task coming from a dump in the scheduler.
     /// By taking a mutable ref we're pretty sure no one will ever import a dump while actix is running.
     pub fn register_dumped_task(&mut self) -> Result<Dump> {
@@ -699,6 +918,43 @@ impl IndexScheduler {
         Ok(())
     }
 
+    /// Subscribe to the changes of the tasks.
+    ///
+    /// The tasks are sent to the returned receiver when they are registered, when they start
+    /// being processed and once they are processed. The subscription ends when the receiver is dropped.
+    pub fn subscribe_to_task_events(&self) -> tokio::sync::broadcast::Receiver<Task> {
+        self.task_events.subscribe()
+    }
+
+    /// Returns the uid and the progress of the batch being processed, along with the indexes it operates on.
+    pub fn processing_batch_progress(&self) -> Option<(BatchId, HashSet<String>, ProgressView)> {
+        let processing = self.processing_tasks.read().unwrap();
+        let batch = processing.batch.as_ref()?;
+        Some((batch.uid, batch.indexes.clone(), processing.get_progress_view()?))
+    }
+
+    /// Send the current state of the given tasks to the subscribers of the task changes.
+    fn notify_task_events(&self, updated: &RoaringBitmap) -> Result<()> {
+        if self.task_events.is_empty() {
+            return Ok(());
+        }
+
+        let rtxn = self.env.read_txn()?;
+        let mut tasks = self.queue.tasks.get_existing_tasks(&rtxn, updated)?;
+        let processing = self.processing_tasks.read().unwrap();
+        if let Some(batch) = &processing.batch {
+            for task in tasks.iter_mut().filter(|task| processing.processing.contains(task.uid)) {
+                task.status = Status::Processing;
+                task.batch_uid = Some(batch.uid);
+                task.started_at = Some(batch.started_at);
+            }
+        }
+        drop(processing);
+
+        self.task_events.broadcast(&tasks);
+        Ok(())
+    }
+
     /// Once the tasks changes have been committed we must send all the tasks that were updated to our webhook if there is one.
     fn notify_webhook(&self, updated: &RoaringBitmap) -> Result<()> {
         if let Some(ref url) = self.webhook_url {
@@ -781,6 +1037,19 @@ impl IndexScheduler {
         Ok(())
     }
 
+    /// Hands the tasks that were updated over to the webhooks registered through the `/webhooks` route.
+    fn notify_webhooks(&self, updated: &RoaringBitmap) -> Result<()> {
+        let rtxn = self.env.read_txn()?;
+        let webhooks = self.webhooks.all(&rtxn)?;
+        if webhooks.is_empty() {
+            return Ok(());
+        }
+
+        let tasks = self.queue.tasks.get_existing_tasks(&rtxn, updated)?;
+        drop(rtxn);
+        self.webhooks.notify(webhooks, &tasks)
+    }
+
     pub fn index_stats(&self, index_uid: &str) -> Result<IndexStats> {
         let is_indexing = self.is_index_processing(index_uid)?;
         let rtxn = self.read_txn()?;
@@ -809,22 +1078,99 @@ impl IndexScheduler {
         self.features.network()
     }
 
+    /// Returns whether the task queue is paused, globally or for some indexes.
+    pub fn queue_pause(&self) -> QueuePause {
+        self.pause.get()
+    }
+
+    /// Stops starting new batches, the tasks are still enqueued and the current batch keeps processing.
+    ///
+    /// Pauses the whole queue when no index is specified, otherwise only the tasks of the specified indexes are paused.
+    pub fn pause_queue(&self, indexes: Option<Vec<String>>) -> Result<QueuePause> {
+        let wtxn = self.env.write_txn().map_err(Error::HeedTransaction)?;
+        self.pause.update(wtxn, |pause| match indexes {
+            Some(indexes) => pause.paused_indexes.extend(indexes),
+            None => pause.paused = true,
+        })
+    }
+
+    /// Resumes the processing of the tasks paused with [`Self::pause_queue`].
+    ///
+    /// Resumes the whole queue, including the paused indexes, when no index is specified.
+    pub fn resume_queue(&self, indexes: Option<Vec<String>>) -> Result<QueuePause> {
+        let wtxn = self.env.write_txn().map_err(Error::HeedTransaction)?;
+        let pause = self.pause.update(wtxn, |pause| match indexes {
+            Some(indexes) => {
+                for index in indexes {
+                    pause.paused_indexes.remove(&index);
+                }
+            }
+            None => *pause = QueuePause::default(),
+        })?;
+        // the paused tasks may be processed right away
+        self.scheduler.wake_up.signal();
+        Ok(pause)
+    }
+
+    /// Returns the webhooks registered through the `/webhooks` route, from the oldest to the most recent.
+    pub fn webhooks(&self) -> Result<Vec<Webhook>> {
+        let rtxn = self.read_txn()?;
+        self.webhooks.all(&rtxn)
+    }
+
+    pub fn webhook(&self, uuid: Uuid) -> Result<Webhook> {
+        let rtxn = self.read_txn()?;
+        self.webhooks.get(&rtxn, uuid)?.ok_or_else(|| Error::WebhookNotFound(uuid.to_string()))
+    }
+
+    /// Registers the webhook, or replaces the existing webhook with the same uuid.
+    pub fn put_webhook(&self, webhook: &Webhook) -> Result<()> {
+        let mut wtxn = self.env.write_txn()?;
+        self.webhooks.put(&mut wtxn, webhook)?;
+        wtxn.commit()?;
+        Ok(())
+    }
+
+    /// Deletes the webhook and its delivery log.
+    pub fn delete_webhook(&self, uuid: Uuid) -> Result<()> {
+        let mut wtxn = self.env.write_txn()?;
+        if !self.webhooks.delete(&mut wtxn, uuid)? {
+            return Err(Error::WebhookNotFound(uuid.to_string()));
+        }
+        wtxn.commit()?;
+        self.webhooks.cancel_deliveries(uuid);
+        Ok(())
+    }
+
+    /// Returns the logged deliveries of the webhook, from the most recent to the oldest.
+    pub fn webhook_deliveries(&self, uuid: Uuid) -> Result<Vec<WebhookDelivery>> {
+        let rtxn = self.read_txn()?;
+        if self.webhooks.get(&rtxn, uuid)?.is_none() {
+            return Err(Error::WebhookNotFound(uuid.to_string()));
+        }
+        self.webhooks.deliveries(&rtxn, uuid)
+    }
+
     pub fn embedders(
         &self,
         index_uid: String,
         embedding_configs: Vec<IndexEmbeddingConfig>,
     ) -> Result<EmbeddingConfigs> {
+        let document_caches = match &self.indexer_config().embedding_cache {
+            Some(cache) => cache.for_embedders(&embedding_configs),
+            None => HashMap::new(),
+        };
         let res: Result<_> = embedding_configs
             .into_iter()
             .map(
                 |IndexEmbeddingConfig {
                      name,
-                     config: milli::vector::EmbeddingConfig { embedder_options, prompt, quantized },
+                     config:
+                         milli::vector::EmbeddingConfig { embedder_options, prompt, quantized, .. },
                      ..
                  }| {
                     let prompt = Arc::new(
-                        prompt
-                            .try_into()
+                        Prompt::for_embedder(prompt, &embedder_options)
                             .map_err(meilisearch_types::milli::Error::from)
                             .map_err(|err| Error::from_milli(err, Some(index_uid.clone())))?,
                     );
@@ -855,7 +1201,32 @@ impl IndexScheduler {
                 },
             )
             .collect();
-        res.map(EmbeddingConfigs::new)
+        res.map(|embedders| EmbeddingConfigs::new(embedders).with_document_caches(document_caches))
+    }
+
+    /// Returns the runtime representation of the reranker with the passed settings.
+    ///
+    /// Like embedders, rerankers are shared between all the indexes using the same settings.
+    pub fn reranker(&self, index_uid: String, settings: RerankerSettings) -> Result<Arc<Reranker>> {
+        // optimistically return existing reranker
+        {
+            let rerankers = self.rerankers.read().unwrap();
+            if let Some(reranker) = rerankers.get(&settings) {
+                return Ok(reranker.clone());
+            }
+        }
+
+        // add missing reranker
+        let reranker = Arc::new(
+            Reranker::new(settings.clone())
+                .map_err(meilisearch_types::milli::vector::Error::from)
+                .map_err(|err| Error::from_milli(err.into(), Some(index_uid)))?,
+        );
+        {
+            let mut rerankers = self.rerankers.write().unwrap();
+            rerankers.insert(settings, reranker.clone());
+        }
+        Ok(reranker)
     }
 }
 
@@ -867,6 +1238,9 @@ pub enum TickOutcome {
     TickAgain(u64),
     /// The scheduler should wait for an external signal before attempting another `tick`.
     WaitForSignal,
+    /// The scheduler should wait for an external signal, or until a scheduled task is due,
+    /// before attempting another `tick`.
+    WaitUntil(OffsetDateTime),
     /// The scheduler exits the run-loop and will never process tasks again
     StopProcessingForever,
 }
@@ -0,0 +1,66 @@
+//! The pause state of the task queue, set through the `/tasks/pause` and `/tasks/resume` routes.
+//!
+//! The state is persisted so that a paused queue stays paused after a restart, and is also kept in
+//! memory since it's read every time the scheduler creates a batch.
+
+use std::sync::{Arc, RwLock};
+
+use meilisearch_types::heed::types::{SerdeJson, Str};
+use meilisearch_types::heed::{Database, Env, RwTxn, WithoutTls};
+use meilisearch_types::tasks::QueuePause;
+
+use crate::Result;
+
+/// The number of database used by the pause state
+const NUMBER_OF_DATABASES: u32 = 1;
+/// Database const names for the `PauseState`.
+mod db_name {
+    pub const QUEUE_PAUSE: &str = "queue-pause";
+}
+
+mod db_keys {
+    pub const QUEUE_PAUSE: &str = "queue-pause";
+}
+
+#[derive(Clone)]
+pub(crate) struct PauseState {
+    persisted: Database<Str, SerdeJson<QueuePause>>,
+    current: Arc<RwLock<QueuePause>>,
+}
+
+impl PauseState {
+    pub(crate) const fn nb_db() -> u32 {
+        NUMBER_OF_DATABASES
+    }
+
+    pub(crate) fn new(env: &Env<WithoutTls>, wtxn: &mut RwTxn) -> Result<Self> {
+        let persisted = env.create_database(wtxn, Some(db_name::QUEUE_PAUSE))?;
+        let current = persisted.get(wtxn, db_keys::QUEUE_PAUSE)?.unwrap_or_default();
+        Ok(Self { persisted, current: Arc::new(RwLock::new(current)) })
+    }
+
+    pub(crate) fn get(&self) -> QueuePause {
+        // sound to unwrap, the lock will only fail if:
+        // 1. requested by the same thread concurrently -> it is called and released in methods that don't call each other
+        // 2. there's a panic while the thread is held -> it is only used for cloning the data here
+        self.current.read().unwrap().clone()
+    }
+
+    /// Applies the update to the pause state and persists it, returns the new state.
+    pub(crate) fn update(
+        &self,
+        mut wtxn: RwTxn,
+        update: impl FnOnce(&mut QueuePause),
+    ) -> Result<QueuePause> {
+        // The write lock is held until the state is persisted so that concurrent updates don't overwrite each other.
+        // safe to unwrap, the lock is never held while calling a method that could take it again.
+        let mut current = self.current.write().unwrap();
+        let mut pause = current.clone();
+        update(&mut pause);
+        self.persisted.put(&mut wtxn, db_keys::QUEUE_PAUSE, &pause)?;
+        wtxn.commit()?;
+
+        *current = pause.clone();
+        Ok(pause)
+    }
+}
@@ -1,6 +1,7 @@
 use std::sync::Arc;
 
 use meilisearch_types::milli::progress::{AtomicSubStep, NamedStep, Progress, ProgressView};
+use meilisearch_types::milli::update::new::IndexingStep;
 use meilisearch_types::milli::{make_atomic_progress, make_enum_progress};
 use roaring::RoaringBitmap;
 
@@ -13,6 +14,9 @@ pub struct ProcessingTasks {
     pub processing: Arc<RoaringBitmap>,
     /// The progress on processing tasks
     pub progress: Option<Progress>,
+    /// The tasks whose processing was aborted and the indexing step they were in,
+    /// reported in the tasks once they are canceled.
+    pub interrupted: Option<(Arc<RoaringBitmap>, String)>,
 }
 
 impl ProcessingTasks {
@@ -48,9 +52,19 @@ impl ProcessingTasks {
             batch: std::mem::take(&mut self.batch),
             processing: std::mem::take(&mut self.processing),
             progress: None,
+            interrupted: self.interrupted.take(),
         }
     }
 
+    /// Records the indexing step the processing tasks were in when their processing was aborted.
+    ///
+    /// Must be called before the processing of the next batch starts.
+    pub fn abort_processing(&mut self, progress: &Progress) {
+        self.interrupted = progress
+            .current_step_of::<IndexingStep>()
+            .map(|step| (self.processing.clone(), step.into_owned()));
+    }
+
     /// Returns `true` if there, at least, is one task that is currently processing that we must stop.
     pub fn must_cancel_processing_tasks(&self, canceled_tasks: &RoaringBitmap) -> bool {
         !self.processing.is_disjoint(canceled_tasks)
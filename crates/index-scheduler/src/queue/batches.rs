@@ -317,6 +317,8 @@ impl Queue {
             after_started_at,
             before_finished_at,
             after_finished_at,
+            before_run_after,
+            after_run_after,
         } = query;
 
         let mut batches = self.batches.all_batch_ids(rtxn)?;
@@ -373,6 +375,25 @@ impl Queue {
             batches &= batches_by_task_uids;
         }
 
+        // The batches don't have a scheduled date, we match the batches of the scheduled tasks instead
+        if after_run_after.is_some() || before_run_after.is_some() {
+            let mut scheduled_tasks = self.tasks.all_task_ids(rtxn)?;
+            keep_ids_within_datetimes(
+                rtxn,
+                &mut scheduled_tasks,
+                self.tasks.run_after,
+                *after_run_after,
+                *before_run_after,
+            )?;
+            let mut scheduled_batches = RoaringBitmap::new();
+            for task in self.tasks.get_existing_tasks(rtxn, scheduled_tasks)? {
+                if let Some(batch_uid) = task.batch_uid {
+                    scheduled_batches.insert(batch_uid);
+                }
+            }
+            batches &= scheduled_batches;
+        }
+
         // There is no database for this query, we must retrieve the task queried by the client and ensure it's valid
         if let Some(canceled_by) = &canceled_by {
             let mut all_canceled_batches = RoaringBitmap::new();
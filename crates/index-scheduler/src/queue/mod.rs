@@ -15,7 +15,7 @@ use file_store::FileStore;
 use meilisearch_types::batches::BatchId;
 use meilisearch_types::heed::{Database, Env, RoTxn, RwTxn, WithoutTls};
 use meilisearch_types::milli::{CboRoaringBitmapCodec, BEU32};
-use meilisearch_types::tasks::{Kind, KindWithContent, Status, Task};
+use meilisearch_types::tasks::{Kind, KindWithContent, Status, Task, TaskPriority};
 use roaring::RoaringBitmap;
 use time::format_description::well_known::Rfc3339;
 use time::OffsetDateTime;
@@ -36,6 +36,15 @@ mod db_name {
     pub const BATCH_TO_TASKS_MAPPING: &str = "batch-to-tasks-mapping";
 }
 
+/// The options given to a task when it is registered.
+#[derive(Default, Debug, Clone, PartialEq, Eq)]
+pub struct TaskOptions {
+    /// The [priority](`meilisearch_types::tasks::Task::priority`) of the task.
+    pub priority: TaskPriority,
+    /// The date before which the task must not be [processed](`meilisearch_types::tasks::Task::run_after`).
+    pub run_after: Option<OffsetDateTime>,
+}
+
 /// Defines a subset of tasks to be retrieved from the [`IndexScheduler`].
 ///
 /// An empty/default query (where each field is set to `None`) matches all tasks.
@@ -81,6 +90,10 @@ pub struct Query {
     pub before_finished_at: Option<OffsetDateTime>,
     /// Exclusive lower bound of the matched tasks' [`finished_at`](meilisearch_types::tasks::Task::finished_at) field.
     pub after_finished_at: Option<OffsetDateTime>,
+    /// Exclusive upper bound of the matched tasks' [`run_after`](meilisearch_types::tasks::Task::run_after) field.
+    pub before_run_after: Option<OffsetDateTime>,
+    /// Exclusive lower bound of the matched tasks' [`run_after`](meilisearch_types::tasks::Task::run_after) field.
+    pub after_run_after: Option<OffsetDateTime>,
 }
 
 impl Query {
@@ -105,6 +118,8 @@ impl Query {
                 after_started_at: None,
                 before_finished_at: None,
                 after_finished_at: None,
+                before_run_after: None,
+                after_run_after: None,
             }
         )
     }
@@ -193,6 +208,12 @@ impl Queue {
                     .tasks
                     .get_task(rtxn, task_id)
                     .and_then(|task| task.ok_or(Error::CorruptedTaskQueue));
+                // The error of the previous attempt of a retried task must not affect this one.
+                if let Ok(task) = &mut task {
+                    if task.retries > 0 {
+                        task.error = None;
+                    }
+                }
                 processing_batch.processing(&mut task);
                 task
             })
@@ -222,6 +243,11 @@ impl Queue {
         self.file_store.get_update(uuid)
     }
 
+    /// Whether the task's content file is still stored, i.e. it was kept after a transient failure.
+    pub(crate) fn update_file_exists(&self, uuid: Uuid) -> bool {
+        self.file_store.get_update_path(uuid).exists()
+    }
+
     /// Delete a file from the index scheduler.
     ///
     /// Counterpart to the [`create_update_file`](IndexScheduler::create_update_file) method.
@@ -242,6 +268,15 @@ impl Queue {
         }
     }
 
+    /// Copy the content file of a task into a new file, returning the uuid of the new file.
+    pub(crate) fn copy_update_file(&self, uuid: Uuid) -> Result<Uuid> {
+        let mut source = self.file_store.get_update(uuid)?;
+        let (new_uuid, mut file) = self.file_store.new_update()?;
+        std::io::copy(&mut source, &mut file)?;
+        file.persist()?;
+        Ok(new_uuid)
+    }
+
     #[cfg(test)]
     pub fn create_update_file_with_uuid(&self, uuid: u128) -> Result<(Uuid, file_store::File)> {
         Ok(self.file_store.new_update_with_uuid(uuid)?)
@@ -252,13 +287,17 @@ impl Queue {
         Ok(self.file_store.compute_total_size()?)
     }
 
+    /// Register a new task in the queue, as part of the task group starting at `group_uid` if any.
     pub fn register(
         &self,
         wtxn: &mut RwTxn,
         kind: &KindWithContent,
         task_id: Option<TaskId>,
         dry_run: bool,
+        options: TaskOptions,
+        group_uid: Option<TaskId>,
     ) -> Result<Task> {
+        let TaskOptions { priority, run_after } = options;
         let next_task_id = self.tasks.next_task_id(wtxn)?;
 
         if let Some(uid) = task_id {
@@ -279,6 +318,11 @@ impl Queue {
             details: kind.default_details(),
             status: Status::Enqueued,
             kind: kind.clone(),
+            priority,
+            run_after,
+            group_uid,
+            retries: 0,
+            interrupted_step: None,
         };
         // For deletion and cancelation tasks, we want to make extra sure that they
         // don't attempt to delete/cancel tasks that are newer than themselves.
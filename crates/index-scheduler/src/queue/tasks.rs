@@ -3,7 +3,7 @@ use std::ops::{Bound, RangeBounds};
 use meilisearch_types::heed::types::{DecodeIgnore, SerdeBincode, SerdeJson, Str};
 use meilisearch_types::heed::{Database, Env, RoTxn, RwTxn, WithoutTls};
 use meilisearch_types::milli::{CboRoaringBitmapCodec, RoaringBitmapCodec, BEU32};
-use meilisearch_types::tasks::{Kind, Status, Task};
+use meilisearch_types::tasks::{Kind, Status, Task, TaskPriority};
 use roaring::{MultiOps, RoaringBitmap};
 use time::OffsetDateTime;
 
@@ -15,18 +15,20 @@ use crate::utils::{
 use crate::{Error, Result, TaskId, BEI128};
 
 /// The number of database used by the task queue
-const NUMBER_OF_DATABASES: u32 = 8;
+const NUMBER_OF_DATABASES: u32 = 10;
 /// Database const names for the `IndexScheduler`.
 mod db_name {
     pub const ALL_TASKS: &str = "all-tasks";
 
     pub const STATUS: &str = "status";
     pub const KIND: &str = "kind";
+    pub const PRIORITY: &str = "priority";
     pub const INDEX_TASKS: &str = "index-tasks";
     pub const CANCELED_BY: &str = "canceled_by";
     pub const ENQUEUED_AT: &str = "enqueued-at";
     pub const STARTED_AT: &str = "started-at";
     pub const FINISHED_AT: &str = "finished-at";
+    pub const RUN_AFTER: &str = "run-after";
 }
 
 pub struct TaskQueue {
@@ -38,6 +40,8 @@ pub struct TaskQueue {
     pub(crate) status: Database<SerdeBincode<Status>, RoaringBitmapCodec>,
     /// All the tasks ids grouped by their kind.
     pub(crate) kind: Database<SerdeBincode<Kind>, RoaringBitmapCodec>,
+    /// All the tasks ids grouped by their priority, the tasks of normal priority are not stored.
+    pub(crate) priority: Database<SerdeBincode<TaskPriority>, RoaringBitmapCodec>,
     /// Store the tasks associated to an index.
     pub(crate) index_tasks: Database<Str, RoaringBitmapCodec>,
     /// Store the tasks that were canceled by a task uid
@@ -48,6 +52,8 @@ pub struct TaskQueue {
     pub(crate) started_at: Database<BEI128, CboRoaringBitmapCodec>,
     /// Store the task ids of tasks which finished at a specific date
     pub(crate) finished_at: Database<BEI128, CboRoaringBitmapCodec>,
+    /// Store the task ids of scheduled tasks which must not be processed before a specific date
+    pub(crate) run_after: Database<BEI128, CboRoaringBitmapCodec>,
 }
 
 impl TaskQueue {
@@ -56,11 +62,13 @@ impl TaskQueue {
             all_tasks: self.all_tasks,
             status: self.status,
             kind: self.kind,
+            priority: self.priority,
             index_tasks: self.index_tasks,
             canceled_by: self.canceled_by,
             enqueued_at: self.enqueued_at,
             started_at: self.started_at,
             finished_at: self.finished_at,
+            run_after: self.run_after,
         }
     }
 
@@ -73,11 +81,13 @@ impl TaskQueue {
             all_tasks: env.create_database(wtxn, Some(db_name::ALL_TASKS))?,
             status: env.create_database(wtxn, Some(db_name::STATUS))?,
             kind: env.create_database(wtxn, Some(db_name::KIND))?,
+            priority: env.create_database(wtxn, Some(db_name::PRIORITY))?,
             index_tasks: env.create_database(wtxn, Some(db_name::INDEX_TASKS))?,
             canceled_by: env.create_database(wtxn, Some(db_name::CANCELED_BY))?,
             enqueued_at: env.create_database(wtxn, Some(db_name::ENQUEUED_AT))?,
             started_at: env.create_database(wtxn, Some(db_name::STARTED_AT))?,
             finished_at: env.create_database(wtxn, Some(db_name::FINISHED_AT))?,
+            run_after: env.create_database(wtxn, Some(db_name::RUN_AFTER))?,
         })
     }
 
@@ -104,9 +114,12 @@ impl TaskQueue {
         debug_assert!(old_task != *task);
         debug_assert_eq!(old_task.uid, task.uid);
 
-        // If we're processing a task that failed it may already contains a batch_uid
+        // If we're processing a task that failed it may already contains a batch_uid.
+        // A task enqueued again to be retried later is not part of any batch.
         debug_assert!(
-            reprocessing || (old_task.batch_uid.is_none() && task.batch_uid.is_some()),
+            reprocessing
+                || task.status == Status::Enqueued
+                || (old_task.batch_uid.is_none() && task.batch_uid.is_some()),
             "\n==> old: {old_task:?}\n==> new: {task:?}"
         );
 
@@ -128,6 +141,15 @@ impl TaskQueue {
             })?;
         }
 
+        if old_task.priority != task.priority {
+            self.update_priority(wtxn, old_task.priority, |bitmap| {
+                bitmap.remove(task.uid);
+            })?;
+            self.update_priority(wtxn, task.priority, |bitmap| {
+                bitmap.insert(task.uid);
+            })?;
+        }
+
         assert_eq!(
             old_task.enqueued_at, task.enqueued_at,
             "Cannot update a task's enqueued_at time"
@@ -156,6 +178,14 @@ impl TaskQueue {
                 insert_task_datetime(wtxn, self.finished_at, finished_at, task.uid)?;
             }
         }
+        if old_task.run_after != task.run_after {
+            if let Some(run_after) = old_task.run_after {
+                remove_task_datetime(wtxn, self.run_after, run_after, task.uid)?;
+            }
+            if let Some(run_after) = task.run_after {
+                insert_task_datetime(wtxn, self.run_after, run_after, task.uid)?;
+            }
+        }
 
         self.all_tasks.put(wtxn, &task.uid, task)?;
         Ok(())
@@ -235,6 +265,47 @@ impl TaskQueue {
         Ok(())
     }
 
+    /// Returns the tasks of the given priority.
+    ///
+    /// The tasks of normal priority are not stored and must be deduced from the other priorities.
+    pub(crate) fn get_priority(
+        &self,
+        rtxn: &RoTxn,
+        priority: TaskPriority,
+    ) -> Result<RoaringBitmap> {
+        Ok(self.priority.get(rtxn, &priority)?.unwrap_or_default())
+    }
+
+    pub(crate) fn put_priority(
+        &self,
+        wtxn: &mut RwTxn,
+        priority: TaskPriority,
+        bitmap: &RoaringBitmap,
+    ) -> Result<()> {
+        if priority.is_normal() {
+            return Ok(());
+        }
+        if bitmap.is_empty() {
+            self.priority.delete(wtxn, &priority)?;
+        } else {
+            self.priority.put(wtxn, &priority, bitmap)?;
+        }
+        Ok(())
+    }
+
+    pub(crate) fn update_priority(
+        &self,
+        wtxn: &mut RwTxn,
+        priority: TaskPriority,
+        f: impl Fn(&mut RoaringBitmap),
+    ) -> Result<()> {
+        let mut tasks = self.get_priority(wtxn, priority)?;
+        f(&mut tasks);
+        self.put_priority(wtxn, priority, &tasks)?;
+
+        Ok(())
+    }
+
     /// Convert an iterator to a `Vec` of tasks. The tasks MUST exist or a
     /// `CorruptedTaskQueue` error will be thrown.
     pub(crate) fn get_existing_tasks(
@@ -267,10 +338,53 @@ impl TaskQueue {
             bitmap.insert(task.uid);
         })?;
 
+        self.update_priority(wtxn, task.priority, |bitmap| {
+            bitmap.insert(task.uid);
+        })?;
+
         utils::insert_task_datetime(wtxn, self.enqueued_at, task.enqueued_at, task.uid)?;
+        if let Some(run_after) = task.run_after {
+            utils::insert_task_datetime(wtxn, self.run_after, run_after, task.uid)?;
+        }
 
         Ok(())
     }
+
+    /// Returns the tasks that must not be processed before a date later than `now`.
+    pub(crate) fn scheduled_after(
+        &self,
+        rtxn: &RoTxn,
+        now: OffsetDateTime,
+    ) -> Result<RoaringBitmap> {
+        let mut tasks = RoaringBitmap::new();
+        for entry in self
+            .run_after
+            .range(rtxn, &(Bound::Excluded(now.unix_timestamp_nanos()), Bound::Unbounded))?
+        {
+            let (_timestamp, ids) = entry?;
+            tasks |= ids;
+        }
+        Ok(tasks)
+    }
+
+    /// Returns the date at which the first of the given tasks, that is not yet due, can be processed.
+    pub(crate) fn next_run_after(
+        &self,
+        rtxn: &RoTxn,
+        tasks: &RoaringBitmap,
+        now: OffsetDateTime,
+    ) -> Result<Option<OffsetDateTime>> {
+        for entry in self
+            .run_after
+            .range(rtxn, &(Bound::Excluded(now.unix_timestamp_nanos()), Bound::Unbounded))?
+        {
+            let (timestamp, ids) = entry?;
+            if !ids.is_disjoint(tasks) {
+                return Ok(OffsetDateTime::from_unix_timestamp_nanos(timestamp).ok());
+            }
+        }
+        Ok(None)
+    }
 }
 
 impl Queue {
@@ -281,8 +395,12 @@ impl Queue {
         query: &Query,
         processing_tasks: &ProcessingTasks,
     ) -> Result<RoaringBitmap> {
-        let ProcessingTasks { batch: processing_batch, processing: processing_tasks, progress: _ } =
-            processing_tasks;
+        let ProcessingTasks {
+            batch: processing_batch,
+            processing: processing_tasks,
+            progress: _,
+            interrupted: _,
+        } = processing_tasks;
         let Query {
             limit,
             from,
@@ -299,6 +417,8 @@ impl Queue {
             after_started_at,
             before_finished_at,
             after_finished_at,
+            before_run_after,
+            after_run_after,
         } = query;
 
         let mut tasks = self.tasks.all_task_ids(rtxn)?;
@@ -444,6 +564,14 @@ impl Queue {
             *before_finished_at,
         )?;
 
+        keep_ids_within_datetimes(
+            rtxn,
+            &mut tasks,
+            self.tasks.run_after,
+            *after_run_after,
+            *before_run_after,
+        )?;
+
         if let Some(limit) = limit {
             tasks = if query.reverse.unwrap_or_default() {
                 tasks.into_iter().take(*limit as usize).collect()
@@ -512,7 +640,7 @@ impl Queue {
             .tasks
             .get_existing_tasks(rtxn, tasks.take(query.limit.unwrap_or(u32::MAX) as usize))?;
 
-        let ProcessingTasks { batch, processing, progress: _ } = processing_tasks;
+        let ProcessingTasks { batch, processing, progress: _, interrupted: _ } = processing_tasks;
 
         let ret = tasks.into_iter();
         if processing.is_empty() || batch.is_none() {
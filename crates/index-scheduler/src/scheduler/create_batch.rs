@@ -3,13 +3,16 @@ use std::fmt;
 use meilisearch_types::heed::RoTxn;
 use meilisearch_types::milli::update::IndexDocumentsMethod;
 use meilisearch_types::settings::{Settings, Unchecked};
-use meilisearch_types::tasks::{BatchStopReason, Kind, KindWithContent, Status, Task};
+use meilisearch_types::tasks::{
+    BatchSelectionReason, BatchStopReason, Kind, KindWithContent, Status, Task, TaskPriority,
+};
 use roaring::RoaringBitmap;
+use time::OffsetDateTime;
 use uuid::Uuid;
 
 use super::autobatcher::{self, BatchKind};
 use crate::utils::ProcessingBatch;
-use crate::{Error, IndexScheduler, Result};
+use crate::{Error, IndexScheduler, Result, TaskId};
 
 /// Represents a combination of tasks that can all be processed at the same time.
 ///
@@ -50,6 +53,8 @@ pub(crate) enum Batch {
     UpgradeDatabase {
         tasks: Vec<Task>,
     },
+    /// Batches of different indexes that are processed at the same time.
+    Parallel(Vec<Batch>),
 }
 
 #[derive(Debug)]
@@ -94,6 +99,11 @@ pub(crate) enum IndexOperation {
         settings: Vec<(bool, Settings<Unchecked>)>,
         settings_tasks: Vec<Task>,
     },
+    /// The operations of a task group, applied one after the other and committed together.
+    TaskGroup {
+        index_uid: String,
+        operations: Vec<IndexOperation>,
+    },
 }
 
 impl Batch {
@@ -112,25 +122,11 @@ impl Batch {
             | Batch::IndexDeletion { tasks, .. } => {
                 RoaringBitmap::from_iter(tasks.iter().map(|task| task.uid))
             }
-            Batch::IndexOperation { op, .. } => match op {
-                IndexOperation::DocumentOperation { tasks, .. }
-                | IndexOperation::Settings { tasks, .. }
-                | IndexOperation::DocumentDeletion { tasks, .. }
-                | IndexOperation::DocumentClear { tasks, .. } => {
-                    RoaringBitmap::from_iter(tasks.iter().map(|task| task.uid))
-                }
-                IndexOperation::DocumentEdition { task, .. } => {
-                    RoaringBitmap::from_sorted_iter(std::iter::once(task.uid)).unwrap()
-                }
-                IndexOperation::DocumentClearAndSetting {
-                    cleared_tasks: tasks,
-                    settings_tasks: other,
-                    ..
-                } => RoaringBitmap::from_iter(tasks.iter().chain(other).map(|task| task.uid)),
-            },
+            Batch::IndexOperation { op, .. } => op.ids(),
             Batch::IndexSwap { task } => {
                 RoaringBitmap::from_sorted_iter(std::iter::once(task.uid)).unwrap()
             }
+            Batch::Parallel(batches) => batches.iter().map(Batch::ids).collect(),
         }
     }
 
@@ -143,7 +139,8 @@ impl Batch {
             | SnapshotCreation(_)
             | Dump(_)
             | UpgradeDatabase { .. }
-            | IndexSwap { .. } => None,
+            | IndexSwap { .. }
+            | Parallel(_) => None,
             IndexOperation { op, .. } => Some(op.index_uid()),
             IndexCreation { index_uid, .. }
             | IndexUpdate { index_uid, .. }
@@ -168,6 +165,16 @@ impl fmt::Display for Batch {
             Batch::IndexDeletion { .. } => f.write_str("IndexDeletion")?,
             Batch::IndexSwap { .. } => f.write_str("IndexSwap")?,
             Batch::UpgradeDatabase { .. } => f.write_str("UpgradeDatabase")?,
+            Batch::Parallel(batches) => {
+                f.write_str("Parallel(")?;
+                for (i, batch) in batches.iter().enumerate() {
+                    if i != 0 {
+                        f.write_str(", ")?;
+                    }
+                    write!(f, "{batch}")?;
+                }
+                f.write_str(")")?;
+            }
         };
         match index_uid {
             Some(name) => f.write_fmt(format_args!(" on {name:?} from tasks: {tasks:?}")),
@@ -177,6 +184,29 @@ impl fmt::Display for Batch {
 }
 
 impl IndexOperation {
+    /// Return the task ids associated with this index operation.
+    pub fn ids(&self) -> RoaringBitmap {
+        match self {
+            IndexOperation::DocumentOperation { tasks, .. }
+            | IndexOperation::Settings { tasks, .. }
+            | IndexOperation::DocumentDeletion { tasks, .. }
+            | IndexOperation::DocumentClear { tasks, .. } => {
+                RoaringBitmap::from_iter(tasks.iter().map(|task| task.uid))
+            }
+            IndexOperation::DocumentEdition { task, .. } => {
+                RoaringBitmap::from_sorted_iter(std::iter::once(task.uid)).unwrap()
+            }
+            IndexOperation::DocumentClearAndSetting {
+                cleared_tasks: tasks,
+                settings_tasks: other,
+                ..
+            } => RoaringBitmap::from_iter(tasks.iter().chain(other).map(|task| task.uid)),
+            IndexOperation::TaskGroup { operations, .. } => {
+                operations.iter().map(IndexOperation::ids).collect()
+            }
+        }
+    }
+
     pub fn index_uid(&self) -> &str {
         match self {
             IndexOperation::DocumentOperation { index_uid, .. }
@@ -184,7 +214,8 @@ impl IndexOperation {
             | IndexOperation::DocumentDeletion { index_uid, .. }
             | IndexOperation::DocumentClear { index_uid, .. }
             | IndexOperation::Settings { index_uid, .. }
-            | IndexOperation::DocumentClearAndSetting { index_uid, .. } => index_uid,
+            | IndexOperation::DocumentClearAndSetting { index_uid, .. }
+            | IndexOperation::TaskGroup { index_uid, .. } => index_uid,
         }
     }
 }
@@ -206,6 +237,7 @@ impl fmt::Display for IndexOperation {
             IndexOperation::DocumentClearAndSetting { .. } => {
                 f.write_str("IndexOperation::DocumentClearAndSetting")
             }
+            IndexOperation::TaskGroup { .. } => f.write_str("IndexOperation::TaskGroup"),
         }
     }
 }
@@ -422,13 +454,129 @@ impl IndexScheduler {
         }
     }
 
+    /// Returns the enqueued task the next batch must start from, along with the reason it was
+    /// picked before the older enqueued tasks, if any.
+    ///
+    /// Only the indexes having enqueued tasks of the highest priority are considered. Among them
+    /// the index of the oldest of these tasks is picked, unless it was also the index of the last
+    /// batch while the tasks of other indexes were already waiting. In that case the index that was
+    /// batched the least recently is picked instead so that a single index cannot monopolize the
+    /// scheduler. The tasks of an index are always processed in the order they were enqueued.
+    fn next_task_to_batch(
+        &self,
+        rtxn: &RoTxn,
+        enqueued: &RoaringBitmap,
+    ) -> Result<Option<(TaskId, Option<BatchSelectionReason>)>> {
+        let Some(oldest_task_id) = enqueued.min() else { return Ok(None) };
+        let oldest_task =
+            self.queue.tasks.get_task(rtxn, oldest_task_id)?.ok_or(Error::CorruptedTaskQueue)?;
+        // The tasks that aren't associated with any index are processed in order.
+        if oldest_task.indexes().is_empty() {
+            return Ok(Some((oldest_task_id, None)));
+        }
+
+        let high = self.queue.tasks.get_priority(rtxn, TaskPriority::High)? & enqueued;
+        let low = self.queue.tasks.get_priority(rtxn, TaskPriority::Low)? & enqueued;
+        let last_batch_id = self.queue.batches.next_batch_id(rtxn)?.checked_sub(1);
+
+        // Without any prioritized task, the oldest task goes first unless its index was the one
+        // of the last batch, in which case the other indexes may have to go first.
+        if high.is_empty() && low.is_empty() {
+            let mut in_last_batch = false;
+            if let Some(last_batch_id) = last_batch_id {
+                for index in oldest_task.indexes() {
+                    in_last_batch |=
+                        self.queue.batches.index_batches(rtxn, index)?.contains(last_batch_id);
+                }
+            }
+            if !in_last_batch {
+                return Ok(Some((oldest_task_id, None)));
+            }
+        }
+
+        let normal = enqueued - &high - &low;
+        let (priority, prioritized) = if !high.is_empty() {
+            (TaskPriority::High, high)
+        } else if !normal.is_empty() {
+            (TaskPriority::Normal, normal)
+        } else {
+            (TaskPriority::Low, low)
+        };
+
+        // Each index with its oldest enqueued task and its oldest task of the highest priority.
+        let mut candidates = Vec::new();
+        for ret in self.queue.tasks.index_tasks.iter(rtxn)? {
+            let (index, tasks) = ret?;
+            let Some(oldest_prioritized) = (&tasks & &prioritized).min() else { continue };
+            let Some(oldest_enqueued) = (&tasks & enqueued).min() else { continue };
+            // An index swap must be processed in order as it affects the tasks of two indexes.
+            if oldest_enqueued != oldest_task_id {
+                let task = self
+                    .queue
+                    .tasks
+                    .get_task(rtxn, oldest_enqueued)?
+                    .ok_or(Error::CorruptedTaskQueue)?;
+                if task.kind.as_kind() == Kind::IndexSwap {
+                    continue;
+                }
+            }
+            candidates.push((index, oldest_enqueued, oldest_prioritized));
+        }
+
+        let Some(&(fifo_index, fifo_task_id, _)) =
+            candidates.iter().min_by_key(|(_, _, oldest_prioritized)| *oldest_prioritized)
+        else {
+            return Ok(Some((oldest_task_id, None)));
+        };
+
+        // If the index was already the one of the last batch we let the other indexes, whose
+        // tasks were enqueued before this batch started, go first.
+        if let Some(last_batch_id) = last_batch_id {
+            if self.queue.batches.index_batches(rtxn, fifo_index)?.contains(last_batch_id) {
+                let last_batch = self
+                    .queue
+                    .batches
+                    .get_batch(rtxn, last_batch_id)?
+                    .ok_or(Error::CorruptedTaskQueue)?;
+
+                let mut waiting = Vec::new();
+                for &(index, oldest_enqueued, oldest_prioritized) in &candidates {
+                    if index == fifo_index {
+                        continue;
+                    }
+                    let task = self
+                        .queue
+                        .tasks
+                        .get_task(rtxn, oldest_prioritized)?
+                        .ok_or(Error::CorruptedTaskQueue)?;
+                    if task.enqueued_at < last_batch.started_at {
+                        let last_batched = self.queue.batches.index_batches(rtxn, index)?.max();
+                        waiting.push((last_batched, oldest_prioritized, index, oldest_enqueued));
+                    }
+                }
+
+                if let Some((_, _, index, task_id)) = waiting.into_iter().min() {
+                    let reason = BatchSelectionReason::RoundRobin { index: index.to_string() };
+                    return Ok(Some((task_id, Some(reason))));
+                }
+            }
+        }
+
+        let reason = (oldest_task.priority < priority
+            && !oldest_task.indexes().contains(&fifo_index))
+        .then(|| BatchSelectionReason::Priority { index: fifo_index.to_string(), priority });
+        Ok(Some((fifo_task_id, reason)))
+    }
+
     /// Create the next batch to be processed;
     /// 0. We get the *last* task to cancel.
     /// 1. We get the tasks to upgrade.
+    ///    Nothing else is batched while the queue is paused.
     /// 2. We get the *next* task to delete.
     /// 3. We get the *next* snapshot to process.
     /// 4. We get the *next* dump to process.
     /// 5. We get the *next* tasks to process for a specific index.
+    /// 6. We get the *next* tasks of other indexes to process in parallel, if enabled.
     #[tracing::instrument(level = "trace", skip(self, rtxn), target = "indexing::scheduler")]
     pub(crate) fn create_next_batch(
         &self,
@@ -440,7 +588,28 @@ impl IndexScheduler {
         let batch_id = self.queue.batches.next_batch_id(rtxn)?;
         let mut current_batch = ProcessingBatch::new(batch_id);
 
-        let enqueued = &self.queue.tasks.get_status(rtxn, Status::Enqueued)?;
+        // The scheduled tasks are ignored until they are due.
+        let scheduled = self.queue.tasks.scheduled_after(rtxn, OffsetDateTime::now_utc())?;
+        let all_enqueued = self.queue.tasks.get_status(rtxn, Status::Enqueued)?;
+        let mut enqueued = &all_enqueued - &scheduled;
+        // The tasks enqueued again after a transient failure keep their place in the queue of
+        // their index, the later tasks of the index wait until they are retried.
+        for task in self.queue.tasks.get_existing_tasks(rtxn, &all_enqueued & &scheduled)? {
+            if task.retries == 0 {
+                continue;
+            }
+            for index in task.indexes() {
+                let mut later_tasks = self.queue.tasks.index_tasks(rtxn, index)?;
+                later_tasks.remove_range(..task.uid);
+                enqueued -= later_tasks;
+            }
+        }
+        // The tasks of the paused indexes are ignored until they are resumed.
+        let pause = self.pause.get();
+        for index in &pause.paused_indexes {
+            enqueued -= self.queue.tasks.index_tasks(rtxn, index)?;
+        }
+        let enqueued = &enqueued;
         let count_total_enqueued = enqueued.len();
         let failed = &self.queue.tasks.get_status(rtxn, Status::Failed)?;
 
@@ -473,6 +642,12 @@ impl IndexScheduler {
             return Ok(Some((Batch::UpgradeDatabase { tasks }, current_batch)));
         }
 
+        // The cancelations and upgrades are still processed so that the enqueued tasks can be
+        // canceled while the queue is paused.
+        if pause.paused {
+            return Ok(None);
+        }
+
         // check the version of the scheduler here.
         // if the version is not the current, refuse to batch any additional task.
         let version = self.version.get_version(rtxn)?;
@@ -523,8 +698,12 @@ impl IndexScheduler {
             return Ok(Some((Batch::Dump(task), current_batch)));
         }
 
-        // 5. We make a batch from the unprioritised tasks. Start by taking the next enqueued task.
-        let task_id = if let Some(task_id) = enqueued.min() { task_id } else { return Ok(None) };
+        // 5. We make a batch from the unprioritised tasks. Start by taking the next enqueued task
+        //    of the index picked by the priorities and the fairness between the indexes.
+        let Some((task_id, selection)) = self.next_task_to_batch(rtxn, enqueued)? else {
+            return Ok(None);
+        };
+        current_batch.selection = selection;
         let mut task =
             self.queue.tasks.get_task(rtxn, task_id)?.ok_or(Error::CorruptedTaskQueue)?;
 
@@ -544,6 +723,33 @@ impl IndexScheduler {
             return Ok(Some((Batch::IndexSwap { task }, current_batch)));
         };
 
+        let Some(batch) = self.create_index_batch(
+            rtxn,
+            index_name,
+            enqueued,
+            count_total_enqueued,
+            &mut current_batch,
+        )?
+        else {
+            // If we found no tasks then we were notified for something that got autobatched
+            // somehow and there is nothing to do.
+            return Ok(None);
+        };
+
+        // 6. We process the tasks of other indexes at the same time, if enabled.
+        let batch = self.create_parallel_batches(rtxn, batch, enqueued, &mut current_batch)?;
+        Ok(Some((batch, current_batch)))
+    }
+
+    /// Uses the autobatcher to batch the enqueued tasks of the given index.
+    fn create_index_batch(
+        &self,
+        rtxn: &RoTxn,
+        index_name: &str,
+        enqueued: &RoaringBitmap,
+        count_total_enqueued: u64,
+        current_batch: &mut ProcessingBatch,
+    ) -> Result<Option<Batch>> {
         let index_already_exists = self.index_mapper.exists(rtxn, index_name)?;
         let mut primary_key = None;
         if index_already_exists {
@@ -554,6 +760,23 @@ impl IndexScheduler {
 
         let index_tasks = self.queue.tasks.index_tasks(rtxn, index_name)? & enqueued;
 
+        // The tasks of a group are always batched alone.
+        if let Some(task_id) = index_tasks.min() {
+            let task =
+                self.queue.tasks.get_task(rtxn, task_id)?.ok_or(Error::CorruptedTaskQueue)?;
+            if let Some(group_uid) = task.group_uid {
+                return self.create_task_group_batch(
+                    rtxn,
+                    index_name,
+                    group_uid,
+                    &index_tasks,
+                    index_already_exists,
+                    primary_key.as_deref(),
+                    current_batch,
+                );
+            }
+        }
+
         // If autobatching is disabled we only take one task at a time.
         // Otherwise, we take only a maximum of tasks to create batches.
         let tasks_limit = if self.scheduler.autobatching_enabled {
@@ -587,6 +810,11 @@ impl IndexScheduler {
                 break;
             }
 
+            if task.group_uid.is_some() {
+                stop_reason = BatchStopReason::TaskGroup { id: task.uid };
+                break;
+            }
+
             enqueued.push((task.uid, task.kind));
         }
 
@@ -602,19 +830,126 @@ impl IndexScheduler {
             autobatcher::autobatch(enqueued, index_already_exists, primary_key.as_deref())
         {
             current_batch.reason(autobatch_stop_reason.unwrap_or(stop_reason));
-            return Ok(self
-                .create_next_batch_index(
-                    rtxn,
-                    index_name.to_string(),
-                    batchkind,
-                    &mut current_batch,
-                    create_index,
-                )?
-                .map(|batch| (batch, current_batch)));
+            return self.create_next_batch_index(
+                rtxn,
+                index_name.to_string(),
+                batchkind,
+                current_batch,
+                create_index,
+            );
         }
 
-        // If we found no tasks then we were notified for something that got autobatched
-        // somehow and there is nothing to do.
         Ok(None)
     }
+
+    /// Creates a batch containing the enqueued tasks of the given task group, each task is
+    /// turned into its own index operation so that they are applied in their registration order.
+    #[allow(clippy::too_many_arguments)]
+    fn create_task_group_batch(
+        &self,
+        rtxn: &RoTxn,
+        index_name: &str,
+        group_uid: TaskId,
+        index_tasks: &RoaringBitmap,
+        mut index_exists: bool,
+        primary_key: Option<&str>,
+        current_batch: &mut ProcessingBatch,
+    ) -> Result<Option<Batch>> {
+        let mut operations = Vec::new();
+        let mut must_create_index = false;
+        for task_id in index_tasks {
+            let task =
+                self.queue.tasks.get_task(rtxn, task_id)?.ok_or(Error::CorruptedTaskQueue)?;
+            if task.group_uid != Some(group_uid) {
+                break;
+            }
+
+            let Some((batchkind, create_index, _)) =
+                autobatcher::autobatch(vec![(task.uid, task.kind)], index_exists, primary_key)
+            else {
+                continue;
+            };
+            match self.create_next_batch_index(
+                rtxn,
+                index_name.to_string(),
+                batchkind,
+                current_batch,
+                create_index,
+            )? {
+                Some(Batch::IndexOperation { op, must_create_index: create_index }) => {
+                    must_create_index |= create_index;
+                    index_exists |= create_index;
+                    operations.push(op);
+                }
+                // Only document operations and settings updates can be registered in a group.
+                _ => return Err(Error::CorruptedTaskQueue),
+            }
+        }
+
+        current_batch.reason(BatchStopReason::ExhaustedTaskGroup { group_uid });
+        Ok(Some(Batch::IndexOperation {
+            op: IndexOperation::TaskGroup { index_uid: index_name.to_string(), operations },
+            must_create_index,
+        }))
+    }
+
+    /// Adds the index operations of other indexes to the given index operation so that they
+    /// are processed at the same time, until [`max_parallel_batches`](super::Scheduler::max_parallel_batches) is
+    /// reached. The stop and selection reasons of the first batch are kept.
+    fn create_parallel_batches(
+        &self,
+        rtxn: &RoTxn,
+        batch: Batch,
+        enqueued: &RoaringBitmap,
+        current_batch: &mut ProcessingBatch,
+    ) -> Result<Batch> {
+        let max_parallel_batches = self.scheduler.max_parallel_batches;
+        if max_parallel_batches <= 1 {
+            return Ok(batch);
+        }
+        // Only the document and settings operations are processed in parallel.
+        let Batch::IndexOperation { op, .. } = &batch else { return Ok(batch) };
+        let index_name = op.index_uid();
+        // A swap changes the order of the tasks of the indexes it touches, we must wait for it.
+        if !(self.queue.tasks.get_kind(rtxn, Kind::IndexSwap)? & enqueued).is_empty() {
+            return Ok(batch);
+        }
+
+        let mut remaining = enqueued - self.queue.tasks.index_tasks(rtxn, index_name)?;
+        let count_total_enqueued = enqueued.len();
+        let reason = current_batch.reason.clone();
+        let selection = current_batch.selection.clone();
+
+        let mut batches = vec![batch];
+        while batches.len() < max_parallel_batches {
+            let Some((task_id, _)) = self.next_task_to_batch(rtxn, &remaining)? else { break };
+            let task =
+                self.queue.tasks.get_task(rtxn, task_id)?.ok_or(Error::CorruptedTaskQueue)?;
+            // The tasks of several indexes are never processed in parallel.
+            let indexes = task.indexes();
+            let &[index_name] = indexes.as_slice() else { break };
+            remaining -= self.queue.tasks.index_tasks(rtxn, index_name)?;
+
+            let checkpoint = current_batch.clone();
+            match self.create_index_batch(
+                rtxn,
+                index_name,
+                enqueued,
+                count_total_enqueued,
+                current_batch,
+            )? {
+                Some(batch @ Batch::IndexOperation { .. }) => batches.push(batch),
+                // This index must wait for the next batch, we forget about its tasks.
+                Some(_) => *current_batch = checkpoint,
+                None => (),
+            }
+        }
+
+        current_batch.reason = reason;
+        current_batch.selection = selection;
+        Ok(match batches.len() {
+            1 => batches.pop().unwrap(),
+            _ => Batch::Parallel(batches),
+        })
+    }
 }
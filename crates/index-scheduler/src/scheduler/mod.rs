@@ -19,17 +19,19 @@ mod test_failure;
 use std::path::PathBuf;
 use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
 use std::sync::Arc;
+use std::time::Duration;
 
 use convert_case::{Case, Casing as _};
 use meilisearch_types::error::ResponseError;
 use meilisearch_types::heed::{Env, WithoutTls};
 use meilisearch_types::milli;
-use meilisearch_types::tasks::Status;
+use meilisearch_types::tasks::{Status, Task};
 use process_batch::ProcessBatchInfo;
 use rayon::current_num_threads;
 use rayon::iter::{IntoParallelIterator, ParallelIterator};
 use roaring::RoaringBitmap;
 use synchronoise::SignalEvent;
+use time::OffsetDateTime;
 
 use crate::processing::{AtomicTaskStep, BatchProgress};
 use crate::{Error, IndexScheduler, IndexSchedulerOptions, Result, TickOutcome};
@@ -83,6 +85,18 @@ pub struct Scheduler {
     ///
     /// 0 disables the cache.
     pub(crate) embedding_cache_cap: usize,
+
+    /// The maximum number of batches targeting different indexes that are processed in parallel.
+    pub(crate) max_parallel_batches: usize,
+
+    /// The maximum number of automatic retries of a task that failed because of a transient error.
+    pub(crate) max_task_retries: u32,
+
+    /// The delay before the first automatic retry of a task.
+    pub(crate) task_retry_delay: Duration,
+
+    /// The path used to archive the tasks and batches before deleting them, if any.
+    pub(crate) tasks_archive_path: Option<PathBuf>,
 }
 
 impl Scheduler {
@@ -98,6 +112,10 @@ impl Scheduler {
             auth_env: self.auth_env.clone(),
             version_file_path: self.version_file_path.clone(),
             embedding_cache_cap: self.embedding_cache_cap,
+            max_parallel_batches: self.max_parallel_batches,
+            max_task_retries: self.max_task_retries,
+            task_retry_delay: self.task_retry_delay,
+            tasks_archive_path: self.tasks_archive_path.clone(),
         }
     }
 
@@ -114,11 +132,35 @@ impl Scheduler {
             auth_env,
             version_file_path: options.version_file_path.clone(),
             embedding_cache_cap: options.embedding_cache_cap,
+            max_parallel_batches: options.max_parallel_batches.max(1),
+            max_task_retries: options.max_task_retries,
+            task_retry_delay: options.task_retry_delay,
+            tasks_archive_path: options.tasks_archive_path.clone(),
         }
     }
 }
 
+/// The maximum delay between two automatic retries of a task.
+const MAX_TASK_RETRY_DELAY: Duration = Duration::from_secs(60 * 60);
+
 impl IndexScheduler {
+    /// Enqueues the task again to be retried after an exponential backoff delay, unless it already
+    /// reached the maximum number of retries. Returns `false` if the task must be marked as failed.
+    ///
+    /// The later tasks of the index of the task are not processed until it is retried.
+    pub(crate) fn retry_later(&self, task: &mut Task, error: &ResponseError) -> bool {
+        if task.retries >= self.scheduler.max_task_retries {
+            return false;
+        }
+        let backoff = 2u32.saturating_pow(task.retries);
+        let delay =
+            self.scheduler.task_retry_delay.saturating_mul(backoff).min(MAX_TASK_RETRY_DELAY);
+        task.retries += 1;
+        task.error = Some(error.clone());
+        task.run_after = Some(OffsetDateTime::now_utc() + delay);
+        true
+    }
+
     /// Perform one iteration of the run loop.
     ///
     /// 1. See if we need to cleanup the task queue
@@ -145,11 +187,19 @@ impl IndexScheduler {
             wtxn.commit()?;
         }
 
+        let now = OffsetDateTime::now_utc();
         let rtxn = self.env.read_txn().map_err(Error::HeedTransaction)?;
         let (batch, mut processing_batch) =
             match self.create_next_batch(&rtxn).map_err(|e| Error::CreateBatch(Box::new(e)))? {
                 Some(batch) => batch,
-                None => return Ok(TickOutcome::WaitForSignal),
+                None => {
+                    // Nothing can be processed yet, but a scheduled task may become due later.
+                    let enqueued = self.queue.tasks.get_status(&rtxn, Status::Enqueued)?;
+                    return Ok(match self.queue.tasks.next_run_after(&rtxn, &enqueued, now)? {
+                        Some(run_after) => TickOutcome::WaitUntil(run_after),
+                        None => TickOutcome::WaitForSignal,
+                    });
+                }
             };
         let index_uid = batch.index_uid().map(ToOwned::to_owned);
         drop(rtxn);
@@ -166,6 +216,9 @@ impl IndexScheduler {
             .unwrap()
             // We can clone the processing batch here because we don't want its modification to affect the view of the processing batches
             .start_processing(processing_batch.clone(), ids.clone());
+        // The tasks enqueued again to be retried later are removed from `ids`, they must be notified too.
+        let batched = ids.clone();
+        let _ = self.notify_task_events(&batched);
 
         #[cfg(test)]
         self.breakpoint(crate::test_utils::Breakpoint::BatchCreated);
@@ -214,8 +267,8 @@ impl IndexScheduler {
             })
         };
 
-        // Reset the currently updating index to relinquish the index handle
-        self.index_mapper.set_currently_updating_index(None);
+        // Reset the currently updating indexes to relinquish the index handles
+        self.index_mapper.clear_currently_updating_indexes();
 
         #[cfg(test)]
         self.maybe_fail(crate::test_utils::FailureLocation::AcquiringWtxn)?;
@@ -242,6 +295,16 @@ impl IndexScheduler {
                 #[allow(unused_variables)]
                 for (i, mut task) in tasks.into_iter().enumerate() {
                     task_progress.fetch_add(1, Ordering::Relaxed);
+                    // The task failed because of a transient error or its index was full, it
+                    // was enqueued again and is not part of this batch anymore.
+                    if task.status == Status::Enqueued {
+                        ids.remove(task.uid);
+                        self.queue
+                            .tasks
+                            .update_task(&mut wtxn, &task)
+                            .map_err(|e| Error::UnrecoverableError(Box::new(e)))?;
+                        continue;
+                    }
                     processing_batch.update(&mut task);
                     if task.status == Status::Canceled {
                         canceled.insert(task.uid);
@@ -268,6 +331,10 @@ impl IndexScheduler {
                 if let Some(canceled_by) = canceled_by {
                     self.queue.tasks.canceled_by.put(&mut wtxn, &canceled_by, &canceled)?;
                 }
+                for index_uid in &process_batch_info.full_indexes {
+                    self.index_mapper.resize_index(&wtxn, index_uid)?;
+                    tracing::info!("The max database size was reached. Resizing the index.");
+                }
                 tracing::info!("A batch of tasks was successfully completed with {success} successful tasks and {failure} failed tasks.");
             }
             // If we have an abortion error we must stop the tick here and re-schedule tasks.
@@ -279,6 +346,7 @@ impl IndexScheduler {
                 #[cfg(test)]
                 self.breakpoint(crate::test_utils::Breakpoint::AbortedIndexation);
                 wtxn.abort();
+                self.processing_tasks.write().unwrap().abort_processing(&progress);
 
                 tracing::info!("A batch of tasks was aborted.");
                 // We make sure that we don't call `stop_processing` on the `processing_tasks`,
@@ -295,10 +363,10 @@ impl IndexScheduler {
             // 4. re-schedule tasks
             Err(Error::Milli {
                 error: milli::Error::UserError(milli::UserError::MaxDatabaseSizeReached),
-                ..
-            }) if index_uid.is_some() => {
-                // fixme: add index_uid to match to avoid the unwrap
-                let index_uid = index_uid.unwrap();
+                index_uid: error_index_uid,
+            }) if index_uid.is_some() || error_index_uid.is_some() => {
+                // The batches processed in parallel don't have a single index, the error tells us which one is full.
+                let index_uid = index_uid.or(error_index_uid).unwrap();
                 // fixme: handle error more gracefully? not sure when this could happen
                 self.index_mapper.resize_index(&wtxn, &index_uid)?;
                 wtxn.abort();
@@ -320,8 +388,9 @@ impl IndexScheduler {
                     );
                     stop_scheduler_forever = true;
                 }
+                let transient = err.is_transient();
                 let error: ResponseError = err.into();
-                for id in ids.iter() {
+                for id in ids.clone().iter() {
                     task_progress.fetch_add(1, Ordering::Relaxed);
                     let mut task = self
                         .queue
@@ -329,6 +398,19 @@ impl IndexScheduler {
                         .get_task(&wtxn, id)
                         .map_err(|e| Error::UnrecoverableError(Box::new(e)))?
                         .ok_or(Error::CorruptedTaskQueue)?;
+                    if transient && self.retry_later(&mut task, &error) {
+                        tracing::warn!("Task {id} failed, it will be retried: {error}");
+                        ids.remove(id);
+                        self.queue
+                            .tasks
+                            .update_task(&mut wtxn, &task)
+                            .map_err(|e| Error::UnrecoverableError(Box::new(e)))?;
+                        continue;
+                    }
+                    if transient {
+                        // We keep the content of the task so that it can be retried manually.
+                        process_batch_info.transient_failures.insert(id);
+                    }
                     task.status = Status::Failed;
                     task.error = Some(error.clone());
                     task.details = task.details.map(|d| d.to_failed());
@@ -352,8 +434,13 @@ impl IndexScheduler {
         // We must re-add the canceled task so they're part of the same batch.
         ids |= canceled;
 
-        let ProcessBatchInfo { congestion, pre_commit_dabases_sizes, post_commit_dabases_sizes } =
-            process_batch_info;
+        let ProcessBatchInfo {
+            congestion,
+            pre_commit_dabases_sizes,
+            post_commit_dabases_sizes,
+            transient_failures,
+            full_indexes: _,
+        } = process_batch_info;
 
         processing_batch.stats.progress_trace =
             progress.accumulated_durations().into_iter().map(|(k, v)| (k, v.into())).collect();
@@ -370,9 +457,11 @@ impl IndexScheduler {
                 post_commit_dabases_sizes
                     .get(dbname)
                     .map(|post_size| {
-                        use byte_unit::{Byte, UnitType::Binary};
                         use std::cmp::Ordering::{Equal, Greater, Less};
 
+                        use byte_unit::Byte;
+                        use byte_unit::UnitType::Binary;
+
                         let post = Byte::from_u64(*post_size as u64).get_appropriate_unit(Binary);
                         let diff_size = post_size.abs_diff(*pre_size) as u64;
                         let diff = Byte::from_u64(diff_size).get_appropriate_unit(Binary);
@@ -403,7 +492,10 @@ impl IndexScheduler {
 
         tracing::debug!("call trace: {:?}", progress.accumulated_durations());
 
-        self.queue.write_batch(&mut wtxn, processing_batch, &ids)?;
+        // All the tasks may have been enqueued again to be retried later.
+        if !ids.is_empty() {
+            self.queue.write_batch(&mut wtxn, processing_batch, &ids)?;
+        }
 
         #[cfg(test)]
         self.maybe_fail(crate::test_utils::FailureLocation::CommittingWtxn)?;
@@ -414,14 +506,16 @@ impl IndexScheduler {
         // and then become « not found » for some time until the commit everything is written and the final commit is made.
         self.processing_tasks.write().unwrap().stop_processing();
 
-        // Once the tasks are committed, we should delete all the update files associated ASAP to avoid leaking files in case of a restart
+        // Once the tasks are committed, we should delete all the update files associated ASAP to avoid leaking files in case of a restart.
+        // The update files of the tasks that failed because of a transient error are kept so that they can be retried.
         tracing::debug!("Deleting the update files");
+        let deletable = &ids - &transient_failures;
 
         //We take one read transaction **per thread**. Then, every thread is going to pull out new IDs from the roaring bitmap with the help of an atomic shared index into the bitmap
         let idx = AtomicU32::new(0);
         (0..current_num_threads()).into_par_iter().try_for_each(|_| -> Result<()> {
             let rtxn = self.read_txn()?;
-            while let Some(id) = ids.select(idx.fetch_add(1, Ordering::Relaxed)) {
+            while let Some(id) = deletable.select(idx.fetch_add(1, Ordering::Relaxed)) {
                 let task = self
                     .queue
                     .tasks
@@ -438,8 +532,10 @@ impl IndexScheduler {
             Ok(())
         })?;
 
-        // We shouldn't crash the tick function if we can't send data to the webhook.
+        // We shouldn't crash the tick function if we can't send data to the webhooks.
         let _ = self.notify_webhook(&ids);
+        let _ = self.notify_webhooks(&ids);
+        let _ = self.notify_task_events(&(&ids | &batched));
 
         #[cfg(test)]
         self.breakpoint(crate::test_utils::Breakpoint::AfterProcessing);
@@ -1,17 +1,24 @@
 use std::collections::{BTreeSet, HashMap, HashSet};
 use std::panic::{catch_unwind, AssertUnwindSafe};
-use std::sync::atomic::Ordering;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Barrier;
 
+use meilisearch_types::batch_view::BatchView;
 use meilisearch_types::batches::{BatchEnqueuedAt, BatchId};
+use meilisearch_types::error::ResponseError;
 use meilisearch_types::heed::{RoTxn, RwTxn};
 use meilisearch_types::milli::progress::{Progress, VariableNameStep};
 use meilisearch_types::milli::{self, ChannelCongestion};
-use meilisearch_types::tasks::{Details, IndexSwap, Kind, KindWithContent, Status, Task};
+use meilisearch_types::task_view::TaskView;
+use meilisearch_types::tasks::{
+    Details, IndexSwap, Kind, KindWithContent, Status, Task, TaskPriority,
+};
 use meilisearch_types::versioning::{VERSION_MAJOR, VERSION_MINOR, VERSION_PATCH};
-use milli::update::Settings as MilliSettings;
+use milli::update::{IndexerConfig, Settings as MilliSettings};
 use roaring::RoaringBitmap;
 
-use super::create_batch::Batch;
+use super::create_batch::{Batch, IndexOperation};
+use crate::archive::ArchiveWriter;
 use crate::processing::{
     AtomicBatchStep, AtomicTaskStep, CreateIndexProgress, DeleteIndexProgress, FinalizingIndexStep,
     InnerSwappingTwoIndexes, SwappingTheIndexes, TaskCancelationProgress, TaskDeletionProgress,
@@ -31,6 +38,23 @@ pub struct ProcessBatchInfo {
     pub pre_commit_dabases_sizes: indexmap::IndexMap<&'static str, usize>,
     /// The sizes of the different databases after commiting the indexation.
     pub post_commit_dabases_sizes: indexmap::IndexMap<&'static str, usize>,
+    /// The tasks that failed because of a transient error, their content is kept so they can be retried.
+    pub transient_failures: RoaringBitmap,
+    /// The indexes that were full when committing, they must be resized before processing their tasks again.
+    pub full_indexes: Vec<String>,
+}
+
+/// Whether the batch must be processed again after this error, instead of failing its tasks.
+fn must_retry_batch(error: &Error) -> bool {
+    matches!(
+        error,
+        Error::AbortedTask
+            | Error::Milli {
+                error: milli::Error::InternalError(milli::InternalError::AbortedIndexation)
+                    | milli::Error::UserError(milli::UserError::MaxDatabaseSizeReached),
+                ..
+            }
+    )
 }
 
 impl IndexScheduler {
@@ -50,6 +74,9 @@ impl IndexScheduler {
         #[cfg(test)]
         {
             self.maybe_fail(crate::test_utils::FailureLocation::InsideProcessBatch)?;
+            self.maybe_fail(
+                crate::test_utils::FailureLocation::TransientFailureInsideProcessBatch,
+            )?;
             self.maybe_fail(crate::test_utils::FailureLocation::PanicInsideProcessBatch)?;
             self.breakpoint(crate::test_utils::Breakpoint::InsideProcessBatch);
         }
@@ -134,79 +161,131 @@ impl IndexScheduler {
             Batch::Dump(task) => self
                 .process_dump_creation(progress, task)
                 .map(|tasks| (tasks, ProcessBatchInfo::default())),
-            Batch::IndexOperation { op, must_create_index } => {
-                let index_uid = op.index_uid().to_string();
-                let index = if must_create_index {
-                    // create the index if it doesn't already exist
-                    let wtxn = self.env.write_txn()?;
-                    self.index_mapper.create_index(wtxn, &index_uid, None)?
-                } else {
-                    let rtxn = self.env.read_txn()?;
-                    self.index_mapper.index(&rtxn, &index_uid)?
+            Batch::IndexOperation { op, must_create_index } => self.process_index_operation(
+                op,
+                must_create_index,
+                self.index_mapper.indexer_config(),
+                &progress,
+                |_| true,
+            ),
+            Batch::Parallel(batches) => {
+                // The index operations wait for each other before committing. If one of them
+                // must be retried, because it was aborted or its index is full, none of them
+                // commits and the whole batch is processed again.
+                let barrier = Barrier::new(batches.len());
+                let must_retry = AtomicBool::new(false);
+                // The indexing memory is shared between the index operations.
+                let indexer_config = self.index_mapper.indexer_config();
+                let indexer_config = IndexerConfig {
+                    max_memory: indexer_config.max_memory.map(|memory| memory / batches.len()),
+                    ..indexer_config.clone()
                 };
-
-                let mut index_wtxn = index.write_txn()?;
-
-                let index_version = index.get_version(&index_wtxn)?.unwrap_or((1, 12, 0));
-                let package_version = (VERSION_MAJOR, VERSION_MINOR, VERSION_PATCH);
-                if index_version != package_version {
-                    return Err(Error::IndexVersionMismatch {
-                        index: index_uid,
-                        index_version,
-                        package_version,
-                    });
-                }
-
-                // the index operation can take a long time, so save this handle to make it available to the search for the duration of the tick
-                self.index_mapper
-                    .set_currently_updating_index(Some((index_uid.clone(), index.clone())));
-
-                let pre_commit_dabases_sizes = index.database_sizes(&index_wtxn)?;
-                let (tasks, congestion) =
-                    self.apply_index_operation(&mut index_wtxn, &index, op, &progress)?;
-
-                {
-                    progress.update_progress(FinalizingIndexStep::Committing);
-                    let span = tracing::trace_span!(target: "indexing::scheduler", "commit");
-                    let _entered = span.enter();
-
-                    index_wtxn.commit()?;
+                let results: Vec<_> = std::thread::scope(|s| {
+                    let handles: Vec<_> = batches
+                        .into_iter()
+                        .enumerate()
+                        .map(|(i, batch)| {
+                            let ids = batch.ids();
+                            let Batch::IndexOperation { op, must_create_index } = batch else {
+                                unreachable!("only index operations are processed in parallel")
+                            };
+                            // Only the first index operation reports its progress.
+                            let progress =
+                                if i == 0 { progress.clone() } else { Progress::default() };
+                            let (barrier, must_retry) = (&barrier, &must_retry);
+                            let indexer_config = &indexer_config;
+                            let handle = std::thread::Builder::new()
+                                .name(format!("batch-operation-{i}"))
+                                .spawn_scoped(s, move || {
+                                    self.process_index_operation(
+                                        op,
+                                        must_create_index,
+                                        indexer_config,
+                                        &progress,
+                                        |error| {
+                                            if error.is_some_and(must_retry_batch) {
+                                                must_retry.store(true, Ordering::Relaxed);
+                                            }
+                                            barrier.wait();
+                                            !must_retry.load(Ordering::Relaxed)
+                                        },
+                                    )
+                                })
+                                .unwrap();
+                            (ids, handle)
+                        })
+                        .collect();
+                    handles.into_iter().map(|(ids, handle)| (ids, handle.join().unwrap())).collect()
+                });
+
+                if must_retry.into_inner() {
+                    // The index that is full must be resized before trying again.
+                    return Err(results
+                        .into_iter()
+                        .filter_map(|(_, result)| result.err())
+                        .find(|error| {
+                            matches!(
+                                error,
+                                Error::Milli {
+                                    error: milli::Error::UserError(
+                                        milli::UserError::MaxDatabaseSizeReached
+                                    ),
+                                    ..
+                                }
+                            )
+                        })
+                        .unwrap_or(Error::AbortedTask));
                 }
 
-                // if the update processed successfully, we're going to store the new
-                // stats of the index. Since the tasks have already been processed and
-                // this is a non-critical operation. If it fails, we should not fail
-                // the entire batch.
-                let mut post_commit_dabases_sizes = None;
-                let res = || -> Result<()> {
-                    progress.update_progress(FinalizingIndexStep::ComputingStats);
-                    let index_rtxn = index.read_txn()?;
-                    let stats = crate::index_mapper::IndexStats::new(&index, &index_rtxn)
-                        .map_err(|e| Error::from_milli(e, Some(index_uid.to_string())))?;
-                    let mut wtxn = self.env.write_txn()?;
-                    self.index_mapper.store_stats_of(&mut wtxn, &index_uid, &stats)?;
-                    post_commit_dabases_sizes = Some(index.database_sizes(&index_rtxn)?);
-                    wtxn.commit()?;
-                    Ok(())
-                }();
-
-                match res {
-                    Ok(_) => (),
-                    Err(e) => tracing::error!(
-                        error = &e as &dyn std::error::Error,
-                        "Could not write the stats of the index"
-                    ),
+                // An index operation that failed only fails its own tasks.
+                let rtxn = self.env.read_txn()?;
+                let mut tasks = Vec::new();
+                let mut info = None;
+                let mut transient_failures = RoaringBitmap::new();
+                let mut full_indexes = Vec::new();
+                for (ids, result) in results {
+                    match result {
+                        Ok((processed, processed_info)) => {
+                            tasks.extend(processed);
+                            info.get_or_insert(processed_info);
+                        }
+                        // The other index operations are committed, the tasks of the full
+                        // index stay enqueued and are processed again once it is resized.
+                        Err(Error::Milli {
+                            error: milli::Error::UserError(milli::UserError::MaxDatabaseSizeReached),
+                            index_uid: Some(index_uid),
+                        }) => {
+                            tasks.extend(self.queue.tasks.get_existing_tasks(&rtxn, ids)?);
+                            full_indexes.push(index_uid);
+                        }
+                        Err(err) => {
+                            tracing::error!(
+                                error = &err as &dyn std::error::Error,
+                                "Index operation failed"
+                            );
+                            let transient = err.is_transient();
+                            let error: ResponseError = err.into();
+                            for mut task in self.queue.tasks.get_existing_tasks(&rtxn, ids)? {
+                                // The task stays enqueued and is handled by the next batches.
+                                if transient && self.retry_later(&mut task, &error) {
+                                    tasks.push(task);
+                                    continue;
+                                }
+                                if transient {
+                                    transient_failures.insert(task.uid);
+                                }
+                                task.status = Status::Failed;
+                                task.error = Some(error.clone());
+                                task.details = task.details.map(|d| d.to_failed());
+                                tasks.push(task);
+                            }
+                        }
+                    }
                 }
 
-                let info = ProcessBatchInfo {
-                    congestion,
-                    // In case we fail to the get post-commit sizes we decide
-                    // that nothing changed and use the pre-commit sizes.
-                    post_commit_dabases_sizes: post_commit_dabases_sizes
-                        .unwrap_or_else(|| pre_commit_dabases_sizes.clone()),
-                    pre_commit_dabases_sizes,
-                };
-
+                let mut info: ProcessBatchInfo = info.unwrap_or_default();
+                info.transient_failures = transient_failures;
+                info.full_indexes = full_indexes;
                 Ok((tasks, info))
             }
             Batch::IndexCreation { index_uid, primary_key, task } => {
@@ -396,6 +475,138 @@ impl IndexScheduler {
         }
     }
 
+    /// Apply an index operation on its index and commit it.
+    ///
+    /// The `may_commit` function is called once the operation is applied, with `None`, or once
+    /// it failed, with its error. When it returns `false` the applied operation is dropped and
+    /// [`Error::AbortedTask`] is returned instead.
+    fn process_index_operation(
+        &self,
+        op: IndexOperation,
+        must_create_index: bool,
+        indexer_config: &IndexerConfig,
+        progress: &Progress,
+        may_commit: impl FnOnce(Option<&Error>) -> bool,
+    ) -> Result<(Vec<Task>, ProcessBatchInfo)> {
+        let mut may_commit = Some(may_commit);
+        let ret = catch_unwind(AssertUnwindSafe(|| {
+            self.apply_and_commit_index_operation(
+                op,
+                must_create_index,
+                indexer_config,
+                progress,
+                &mut may_commit,
+            )
+        }));
+        let ret = ret.unwrap_or_else(|panic| {
+            let msg = match panic.downcast_ref::<&'static str>() {
+                Some(s) => *s,
+                None => match panic.downcast_ref::<String>() {
+                    Some(s) => &s[..],
+                    None => "Box<dyn Any>",
+                },
+            };
+            Err(Error::ProcessBatchPanicked(msg.to_string()))
+        });
+        if let (Err(error), Some(may_commit)) = (&ret, may_commit) {
+            may_commit(Some(error));
+        }
+        ret
+    }
+
+    fn apply_and_commit_index_operation(
+        &self,
+        op: IndexOperation,
+        must_create_index: bool,
+        indexer_config: &IndexerConfig,
+        progress: &Progress,
+        may_commit: &mut Option<impl FnOnce(Option<&Error>) -> bool>,
+    ) -> Result<(Vec<Task>, ProcessBatchInfo)> {
+        let index_uid = op.index_uid().to_string();
+        let index = if must_create_index {
+            // create the index if it doesn't already exist
+            let wtxn = self.env.write_txn()?;
+            self.index_mapper.create_index(wtxn, &index_uid, None)?
+        } else {
+            let rtxn = self.env.read_txn()?;
+            self.index_mapper.index(&rtxn, &index_uid)?
+        };
+
+        let mut index_wtxn = index.write_txn()?;
+
+        let index_version = index.get_version(&index_wtxn)?.unwrap_or((1, 12, 0));
+        let package_version = (VERSION_MAJOR, VERSION_MINOR, VERSION_PATCH);
+        if index_version != package_version {
+            return Err(Error::IndexVersionMismatch {
+                index: index_uid,
+                index_version,
+                package_version,
+            });
+        }
+
+        // the index operation can take a long time, so save this handle to make it available to the search for the duration of the tick
+        self.index_mapper.add_currently_updating_index(index_uid.clone(), index.clone());
+
+        let pre_commit_dabases_sizes = index.database_sizes(&index_wtxn)?;
+        let (tasks, congestion) =
+            self.apply_index_operation(&mut index_wtxn, &index, op, indexer_config, progress)?;
+
+        // We may have to wait for the other index operations before committing.
+        if let Some(may_commit) = may_commit.take() {
+            if !may_commit(None) {
+                return Err(Error::AbortedTask);
+            }
+        }
+
+        {
+            progress.update_progress(FinalizingIndexStep::Committing);
+            let span = tracing::trace_span!(target: "indexing::scheduler", "commit");
+            let _entered = span.enter();
+
+            // The index may be full only when committing, the tick must resize it and retry.
+            index_wtxn
+                .commit()
+                .map_err(|e| Error::from_milli(e.into(), Some(index_uid.clone())))?;
+        }
+
+        // if the update processed successfully, we're going to store the new
+        // stats of the index. Since the tasks have already been processed and
+        // this is a non-critical operation. If it fails, we should not fail
+        // the entire batch.
+        let mut post_commit_dabases_sizes = None;
+        let res = || -> Result<()> {
+            progress.update_progress(FinalizingIndexStep::ComputingStats);
+            let index_rtxn = index.read_txn()?;
+            let stats = crate::index_mapper::IndexStats::new(&index, &index_rtxn)
+                .map_err(|e| Error::from_milli(e, Some(index_uid.to_string())))?;
+            let mut wtxn = self.env.write_txn()?;
+            self.index_mapper.store_stats_of(&mut wtxn, &index_uid, &stats)?;
+            post_commit_dabases_sizes = Some(index.database_sizes(&index_rtxn)?);
+            wtxn.commit()?;
+            Ok(())
+        }();
+
+        match res {
+            Ok(_) => (),
+            Err(e) => tracing::error!(
+                error = &e as &dyn std::error::Error,
+                "Could not write the stats of the index"
+            ),
+        }
+
+        let info = ProcessBatchInfo {
+            congestion,
+            // In case we fail to the get post-commit sizes we decide
+            // that nothing changed and use the pre-commit sizes.
+            post_commit_dabases_sizes: post_commit_dabases_sizes
+                .unwrap_or_else(|| pre_commit_dabases_sizes.clone()),
+            pre_commit_dabases_sizes,
+            transient_failures: RoaringBitmap::new(),
+        };
+
+        Ok((tasks, info))
+    }
+
     /// Swap the index `lhs` with the index `rhs`.
     fn apply_index_swap(
         &self,
@@ -481,6 +692,11 @@ impl IndexScheduler {
         let mut affected_canceled_by = RoaringBitmap::new();
         // The tasks that have been removed *per batches*.
         let mut affected_batches: HashMap<BatchId, RoaringBitmap> = HashMap::new();
+        // The archives in which the deleted tasks and batches are written, if enabled.
+        let mut archives =
+            self.scheduler.tasks_archive_path.as_deref().map(|path| {
+                (ArchiveWriter::new(path, "tasks"), ArchiveWriter::new(path, "batches"))
+            });
 
         let (atomic_progress, task_progress) = AtomicTaskStep::new(to_delete_tasks.len() as u32);
         progress.update_progress(task_progress);
@@ -488,13 +704,21 @@ impl IndexScheduler {
             let task =
                 self.queue.tasks.get_task(wtxn, task_id)?.ok_or(Error::CorruptedTaskQueue)?;
 
+            if let Some((tasks_archive, _)) = &mut archives {
+                tasks_archive.push(task.uid, &TaskView::from_task(&task))?;
+            }
             affected_indexes.extend(task.indexes().into_iter().map(|x| x.to_owned()));
             affected_statuses.insert(task.status);
             affected_kinds.insert(task.kind.as_kind());
-            // Note: don't delete the persisted task data since
-            // we can only delete succeeded, failed, and canceled tasks.
-            // In each of those cases, the persisted data is supposed to
-            // have been deleted already.
+            // Note: we can only delete succeeded, failed, and canceled tasks.
+            // In each of those cases, the persisted data is supposed to have been
+            // deleted already, except for the tasks that failed because of a transient
+            // error which keep their content to be retried.
+            if let Some(content_file) = task.content_uuid() {
+                if task.status == Status::Failed && self.queue.update_file_exists(content_file) {
+                    self.queue.delete_update_file(content_file)?;
+                }
+            }
             utils::remove_task_datetime(
                 wtxn,
                 self.queue.tasks.enqueued_at,
@@ -517,6 +741,9 @@ impl IndexScheduler {
                     task.uid,
                 )?;
             }
+            if let Some(run_after) = task.run_after {
+                utils::remove_task_datetime(wtxn, self.queue.tasks.run_after, run_after, task.uid)?;
+            }
             if let Some(canceled_by) = task.canceled_by {
                 affected_canceled_by.insert(canceled_by);
             }
@@ -546,6 +773,12 @@ impl IndexScheduler {
             atomic_progress.fetch_add(1, Ordering::Relaxed);
         }
 
+        for priority in enum_iterator::all::<TaskPriority>() {
+            self.queue
+                .tasks
+                .update_priority(wtxn, priority, |bitmap| *bitmap -= &to_delete_tasks)?;
+        }
+
         progress.update_progress(TaskDeletionProgress::DeletingTasks);
         let (atomic_progress, task_progress) = AtomicTaskStep::new(to_delete_tasks.len() as u32);
         progress.update_progress(task_progress);
@@ -572,6 +805,9 @@ impl IndexScheduler {
                 // We must remove the batch entirely
                 if tasks.is_empty() {
                     if let Some(batch) = self.queue.batches.get_batch(wtxn, batch_id)? {
+                        if let Some((_, batches_archive)) = &mut archives {
+                            batches_archive.push(batch_id, &BatchView::from_batch(&batch))?;
+                        }
                         if let Some(BatchEnqueuedAt { earliest, oldest }) = batch.enqueued_at {
                             remove_task_datetime(
                                 wtxn,
@@ -652,6 +888,17 @@ impl IndexScheduler {
             atomic_progress.fetch_add(1, Ordering::Relaxed);
         }
 
+        // The archives are persisted before the deletion is committed so that a crash
+        // can't lose the deleted tasks, at worst they are archived twice.
+        if let Some((tasks_archive, batches_archive)) = archives {
+            if let Some(path) = tasks_archive.persist()? {
+                tracing::info!("Archived the deleted tasks in {}", path.display());
+            }
+            if let Some(path) = batches_archive.persist()? {
+                tracing::info!("Archived the deleted batches in {}", path.display());
+            }
+        }
+
         Ok(to_delete_tasks)
     }
 
@@ -684,6 +931,27 @@ impl IndexScheduler {
         //    have already aborted the indexation of the _processing_ ones
         tasks_to_cancel |= enqueued_tasks & matched_tasks;
 
+        //    The tasks of a task group are applied together, canceling one of them cancels
+        //    the whole group. They are registered together so their uids follow the group uid.
+        let mut group_uids = BTreeSet::new();
+        for task_id in &tasks_to_cancel {
+            let task =
+                self.queue.tasks.get_task(rtxn, task_id)?.ok_or(Error::CorruptedTaskQueue)?;
+            group_uids.extend(task.group_uid);
+        }
+        for group_uid in group_uids {
+            let mut group_candidates = enqueued_tasks.clone();
+            group_candidates.remove_range(..group_uid);
+            for task_id in &group_candidates {
+                let task =
+                    self.queue.tasks.get_task(rtxn, task_id)?.ok_or(Error::CorruptedTaskQueue)?;
+                if task.group_uid != Some(group_uid) {
+                    break;
+                }
+                tasks_to_cancel.insert(task_id);
+            }
+        }
+
         // 2. If we're canceling an upgrade, attempt the rollback
         if let Some(latest_upgrade_task) = (&tasks_to_cancel & upgrade_tasks).max() {
             progress.update_progress(TaskCancelationProgress::CancelingUpgrade);
@@ -750,10 +1018,17 @@ impl IndexScheduler {
         progress.update_progress(TaskCancelationProgress::UpdatingTasks);
         let (task_progress, progress_obj) = AtomicTaskStep::new(tasks_to_cancel.len() as u32);
         progress.update_progress(progress_obj);
+        // The tasks whose indexation was aborted report the indexing step they were in.
+        let interrupted = self.processing_tasks.read().unwrap().interrupted.clone();
         for task in tasks.iter_mut() {
             task.status = Status::Canceled;
             task.canceled_by = Some(cancel_task_id);
             task.details = task.details.as_ref().map(|d| d.to_failed());
+            if let Some((aborted, step)) = &interrupted {
+                if aborted.contains(task.uid) {
+                    task.interrupted_step = Some(step.clone());
+                }
+            }
             current_batch.processing(Some(task));
             task_progress.fetch_add(1, Ordering::Relaxed);
         }
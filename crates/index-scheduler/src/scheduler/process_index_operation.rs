@@ -4,7 +4,7 @@ use meilisearch_types::heed::RwTxn;
 use meilisearch_types::milli::documents::PrimaryKey;
 use meilisearch_types::milli::progress::Progress;
 use meilisearch_types::milli::update::new::indexer::{self, UpdateByFunction};
-use meilisearch_types::milli::update::DocumentAdditionResult;
+use meilisearch_types::milli::update::{DocumentAdditionResult, IndexerConfig};
 use meilisearch_types::milli::{self, ChannelCongestion, Filter, ThreadPoolNoAbortBuilder};
 use meilisearch_types::settings::apply_settings_to_builder;
 use meilisearch_types::tasks::{Details, KindWithContent, Status, Task};
@@ -18,13 +18,13 @@ use crate::processing::{
 use crate::{Error, IndexScheduler, Result};
 
 impl IndexScheduler {
-    /// Process the index operation on the given index.
+    /// Process the index operation on the given index, with the given indexer configuration.
     ///
     /// ## Return
     /// The list of processed tasks.
     #[tracing::instrument(
         level = "trace",
-        skip(self, index_wtxn, index, progress),
+        skip(self, index_wtxn, index, indexer_config, progress),
         target = "indexing::scheduler"
     )]
     pub(crate) fn apply_index_operation<'i>(
@@ -32,6 +32,7 @@ impl IndexScheduler {
         index_wtxn: &mut RwTxn<'i>,
         index: &'i Index,
         operation: IndexOperation,
+        indexer_config: &IndexerConfig,
         progress: &Progress,
     ) -> Result<(Vec<Task>, Option<ChannelCongestion>)> {
         let indexer_alloc = Bump::new();
@@ -114,8 +115,7 @@ impl IndexScheduler {
                 }
 
                 let local_pool;
-                let indexer_config = self.index_mapper.indexer_config();
-                let pool = match &indexer_config.thread_pool {
+                let pool = match indexer_config.thread_pool.as_deref() {
                     Some(pool) => pool,
                     None => {
                         local_pool = ThreadPoolNoAbortBuilder::new()
@@ -172,6 +172,8 @@ impl IndexScheduler {
                 }
 
                 progress.update_progress(DocumentOperationProgress::Indexing);
+                #[cfg(test)]
+                let paused = std::sync::atomic::AtomicBool::new(false);
                 let mut congestion = None;
                 if tasks.iter().any(|res| res.error.is_none()) {
                     congestion = Some(
@@ -185,7 +187,11 @@ impl IndexScheduler {
                             primary_key,
                             &document_changes,
                             embedders,
-                            &|| must_stop_processing.get(),
+                            &|| {
+                                #[cfg(test)]
+                                self.maybe_pause_post_processing(progress, &paused);
+                                must_stop_processing.get()
+                            },
                             progress,
                         )
                         .map_err(|e| Error::from_milli(e, Some(index_uid.clone())))?,
@@ -267,8 +273,7 @@ impl IndexScheduler {
                 let mut congestion = None;
                 if task.error.is_none() {
                     let local_pool;
-                    let indexer_config = self.index_mapper.indexer_config();
-                    let pool = match &indexer_config.thread_pool {
+                    let pool = match indexer_config.thread_pool.as_deref() {
                         Some(pool) => pool,
                         None => {
                             local_pool = ThreadPoolNoAbortBuilder::new()
@@ -430,8 +435,7 @@ impl IndexScheduler {
                 let mut congestion = None;
                 if !tasks.iter().all(|res| res.error.is_some()) {
                     let local_pool;
-                    let indexer_config = self.index_mapper.indexer_config();
-                    let pool = match &indexer_config.thread_pool {
+                    let pool = match indexer_config.thread_pool.as_deref() {
                         Some(pool) => pool,
                         None => {
                             local_pool = ThreadPoolNoAbortBuilder::new()
@@ -484,7 +488,6 @@ impl IndexScheduler {
             }
             IndexOperation::Settings { index_uid, settings, mut tasks } => {
                 progress.update_progress(SettingsProgress::RetrievingAndMergingTheSettings);
-                let indexer_config = self.index_mapper.indexer_config();
                 let mut builder = milli::update::Settings::new(index_wtxn, index, indexer_config);
 
                 for (task, (_, settings)) in tasks.iter_mut().zip(settings) {
@@ -520,6 +523,7 @@ impl IndexScheduler {
                         index_uid: index_uid.clone(),
                         tasks: cleared_tasks,
                     },
+                    indexer_config,
                     progress,
                 )?;
 
@@ -527,6 +531,7 @@ impl IndexScheduler {
                     index_wtxn,
                     index,
                     IndexOperation::Settings { index_uid, settings, tasks: settings_tasks },
+                    indexer_config,
                     progress,
                 )?;
 
@@ -534,6 +539,35 @@ impl IndexScheduler {
                 tasks.append(&mut import_tasks);
                 Ok((tasks, None))
             }
+            IndexOperation::TaskGroup { index_uid: _, operations } => {
+                let mut tasks = Vec::new();
+                let mut congestion = None;
+                for operation in operations {
+                    let (mut processed, operation_congestion) = self.apply_index_operation(
+                        index_wtxn,
+                        index,
+                        operation,
+                        indexer_config,
+                        progress,
+                    )?;
+                    // A single failed task rolls back the whole group.
+                    if let Some(task) = processed.iter().find(|task| task.status == Status::Failed)
+                    {
+                        let message = task.error.as_ref().map_or_else(
+                            || String::from("an unknown error occurred"),
+                            |error| error.message.clone(),
+                        );
+                        // The group fails with an internal error when its task did.
+                        let internal =
+                            task.error.as_ref().is_some_and(|error| error.code.is_server_error());
+                        return Err(Error::TaskGroupFailed { task: task.uid, message, internal });
+                    }
+                    congestion = congestion.or(operation_congestion);
+                    tasks.append(&mut processed);
+                }
+
+                Ok((tasks, congestion))
+            }
         }
     }
 }
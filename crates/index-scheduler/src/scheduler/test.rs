@@ -7,15 +7,16 @@ use meilisearch_types::milli::index::IndexEmbeddingConfig;
 use meilisearch_types::milli::update::IndexDocumentsMethod::*;
 use meilisearch_types::milli::{self};
 use meilisearch_types::settings::SettingEmbeddingSettings;
-use meilisearch_types::tasks::{IndexSwap, KindWithContent};
+use meilisearch_types::tasks::{IndexSwap, KindWithContent, QueuePause, Status, TaskPriority};
 use roaring::RoaringBitmap;
 
-use crate::insta_snapshot::snapshot_index_scheduler;
+use crate::archive::ArchiveWriter;
+use crate::insta_snapshot::{snapshot_bitmap, snapshot_index_scheduler};
 use crate::test_utils::Breakpoint::*;
 use crate::test_utils::{
-    index_creation_task, read_json, replace_document_import_task, sample_documents,
+    index_creation_task, read_json, replace_document_import_task, sample_documents, FailureLocation,
 };
-use crate::IndexScheduler;
+use crate::{IndexScheduler, Query, TaskOptions};
 
 #[test]
 fn insert_task_while_another_task_is_processing() {
@@ -263,6 +264,100 @@ fn task_deletion_delete_same_task_twice() {
     snapshot!(snapshot_index_scheduler(&index_scheduler), name: "task_deletion_processed");
 }
 
+#[test]
+fn task_deletion_archives_the_deleted_tasks() {
+    use std::io::BufRead;
+
+    let archive_dir = tempfile::tempdir().unwrap();
+    let archive_path = archive_dir.path().to_path_buf();
+    let (index_scheduler, mut handle) = IndexScheduler::test_with_custom_config(vec![], |config| {
+        config.tasks_archive_path = Some(archive_path.clone());
+        None
+    });
+
+    index_scheduler.register(index_creation_task("catto", "id"), None, false).unwrap();
+    index_scheduler.register(index_creation_task("doggo", "id"), None, false).unwrap();
+    handle.advance_n_successful_batches(2);
+
+    index_scheduler
+        .register(
+            KindWithContent::TaskDeletion {
+                query: "test_query".to_owned(),
+                tasks: RoaringBitmap::from_iter([0]),
+            },
+            None,
+            false,
+        )
+        .unwrap();
+    handle.advance_one_successful_batch();
+    index_scheduler.assert_internally_consistent();
+
+    let mut files: Vec<_> = std::fs::read_dir(&archive_path)
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+        .collect();
+    files.sort();
+    // the archival timestamp at the end of the names changes at every run
+    let archives: Vec<_> = files
+        .iter()
+        .map(|name| name.splitn(4, '-').take(3).collect::<Vec<_>>().join("-"))
+        .collect();
+    snapshot!(format!("{archives:?}"), @r###"["batches-0-0", "tasks-0-0"]"###);
+
+    let read_archive = |prefix: &str| -> Vec<serde_json::Value> {
+        let name = files.iter().find(|name| name.starts_with(prefix)).unwrap();
+        let file = std::fs::File::open(archive_path.join(name)).unwrap();
+        std::io::BufReader::new(flate2::read::GzDecoder::new(file))
+            .lines()
+            .map(|line| serde_json::from_str(&line.unwrap()).unwrap())
+            .collect()
+    };
+    let tasks = read_archive("tasks-0-0-");
+    snapshot!(json_string!(tasks, { "[].enqueuedAt" => "[date]", "[].startedAt" => "[date]", "[].finishedAt" => "[date]", "[].duration" => "[duration]" }), @r###"
+    [
+      {
+        "uid": 0,
+        "batchUid": 0,
+        "indexUid": "catto",
+        "status": "succeeded",
+        "type": "indexCreation",
+        "canceledBy": null,
+        "details": {
+          "primaryKey": "id"
+        },
+        "error": null,
+        "duration": "[duration]",
+        "enqueuedAt": "[date]",
+        "startedAt": "[date]",
+        "finishedAt": "[date]"
+      }
+    ]
+    "###);
+    let batches = read_archive("batches-0-0-");
+    assert_eq!(batches.len(), 1);
+    assert_eq!(batches[0]["uid"], 0);
+
+    // The deleted task and batch are not in the task queue anymore
+    let rtxn = index_scheduler.read_txn().unwrap();
+    assert!(index_scheduler.queue.tasks.get_task(&rtxn, 0).unwrap().is_none());
+    assert!(index_scheduler.queue.batches.get_batch(&rtxn, 0).unwrap().is_none());
+}
+
+#[test]
+fn archives_with_the_same_uids_are_not_overwritten() {
+    let archive_dir = tempfile::tempdir().unwrap();
+
+    // the uids restart from zero when the database is recreated
+    for _ in 0..2 {
+        let mut archive = ArchiveWriter::new(archive_dir.path(), "tasks");
+        archive.push(0, &serde_json::json!({ "uid": 0 })).unwrap();
+        archive.persist().unwrap().unwrap();
+    }
+
+    let files = std::fs::read_dir(archive_dir.path()).unwrap().count();
+    snapshot!(files, @"2");
+}
+
 #[test]
 fn document_addition_and_index_deletion() {
     let (index_scheduler, mut handle) = IndexScheduler::test(true, vec![]);
@@ -588,6 +683,46 @@ fn cancel_processing_task() {
     snapshot!(snapshot_index_scheduler(&index_scheduler), name: "cancel_processed");
 }
 
+#[test]
+fn cancel_processing_task_during_post_processing() {
+    let (index_scheduler, mut handle) =
+        IndexScheduler::test(true, vec![(1, FailureLocation::InsidePostProcessing)]);
+
+    let (file0, documents_count0) = sample_documents(&index_scheduler, 0, 0);
+    file0.persist().unwrap();
+    let _ = index_scheduler
+        .register(replace_document_import_task("catto", None, 0, documents_count0), None, false)
+        .unwrap();
+
+    // The indexation is paused while it computes the words prefixes
+    handle.advance_till([Start, BatchCreated]);
+    loop {
+        match handle.advance() {
+            InsideProcessBatch => (),
+            InsidePostProcessing => break,
+            breakpoint => panic!("Encountered an unexpected breakpoint `{breakpoint:?}`."),
+        }
+    }
+
+    index_scheduler
+        .register(
+            KindWithContent::TaskCancelation {
+                query: "test_query".to_owned(),
+                tasks: RoaringBitmap::from_iter([0]),
+            },
+            None,
+            false,
+        )
+        .unwrap();
+    handle.advance_till([AbortedIndexation]);
+    handle.advance_one_successful_batch();
+
+    let rtxn = index_scheduler.read_txn().unwrap();
+    let task = index_scheduler.queue.tasks.get_task(&rtxn, 0).unwrap().unwrap();
+    snapshot!(format!("{:?}", task.status), @"Canceled");
+    snapshot!(format!("{:?}", task.interrupted_step), @r###"Some("post processing words")"###);
+}
+
 #[test]
 fn cancel_mix_of_tasks() {
     let (index_scheduler, mut handle) = IndexScheduler::test(true, vec![]);
@@ -929,3 +1064,508 @@ fn create_and_list_index() {
     ]
     "###);
 }
+
+#[test]
+fn process_tasks_of_highest_priority_first() {
+    let (index_scheduler, mut handle) = IndexScheduler::test(false, vec![]);
+
+    let with_priority = |priority| TaskOptions { priority, ..Default::default() };
+    index_scheduler
+        .register_with_options(
+            index_creation_task("index_a", "id"),
+            None,
+            false,
+            with_priority(TaskPriority::Low),
+        )
+        .unwrap();
+    index_scheduler.register(index_creation_task("index_b", "id"), None, false).unwrap();
+    index_scheduler
+        .register_with_options(
+            index_creation_task("index_c", "id"),
+            None,
+            false,
+            with_priority(TaskPriority::High),
+        )
+        .unwrap();
+    index_scheduler.assert_internally_consistent();
+
+    handle.advance_n_successful_batches(3);
+    index_scheduler.assert_internally_consistent();
+
+    let rtxn = index_scheduler.read_txn().unwrap();
+    let batch_uid = |task_id| {
+        index_scheduler.queue.tasks.get_task(&rtxn, task_id).unwrap().unwrap().batch_uid.unwrap()
+    };
+    assert_eq!([batch_uid(0), batch_uid(1), batch_uid(2)], [2, 1, 0]);
+
+    let batch = index_scheduler.queue.batches.get_batch(&rtxn, 0).unwrap().unwrap();
+    snapshot!(batch.stop_reason.split("; ").last().unwrap(), @"index `index_c` was picked before older tasks as it has enqueued tasks of `high` priority");
+    let batch = index_scheduler.queue.batches.get_batch(&rtxn, 1).unwrap().unwrap();
+    snapshot!(batch.stop_reason.split("; ").last().unwrap(), @"index `index_b` was picked before older tasks as it has enqueued tasks of `normal` priority");
+}
+
+#[test]
+fn process_indexes_in_turn_when_one_monopolizes_the_queue() {
+    let (index_scheduler, mut handle) = IndexScheduler::test(false, vec![]);
+
+    index_scheduler.register(index_creation_task("index_a", "id"), None, false).unwrap();
+    index_scheduler
+        .register(KindWithContent::IndexDeletion { index_uid: S("index_a") }, None, false)
+        .unwrap();
+    index_scheduler.register(index_creation_task("index_b", "id"), None, false).unwrap();
+
+    handle.advance_n_successful_batches(3);
+    index_scheduler.assert_internally_consistent();
+
+    // the index `index_b` doesn't wait for all the tasks of `index_a` to be processed
+    let rtxn = index_scheduler.read_txn().unwrap();
+    let batch_uid = |task_id| {
+        index_scheduler.queue.tasks.get_task(&rtxn, task_id).unwrap().unwrap().batch_uid.unwrap()
+    };
+    assert_eq!([batch_uid(0), batch_uid(1), batch_uid(2)], [0, 2, 1]);
+
+    let batch = index_scheduler.queue.batches.get_batch(&rtxn, 1).unwrap().unwrap();
+    snapshot!(batch.stop_reason.split("; ").last().unwrap(), @"index `index_b` was picked before older tasks as the indexes of these tasks were batched more recently");
+}
+
+#[test]
+fn process_batches_of_different_indexes_in_parallel() {
+    use std::collections::BTreeSet;
+
+    use meilisearch_types::settings::{Settings, Unchecked};
+    use milli::update::Setting;
+
+    let (index_scheduler, mut handle) = IndexScheduler::test_with_custom_config(vec![], |config| {
+        config.max_parallel_batches = 2;
+        None
+    });
+
+    for index in ["index_a", "index_b", "index_c"] {
+        let mut new_settings = Box::<Settings<Unchecked>>::default();
+        new_settings.sortable_attributes = Setting::Set(BTreeSet::from([S("catto")]));
+        let kind = KindWithContent::SettingsUpdate {
+            index_uid: S(index),
+            new_settings,
+            is_deletion: false,
+            allow_index_creation: true,
+        };
+        index_scheduler.register(kind, None, false).unwrap();
+    }
+
+    handle.advance_n_successful_batches(2);
+    index_scheduler.assert_internally_consistent();
+
+    // the two first indexes are processed in the same batch, the third one must wait for it
+    let rtxn = index_scheduler.read_txn().unwrap();
+    let task = |task_id| index_scheduler.queue.tasks.get_task(&rtxn, task_id).unwrap().unwrap();
+    assert_eq!(
+        [task(0).batch_uid, task(1).batch_uid, task(2).batch_uid],
+        [Some(0), Some(0), Some(1)]
+    );
+    assert!((0..3).all(|task_id| task(task_id).status == Status::Succeeded));
+
+    let batch = index_scheduler.queue.batches.get_batch(&rtxn, 0).unwrap().unwrap();
+    snapshot!(json_string!(batch.stats.index_uids), @r###"
+    {
+      "index_a": 1,
+      "index_b": 1
+    }
+    "###);
+}
+
+#[test]
+fn skip_scheduled_tasks_until_they_are_due() {
+    let (index_scheduler, mut handle) = IndexScheduler::test(true, vec![]);
+
+    let now = time::OffsetDateTime::now_utc();
+    let scheduled =
+        TaskOptions { run_after: Some(now + time::Duration::hours(1)), ..Default::default() };
+    index_scheduler
+        .register_with_options(index_creation_task("index_a", "id"), None, false, scheduled)
+        .unwrap();
+    index_scheduler.register(index_creation_task("index_b", "id"), None, false).unwrap();
+
+    // the task scheduled in an hour is skipped
+    handle.advance_one_successful_batch();
+    index_scheduler.assert_internally_consistent();
+    {
+        let rtxn = index_scheduler.read_txn().unwrap();
+        let task = |task_id| index_scheduler.queue.tasks.get_task(&rtxn, task_id).unwrap().unwrap();
+        assert_eq!([task(0).status, task(1).status], [Status::Enqueued, Status::Succeeded]);
+    }
+
+    let query = Query { after_run_after: Some(now), ..Default::default() };
+    let (tasks, _) = index_scheduler
+        .get_task_ids_from_authorized_indexes(&query, &AuthFilter::default())
+        .unwrap();
+    snapshot!(snapshot_bitmap(&tasks), @"[0,]");
+
+    // it can be canceled before being processed
+    index_scheduler
+        .register(
+            KindWithContent::TaskCancelation {
+                query: "test_query".to_owned(),
+                tasks: RoaringBitmap::from_iter([0]),
+            },
+            None,
+            false,
+        )
+        .unwrap();
+    handle.advance_one_successful_batch();
+    index_scheduler.assert_internally_consistent();
+
+    let rtxn = index_scheduler.read_txn().unwrap();
+    let task = index_scheduler.queue.tasks.get_task(&rtxn, 0).unwrap().unwrap();
+    assert_eq!(task.status, Status::Canceled);
+    assert_eq!(task.canceled_by, Some(2));
+}
+
+#[test]
+fn process_task_groups_atomically() {
+    let (index_scheduler, mut handle) = IndexScheduler::test(true, vec![]);
+
+    let (file, documents_count) = sample_documents(&index_scheduler, 0, 0);
+    file.persist().unwrap();
+    let kind = replace_document_import_task("doggos", Some("id"), 0, documents_count);
+    index_scheduler.register(kind, None, false).unwrap();
+    handle.advance_one_successful_batch();
+
+    let delete_doggo_0 =
+        KindWithContent::DocumentDeletion { index_uid: S("doggos"), documents_ids: vec![S("0")] };
+
+    // the second operation of the first group fails because its document has no id
+    let (_uuid, mut file) = index_scheduler.queue.create_update_file_with_uuid(1).unwrap();
+    let documents_count = read_json(br#"{ "name": "bob" }"#, &mut file).unwrap();
+    file.persist().unwrap();
+    let kinds = vec![
+        delete_doggo_0.clone(),
+        replace_document_import_task("doggos", Some("id"), 1, documents_count),
+    ];
+    index_scheduler.register_group(kinds, false, TaskOptions::default()).unwrap();
+
+    let (file, documents_count) = sample_documents(&index_scheduler, 2, 1);
+    file.persist().unwrap();
+    let kinds = vec![
+        delete_doggo_0,
+        replace_document_import_task("doggos", Some("id"), 2, documents_count),
+    ];
+    index_scheduler.register_group(kinds, false, TaskOptions::default()).unwrap();
+
+    // a task registered after a group is never batched with it
+    let (file, documents_count) = sample_documents(&index_scheduler, 3, 2);
+    file.persist().unwrap();
+    let kind = replace_document_import_task("doggos", Some("id"), 3, documents_count);
+    index_scheduler.register(kind, None, false).unwrap();
+    index_scheduler.assert_internally_consistent();
+
+    handle.advance_one_failed_batch();
+    handle.advance_n_successful_batches(2);
+    index_scheduler.assert_internally_consistent();
+
+    let rtxn = index_scheduler.read_txn().unwrap();
+    let task = |task_id| index_scheduler.queue.tasks.get_task(&rtxn, task_id).unwrap().unwrap();
+    assert_eq!(
+        (1..6)
+            .map(|task_id| (task(task_id).group_uid, task(task_id).batch_uid))
+            .collect::<Vec<_>>(),
+        [
+            (Some(1), Some(1)),
+            (Some(1), Some(1)),
+            (Some(3), Some(2)),
+            (Some(3), Some(2)),
+            (None, Some(3))
+        ]
+    );
+    assert_eq!(
+        (1..6).map(|task_id| task(task_id).status).collect::<Vec<_>>(),
+        [Status::Failed, Status::Failed, Status::Succeeded, Status::Succeeded, Status::Succeeded]
+    );
+    let message = task(1).error.unwrap().message;
+    assert!(message.starts_with("The task group was rolled back because task `2` failed"));
+
+    let batch = index_scheduler.queue.batches.get_batch(&rtxn, 2).unwrap().unwrap();
+    snapshot!(batch.stop_reason, @"batched all enqueued tasks of the task group 3");
+
+    // the deletion of the failed group was rolled back, the one of the succeeded group wasn't
+    let index = index_scheduler.index("doggos").unwrap();
+    let rtxn = index.read_txn().unwrap();
+    let external_ids = index.external_documents_ids();
+    let ids = ["0", "1", "2"].map(|id| external_ids.get(&rtxn, id).unwrap().is_some());
+    assert_eq!(ids, [false, true, true]);
+}
+
+#[test]
+fn cancel_the_whole_task_group() {
+    let (index_scheduler, mut handle) = IndexScheduler::test(true, vec![]);
+
+    let (file, documents_count) = sample_documents(&index_scheduler, 0, 0);
+    file.persist().unwrap();
+    let kinds = vec![
+        replace_document_import_task("doggos", Some("id"), 0, documents_count),
+        KindWithContent::DocumentDeletion { index_uid: S("doggos"), documents_ids: vec![S("0")] },
+    ];
+    index_scheduler.register_group(kinds, false, TaskOptions::default()).unwrap();
+    // only the second task of the group is canceled
+    index_scheduler
+        .register(
+            KindWithContent::TaskCancelation {
+                query: "test_query".to_owned(),
+                tasks: RoaringBitmap::from_iter([1]),
+            },
+            None,
+            false,
+        )
+        .unwrap();
+
+    handle.advance_one_successful_batch();
+    index_scheduler.assert_internally_consistent();
+
+    let rtxn = index_scheduler.read_txn().unwrap();
+    let task = |task_id| index_scheduler.queue.tasks.get_task(&rtxn, task_id).unwrap().unwrap();
+    assert_eq!(
+        (0..2).map(|task_id| (task(task_id).status, task(task_id).canceled_by)).collect::<Vec<_>>(),
+        [(Status::Canceled, Some(2)), (Status::Canceled, Some(2))]
+    );
+    assert!(!index_scheduler.index_exists("doggos").unwrap());
+}
+
+#[test]
+fn enqueue_again_tasks_failing_with_a_transient_error() {
+    let (index_scheduler, mut handle) = IndexScheduler::test_with_custom_config(
+        vec![(1, FailureLocation::TransientFailureInsideProcessBatch)],
+        |config| {
+            config.max_task_retries = 1;
+            config.task_retry_delay = std::time::Duration::from_secs(60 * 60);
+            None
+        },
+    );
+
+    let permanent = milli::UserError::DocumentEmbeddingError {
+        message: S("no vectors provided"),
+        transient: false,
+    };
+    assert!(!crate::Error::from_milli(permanent.into(), None).is_transient());
+
+    index_scheduler.register(index_creation_task("doggos", "id"), None, false).unwrap();
+    handle.advance_one_failed_batch();
+    index_scheduler.assert_internally_consistent();
+    {
+        let rtxn = index_scheduler.read_txn().unwrap();
+        let task = index_scheduler.queue.tasks.get_task(&rtxn, 0).unwrap().unwrap();
+        assert_eq!((task.status, task.retries), (Status::Enqueued, 1));
+        assert!(task.run_after.unwrap() > time::OffsetDateTime::now_utc());
+        snapshot!(task.error.unwrap().message, @"the embedder is down");
+    }
+
+    // the later tasks of the index wait for the retry, the other indexes don't
+    index_scheduler.register(index_creation_task("catto", "id"), None, false).unwrap();
+    index_scheduler.register(index_creation_task("doggos", "id"), None, false).unwrap();
+    handle.advance_one_successful_batch();
+    index_scheduler.assert_internally_consistent();
+    {
+        let rtxn = index_scheduler.read_txn().unwrap();
+        let task = |task_id| index_scheduler.queue.tasks.get_task(&rtxn, task_id).unwrap().unwrap();
+        assert_eq!(
+            [task(0).status, task(1).status, task(2).status],
+            [Status::Enqueued, Status::Succeeded, Status::Enqueued]
+        );
+    }
+
+    // once the retry is due, the task is processed before the later task of its index
+    let mut wtxn = index_scheduler.env.write_txn().unwrap();
+    let mut task = index_scheduler.queue.tasks.get_task(&wtxn, 0).unwrap().unwrap();
+    task.run_after = Some(time::OffsetDateTime::now_utc());
+    index_scheduler.queue.tasks.update_task(&mut wtxn, &task).unwrap();
+    wtxn.commit().unwrap();
+    handle.advance_one_successful_batch();
+    handle.advance_one_failed_batch();
+    index_scheduler.assert_internally_consistent();
+
+    let rtxn = index_scheduler.read_txn().unwrap();
+    let task = |task_id| index_scheduler.queue.tasks.get_task(&rtxn, task_id).unwrap().unwrap();
+    assert_eq!([task(0).status, task(2).status], [Status::Succeeded, Status::Failed]);
+    snapshot!(task(2).error.unwrap().message, @"Index `doggos` already exists.");
+}
+
+#[test]
+fn retried_edition_and_deletion_are_applied() {
+    let (index_scheduler, mut handle) = IndexScheduler::test_with_custom_config(
+        vec![
+            (2, FailureLocation::TransientFailureInsideProcessBatch),
+            (4, FailureLocation::TransientFailureInsideProcessBatch),
+        ],
+        |config| {
+            config.max_task_retries = 1;
+            config.task_retry_delay = std::time::Duration::from_secs(60 * 60);
+            None
+        },
+    );
+    let retry_now = |task_id| {
+        let mut wtxn = index_scheduler.env.write_txn().unwrap();
+        let mut task = index_scheduler.queue.tasks.get_task(&wtxn, task_id).unwrap().unwrap();
+        assert_eq!((task.status, task.retries), (Status::Enqueued, 1));
+        task.run_after = Some(time::OffsetDateTime::now_utc());
+        index_scheduler.queue.tasks.update_task(&mut wtxn, &task).unwrap();
+        wtxn.commit().unwrap();
+    };
+    let documents = || {
+        let index = index_scheduler.index("doggos").unwrap();
+        let rtxn = index.read_txn().unwrap();
+        let field_ids_map = index.fields_ids_map(&rtxn).unwrap();
+        let field_ids = field_ids_map.ids().collect::<Vec<_>>();
+        index
+            .all_documents(&rtxn)
+            .unwrap()
+            .map(|ret| {
+                let document = milli::obkv_to_json(&field_ids, &field_ids_map, ret.unwrap().1);
+                let document = document.unwrap();
+                format!("{}: {}", document["id"], document["doggo"])
+            })
+            .collect::<Vec<_>>()
+    };
+
+    let content = r#"[{ "id": 0, "doggo": "kefir" }, { "id": 1, "doggo": "intel" }]"#;
+    let (_uuid, mut file) = index_scheduler.queue.create_update_file_with_uuid(0).unwrap();
+    let documents_count = read_json(content.as_bytes(), &mut file).unwrap();
+    file.persist().unwrap();
+    index_scheduler
+        .register(
+            replace_document_import_task("doggos", Some("id"), 0, documents_count),
+            None,
+            false,
+        )
+        .unwrap();
+    handle.advance_one_successful_batch();
+
+    let edition = KindWithContent::DocumentEdition {
+        index_uid: S("doggos"),
+        filter_expr: None,
+        context: None,
+        function: S(r#"doc.doggo = "bork""#),
+    };
+    index_scheduler.register(edition, None, false).unwrap();
+    handle.advance_one_failed_batch();
+    retry_now(1);
+    handle.advance_one_successful_batch();
+    snapshot!(format!("{:?}", documents()), @r###"["0: \"bork\"", "1: \"bork\""]"###);
+
+    let deletion =
+        KindWithContent::DocumentDeletion { index_uid: S("doggos"), documents_ids: vec![S("1")] };
+    index_scheduler.register(deletion, None, false).unwrap();
+    handle.advance_one_failed_batch();
+    retry_now(2);
+    handle.advance_one_successful_batch();
+    snapshot!(format!("{:?}", documents()), @r###"["0: \"bork\""]"###);
+
+    let rtxn = index_scheduler.read_txn().unwrap();
+    for task_id in [1, 2] {
+        let task = index_scheduler.queue.tasks.get_task(&rtxn, task_id).unwrap().unwrap();
+        assert_eq!((task.status, task.error), (Status::Succeeded, None));
+    }
+}
+
+#[test]
+fn retry_failed_tasks() {
+    let (index_scheduler, mut handle) = IndexScheduler::test(true, vec![]);
+
+    // deleting an index that does not exist fails
+    index_scheduler
+        .register(KindWithContent::IndexDeletion { index_uid: S("doggos") }, None, false)
+        .unwrap();
+    handle.advance_one_failed_batch();
+
+    // the content of a document addition that failed because of a permanent error is deleted
+    let content = r#"{ "id": "an invalid document id" }"#;
+    let (uuid, mut file) = index_scheduler.queue.create_update_file_with_uuid(0).unwrap();
+    let documents_count = read_json(content.as_bytes(), &mut file).unwrap();
+    file.persist().unwrap();
+    index_scheduler
+        .register(
+            replace_document_import_task("doggos", Some("id"), 0, documents_count),
+            None,
+            false,
+        )
+        .unwrap();
+    // the invalid document only fails its own task
+    handle.advance_one_successful_batch();
+    {
+        let rtxn = index_scheduler.read_txn().unwrap();
+        let task = index_scheduler.queue.tasks.get_task(&rtxn, 1).unwrap().unwrap();
+        assert_eq!(task.status, Status::Failed);
+    }
+    assert!(!index_scheduler.queue.update_file_exists(uuid));
+
+    // the document addition cannot be retried without its documents
+    let error = index_scheduler.retry_tasks(&RoaringBitmap::from_iter([0, 1]), false).unwrap_err();
+    snapshot!(error, @"Task `1` cannot be retried because its documents were deleted. Only the document additions that failed because of a transient error keep their documents.");
+    index_scheduler.assert_internally_consistent();
+
+    let retried = index_scheduler.retry_tasks(&RoaringBitmap::from_iter([0]), false).unwrap();
+    let retried: Vec<_> = retried.iter().map(|task| (task.uid, task.kind.as_kind())).collect();
+    snapshot!(format!("{retried:?}"), @"[(2, IndexDeletion)]");
+    index_scheduler.assert_internally_consistent();
+
+    handle.advance_one_failed_batch();
+    index_scheduler.assert_internally_consistent();
+}
+
+#[test]
+fn notify_the_task_changes_to_the_subscribers() {
+    let (index_scheduler, mut handle) = IndexScheduler::test(true, vec![]);
+    let mut task_events = index_scheduler.subscribe_to_task_events();
+
+    index_scheduler.register(index_creation_task("doggos", "id"), None, false).unwrap();
+    // the dry runs are not notified
+    index_scheduler.register(index_creation_task("catto", "id"), None, true).unwrap();
+    handle.advance_one_successful_batch();
+
+    let events: Vec<_> = std::iter::from_fn(|| task_events.try_recv().ok())
+        .map(|task| (task.uid, task.status))
+        .collect();
+    snapshot!(format!("{events:?}"), @"[(0, Enqueued), (0, Processing), (0, Succeeded)]");
+
+    // the subscription ends when the receiver is dropped
+    drop(task_events);
+    index_scheduler.register(index_creation_task("catto", "id"), None, false).unwrap();
+    assert!(index_scheduler.task_events.is_empty());
+}
+
+#[test]
+fn do_not_batch_the_tasks_of_a_paused_queue() {
+    let (index_scheduler, mut handle) = IndexScheduler::test(true, vec![]);
+
+    let pause = index_scheduler.pause_queue(Some(vec![S("doggos")])).unwrap();
+    assert!(pause.is_index_paused("doggos"));
+    assert!(!pause.is_index_paused("catto"));
+    index_scheduler.register(index_creation_task("doggos", "id"), None, false).unwrap();
+    index_scheduler.register(index_creation_task("catto", "id"), None, false).unwrap();
+
+    // only the task of the index that is not paused is processed
+    handle.advance_one_successful_batch();
+    index_scheduler.assert_internally_consistent();
+    {
+        let rtxn = index_scheduler.read_txn().unwrap();
+        let task = |task_id| index_scheduler.queue.tasks.get_task(&rtxn, task_id).unwrap().unwrap();
+        assert_eq!([task(0).status, task(1).status], [Status::Enqueued, Status::Succeeded]);
+    }
+
+    // once the whole queue is paused, nothing is batched even after resuming the index
+    index_scheduler.pause_queue(None).unwrap();
+    let pause = index_scheduler.resume_queue(Some(vec![S("doggos")])).unwrap();
+    assert!(pause.paused && pause.paused_indexes.is_empty());
+    index_scheduler.register(index_creation_task("pingu", "id"), None, false).unwrap();
+    {
+        let rtxn = index_scheduler.read_txn().unwrap();
+        assert!(index_scheduler.create_next_batch(&rtxn).unwrap().is_none());
+    }
+
+    // resuming the queue processes the enqueued tasks
+    index_scheduler.pause_queue(Some(vec![S("pingu")])).unwrap();
+    assert_eq!(index_scheduler.resume_queue(None).unwrap(), QueuePause::default());
+    handle.advance_one_successful_batch();
+    handle.advance_one_successful_batch();
+    index_scheduler.assert_internally_consistent();
+    let rtxn = index_scheduler.read_txn().unwrap();
+    let succeeded = index_scheduler.queue.tasks.get_status(&rtxn, Status::Succeeded).unwrap();
+    snapshot!(snapshot_bitmap(&succeeded), @"[0,1,2,]");
+}
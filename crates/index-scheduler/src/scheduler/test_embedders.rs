@@ -423,8 +423,10 @@ fn import_vectors_first_and_embedder_later() {
                     max_bytes: Some(
                         400,
                     ),
+                    chunking: None,
                 },
                 quantized: None,
+                reranker: None,
             },
             user_provided: RoaringBitmap<[1, 2]>,
         },
@@ -620,8 +622,10 @@ fn delete_document_containing_vector() {
                         max_bytes: Some(
                             400,
                         ),
+                        chunking: None,
                     },
                     quantized: None,
+                    reranker: None,
                 },
                 user_provided: RoaringBitmap<[0]>,
             },
@@ -664,8 +668,10 @@ fn delete_document_containing_vector() {
                         max_bytes: Some(
                             400,
                         ),
+                        chunking: None,
                     },
                     quantized: None,
+                    reranker: None,
                 },
                 user_provided: RoaringBitmap<[]>,
             },
@@ -1,4 +1,5 @@
 use std::io::{BufWriter, Write};
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 use std::time::Duration;
 
@@ -7,6 +8,8 @@ use crossbeam_channel::RecvTimeoutError;
 use file_store::File;
 use meilisearch_auth::open_auth_store_env;
 use meilisearch_types::document_formats::DocumentFormatError;
+use meilisearch_types::milli::progress::{Progress, Step};
+use meilisearch_types::milli::update::new::IndexingStep;
 use meilisearch_types::milli::update::IndexDocumentsMethod::ReplaceDocuments;
 use meilisearch_types::milli::update::IndexerConfig;
 use meilisearch_types::tasks::KindWithContent;
@@ -30,16 +33,22 @@ pub(crate) enum Breakpoint {
     ProcessBatchSucceeded,
     ProcessBatchFailed,
     InsideProcessBatch,
+    InsidePostProcessing,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub(crate) enum FailureLocation {
     InsideCreateBatch,
     InsideProcessBatch,
+    TransientFailureInsideProcessBatch,
     PanicInsideProcessBatch,
     ProcessUpgrade,
+    /// Doesn't fail but pauses the indexation on the `InsidePostProcessing` breakpoint.
+    InsidePostProcessing,
     AcquiringWtxn,
-    UpdatingTaskAfterProcessBatchSuccess { task_uid: u32 },
+    UpdatingTaskAfterProcessBatchSuccess {
+        task_uid: u32,
+    },
     UpdatingTaskAfterProcessBatchFailure,
     CommittingWtxn,
 }
@@ -113,6 +122,10 @@ impl IndexScheduler {
             instance_features: Default::default(),
             auto_upgrade: true, // Don't cost much and will ensure the happy path works
             embedding_cache_cap: 10,
+            max_parallel_batches: 1,
+            max_task_retries: 0,
+            task_retry_delay: std::time::Duration::ZERO,
+            tasks_archive_path: None,
         };
         let version = configuration(&mut options).unwrap_or({
             (versioning::VERSION_MAJOR, versioning::VERSION_MINOR, versioning::VERSION_PATCH)
@@ -143,6 +156,18 @@ impl IndexScheduler {
         (index_scheduler, index_scheduler_handle)
     }
 
+    /// Blocks the indexation on the `InsidePostProcessing` breakpoint the first time it checks
+    /// whether it must stop while post processing the words, if it is planned for this iteration.
+    pub(crate) fn maybe_pause_post_processing(&self, progress: &Progress, paused: &AtomicBool) {
+        let step = progress.current_step_of::<IndexingStep>();
+        if step == Some(IndexingStep::PostProcessingWords.name())
+            && self.maybe_fail(FailureLocation::InsidePostProcessing).is_err()
+            && !paused.swap(true, Ordering::Relaxed)
+        {
+            self.breakpoint(Breakpoint::InsidePostProcessing);
+        }
+    }
+
     /// Return a [`PlannedFailure`](Error::PlannedFailure) error if a failure is planned
     /// for the given location and current run loop iteration.
     pub(crate) fn maybe_fail(&self, location: FailureLocation) -> crate::Result<()> {
@@ -151,6 +176,13 @@ impl IndexScheduler {
                 FailureLocation::PanicInsideProcessBatch => {
                     panic!("simulated panic")
                 }
+                FailureLocation::TransientFailureInsideProcessBatch => {
+                    let error = meilisearch_types::milli::UserError::DocumentEmbeddingError {
+                        message: S("the embedder is down"),
+                        transient: true,
+                    };
+                    Err(Error::from_milli(error.into(), None))
+                }
                 _ => Err(Error::PlannedFailure),
             }
         } else {
@@ -1,6 +1,6 @@
 use anyhow::bail;
 use meilisearch_types::heed::{Env, RwTxn, WithoutTls};
-use meilisearch_types::tasks::{Details, KindWithContent, Status, Task};
+use meilisearch_types::tasks::{Details, KindWithContent, Status, Task, TaskPriority};
 use meilisearch_types::versioning::{VERSION_MAJOR, VERSION_MINOR, VERSION_PATCH};
 use time::OffsetDateTime;
 use tracing::info;
@@ -88,6 +88,11 @@ pub fn upgrade_index_scheduler(
             details: Some(Details::UpgradeDatabase { from, to }),
             status: Status::Enqueued,
             kind: KindWithContent::UpgradeDatabase { from },
+            priority: TaskPriority::Normal,
+            run_after: None,
+            group_uid: None,
+            retries: 0,
+            interrupted_step: None,
         },
     )?;
     wtxn.commit()?;
@@ -8,7 +8,7 @@ use meilisearch_types::heed::{Database, RoTxn, RwTxn};
 use meilisearch_types::milli::CboRoaringBitmapCodec;
 use meilisearch_types::task_view::DetailsView;
 use meilisearch_types::tasks::{
-    BatchStopReason, Details, IndexSwap, Kind, KindWithContent, Status,
+    BatchSelectionReason, BatchStopReason, Details, IndexSwap, Kind, KindWithContent, Status,
 };
 use roaring::RoaringBitmap;
 use time::OffsetDateTime;
@@ -36,6 +36,8 @@ pub struct ProcessingBatch {
     pub started_at: OffsetDateTime,
     pub finished_at: Option<OffsetDateTime>,
     pub reason: BatchStopReason,
+    /// Why the tasks of this batch were picked before older enqueued tasks, if they were.
+    pub selection: Option<BatchSelectionReason>,
 }
 
 impl ProcessingBatch {
@@ -57,6 +59,7 @@ impl ProcessingBatch {
             started_at: OffsetDateTime::now_utc(),
             finished_at: None,
             reason: Default::default(),
+            selection: None,
         }
     }
 
@@ -149,7 +152,10 @@ impl ProcessingBatch {
             started_at: self.started_at,
             finished_at: self.finished_at,
             enqueued_at: self.enqueued_at,
-            stop_reason: self.reason.to_string(),
+            stop_reason: match &self.selection {
+                Some(selection) => format!("{}; {selection}", self.reason),
+                None => self.reason.to_string(),
+            },
         }
     }
 }
@@ -366,6 +372,11 @@ impl crate::IndexScheduler {
                 details,
                 status,
                 kind,
+                priority,
+                run_after,
+                group_uid,
+                retries: _,
+                interrupted_step: _,
             } = task;
             assert_eq!(uid, task.uid);
             if task.status != Status::Enqueued {
@@ -428,6 +439,20 @@ impl crate::IndexScheduler {
                     .unwrap();
                 assert!(db_finished_at.contains(task_id));
             }
+            if let Some(run_after) = run_after {
+                let db_run_after = self
+                    .queue
+                    .tasks
+                    .run_after
+                    .get(&rtxn, &run_after.unix_timestamp_nanos())
+                    .unwrap()
+                    .unwrap();
+                assert!(db_run_after.contains(task_id));
+            }
+            if let Some(group_uid) = group_uid {
+                // The group is identified by its first task.
+                assert!(group_uid <= uid);
+            }
             if let Some(canceled_by) = canceled_by {
                 let db_canceled_tasks =
                     self.queue.tasks.get_status(&rtxn, Status::Canceled).unwrap();
@@ -608,6 +633,9 @@ impl crate::IndexScheduler {
 
             assert!(self.queue.tasks.get_status(&rtxn, status).unwrap().contains(uid));
             assert!(self.queue.tasks.get_kind(&rtxn, kind.as_kind()).unwrap().contains(uid));
+            if !priority.is_normal() {
+                assert!(self.queue.tasks.get_priority(&rtxn, priority).unwrap().contains(uid));
+            }
 
             if let KindWithContent::DocumentAdditionOrUpdate { content_file, .. } = kind {
                 match status {
@@ -0,0 +1,437 @@
+//! The webhooks registered through the `/webhooks` route.
+//!
+//! Once a batch has been processed, the tasks matching the filter of a webhook are serialized into
+//! an NDJSON payload and handed over to a dedicated thread through a bounded queue. This thread
+//! dispatches the payloads to a small pool of threads sending them, with at most one request in
+//! flight per webhook so that a slow webhook never holds back the others. It schedules the retries
+//! of the failed deliveries with an exponential backoff, drops them once their webhook is deleted,
+//! and records the outcome of every delivery in a log that can be inspected through the
+//! `/webhooks/{uuid}/deliveries` route.
+
+use std::collections::HashSet;
+use std::io::Write;
+use std::time::{Duration, Instant};
+
+use crossbeam_channel::{Receiver, Sender, TrySendError};
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use hmac::{Hmac, Mac};
+use meilisearch_types::heed::types::SerdeJson;
+use meilisearch_types::heed::{Database, Env, RoTxn, RwTxn, WithoutTls};
+use meilisearch_types::milli::{self, BEU32};
+use meilisearch_types::task_view::TaskView;
+use meilisearch_types::tasks::{Task, TaskId};
+use meilisearch_types::webhooks::{DeliveryStatus, Webhook, WebhookDelivery};
+use sha2::Sha256;
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+use crate::uuid_codec::UuidCodec;
+use crate::{Error, Result};
+
+/// The number of database used by the webhooks
+const NUMBER_OF_DATABASES: u32 = 2;
+/// Database const names for the `WebhookStore`.
+mod db_name {
+    pub const WEBHOOKS: &str = "webhooks";
+    pub const WEBHOOK_DELIVERIES: &str = "webhook-deliveries";
+}
+
+/// The header containing the signature of the payload when the webhook has a signing secret.
+pub const WEBHOOK_SIGNATURE_HEADER: &str = "X-Meilisearch-Signature";
+/// The number of requests sent for a single payload before giving up.
+const MAX_DELIVERY_ATTEMPTS: u32 = 5;
+/// The delay before retrying a failed delivery, doubled after every attempt.
+const DELIVERY_RETRY_DELAY: Duration = Duration::from_secs(1);
+/// The number of payloads waiting to be sent, the next payloads are dropped once it is reached.
+const DELIVERY_QUEUE_CAPACITY: usize = 1000;
+/// The number of deliveries kept in the log, the oldest ones are removed first.
+const MAX_LOGGED_DELIVERIES: u64 = 1000;
+/// The number of threads sending the payloads to the webhooks.
+const DELIVERY_WORKERS: usize = 4;
+/// The maximum time to connect to a webhook.
+const DELIVERY_CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+/// The maximum time of a request to a webhook, connection included.
+const DELIVERY_TIMEOUT: Duration = Duration::from_secs(30);
+
+#[derive(Clone)]
+pub(crate) struct WebhookStore {
+    webhooks: Database<UuidCodec, SerdeJson<Webhook>>,
+    deliveries: Database<BEU32, SerdeJson<WebhookDelivery>>,
+    /// Sends the payloads to the delivery thread, which stops once every sender is dropped.
+    sender: Sender<PendingDelivery>,
+    /// Sends the deleted webhooks to the delivery thread, which drops their pending deliveries.
+    deleted: Sender<Uuid>,
+}
+
+/// A payload waiting to be sent to a webhook.
+struct PendingDelivery {
+    webhook: Webhook,
+    task_uids: Vec<TaskId>,
+    payload: Vec<u8>,
+    enqueued_at: OffsetDateTime,
+}
+
+/// A payload being delivered to a webhook.
+struct Delivery {
+    webhook: Webhook,
+    task_uids: Vec<TaskId>,
+    /// The gzip compressed payload.
+    body: Vec<u8>,
+    signature: Option<String>,
+    enqueued_at: OffsetDateTime,
+    attempts: u32,
+    /// The failed deliveries are retried once this instant is reached.
+    next_attempt: Instant,
+}
+
+/// Why a request to a webhook failed.
+struct DeliveryError {
+    status_code: Option<u16>,
+    message: String,
+    /// Network errors, timeouts and server errors are retried, other client errors are not.
+    retryable: bool,
+}
+
+impl WebhookStore {
+    pub(crate) const fn nb_db() -> u32 {
+        NUMBER_OF_DATABASES
+    }
+
+    pub(crate) fn new(env: &Env<WithoutTls>, wtxn: &mut RwTxn) -> Result<Self> {
+        let webhooks = env.create_database(wtxn, Some(db_name::WEBHOOKS))?;
+        let deliveries = env.create_database(wtxn, Some(db_name::WEBHOOK_DELIVERIES))?;
+
+        let (sender, receiver) = crossbeam_channel::bounded(DELIVERY_QUEUE_CAPACITY);
+        let (deleted, deleted_receiver) = crossbeam_channel::unbounded();
+        let env = env.clone();
+        std::thread::Builder::new()
+            .name(String::from("webhook-delivery"))
+            .spawn(move || deliver_payloads(env, deliveries, receiver, deleted_receiver))?;
+
+        Ok(Self { webhooks, deliveries, sender, deleted })
+    }
+
+    /// Returns all the webhooks, from the oldest to the most recently created.
+    pub(crate) fn all(&self, rtxn: &RoTxn) -> Result<Vec<Webhook>> {
+        let mut webhooks = self
+            .webhooks
+            .iter(rtxn)?
+            .map(|ret| ret.map(|(_, webhook)| webhook))
+            .collect::<Result<Vec<_>, _>>()?;
+        webhooks.sort_by_key(|webhook| webhook.created_at);
+        Ok(webhooks)
+    }
+
+    pub(crate) fn get(&self, rtxn: &RoTxn, uuid: Uuid) -> Result<Option<Webhook>> {
+        Ok(self.webhooks.get(rtxn, &uuid)?)
+    }
+
+    pub(crate) fn put(&self, wtxn: &mut RwTxn, webhook: &Webhook) -> Result<()> {
+        Ok(self.webhooks.put(wtxn, &webhook.uuid, webhook)?)
+    }
+
+    /// Deletes the webhook along with its deliveries, returns `false` if it didn't exist.
+    pub(crate) fn delete(&self, wtxn: &mut RwTxn, uuid: Uuid) -> Result<bool> {
+        if !self.webhooks.delete(wtxn, &uuid)? {
+            return Ok(false);
+        }
+
+        let mut iter = self.deliveries.iter_mut(wtxn)?;
+        while let Some((_, delivery)) = iter.next().transpose()? {
+            if delivery.webhook_uuid == uuid {
+                // safety: We don't keep references to the database
+                unsafe { iter.del_current()? };
+            }
+        }
+
+        Ok(true)
+    }
+
+    /// Drops the payloads waiting to be sent to the deleted webhook, including the retries.
+    pub(crate) fn cancel_deliveries(&self, uuid: Uuid) {
+        // The delivery thread only stops once the store is dropped.
+        let _ = self.deleted.send(uuid);
+    }
+
+    /// Returns the logged deliveries of the webhook, from the most recent to the oldest.
+    pub(crate) fn deliveries(&self, rtxn: &RoTxn, uuid: Uuid) -> Result<Vec<WebhookDelivery>> {
+        let mut deliveries = Vec::new();
+        for ret in self.deliveries.rev_iter(rtxn)? {
+            let (_, delivery) = ret?;
+            if delivery.webhook_uuid == uuid {
+                deliveries.push(delivery);
+            }
+        }
+        Ok(deliveries)
+    }
+
+    /// Sends the tasks matching the filter of each webhook to the delivery thread.
+    pub(crate) fn notify(&self, webhooks: Vec<Webhook>, tasks: &[Task]) -> Result<()> {
+        let enqueued_at = OffsetDateTime::now_utc();
+
+        for webhook in webhooks {
+            let mut task_uids = Vec::new();
+            let mut payload = Vec::new();
+            for task in tasks.iter().filter(|task| webhook.filter.matches(task)) {
+                task_uids.push(task.uid);
+                serde_json::to_writer(&mut payload, &TaskView::from_task(task)).map_err(|e| {
+                    Error::from_milli(milli::InternalError::SerdeJson(e).into(), None)
+                })?;
+                payload.push(b'\n');
+            }
+
+            if task_uids.is_empty() {
+                continue;
+            }
+
+            let pending = PendingDelivery { webhook, task_uids, payload, enqueued_at };
+            match self.sender.try_send(pending) {
+                Ok(()) => (),
+                Err(TrySendError::Full(pending)) => tracing::error!(
+                    "The webhook delivery queue is full, the tasks {:?} are not sent to `{}`",
+                    pending.task_uids,
+                    pending.webhook.uuid
+                ),
+                // The delivery thread only stops once the store is dropped.
+                Err(TrySendError::Disconnected(_)) => (),
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// The run loop of the delivery thread, dispatching the payloads to the sending threads.
+fn deliver_payloads(
+    env: Env<WithoutTls>,
+    deliveries: Database<BEU32, SerdeJson<WebhookDelivery>>,
+    receiver: Receiver<PendingDelivery>,
+    deleted_receiver: Receiver<Uuid>,
+) {
+    let agent = ureq::AgentBuilder::new()
+        .timeout_connect(DELIVERY_CONNECT_TIMEOUT)
+        .timeout(DELIVERY_TIMEOUT)
+        .build();
+    let (requests, requests_receiver) = crossbeam_channel::unbounded::<Delivery>();
+    let (responses_sender, responses) = crossbeam_channel::unbounded();
+    for worker in 0..DELIVERY_WORKERS {
+        let agent = agent.clone();
+        let requests = requests_receiver.clone();
+        let responses = responses_sender.clone();
+        let spawned = std::thread::Builder::new().name(format!("webhook-delivery-{worker}")).spawn(
+            move || {
+                // The sending threads stop once the delivery thread stops.
+                for delivery in requests {
+                    let response = send(&agent, &delivery);
+                    if responses.send((delivery, response)).is_err() {
+                        break;
+                    }
+                }
+            },
+        );
+        if let Err(e) = spawned {
+            tracing::error!("Could not spawn a webhook delivery thread: {e}");
+        }
+    }
+    drop(responses_sender);
+
+    let never = crossbeam_channel::never();
+    // The deliveries waiting for their first or next attempt, in the order they were received.
+    let mut waiting: Vec<Delivery> = Vec::new();
+    // The webhooks with a request in flight.
+    let mut in_flight: HashSet<Uuid> = HashSet::new();
+    let mut deleted: HashSet<Uuid> = HashSet::new();
+
+    loop {
+        let now = Instant::now();
+        let mut index = 0;
+        while index < waiting.len() {
+            let delivery = &waiting[index];
+            if delivery.next_attempt <= now && !in_flight.contains(&delivery.webhook.uuid) {
+                let delivery = waiting.remove(index);
+                in_flight.insert(delivery.webhook.uuid);
+                // The sending threads only stop once this sender is dropped.
+                let _ = requests.send(delivery);
+            } else {
+                index += 1;
+            }
+        }
+
+        // We stop receiving new payloads while too many deliveries are waiting to be sent.
+        let receiver = if waiting.len() < DELIVERY_QUEUE_CAPACITY { &receiver } else { &never };
+        let next_attempt = waiting
+            .iter()
+            .filter(|delivery| !in_flight.contains(&delivery.webhook.uuid))
+            .map(|delivery| delivery.next_attempt)
+            .min();
+        let timeout = next_attempt.map_or_else(crossbeam_channel::never, crossbeam_channel::at);
+
+        crossbeam_channel::select! {
+            recv(receiver) -> pending => match pending {
+                Ok(pending) if deleted.contains(&pending.webhook.uuid) => (),
+                Ok(pending) => match prepare(pending) {
+                    Ok(delivery) => waiting.push(delivery),
+                    Err(failed) => record_delivery(&env, deliveries, failed),
+                },
+                Err(_) => break,
+            },
+            recv(deleted_receiver) -> uuid => match uuid {
+                Ok(uuid) => {
+                    waiting.retain(|delivery| delivery.webhook.uuid != uuid);
+                    deleted.insert(uuid);
+                }
+                Err(_) => break,
+            },
+            recv(responses) -> response => match response {
+                Ok((delivery, response)) => {
+                    in_flight.remove(&delivery.webhook.uuid);
+                    if !deleted.contains(&delivery.webhook.uuid) {
+                        waiting.extend(conclude(&env, deliveries, delivery, response));
+                    }
+                }
+                Err(_) => break,
+            },
+            recv(timeout) -> _ => (),
+        }
+    }
+}
+
+/// Compresses and signs the payload, returns the failed delivery to log if it can't be compressed.
+fn prepare(pending: PendingDelivery) -> Result<Delivery, WebhookDelivery> {
+    let PendingDelivery { webhook, task_uids, payload, enqueued_at } = pending;
+
+    // The signature is computed on the uncompressed payload.
+    let signature =
+        webhook.signing_secret.as_deref().map(|secret| sign_webhook_payload(secret, &payload));
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    match encoder.write_all(&payload).and_then(|()| encoder.finish()) {
+        Ok(body) => Ok(Delivery {
+            webhook,
+            task_uids,
+            body,
+            signature,
+            enqueued_at,
+            attempts: 0,
+            next_attempt: Instant::now(),
+        }),
+        Err(e) => Err(WebhookDelivery {
+            // the uid is assigned when the delivery is logged
+            uid: 0,
+            webhook_uuid: webhook.uuid,
+            task_uids,
+            status: DeliveryStatus::Failed,
+            attempts: 0,
+            status_code: None,
+            error: Some(format!("Could not compress the payload: {e}")),
+            enqueued_at,
+            finished_at: OffsetDateTime::now_utc(),
+        }),
+    }
+}
+
+/// Logs the outcome of a request to the webhook, returns the delivery to retry if any.
+fn conclude(
+    env: &Env<WithoutTls>,
+    deliveries: Database<BEU32, SerdeJson<WebhookDelivery>>,
+    mut delivery: Delivery,
+    response: Result<u16, DeliveryError>,
+) -> Option<Delivery> {
+    delivery.attempts += 1;
+    let (status, status_code, error) = match response {
+        Ok(status_code) => (DeliveryStatus::Succeeded, Some(status_code), None),
+        Err(DeliveryError { retryable: true, .. }) if delivery.attempts < MAX_DELIVERY_ATTEMPTS => {
+            let delay = DELIVERY_RETRY_DELAY * 2u32.pow(delivery.attempts - 1);
+            delivery.next_attempt = Instant::now() + delay;
+            return Some(delivery);
+        }
+        Err(DeliveryError { status_code, message, .. }) => {
+            tracing::error!(
+                "While sending data to the webhook `{}`: {message}",
+                delivery.webhook.uuid
+            );
+            (DeliveryStatus::Failed, status_code, Some(message))
+        }
+    };
+
+    let Delivery { webhook, task_uids, enqueued_at, attempts, .. } = delivery;
+    let delivery = WebhookDelivery {
+        // the uid is assigned when the delivery is logged
+        uid: 0,
+        webhook_uuid: webhook.uuid,
+        task_uids,
+        status,
+        attempts,
+        status_code,
+        error,
+        enqueued_at,
+        finished_at: OffsetDateTime::now_utc(),
+    };
+    record_delivery(env, deliveries, delivery);
+    None
+}
+
+fn send(agent: &ureq::Agent, delivery: &Delivery) -> Result<u16, DeliveryError> {
+    let Delivery { webhook, body, signature, .. } = delivery;
+    let mut request = agent
+        .post(&webhook.url)
+        .set("Content-Encoding", "gzip")
+        .set("Content-Type", "application/x-ndjson");
+    for (name, value) in &webhook.headers {
+        request = request.set(name, value);
+    }
+    if let Some(signature) = signature {
+        request = request.set(WEBHOOK_SIGNATURE_HEADER, signature);
+    }
+
+    match request.send_bytes(body) {
+        Ok(response) => Ok(response.status()),
+        Err(ureq::Error::Status(code, _)) => Err(DeliveryError {
+            status_code: Some(code),
+            message: format!("The webhook responded with the status code `{code}`."),
+            retryable: code == 408 || code == 429 || code >= 500,
+        }),
+        Err(e) => Err(DeliveryError { status_code: None, message: e.to_string(), retryable: true }),
+    }
+}
+
+/// Returns the value of the signature header: the hex encoded HMAC-SHA256 of the payload.
+pub fn sign_webhook_payload(secret: &str, payload: &[u8]) -> String {
+    // safe to unwrap, HMAC accepts keys of any size
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes()).unwrap();
+    mac.update(payload);
+    format!("sha256={:x}", mac.finalize().into_bytes())
+}
+
+fn record_delivery(
+    env: &Env<WithoutTls>,
+    deliveries: Database<BEU32, SerdeJson<WebhookDelivery>>,
+    delivery: WebhookDelivery,
+) {
+    if let Err(e) = log_delivery(env, deliveries, delivery) {
+        tracing::error!("While logging a webhook delivery: {e}");
+    }
+}
+
+fn log_delivery(
+    env: &Env<WithoutTls>,
+    deliveries: Database<BEU32, SerdeJson<WebhookDelivery>>,
+    mut delivery: WebhookDelivery,
+) -> Result<()> {
+    let mut wtxn = env.write_txn()?;
+    delivery.uid = match deliveries.last(&wtxn)? {
+        Some((uid, _)) => uid + 1,
+        None => 0,
+    };
+    deliveries.put(&mut wtxn, &delivery.uid, &delivery)?;
+
+    let to_remove = deliveries.len(&wtxn)?.saturating_sub(MAX_LOGGED_DELIVERIES);
+    for _ in 0..to_remove {
+        if let Some((uid, _)) = deliveries.first(&wtxn)? {
+            deliveries.delete(&mut wtxn, &uid)?;
+        }
+    }
+
+    wtxn.commit()?;
+    Ok(())
+}
@@ -10,6 +10,7 @@ use meilisearch_types::heed::{Env, WithoutTls};
 use meilisearch_types::index_uid_pattern::IndexUidPattern;
 use meilisearch_types::keys::{Action, CreateApiKey, Key, PatchApiKey};
 use meilisearch_types::milli::update::Setting;
+use meilisearch_types::tasks::TaskPriority;
 use serde::{Deserialize, Serialize};
 pub use store::open_auth_store_env;
 use store::{generate_key_as_hexa, HeedAuthStore};
@@ -101,7 +102,12 @@ impl AuthController {
 
         let allow_index_creation = self.is_key_authorized(uid, Action::IndexesAdd, None)?;
 
-        Ok(AuthFilter { search_rules, key_authorized_indexes, allow_index_creation })
+        Ok(AuthFilter {
+            search_rules,
+            key_authorized_indexes,
+            allow_index_creation,
+            task_priority: Some(key.task_priority.unwrap_or_default()),
+        })
     }
 
     pub fn list_keys(&self) -> Result<Vec<Key>> {
@@ -165,10 +171,12 @@ impl AuthController {
     }
 }
 
+#[derive(Clone)]
 pub struct AuthFilter {
     search_rules: Option<SearchRules>,
     key_authorized_indexes: SearchRules,
     allow_index_creation: bool,
+    task_priority: Option<TaskPriority>,
 }
 
 impl Default for AuthFilter {
@@ -177,6 +185,7 @@ impl Default for AuthFilter {
             search_rules: None,
             key_authorized_indexes: SearchRules::default(),
             allow_index_creation: true,
+            task_priority: None,
         }
     }
 }
@@ -198,9 +207,18 @@ impl AuthFilter {
             search_rules: None,
             key_authorized_indexes: SearchRules::Set(allowed_indexes),
             allow_index_creation: false,
+            task_priority: None,
         }
     }
 
+    /// Return the priority given by the API key to the tasks it registers.
+    ///
+    /// It is `None` when the request doesn't use an API key, e.g. with the master key.
+    #[inline]
+    pub fn task_priority(&self) -> Option<TaskPriority> {
+        self.task_priority
+    }
+
     pub fn all_indexes_authorized(&self) -> bool {
         self.key_authorized_indexes.all_indexes_authorized()
             && self
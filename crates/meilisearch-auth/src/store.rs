@@ -117,7 +117,14 @@ impl HeedAuthStore {
                     actions.insert(Action::SnapshotsCreate);
                 }
                 Action::TasksAll => {
-                    actions.extend([Action::TasksGet, Action::TasksDelete, Action::TasksCancel]);
+                    actions.extend([
+                        Action::TasksGet,
+                        Action::TasksDelete,
+                        Action::TasksCancel,
+                        Action::TasksRetry,
+                        Action::TasksPause,
+                        Action::TasksResume,
+                    ]);
                 }
                 Action::StatsAll => {
                     actions.insert(Action::StatsGet);
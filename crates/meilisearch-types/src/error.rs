@@ -209,6 +209,7 @@ ImmutableApiKeyCreatedAt              , InvalidRequest       , BAD_REQUEST;
 ImmutableApiKeyExpiresAt              , InvalidRequest       , BAD_REQUEST;
 ImmutableApiKeyIndexes                , InvalidRequest       , BAD_REQUEST;
 ImmutableApiKeyKey                    , InvalidRequest       , BAD_REQUEST;
+ImmutableApiKeyTaskPriority           , InvalidRequest       , BAD_REQUEST;
 ImmutableApiKeyUid                    , InvalidRequest       , BAD_REQUEST;
 ImmutableApiKeyUpdatedAt              , InvalidRequest       , BAD_REQUEST;
 ImmutableIndexCreatedAt               , InvalidRequest       , BAD_REQUEST;
@@ -229,7 +230,12 @@ InvalidApiKeyIndexes                  , InvalidRequest       , BAD_REQUEST ;
 InvalidApiKeyLimit                    , InvalidRequest       , BAD_REQUEST ;
 InvalidApiKeyName                     , InvalidRequest       , BAD_REQUEST ;
 InvalidApiKeyOffset                   , InvalidRequest       , BAD_REQUEST ;
+InvalidApiKeyTaskPriority             , InvalidRequest       , BAD_REQUEST ;
 InvalidApiKeyUid                      , InvalidRequest       , BAD_REQUEST ;
+InvalidAutocompleteAttributesToSearchOn, InvalidRequest     , BAD_REQUEST ;
+InvalidAutocompleteFilter             , InvalidRequest       , BAD_REQUEST ;
+InvalidAutocompleteLimit              , InvalidRequest       , BAD_REQUEST ;
+InvalidAutocompleteQ                  , InvalidRequest       , BAD_REQUEST ;
 InvalidContentType                    , InvalidRequest       , UNSUPPORTED_MEDIA_TYPE ;
 InvalidDocumentCsvDelimiter           , InvalidRequest       , BAD_REQUEST ;
 InvalidDocumentFields                 , InvalidRequest       , BAD_REQUEST ;
@@ -297,15 +303,18 @@ InvalidSimilarOffset                  , InvalidRequest       , BAD_REQUEST ;
 InvalidSearchOffset                   , InvalidRequest       , BAD_REQUEST ;
 InvalidSearchPage                     , InvalidRequest       , BAD_REQUEST ;
 InvalidSearchQ                        , InvalidRequest       , BAD_REQUEST ;
+InvalidSearchQuerySyntax              , InvalidRequest       , BAD_REQUEST ;
 InvalidFacetSearchQuery               , InvalidRequest       , BAD_REQUEST ;
 InvalidFacetSearchName                , InvalidRequest       , BAD_REQUEST ;
 FacetSearchDisabled                   , InvalidRequest       , BAD_REQUEST ;
 InvalidSearchVector                   , InvalidRequest       , BAD_REQUEST ;
+InvalidSearchVectorSearch             , InvalidRequest       , BAD_REQUEST ;
 InvalidSearchShowMatchesPosition      , InvalidRequest       , BAD_REQUEST ;
 InvalidSearchShowRankingScore         , InvalidRequest       , BAD_REQUEST ;
 InvalidSimilarShowRankingScore        , InvalidRequest       , BAD_REQUEST ;
 InvalidSearchShowRankingScoreDetails  , InvalidRequest       , BAD_REQUEST ;
 InvalidSimilarShowRankingScoreDetails , InvalidRequest       , BAD_REQUEST ;
+InvalidSearchShowSuggestions          , InvalidRequest       , BAD_REQUEST ;
 InvalidSearchSort                     , InvalidRequest       , BAD_REQUEST ;
 InvalidSearchDistinct                 , InvalidRequest       , BAD_REQUEST ;
 InvalidSettingsDisplayedAttributes    , InvalidRequest       , BAD_REQUEST ;
@@ -313,6 +322,11 @@ InvalidSettingsDistinctAttribute      , InvalidRequest       , BAD_REQUEST ;
 InvalidSettingsProximityPrecision     , InvalidRequest       , BAD_REQUEST ;
 InvalidSettingsFacetSearch            , InvalidRequest       , BAD_REQUEST ;
 InvalidSettingsPrefixSearch           , InvalidRequest       , BAD_REQUEST ;
+InvalidSettingsStemming               , InvalidRequest       , BAD_REQUEST ;
+InvalidSettingsSynonymRules           , InvalidRequest       , BAD_REQUEST ;
+InvalidSettingsSearchableAttributesWeights, InvalidRequest  , BAD_REQUEST ;
+InvalidSettingsNgramAttributes        , InvalidRequest       , BAD_REQUEST ;
+InvalidSettingsQueryRules             , InvalidRequest       , BAD_REQUEST ;
 InvalidSettingsFaceting               , InvalidRequest       , BAD_REQUEST ;
 InvalidSettingsFilterableAttributes   , InvalidRequest       , BAD_REQUEST ;
 InvalidSettingsPagination             , InvalidRequest       , BAD_REQUEST ;
@@ -334,18 +348,28 @@ InvalidSwapDuplicateIndexFound        , InvalidRequest       , BAD_REQUEST ;
 InvalidSwapIndexes                    , InvalidRequest       , BAD_REQUEST ;
 InvalidTaskAfterEnqueuedAt            , InvalidRequest       , BAD_REQUEST ;
 InvalidTaskAfterFinishedAt            , InvalidRequest       , BAD_REQUEST ;
+InvalidTaskAfterRunAfter              , InvalidRequest       , BAD_REQUEST ;
 InvalidTaskAfterStartedAt             , InvalidRequest       , BAD_REQUEST ;
 InvalidTaskBeforeEnqueuedAt           , InvalidRequest       , BAD_REQUEST ;
 InvalidTaskBeforeFinishedAt           , InvalidRequest       , BAD_REQUEST ;
+InvalidTaskBeforeRunAfter             , InvalidRequest       , BAD_REQUEST ;
 InvalidTaskBeforeStartedAt            , InvalidRequest       , BAD_REQUEST ;
 InvalidTaskCanceledBy                 , InvalidRequest       , BAD_REQUEST ;
 InvalidTaskFrom                       , InvalidRequest       , BAD_REQUEST ;
+InvalidTaskGroupOperations            , InvalidRequest       , BAD_REQUEST ;
 InvalidTaskLimit                      , InvalidRequest       , BAD_REQUEST ;
 InvalidTaskReverse                    , InvalidRequest       , BAD_REQUEST ;
+InvalidTaskRunAfter                   , InvalidRequest       , BAD_REQUEST ;
 InvalidTaskStatuses                   , InvalidRequest       , BAD_REQUEST ;
 InvalidTaskTypes                      , InvalidRequest       , BAD_REQUEST ;
 InvalidTaskUids                       , InvalidRequest       , BAD_REQUEST  ;
 InvalidBatchUids                      , InvalidRequest       , BAD_REQUEST  ;
+InvalidWebhookDeliveryLimit           , InvalidRequest       , BAD_REQUEST ;
+InvalidWebhookDeliveryOffset          , InvalidRequest       , BAD_REQUEST ;
+InvalidWebhookFilter                  , InvalidRequest       , BAD_REQUEST ;
+InvalidWebhookHeaders                 , InvalidRequest       , BAD_REQUEST ;
+InvalidWebhookSigningSecret           , InvalidRequest       , BAD_REQUEST ;
+InvalidWebhookUrl                     , InvalidRequest       , BAD_REQUEST ;
 IoError                               , System               , UNPROCESSABLE_ENTITY;
 FeatureNotEnabled                     , InvalidRequest       , BAD_REQUEST ;
 MalformedPayload                      , InvalidRequest       , BAD_REQUEST ;
@@ -364,6 +388,7 @@ MissingPayload                        , InvalidRequest       , BAD_REQUEST ;
 MissingSearchHybrid                   , InvalidRequest       , BAD_REQUEST ;
 MissingSwapIndexes                    , InvalidRequest       , BAD_REQUEST ;
 MissingTaskFilters                    , InvalidRequest       , BAD_REQUEST ;
+MissingWebhookUrl                     , InvalidRequest       , BAD_REQUEST ;
 NoSpaceLeftOnDevice                   , System               , UNPROCESSABLE_ENTITY;
 PayloadTooLarge                       , InvalidRequest       , PAYLOAD_TOO_LARGE ;
 RemoteBadResponse                     , System               , BAD_GATEWAY ;
@@ -375,12 +400,16 @@ RemoteTimeout                         , System               , BAD_GATEWAY ;
 TooManySearchRequests                 , System               , SERVICE_UNAVAILABLE ;
 TaskNotFound                          , InvalidRequest       , NOT_FOUND ;
 TaskFileNotFound                      , InvalidRequest       , NOT_FOUND ;
+TaskNotRetriable                      , InvalidRequest       , BAD_REQUEST ;
+TaskGroupFailed                       , InvalidRequest       , BAD_REQUEST ;
+TaskGroupInternalError                , Internal             , INTERNAL_SERVER_ERROR ;
 BatchNotFound                         , InvalidRequest       , NOT_FOUND ;
 TooManyOpenFiles                      , System               , UNPROCESSABLE_ENTITY ;
 TooManyVectors                        , InvalidRequest       , BAD_REQUEST ;
 UnretrievableDocument                 , Internal             , BAD_REQUEST ;
 UnretrievableErrorCode                , InvalidRequest       , BAD_REQUEST ;
 UnsupportedMediaType                  , InvalidRequest       , UNSUPPORTED_MEDIA_TYPE ;
+WebhookNotFound                       , InvalidRequest       , NOT_FOUND ;
 
 // Experimental features
 VectorEmbeddingError                  , InvalidRequest       , BAD_REQUEST ;
@@ -449,6 +478,7 @@ impl ErrorCode for milli::Error {
                     UserError::InvalidSearchableAttribute { .. } => {
                         Code::InvalidSearchAttributesToSearchOn
                     }
+                    UserError::InvalidAdvancedQuery(_) => Code::InvalidSearchQ,
                     UserError::InvalidFacetSearchFacetName { .. } => {
                         Code::InvalidFacetSearchFacetName
                     }
@@ -465,11 +495,14 @@ impl ErrorCode for milli::Error {
                     UserError::InvalidMinTypoWordLenSetting(_, _) => {
                         Code::InvalidSettingsTypoTolerance
                     }
+                    UserError::UnsupportedStemmingLocale { .. } => Code::InvalidSettingsStemming,
+                    UserError::InvalidSynonymRule { .. } => Code::InvalidSettingsSynonymRules,
+                    UserError::InvalidNgramMinLength { .. } => Code::InvalidSettingsNgramAttributes,
+                    UserError::InvalidQueryRule { .. } => Code::InvalidSettingsQueryRules,
                     UserError::InvalidSearchEmbedder(_) => Code::InvalidSearchEmbedder,
                     UserError::InvalidSimilarEmbedder(_) => Code::InvalidSimilarEmbedder,
-                    UserError::VectorEmbeddingError(_) | UserError::DocumentEmbeddingError(_) => {
-                        Code::VectorEmbeddingError
-                    }
+                    UserError::VectorEmbeddingError(_)
+                    | UserError::DocumentEmbeddingError { .. } => Code::VectorEmbeddingError,
                     UserError::DocumentEditionCannotModifyPrimaryKey
                     | UserError::DocumentEditionDocumentMustBeObject
                     | UserError::DocumentEditionRuntimeError(_)
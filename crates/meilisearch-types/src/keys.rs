@@ -16,6 +16,7 @@ use crate::deserr::{immutable_field_error, DeserrError, DeserrJsonError};
 use crate::error::deserr_codes::*;
 use crate::error::{Code, ErrorCode, ParseOffsetDateTimeError};
 use crate::index_uid_pattern::{IndexUidPattern, IndexUidPatternFormatError};
+use crate::tasks::TaskPriority;
 
 pub type KeyId = Uuid;
 
@@ -60,11 +61,16 @@ pub struct CreateApiKey {
     /// Represent the expiration date and time as RFC 3339 format. `null` equals to no expiration time.
     #[deserr(error = DeserrJsonError<InvalidApiKeyExpiresAt>, try_from(Option<String>) = parse_expiration_date -> ParseOffsetDateTimeError, missing_field_error = DeserrJsonError::missing_api_key_expires_at)]
     pub expires_at: Option<OffsetDateTime>,
+    /// The priority of the tasks enqueued with this key. The `TaskPriority` header can lower it but not raise it. `null` equals to the `normal` priority.
+    #[schema(value_type = Option<TaskPriority>, example = json!("low"))]
+    #[deserr(default, error = DeserrJsonError<InvalidApiKeyTaskPriority>)]
+    pub task_priority: Option<TaskPriority>,
 }
 
 impl CreateApiKey {
     pub fn to_key(self) -> Key {
-        let CreateApiKey { description, name, uid, actions, indexes, expires_at } = self;
+        let CreateApiKey { description, name, uid, actions, indexes, expires_at, task_priority } =
+            self;
         let now = OffsetDateTime::now_utc();
         Key {
             description,
@@ -75,6 +81,7 @@ impl CreateApiKey {
             expires_at,
             created_at: now,
             updated_at: now,
+            task_priority,
         }
     }
 }
@@ -91,6 +98,7 @@ fn deny_immutable_fields_api_key(
         "expiresAt" => immutable_field_error(field, accepted, Code::ImmutableApiKeyExpiresAt),
         "createdAt" => immutable_field_error(field, accepted, Code::ImmutableApiKeyCreatedAt),
         "updatedAt" => immutable_field_error(field, accepted, Code::ImmutableApiKeyUpdatedAt),
+        "taskPriority" => immutable_field_error(field, accepted, Code::ImmutableApiKeyTaskPriority),
         _ => deserr::take_cf_content(DeserrJsonError::<BadRequest>::error::<Infallible>(
             None,
             deserr::ErrorKind::UnknownKey { key: field, accepted },
@@ -126,6 +134,8 @@ pub struct Key {
     pub created_at: OffsetDateTime,
     #[serde(with = "time::serde::rfc3339")]
     pub updated_at: OffsetDateTime,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub task_priority: Option<TaskPriority>,
 }
 
 impl Key {
@@ -141,6 +151,7 @@ impl Key {
             expires_at: None,
             created_at: now,
             updated_at: now,
+            task_priority: None,
         }
     }
 
@@ -156,6 +167,7 @@ impl Key {
             expires_at: None,
             created_at: now,
             updated_at: now,
+            task_priority: None,
         }
     }
 }
@@ -308,6 +320,27 @@ pub enum Action {
     #[serde(rename = "network.update")]
     #[deserr(rename = "network.update")]
     NetworkUpdate,
+    #[serde(rename = "tasks.retry")]
+    #[deserr(rename = "tasks.retry")]
+    TasksRetry,
+    #[serde(rename = "webhooks.get")]
+    #[deserr(rename = "webhooks.get")]
+    WebhooksGet,
+    #[serde(rename = "webhooks.create")]
+    #[deserr(rename = "webhooks.create")]
+    WebhooksCreate,
+    #[serde(rename = "webhooks.update")]
+    #[deserr(rename = "webhooks.update")]
+    WebhooksUpdate,
+    #[serde(rename = "webhooks.delete")]
+    #[deserr(rename = "webhooks.delete")]
+    WebhooksDelete,
+    #[serde(rename = "tasks.pause")]
+    #[deserr(rename = "tasks.pause")]
+    TasksPause,
+    #[serde(rename = "tasks.resume")]
+    #[deserr(rename = "tasks.resume")]
+    TasksResume,
 }
 
 impl Action {
@@ -349,6 +382,13 @@ impl Action {
             EXPERIMENTAL_FEATURES_UPDATE => Some(Self::ExperimentalFeaturesUpdate),
             NETWORK_GET => Some(Self::NetworkGet),
             NETWORK_UPDATE => Some(Self::NetworkUpdate),
+            TASKS_RETRY => Some(Self::TasksRetry),
+            WEBHOOKS_GET => Some(Self::WebhooksGet),
+            WEBHOOKS_CREATE => Some(Self::WebhooksCreate),
+            WEBHOOKS_UPDATE => Some(Self::WebhooksUpdate),
+            WEBHOOKS_DELETE => Some(Self::WebhooksDelete),
+            TASKS_PAUSE => Some(Self::TasksPause),
+            TASKS_RESUME => Some(Self::TasksResume),
             _otherwise => None,
         }
     }
@@ -397,4 +437,14 @@ pub mod actions {
 
     pub const NETWORK_GET: u8 = NetworkGet.repr();
     pub const NETWORK_UPDATE: u8 = NetworkUpdate.repr();
+
+    pub const TASKS_RETRY: u8 = TasksRetry.repr();
+
+    pub const WEBHOOKS_GET: u8 = WebhooksGet.repr();
+    pub const WEBHOOKS_CREATE: u8 = WebhooksCreate.repr();
+    pub const WEBHOOKS_UPDATE: u8 = WebhooksUpdate.repr();
+    pub const WEBHOOKS_DELETE: u8 = WebhooksDelete.repr();
+
+    pub const TASKS_PAUSE: u8 = TasksPause.repr();
+    pub const TASKS_RESUME: u8 = TasksResume.repr();
 }
@@ -8,9 +8,16 @@ use std::str::FromStr;
 
 use deserr::{DeserializeError, Deserr, ErrorKind, MergeWithError, ValuePointerRef};
 use fst::IntoStreamer;
+use milli::attributes_typo_tolerance::AttributeTypoTolerance;
 use milli::disabled_typos_terms::DisabledTyposTerms;
 use milli::index::{IndexEmbeddingConfig, PrefixSearch};
+use milli::ngram_attributes::{
+    NgramAttribute, NgramMode, DEFAULT_NGRAM_MAX_MATCHES, DEFAULT_NGRAM_MIN_LENGTH,
+    MIN_NGRAM_LENGTH,
+};
 use milli::proximity::ProximityPrecision;
+use milli::query_rules::QueryRule;
+use milli::synonym_rules::SynonymRule;
 use milli::update::Setting;
 use milli::{Criterion, CriterionError, FilterableAttributesRule, Index, DEFAULT_VALUES_PER_FACET};
 use serde::{Deserialize, Serialize, Serializer};
@@ -19,7 +26,7 @@ use utoipa::ToSchema;
 use crate::deserr::DeserrJsonError;
 use crate::error::deserr_codes::*;
 use crate::facet_values_sort::FacetValuesSort;
-use crate::locales::LocalizedAttributesRuleView;
+use crate::locales::{Locale, LocalizedAttributesRuleView};
 
 /// The maximum number of results that the engine
 /// will be able to return in one search call.
@@ -109,6 +116,73 @@ pub struct TypoSettings {
     #[deserr(default)]
     #[schema(value_type = Option<bool>, example = json!(true))]
     pub disable_on_numbers: Setting<bool>,
+    /// Typo tolerance and prefix search rules overriding the index-wide ones for some attributes.
+    #[serde(default, skip_serializing_if = "Setting::is_not_set")]
+    #[deserr(default, error = DeserrJsonError<InvalidSettingsTypoTolerance>)]
+    #[schema(value_type = Option<BTreeMap<String, AttributeTypoSettings>>, example = json!({ "sku": { "enabled": false, "prefixSearch": "disabled" } }))]
+    pub per_attribute: Setting<BTreeMap<String, AttributeTypoSettings>>,
+}
+
+/// Typo tolerance and prefix search rules of an attribute, the ones that are not set fall back to
+/// the index-wide rules.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq, Deserr, ToSchema)]
+#[serde(deny_unknown_fields, rename_all = "camelCase")]
+#[deserr(error = DeserrJsonError<InvalidSettingsTypoTolerance>, rename_all = camelCase, deny_unknown_fields)]
+pub struct AttributeTypoSettings {
+    #[serde(default, skip_serializing_if = "Setting::is_not_set")]
+    #[deserr(default)]
+    #[schema(value_type = Option<bool>, example = json!(false))]
+    pub enabled: Setting<bool>,
+    #[serde(default, skip_serializing_if = "Setting::is_not_set")]
+    #[deserr(default, error = DeserrJsonError<InvalidSettingsTypoTolerance>)]
+    #[schema(value_type = Option<MinWordSizeTyposSetting>, example = json!({ "oneTypo": 3 }))]
+    pub min_word_size_for_typos: Setting<MinWordSizeTyposSetting>,
+    #[serde(default, skip_serializing_if = "Setting::is_not_set")]
+    #[deserr(default, error = DeserrJsonError<InvalidSettingsPrefixSearch>)]
+    #[schema(value_type = Option<PrefixSearchSettings>, example = json!("disabled"))]
+    pub prefix_search: Setting<PrefixSearchSettings>,
+}
+
+impl From<AttributeTypoTolerance> for AttributeTypoSettings {
+    fn from(value: AttributeTypoTolerance) -> Self {
+        let AttributeTypoTolerance {
+            enabled,
+            min_word_len_one_typo,
+            min_word_len_two_typos,
+            prefix_search,
+        } = value;
+
+        let min_word_size_for_typos =
+            if min_word_len_one_typo.is_none() && min_word_len_two_typos.is_none() {
+                Setting::NotSet
+            } else {
+                Setting::Set(MinWordSizeTyposSetting {
+                    one_typo: Setting::some_or_not_set(min_word_len_one_typo),
+                    two_typos: Setting::some_or_not_set(min_word_len_two_typos),
+                })
+            };
+
+        Self {
+            enabled: Setting::some_or_not_set(enabled),
+            min_word_size_for_typos,
+            prefix_search: Setting::some_or_not_set(prefix_search.map(Into::into)),
+        }
+    }
+}
+
+impl From<AttributeTypoSettings> for AttributeTypoTolerance {
+    fn from(value: AttributeTypoSettings) -> Self {
+        let AttributeTypoSettings { enabled, min_word_size_for_typos, prefix_search } = value;
+        let MinWordSizeTyposSetting { one_typo, two_typos } =
+            min_word_size_for_typos.set().unwrap_or_default();
+
+        Self {
+            enabled: enabled.set(),
+            min_word_len_one_typo: one_typo.set(),
+            min_word_len_two_typos: two_typos.set(),
+            prefix_search: prefix_search.set().map(Into::into),
+        }
+    }
 }
 
 #[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq, Deserr, ToSchema)]
@@ -293,6 +367,31 @@ pub struct Settings<T> {
     #[deserr(default, error = DeserrJsonError<InvalidSettingsPrefixSearch>)]
     #[schema(value_type = Option<PrefixSearchSettings>, example = json!("Hemlo"))]
     pub prefix_search: Setting<PrefixSearchSettings>,
+    /// Locales in which the words are stemmed, so that the words sharing a stem match each other.
+    #[serde(default, skip_serializing_if = "Setting::is_not_set")]
+    #[deserr(default, error = DeserrJsonError<InvalidSettingsStemming>)]
+    #[schema(value_type = Option<Vec<Locale>>, example = json!(["eng", "fra"]))]
+    pub stemming: Setting<Vec<Locale>>,
+    /// One-way synonyms and groups of equivalent words, with the weight of their synonyms.
+    #[serde(default, skip_serializing_if = "Setting::is_not_set")]
+    #[deserr(default, error = DeserrJsonError<InvalidSettingsSynonymRules>)]
+    #[schema(value_type = Option<Vec<SynonymRule>>, example = json!([{ "input": "iphone", "synonyms": ["apple phone"], "weight": 0.8 }]))]
+    pub synonym_rules: Setting<Vec<SynonymRule>>,
+    /// Weights of the searchable attributes, the higher the more important. Replace the weights deduced from the order of the searchable attributes.
+    #[serde(default, skip_serializing_if = "Setting::is_not_set")]
+    #[deserr(default, error = DeserrJsonError<InvalidSettingsSearchableAttributesWeights>)]
+    #[schema(value_type = Option<BTreeMap<String, u16>>, example = json!({ "title": 3, "description": 1 }))]
+    pub searchable_attributes_weights: Setting<BTreeMap<String, u16>>,
+    /// Attributes whose words are split into n-grams, so that a query word also matches the words of these attributes containing it.
+    #[serde(default, skip_serializing_if = "Setting::is_not_set")]
+    #[deserr(default, error = DeserrJsonError<InvalidSettingsNgramAttributes>)]
+    #[schema(value_type = Option<BTreeMap<String, NgramAttributeSettings>>, example = json!({ "sku": { "mode": "ngram", "minLength": 3, "maxMatches": 50 } }))]
+    pub ngram_attributes: Setting<BTreeMap<String, NgramAttributeSettings>>,
+    /// Rules altering the searches whose query matches a pattern, evaluated in order.
+    #[serde(default, skip_serializing_if = "Setting::is_not_set")]
+    #[deserr(default, error = DeserrJsonError<InvalidSettingsQueryRules>)]
+    #[schema(value_type = Option<Vec<QueryRule>>, example = json!([{ "match": "contains", "pattern": "black friday", "filter": "on_sale = true" }]))]
+    pub query_rules: Setting<Vec<QueryRule>>,
 
     #[serde(skip)]
     #[deserr(skip)]
@@ -310,6 +409,12 @@ impl<T> Settings<T> {
                 continue;
             };
 
+            if let Setting::Set(reranker) = &mut embedder.reranker {
+                if let Some(api_key) = &mut reranker.api_key {
+                    Self::hide_secret(api_key);
+                }
+            }
+
             let Setting::Set(api_key) = &mut embedder.api_key else {
                 continue;
             };
@@ -359,6 +464,11 @@ impl Settings<Checked> {
             localized_attributes: Setting::Reset,
             facet_search: Setting::Reset,
             prefix_search: Setting::Reset,
+            stemming: Setting::Reset,
+            synonym_rules: Setting::Reset,
+            searchable_attributes_weights: Setting::Reset,
+            ngram_attributes: Setting::Reset,
+            query_rules: Setting::Reset,
             _kind: PhantomData,
         }
     }
@@ -385,6 +495,11 @@ impl Settings<Checked> {
             localized_attributes: localized_attributes_rules,
             facet_search,
             prefix_search,
+            stemming,
+            synonym_rules,
+            searchable_attributes_weights,
+            ngram_attributes,
+            query_rules,
             _kind,
         } = self;
 
@@ -409,6 +524,11 @@ impl Settings<Checked> {
             localized_attributes: localized_attributes_rules,
             facet_search,
             prefix_search,
+            stemming,
+            synonym_rules,
+            searchable_attributes_weights,
+            ngram_attributes,
+            query_rules,
             _kind: PhantomData,
         }
     }
@@ -459,12 +579,74 @@ impl Settings<Unchecked> {
             localized_attributes: self.localized_attributes,
             facet_search: self.facet_search,
             prefix_search: self.prefix_search,
+            stemming: self.stemming,
+            synonym_rules: self.synonym_rules,
+            searchable_attributes_weights: self.searchable_attributes_weights,
+            ngram_attributes: self.ngram_attributes,
+            query_rules: self.query_rules,
             _kind: PhantomData,
         }
     }
 
     pub fn validate(self) -> Result<Self, milli::Error> {
-        self.validate_embedding_settings()
+        self.validate_embedding_settings()?
+            .validate_stemming_settings()?
+            .validate_synonym_rules_settings()?
+            .validate_ngram_attributes_settings()?
+            .validate_query_rules_settings()
+    }
+
+    fn validate_query_rules_settings(self) -> Result<Self, milli::Error> {
+        if let Setting::Set(rules) = &self.query_rules {
+            for (rule_index, rule) in rules.iter().enumerate() {
+                if let Err(reason) = rule.validate() {
+                    return Err(milli::UserError::InvalidQueryRule { rule_index, reason }.into());
+                }
+            }
+        }
+        Ok(self)
+    }
+
+    fn validate_ngram_attributes_settings(self) -> Result<Self, milli::Error> {
+        if let Setting::Set(rules) = &self.ngram_attributes {
+            for (attribute, rule) in rules {
+                if let Setting::Set(min_length) = rule.min_length {
+                    if min_length < MIN_NGRAM_LENGTH {
+                        return Err(milli::UserError::InvalidNgramMinLength {
+                            attribute: attribute.clone(),
+                            min_length,
+                            min: MIN_NGRAM_LENGTH,
+                        }
+                        .into());
+                    }
+                }
+            }
+        }
+        Ok(self)
+    }
+
+    fn validate_synonym_rules_settings(self) -> Result<Self, milli::Error> {
+        if let Setting::Set(rules) = &self.synonym_rules {
+            for (rule_index, rule) in rules.iter().enumerate() {
+                if let Err(reason) = rule.validate() {
+                    return Err(milli::UserError::InvalidSynonymRule { rule_index, reason }.into());
+                }
+            }
+        }
+        Ok(self)
+    }
+
+    fn validate_stemming_settings(self) -> Result<Self, milli::Error> {
+        if let Setting::Set(locales) = &self.stemming {
+            if let Some(locale) = locales
+                .iter()
+                .find(|locale| !milli::stemming::is_stemming_supported((**locale).into()))
+            {
+                let locale = format!("{locale:?}").to_lowercase();
+                return Err(milli::UserError::UnsupportedStemmingLocale { locale }.into());
+            }
+        }
+        Ok(self)
     }
 
     fn validate_embedding_settings(mut self) -> Result<Self, milli::Error> {
@@ -535,6 +717,14 @@ impl Settings<Unchecked> {
             },
             prefix_search: other.prefix_search.or(self.prefix_search),
             facet_search: other.facet_search.or(self.facet_search),
+            stemming: other.stemming.clone().or(self.stemming.clone()),
+            synonym_rules: other.synonym_rules.clone().or(self.synonym_rules.clone()),
+            searchable_attributes_weights: other
+                .searchable_attributes_weights
+                .clone()
+                .or(self.searchable_attributes_weights.clone()),
+            ngram_attributes: other.ngram_attributes.clone().or(self.ngram_attributes.clone()),
+            query_rules: other.query_rules.clone().or(self.query_rules.clone()),
             _kind: PhantomData,
         }
     }
@@ -573,6 +763,11 @@ pub fn apply_settings_to_builder(
         localized_attributes: localized_attributes_rules,
         facet_search,
         prefix_search,
+        stemming,
+        synonym_rules,
+        searchable_attributes_weights,
+        ngram_attributes,
+        query_rules,
         _kind,
     } = settings;
 
@@ -712,6 +907,17 @@ pub fn apply_settings_to_builder(
                 Setting::Reset => builder.reset_disable_on_numbers(),
                 Setting::NotSet => (),
             }
+
+            match value.per_attribute {
+                Setting::Set(ref rules) => builder.set_attributes_typo_tolerance(
+                    rules
+                        .iter()
+                        .map(|(attribute, rules)| (attribute.clone(), rules.clone().into()))
+                        .collect(),
+                ),
+                Setting::Reset => builder.reset_attributes_typo_tolerance(),
+                Setting::NotSet => (),
+            }
         }
         Setting::Reset => {
             // all typo settings need to be reset here.
@@ -720,6 +926,7 @@ pub fn apply_settings_to_builder(
             builder.reset_min_word_len_two_typos();
             builder.reset_exact_words();
             builder.reset_exact_attributes();
+            builder.reset_attributes_typo_tolerance();
         }
         Setting::NotSet => (),
     }
@@ -783,6 +990,43 @@ pub fn apply_settings_to_builder(
         Setting::Reset => builder.reset_facet_search(),
         Setting::NotSet => (),
     }
+
+    match stemming {
+        Setting::Set(locales) => {
+            builder.set_stemming(locales.iter().map(|locale| (*locale).into()).collect())
+        }
+        Setting::Reset => builder.reset_stemming(),
+        Setting::NotSet => (),
+    }
+
+    match synonym_rules {
+        Setting::Set(rules) => builder.set_synonym_rules(rules.clone()),
+        Setting::Reset => builder.reset_synonym_rules(),
+        Setting::NotSet => (),
+    }
+
+    match searchable_attributes_weights {
+        Setting::Set(weights) => builder.set_searchable_attributes_weights(weights.clone()),
+        Setting::Reset => builder.reset_searchable_attributes_weights(),
+        Setting::NotSet => (),
+    }
+
+    match ngram_attributes {
+        Setting::Set(rules) => builder.set_ngram_attributes(
+            rules
+                .iter()
+                .map(|(attribute, rule)| (attribute.clone(), rule.clone().into()))
+                .collect(),
+        ),
+        Setting::Reset => builder.reset_ngram_attributes(),
+        Setting::NotSet => (),
+    }
+
+    match query_rules {
+        Setting::Set(rules) => builder.set_query_rules(rules.clone()),
+        Setting::Reset => builder.reset_query_rules(),
+        Setting::NotSet => (),
+    }
 }
 
 pub enum SecretPolicy {
@@ -838,6 +1082,11 @@ pub fn settings(
 
     let disabled_attributes = index.exact_attributes(rtxn)?.into_iter().map(String::from).collect();
     let DisabledTyposTerms { disable_on_numbers } = index.disabled_typos_terms(rtxn)?;
+    let per_attribute = index
+        .attributes_typo_tolerance(rtxn)?
+        .into_iter()
+        .map(|(attribute, rules)| (attribute, rules.into()))
+        .collect();
 
     let typo_tolerance = TypoSettings {
         enabled: Setting::Set(index.authorize_typos(rtxn)?),
@@ -845,6 +1094,7 @@ pub fn settings(
         disable_on_words: Setting::Set(disabled_words),
         disable_on_attributes: Setting::Set(disabled_attributes),
         disable_on_numbers: Setting::Set(disable_on_numbers),
+        per_attribute: Setting::Set(per_attribute),
     };
 
     let faceting = FacetingSettings {
@@ -889,6 +1139,16 @@ pub fn settings(
 
     let facet_search = index.facet_search(rtxn)?;
 
+    let stemming = index.stemming(rtxn)?.unwrap_or_default();
+
+    let synonym_rules = index.synonym_rules(rtxn)?;
+
+    let searchable_attributes_weights = index.searchable_attributes_weights(rtxn)?;
+
+    let ngram_attributes = index.ngram_attributes(rtxn)?;
+
+    let query_rules = index.query_rules(rtxn)?;
+
     let mut settings = Settings {
         displayed_attributes: match displayed_attributes {
             Some(attrs) => Setting::Set(attrs),
@@ -927,6 +1187,16 @@ pub fn settings(
         },
         prefix_search: Setting::Set(prefix_search.unwrap_or_default()),
         facet_search: Setting::Set(facet_search),
+        stemming: Setting::Set(stemming.into_iter().map(Locale::from).collect()),
+        synonym_rules: Setting::Set(synonym_rules),
+        searchable_attributes_weights: Setting::Set(searchable_attributes_weights),
+        ngram_attributes: Setting::Set(
+            ngram_attributes
+                .into_iter()
+                .map(|(attribute, rule)| (attribute, rule.into()))
+                .collect(),
+        ),
+        query_rules: Setting::Set(query_rules),
         _kind: PhantomData,
     };
 
@@ -1109,6 +1379,61 @@ pub enum PrefixSearchSettings {
     Disabled,
 }
 
+/// How the words of an attribute are split into n-grams.
+#[derive(Debug, Clone, PartialEq, Eq, Deserr, Serialize, Deserialize, ToSchema)]
+#[serde(deny_unknown_fields, rename_all = "camelCase")]
+#[deserr(error = DeserrJsonError<InvalidSettingsNgramAttributes>, rename_all = camelCase, deny_unknown_fields)]
+pub struct NgramAttributeSettings {
+    /// `ngram` to match any part of the words, `edgeNgram` to only match their beginning.
+    #[schema(value_type = NgramModeSettings, example = json!("ngram"))]
+    pub mode: NgramModeSettings,
+    /// The minimum number of characters of a part of a word to match it, `3` by default.
+    #[serde(default, skip_serializing_if = "Setting::is_not_set")]
+    #[deserr(default)]
+    #[schema(value_type = Option<u8>, example = json!(3))]
+    pub min_length: Setting<u8>,
+    /// The maximum number of words matched by a part of a word, `50` by default.
+    #[serde(default, skip_serializing_if = "Setting::is_not_set")]
+    #[deserr(default)]
+    #[schema(value_type = Option<u16>, example = json!(50))]
+    pub max_matches: Setting<u16>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserr, Serialize, Deserialize, ToSchema)]
+#[serde(deny_unknown_fields, rename_all = "camelCase")]
+#[schema(rename_all = "camelCase")]
+#[deserr(error = DeserrJsonError<InvalidSettingsNgramAttributes>, rename_all = camelCase, deny_unknown_fields)]
+pub enum NgramModeSettings {
+    Ngram,
+    EdgeNgram,
+}
+
+impl From<NgramAttribute> for NgramAttributeSettings {
+    fn from(value: NgramAttribute) -> Self {
+        let NgramAttribute { mode, min_length, max_matches } = value;
+        let mode = match mode {
+            NgramMode::Ngram => NgramModeSettings::Ngram,
+            NgramMode::EdgeNgram => NgramModeSettings::EdgeNgram,
+        };
+        Self { mode, min_length: Setting::Set(min_length), max_matches: Setting::Set(max_matches) }
+    }
+}
+
+impl From<NgramAttributeSettings> for NgramAttribute {
+    fn from(value: NgramAttributeSettings) -> Self {
+        let NgramAttributeSettings { mode, min_length, max_matches } = value;
+        let mode = match mode {
+            NgramModeSettings::Ngram => NgramMode::Ngram,
+            NgramModeSettings::EdgeNgram => NgramMode::EdgeNgram,
+        };
+        Self {
+            mode,
+            min_length: min_length.set().unwrap_or(DEFAULT_NGRAM_MIN_LENGTH),
+            max_matches: max_matches.set().unwrap_or(DEFAULT_NGRAM_MAX_MATCHES),
+        }
+    }
+}
+
 impl From<PrefixSearch> for PrefixSearchSettings {
     fn from(value: PrefixSearch) -> Self {
         match value {
@@ -1154,6 +1479,11 @@ pub(crate) mod test {
             search_cutoff_ms: Setting::NotSet,
             facet_search: Setting::NotSet,
             prefix_search: Setting::NotSet,
+            stemming: Setting::NotSet,
+            synonym_rules: Setting::NotSet,
+            searchable_attributes_weights: Setting::NotSet,
+            ngram_attributes: Setting::NotSet,
+            query_rules: Setting::NotSet,
             _kind: PhantomData::<Unchecked>,
         };
 
@@ -1185,6 +1515,11 @@ pub(crate) mod test {
             search_cutoff_ms: Setting::NotSet,
             facet_search: Setting::NotSet,
             prefix_search: Setting::NotSet,
+            stemming: Setting::NotSet,
+            synonym_rules: Setting::NotSet,
+            searchable_attributes_weights: Setting::NotSet,
+            ngram_attributes: Setting::NotSet,
+            query_rules: Setting::NotSet,
             _kind: PhantomData::<Unchecked>,
         };
 
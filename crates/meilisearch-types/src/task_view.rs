@@ -6,7 +6,9 @@ use utoipa::ToSchema;
 use crate::batches::BatchId;
 use crate::error::ResponseError;
 use crate::settings::{Settings, Unchecked};
-use crate::tasks::{serialize_duration, Details, IndexSwap, Kind, Status, Task, TaskId};
+use crate::tasks::{
+    serialize_duration, Details, IndexSwap, Kind, Status, Task, TaskId, TaskPriority,
+};
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, ToSchema)]
 #[serde(rename_all = "camelCase")]
@@ -46,10 +48,33 @@ pub struct TaskView {
     #[schema(value_type = String, example = json!("2024-08-08_14:12:09.393Z"))]
     #[serde(with = "time::serde::rfc3339::option", default)]
     pub finished_at: Option<OffsetDateTime>,
+    /// The priority of the task, only displayed when it is not `normal`.
+    #[serde(default, skip_serializing_if = "TaskPriority::is_normal")]
+    pub priority: TaskPriority,
+    /// The date before which the task is not processed, only displayed for scheduled tasks.
+    #[schema(value_type = Option<String>, example = json!("2024-08-09T00:00:00Z"))]
+    #[serde(
+        with = "time::serde::rfc3339::option",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub run_after: Option<OffsetDateTime>,
+    /// The uid of the first task of the task group, only displayed for grouped tasks.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub group_uid: Option<TaskId>,
+    /// The number of times the task was automatically retried after a transient failure,
+    /// only displayed for retried tasks.
+    #[serde(default, skip_serializing_if = "crate::tasks::is_zero")]
+    pub retries: u32,
 }
 
 impl TaskView {
     pub fn from_task(task: &Task) -> TaskView {
+        let mut details = task.details.clone().map(DetailsView::from);
+        if let Some(step) = &task.interrupted_step {
+            details.get_or_insert_with(DetailsView::default).interrupted_step = Some(step.clone());
+        }
+
         TaskView {
             uid: task.uid,
             batch_uid: task.batch_uid,
@@ -57,12 +82,16 @@ impl TaskView {
             status: task.status,
             kind: task.kind.as_kind(),
             canceled_by: task.canceled_by,
-            details: task.details.clone().map(DetailsView::from),
+            details,
             error: task.error.clone(),
             duration: task.started_at.zip(task.finished_at).map(|(start, end)| end - start),
             enqueued_at: task.enqueued_at,
             started_at: task.started_at,
             finished_at: task.finished_at,
+            priority: task.priority,
+            run_after: task.run_after,
+            group_uid: task.group_uid,
+            retries: task.retries,
         }
     }
 }
@@ -118,6 +147,10 @@ pub struct DetailsView {
     pub upgrade_from: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub upgrade_to: Option<String>,
+    /// The indexing step the task was in when its processing was canceled.
+    #[schema(example = json!("post processing words"))]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub interrupted_step: Option<String>,
 }
 
 impl DetailsView {
@@ -250,6 +283,7 @@ impl DetailsView {
                 (None, Some(to)) | (Some(to), None) => Some(to),
                 (Some(_), Some(to)) => Some(to),
             },
+            interrupted_step: self.interrupted_step.clone().or(other.interrupted_step.clone()),
         }
     }
 }
@@ -1,8 +1,9 @@
 use core::fmt;
-use std::collections::HashSet;
+use std::collections::{BTreeSet, HashSet};
 use std::fmt::{Display, Write};
 use std::str::FromStr;
 
+use deserr::Deserr;
 use enum_iterator::Sequence;
 use milli::update::IndexDocumentsMethod;
 use milli::Object;
@@ -39,6 +40,30 @@ pub struct Task {
 
     pub status: Status,
     pub kind: KindWithContent,
+
+    #[serde(default, skip_serializing_if = "TaskPriority::is_normal")]
+    pub priority: TaskPriority,
+    /// The task is not processed before this date.
+    #[serde(
+        default,
+        with = "time::serde::rfc3339::option",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub run_after: Option<OffsetDateTime>,
+    /// The uid of the first task of the group this task belongs to. The tasks of a group
+    /// are processed in a single batch and either all succeed or all fail.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub group_uid: Option<TaskId>,
+    /// The number of times the task was automatically enqueued again after a transient failure.
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub retries: u32,
+    /// The indexing step the task was in when its processing was canceled.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub interrupted_step: Option<String>,
+}
+
+pub fn is_zero(n: &u32) -> bool {
+    *n == 0
 }
 
 impl Task {
@@ -163,6 +188,26 @@ pub struct IndexSwap {
     pub indexes: (String, String),
 }
 
+/// Whether the task queue is paused, globally or for some indexes only.
+///
+/// The tasks are still enqueued while the queue is paused, but no new batch is started with them.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+#[schema(rename_all = "camelCase")]
+pub struct QueuePause {
+    /// No batch is started while the whole queue is paused.
+    pub paused: bool,
+    /// The indexes whose tasks are not processed, ignored when the whole queue is paused.
+    #[schema(example = json!(["movies"]))]
+    pub paused_indexes: BTreeSet<String>,
+}
+
+impl QueuePause {
+    pub fn is_index_paused(&self, index: &str) -> bool {
+        self.paused || self.paused_indexes.contains(index)
+    }
+}
+
 impl KindWithContent {
     pub fn as_kind(&self) -> Kind {
         match self {
@@ -469,6 +514,85 @@ impl fmt::Display for ParseTaskStatusError {
 }
 impl std::error::Error for ParseTaskStatusError {}
 
+/// The priority of a task.
+///
+/// The scheduler processes the indexes having enqueued tasks of the highest priority first, but
+/// the tasks of an index are always processed in the order they were enqueued.
+#[derive(
+    Debug,
+    Default,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    Hash,
+    Serialize,
+    Deserialize,
+    Deserr,
+    Sequence,
+    PartialOrd,
+    Ord,
+    ToSchema,
+)]
+#[schema(example = json!(TaskPriority::High))]
+#[serde(rename_all = "camelCase")]
+#[deserr(rename_all = camelCase)]
+pub enum TaskPriority {
+    Low,
+    #[default]
+    Normal,
+    High,
+}
+
+impl TaskPriority {
+    pub fn is_normal(&self) -> bool {
+        *self == TaskPriority::Normal
+    }
+}
+
+impl Display for TaskPriority {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TaskPriority::Low => write!(f, "low"),
+            TaskPriority::Normal => write!(f, "normal"),
+            TaskPriority::High => write!(f, "high"),
+        }
+    }
+}
+
+impl FromStr for TaskPriority {
+    type Err = ParseTaskPriorityError;
+
+    fn from_str(priority: &str) -> Result<Self, Self::Err> {
+        if priority.eq_ignore_ascii_case("low") {
+            Ok(TaskPriority::Low)
+        } else if priority.eq_ignore_ascii_case("normal") {
+            Ok(TaskPriority::Normal)
+        } else if priority.eq_ignore_ascii_case("high") {
+            Ok(TaskPriority::High)
+        } else {
+            Err(ParseTaskPriorityError(priority.to_owned()))
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct ParseTaskPriorityError(pub String);
+impl fmt::Display for ParseTaskPriorityError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "`{}` is not a valid task priority. Available priorities are {}.",
+            self.0,
+            enum_iterator::all::<TaskPriority>()
+                .map(|p| format!("`{p}`"))
+                .collect::<Vec<String>>()
+                .join(", ")
+        )
+    }
+}
+impl std::error::Error for ParseTaskPriorityError {}
+
 /// The type of the task.
 #[derive(
     Debug,
@@ -724,6 +848,36 @@ pub enum BatchStopReason {
     SettingsWithDocumentOperation {
         id: TaskId,
     },
+    TaskGroup {
+        id: TaskId,
+    },
+    ExhaustedTaskGroup {
+        group_uid: TaskId,
+    },
+}
+
+/// Why the scheduler batched the tasks of an index before older enqueued tasks.
+#[derive(Debug, Clone)]
+pub enum BatchSelectionReason {
+    /// The index has enqueued tasks of a higher priority than the older tasks.
+    Priority { index: String, priority: TaskPriority },
+    /// The indexes of the older tasks were batched more recently than this index.
+    RoundRobin { index: String },
+}
+
+impl Display for BatchSelectionReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BatchSelectionReason::Priority { index, priority } => write!(
+                f,
+                "index `{index}` was picked before older tasks as it has enqueued tasks of `{priority}` priority"
+            ),
+            BatchSelectionReason::RoundRobin { index } => write!(
+                f,
+                "index `{index}` was picked before older tasks as the indexes of these tasks were batched more recently"
+            ),
+        }
+    }
 }
 
 impl BatchStopReason {
@@ -812,6 +966,12 @@ impl Display for BatchStopReason {
                     "task with id {id} is a document operation in a batch of settings changes"
                 )
             }
+            BatchStopReason::TaskGroup { id } => {
+                write!(f, "task with id {id} is part of a task group that is batched alone")
+            }
+            BatchStopReason::ExhaustedTaskGroup { group_uid } => {
+                write!(f, "batched all enqueued tasks of the task group {group_uid}")
+            }
         }
     }
 }
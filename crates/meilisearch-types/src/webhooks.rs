@@ -0,0 +1,93 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use crate::index_uid_pattern::IndexUidPattern;
+use crate::tasks::{Kind, Status, Task, TaskId};
+
+/// A webhook registered through the `/webhooks` route.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct Webhook {
+    pub uuid: Uuid,
+    pub url: String,
+    /// The headers sent with every delivery, e.g. an `Authorization` header.
+    #[serde(default)]
+    pub headers: BTreeMap<String, String>,
+    #[serde(default)]
+    pub filter: WebhookFilter,
+    /// The secret used to sign the payloads, the signature is sent in the `X-Meilisearch-Signature` header.
+    #[serde(default)]
+    pub signing_secret: Option<String>,
+    #[serde(with = "time::serde::rfc3339")]
+    pub created_at: OffsetDateTime,
+    #[serde(with = "time::serde::rfc3339")]
+    pub updated_at: OffsetDateTime,
+}
+
+/// Restricts the tasks sent to a webhook, a missing field matches every task.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq, ToSchema)]
+#[serde(rename_all = "camelCase")]
+#[schema(rename_all = "camelCase")]
+pub struct WebhookFilter {
+    #[schema(value_type = Option<Vec<String>>, example = json!(["movies", "products_*"]))]
+    #[serde(default)]
+    pub index_uids: Option<Vec<IndexUidPattern>>,
+    #[serde(default)]
+    pub types: Option<Vec<Kind>>,
+    #[serde(default)]
+    pub statuses: Option<Vec<Status>>,
+}
+
+impl WebhookFilter {
+    /// Whether the task must be sent to the webhook.
+    ///
+    /// Tasks that are not related to an index are never matched by a filter on the index uids.
+    pub fn matches(&self, task: &Task) -> bool {
+        let index_matches = self.index_uids.as_ref().is_none_or(|patterns| {
+            task.index_uid().is_some_and(|uid| patterns.iter().any(|p| p.matches_str(uid)))
+        });
+        let type_matches =
+            self.types.as_ref().is_none_or(|types| types.contains(&task.kind.as_kind()));
+        let status_matches =
+            self.statuses.as_ref().is_none_or(|statuses| statuses.contains(&task.status));
+
+        index_matches && type_matches && status_matches
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub enum DeliveryStatus {
+    Succeeded,
+    Failed,
+}
+
+/// The outcome of sending a payload to a webhook, kept in the delivery log.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, ToSchema)]
+#[serde(rename_all = "camelCase")]
+#[schema(rename_all = "camelCase")]
+pub struct WebhookDelivery {
+    pub uid: u32,
+    #[schema(value_type = String, example = "05e3d3f8-5a2d-4c3b-9a61-3e2ad7d1a0c1")]
+    pub webhook_uuid: Uuid,
+    /// The uids of the tasks sent in the payload.
+    #[schema(value_type = Vec<u32>)]
+    pub task_uids: Vec<TaskId>,
+    pub status: DeliveryStatus,
+    /// The number of requests that were sent, including the retries.
+    pub attempts: u32,
+    /// The HTTP status of the last response, if the webhook responded.
+    pub status_code: Option<u16>,
+    /// Why the last attempt failed.
+    pub error: Option<String>,
+    #[schema(value_type = String, example = json!("2024-08-08T14:12:09.393Z"))]
+    #[serde(with = "time::serde::rfc3339")]
+    pub enqueued_at: OffsetDateTime,
+    #[schema(value_type = String, example = json!("2024-08-08T14:12:10.102Z"))]
+    #[serde(with = "time::serde::rfc3339")]
+    pub finished_at: OffsetDateTime,
+}
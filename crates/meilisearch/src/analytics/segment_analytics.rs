@@ -200,6 +200,10 @@ struct Infos {
     experimental_get_task_documents_route: bool,
     experimental_composite_embedders: bool,
     experimental_embedding_cache_entries: usize,
+    experimental_persistent_embedding_cache_size: u64,
+    experimental_max_parallel_batches: usize,
+    experimental_max_task_retries: u32,
+    experimental_tasks_archive_dir: bool,
     gpu_enabled: bool,
     db_path: bool,
     import_dump: bool,
@@ -248,6 +252,11 @@ impl Infos {
             experimental_max_number_of_batched_tasks,
             experimental_limit_batched_tasks_total_size,
             experimental_embedding_cache_entries,
+            experimental_persistent_embedding_cache_size,
+            experimental_max_parallel_batches,
+            experimental_max_task_retries,
+            experimental_task_retry_delay_sec: _,
+            experimental_tasks_archive_dir,
             http_addr,
             master_key: _,
             env,
@@ -315,6 +324,11 @@ impl Infos {
             experimental_get_task_documents_route: get_task_documents_route,
             experimental_composite_embedders: composite_embedders,
             experimental_embedding_cache_entries,
+            experimental_persistent_embedding_cache_size:
+                experimental_persistent_embedding_cache_size.as_u64(),
+            experimental_max_parallel_batches: experimental_max_parallel_batches.get(),
+            experimental_max_task_retries,
+            experimental_tasks_archive_dir: experimental_tasks_archive_dir.is_some(),
             gpu_enabled: meilisearch_types::milli::vector::is_cuda_enabled(),
             db_path: db_path != PathBuf::from("./data.ms"),
             import_dump: import_dump.is_some(),
@@ -37,7 +37,8 @@ use index_scheduler::{IndexScheduler, IndexSchedulerOptions};
 use meilisearch_auth::{open_auth_store_env, AuthController};
 use meilisearch_types::milli::constants::VERSION_MAJOR;
 use meilisearch_types::milli::documents::{DocumentsBatchBuilder, DocumentsBatchReader};
-use meilisearch_types::milli::update::{IndexDocumentsConfig, IndexDocumentsMethod};
+use meilisearch_types::milli::update::{IndexDocumentsConfig, IndexDocumentsMethod, IndexerConfig};
+use meilisearch_types::milli::vector::persistent_cache::PersistentEmbeddingCache;
 use meilisearch_types::settings::apply_settings_to_builder;
 use meilisearch_types::tasks::KindWithContent;
 use meilisearch_types::versioning::{
@@ -210,6 +211,16 @@ enum OnFailure {
 }
 
 pub fn setup_meilisearch(opt: &Opt) -> anyhow::Result<(Arc<IndexScheduler>, Arc<AuthController>)> {
+    let mut indexer_config: IndexerConfig = (&opt.indexer_options).try_into()?;
+    let persistent_embedding_cache_size =
+        opt.experimental_persistent_embedding_cache_size.as_u64() as usize;
+    if persistent_embedding_cache_size != 0 {
+        indexer_config.embedding_cache = Some(Arc::new(PersistentEmbeddingCache::new(
+            opt.db_path.join("embedding_cache"),
+            persistent_embedding_cache_size,
+        )));
+    }
+    let max_parallel_batches = opt.experimental_max_parallel_batches.get();
     let index_scheduler_opt = IndexSchedulerOptions {
         version_file_path: opt.db_path.join(VERSION_FILE_NAME),
         auth_path: opt.db_path.join("auth"),
@@ -223,7 +234,7 @@ pub fn setup_meilisearch(opt: &Opt) -> anyhow::Result<(Arc<IndexScheduler>, Arc<
         task_db_size: opt.max_task_db_size.as_u64() as usize,
         index_base_map_size: opt.max_index_size.as_u64() as usize,
         enable_mdb_writemap: opt.experimental_reduce_indexing_memory_usage,
-        indexer_config: Arc::new((&opt.indexer_options).try_into()?),
+        indexer_config: Arc::new(indexer_config),
         autobatching_enabled: true,
         cleanup_enabled: !opt.experimental_replication_parameters,
         max_number_of_tasks: 1_000_000,
@@ -234,6 +245,10 @@ pub fn setup_meilisearch(opt: &Opt) -> anyhow::Result<(Arc<IndexScheduler>, Arc<
         instance_features: opt.to_instance_features(),
         auto_upgrade: opt.experimental_dumpless_upgrade,
         embedding_cache_cap: opt.experimental_embedding_cache_entries,
+        max_parallel_batches,
+        max_task_retries: opt.experimental_max_task_retries,
+        task_retry_delay: Duration::from_secs(opt.experimental_task_retry_delay_sec),
+        tasks_archive_path: opt.experimental_tasks_archive_dir.clone(),
     };
     let binary_version = (VERSION_MAJOR, VERSION_MINOR, VERSION_PATCH);
 
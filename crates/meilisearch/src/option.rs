@@ -65,6 +65,12 @@ const MEILI_EXPERIMENTAL_LIMIT_BATCHED_TASKS_TOTAL_SIZE: &str =
     "MEILI_EXPERIMENTAL_LIMIT_BATCHED_TASKS_SIZE";
 const MEILI_EXPERIMENTAL_EMBEDDING_CACHE_ENTRIES: &str =
     "MEILI_EXPERIMENTAL_EMBEDDING_CACHE_ENTRIES";
+const MEILI_EXPERIMENTAL_PERSISTENT_EMBEDDING_CACHE_SIZE: &str =
+    "MEILI_EXPERIMENTAL_PERSISTENT_EMBEDDING_CACHE_SIZE";
+const MEILI_EXPERIMENTAL_MAX_PARALLEL_BATCHES: &str = "MEILI_EXPERIMENTAL_MAX_PARALLEL_BATCHES";
+const MEILI_EXPERIMENTAL_MAX_TASK_RETRIES: &str = "MEILI_EXPERIMENTAL_MAX_TASK_RETRIES";
+const MEILI_EXPERIMENTAL_TASK_RETRY_DELAY_SEC: &str = "MEILI_EXPERIMENTAL_TASK_RETRY_DELAY_SEC";
+const MEILI_EXPERIMENTAL_TASKS_ARCHIVE_DIR: &str = "MEILI_EXPERIMENTAL_TASKS_ARCHIVE_DIR";
 const DEFAULT_CONFIG_FILE_PATH: &str = "./config.toml";
 const DEFAULT_DB_PATH: &str = "./data.ms";
 const DEFAULT_HTTP_ADDR: &str = "localhost:7700";
@@ -455,6 +461,41 @@ pub struct Opt {
     #[serde(default = "default_embedding_cache_entries")]
     pub experimental_embedding_cache_entries: usize,
 
+    /// Enables an experimental persistent cache of the embeddings computed when indexing documents.
+    /// The value represents the maximal size of the cache on disk, 0 disables the cache.
+    ///
+    /// Documents whose rendered text did not change are not sent again to the embedder when they are reindexed.
+    #[clap(long, env = MEILI_EXPERIMENTAL_PERSISTENT_EMBEDDING_CACHE_SIZE, default_value_t = default_persistent_embedding_cache_size())]
+    #[serde(default = "default_persistent_embedding_cache_size")]
+    pub experimental_persistent_embedding_cache_size: Byte,
+
+    /// Experimentally processes up to this number of batches in parallel, as long as they target
+    /// different indexes. The indexing memory is shared equally between the batches being processed.
+    #[clap(long, env = MEILI_EXPERIMENTAL_MAX_PARALLEL_BATCHES, default_value_t = default_max_parallel_batches())]
+    #[serde(default = "default_max_parallel_batches")]
+    pub experimental_max_parallel_batches: NonZeroUsize,
+
+    /// Experimentally enqueues again, up to this number of times, the tasks that failed because of
+    /// a transient error such as a network error or a rate limit of a remote embedder.
+    /// 0 disables the automatic retries.
+    #[clap(long, env = MEILI_EXPERIMENTAL_MAX_TASK_RETRIES, default_value_t)]
+    #[serde(default)]
+    pub experimental_max_task_retries: u32,
+
+    /// Experimental delay, in seconds, before the first automatic retry of a task.
+    /// The delay is doubled at each new attempt.
+    #[clap(long, env = MEILI_EXPERIMENTAL_TASK_RETRY_DELAY_SEC, default_value_t = default_task_retry_delay_sec())]
+    #[serde(default = "default_task_retry_delay_sec")]
+    pub experimental_task_retry_delay_sec: u64,
+
+    /// Experimentally archives the finished tasks and batches as gzip-compressed NDJSON files
+    /// in this directory before deleting them from the task queue, either on demand or
+    /// automatically when the task queue is full.
+    ///
+    /// The archived tasks can be read offline with the `meilitool export-archived-tasks` command.
+    #[clap(long, env = MEILI_EXPERIMENTAL_TASKS_ARCHIVE_DIR)]
+    pub experimental_tasks_archive_dir: Option<PathBuf>,
+
     #[serde(flatten)]
     #[clap(flatten)]
     pub indexer_options: IndexerOpts,
@@ -559,6 +600,11 @@ impl Opt {
             experimental_max_number_of_batched_tasks,
             experimental_limit_batched_tasks_total_size,
             experimental_embedding_cache_entries,
+            experimental_persistent_embedding_cache_size,
+            experimental_max_parallel_batches,
+            experimental_max_task_retries,
+            experimental_task_retry_delay_sec,
+            experimental_tasks_archive_dir,
         } = self;
         export_to_env_if_not_present(MEILI_DB_PATH, db_path);
         export_to_env_if_not_present(MEILI_HTTP_ADDR, http_addr);
@@ -655,6 +701,28 @@ impl Opt {
             MEILI_EXPERIMENTAL_EMBEDDING_CACHE_ENTRIES,
             experimental_embedding_cache_entries.to_string(),
         );
+        export_to_env_if_not_present(
+            MEILI_EXPERIMENTAL_PERSISTENT_EMBEDDING_CACHE_SIZE,
+            experimental_persistent_embedding_cache_size.to_string(),
+        );
+        export_to_env_if_not_present(
+            MEILI_EXPERIMENTAL_MAX_PARALLEL_BATCHES,
+            experimental_max_parallel_batches.to_string(),
+        );
+        export_to_env_if_not_present(
+            MEILI_EXPERIMENTAL_MAX_TASK_RETRIES,
+            experimental_max_task_retries.to_string(),
+        );
+        export_to_env_if_not_present(
+            MEILI_EXPERIMENTAL_TASK_RETRY_DELAY_SEC,
+            experimental_task_retry_delay_sec.to_string(),
+        );
+        if let Some(experimental_tasks_archive_dir) = experimental_tasks_archive_dir {
+            export_to_env_if_not_present(
+                MEILI_EXPERIMENTAL_TASKS_ARCHIVE_DIR,
+                experimental_tasks_archive_dir,
+            );
+        }
         indexer_options.export_to_env();
     }
 
@@ -765,7 +833,7 @@ impl TryFrom<&IndexerOpts> for IndexerConfig {
         Ok(Self {
             log_every_n: Some(DEFAULT_LOG_EVERY_N),
             max_memory: other.max_indexing_memory.map(|b| b.as_u64() as usize),
-            thread_pool: Some(thread_pool),
+            thread_pool: Some(Arc::new(thread_pool)),
             max_positions_per_attributes: None,
             skip_index_budget: other.skip_index_budget,
             ..Default::default()
@@ -976,6 +1044,18 @@ fn default_embedding_cache_entries() -> usize {
     0
 }
 
+fn default_persistent_embedding_cache_size() -> Byte {
+    Byte::from_u64(0)
+}
+
+fn default_max_parallel_batches() -> NonZeroUsize {
+    NonZeroUsize::MIN
+}
+
+fn default_task_retry_delay_sec() -> u64 {
+    10
+}
+
 fn default_snapshot_dir() -> PathBuf {
     PathBuf::from(DEFAULT_SNAPSHOT_DIR)
 }
@@ -11,6 +11,7 @@ use meilisearch_types::deserr::{DeserrJsonError, DeserrQueryParamError};
 use meilisearch_types::error::deserr_codes::*;
 use meilisearch_types::error::{Code, ResponseError};
 use meilisearch_types::keys::{CreateApiKey, Key, PatchApiKey};
+use meilisearch_types::tasks::TaskPriority;
 use serde::{Deserialize, Serialize};
 use time::OffsetDateTime;
 use utoipa::{IntoParams, OpenApi, ToSchema};
@@ -394,6 +395,9 @@ pub(super) struct KeyView {
     actions: Vec<Action>,
     /// The indexes accessible with this key.
     indexes: Vec<String>,
+    /// The priority given to the tasks registered with this key that don't specify one.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    task_priority: Option<TaskPriority>,
     /// The expiration date of the key. Once this timestamp is exceeded the key is not deleted but cannot be used anymore.
     #[serde(serialize_with = "time::serde::rfc3339::option::serialize")]
     expires_at: Option<OffsetDateTime>,
@@ -418,6 +422,7 @@ impl KeyView {
             uid: key.uid,
             actions: key.actions,
             indexes: key.indexes.into_iter().map(|x| x.to_string()).collect(),
+            task_priority: key.task_priority,
             expires_at: key.expires_at,
             created_at: key.created_at,
             updated_at: key.updated_at,
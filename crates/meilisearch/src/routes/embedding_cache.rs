@@ -0,0 +1,152 @@
+use actix_web::web::{self, Data};
+use actix_web::HttpResponse;
+use index_scheduler::IndexScheduler;
+use meilisearch_types::error::{Code, ResponseError};
+use meilisearch_types::keys::actions;
+use meilisearch_types::milli::vector::persistent_cache::{
+    PersistentEmbeddingCache, PersistentEmbeddingCacheStats,
+};
+use serde::Serialize;
+use tracing::debug;
+use utoipa::{OpenApi, ToSchema};
+
+use crate::error::MeilisearchHttpError;
+use crate::extractors::authentication::policies::ActionPolicy;
+use crate::extractors::authentication::GuardedData;
+use crate::extractors::sequential_extractor::SeqHandler;
+
+#[derive(OpenApi)]
+#[openapi(
+    paths(get_embedding_cache, delete_embedding_cache),
+    tags((
+        name = "Embedding cache",
+        description = "The `/embedding-cache` route gives information about the persistent cache of the embeddings computed when indexing documents, and allows purging it.
+
+The cache is enabled with the `--experimental-persistent-embedding-cache-size` option. This route is **synchronous**.",
+    )),
+)]
+pub struct EmbeddingCacheApi;
+
+pub fn configure(cfg: &mut web::ServiceConfig) {
+    cfg.service(
+        web::resource("")
+            .route(web::get().to(get_embedding_cache))
+            .route(web::delete().to(SeqHandler(delete_embedding_cache))),
+    );
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+#[schema(rename_all = "camelCase")]
+pub struct EmbeddingCacheStats {
+    /// Number of embeddings in the cache
+    pub entries: u64,
+    /// Size used by the cache on disk, in bytes
+    pub size_bytes: u64,
+    /// Number of texts retrieved from the cache instead of being embedded, since the start of the instance
+    pub hits: u64,
+    /// Number of texts that had to be embedded, since the start of the instance
+    pub misses: u64,
+}
+
+impl From<PersistentEmbeddingCacheStats> for EmbeddingCacheStats {
+    fn from(stats: PersistentEmbeddingCacheStats) -> Self {
+        let PersistentEmbeddingCacheStats { entries, size, hits, misses } = stats;
+        Self { entries, size_bytes: size, hits, misses }
+    }
+}
+
+/// Get the embedding cache stats
+///
+/// Get the number of embeddings in the persistent embedding cache, and how often it was used.
+#[utoipa::path(
+    get,
+    path = "",
+    tag = "Embedding cache",
+    security(("Bearer" = ["experimental.get", "*"])),
+    responses(
+        (status = OK, description = "The stats of the cache are returned", body = EmbeddingCacheStats, content_type = "application/json", example = json!(
+            {
+                "entries": 1024,
+                "sizeBytes": 4259840,
+                "hits": 896,
+                "misses": 128
+            }
+        )),
+        (status = 401, description = "The authorization header is missing", body = ResponseError, content_type = "application/json", example = json!(
+            {
+                "message": "The Authorization header is missing. It must use the bearer authorization method.",
+                "code": "missing_authorization_header",
+                "type": "auth",
+                "link": "https://docs.meilisearch.com/errors#missing_authorization_header"
+            }
+        )),
+    )
+)]
+async fn get_embedding_cache(
+    index_scheduler: GuardedData<
+        ActionPolicy<{ actions::EXPERIMENTAL_FEATURES_GET }>,
+        Data<IndexScheduler>,
+    >,
+) -> Result<HttpResponse, ResponseError> {
+    let cache = embedding_cache(&index_scheduler, "Getting the embedding cache stats")?;
+
+    let stats: EmbeddingCacheStats =
+        cache.stats().map_err(|err| MeilisearchHttpError::from_milli(err, None))?.into();
+    debug!(returns = ?stats, "Get embedding cache");
+    Ok(HttpResponse::Ok().json(stats))
+}
+
+/// Purge the embedding cache
+///
+/// Remove all the embeddings from the persistent embedding cache, returning the stats of the emptied cache.
+#[utoipa::path(
+    delete,
+    path = "",
+    tag = "Embedding cache",
+    security(("Bearer" = ["experimental.update", "*"])),
+    responses(
+        (status = OK, description = "The cache has been purged", body = EmbeddingCacheStats, content_type = "application/json", example = json!(
+            {
+                "entries": 0,
+                "sizeBytes": 16384,
+                "hits": 896,
+                "misses": 128
+            }
+        )),
+        (status = 401, description = "The authorization header is missing", body = ResponseError, content_type = "application/json", example = json!(
+            {
+                "message": "The Authorization header is missing. It must use the bearer authorization method.",
+                "code": "missing_authorization_header",
+                "type": "auth",
+                "link": "https://docs.meilisearch.com/errors#missing_authorization_header"
+            }
+        )),
+    )
+)]
+async fn delete_embedding_cache(
+    index_scheduler: GuardedData<
+        ActionPolicy<{ actions::EXPERIMENTAL_FEATURES_UPDATE }>,
+        Data<IndexScheduler>,
+    >,
+) -> Result<HttpResponse, ResponseError> {
+    let cache = embedding_cache(&index_scheduler, "Purging the embedding cache")?;
+
+    cache.purge().map_err(|err| MeilisearchHttpError::from_milli(err, None))?;
+    let stats: EmbeddingCacheStats =
+        cache.stats().map_err(|err| MeilisearchHttpError::from_milli(err, None))?.into();
+    debug!(returns = ?stats, "Delete embedding cache");
+    Ok(HttpResponse::Ok().json(stats))
+}
+
+fn embedding_cache<'a>(
+    index_scheduler: &'a IndexScheduler,
+    action: &str,
+) -> Result<&'a PersistentEmbeddingCache, ResponseError> {
+    index_scheduler.indexer_config().embedding_cache.as_deref().ok_or_else(|| {
+        ResponseError::from_msg(
+            format!("{action} requires enabling the persistent embedding cache with the `--experimental-persistent-embedding-cache-size` option"),
+            Code::FeatureNotEnabled,
+        )
+    })
+}
@@ -324,6 +324,7 @@ impl From<FacetSearchQuery> for SearchQuery {
             show_matches_position: false,
             show_ranking_score: false,
             show_ranking_score_details: false,
+            show_suggestions: false,
             filter,
             sort: None,
             distinct: None,
@@ -335,6 +336,8 @@ impl From<FacetSearchQuery> for SearchQuery {
             vector,
             attributes_to_search_on,
             hybrid,
+            vector_search: None,
+            query_syntax: Default::default(),
             ranking_score_threshold,
             locales,
         }
@@ -8,7 +8,7 @@ use serde_json::{json, Value};
 use crate::aggregate_methods;
 use crate::analytics::{Aggregate, AggregateMethod};
 use crate::search::{
-    SearchQuery, SearchResult, DEFAULT_CROP_LENGTH, DEFAULT_CROP_MARKER,
+    QuerySyntax, SearchQuery, SearchResult, DEFAULT_CROP_LENGTH, DEFAULT_CROP_MARKER,
     DEFAULT_HIGHLIGHT_POST_TAG, DEFAULT_HIGHLIGHT_PRE_TAG, DEFAULT_SEARCH_LIMIT,
     DEFAULT_SEMANTIC_RATIO,
 };
@@ -53,6 +53,8 @@ pub struct SearchAggregator<Method: AggregateMethod> {
     // q
     // The maximum number of terms in a q request
     max_terms_number: usize,
+    advanced_query_syntax: bool,
+    show_suggestions: bool,
 
     // vector
     // The maximum number of floats in a vector request
@@ -61,6 +63,8 @@ pub struct SearchAggregator<Method: AggregateMethod> {
     semantic_ratio: bool,
     hybrid: bool,
     retrieve_vectors: bool,
+    // Whether the vector search was tuned with `vectorSearch`.
+    vector_search: bool,
 
     // every time a search is done, we increment the counter linked to the used settings
     matching_strategy: HashMap<String, usize>,
@@ -100,6 +104,7 @@ impl<Method: AggregateMethod> SearchAggregator<Method> {
     pub fn from_query(query: &SearchQuery) -> Self {
         let SearchQuery {
             q,
+            query_syntax,
             vector,
             offset,
             limit,
@@ -113,6 +118,7 @@ impl<Method: AggregateMethod> SearchAggregator<Method> {
             show_matches_position,
             show_ranking_score,
             show_ranking_score_details,
+            show_suggestions,
             filter,
             sort,
             distinct,
@@ -123,6 +129,7 @@ impl<Method: AggregateMethod> SearchAggregator<Method> {
             matching_strategy,
             attributes_to_search_on,
             hybrid,
+            vector_search,
             ranking_score_threshold,
             locales,
         } = query;
@@ -171,6 +178,8 @@ impl<Method: AggregateMethod> SearchAggregator<Method> {
         if let Some(ref q) = q {
             ret.max_terms_number = q.split_whitespace().count();
         }
+        ret.advanced_query_syntax = *query_syntax == QuerySyntax::Advanced;
+        ret.show_suggestions = *show_suggestions;
 
         if let Some(ref vector) = vector {
             ret.max_vector_size = vector.len();
@@ -208,6 +217,7 @@ impl<Method: AggregateMethod> SearchAggregator<Method> {
             ret.semantic_ratio = hybrid.semantic_ratio != DEFAULT_SEMANTIC_RATIO();
             ret.hybrid = true;
         }
+        ret.vector_search = vector_search.is_some();
 
         ret
     }
@@ -219,6 +229,8 @@ impl<Method: AggregateMethod> SearchAggregator<Method> {
             processing_time_ms,
             hits_info: _,
             semantic_hit_count: _,
+            suggestions: _,
+            user_data: _,
             facet_distribution: _,
             facet_stats: _,
             degraded,
@@ -257,6 +269,8 @@ impl<Method: AggregateMethod> Aggregate for SearchAggregator<Method> {
             used_syntax,
             attributes_to_search_on_total_number_of_uses,
             max_terms_number,
+            advanced_query_syntax,
+            show_suggestions,
             max_vector_size,
             retrieve_vectors,
             matching_strategy,
@@ -277,6 +291,7 @@ impl<Method: AggregateMethod> Aggregate for SearchAggregator<Method> {
             show_ranking_score_details,
             semantic_ratio,
             hybrid,
+            vector_search,
             total_degraded,
             total_used_negative_operator,
             ranking_score_threshold,
@@ -321,12 +336,15 @@ impl<Method: AggregateMethod> Aggregate for SearchAggregator<Method> {
 
         // q
         self.max_terms_number = self.max_terms_number.max(max_terms_number);
+        self.advanced_query_syntax |= advanced_query_syntax;
+        self.show_suggestions |= show_suggestions;
 
         // vector
         self.max_vector_size = self.max_vector_size.max(max_vector_size);
         self.retrieve_vectors |= retrieve_vectors;
         self.semantic_ratio |= semantic_ratio;
         self.hybrid |= hybrid;
+        self.vector_search |= vector_search;
 
         // pagination
         self.max_limit = self.max_limit.max(max_limit);
@@ -383,6 +401,8 @@ impl<Method: AggregateMethod> Aggregate for SearchAggregator<Method> {
             used_syntax,
             attributes_to_search_on_total_number_of_uses,
             max_terms_number,
+            advanced_query_syntax,
+            show_suggestions,
             max_vector_size,
             retrieve_vectors,
             matching_strategy,
@@ -403,6 +423,7 @@ impl<Method: AggregateMethod> Aggregate for SearchAggregator<Method> {
             show_ranking_score_details,
             semantic_ratio,
             hybrid,
+            vector_search,
             total_degraded,
             total_used_negative_operator,
             ranking_score_threshold,
@@ -442,10 +463,13 @@ impl<Method: AggregateMethod> Aggregate for SearchAggregator<Method> {
             },
             "q": {
                "max_terms_number": max_terms_number,
+               "advanced_query_syntax": advanced_query_syntax,
+               "show_suggestions": show_suggestions,
             },
             "vector": {
                 "max_vector_size": max_vector_size,
                 "retrieve_vectors": retrieve_vectors,
+                "vector_search": vector_search,
             },
             "hybrid": {
                 "enabled": hybrid,
@@ -16,7 +16,7 @@ use super::settings_analytics::*;
 use crate::analytics::Analytics;
 use crate::extractors::authentication::policies::*;
 use crate::extractors::authentication::GuardedData;
-use crate::routes::{get_task_id, is_dry_run, SummarizedTaskView};
+use crate::routes::{get_task_id, get_task_options, is_dry_run, SummarizedTaskView};
 use crate::Opt;
 
 /// This macro generates the routes for the settings.
@@ -82,7 +82,7 @@ macro_rules! make_setting_route {
             use $crate::extractors::authentication::GuardedData;
             use $crate::extractors::sequential_extractor::SeqHandler;
             use $crate::Opt;
-            use $crate::routes::{is_dry_run, get_task_id, SummarizedTaskView};
+            use $crate::routes::{is_dry_run, get_task_id, get_task_options, SummarizedTaskView};
             #[allow(unused_imports)]
             use super::*;
 
@@ -140,10 +140,12 @@ macro_rules! make_setting_route {
                 };
                 let uid = get_task_id(&req, &opt)?;
                 let dry_run = is_dry_run(&req, &opt)?;
-                let task: SummarizedTaskView =
-                    tokio::task::spawn_blocking(move || index_scheduler.register(task, uid, dry_run))
-                        .await??
-                        .into();
+                let options = get_task_options(&req, index_scheduler.filters())?;
+                let task: SummarizedTaskView = tokio::task::spawn_blocking(move || {
+                    index_scheduler.register_with_options(task, uid, dry_run, options)
+                })
+                .await??
+                .into();
 
                 debug!(returns = ?task, "Delete settings");
                 Ok(HttpResponse::Accepted().json(task))
@@ -226,10 +228,12 @@ macro_rules! make_setting_route {
                 };
                 let uid = get_task_id(&req, &opt)?;
                 let dry_run = is_dry_run(&req, &opt)?;
-                let task: SummarizedTaskView =
-                    tokio::task::spawn_blocking(move || index_scheduler.register(task, uid, dry_run))
-                        .await??
-                        .into();
+                let options = get_task_options(&req, index_scheduler.filters())?;
+                let task: SummarizedTaskView = tokio::task::spawn_blocking(move || {
+                    index_scheduler.register_with_options(task, uid, dry_run, options)
+                })
+                .await??
+                .into();
 
                 debug!(returns = ?task, "Update settings");
                 Ok(HttpResponse::Accepted().json(task))
@@ -508,6 +512,61 @@ make_setting_routes!(
         camelcase_attr: "prefixSearch",
         analytics: PrefixSearchAnalytics
     },
+    {
+        route: "/stemming",
+        update_verb: put,
+        value_type: Vec<meilisearch_types::locales::Locale>,
+        err_type: meilisearch_types::deserr::DeserrJsonError<
+            meilisearch_types::error::deserr_codes::InvalidSettingsStemming,
+        >,
+        attr: stemming,
+        camelcase_attr: "stemming",
+        analytics: StemmingAnalytics
+    },
+    {
+        route: "/synonym-rules",
+        update_verb: put,
+        value_type: Vec<meilisearch_types::milli::synonym_rules::SynonymRule>,
+        err_type: meilisearch_types::deserr::DeserrJsonError<
+            meilisearch_types::error::deserr_codes::InvalidSettingsSynonymRules,
+        >,
+        attr: synonym_rules,
+        camelcase_attr: "synonymRules",
+        analytics: SynonymRulesAnalytics
+    },
+    {
+        route: "/searchable-attributes-weights",
+        update_verb: put,
+        value_type: std::collections::BTreeMap<String, u16>,
+        err_type: meilisearch_types::deserr::DeserrJsonError<
+            meilisearch_types::error::deserr_codes::InvalidSettingsSearchableAttributesWeights,
+        >,
+        attr: searchable_attributes_weights,
+        camelcase_attr: "searchableAttributesWeights",
+        analytics: SearchableAttributesWeightsAnalytics
+    },
+    {
+        route: "/ngram-attributes",
+        update_verb: put,
+        value_type: std::collections::BTreeMap<String, meilisearch_types::settings::NgramAttributeSettings>,
+        err_type: meilisearch_types::deserr::DeserrJsonError<
+            meilisearch_types::error::deserr_codes::InvalidSettingsNgramAttributes,
+        >,
+        attr: ngram_attributes,
+        camelcase_attr: "ngramAttributes",
+        analytics: NgramAttributesAnalytics
+    },
+    {
+        route: "/query-rules",
+        update_verb: put,
+        value_type: Vec<meilisearch_types::milli::query_rules::QueryRule>,
+        err_type: meilisearch_types::deserr::DeserrJsonError<
+            meilisearch_types::error::deserr_codes::InvalidSettingsQueryRules,
+        >,
+        attr: query_rules,
+        camelcase_attr: "queryRules",
+        analytics: QueryRulesAnalytics
+    },
 );
 
 #[utoipa::path(
@@ -597,6 +656,15 @@ pub async fn update_all(
             ),
             facet_search: FacetSearchAnalytics::new(new_settings.facet_search.as_ref().set()),
             prefix_search: PrefixSearchAnalytics::new(new_settings.prefix_search.as_ref().set()),
+            stemming: StemmingAnalytics::new(new_settings.stemming.as_ref().set()),
+            synonym_rules: SynonymRulesAnalytics::new(new_settings.synonym_rules.as_ref().set()),
+            searchable_attributes_weights: SearchableAttributesWeightsAnalytics::new(
+                new_settings.searchable_attributes_weights.as_ref().set(),
+            ),
+            ngram_attributes: NgramAttributesAnalytics::new(
+                new_settings.ngram_attributes.as_ref().set(),
+            ),
+            query_rules: QueryRulesAnalytics::new(new_settings.query_rules.as_ref().set()),
         },
         &req,
     );
@@ -611,10 +679,12 @@ pub async fn update_all(
     };
     let uid = get_task_id(&req, &opt)?;
     let dry_run = is_dry_run(&req, &opt)?;
-    let task: SummarizedTaskView =
-        tokio::task::spawn_blocking(move || index_scheduler.register(task, uid, dry_run))
-            .await??
-            .into();
+    let options = get_task_options(&req, index_scheduler.filters())?;
+    let task: SummarizedTaskView = tokio::task::spawn_blocking(move || {
+        index_scheduler.register_with_options(task, uid, dry_run, options)
+    })
+    .await??
+    .into();
 
     debug!(returns = ?task, "Update all settings");
     Ok(HttpResponse::Accepted().json(task))
@@ -705,10 +775,12 @@ pub async fn delete_all(
     };
     let uid = get_task_id(&req, &opt)?;
     let dry_run = is_dry_run(&req, &opt)?;
-    let task: SummarizedTaskView =
-        tokio::task::spawn_blocking(move || index_scheduler.register(task, uid, dry_run))
-            .await??
-            .into();
+    let options = get_task_options(&req, index_scheduler.filters())?;
+    let task: SummarizedTaskView = tokio::task::spawn_blocking(move || {
+        index_scheduler.register_with_options(task, uid, dry_run, options)
+    })
+    .await??
+    .into();
 
     debug!(returns = ?task, "Delete all settings");
     Ok(HttpResponse::Accepted().json(task))
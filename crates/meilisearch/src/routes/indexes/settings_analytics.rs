@@ -7,11 +7,14 @@ use std::collections::{BTreeMap, BTreeSet, HashSet};
 
 use meilisearch_types::facet_values_sort::FacetValuesSort;
 use meilisearch_types::locales::{Locale, LocalizedAttributesRuleView};
+use meilisearch_types::milli::query_rules::{QueryRule, QueryRuleMatch};
+use meilisearch_types::milli::synonym_rules::SynonymRule;
 use meilisearch_types::milli::update::Setting;
 use meilisearch_types::milli::FilterableAttributesRule;
 use meilisearch_types::settings::{
-    FacetingSettings, PaginationSettings, PrefixSearchSettings, ProximityPrecisionView,
-    RankingRuleView, SettingEmbeddingSettings, TypoSettings,
+    FacetingSettings, NgramAttributeSettings, NgramModeSettings, PaginationSettings,
+    PrefixSearchSettings, ProximityPrecisionView, RankingRuleView, SettingEmbeddingSettings,
+    TypoSettings,
 };
 use serde::Serialize;
 
@@ -39,6 +42,11 @@ pub struct SettingsAnalytics {
     pub non_separator_tokens: NonSeparatorTokensAnalytics,
     pub facet_search: FacetSearchAnalytics,
     pub prefix_search: PrefixSearchAnalytics,
+    pub stemming: StemmingAnalytics,
+    pub synonym_rules: SynonymRulesAnalytics,
+    pub searchable_attributes_weights: SearchableAttributesWeightsAnalytics,
+    pub ngram_attributes: NgramAttributesAnalytics,
+    pub query_rules: QueryRulesAnalytics,
 }
 
 impl Aggregate for SettingsAnalytics {
@@ -120,6 +128,10 @@ impl Aggregate for SettingsAnalytics {
                     .typo_tolerance
                     .min_word_size_for_two_typos
                     .or(self.typo_tolerance.min_word_size_for_two_typos),
+                per_attribute: new
+                    .typo_tolerance
+                    .per_attribute
+                    .or(self.typo_tolerance.per_attribute),
             },
             faceting: FacetingAnalytics {
                 max_values_per_facet: new
@@ -198,6 +210,27 @@ impl Aggregate for SettingsAnalytics {
                 set: new.prefix_search.set | self.prefix_search.set,
                 value: new.prefix_search.value.or(self.prefix_search.value),
             },
+            stemming: StemmingAnalytics { locales: new.stemming.locales.or(self.stemming.locales) },
+            synonym_rules: SynonymRulesAnalytics {
+                total: new.synonym_rules.total.or(self.synonym_rules.total),
+                one_way: new.synonym_rules.one_way.or(self.synonym_rules.one_way),
+                weighted: new.synonym_rules.weighted.or(self.synonym_rules.weighted),
+            },
+            searchable_attributes_weights: SearchableAttributesWeightsAnalytics {
+                total: new
+                    .searchable_attributes_weights
+                    .total
+                    .or(self.searchable_attributes_weights.total),
+            },
+            ngram_attributes: NgramAttributesAnalytics {
+                total: new.ngram_attributes.total.or(self.ngram_attributes.total),
+                edge_ngram: new.ngram_attributes.edge_ngram.or(self.ngram_attributes.edge_ngram),
+            },
+            query_rules: QueryRulesAnalytics {
+                total: new.query_rules.total.or(self.query_rules.total),
+                regex: new.query_rules.regex.or(self.query_rules.regex),
+                with_user_data: new.query_rules.with_user_data.or(self.query_rules.with_user_data),
+            },
         })
     }
 
@@ -392,6 +425,7 @@ pub struct TypoToleranceAnalytics {
     pub disable_on_words: Option<bool>,
     pub min_word_size_for_one_typo: Option<u8>,
     pub min_word_size_for_two_typos: Option<u8>,
+    pub per_attribute: Option<usize>,
 }
 
 impl TypoToleranceAnalytics {
@@ -412,6 +446,9 @@ impl TypoToleranceAnalytics {
                 .as_ref()
                 .and_then(|s| s.min_word_size_for_typos.as_ref().set().map(|s| s.two_typos.set()))
                 .flatten(),
+            per_attribute: setting
+                .as_ref()
+                .and_then(|s| s.per_attribute.as_ref().set().map(|rules| rules.len())),
         }
     }
     pub fn into_settings(self) -> SettingsAnalytics {
@@ -674,3 +711,99 @@ impl PrefixSearchAnalytics {
         SettingsAnalytics { prefix_search: self, ..Default::default() }
     }
 }
+
+#[derive(Serialize, Default)]
+pub struct StemmingAnalytics {
+    pub locales: Option<BTreeSet<Locale>>,
+}
+
+impl StemmingAnalytics {
+    pub fn new(settings: Option<&Vec<Locale>>) -> Self {
+        Self { locales: settings.map(|locales| locales.iter().copied().collect()) }
+    }
+
+    pub fn into_settings(self) -> SettingsAnalytics {
+        SettingsAnalytics { stemming: self, ..Default::default() }
+    }
+}
+
+#[derive(Serialize, Default)]
+pub struct SynonymRulesAnalytics {
+    pub total: Option<usize>,
+    pub one_way: Option<usize>,
+    pub weighted: Option<usize>,
+}
+
+impl SynonymRulesAnalytics {
+    pub fn new(rules: Option<&Vec<SynonymRule>>) -> Self {
+        Self {
+            total: rules.map(|rules| rules.len()),
+            one_way: rules.map(|rules| rules.iter().filter(|rule| rule.input.is_some()).count()),
+            weighted: rules
+                .map(|rules| rules.iter().filter(|rule| rule.weight.get() < 1.0).count()),
+        }
+    }
+
+    pub fn into_settings(self) -> SettingsAnalytics {
+        SettingsAnalytics { synonym_rules: self, ..Default::default() }
+    }
+}
+
+#[derive(Serialize, Default)]
+pub struct SearchableAttributesWeightsAnalytics {
+    pub total: Option<usize>,
+}
+
+impl SearchableAttributesWeightsAnalytics {
+    pub fn new(weights: Option<&BTreeMap<String, u16>>) -> Self {
+        Self { total: weights.map(|weights| weights.len()) }
+    }
+
+    pub fn into_settings(self) -> SettingsAnalytics {
+        SettingsAnalytics { searchable_attributes_weights: self, ..Default::default() }
+    }
+}
+
+#[derive(Serialize, Default)]
+pub struct NgramAttributesAnalytics {
+    pub total: Option<usize>,
+    pub edge_ngram: Option<bool>,
+}
+
+impl NgramAttributesAnalytics {
+    pub fn new(rules: Option<&BTreeMap<String, NgramAttributeSettings>>) -> Self {
+        Self {
+            total: rules.map(|rules| rules.len()),
+            edge_ngram: rules
+                .map(|rules| rules.values().any(|rule| rule.mode == NgramModeSettings::EdgeNgram)),
+        }
+    }
+
+    pub fn into_settings(self) -> SettingsAnalytics {
+        SettingsAnalytics { ngram_attributes: self, ..Default::default() }
+    }
+}
+
+#[derive(Serialize, Default)]
+pub struct QueryRulesAnalytics {
+    pub total: Option<usize>,
+    pub regex: Option<usize>,
+    pub with_user_data: Option<usize>,
+}
+
+impl QueryRulesAnalytics {
+    pub fn new(rules: Option<&Vec<QueryRule>>) -> Self {
+        Self {
+            total: rules.map(|rules| rules.len()),
+            regex: rules.map(|rules| {
+                rules.iter().filter(|rule| rule.match_kind == QueryRuleMatch::Regex).count()
+            }),
+            with_user_data: rules
+                .map(|rules| rules.iter().filter(|rule| rule.user_data.is_some()).count()),
+        }
+    }
+
+    pub fn into_settings(self) -> SettingsAnalytics {
+        SettingsAnalytics { query_rules: self, ..Default::default() }
+    }
+}
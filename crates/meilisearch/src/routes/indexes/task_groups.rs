@@ -0,0 +1,312 @@
+use std::io::Seek as _;
+
+use actix_web::web::Data;
+use actix_web::{web, HttpRequest, HttpResponse};
+use deserr::actix_web::AwebJson;
+use deserr::Deserr;
+use index_scheduler::IndexScheduler;
+use meilisearch_types::deserr::DeserrJsonError;
+use meilisearch_types::document_formats::read_json;
+use meilisearch_types::error::deserr_codes::*;
+use meilisearch_types::error::{Code, ResponseError};
+use meilisearch_types::index_uid::IndexUid;
+use meilisearch_types::milli::update::IndexDocumentsMethod;
+use meilisearch_types::tasks::KindWithContent;
+use serde::Serialize;
+use serde_json::Value;
+use tempfile::tempfile;
+use tracing::debug;
+use utoipa::{OpenApi, ToSchema};
+
+use crate::analytics::{Aggregate, Analytics};
+use crate::error::MeilisearchHttpError;
+use crate::extractors::authentication::policies::*;
+use crate::extractors::authentication::GuardedData;
+use crate::extractors::sequential_extractor::SeqHandler;
+use crate::routes::{get_task_options, is_dry_run, SummarizedTaskView};
+use crate::Opt;
+
+#[derive(OpenApi)]
+#[openapi(
+    paths(register_task_group),
+    tags(
+        (
+            name = "Task groups",
+            description = "The `/task-groups` route enqueues several document operations on an index that are applied atomically: they are processed in a single batch and either all succeed or all fail.",
+        ),
+    ),
+)]
+pub struct TaskGroupsApi;
+
+pub fn configure(cfg: &mut web::ServiceConfig) {
+    cfg.service(web::resource("").route(web::post().to(SeqHandler(register_task_group))));
+}
+
+#[derive(Debug, Deserr, ToSchema)]
+#[deserr(error = DeserrJsonError, rename_all = camelCase, deny_unknown_fields)]
+#[schema(rename_all = "camelCase")]
+pub struct TaskGroupPayload {
+    /// The operations of the group, applied in order.
+    pub operations: Vec<TaskGroupOperation>,
+}
+
+/// A single operation of a task group, exactly one of the operation fields must be specified.
+#[derive(Debug, Deserr, ToSchema)]
+#[deserr(error = DeserrJsonError, rename_all = camelCase, deny_unknown_fields)]
+#[schema(rename_all = "camelCase")]
+pub struct TaskGroupOperation {
+    /// Documents to add or replace.
+    #[deserr(default, error = DeserrJsonError<InvalidTaskGroupOperations>)]
+    pub add_documents: Option<Vec<Value>>,
+    /// Documents to add or update.
+    #[deserr(default, error = DeserrJsonError<InvalidTaskGroupOperations>)]
+    pub update_documents: Option<Vec<Value>>,
+    /// The primary key of the added or updated documents.
+    #[deserr(default, error = DeserrJsonError<InvalidTaskGroupOperations>)]
+    pub primary_key: Option<String>,
+    /// The ids of the documents to delete.
+    #[deserr(default, error = DeserrJsonError<InvalidTaskGroupOperations>)]
+    pub delete_documents: Option<Vec<Value>>,
+    /// The filter matching the documents to delete.
+    #[deserr(default, error = DeserrJsonError<InvalidTaskGroupOperations>)]
+    pub delete_documents_by_filter: Option<Value>,
+    /// Whether to delete all the documents of the index.
+    #[deserr(default, error = DeserrJsonError<InvalidTaskGroupOperations>)]
+    pub clear_documents: Option<bool>,
+}
+
+/// A task group operation once validated, the documents are written to an update file
+/// before the group is registered.
+enum Operation {
+    Documents { documents: Vec<Value>, method: IndexDocumentsMethod, primary_key: Option<String> },
+    Kind(KindWithContent),
+}
+
+#[derive(Serialize)]
+struct TaskGroupAnalytics {
+    max_operation_number: usize,
+}
+
+impl Aggregate for TaskGroupAnalytics {
+    fn event_name(&self) -> &'static str {
+        "Task Group Registered"
+    }
+
+    fn aggregate(self: Box<Self>, new: Box<Self>) -> Box<Self> {
+        Box::new(Self {
+            max_operation_number: self.max_operation_number.max(new.max_operation_number),
+        })
+    }
+
+    fn into_event(self: Box<Self>) -> serde_json::Value {
+        serde_json::to_value(*self).unwrap_or_default()
+    }
+}
+
+/// Register a task group
+///
+/// Enqueue several document operations on an index as a task group. The tasks of the group are
+/// processed in a single batch and their changes are committed in the same write transaction:
+/// if one of them fails, all of them fail and the index is left untouched.
+#[utoipa::path(
+    post,
+    path = "{indexUid}/task-groups",
+    tag = "Task groups",
+    security(("Bearer" = ["documents.*", "*"])),
+    params(("indexUid", example = "movies", description = "Index Unique Identifier", nullable = false)),
+    request_body = TaskGroupPayload,
+    responses(
+        (status = ACCEPTED, description = "Tasks successfully enqueued", body = Vec<SummarizedTaskView>, content_type = "application/json", example = json!(
+            [
+                {
+                    "taskUid": 147,
+                    "indexUid": "movies",
+                    "status": "enqueued",
+                    "type": "documentDeletion",
+                    "enqueuedAt": "2024-08-08T17:05:55.791772Z",
+                    "groupUid": 147
+                },
+                {
+                    "taskUid": 148,
+                    "indexUid": "movies",
+                    "status": "enqueued",
+                    "type": "documentAdditionOrUpdate",
+                    "enqueuedAt": "2024-08-08T17:05:55.791772Z",
+                    "groupUid": 147
+                }
+            ]
+        )),
+        (status = 401, description = "The authorization header is missing", body = ResponseError, content_type = "application/json", example = json!(
+            {
+                "message": "The Authorization header is missing. It must use the bearer authorization method.",
+                "code": "missing_authorization_header",
+                "type": "auth",
+                "link": "https://docs.meilisearch.com/errors#missing_authorization_header"
+            }
+        )),
+    )
+)]
+pub async fn register_task_group(
+    index_scheduler: GuardedData<ActionPolicy<{ actions::DOCUMENTS_ALL }>, Data<IndexScheduler>>,
+    index_uid: web::Path<String>,
+    body: AwebJson<TaskGroupPayload, DeserrJsonError>,
+    req: HttpRequest,
+    opt: web::Data<Opt>,
+    analytics: web::Data<Analytics>,
+) -> Result<HttpResponse, ResponseError> {
+    debug!(parameters = ?body, "Register task group");
+    let index_uid = IndexUid::try_from(index_uid.into_inner())?;
+    let index_uid = index_uid.into_inner();
+    let TaskGroupPayload { operations } = body.into_inner();
+
+    analytics.publish(TaskGroupAnalytics { max_operation_number: operations.len() }, &req);
+
+    if operations.is_empty() {
+        return Err(ResponseError::from_msg(
+            "A task group must contain at least one operation.".to_string(),
+            Code::InvalidTaskGroupOperations,
+        ));
+    }
+
+    let allow_index_creation = index_scheduler.filters().allow_index_creation(&index_uid);
+    let mut validated = Vec::with_capacity(operations.len());
+    for (i, operation) in operations.into_iter().enumerate() {
+        validated.push(validate_operation(&index_scheduler, &index_uid, i, operation)?);
+    }
+
+    let dry_run = is_dry_run(&req, &opt)?;
+    let options = get_task_options(&req, index_scheduler.filters())?;
+    let tasks = tokio::task::spawn_blocking(move || {
+        let mut content_files = Vec::new();
+        let result = (|| {
+            let mut kinds = Vec::with_capacity(validated.len());
+            for operation in validated {
+                let kind = match operation {
+                    Operation::Documents { documents, method, primary_key } => {
+                        let (uuid, mut update_file) =
+                            index_scheduler.queue.create_update_file(dry_run)?;
+                        if !dry_run {
+                            content_files.push(uuid);
+                        }
+                        let mut read_file = tempfile().map_err(index_scheduler::Error::IoError)?;
+                        serde_json::to_writer(&mut read_file, &documents)?;
+                        read_file.rewind().map_err(index_scheduler::Error::IoError)?;
+                        let documents_count = read_json(&read_file, &mut update_file)?;
+                        update_file.persist()?;
+                        KindWithContent::DocumentAdditionOrUpdate {
+                            index_uid: index_uid.clone(),
+                            primary_key,
+                            method,
+                            content_file: uuid,
+                            documents_count,
+                            allow_index_creation,
+                        }
+                    }
+                    Operation::Kind(kind) => kind,
+                };
+                kinds.push(kind);
+            }
+
+            Ok::<_, MeilisearchHttpError>(index_scheduler.register_group(kinds, dry_run, options)?)
+        })();
+
+        if result.is_err() {
+            for uuid in content_files {
+                if let Err(e) = index_scheduler.queue.delete_update_file(uuid) {
+                    tracing::warn!(
+                        index_uuid = %uuid,
+                        "Unknown error happened while deleting the update file of a task group: {e}"
+                    );
+                }
+            }
+        }
+        result
+    })
+    .await??;
+
+    let tasks: Vec<SummarizedTaskView> = tasks.into_iter().map(Into::into).collect();
+    debug!(returns = ?tasks, "Register task group");
+    Ok(HttpResponse::Accepted().json(tasks))
+}
+
+fn validate_operation(
+    index_scheduler: &IndexScheduler,
+    index_uid: &str,
+    position: usize,
+    operation: TaskGroupOperation,
+) -> Result<Operation, ResponseError> {
+    let TaskGroupOperation {
+        add_documents,
+        update_documents,
+        primary_key,
+        delete_documents,
+        delete_documents_by_filter,
+        clear_documents,
+    } = operation;
+
+    let invalid = |message: &str| {
+        ResponseError::from_msg(
+            format!("Invalid operation at position {position}: {message}"),
+            Code::InvalidTaskGroupOperations,
+        )
+    };
+
+    let specified = [
+        add_documents.is_some(),
+        update_documents.is_some(),
+        delete_documents.is_some(),
+        delete_documents_by_filter.is_some(),
+        clear_documents.is_some(),
+    ];
+    if specified.into_iter().filter(|specified| *specified).count() != 1 {
+        return Err(invalid(
+            "exactly one of `addDocuments`, `updateDocuments`, `deleteDocuments`, `deleteDocumentsByFilter` or `clearDocuments` must be specified.",
+        ));
+    }
+    if primary_key.is_some() && add_documents.is_none() && update_documents.is_none() {
+        return Err(invalid(
+            "`primaryKey` can only be specified with `addDocuments` or `updateDocuments`.",
+        ));
+    }
+
+    let index_uid = index_uid.to_string();
+    let operation = if let Some(documents) = add_documents {
+        Operation::Documents {
+            documents,
+            method: IndexDocumentsMethod::ReplaceDocuments,
+            primary_key,
+        }
+    } else if let Some(documents) = update_documents {
+        Operation::Documents {
+            documents,
+            method: IndexDocumentsMethod::UpdateDocuments,
+            primary_key,
+        }
+    } else if let Some(ids) = delete_documents {
+        let documents_ids =
+            ids.iter().map(|v| v.as_str().map(String::from).unwrap_or_else(|| v.to_string()));
+        Operation::Kind(KindWithContent::DocumentDeletion {
+            index_uid,
+            documents_ids: documents_ids.collect(),
+        })
+    } else if let Some(filter) = delete_documents_by_filter {
+        // we ensure the filter is well formed before enqueuing it
+        crate::search::parse_filter(
+            &filter,
+            Code::InvalidDocumentFilter,
+            index_scheduler.features(),
+        )?
+        .ok_or(MeilisearchHttpError::EmptyFilter)?;
+        Operation::Kind(KindWithContent::DocumentDeletionByFilter {
+            index_uid,
+            filter_expr: filter,
+        })
+    } else {
+        if clear_documents != Some(true) {
+            return Err(invalid("`clearDocuments` must be `true`."));
+        }
+        Operation::Kind(KindWithContent::DocumentClear { index_uid })
+    };
+
+    Ok(operation)
+}
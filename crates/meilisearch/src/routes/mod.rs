@@ -2,24 +2,29 @@ use std::collections::BTreeMap;
 
 use actix_web::web::Data;
 use actix_web::{web, HttpRequest, HttpResponse};
-use index_scheduler::IndexScheduler;
-use meilisearch_auth::AuthController;
+use index_scheduler::{IndexScheduler, TaskOptions};
+use meilisearch_auth::{AuthController, AuthFilter};
 use meilisearch_types::batch_view::BatchView;
 use meilisearch_types::batches::BatchStats;
 use meilisearch_types::error::{Code, ErrorType, ResponseError};
 use meilisearch_types::index_uid::IndexUid;
 use meilisearch_types::keys::CreateApiKey;
+use meilisearch_types::milli::query_rules::{QueryRule, QueryRuleMatch};
+use meilisearch_types::milli::synonym_rules::SynonymRule;
 use meilisearch_types::milli::{
     AttributePatterns, FilterFeatures, FilterableAttributesFeatures, FilterableAttributesPatterns,
     FilterableAttributesRule,
 };
 use meilisearch_types::settings::{
-    Checked, FacetingSettings, MinWordSizeTyposSetting, PaginationSettings, Settings, TypoSettings,
+    AttributeTypoSettings, Checked, FacetingSettings, MinWordSizeTyposSetting,
+    NgramAttributeSettings, NgramModeSettings, PaginationSettings, Settings, TypoSettings,
     Unchecked,
 };
 use meilisearch_types::task_view::{DetailsView, TaskView};
-use meilisearch_types::tasks::{Kind, Status, Task, TaskId};
+use meilisearch_types::tasks::{Kind, QueuePause, Status, Task, TaskId, TaskPriority};
+use meilisearch_types::webhooks::{DeliveryStatus, WebhookDelivery, WebhookFilter};
 use serde::{Deserialize, Serialize};
+use time::format_description::well_known::Rfc3339;
 use time::OffsetDateTime;
 use tracing::debug;
 use utoipa::{OpenApi, ToSchema};
@@ -36,13 +41,18 @@ use crate::milli::progress::{ProgressStepView, ProgressView};
 use crate::routes::batches::AllBatches;
 use crate::routes::features::RuntimeTogglableFeatures;
 use crate::routes::indexes::documents::{DocumentDeletionByFilter, DocumentEditionByFunction};
+use crate::routes::indexes::task_groups::{TaskGroupOperation, TaskGroupPayload};
 use crate::routes::indexes::IndexView;
 use crate::routes::multi_search::SearchResults;
 use crate::routes::network::{Network, Remote};
 use crate::routes::swap_indexes::SwapIndexesPayload;
+use crate::routes::webhooks::{
+    WebhookFilterSettings, WebhookResults, WebhookSettings, WebhookView,
+};
 use crate::search::{
-    FederatedSearch, FederatedSearchResult, Federation, FederationOptions, MergeFacets,
-    SearchQueryWithIndex, SearchResultWithIndex, SimilarQuery, SimilarResult,
+    AutocompleteHit, AutocompleteQuery, AutocompleteResult, FederatedSearch, FederatedSearchResult,
+    Federation, FederationOptions, MergeFacets, SearchQueryWithIndex, SearchResultWithIndex,
+    SimilarQuery, SimilarResult,
 };
 use crate::search_queue::SearchQueue;
 use crate::Opt;
@@ -53,6 +63,7 @@ const PAGINATION_DEFAULT_LIMIT_FN: fn() -> usize = || 20;
 mod api_key;
 pub mod batches;
 mod dump;
+mod embedding_cache;
 pub mod features;
 pub mod indexes;
 mod logs;
@@ -66,6 +77,7 @@ mod swap_indexes;
 pub mod tasks;
 #[cfg(test)]
 mod tasks_test;
+mod webhooks;
 
 #[derive(OpenApi)]
 #[openapi(
@@ -84,6 +96,8 @@ mod tasks_test;
         (path = "/swap-indexes", api = swap_indexes::SwapIndexesApi),
         (path = "/experimental-features", api = features::ExperimentalFeaturesApi),
         (path = "/network", api = network::NetworkApi),
+        (path = "/embedding-cache", api = embedding_cache::EmbeddingCacheApi),
+        (path = "/webhooks", api = webhooks::WebhooksApi),
     ),
     paths(get_health, get_version, get_stats),
     tags(
@@ -94,7 +108,7 @@ mod tasks_test;
         url = "/",
         description = "Local server",
     )),
-    components(schemas(PaginationView<KeyView>, PaginationView<IndexView>, IndexView, DocumentDeletionByFilter, AllBatches, BatchStats, ProgressStepView, ProgressView, BatchView, RuntimeTogglableFeatures, SwapIndexesPayload, DocumentEditionByFunction, MergeFacets, FederationOptions, SearchQueryWithIndex, Federation, FederatedSearch, FederatedSearchResult, SearchResults, SearchResultWithIndex, SimilarQuery, SimilarResult, PaginationView<serde_json::Value>, BrowseQuery, UpdateIndexRequest, IndexUid, IndexCreateRequest, KeyView, Action, CreateApiKey, UpdateStderrLogs, LogMode, GetLogs, IndexStats, Stats, HealthStatus, HealthResponse, VersionResponse, Code, ErrorType, AllTasks, TaskView, Status, DetailsView, ResponseError, Settings<Unchecked>, Settings<Checked>, TypoSettings, MinWordSizeTyposSetting, FacetingSettings, PaginationSettings, SummarizedTaskView, Kind, Network, Remote, FilterableAttributesRule, FilterableAttributesPatterns, AttributePatterns, FilterableAttributesFeatures, FilterFeatures))
+    components(schemas(PaginationView<KeyView>, PaginationView<IndexView>, IndexView, DocumentDeletionByFilter, AllBatches, BatchStats, ProgressStepView, ProgressView, BatchView, RuntimeTogglableFeatures, SwapIndexesPayload, DocumentEditionByFunction, TaskGroupPayload, TaskGroupOperation, MergeFacets, FederationOptions, SearchQueryWithIndex, Federation, FederatedSearch, FederatedSearchResult, SearchResults, SearchResultWithIndex, SimilarQuery, SimilarResult, AutocompleteQuery, AutocompleteResult, AutocompleteHit, PaginationView<serde_json::Value>, BrowseQuery, UpdateIndexRequest, IndexUid, IndexCreateRequest, KeyView, Action, CreateApiKey, UpdateStderrLogs, LogMode, GetLogs, IndexStats, Stats, QueuePause, HealthStatus, HealthResponse, QueueHealth, VersionResponse, Code, ErrorType, AllTasks, TaskView, Status, TaskPriority, DetailsView, ResponseError, Settings<Unchecked>, Settings<Checked>, TypoSettings, AttributeTypoSettings, MinWordSizeTyposSetting, NgramAttributeSettings, NgramModeSettings, FacetingSettings, PaginationSettings, SummarizedTaskView, Kind, Network, Remote, FilterableAttributesRule, FilterableAttributesPatterns, AttributePatterns, FilterableAttributesFeatures, FilterFeatures, SynonymRule, QueryRule, QueryRuleMatch, WebhookView, WebhookResults, WebhookSettings, WebhookFilterSettings, WebhookFilter, WebhookDelivery, DeliveryStatus, PaginationView<WebhookDelivery>))
 )]
 pub struct MeilisearchApi;
 
@@ -113,7 +127,9 @@ pub fn configure(cfg: &mut web::ServiceConfig) {
         .service(web::scope("/swap-indexes").configure(swap_indexes::configure))
         .service(web::scope("/metrics").configure(metrics::configure))
         .service(web::scope("/experimental-features").configure(features::configure))
-        .service(web::scope("/network").configure(network::configure));
+        .service(web::scope("/network").configure(network::configure))
+        .service(web::scope("/embedding-cache").configure(embedding_cache::configure))
+        .service(web::scope("/webhooks").configure(webhooks::configure));
 
     #[cfg(feature = "swagger")]
     {
@@ -173,6 +189,63 @@ pub fn is_dry_run(req: &HttpRequest, opt: &Opt) -> Result<bool, ResponseError> {
         .is_some_and(|s| s.to_lowercase() == "true"))
 }
 
+/// Returns the options of the task registered by the request.
+///
+/// The priority of the task is read from the `TaskPriority` header and defaults to the priority
+/// of the API key used by the request. The header cannot raise the priority above the one of
+/// the API key. The task is scheduled when the `RunAfter` header contains an RFC 3339 date.
+pub fn get_task_options(
+    req: &HttpRequest,
+    filters: &AuthFilter,
+) -> Result<TaskOptions, ResponseError> {
+    let priority = req
+        .headers()
+        .get("TaskPriority")
+        .map(|header| {
+            header.to_str().map_err(|e| {
+                ResponseError::from_msg(
+                    format!("TaskPriority is not a valid utf-8 string: {e}"),
+                    Code::BadRequest,
+                )
+            })
+        })
+        .transpose()?
+        .map(|s| {
+            s.parse::<TaskPriority>()
+                .map_err(|e| ResponseError::from_msg(e.to_string(), Code::BadRequest))
+        })
+        .transpose()?;
+    let priority = match (priority, filters.task_priority()) {
+        (Some(priority), Some(max_priority)) if priority > max_priority => {
+            return Err(ResponseError::from_msg(
+                format!(
+                    "The task priority cannot be `{priority}` as the API key used registers tasks with a `{max_priority}` priority at most."
+                ),
+                Code::BadRequest,
+            ))
+        }
+        (priority, max_priority) => priority.or(max_priority).unwrap_or_default(),
+    };
+    let run_after = req
+        .headers()
+        .get("RunAfter")
+        .map(|header| {
+            header.to_str().ok().and_then(|s| OffsetDateTime::parse(s, &Rfc3339).ok()).ok_or_else(
+                || {
+                    ResponseError::from_msg(
+                        format!(
+                            "RunAfter must be a valid RFC 3339 date, found `{}`.",
+                            String::from_utf8_lossy(header.as_bytes())
+                        ),
+                        Code::InvalidTaskRunAfter,
+                    )
+                },
+            )
+        })
+        .transpose()?;
+    Ok(TaskOptions { priority, run_after })
+}
+
 #[derive(Debug, Serialize, ToSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct SummarizedTaskView {
@@ -189,6 +262,15 @@ pub struct SummarizedTaskView {
     /// The date on which the task was enqueued.
     #[serde(serialize_with = "time::serde::rfc3339::serialize")]
     enqueued_at: OffsetDateTime,
+    /// The date before which the task is not processed, only present for scheduled tasks.
+    #[serde(
+        serialize_with = "time::serde::rfc3339::option::serialize",
+        skip_serializing_if = "Option::is_none"
+    )]
+    run_after: Option<OffsetDateTime>,
+    /// The uid of the first task of the task group, only present for grouped tasks.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    group_uid: Option<TaskId>,
 }
 
 impl From<Task> for SummarizedTaskView {
@@ -199,6 +281,8 @@ impl From<Task> for SummarizedTaskView {
             status: task.status,
             kind: task.kind.as_kind(),
             enqueued_at: task.enqueued_at,
+            run_after: task.run_after,
+            group_uid: task.group_uid,
         }
     }
 }
@@ -379,6 +463,8 @@ pub struct Stats {
     /// The stats of every individual index your API key lets you access.
     #[schema(value_type = HashMap<String, indexes::IndexStats>)]
     pub indexes: BTreeMap<String, indexes::IndexStats>,
+    /// Whether the task queue is paused, only the paused indexes your API key lets you access are listed.
+    pub queue: QueuePause,
 }
 
 /// Get stats of all indexes.
@@ -407,6 +493,10 @@ pub struct Stats {
                             "author": 9
                         }
                     }
+                },
+                "queue": {
+                    "paused": false,
+                    "pausedIndexes": ["movies"]
                 }
             }
         )),
@@ -465,7 +555,10 @@ pub fn create_all_stats(
     database_size += auth_controller.size()?;
     used_database_size += auth_controller.used_size()?;
 
-    let stats = Stats { database_size, used_database_size, last_update: last_task, indexes };
+    let mut queue = index_scheduler.queue_pause();
+    queue.paused_indexes.retain(|index| filters.is_index_authorized(index));
+
+    let stats = Stats { database_size, used_database_size, last_update: last_task, indexes, queue };
     Ok(stats)
 }
 
@@ -530,6 +623,17 @@ async fn get_version(
 struct HealthResponse {
     /// The status of the instance.
     status: HealthStatus,
+    /// Whether the task queue is paused.
+    queue: QueueHealth,
+}
+
+#[derive(Default, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+struct QueueHealth {
+    /// No new batch is started while the whole queue is paused.
+    paused: bool,
+    /// The number of indexes whose tasks are not processed, their names are only listed in the `/stats` route.
+    number_of_paused_indexes: usize,
 }
 
 #[derive(Default, Serialize, ToSchema)]
@@ -549,7 +653,11 @@ enum HealthStatus {
     responses(
         (status = 200, description = "Instance is healthy", body = HealthResponse, content_type = "application/json", example = json!(
             {
-                "status": "available"
+                "status": "available",
+                "queue": {
+                    "paused": false,
+                    "numberOfPausedIndexes": 0
+                }
             }
         )),
     )
@@ -563,5 +671,8 @@ pub async fn get_health(
     index_scheduler.health().unwrap();
     auth_controller.health().unwrap();
 
-    Ok(HttpResponse::Ok().json(HealthResponse::default()))
+    let pause = index_scheduler.queue_pause();
+    let queue =
+        QueueHealth { paused: pause.paused, number_of_paused_indexes: pause.paused_indexes.len() };
+    Ok(HttpResponse::Ok().json(HealthResponse { queue, ..Default::default() }))
 }
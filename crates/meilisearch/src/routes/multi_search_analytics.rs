@@ -54,6 +54,7 @@ impl MultiSearchAggregator {
             show_ranking_score: _,
             show_ranking_score_details: _,
             show_matches_position: _,
+            show_suggestions: _,
             filter: _,
             sort: _,
             distinct: _,
@@ -64,6 +65,8 @@ impl MultiSearchAggregator {
             matching_strategy: _,
             attributes_to_search_on: _,
             hybrid: _,
+            vector_search: _,
+            query_syntax: _,
             ranking_score_threshold: _,
             locales: _,
         } in &federated_search.queries
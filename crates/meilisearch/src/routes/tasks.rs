@@ -1,37 +1,45 @@
+use std::collections::HashSet;
 use std::io::ErrorKind;
 
-use actix_web::web::Data;
+use actix_web::http::header;
+use actix_web::web::{Bytes, Data};
 use actix_web::{web, HttpRequest, HttpResponse};
 use deserr::actix_web::AwebQueryParameter;
 use deserr::Deserr;
 use index_scheduler::{IndexScheduler, Query, TaskId};
+use meilisearch_auth::{AuthController, AuthFilter};
 use meilisearch_types::batches::BatchId;
 use meilisearch_types::deserr::query_params::Param;
 use meilisearch_types::deserr::DeserrQueryParamError;
 use meilisearch_types::error::deserr_codes::*;
 use meilisearch_types::error::{InvalidTaskDateError, ResponseError};
 use meilisearch_types::index_uid::IndexUid;
+use meilisearch_types::milli::progress::ProgressView;
 use meilisearch_types::star_or::{OptionStarOr, OptionStarOrList};
 use meilisearch_types::task_view::TaskView;
-use meilisearch_types::tasks::{Kind, KindWithContent, Status};
+use meilisearch_types::tasks::{Kind, KindWithContent, QueuePause, Status, Task};
 use serde::Serialize;
 use time::format_description::well_known::Rfc3339;
 use time::macros::format_description;
 use time::{Date, Duration, OffsetDateTime, Time};
 use tokio::io::AsyncReadExt;
+use tokio::sync::broadcast;
+use tokio::sync::broadcast::error::RecvError;
 use tokio::task;
 use utoipa::{IntoParams, OpenApi, ToSchema};
 
-use super::{get_task_id, is_dry_run, SummarizedTaskView, PAGINATION_DEFAULT_LIMIT};
+use super::{
+    get_task_id, get_task_options, is_dry_run, SummarizedTaskView, PAGINATION_DEFAULT_LIMIT,
+};
 use crate::analytics::{Aggregate, AggregateMethod, Analytics};
 use crate::extractors::authentication::policies::*;
-use crate::extractors::authentication::GuardedData;
+use crate::extractors::authentication::{AuthenticationError, GuardedData, Policy};
 use crate::extractors::sequential_extractor::SeqHandler;
 use crate::{aggregate_methods, Opt};
 
 #[derive(OpenApi)]
 #[openapi(
-    paths(get_tasks, delete_tasks, cancel_tasks, get_task),
+    paths(get_tasks, delete_tasks, cancel_tasks, retry_tasks, pause_tasks, resume_tasks, get_task_events, get_task),
     tags((
         name = "Tasks",
         description = "The tasks route gives information about the progress of the [asynchronous operations](https://docs.meilisearch.com/learn/advanced/asynchronous_operations.html).",
@@ -47,6 +55,10 @@ pub fn configure(cfg: &mut web::ServiceConfig) {
             .route(web::delete().to(SeqHandler(delete_tasks))),
     )
     .service(web::resource("/cancel").route(web::post().to(SeqHandler(cancel_tasks))))
+    .service(web::resource("/retry").route(web::post().to(SeqHandler(retry_tasks))))
+    .service(web::resource("/pause").route(web::post().to(SeqHandler(pause_tasks))))
+    .service(web::resource("/resume").route(web::post().to(SeqHandler(resume_tasks))))
+    .service(web::resource("/events").route(web::get().to(SeqHandler(get_task_events))))
     .service(web::resource("/{task_id}").route(web::get().to(SeqHandler(get_task))))
     .service(
         web::resource("/{task_id}/documents")
@@ -121,6 +133,14 @@ pub struct TasksFilterQuery {
     #[deserr(default, error = DeserrQueryParamError<InvalidTaskBeforeFinishedAt>, try_from(OptionStarOr<String>) = deserialize_date_before -> InvalidTaskDateError)]
     #[param(required = false, value_type = Option<String>, example = json!(["2024-08-08T16:37:09.971Z", "*"]))]
     pub before_finished_at: OptionStarOr<OffsetDateTime>,
+    /// Permits to filter the scheduled tasks based on their runAfter time. Matches tasks scheduled after the given date. Supports RFC 3339 date format.
+    #[deserr(default, error = DeserrQueryParamError<InvalidTaskAfterRunAfter>, try_from(OptionStarOr<String>) = deserialize_date_after -> InvalidTaskDateError)]
+    #[param(required = false, value_type = Option<String>, example = json!(["2024-08-08T16:37:09.971Z", "*"]))]
+    pub after_run_after: OptionStarOr<OffsetDateTime>,
+    /// Permits to filter the scheduled tasks based on their runAfter time. Matches tasks scheduled before the given date. Supports RFC 3339 date format.
+    #[deserr(default, error = DeserrQueryParamError<InvalidTaskBeforeRunAfter>, try_from(OptionStarOr<String>) = deserialize_date_before -> InvalidTaskDateError)]
+    #[param(required = false, value_type = Option<String>, example = json!(["2024-08-08T16:37:09.971Z", "*"]))]
+    pub before_run_after: OptionStarOr<OffsetDateTime>,
 }
 
 impl TasksFilterQuery {
@@ -141,6 +161,8 @@ impl TasksFilterQuery {
             after_started_at: self.after_started_at.merge_star_and_none(),
             before_finished_at: self.before_finished_at.merge_star_and_none(),
             after_finished_at: self.after_finished_at.merge_star_and_none(),
+            before_run_after: self.before_run_after.merge_star_and_none(),
+            after_run_after: self.after_run_after.merge_star_and_none(),
         }
     }
 }
@@ -161,7 +183,9 @@ impl TaskDeletionOrCancelationQuery {
                 after_started_at: OptionStarOr::None,
                 before_started_at: OptionStarOr::None,
                 after_finished_at: OptionStarOr::None,
-                before_finished_at: OptionStarOr::None
+                before_finished_at: OptionStarOr::None,
+                after_run_after: OptionStarOr::None,
+                before_run_after: OptionStarOr::None
             }
         )
     }
@@ -220,6 +244,14 @@ pub struct TaskDeletionOrCancelationQuery {
     #[deserr(default, error = DeserrQueryParamError<InvalidTaskBeforeFinishedAt>, try_from(OptionStarOr<String>) = deserialize_date_before -> InvalidTaskDateError)]
     #[param(required = false, value_type = Option<String>, example = json!(["2024-08-08T16:37:09.971Z", "*"]))]
     pub before_finished_at: OptionStarOr<OffsetDateTime>,
+    /// Permits to filter the scheduled tasks based on their runAfter time. Matches tasks scheduled after the given date. Supports RFC 3339 date format.
+    #[deserr(default, error = DeserrQueryParamError<InvalidTaskAfterRunAfter>, try_from(OptionStarOr<String>) = deserialize_date_after -> InvalidTaskDateError)]
+    #[param(required = false, value_type = Option<String>, example = json!(["2024-08-08T16:37:09.971Z", "*"]))]
+    pub after_run_after: OptionStarOr<OffsetDateTime>,
+    /// Permits to filter the scheduled tasks based on their runAfter time. Matches tasks scheduled before the given date. Supports RFC 3339 date format.
+    #[deserr(default, error = DeserrQueryParamError<InvalidTaskBeforeRunAfter>, try_from(OptionStarOr<String>) = deserialize_date_before -> InvalidTaskDateError)]
+    #[param(required = false, value_type = Option<String>, example = json!(["2024-08-08T16:37:09.971Z", "*"]))]
+    pub before_run_after: OptionStarOr<OffsetDateTime>,
 }
 
 impl TaskDeletionOrCancelationQuery {
@@ -240,6 +272,8 @@ impl TaskDeletionOrCancelationQuery {
             after_started_at: self.after_started_at.merge_star_and_none(),
             before_finished_at: self.before_finished_at.merge_star_and_none(),
             after_finished_at: self.after_finished_at.merge_star_and_none(),
+            before_run_after: self.before_run_after.merge_star_and_none(),
+            after_run_after: self.after_run_after.merge_star_and_none(),
         }
     }
 }
@@ -247,6 +281,7 @@ impl TaskDeletionOrCancelationQuery {
 aggregate_methods!(
     CancelTasks => "Tasks Canceled",
     DeleteTasks => "Tasks Deleted",
+    RetryTasks => "Tasks Retried",
 );
 
 #[derive(Serialize)]
@@ -262,6 +297,8 @@ struct TaskFilterAnalytics<Method: AggregateMethod> {
     filtered_by_after_started_at: bool,
     filtered_by_before_finished_at: bool,
     filtered_by_after_finished_at: bool,
+    filtered_by_before_run_after: bool,
+    filtered_by_after_run_after: bool,
 
     #[serde(skip)]
     marker: std::marker::PhantomData<Method>,
@@ -291,6 +328,10 @@ impl<Method: AggregateMethod + 'static> Aggregate for TaskFilterAnalytics<Method
                 | new.filtered_by_before_finished_at,
             filtered_by_after_finished_at: self.filtered_by_after_finished_at
                 | new.filtered_by_after_finished_at,
+            filtered_by_before_run_after: self.filtered_by_before_run_after
+                | new.filtered_by_before_run_after,
+            filtered_by_after_run_after: self.filtered_by_after_run_after
+                | new.filtered_by_after_run_after,
 
             marker: std::marker::PhantomData,
         })
@@ -372,6 +413,8 @@ async fn cancel_tasks(
             filtered_by_after_started_at: params.after_started_at.is_some(),
             filtered_by_before_finished_at: params.before_finished_at.is_some(),
             filtered_by_after_finished_at: params.after_finished_at.is_some(),
+            filtered_by_before_run_after: params.before_run_after.is_some(),
+            filtered_by_after_run_after: params.after_run_after.is_some(),
 
             marker: std::marker::PhantomData,
         },
@@ -387,14 +430,206 @@ async fn cancel_tasks(
 
     let uid = get_task_id(&req, &opt)?;
     let dry_run = is_dry_run(&req, &opt)?;
-    let task =
-        task::spawn_blocking(move || index_scheduler.register(task_cancelation, uid, dry_run))
-            .await??;
+    let options = get_task_options(&req, index_scheduler.filters())?;
+    let task = task::spawn_blocking(move || {
+        index_scheduler.register_with_options(task_cancelation, uid, dry_run, options)
+    })
+    .await??;
     let task: SummarizedTaskView = task.into();
 
     Ok(HttpResponse::Ok().json(task))
 }
 
+/// Retry tasks
+///
+/// Enqueue again the failed [tasks](https://www.meilisearch.com/docs/learn/async/asynchronous_operations) matching the filters as new tasks.
+/// The document additions can only be retried when they failed because of a transient error, such as an outage of a remote embedder.
+#[utoipa::path(
+    post,
+    path = "/retry",
+    tag = "Tasks",
+    security(("Bearer" = ["tasks.retry", "tasks.*", "*"])),
+    params(TaskDeletionOrCancelationQuery),
+    responses(
+        (status = 202, description = "Tasks successfully enqueued", body = Vec<SummarizedTaskView>, content_type = "application/json", example = json!(
+            [
+                {
+                    "taskUid": 148,
+                    "indexUid": "movies",
+                    "status": "enqueued",
+                    "type": "documentAdditionOrUpdate",
+                    "enqueuedAt": "2024-08-08T17:05:55.791772Z"
+                }
+            ]
+        )),
+        (status = 400, description = "A filter is missing", body = ResponseError, content_type = "application/json", example = json!(
+            {
+                "message": "Query parameters to filter the tasks to retry are missing. Available query parameters are: `uids`, `indexUids`, `statuses`, `types`, `canceledBy`, `beforeEnqueuedAt`, `afterEnqueuedAt`, `beforeStartedAt`, `afterStartedAt`, `beforeFinishedAt`, `afterFinishedAt`.",
+                "code": "missing_task_filters",
+                "type": "invalid_request",
+                "link": "https://docs.meilisearch.com/errors#missing_task_filters"
+            }
+        )),
+        (status = 401, description = "The authorization header is missing", body = ResponseError, content_type = "application/json", example = json!(
+            {
+                "message": "The Authorization header is missing. It must use the bearer authorization method.",
+                "code": "missing_authorization_header",
+                "type": "auth",
+                "link": "https://docs.meilisearch.com/errors#missing_authorization_header"
+            }
+        )),
+    )
+)]
+async fn retry_tasks(
+    index_scheduler: GuardedData<ActionPolicy<{ actions::TASKS_RETRY }>, Data<IndexScheduler>>,
+    params: AwebQueryParameter<TaskDeletionOrCancelationQuery, DeserrQueryParamError>,
+    req: HttpRequest,
+    opt: web::Data<Opt>,
+    analytics: web::Data<Analytics>,
+) -> Result<HttpResponse, ResponseError> {
+    let params = params.into_inner();
+
+    if params.is_empty() {
+        return Err(index_scheduler::Error::TaskRetryWithEmptyQuery.into());
+    }
+
+    analytics.publish(
+        TaskFilterAnalytics::<RetryTasks> {
+            filtered_by_uid: params.uids.is_some(),
+            filtered_by_index_uid: params.index_uids.is_some(),
+            filtered_by_type: params.types.is_some(),
+            filtered_by_status: params.statuses.is_some(),
+            filtered_by_canceled_by: params.canceled_by.is_some(),
+            filtered_by_before_enqueued_at: params.before_enqueued_at.is_some(),
+            filtered_by_after_enqueued_at: params.after_enqueued_at.is_some(),
+            filtered_by_before_started_at: params.before_started_at.is_some(),
+            filtered_by_after_started_at: params.after_started_at.is_some(),
+            filtered_by_before_finished_at: params.before_finished_at.is_some(),
+            filtered_by_after_finished_at: params.after_finished_at.is_some(),
+            filtered_by_before_run_after: params.before_run_after.is_some(),
+            filtered_by_after_run_after: params.after_run_after.is_some(),
+
+            marker: std::marker::PhantomData,
+        },
+        &req,
+    );
+
+    let query = params.into_query();
+
+    let (tasks, _) =
+        index_scheduler.get_task_ids_from_authorized_indexes(&query, index_scheduler.filters())?;
+
+    let dry_run = is_dry_run(&req, &opt)?;
+    let tasks =
+        task::spawn_blocking(move || index_scheduler.retry_tasks(&tasks, dry_run)).await??;
+    let tasks: Vec<SummarizedTaskView> = tasks.into_iter().map(Into::into).collect();
+
+    Ok(HttpResponse::Accepted().json(tasks))
+}
+
+#[derive(Debug, Deserr, IntoParams)]
+#[deserr(error = DeserrQueryParamError, rename_all = camelCase, deny_unknown_fields)]
+#[into_params(rename_all = "camelCase", parameter_in = Query)]
+pub struct QueuePauseQuery {
+    /// The indexes to pause or resume. By default, when `indexUids` query parameter is not set or is `*`, the whole queue is paused or resumed. It is possible to specify several indexes by separating them with the `,` character.
+    #[deserr(default, error = DeserrQueryParamError<InvalidIndexUid>)]
+    #[param(required = false, value_type = Option<Vec<String>>, example = json!(["movies", "theater"]))]
+    pub index_uids: OptionStarOrList<IndexUid>,
+}
+
+impl QueuePauseQuery {
+    /// Returns the indexes to pause or resume, `None` targets the whole queue.
+    ///
+    /// The key must be authorized on all the indexes to pause or resume the whole queue.
+    fn authorized_indexes(
+        self,
+        filters: &AuthFilter,
+    ) -> Result<Option<Vec<String>>, ResponseError> {
+        let indexes = self.index_uids.map(|index_uid| index_uid.to_string()).merge_star_and_none();
+        let authorized = match &indexes {
+            Some(indexes) => indexes.iter().all(|index| filters.is_index_authorized(index)),
+            None => filters.all_indexes_authorized(),
+        };
+        if !authorized {
+            return Err(AuthenticationError::InvalidToken.into());
+        }
+        Ok(indexes)
+    }
+}
+
+/// Pause tasks
+///
+/// Stop processing the enqueued [tasks](https://www.meilisearch.com/docs/learn/async/asynchronous_operations), globally or for some indexes only.
+/// The tasks are still enqueued while the queue is paused, and the batch being processed is not interrupted. The task cancelations are still processed.
+/// The queue stays paused after a restart, until it is resumed.
+#[utoipa::path(
+    post,
+    path = "/pause",
+    tag = "Tasks",
+    security(("Bearer" = ["tasks.pause", "tasks.*", "*"])),
+    params(QueuePauseQuery),
+    responses(
+        (status = 200, description = "The queue is paused", body = QueuePause, content_type = "application/json", example = json!(
+            {
+                "paused": false,
+                "pausedIndexes": ["movies"]
+            }
+        )),
+        (status = 401, description = "The authorization header is missing", body = ResponseError, content_type = "application/json", example = json!(
+            {
+                "message": "The Authorization header is missing. It must use the bearer authorization method.",
+                "code": "missing_authorization_header",
+                "type": "auth",
+                "link": "https://docs.meilisearch.com/errors#missing_authorization_header"
+            }
+        )),
+    )
+)]
+async fn pause_tasks(
+    index_scheduler: GuardedData<ActionPolicy<{ actions::TASKS_PAUSE }>, Data<IndexScheduler>>,
+    params: AwebQueryParameter<QueuePauseQuery, DeserrQueryParamError>,
+) -> Result<HttpResponse, ResponseError> {
+    let indexes = params.into_inner().authorized_indexes(index_scheduler.filters())?;
+    let pause = task::spawn_blocking(move || index_scheduler.pause_queue(indexes)).await??;
+    Ok(HttpResponse::Ok().json(pause))
+}
+
+/// Resume tasks
+///
+/// Resume the processing of the [tasks](https://www.meilisearch.com/docs/learn/async/asynchronous_operations) paused with the `/tasks/pause` route.
+/// Resuming the whole queue also resumes all the paused indexes.
+#[utoipa::path(
+    post,
+    path = "/resume",
+    tag = "Tasks",
+    security(("Bearer" = ["tasks.resume", "tasks.*", "*"])),
+    params(QueuePauseQuery),
+    responses(
+        (status = 200, description = "The queue is resumed", body = QueuePause, content_type = "application/json", example = json!(
+            {
+                "paused": false,
+                "pausedIndexes": []
+            }
+        )),
+        (status = 401, description = "The authorization header is missing", body = ResponseError, content_type = "application/json", example = json!(
+            {
+                "message": "The Authorization header is missing. It must use the bearer authorization method.",
+                "code": "missing_authorization_header",
+                "type": "auth",
+                "link": "https://docs.meilisearch.com/errors#missing_authorization_header"
+            }
+        )),
+    )
+)]
+async fn resume_tasks(
+    index_scheduler: GuardedData<ActionPolicy<{ actions::TASKS_RESUME }>, Data<IndexScheduler>>,
+    params: AwebQueryParameter<QueuePauseQuery, DeserrQueryParamError>,
+) -> Result<HttpResponse, ResponseError> {
+    let indexes = params.into_inner().authorized_indexes(index_scheduler.filters())?;
+    let pause = task::spawn_blocking(move || index_scheduler.resume_queue(indexes)).await??;
+    Ok(HttpResponse::Ok().json(pause))
+}
+
 /// Delete tasks
 ///
 /// Delete [tasks](https://docs.meilisearch.com/learn/advanced/asynchronous_operations.html) on filter
@@ -466,6 +701,8 @@ async fn delete_tasks(
             filtered_by_after_started_at: params.after_started_at.is_some(),
             filtered_by_before_finished_at: params.before_finished_at.is_some(),
             filtered_by_after_finished_at: params.after_finished_at.is_some(),
+            filtered_by_before_run_after: params.before_run_after.is_some(),
+            filtered_by_after_run_after: params.after_run_after.is_some(),
 
             marker: std::marker::PhantomData,
         },
@@ -481,8 +718,11 @@ async fn delete_tasks(
 
     let uid = get_task_id(&req, &opt)?;
     let dry_run = is_dry_run(&req, &opt)?;
-    let task = task::spawn_blocking(move || index_scheduler.register(task_deletion, uid, dry_run))
-        .await??;
+    let options = get_task_options(&req, index_scheduler.filters())?;
+    let task = task::spawn_blocking(move || {
+        index_scheduler.register_with_options(task_deletion, uid, dry_run, options)
+    })
+    .await??;
     let task: SummarizedTaskView = task.into();
 
     Ok(HttpResponse::Ok().json(task))
@@ -716,6 +956,242 @@ async fn get_task_documents_file(
     }
 }
 
+#[derive(Debug, Deserr, IntoParams)]
+#[deserr(error = DeserrQueryParamError, rename_all = camelCase, deny_unknown_fields)]
+#[into_params(rename_all = "camelCase", parameter_in = Query)]
+pub struct TaskEventsQuery {
+    /// Permits to filter the events by the type of the tasks. By default, when `types` query parameter is not set, the events of all the task types are sent. It's possible to specify several types by separating them with the `,` character.
+    #[deserr(default, error = DeserrQueryParamError<InvalidTaskTypes>)]
+    #[param(required = false, value_type = Option<Vec<String>>, example = json!([Kind::DocumentAdditionOrUpdate, "*"]))]
+    pub types: OptionStarOrList<Kind>,
+    /// Permits to filter the events by the status of the tasks. By default, when `statuses` query parameter is not set, the events of all the task statuses are sent. It's possible to specify several statuses by separating them with the `,` character.
+    #[deserr(default, error = DeserrQueryParamError<InvalidTaskStatuses>)]
+    #[param(required = false, value_type = Option<Vec<Status>>, example = json!([Status::Succeeded, Status::Failed, Status::Processing, "*"]))]
+    pub statuses: OptionStarOrList<Status>,
+    /// Permits to filter the events by the index of the tasks. By default, when `indexUids` query parameter is not set, the events of the tasks of all the indexes are sent. It is possible to specify several indexes by separating them with the `,` character.
+    #[deserr(default, error = DeserrQueryParamError<InvalidIndexUid>)]
+    #[param(required = false, value_type = Option<Vec<String>>, example = json!(["movies", "theater", "*"]))]
+    pub index_uids: OptionStarOrList<IndexUid>,
+}
+
+/// The filters of the task events, once validated.
+struct TaskEventsFilter {
+    types: Option<Vec<Kind>>,
+    statuses: Option<Vec<Status>>,
+    index_uids: Option<Vec<String>>,
+    auth: AuthFilter,
+}
+
+impl TaskEventsFilter {
+    fn new(query: TaskEventsQuery, auth: AuthFilter) -> Self {
+        TaskEventsFilter {
+            types: query.types.merge_star_and_none(),
+            statuses: query.statuses.merge_star_and_none(),
+            index_uids: query
+                .index_uids
+                .map(|index_uid| index_uid.to_string())
+                .merge_star_and_none(),
+            auth,
+        }
+    }
+
+    /// Follows the same rules as the tasks listing: a task must match all the filters and
+    /// the tasks that are not related to exactly one index are only visible to the keys
+    /// authorized on all the indexes.
+    fn matches_task(&self, task: &Task) -> bool {
+        let kind = task.kind.as_kind();
+        if (self.index_uids.is_some() || !self.auth.all_indexes_authorized())
+            && !kind.related_to_one_index()
+        {
+            return false;
+        }
+        if !task.indexes().into_iter().all(|index| self.auth.is_index_authorized(index)) {
+            return false;
+        }
+        if let Some(index_uids) = &self.index_uids {
+            if !task.index_uid().is_some_and(|uid| index_uids.iter().any(|index| index == uid)) {
+                return false;
+            }
+        }
+        let matches_type = self.types.as_ref().is_none_or(|types| types.contains(&kind));
+        let matches_status =
+            self.statuses.as_ref().is_none_or(|statuses| statuses.contains(&task.status));
+        matches_type && matches_status
+    }
+
+    /// The batches that are not related to any index, such as the dumps, are only visible to the
+    /// keys authorized on all the indexes.
+    fn matches_batch(&self, indexes: &HashSet<String>) -> bool {
+        if indexes.is_empty() && !self.auth.all_indexes_authorized() {
+            return false;
+        }
+        if !indexes.iter().all(|index| self.auth.is_index_authorized(index)) {
+            return false;
+        }
+        match &self.index_uids {
+            Some(index_uids) => index_uids.iter().any(|index| indexes.contains(index)),
+            None => true,
+        }
+    }
+}
+
+/// The interval at which the progress of the processing batch is checked.
+const BATCH_PROGRESS_INTERVAL: std::time::Duration = std::time::Duration::from_millis(500);
+/// Sends a comment to the client when nothing happened for this long, to detect the disconnections.
+const KEEP_ALIVE_INTERVAL: std::time::Duration = std::time::Duration::from_secs(15);
+/// The interval at which the API key of the request is checked again.
+const AUTHORIZATION_CHECK_INTERVAL: std::time::Duration = std::time::Duration::from_secs(5);
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct BatchProgressEvent {
+    batch_uid: BatchId,
+    progress: ProgressView,
+}
+
+fn server_sent_event(event: &str, data: &impl Serialize) -> Option<Bytes> {
+    let data = serde_json::to_string(data).ok()?;
+    Some(Bytes::from(format!("event: {event}\ndata: {data}\n\n")))
+}
+
+/// Stream task events
+///
+/// Stream the changes of the [tasks](https://www.meilisearch.com/docs/learn/async/asynchronous_operations) and the progress of the processing batch as [server-sent events](https://developer.mozilla.org/en-US/docs/Web/API/Server-sent_events).
+/// A `task` event containing the task is sent each time a task is enqueued, starts being processed or is finished.
+/// A `batchProgress` event containing the uid of the batch and its progress is sent each time the progress of the processing batch changes.
+#[utoipa::path(
+    get,
+    path = "/events",
+    tag = "Tasks",
+    security(("Bearer" = ["tasks.get", "tasks.*", "*"])),
+    params(TaskEventsQuery),
+    responses(
+        (status = 200, description = "The stream of task events", body = String, content_type = "text/event-stream", example = json!(
+            "event: task\ndata: {\"uid\":147,\"batchUid\":null,\"indexUid\":\"movies\",\"status\":\"enqueued\",\"type\":\"documentAdditionOrUpdate\",\"canceledBy\":null,\"details\":{\"receivedDocuments\":1,\"indexedDocuments\":null},\"error\":null,\"duration\":null,\"enqueuedAt\":\"2024-08-08T17:05:55.791772Z\",\"startedAt\":null,\"finishedAt\":null}\n\n"
+        )),
+        (status = 401, description = "The authorization header is missing", body = ResponseError, content_type = "application/json", example = json!(
+            {
+                "message": "The Authorization header is missing. It must use the bearer authorization method.",
+                "code": "missing_authorization_header",
+                "type": "auth",
+                "link": "https://docs.meilisearch.com/errors#missing_authorization_header"
+            }
+        )),
+    )
+)]
+async fn get_task_events(
+    index_scheduler: GuardedData<ActionPolicy<{ actions::TASKS_GET }>, Data<IndexScheduler>>,
+    params: AwebQueryParameter<TaskEventsQuery, DeserrQueryParamError>,
+    req: HttpRequest,
+) -> Result<HttpResponse, ResponseError> {
+    let filter = TaskEventsFilter::new(params.into_inner(), index_scheduler.filters().clone());
+    let task_events = index_scheduler.subscribe_to_task_events();
+    let authorization = req.app_data::<Data<AuthController>>().cloned().zip(
+        req.headers()
+            .get("Authorization")
+            .and_then(|header| header.to_str().ok()?.strip_prefix("Bearer "))
+            .map(String::from),
+    );
+
+    let events = TaskEvents {
+        index_scheduler: Data::clone(&index_scheduler),
+        filter,
+        task_events,
+        progress_interval: tokio::time::interval(BATCH_PROGRESS_INTERVAL),
+        last_progress: None,
+        last_event: std::time::Instant::now(),
+        authorization,
+        last_authorization_check: std::time::Instant::now(),
+    };
+    let stream = futures_util::stream::unfold(events, |mut events| async move {
+        let event = events.next_event().await?;
+        Some((Ok::<_, ResponseError>(event), events))
+    });
+
+    Ok(HttpResponse::Ok()
+        .content_type("text/event-stream")
+        .insert_header((header::CACHE_CONTROL, "no-cache"))
+        .streaming(stream))
+}
+
+/// The state of a stream of task events sent to a client.
+struct TaskEvents {
+    index_scheduler: Data<IndexScheduler>,
+    filter: TaskEventsFilter,
+    task_events: broadcast::Receiver<Task>,
+    progress_interval: tokio::time::Interval,
+    last_progress: Option<Bytes>,
+    last_event: std::time::Instant,
+    /// The API key or tenant token of the request, if any, along with the controller to check it.
+    authorization: Option<(Data<AuthController>, String)>,
+    last_authorization_check: std::time::Instant,
+}
+
+impl TaskEvents {
+    /// Waits for the next event to send to the client, or returns `None` to end the stream.
+    async fn next_event(&mut self) -> Option<Bytes> {
+        loop {
+            tokio::select! {
+                task = self.task_events.recv() => match task {
+                    Ok(task) if self.filter.matches_task(&task) => {
+                        if let Some(event) = server_sent_event("task", &TaskView::from_task(&task)) {
+                            self.last_event = std::time::Instant::now();
+                            return Some(event);
+                        }
+                    }
+                    Ok(_) => (),
+                    Err(RecvError::Lagged(count)) => {
+                        tracing::warn!(
+                            "A task events subscriber is lagging, {count} task changes were dropped"
+                        );
+                    }
+                    Err(RecvError::Closed) => return None,
+                },
+                _ = self.progress_interval.tick() => {
+                    // The stream ends once the API key expired or was deleted.
+                    if self.last_authorization_check.elapsed() >= AUTHORIZATION_CHECK_INTERVAL {
+                        self.last_authorization_check = std::time::Instant::now();
+                        if !self.is_still_authorized().await {
+                            return None;
+                        }
+                    }
+
+                    let progress = self
+                        .index_scheduler
+                        .processing_batch_progress()
+                        .filter(|(_, indexes, _)| self.filter.matches_batch(indexes))
+                        .and_then(|(batch_uid, _, progress)| {
+                            let event = BatchProgressEvent { batch_uid, progress };
+                            server_sent_event("batchProgress", &event)
+                        });
+                    let changed = progress.is_some() && progress != self.last_progress;
+                    self.last_progress = progress.clone();
+                    if changed {
+                        self.last_event = std::time::Instant::now();
+                        return progress;
+                    }
+
+                    if self.last_event.elapsed() >= KEEP_ALIVE_INTERVAL {
+                        self.last_event = std::time::Instant::now();
+                        return Some(Bytes::from_static(b": keep-alive\n\n"));
+                    }
+                }
+            }
+        }
+    }
+
+    async fn is_still_authorized(&self) -> bool {
+        let Some((auth, token)) = self.authorization.clone() else {
+            return true;
+        };
+        task::spawn_blocking(move || {
+            ActionPolicy::<{ actions::TASKS_GET }>::authenticate(auth, &token, None).is_ok()
+        })
+        .await
+        .unwrap_or(false)
+    }
+}
+
 pub enum DeserializeDateOption {
     Before,
     After,
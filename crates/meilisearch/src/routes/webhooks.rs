@@ -0,0 +1,539 @@
+use std::collections::BTreeMap;
+use std::str::FromStr;
+
+use actix_web::http::header::{HeaderName, HeaderValue};
+use actix_web::web::{self, Data};
+use actix_web::HttpResponse;
+use deserr::actix_web::{AwebJson, AwebQueryParameter};
+use deserr::Deserr;
+use index_scheduler::IndexScheduler;
+use meilisearch_types::deserr::query_params::Param;
+use meilisearch_types::deserr::{DeserrJsonError, DeserrQueryParamError};
+use meilisearch_types::error::deserr_codes::*;
+use meilisearch_types::error::{Code, ResponseError};
+use meilisearch_types::index_uid_pattern::IndexUidPattern;
+use meilisearch_types::keys::actions;
+use meilisearch_types::milli::update::Setting;
+use meilisearch_types::tasks::{Kind, Status};
+use meilisearch_types::webhooks::{Webhook, WebhookDelivery, WebhookFilter};
+use serde::Serialize;
+use time::OffsetDateTime;
+use tracing::debug;
+use utoipa::{IntoParams, OpenApi, ToSchema};
+use uuid::Uuid;
+
+use super::{Pagination, PaginationView, PAGINATION_DEFAULT_LIMIT, PAGINATION_DEFAULT_LIMIT_FN};
+use crate::extractors::authentication::policies::ActionPolicy;
+use crate::extractors::authentication::GuardedData;
+use crate::extractors::sequential_extractor::SeqHandler;
+
+#[derive(OpenApi)]
+#[openapi(
+    paths(list_webhooks, create_webhook, get_webhook, patch_webhook, delete_webhook, list_webhook_deliveries),
+    tags((
+        name = "Webhooks",
+        description = "The `/webhooks` route allows you to register the URLs that are called with the tasks once they are processed.
+Each webhook can filter the tasks it receives, send its own headers and sign its payloads. Failed deliveries are retried with an exponential backoff and every delivery is logged.
+
+This route is **synchronous**. This means that no task object will be returned, and any change to the webhooks will be made available immediately.",
+        external_docs(url = "https://www.meilisearch.com/docs/reference/api/webhooks"),
+    )),
+)]
+pub struct WebhooksApi;
+
+pub fn configure(cfg: &mut web::ServiceConfig) {
+    cfg.service(
+        web::resource("")
+            .route(web::get().to(SeqHandler(list_webhooks)))
+            .route(web::post().to(SeqHandler(create_webhook))),
+    )
+    .service(
+        web::resource("/{uuid}")
+            .route(web::get().to(SeqHandler(get_webhook)))
+            .route(web::patch().to(SeqHandler(patch_webhook)))
+            .route(web::delete().to(SeqHandler(delete_webhook))),
+    )
+    .service(
+        web::resource("/{uuid}/deliveries")
+            .route(web::get().to(SeqHandler(list_webhook_deliveries))),
+    );
+}
+
+#[derive(Debug, Deserr, ToSchema)]
+#[deserr(error = DeserrJsonError, rename_all = camelCase, deny_unknown_fields)]
+#[schema(rename_all = "camelCase")]
+pub struct WebhookSettings {
+    /// The URL called with the tasks, it must use the `http` or `https` scheme.
+    #[schema(value_type = Option<String>, example = json!("https://example.com/meilisearch-tasks"))]
+    #[deserr(default, error = DeserrJsonError<InvalidWebhookUrl>)]
+    pub url: Setting<String>,
+    /// The headers sent with every delivery, they replace the previous headers of the webhook.
+    #[schema(value_type = Option<BTreeMap<String, String>>, example = json!({ "Authorization": "Bearer a-secret-token" }))]
+    #[deserr(default, error = DeserrJsonError<InvalidWebhookHeaders>)]
+    pub headers: Setting<BTreeMap<String, String>>,
+    /// Restricts the tasks sent to the webhook.
+    #[schema(value_type = Option<WebhookFilterSettings>)]
+    #[deserr(default, error = DeserrJsonError<InvalidWebhookFilter>)]
+    pub filter: Setting<WebhookFilterSettings>,
+    /// The secret used to compute the `X-Meilisearch-Signature` header of the deliveries.
+    #[schema(value_type = Option<String>, example = json!("a-signing-secret"))]
+    #[deserr(default, error = DeserrJsonError<InvalidWebhookSigningSecret>)]
+    pub signing_secret: Setting<String>,
+}
+
+#[derive(Debug, Deserr, ToSchema)]
+#[deserr(error = DeserrJsonError<InvalidWebhookFilter>, rename_all = camelCase, deny_unknown_fields)]
+#[schema(rename_all = "camelCase")]
+pub struct WebhookFilterSettings {
+    /// Only send the tasks of the indexes matching one of these patterns.
+    #[schema(value_type = Option<Vec<String>>, example = json!(["movies", "products_*"]))]
+    #[deserr(default)]
+    pub index_uids: Option<Vec<IndexUidPattern>>,
+    /// Only send the tasks of these types.
+    #[schema(value_type = Option<Vec<String>>, example = json!(["documentAdditionOrUpdate"]))]
+    #[deserr(default)]
+    pub types: Option<Vec<String>>,
+    /// Only send the tasks with these statuses.
+    #[schema(value_type = Option<Vec<String>>, example = json!(["failed"]))]
+    #[deserr(default)]
+    pub statuses: Option<Vec<String>>,
+}
+
+impl WebhookFilterSettings {
+    fn into_filter(self) -> Result<WebhookFilter, ResponseError> {
+        let WebhookFilterSettings { index_uids, types, statuses } = self;
+        let invalid_filter =
+            |error: String| ResponseError::from_msg(error, Code::InvalidWebhookFilter);
+
+        let types = types
+            .map(|types| {
+                types.iter().map(|kind| Kind::from_str(kind)).collect::<Result<Vec<_>, _>>()
+            })
+            .transpose()
+            .map_err(|e| invalid_filter(format!("Invalid value in `.filter.types`: {e}")))?;
+        let statuses = statuses
+            .map(|statuses| {
+                statuses
+                    .iter()
+                    .map(|status| Status::from_str(status))
+                    .collect::<Result<Vec<_>, _>>()
+            })
+            .transpose()
+            .map_err(|e| invalid_filter(format!("Invalid value in `.filter.statuses`: {e}")))?;
+
+        Ok(WebhookFilter { index_uids, types, statuses })
+    }
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+#[schema(rename_all = "camelCase")]
+pub struct WebhookView {
+    uuid: Uuid,
+    url: String,
+    /// The names of the headers sent with every delivery, their values are redacted.
+    headers: BTreeMap<String, String>,
+    filter: WebhookFilter,
+    /// Whether the deliveries are signed, the signing secret itself is never returned.
+    has_signing_secret: bool,
+    #[schema(value_type = String, example = json!("2024-08-08T14:12:09.393Z"))]
+    #[serde(serialize_with = "time::serde::rfc3339::serialize")]
+    created_at: OffsetDateTime,
+    #[schema(value_type = String, example = json!("2024-08-08T14:12:09.393Z"))]
+    #[serde(serialize_with = "time::serde::rfc3339::serialize")]
+    updated_at: OffsetDateTime,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct WebhookResults {
+    results: Vec<WebhookView>,
+}
+
+/// Replaces the values of the headers in the responses, they often contain credentials.
+const REDACTED_HEADER_VALUE: &str = "XXX...";
+
+impl From<Webhook> for WebhookView {
+    fn from(webhook: Webhook) -> Self {
+        let Webhook { uuid, url, headers, filter, signing_secret, created_at, updated_at } =
+            webhook;
+        WebhookView {
+            uuid,
+            url,
+            headers: headers
+                .into_keys()
+                .map(|name| (name, String::from(REDACTED_HEADER_VALUE)))
+                .collect(),
+            filter,
+            has_signing_secret: signing_secret.is_some(),
+            created_at,
+            updated_at,
+        }
+    }
+}
+
+fn check_url(url: String) -> Result<String, ResponseError> {
+    match url::Url::parse(&url) {
+        Ok(parsed) if matches!(parsed.scheme(), "http" | "https") => Ok(url),
+        Ok(_) => Err(ResponseError::from_msg(
+            format!("Invalid `.url` (`{url}`): the scheme must be `http` or `https`."),
+            Code::InvalidWebhookUrl,
+        )),
+        Err(error) => Err(ResponseError::from_msg(
+            format!("Invalid `.url` (`{url}`): {error}"),
+            Code::InvalidWebhookUrl,
+        )),
+    }
+}
+
+fn check_headers(
+    headers: BTreeMap<String, String>,
+) -> Result<BTreeMap<String, String>, ResponseError> {
+    for (name, value) in &headers {
+        if HeaderName::from_bytes(name.as_bytes()).is_err() {
+            return Err(ResponseError::from_msg(
+                format!("Invalid `.headers`: `{name}` is not a valid header name."),
+                Code::InvalidWebhookHeaders,
+            ));
+        }
+        if HeaderValue::from_str(value).is_err() {
+            return Err(ResponseError::from_msg(
+                format!("Invalid `.headers.{name}`: the value is not a valid header value."),
+                Code::InvalidWebhookHeaders,
+            ));
+        }
+    }
+    Ok(headers)
+}
+
+fn parse_uuid(uuid: &str) -> Result<Uuid, ResponseError> {
+    Uuid::from_str(uuid)
+        .map_err(|_| index_scheduler::Error::WebhookNotFound(uuid.to_string()).into())
+}
+
+/// List webhooks
+///
+/// List all the webhooks registered on this instance.
+#[utoipa::path(
+    get,
+    path = "",
+    tag = "Webhooks",
+    security(("Bearer" = ["webhooks.get", "*"])),
+    responses(
+        (status = OK, description = "The webhooks are returned", body = WebhookResults, content_type = "application/json", example = json!(
+            {
+                "results": [
+                    {
+                        "uuid": "05e3d3f8-5a2d-4c3b-9a61-3e2ad7d1a0c1",
+                        "url": "https://example.com/meilisearch-tasks",
+                        "headers": { "Authorization": "XXX..." },
+                        "filter": { "indexUids": ["movies"], "types": null, "statuses": ["failed"] },
+                        "hasSigningSecret": true,
+                        "createdAt": "2024-08-08T14:12:09.393Z",
+                        "updatedAt": "2024-08-08T14:12:09.393Z"
+                    }
+                ]
+            }
+        )),
+        (status = 401, description = "The authorization header is missing", body = ResponseError, content_type = "application/json", example = json!(
+            {
+                "message": "The Authorization header is missing. It must use the bearer authorization method.",
+                "code": "missing_authorization_header",
+                "type": "auth",
+                "link": "https://docs.meilisearch.com/errors#missing_authorization_header"
+            }
+        )),
+    )
+)]
+async fn list_webhooks(
+    index_scheduler: GuardedData<ActionPolicy<{ actions::WEBHOOKS_GET }>, Data<IndexScheduler>>,
+) -> Result<HttpResponse, ResponseError> {
+    let results = index_scheduler.webhooks()?.into_iter().map(WebhookView::from).collect();
+    let webhooks = WebhookResults { results };
+    debug!(returns = ?webhooks, "List webhooks");
+    Ok(HttpResponse::Ok().json(webhooks))
+}
+
+/// Create a webhook
+///
+/// Register a new webhook, the `url` is mandatory.
+#[utoipa::path(
+    post,
+    path = "",
+    tag = "Webhooks",
+    request_body = WebhookSettings,
+    security(("Bearer" = ["webhooks.create", "*"])),
+    responses(
+        (status = CREATED, description = "The webhook has been created", body = WebhookView, content_type = "application/json", example = json!(
+            {
+                "uuid": "05e3d3f8-5a2d-4c3b-9a61-3e2ad7d1a0c1",
+                "url": "https://example.com/meilisearch-tasks",
+                "headers": {},
+                "filter": { "indexUids": null, "types": null, "statuses": null },
+                "hasSigningSecret": false,
+                "createdAt": "2024-08-08T14:12:09.393Z",
+                "updatedAt": "2024-08-08T14:12:09.393Z"
+            }
+        )),
+        (status = 401, description = "The authorization header is missing", body = ResponseError, content_type = "application/json", example = json!(
+            {
+                "message": "The Authorization header is missing. It must use the bearer authorization method.",
+                "code": "missing_authorization_header",
+                "type": "auth",
+                "link": "https://docs.meilisearch.com/errors#missing_authorization_header"
+            }
+        )),
+    )
+)]
+async fn create_webhook(
+    index_scheduler: GuardedData<ActionPolicy<{ actions::WEBHOOKS_CREATE }>, Data<IndexScheduler>>,
+    settings: AwebJson<WebhookSettings, DeserrJsonError>,
+) -> Result<HttpResponse, ResponseError> {
+    let WebhookSettings { url, headers, filter, signing_secret } = settings.into_inner();
+    debug!(parameters = ?url, "Create webhook");
+
+    let url = match url {
+        Setting::Set(url) => check_url(url)?,
+        Setting::Reset | Setting::NotSet => {
+            return Err(ResponseError::from_msg(
+                "Missing field `url`".to_string(),
+                Code::MissingWebhookUrl,
+            ))
+        }
+    };
+    let now = OffsetDateTime::now_utc();
+    let webhook = Webhook {
+        uuid: Uuid::new_v4(),
+        url,
+        headers: check_headers(headers.set().unwrap_or_default())?,
+        filter: filter
+            .set()
+            .map(WebhookFilterSettings::into_filter)
+            .transpose()?
+            .unwrap_or_default(),
+        signing_secret: signing_secret.set(),
+        created_at: now,
+        updated_at: now,
+    };
+
+    index_scheduler.put_webhook(&webhook)?;
+    let webhook = WebhookView::from(webhook);
+    debug!(returns = ?webhook, "Create webhook");
+    Ok(HttpResponse::Created().json(webhook))
+}
+
+/// Get a webhook
+///
+/// Get a webhook from its `uuid`.
+#[utoipa::path(
+    get,
+    path = "/{uuid}",
+    tag = "Webhooks",
+    security(("Bearer" = ["webhooks.get", "*"])),
+    params(("uuid" = String, Path, example = "05e3d3f8-5a2d-4c3b-9a61-3e2ad7d1a0c1", description = "The uuid of the webhook", nullable = false)),
+    responses(
+        (status = OK, description = "The webhook is returned", body = WebhookView, content_type = "application/json", example = json!(
+            {
+                "uuid": "05e3d3f8-5a2d-4c3b-9a61-3e2ad7d1a0c1",
+                "url": "https://example.com/meilisearch-tasks",
+                "headers": {},
+                "filter": { "indexUids": null, "types": null, "statuses": null },
+                "hasSigningSecret": false,
+                "createdAt": "2024-08-08T14:12:09.393Z",
+                "updatedAt": "2024-08-08T14:12:09.393Z"
+            }
+        )),
+        (status = 404, description = "The webhook does not exist", body = ResponseError, content_type = "application/json", example = json!(
+            {
+                "message": "Webhook `05e3d3f8-5a2d-4c3b-9a61-3e2ad7d1a0c1` not found.",
+                "code": "webhook_not_found",
+                "type": "invalid_request",
+                "link": "https://docs.meilisearch.com/errors#webhook_not_found"
+            }
+        )),
+    )
+)]
+async fn get_webhook(
+    index_scheduler: GuardedData<ActionPolicy<{ actions::WEBHOOKS_GET }>, Data<IndexScheduler>>,
+    uuid: web::Path<String>,
+) -> Result<HttpResponse, ResponseError> {
+    let uuid = parse_uuid(&uuid)?;
+    let webhook = WebhookView::from(index_scheduler.webhook(uuid)?);
+    debug!(returns = ?webhook, "Get webhook");
+    Ok(HttpResponse::Ok().json(webhook))
+}
+
+/// Update a webhook
+///
+/// Update the fields of a webhook, the fields missing from the payload are left unchanged.
+#[utoipa::path(
+    patch,
+    path = "/{uuid}",
+    tag = "Webhooks",
+    request_body = WebhookSettings,
+    security(("Bearer" = ["webhooks.update", "*"])),
+    params(("uuid" = String, Path, example = "05e3d3f8-5a2d-4c3b-9a61-3e2ad7d1a0c1", description = "The uuid of the webhook", nullable = false)),
+    responses(
+        (status = OK, description = "The webhook has been updated", body = WebhookView, content_type = "application/json", example = json!(
+            {
+                "uuid": "05e3d3f8-5a2d-4c3b-9a61-3e2ad7d1a0c1",
+                "url": "https://example.com/meilisearch-tasks",
+                "headers": {},
+                "filter": { "indexUids": ["movies"], "types": null, "statuses": null },
+                "hasSigningSecret": false,
+                "createdAt": "2024-08-08T14:12:09.393Z",
+                "updatedAt": "2024-08-09T10:02:12.114Z"
+            }
+        )),
+        (status = 404, description = "The webhook does not exist", body = ResponseError, content_type = "application/json", example = json!(
+            {
+                "message": "Webhook `05e3d3f8-5a2d-4c3b-9a61-3e2ad7d1a0c1` not found.",
+                "code": "webhook_not_found",
+                "type": "invalid_request",
+                "link": "https://docs.meilisearch.com/errors#webhook_not_found"
+            }
+        )),
+    )
+)]
+async fn patch_webhook(
+    index_scheduler: GuardedData<ActionPolicy<{ actions::WEBHOOKS_UPDATE }>, Data<IndexScheduler>>,
+    uuid: web::Path<String>,
+    settings: AwebJson<WebhookSettings, DeserrJsonError>,
+) -> Result<HttpResponse, ResponseError> {
+    let uuid = parse_uuid(&uuid)?;
+    let WebhookSettings { url, headers, filter, signing_secret } = settings.into_inner();
+    let mut webhook = index_scheduler.webhook(uuid)?;
+    debug!(parameters = ?url, "Patch webhook");
+
+    match url {
+        Setting::Set(url) => webhook.url = check_url(url)?,
+        Setting::Reset => {
+            return Err(ResponseError::from_msg(
+                "Field `url` cannot be set to `null`".to_string(),
+                Code::InvalidWebhookUrl,
+            ))
+        }
+        Setting::NotSet => (),
+    }
+    match headers {
+        Setting::Set(headers) => webhook.headers = check_headers(headers)?,
+        Setting::Reset => webhook.headers = BTreeMap::new(),
+        Setting::NotSet => (),
+    }
+    match filter {
+        Setting::Set(filter) => webhook.filter = filter.into_filter()?,
+        Setting::Reset => webhook.filter = WebhookFilter::default(),
+        Setting::NotSet => (),
+    }
+    match signing_secret {
+        Setting::Set(signing_secret) => webhook.signing_secret = Some(signing_secret),
+        Setting::Reset => webhook.signing_secret = None,
+        Setting::NotSet => (),
+    }
+    webhook.updated_at = OffsetDateTime::now_utc();
+
+    index_scheduler.put_webhook(&webhook)?;
+    let webhook = WebhookView::from(webhook);
+    debug!(returns = ?webhook, "Patch webhook");
+    Ok(HttpResponse::Ok().json(webhook))
+}
+
+/// Delete a webhook
+///
+/// Delete a webhook along with its delivery log. The payloads that are already being delivered are still sent.
+#[utoipa::path(
+    delete,
+    path = "/{uuid}",
+    tag = "Webhooks",
+    security(("Bearer" = ["webhooks.delete", "*"])),
+    params(("uuid" = String, Path, example = "05e3d3f8-5a2d-4c3b-9a61-3e2ad7d1a0c1", description = "The uuid of the webhook", nullable = false)),
+    responses(
+        (status = NO_CONTENT, description = "The webhook has been deleted"),
+        (status = 404, description = "The webhook does not exist", body = ResponseError, content_type = "application/json", example = json!(
+            {
+                "message": "Webhook `05e3d3f8-5a2d-4c3b-9a61-3e2ad7d1a0c1` not found.",
+                "code": "webhook_not_found",
+                "type": "invalid_request",
+                "link": "https://docs.meilisearch.com/errors#webhook_not_found"
+            }
+        )),
+    )
+)]
+async fn delete_webhook(
+    index_scheduler: GuardedData<ActionPolicy<{ actions::WEBHOOKS_DELETE }>, Data<IndexScheduler>>,
+    uuid: web::Path<String>,
+) -> Result<HttpResponse, ResponseError> {
+    let uuid = parse_uuid(&uuid)?;
+    index_scheduler.delete_webhook(uuid)?;
+    debug!(webhook = %uuid, "Delete webhook");
+    Ok(HttpResponse::NoContent().finish())
+}
+
+#[derive(Deserr, Debug, Clone, Copy, IntoParams)]
+#[deserr(error = DeserrQueryParamError, rename_all = camelCase, deny_unknown_fields)]
+#[into_params(rename_all = "camelCase", parameter_in = Query)]
+pub struct ListWebhookDeliveries {
+    #[deserr(default, error = DeserrQueryParamError<InvalidWebhookDeliveryOffset>)]
+    #[param(value_type = usize, default = 0)]
+    pub offset: Param<usize>,
+    #[deserr(default = Param(PAGINATION_DEFAULT_LIMIT), error = DeserrQueryParamError<InvalidWebhookDeliveryLimit>)]
+    #[param(value_type = usize, default = PAGINATION_DEFAULT_LIMIT_FN)]
+    pub limit: Param<usize>,
+}
+
+/// List the deliveries of a webhook
+///
+/// List the last deliveries of a webhook, from the most recent to the oldest.
+/// Only the last thousand deliveries of the instance are kept.
+#[utoipa::path(
+    get,
+    path = "/{uuid}/deliveries",
+    tag = "Webhooks",
+    security(("Bearer" = ["webhooks.get", "*"])),
+    params(
+        ("uuid" = String, Path, example = "05e3d3f8-5a2d-4c3b-9a61-3e2ad7d1a0c1", description = "The uuid of the webhook", nullable = false),
+        ListWebhookDeliveries
+    ),
+    responses(
+        (status = OK, description = "The deliveries are returned", body = PaginationView<WebhookDelivery>, content_type = "application/json", example = json!(
+            {
+                "results": [
+                    {
+                        "uid": 12,
+                        "webhookUuid": "05e3d3f8-5a2d-4c3b-9a61-3e2ad7d1a0c1",
+                        "taskUids": [42, 43],
+                        "status": "failed",
+                        "attempts": 5,
+                        "statusCode": 503,
+                        "error": "The webhook responded with the status code `503`.",
+                        "enqueuedAt": "2024-08-08T14:12:09.393Z",
+                        "finishedAt": "2024-08-08T14:12:24.512Z"
+                    }
+                ],
+                "offset": 0,
+                "limit": 20,
+                "total": 1
+            }
+        )),
+        (status = 404, description = "The webhook does not exist", body = ResponseError, content_type = "application/json", example = json!(
+            {
+                "message": "Webhook `05e3d3f8-5a2d-4c3b-9a61-3e2ad7d1a0c1` not found.",
+                "code": "webhook_not_found",
+                "type": "invalid_request",
+                "link": "https://docs.meilisearch.com/errors#webhook_not_found"
+            }
+        )),
+    )
+)]
+async fn list_webhook_deliveries(
+    index_scheduler: GuardedData<ActionPolicy<{ actions::WEBHOOKS_GET }>, Data<IndexScheduler>>,
+    uuid: web::Path<String>,
+    params: AwebQueryParameter<ListWebhookDeliveries, DeserrQueryParamError>,
+) -> Result<HttpResponse, ResponseError> {
+    let uuid = parse_uuid(&uuid)?;
+    let ListWebhookDeliveries { offset, limit } = params.into_inner();
+    let deliveries = index_scheduler.webhook_deliveries(uuid)?;
+    let page_view =
+        Pagination { offset: offset.0, limit: limit.0 }.auto_paginate_sized(deliveries.into_iter());
+    debug!(returns = ?page_view, "List webhook deliveries");
+    Ok(HttpResponse::Ok().json(page_view))
+}
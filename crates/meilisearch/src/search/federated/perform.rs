@@ -15,12 +15,14 @@ use meilisearch_types::milli::order_by_map::OrderByMap;
 use meilisearch_types::milli::score_details::{ScoreDetails, WeightedScoreValue};
 use meilisearch_types::milli::{self, DocumentId, OrderBy, TimeBudget, DEFAULT_VALUES_PER_FACET};
 use roaring::RoaringBitmap;
+use serde_json::Value;
 use tokio::task::JoinHandle;
 
 use super::super::ranking_rules::{self, RankingRules};
 use super::super::{
-    compute_facet_distribution_stats, prepare_search, AttributesFormat, ComputedFacets, HitMaker,
-    HitsInfo, RetrieveVectors, SearchHit, SearchKind, SearchQuery, SearchQueryWithIndex,
+    apply_query_rules, compute_facet_distribution_stats, prepare_search, AttributesFormat,
+    ComputedFacets, HitMaker, HitsInfo, RetrieveVectors, SearchHit, SearchKind, SearchQuery,
+    SearchQueryWithIndex,
 };
 use super::proxy::{proxy_search, ProxySearchError, ProxySearchParams};
 use super::types::{
@@ -93,6 +95,7 @@ pub async fn perform_federated_search(
         mut results_by_index,
         previous_query_data: _,
         facet_order,
+        mut user_data,
     } = search_by_index;
 
     let before_waiting_remote_results = std::time::Instant::now();
@@ -102,6 +105,13 @@ pub async fn perform_federated_search(
 
     let after_waiting_remote_results = std::time::Instant::now();
 
+    // the `userData` of the local queries in their order, then the ones of the remotes
+    user_data.sort_by_key(|(query_index, _)| *query_index);
+    let mut user_data: Vec<_> = user_data.into_iter().map(|(_, data)| data).collect();
+    for remote_result in &mut remote_results {
+        user_data.append(&mut remote_result.user_data);
+    }
+
     // 3. merge hits and metadata across indexes and hosts
     // 3.1. merge metadata
     let (estimated_total_hits, degraded, used_negative_operator, facets, max_remote_duration) =
@@ -147,6 +157,7 @@ pub async fn perform_federated_search(
         facet_stats,
         facets_by_index,
         remote_errors: partitioned_queries.has_remote.then_some(remote_errors),
+        user_data,
     })
 }
 
@@ -415,6 +426,7 @@ fn merge_metadata(
         degraded: degraded_for_host,
         used_negative_operator: host_used_negative_operator,
         remote_errors: _,
+        user_data: _,
     } in remote_results
     {
         let this_remote_duration = Duration::from_millis(*processing_time_ms as u64);
@@ -661,6 +673,8 @@ struct SearchByIndex {
     // remember the order and name of first index for each facet when merging with index settings
     // to detect if the order is inconsistent for a facet.
     facet_order: FacetOrder,
+    // the `userData` of the query rules matching the queries, along with the index of the query
+    user_data: Vec<(usize, Value)>,
 }
 
 impl SearchByIndex {
@@ -675,6 +689,7 @@ impl SearchByIndex {
             semantic_hit_count: None,
             results_by_index: Vec::with_capacity(index_count),
             previous_query_data: None,
+            user_data: Vec::new(),
         }
     }
 
@@ -725,10 +740,13 @@ impl SearchByIndex {
             return Err(error);
         }
         let mut results_by_query = Vec::with_capacity(queries.len());
-        for QueryByIndex { query, weight, query_index } in queries {
+        for QueryByIndex { mut query, weight, query_index } in queries {
             // use an immediately invoked lambda to capture the result without returning from the function
 
             let res: Result<(), ResponseError> = (|| {
+                let user_data = apply_query_rules(&index, &rtxn, &mut query)?;
+                self.user_data.extend(user_data.into_iter().map(|data| (query_index, data)));
+
                 let search_kind =
                     search_kind(&query, params.index_scheduler, index_uid.to_string(), &index)?;
 
@@ -838,6 +856,8 @@ impl SearchByIndex {
                     document_scores,
                     degraded: query_degraded,
                     used_negative_operator: query_used_negative_operator,
+                    // suggestions are not returned by federated searches
+                    suggestions: _,
                 } = result;
 
                 candidates |= query_candidates;
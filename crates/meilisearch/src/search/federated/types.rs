@@ -15,6 +15,7 @@ use meilisearch_types::index_uid::IndexUid;
 use meilisearch_types::milli::order_by_map::OrderByMap;
 use meilisearch_types::milli::OrderBy;
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
 use utoipa::ToSchema;
 
 use super::super::{ComputedFacets, FacetStats, HitsInfo, SearchHit, SearchQueryWithIndex};
@@ -131,6 +132,11 @@ pub struct FederatedSearchResult {
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub remote_errors: Option<BTreeMap<String, ResponseError>>,
 
+    /// The `userData` of the query rules matching the queries, in the order of the queries
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    #[schema(value_type = Vec<Object>)]
+    pub user_data: Vec<Value>,
+
     // These fields are only used for analytics purposes
     #[serde(skip)]
     pub degraded: bool,
@@ -151,6 +157,7 @@ impl fmt::Debug for FederatedSearchResult {
             facet_stats,
             facets_by_index,
             remote_errors,
+            user_data,
         } = self;
 
         let mut debug = f.debug_struct("SearchResult");
@@ -179,6 +186,9 @@ impl fmt::Debug for FederatedSearchResult {
         if let Some(remote_errors) = remote_errors {
             debug.field("remote_errors", &remote_errors);
         }
+        if !user_data.is_empty() {
+            debug.field("user_data", &user_data);
+        }
 
         debug.finish()
     }
@@ -1,6 +1,7 @@
 use core::fmt;
 use std::cmp::min;
 use std::collections::{BTreeMap, BTreeSet, HashSet};
+use std::num::NonZeroUsize;
 use std::str::FromStr;
 use std::sync::Arc;
 use std::time::{Duration, Instant};
@@ -16,8 +17,11 @@ use meilisearch_types::error::{Code, ResponseError};
 use meilisearch_types::heed::RoTxn;
 use meilisearch_types::index_uid::IndexUid;
 use meilisearch_types::locales::Locale;
+use meilisearch_types::milli::prompt::Prompt;
+use meilisearch_types::milli::query_rules::{normalize_query, QueryRule};
 use meilisearch_types::milli::score_details::{ScoreDetails, ScoringStrategy};
 use meilisearch_types::milli::vector::parsed_vectors::ExplicitVectors;
+use meilisearch_types::milli::vector::rerank::Reranker;
 use meilisearch_types::milli::vector::Embedder;
 use meilisearch_types::milli::{
     FacetValueHit, InternalError, OrderBy, PatternMatch, SearchForFacetValues, TimeBudget,
@@ -55,16 +59,25 @@ pub const DEFAULT_CROP_MARKER: fn() -> String = || "…".to_string();
 pub const DEFAULT_HIGHLIGHT_PRE_TAG: fn() -> String = || "<em>".to_string();
 pub const DEFAULT_HIGHLIGHT_POST_TAG: fn() -> String = || "</em>".to_string();
 pub const DEFAULT_SEMANTIC_RATIO: fn() -> SemanticRatio = || SemanticRatio(0.5);
+/// The maximum number of spelling suggestions returned by a search.
+pub const SUGGESTIONS_LIMIT: usize = 3;
+pub const DEFAULT_AUTOCOMPLETE_LIMIT: fn() -> usize = || 5;
+/// The maximum number of completions returned by an autocomplete request.
+pub const MAX_AUTOCOMPLETE_LIMIT: usize = 100;
 
 #[derive(Clone, Default, PartialEq, Deserr, ToSchema)]
 #[deserr(error = DeserrJsonError, rename_all = camelCase, deny_unknown_fields)]
 pub struct SearchQuery {
     #[deserr(default, error = DeserrJsonError<InvalidSearchQ>)]
     pub q: Option<String>,
+    #[deserr(default, error = DeserrJsonError<InvalidSearchQuerySyntax>)]
+    pub query_syntax: QuerySyntax,
     #[deserr(default, error = DeserrJsonError<InvalidSearchVector>)]
     pub vector: Option<Vec<f32>>,
     #[deserr(default, error = DeserrJsonError<InvalidSearchHybridQuery>)]
     pub hybrid: Option<HybridQuery>,
+    #[deserr(default, error = DeserrJsonError<InvalidSearchVectorSearch>)]
+    pub vector_search: Option<VectorSearchQuery>,
     #[deserr(default = DEFAULT_SEARCH_OFFSET(), error = DeserrJsonError<InvalidSearchOffset>)]
     #[schema(default = DEFAULT_SEARCH_OFFSET)]
     pub offset: usize,
@@ -92,6 +105,8 @@ pub struct SearchQuery {
     pub show_ranking_score: bool,
     #[deserr(default, error = DeserrJsonError<InvalidSearchShowRankingScoreDetails>)]
     pub show_ranking_score_details: bool,
+    #[deserr(default, error = DeserrJsonError<InvalidSearchShowSuggestions>)]
+    pub show_suggestions: bool,
     #[deserr(default, error = DeserrJsonError<InvalidSearchFilter>)]
     pub filter: Option<Value>,
     #[deserr(default, error = DeserrJsonError<InvalidSearchSort>)]
@@ -161,8 +176,10 @@ impl fmt::Debug for SearchQuery {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         let Self {
             q,
+            query_syntax,
             vector,
             hybrid,
+            vector_search,
             offset,
             limit,
             page,
@@ -175,6 +192,7 @@ impl fmt::Debug for SearchQuery {
             show_matches_position,
             show_ranking_score,
             show_ranking_score_details,
+            show_suggestions,
             filter,
             sort,
             distinct,
@@ -203,6 +221,9 @@ impl fmt::Debug for SearchQuery {
         if let Some(q) = q {
             debug.field("q", &q);
         }
+        if *query_syntax != QuerySyntax::default() {
+            debug.field("query_syntax", &query_syntax);
+        }
         if *retrieve_vectors {
             debug.field("retrieve_vectors", &retrieve_vectors);
         }
@@ -219,6 +240,9 @@ impl fmt::Debug for SearchQuery {
         if let Some(hybrid) = hybrid {
             debug.field("hybrid", &hybrid);
         }
+        if let Some(vector_search) = vector_search {
+            debug.field("vector_search", &vector_search);
+        }
         if let Some(attributes_to_search_on) = attributes_to_search_on {
             debug.field("attributes_to_search_on", &attributes_to_search_on);
         }
@@ -247,6 +271,9 @@ impl fmt::Debug for SearchQuery {
         if *show_ranking_score_details {
             debug.field("self.show_ranking_score_details", show_ranking_score_details);
         }
+        if *show_suggestions {
+            debug.field("show_suggestions", show_suggestions);
+        }
         debug.field("crop_length", &crop_length);
         if let Some(facets) = facets {
             debug.field("facets", &facets);
@@ -287,11 +314,39 @@ pub struct HybridQuery {
     pub embedder: String,
 }
 
+/// Tunes the vector search of semantic and hybrid searches, trading latency for recall.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserr, ToSchema, Serialize)]
+#[deserr(error = DeserrJsonError<InvalidSearchVectorSearch>, rename_all = camelCase, deny_unknown_fields)]
+#[serde(rename_all = "camelCase")]
+pub struct VectorSearchQuery {
+    /// Compare the query to the vector of every candidate rather than traversing the vector store.
+    ///
+    /// Defaults to `true` when the filter leaves at most 10000 candidates.
+    /// Ignored for binary quantized embedders, which are always searched by traversing the vector store.
+    #[deserr(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub exhaustive: Option<bool>,
+    /// The number of nodes of the vector store visited when traversing it, higher values improve recall.
+    #[deserr(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub search_k: Option<usize>,
+}
+
 #[derive(Clone)]
 pub enum SearchKind {
     KeywordOnly,
-    SemanticOnly { embedder_name: String, embedder: Arc<Embedder>, quantized: bool },
-    Hybrid { embedder_name: String, embedder: Arc<Embedder>, quantized: bool, semantic_ratio: f32 },
+    SemanticOnly {
+        embedder_name: String,
+        embedder: Arc<Embedder>,
+        quantized: bool,
+    },
+    Hybrid {
+        embedder_name: String,
+        embedder: Arc<Embedder>,
+        quantized: bool,
+        semantic_ratio: f32,
+        reranker: Option<(Arc<Reranker>, Arc<Prompt>)>,
+    },
 }
 
 impl SearchKind {
@@ -323,13 +378,36 @@ impl SearchKind {
     ) -> Result<Self, ResponseError> {
         let (embedder_name, embedder, quantized) = Self::embedder(
             index_scheduler,
-            index_uid,
+            index_uid.clone(),
             index,
             embedder_name,
             vector_len,
             Route::Search,
         )?;
-        Ok(Self::Hybrid { embedder_name, embedder, quantized, semantic_ratio })
+        let reranker = Self::reranker(index_scheduler, index_uid, index, &embedder_name)?;
+        Ok(Self::Hybrid { embedder_name, embedder, quantized, semantic_ratio, reranker })
+    }
+
+    /// Returns the reranker of the embedder, with the template used to render the documents it reranks.
+    fn reranker(
+        index_scheduler: &index_scheduler::IndexScheduler,
+        index_uid: String,
+        index: &Index,
+        embedder_name: &str,
+    ) -> Result<Option<(Arc<Reranker>, Arc<Prompt>)>, ResponseError> {
+        let rtxn = index.read_txn()?;
+        let Some(config) =
+            index.embedding_configs(&rtxn)?.into_iter().find(|config| config.name == embedder_name)
+        else {
+            return Ok(None);
+        };
+        let Some(settings) = config.config.reranker else {
+            return Ok(None);
+        };
+        // rerankers always work on text, even when the embedder renders multimodal fragments
+        let prompt = Prompt::try_from(config.config.prompt).map_err(milli::Error::from)?;
+        let reranker = index_scheduler.reranker(index_uid, settings)?;
+        Ok(Some((reranker, Arc::new(prompt))))
     }
 
     pub(crate) fn embedder(
@@ -423,10 +501,14 @@ pub struct SearchQueryWithIndex {
     pub index_uid: IndexUid,
     #[deserr(default, error = DeserrJsonError<InvalidSearchQ>)]
     pub q: Option<String>,
+    #[deserr(default, error = DeserrJsonError<InvalidSearchQuerySyntax>)]
+    pub query_syntax: QuerySyntax,
     #[deserr(default, error = DeserrJsonError<InvalidSearchQ>)]
     pub vector: Option<Vec<f32>>,
     #[deserr(default, error = DeserrJsonError<InvalidSearchHybridQuery>)]
     pub hybrid: Option<HybridQuery>,
+    #[deserr(default, error = DeserrJsonError<InvalidSearchVectorSearch>)]
+    pub vector_search: Option<VectorSearchQuery>,
     #[deserr(default, error = DeserrJsonError<InvalidSearchOffset>)]
     pub offset: Option<usize>,
     #[deserr(default, error = DeserrJsonError<InvalidSearchLimit>)]
@@ -451,6 +533,8 @@ pub struct SearchQueryWithIndex {
     pub show_ranking_score_details: bool,
     #[deserr(default, error = DeserrJsonError<InvalidSearchShowMatchesPosition>, default)]
     pub show_matches_position: bool,
+    #[deserr(default, error = DeserrJsonError<InvalidSearchShowSuggestions>, default)]
+    pub show_suggestions: bool,
     #[deserr(default, error = DeserrJsonError<InvalidSearchFilter>)]
     pub filter: Option<Value>,
     #[deserr(default, error = DeserrJsonError<InvalidSearchSort>)]
@@ -504,8 +588,10 @@ impl SearchQueryWithIndex {
     ) -> Self {
         let SearchQuery {
             q,
+            query_syntax,
             vector,
             hybrid,
+            vector_search,
             offset,
             limit,
             page,
@@ -518,6 +604,7 @@ impl SearchQueryWithIndex {
             show_matches_position,
             show_ranking_score,
             show_ranking_score_details,
+            show_suggestions,
             filter,
             sort,
             distinct,
@@ -534,8 +621,10 @@ impl SearchQueryWithIndex {
         SearchQueryWithIndex {
             index_uid,
             q,
+            query_syntax,
             vector,
             hybrid,
+            vector_search,
             offset: if offset == DEFAULT_SEARCH_OFFSET() { None } else { Some(offset) },
             limit: if limit == DEFAULT_SEARCH_LIMIT() { None } else { Some(limit) },
             page,
@@ -547,6 +636,7 @@ impl SearchQueryWithIndex {
             attributes_to_highlight,
             show_ranking_score,
             show_ranking_score_details,
+            show_suggestions,
             show_matches_position,
             filter,
             sort,
@@ -568,6 +658,7 @@ impl SearchQueryWithIndex {
             index_uid,
             federation_options,
             q,
+            query_syntax,
             vector,
             offset,
             limit,
@@ -580,6 +671,7 @@ impl SearchQueryWithIndex {
             attributes_to_highlight,
             show_ranking_score,
             show_ranking_score_details,
+            show_suggestions,
             show_matches_position,
             filter,
             sort,
@@ -591,6 +683,7 @@ impl SearchQueryWithIndex {
             matching_strategy,
             attributes_to_search_on,
             hybrid,
+            vector_search,
             ranking_score_threshold,
             locales,
         } = self;
@@ -598,6 +691,7 @@ impl SearchQueryWithIndex {
             index_uid,
             SearchQuery {
                 q,
+                query_syntax,
                 vector,
                 offset: offset.unwrap_or(DEFAULT_SEARCH_OFFSET()),
                 limit: limit.unwrap_or(DEFAULT_SEARCH_LIMIT()),
@@ -610,6 +704,7 @@ impl SearchQueryWithIndex {
                 attributes_to_highlight,
                 show_ranking_score,
                 show_ranking_score_details,
+                show_suggestions,
                 show_matches_position,
                 filter,
                 sort,
@@ -621,6 +716,7 @@ impl SearchQueryWithIndex {
                 matching_strategy,
                 attributes_to_search_on,
                 hybrid,
+                vector_search,
                 ranking_score_threshold,
                 locales,
                 // do not use ..Default::default() here,
@@ -658,6 +754,25 @@ pub struct SimilarQuery {
     pub ranking_score_threshold: Option<RankingScoreThresholdSimilar>,
 }
 
+#[derive(Debug, Clone, PartialEq, Deserr, ToSchema)]
+#[deserr(error = DeserrJsonError, rename_all = camelCase, deny_unknown_fields)]
+#[schema(rename_all = "camelCase")]
+pub struct AutocompleteQuery {
+    /// The query whose last word is completed
+    #[deserr(default, error = DeserrJsonError<InvalidAutocompleteQ>)]
+    pub q: Option<String>,
+    /// Maximum number of completions returned, at most 100
+    #[deserr(default = DEFAULT_AUTOCOMPLETE_LIMIT(), error = DeserrJsonError<InvalidAutocompleteLimit>)]
+    #[schema(default = DEFAULT_AUTOCOMPLETE_LIMIT)]
+    pub limit: usize,
+    /// Only complete with the words of the documents matching the filter
+    #[deserr(default, error = DeserrJsonError<InvalidAutocompleteFilter>)]
+    pub filter: Option<Value>,
+    /// Only complete with the words of these attributes
+    #[deserr(default, error = DeserrJsonError<InvalidAutocompleteAttributesToSearchOn>)]
+    pub attributes_to_search_on: Option<Vec<String>>,
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub struct ExternalDocumentId(String);
 
@@ -717,6 +832,27 @@ impl From<MatchingStrategy> for TermsMatchingStrategy {
     }
 }
 
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Deserr, ToSchema, Serialize)]
+#[deserr(rename_all = camelCase)]
+#[serde(rename_all = "camelCase")]
+pub enum QuerySyntax {
+    /// Words, quoted phrases and `-` negations
+    #[default]
+    Simple,
+    /// Also `+required` terms, `(a OR b)` groups, `field:word` field-scoped terms,
+    /// `te*`/`ke?ir` wildcard patterns and `/regex/` regular expressions
+    Advanced,
+}
+
+impl From<QuerySyntax> for milli::QuerySyntax {
+    fn from(other: QuerySyntax) -> Self {
+        match other {
+            QuerySyntax::Simple => Self::Simple,
+            QuerySyntax::Advanced => Self::Advanced,
+        }
+    }
+}
+
 #[derive(Debug, Default, Clone, PartialEq, Eq, Deserr)]
 #[deserr(rename_all = camelCase)]
 pub enum FacetValuesSort {
@@ -771,6 +907,15 @@ pub struct SearchResult {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub semantic_hit_count: Option<u32>,
 
+    /// Alternative queries built from the words of the index, when the query matches few documents
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub suggestions: Option<Vec<String>>,
+
+    /// The `userData` of the query rules matching the query, in the order of the rules
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    #[schema(value_type = Vec<Object>)]
+    pub user_data: Vec<Value>,
+
     // These fields are only used for analytics purposes
     #[serde(skip)]
     pub degraded: bool,
@@ -788,6 +933,8 @@ impl fmt::Debug for SearchResult {
             facet_distribution,
             facet_stats,
             semantic_hit_count,
+            suggestions,
+            user_data,
             degraded,
             used_negative_operator,
         } = self;
@@ -813,6 +960,12 @@ impl fmt::Debug for SearchResult {
         if let Some(semantic_hit_count) = semantic_hit_count {
             debug.field("semantic_hit_count", &semantic_hit_count);
         }
+        if let Some(suggestions) = suggestions {
+            debug.field("suggestions", &suggestions);
+        }
+        if !user_data.is_empty() {
+            debug.field("user_data", &user_data);
+        }
 
         debug.finish()
     }
@@ -828,6 +981,22 @@ pub struct SimilarResult {
     pub hits_info: HitsInfo,
 }
 
+#[derive(Serialize, Debug, Clone, PartialEq, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct AutocompleteResult {
+    pub completions: Vec<AutocompleteHit>,
+    pub query: String,
+    pub processing_time_ms: u128,
+}
+
+/// A completion of the query and the number of documents containing it
+#[derive(Serialize, Debug, Clone, PartialEq, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct AutocompleteHit {
+    pub value: String,
+    pub count: u64,
+}
+
 #[derive(Serialize, Debug, Clone, PartialEq, ToSchema)]
 #[serde(rename_all = "camelCase")]
 #[schema(rename_all = "camelCase")]
@@ -864,7 +1033,12 @@ pub struct FacetSearchResult {
 
 /// Incorporate search rules in search query
 pub fn add_search_rules(filter: &mut Option<Value>, rules: IndexSearchRules) {
-    *filter = match (filter.take(), rules.filter) {
+    *filter = fuse_filters(filter.take(), rules.filter);
+}
+
+/// Returns a filter matching the documents matched by both filters
+fn fuse_filters(filter: Option<Value>, rules_filter: Option<Value>) -> Option<Value> {
+    match (filter, rules_filter) {
         (None, rules_filter) => rules_filter,
         (filter, None) => filter,
         (Some(filter), Some(rules_filter)) => {
@@ -882,6 +1056,51 @@ pub fn add_search_rules(filter: &mut Option<Value>, rules: IndexSearchRules) {
     }
 }
 
+/// Alters the search with the query rules of the index matching its query.
+///
+/// Every matching rule adds its filter and sort criteria, but only the first one rewriting the
+/// query is applied. Returns the `userData` of the matching rules.
+fn apply_query_rules(
+    index: &Index,
+    rtxn: &RoTxn,
+    query: &mut SearchQuery,
+) -> Result<Vec<Value>, ResponseError> {
+    let Some(q) = &query.q else { return Ok(Vec::new()) };
+    let matchers = index.query_rule_matchers(rtxn)?;
+    if matchers.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let normalized_query = normalize_query(q);
+    let mut rewritten_query = None;
+    let mut rules_sort = Vec::new();
+    let mut user_data = Vec::new();
+    for matcher in matchers.into_iter().filter(|matcher| matcher.matches(&normalized_query)) {
+        let QueryRule {
+            match_kind: _,
+            pattern: _,
+            query: rule_query,
+            filter,
+            sort,
+            user_data: data,
+        } = matcher.rule;
+        rewritten_query = rewritten_query.or(rule_query);
+        query.filter = fuse_filters(query.filter.take(), filter.map(Value::String));
+        rules_sort.extend(sort.into_iter().flatten());
+        user_data.extend(data);
+    }
+
+    if rewritten_query.is_some() {
+        query.q = rewritten_query;
+    }
+    if !rules_sort.is_empty() {
+        rules_sort.extend(query.sort.take().into_iter().flatten());
+        query.sort = Some(rules_sort);
+    }
+
+    Ok(user_data)
+}
+
 fn prepare_search<'t>(
     index: &'t Index,
     rtxn: &'t RoTxn,
@@ -924,7 +1143,7 @@ fn prepare_search<'t>(
 
             search.semantic(embedder_name.clone(), embedder.clone(), *quantized, Some(vector));
         }
-        SearchKind::Hybrid { embedder_name, embedder, quantized, semantic_ratio: _ } => {
+        SearchKind::Hybrid { embedder_name, embedder, quantized, semantic_ratio: _, reranker } => {
             if let Some(q) = &query.q {
                 search.query(q);
             }
@@ -935,14 +1154,32 @@ fn prepare_search<'t>(
                 *quantized,
                 query.vector.clone(),
             );
+            if let Some((reranker, prompt)) = reranker {
+                search.rerank(reranker.clone(), prompt.clone());
+            }
         }
     }
 
+    if let Some(VectorSearchQuery { exhaustive, search_k }) = query.vector_search {
+        let search_k = search_k
+            .map(|search_k| {
+                NonZeroUsize::new(search_k).ok_or_else(|| {
+                    ResponseError::from_msg(
+                        "Invalid value at `.vectorSearch.searchK`: expected a positive integer, but found `0`".to_string(),
+                        Code::InvalidSearchVectorSearch,
+                    )
+                })
+            })
+            .transpose()?;
+        search.vector_search(milli::VectorSearchParams { exhaustive, search_k });
+    }
+
     if let Some(ref searchable) = query.attributes_to_search_on {
         search.searchable_attributes(searchable);
     }
 
     let is_finite_pagination = query.is_finite_pagination();
+    search.query_syntax(query.query_syntax.into());
     search.terms_matching_strategy(query.matching_strategy.into());
 
     let max_total_hits = index
@@ -982,6 +1219,10 @@ fn prepare_search<'t>(
     search.offset(offset);
     search.limit(limit);
 
+    if query.show_suggestions {
+        search.suggestions(SUGGESTIONS_LIMIT);
+    }
+
     if let Some(ref filter) = query.filter {
         if let Some(facets) = parse_filter(filter, Code::InvalidSearchFilter, features)? {
             search.filter(facets);
@@ -1009,13 +1250,17 @@ fn prepare_search<'t>(
 pub fn perform_search(
     index_uid: String,
     index: &Index,
-    query: SearchQuery,
+    mut query: SearchQuery,
     search_kind: SearchKind,
     retrieve_vectors: RetrieveVectors,
     features: RoFeatures,
 ) -> Result<SearchResult, ResponseError> {
     let before_search = Instant::now();
     let rtxn = index.read_txn()?;
+
+    // the response keeps the query of the user even when a query rule rewrites it
+    let original_query = query.q.clone();
+    let user_data = apply_query_rules(index, &rtxn, &mut query)?;
     let time_budget = match index.search_cutoff(&rtxn)? {
         Some(cutoff) => TimeBudget::new(Duration::from_millis(cutoff)),
         None => TimeBudget::default(),
@@ -1032,12 +1277,14 @@ pub fn perform_search(
             document_scores,
             degraded,
             used_negative_operator,
+            suggestions,
         },
         semantic_hit_count,
     ) = search_from_kind(index_uid, search_kind, search)?;
 
     let SearchQuery {
-        q,
+        // already used in prepare_search
+        q: _,
         limit,
         page,
         hits_per_page,
@@ -1050,6 +1297,7 @@ pub fn perform_search(
         show_matches_position,
         show_ranking_score,
         show_ranking_score_details,
+        show_suggestions,
         sort,
         facets,
         highlight_pre_tag,
@@ -1059,6 +1307,8 @@ pub fn perform_search(
         // already used in prepare_search
         vector: _,
         hybrid: _,
+        vector_search: _,
+        query_syntax: _,
         offset: _,
         ranking_score_threshold: _,
         matching_strategy: _,
@@ -1120,13 +1370,15 @@ pub fn perform_search(
     let result = SearchResult {
         hits: documents,
         hits_info,
-        query: q.unwrap_or_default(),
+        query: original_query.unwrap_or_default(),
         processing_time_ms: before_search.elapsed().as_millis(),
         facet_distribution,
         facet_stats,
         degraded,
         used_negative_operator,
         semantic_hit_count,
+        suggestions: show_suggestions.then_some(suggestions),
+        user_data,
     };
     Ok(result)
 }
@@ -1573,6 +1825,50 @@ pub fn perform_facet_search(
     })
 }
 
+pub fn perform_autocomplete(
+    index: &Index,
+    query: AutocompleteQuery,
+    features: RoFeatures,
+) -> Result<AutocompleteResult, ResponseError> {
+    let before_search = Instant::now();
+    let rtxn = index.read_txn()?;
+
+    let AutocompleteQuery { q, limit, filter, attributes_to_search_on } = query;
+    let q = q.unwrap_or_default();
+
+    let limit = limit.min(MAX_AUTOCOMPLETE_LIMIT);
+    let mut autocomplete = milli::Autocomplete::new(q.clone(), limit, index, &rtxn);
+
+    if let Some(ref filter) = filter {
+        if let Some(facets) = parse_filter(filter, Code::InvalidAutocompleteFilter, features)? {
+            autocomplete.filter(facets);
+        }
+    }
+
+    if let Some(ref attributes_to_search_on) = attributes_to_search_on {
+        autocomplete.searchable_attributes(attributes_to_search_on);
+    }
+
+    let completions = autocomplete.execute().map_err(|err| match err {
+        milli::Error::UserError(milli::UserError::InvalidFilter(_)) => {
+            ResponseError::from_msg(err.to_string(), Code::InvalidAutocompleteFilter)
+        }
+        milli::Error::UserError(milli::UserError::InvalidSearchableAttribute { .. }) => {
+            ResponseError::from_msg(err.to_string(), Code::InvalidAutocompleteAttributesToSearchOn)
+        }
+        err => err.into(),
+    })?;
+
+    Ok(AutocompleteResult {
+        completions: completions
+            .into_iter()
+            .map(|milli::Completion { value, count }| AutocompleteHit { value, count })
+            .collect(),
+        query: q,
+        processing_time_ms: before_search.elapsed().as_millis(),
+    })
+}
+
 pub fn perform_similar(
     index: &Index,
     query: SimilarQuery,
@@ -1640,6 +1936,7 @@ pub fn perform_similar(
         document_scores,
         degraded: _,
         used_negative_operator: _,
+        suggestions: _,
     } = similar.execute().map_err(|err| match err {
         milli::Error::UserError(milli::UserError::InvalidFilter(_)) => {
             ResponseError::from_msg(err.to_string(), Code::InvalidSimilarFilter)
@@ -16,6 +16,7 @@ pub static AUTHORIZATIONS: Lazy<HashMap<(&'static str, &'static str), HashSet<&'
             ("POST",    "/multi-search") =>                                    hashset!{"search", "*"},
             ("POST",    "/indexes/products/search") =>                         hashset!{"search", "*"},
             ("GET",     "/indexes/products/search") =>                         hashset!{"search", "*"},
+            ("POST",    "/indexes/products/autocomplete") =>                   hashset!{"search", "*"},
             ("POST",    "/indexes/products/documents") =>                      hashset!{"documents.add", "documents.*", "*"},
             ("GET",     "/indexes/products/documents") =>                      hashset!{"documents.get", "documents.*", "*"},
             ("POST",    "/indexes/products/documents/fetch") =>                hashset!{"documents.get", "documents.*", "*"},
@@ -25,6 +26,9 @@ pub static AUTHORIZATIONS: Lazy<HashMap<(&'static str, &'static str), HashSet<&'
             ("POST",    "/indexes/products/documents/delete") =>               hashset!{"documents.delete", "documents.*", "*"},
             ("GET",     "/tasks") =>                                           hashset!{"tasks.get", "tasks.*", "*"},
             ("DELETE",  "/tasks") =>                                           hashset!{"tasks.delete", "tasks.*", "*"},
+            ("POST",    "/tasks/retry") =>                                     hashset!{"tasks.retry", "tasks.*", "*"},
+            ("POST",    "/tasks/pause?indexUids=products") =>                  hashset!{"tasks.pause", "tasks.*", "*"},
+            ("POST",    "/tasks/resume?indexUids=products") =>                 hashset!{"tasks.resume", "tasks.*", "*"},
             ("GET",     "/tasks?indexUid=products") =>                         hashset!{"tasks.get", "tasks.*", "*"},
             ("GET",     "/tasks/0") =>                                         hashset!{"tasks.get", "tasks.*", "*"},
             ("PATCH",   "/indexes/products/") =>                               hashset!{"indexes.update", "indexes.*", "*"},
@@ -70,6 +74,14 @@ pub static AUTHORIZATIONS: Lazy<HashMap<(&'static str, &'static str), HashSet<&'
             ("PATCH",   "/experimental-features") =>                           hashset!{"experimental.update", "*"},
             ("GET",   "/network") =>                                           hashset!{"network.get", "*"},
             ("PATCH",   "/network") =>                                         hashset!{"network.update", "*"},
+            ("GET",     "/embedding-cache") =>                                 hashset!{"experimental.get", "*"},
+            ("DELETE",  "/embedding-cache") =>                                 hashset!{"experimental.update", "*"},
+            ("GET",     "/webhooks") =>                                        hashset!{"webhooks.get", "*"},
+            ("POST",    "/webhooks") =>                                        hashset!{"webhooks.create", "*"},
+            ("GET",     "/webhooks/mywebhook") =>                              hashset!{"webhooks.get", "*"},
+            ("PATCH",   "/webhooks/mywebhook") =>                              hashset!{"webhooks.update", "*"},
+            ("DELETE",  "/webhooks/mywebhook") =>                              hashset!{"webhooks.delete", "*"},
+            ("GET",     "/webhooks/mywebhook/deliveries") =>                   hashset!{"webhooks.get", "*"},
         };
 
         authorizations
@@ -520,6 +520,16 @@ impl<State> Index<'_, State> {
         self.service.post_encoded(url, query, self.encoder).await
     }
 
+    pub async fn autocomplete(&self, query: Value) -> (Value, StatusCode) {
+        let url = format!("/indexes/{}/autocomplete", urlencode(self.uid.as_ref()));
+        self.service.post_encoded(url, query, self.encoder).await
+    }
+
+    pub async fn register_task_group(&self, body: Value) -> (Value, StatusCode) {
+        let url = format!("/indexes/{}/task-groups", urlencode(self.uid.as_ref()));
+        self.service.post_encoded(url, body, self.encoder).await
+    }
+
     pub async fn get_distinct_attribute(&self) -> (Value, StatusCode) {
         let url =
             format!("/indexes/{}/settings/{}", urlencode(self.uid.as_ref()), "distinct-attribute");
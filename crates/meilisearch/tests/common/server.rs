@@ -392,6 +392,18 @@ impl<State> Server<State> {
         self.service.post(format!("/tasks/cancel?{}", value), json!(null)).await
     }
 
+    pub async fn retry_tasks(&self, value: &str) -> (Value, StatusCode) {
+        self.service.post(format!("/tasks/retry?{}", value), json!(null)).await
+    }
+
+    pub async fn pause_tasks(&self, value: &str) -> (Value, StatusCode) {
+        self.service.post(format!("/tasks/pause?{}", value), json!(null)).await
+    }
+
+    pub async fn resume_tasks(&self, value: &str) -> (Value, StatusCode) {
+        self.service.post(format!("/tasks/resume?{}", value), json!(null)).await
+    }
+
     pub async fn delete_tasks(&self, value: &str) -> (Value, StatusCode) {
         self.service.delete(format!("/tasks?{}", value)).await
     }
@@ -441,6 +453,38 @@ impl<State> Server<State> {
     pub async fn get_network(&self) -> (Value, StatusCode) {
         self.service.get("/network").await
     }
+
+    pub async fn get_embedding_cache(&self) -> (Value, StatusCode) {
+        self.service.get("/embedding-cache").await
+    }
+
+    pub async fn delete_embedding_cache(&self) -> (Value, StatusCode) {
+        self.service.delete("/embedding-cache").await
+    }
+
+    pub async fn create_webhook(&self, value: Value) -> (Value, StatusCode) {
+        self.service.post("/webhooks", value).await
+    }
+
+    pub async fn get_webhooks(&self) -> (Value, StatusCode) {
+        self.service.get("/webhooks").await
+    }
+
+    pub async fn get_webhook(&self, uuid: impl AsRef<str>) -> (Value, StatusCode) {
+        self.service.get(format!("/webhooks/{}", uuid.as_ref())).await
+    }
+
+    pub async fn patch_webhook(&self, uuid: impl AsRef<str>, value: Value) -> (Value, StatusCode) {
+        self.service.patch(format!("/webhooks/{}", uuid.as_ref()), value).await
+    }
+
+    pub async fn delete_webhook(&self, uuid: impl AsRef<str>) -> (Value, StatusCode) {
+        self.service.delete(format!("/webhooks/{}", uuid.as_ref())).await
+    }
+
+    pub async fn get_webhook_deliveries(&self, uuid: impl AsRef<str>) -> (Value, StatusCode) {
+        self.service.get(format!("/webhooks/{}/deliveries", uuid.as_ref())).await
+    }
 }
 
 pub fn default_settings(dir: impl AsRef<Path>) -> Opt {
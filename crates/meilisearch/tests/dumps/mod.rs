@@ -88,7 +88,8 @@ async fn import_dump_v1_movie_raw() {
         },
         "disableOnWords": [],
         "disableOnAttributes": [],
-        "disableOnNumbers": false
+        "disableOnNumbers": false,
+        "perAttribute": {}
       },
       "faceting": {
         "maxValuesPerFacet": 100,
@@ -103,7 +104,12 @@ async fn import_dump_v1_movie_raw() {
       "searchCutoffMs": null,
       "localizedAttributes": null,
       "facetSearch": true,
-      "prefixSearch": "indexingTime"
+      "prefixSearch": "indexingTime",
+      "stemming": [],
+      "synonymRules": [],
+      "searchableAttributesWeights": {},
+      "ngramAttributes": {},
+      "queryRules": []
     }
     "###
     );
@@ -262,7 +268,8 @@ async fn import_dump_v1_movie_with_settings() {
         },
         "disableOnWords": [],
         "disableOnAttributes": [],
-        "disableOnNumbers": false
+        "disableOnNumbers": false,
+        "perAttribute": {}
       },
       "faceting": {
         "maxValuesPerFacet": 100,
@@ -277,7 +284,12 @@ async fn import_dump_v1_movie_with_settings() {
       "searchCutoffMs": null,
       "localizedAttributes": null,
       "facetSearch": true,
-      "prefixSearch": "indexingTime"
+      "prefixSearch": "indexingTime",
+      "stemming": [],
+      "synonymRules": [],
+      "searchableAttributesWeights": {},
+      "ngramAttributes": {},
+      "queryRules": []
     }
     "###
     );
@@ -435,7 +447,8 @@ async fn import_dump_v1_rubygems_with_settings() {
         },
         "disableOnWords": [],
         "disableOnAttributes": [],
-        "disableOnNumbers": false
+        "disableOnNumbers": false,
+        "perAttribute": {}
       },
       "faceting": {
         "maxValuesPerFacet": 100,
@@ -450,7 +463,12 @@ async fn import_dump_v1_rubygems_with_settings() {
       "searchCutoffMs": null,
       "localizedAttributes": null,
       "facetSearch": true,
-      "prefixSearch": "indexingTime"
+      "prefixSearch": "indexingTime",
+      "stemming": [],
+      "synonymRules": [],
+      "searchableAttributesWeights": {},
+      "ngramAttributes": {},
+      "queryRules": []
     }
     "###
     );
@@ -594,7 +612,8 @@ async fn import_dump_v2_movie_raw() {
         },
         "disableOnWords": [],
         "disableOnAttributes": [],
-        "disableOnNumbers": false
+        "disableOnNumbers": false,
+        "perAttribute": {}
       },
       "faceting": {
         "maxValuesPerFacet": 100,
@@ -609,7 +628,12 @@ async fn import_dump_v2_movie_raw() {
       "searchCutoffMs": null,
       "localizedAttributes": null,
       "facetSearch": true,
-      "prefixSearch": "indexingTime"
+      "prefixSearch": "indexingTime",
+      "stemming": [],
+      "synonymRules": [],
+      "searchableAttributesWeights": {},
+      "ngramAttributes": {},
+      "queryRules": []
     }
     "###
     );
@@ -765,7 +789,8 @@ async fn import_dump_v2_movie_with_settings() {
         },
         "disableOnWords": [],
         "disableOnAttributes": [],
-        "disableOnNumbers": false
+        "disableOnNumbers": false,
+        "perAttribute": {}
       },
       "faceting": {
         "maxValuesPerFacet": 100,
@@ -780,7 +805,12 @@ async fn import_dump_v2_movie_with_settings() {
       "searchCutoffMs": null,
       "localizedAttributes": null,
       "facetSearch": true,
-      "prefixSearch": "indexingTime"
+      "prefixSearch": "indexingTime",
+      "stemming": [],
+      "synonymRules": [],
+      "searchableAttributesWeights": {},
+      "ngramAttributes": {},
+      "queryRules": []
     }
     "###
     );
@@ -935,7 +965,8 @@ async fn import_dump_v2_rubygems_with_settings() {
         },
         "disableOnWords": [],
         "disableOnAttributes": [],
-        "disableOnNumbers": false
+        "disableOnNumbers": false,
+        "perAttribute": {}
       },
       "faceting": {
         "maxValuesPerFacet": 100,
@@ -950,7 +981,12 @@ async fn import_dump_v2_rubygems_with_settings() {
       "searchCutoffMs": null,
       "localizedAttributes": null,
       "facetSearch": true,
-      "prefixSearch": "indexingTime"
+      "prefixSearch": "indexingTime",
+      "stemming": [],
+      "synonymRules": [],
+      "searchableAttributesWeights": {},
+      "ngramAttributes": {},
+      "queryRules": []
     }
     "###
     );
@@ -1094,7 +1130,8 @@ async fn import_dump_v3_movie_raw() {
         },
         "disableOnWords": [],
         "disableOnAttributes": [],
-        "disableOnNumbers": false
+        "disableOnNumbers": false,
+        "perAttribute": {}
       },
       "faceting": {
         "maxValuesPerFacet": 100,
@@ -1109,7 +1146,12 @@ async fn import_dump_v3_movie_raw() {
       "searchCutoffMs": null,
       "localizedAttributes": null,
       "facetSearch": true,
-      "prefixSearch": "indexingTime"
+      "prefixSearch": "indexingTime",
+      "stemming": [],
+      "synonymRules": [],
+      "searchableAttributesWeights": {},
+      "ngramAttributes": {},
+      "queryRules": []
     }
     "###
     );
@@ -1265,7 +1307,8 @@ async fn import_dump_v3_movie_with_settings() {
         },
         "disableOnWords": [],
         "disableOnAttributes": [],
-        "disableOnNumbers": false
+        "disableOnNumbers": false,
+        "perAttribute": {}
       },
       "faceting": {
         "maxValuesPerFacet": 100,
@@ -1280,7 +1323,12 @@ async fn import_dump_v3_movie_with_settings() {
       "searchCutoffMs": null,
       "localizedAttributes": null,
       "facetSearch": true,
-      "prefixSearch": "indexingTime"
+      "prefixSearch": "indexingTime",
+      "stemming": [],
+      "synonymRules": [],
+      "searchableAttributesWeights": {},
+      "ngramAttributes": {},
+      "queryRules": []
     }
     "###
     );
@@ -1435,7 +1483,8 @@ async fn import_dump_v3_rubygems_with_settings() {
         },
         "disableOnWords": [],
         "disableOnAttributes": [],
-        "disableOnNumbers": false
+        "disableOnNumbers": false,
+        "perAttribute": {}
       },
       "faceting": {
         "maxValuesPerFacet": 100,
@@ -1450,7 +1499,12 @@ async fn import_dump_v3_rubygems_with_settings() {
       "searchCutoffMs": null,
       "localizedAttributes": null,
       "facetSearch": true,
-      "prefixSearch": "indexingTime"
+      "prefixSearch": "indexingTime",
+      "stemming": [],
+      "synonymRules": [],
+      "searchableAttributesWeights": {},
+      "ngramAttributes": {},
+      "queryRules": []
     }
     "###
     );
@@ -1594,7 +1648,8 @@ async fn import_dump_v4_movie_raw() {
         },
         "disableOnWords": [],
         "disableOnAttributes": [],
-        "disableOnNumbers": false
+        "disableOnNumbers": false,
+        "perAttribute": {}
       },
       "faceting": {
         "maxValuesPerFacet": 100,
@@ -1609,7 +1664,12 @@ async fn import_dump_v4_movie_raw() {
       "searchCutoffMs": null,
       "localizedAttributes": null,
       "facetSearch": true,
-      "prefixSearch": "indexingTime"
+      "prefixSearch": "indexingTime",
+      "stemming": [],
+      "synonymRules": [],
+      "searchableAttributesWeights": {},
+      "ngramAttributes": {},
+      "queryRules": []
     }
     "###
     );
@@ -1765,7 +1825,8 @@ async fn import_dump_v4_movie_with_settings() {
         },
         "disableOnWords": [],
         "disableOnAttributes": [],
-        "disableOnNumbers": false
+        "disableOnNumbers": false,
+        "perAttribute": {}
       },
       "faceting": {
         "maxValuesPerFacet": 100,
@@ -1780,7 +1841,12 @@ async fn import_dump_v4_movie_with_settings() {
       "searchCutoffMs": null,
       "localizedAttributes": null,
       "facetSearch": true,
-      "prefixSearch": "indexingTime"
+      "prefixSearch": "indexingTime",
+      "stemming": [],
+      "synonymRules": [],
+      "searchableAttributesWeights": {},
+      "ngramAttributes": {},
+      "queryRules": []
     }
     "###
     );
@@ -1935,7 +2001,8 @@ async fn import_dump_v4_rubygems_with_settings() {
         },
         "disableOnWords": [],
         "disableOnAttributes": [],
-        "disableOnNumbers": false
+        "disableOnNumbers": false,
+        "perAttribute": {}
       },
       "faceting": {
         "maxValuesPerFacet": 100,
@@ -1950,7 +2017,12 @@ async fn import_dump_v4_rubygems_with_settings() {
       "searchCutoffMs": null,
       "localizedAttributes": null,
       "facetSearch": true,
-      "prefixSearch": "indexingTime"
+      "prefixSearch": "indexingTime",
+      "stemming": [],
+      "synonymRules": [],
+      "searchableAttributesWeights": {},
+      "ngramAttributes": {},
+      "queryRules": []
     }
     "###
     );
@@ -2225,7 +2297,8 @@ async fn import_dump_v6_containing_experimental_features() {
         },
         "disableOnWords": [],
         "disableOnAttributes": [],
-        "disableOnNumbers": false
+        "disableOnNumbers": false,
+        "perAttribute": {}
       },
       "faceting": {
         "maxValuesPerFacet": 100,
@@ -2240,7 +2313,12 @@ async fn import_dump_v6_containing_experimental_features() {
       "searchCutoffMs": null,
       "localizedAttributes": null,
       "facetSearch": true,
-      "prefixSearch": "indexingTime"
+      "prefixSearch": "indexingTime",
+      "stemming": [],
+      "synonymRules": [],
+      "searchableAttributesWeights": {},
+      "ngramAttributes": {},
+      "queryRules": []
     }
     "###);
 
@@ -2458,7 +2536,8 @@ async fn generate_and_import_dump_containing_vectors() {
         },
         "disableOnWords": [],
         "disableOnAttributes": [],
-        "disableOnNumbers": false
+        "disableOnNumbers": false,
+        "perAttribute": {}
       },
       "faceting": {
         "maxValuesPerFacet": 100,
@@ -2482,7 +2561,12 @@ async fn generate_and_import_dump_containing_vectors() {
       "searchCutoffMs": null,
       "localizedAttributes": null,
       "facetSearch": true,
-      "prefixSearch": "indexingTime"
+      "prefixSearch": "indexingTime",
+      "stemming": [],
+      "synonymRules": [],
+      "searchableAttributesWeights": {},
+      "ngramAttributes": {},
+      "queryRules": []
     }
     "###);
 
@@ -17,6 +17,7 @@ mod swap_indexes;
 mod tasks;
 mod upgrade;
 mod vector;
+mod webhooks;
 
 // Tests are isolated by features in different modules to allow better readability, test
 // targetability, and improved incremental compilation times.
@@ -0,0 +1,174 @@
+use meili_snap::{json_string, snapshot};
+use once_cell::sync::Lazy;
+
+use crate::common::index::Index;
+use crate::common::{Server, Value};
+use crate::json;
+
+async fn index_with_documents<'a>(server: &'a Server, documents: &Value) -> Index<'a> {
+    let index = server.index("test");
+
+    let (task, _status_code) = index.update_settings_filterable_attributes(json!(["genre"])).await;
+    index.wait_task(task.uid()).await.succeeded();
+    let (task, _status_code) = index.add_documents(documents.clone(), None).await;
+    index.wait_task(task.uid()).await.succeeded();
+    index
+}
+
+static DOCUMENTS: Lazy<Value> = Lazy::new(|| {
+    json!([
+        {
+            "id": "1",
+            "title": "Captain Marvel",
+            "genre": "action",
+        },
+        {
+            "id": "2",
+            "title": "Captain Planet",
+            "genre": "cartoon",
+        },
+        {
+            "id": "3",
+            "title": "Shazam",
+            "description": "a Captain Marvel ersatz",
+            "genre": "action",
+        },
+        {
+            "id": "4",
+            "title": "Marvel Cinematic Universe",
+            "genre": "action",
+        },
+        {
+            "id": "5",
+            "title": "Captain America",
+            "genre": "action",
+        },
+    ])
+});
+
+#[actix_rt::test]
+async fn autocomplete_words_and_phrases() {
+    let server = Server::new().await;
+    let index = index_with_documents(&server, &DOCUMENTS).await;
+
+    let (response, code) = index.autocomplete(json!({"q": "ca"})).await;
+    snapshot!(code, @"200 OK");
+    snapshot!(json_string!(response, { ".processingTimeMs" => "[time]" }), @r###"
+    {
+      "completions": [
+        {
+          "value": "captain",
+          "count": 4
+        },
+        {
+          "value": "cartoon",
+          "count": 1
+        }
+      ],
+      "query": "ca",
+      "processingTimeMs": "[time]"
+    }
+    "###);
+
+    // the completion must directly follow the previous word
+    let (response, code) = index.autocomplete(json!({"q": "Captain Ma"})).await;
+    snapshot!(code, @"200 OK");
+    snapshot!(json_string!(response["completions"]), @r###"
+    [
+      {
+        "value": "captain marvel",
+        "count": 2
+      }
+    ]
+    "###);
+
+    let (response, code) = index.autocomplete(json!({"q": "c", "limit": 1})).await;
+    snapshot!(code, @"200 OK");
+    snapshot!(json_string!(response["completions"]), @r###"
+    [
+      {
+        "value": "captain",
+        "count": 4
+      }
+    ]
+    "###);
+
+    let (response, code) = index.autocomplete(json!({"q": "batm"})).await;
+    snapshot!(code, @"200 OK");
+    snapshot!(json_string!(response["completions"]), @"[]");
+}
+
+#[actix_rt::test]
+async fn autocomplete_with_filter_and_attributes_to_search_on() {
+    let server = Server::new().await;
+    let index = index_with_documents(&server, &DOCUMENTS).await;
+
+    let (response, code) = index.autocomplete(json!({"q": "c", "filter": "genre = action"})).await;
+    snapshot!(code, @"200 OK");
+    snapshot!(json_string!(response["completions"]), @r###"
+    [
+      {
+        "value": "captain",
+        "count": 3
+      },
+      {
+        "value": "cinematic",
+        "count": 1
+      }
+    ]
+    "###);
+
+    let (response, code) =
+        index.autocomplete(json!({"q": "c", "attributesToSearchOn": ["title"]})).await;
+    snapshot!(code, @"200 OK");
+    snapshot!(json_string!(response["completions"]), @r###"
+    [
+      {
+        "value": "captain",
+        "count": 3
+      },
+      {
+        "value": "cinematic",
+        "count": 1
+      }
+    ]
+    "###);
+
+    // the phrase of the description is ignored
+    let (response, code) =
+        index.autocomplete(json!({"q": "Captain Ma", "attributesToSearchOn": ["title"]})).await;
+    snapshot!(code, @"200 OK");
+    snapshot!(json_string!(response["completions"]), @r###"
+    [
+      {
+        "value": "captain marvel",
+        "count": 1
+      }
+    ]
+    "###);
+}
+
+#[actix_rt::test]
+async fn autocomplete_bad_parameters() {
+    let server = Server::new().await;
+    let index = index_with_documents(&server, &DOCUMENTS).await;
+
+    let (response, code) = index.autocomplete(json!({"q": "ca", "limit": "hello"})).await;
+    snapshot!(code, @"400 Bad Request");
+    snapshot!(json_string!(response), @r###"
+    {
+      "message": "Invalid value type at `.limit`: expected a positive integer, but found a string: `\"hello\"`",
+      "code": "invalid_autocomplete_limit",
+      "type": "invalid_request",
+      "link": "https://docs.meilisearch.com/errors#invalid_autocomplete_limit"
+    }
+    "###);
+
+    let (response, code) = index.autocomplete(json!({"q": "ca", "filter": "title = Shazam"})).await;
+    snapshot!(code, @"400 Bad Request");
+    snapshot!(response["code"], @r###""invalid_autocomplete_filter""###);
+
+    let (response, code) = index.autocomplete(json!({"q": "ca", "offset": 2})).await;
+    snapshot!(code, @"400 Bad Request");
+    snapshot!(response["code"], @r###""bad_request""###);
+}
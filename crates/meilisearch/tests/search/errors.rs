@@ -368,6 +368,34 @@ async fn search_bad_show_matches_position() {
     "###);
 }
 
+#[actix_rt::test]
+async fn search_bad_show_suggestions() {
+    let server = Server::new_shared();
+    let index = server.unique_index();
+
+    let (response, code) = index.search_post(json!({"showSuggestions": "doggo"})).await;
+    snapshot!(code, @"400 Bad Request");
+    snapshot!(json_string!(response), @r###"
+    {
+      "message": "Invalid value type at `.showSuggestions`: expected a boolean, but found a string: `\"doggo\"`",
+      "code": "invalid_search_show_suggestions",
+      "type": "invalid_request",
+      "link": "https://docs.meilisearch.com/errors#invalid_search_show_suggestions"
+    }
+    "###);
+
+    let (response, code) = index.search_get("?showSuggestions=doggo").await;
+    snapshot!(code, @"400 Bad Request");
+    snapshot!(json_string!(response), @r###"
+    {
+      "message": "Invalid value in parameter `showSuggestions`: could not parse `doggo` as a boolean, expected either `true` or `false`",
+      "code": "invalid_search_show_suggestions",
+      "type": "invalid_request",
+      "link": "https://docs.meilisearch.com/errors#invalid_search_show_suggestions"
+    }
+    "###);
+}
+
 #[actix_rt::test]
 async fn search_bad_facets() {
     let server = Server::new_shared();
@@ -636,6 +664,34 @@ async fn search_bad_matching_strategy() {
     "###);
 }
 
+#[actix_rt::test]
+async fn search_bad_query_syntax() {
+    let server = Server::new_shared();
+    let index = server.unique_index();
+
+    let (response, code) = index.search_post(json!({"querySyntax": "doggo"})).await;
+    snapshot!(code, @"400 Bad Request");
+    snapshot!(json_string!(response), @r###"
+    {
+      "message": "Unknown value `doggo` at `.querySyntax`: expected one of `simple`, `advanced`",
+      "code": "invalid_search_query_syntax",
+      "type": "invalid_request",
+      "link": "https://docs.meilisearch.com/errors#invalid_search_query_syntax"
+    }
+    "###);
+
+    let (response, code) = index.search_get("?querySyntax=doggo").await;
+    snapshot!(code, @"400 Bad Request");
+    snapshot!(json_string!(response), @r###"
+    {
+      "message": "Unknown value `doggo` for parameter `querySyntax`: expected one of `simple`, `advanced`",
+      "code": "invalid_search_query_syntax",
+      "type": "invalid_request",
+      "link": "https://docs.meilisearch.com/errors#invalid_search_query_syntax"
+    }
+    "###);
+}
+
 #[actix_rt::test]
 async fn filter_invalid_syntax_object() {
     test_settings_documents_indexing_swapping_and_search(
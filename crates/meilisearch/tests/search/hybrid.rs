@@ -368,6 +368,64 @@ async fn invalid_semantic_ratio() {
     "###);
 }
 
+#[actix_rt::test]
+async fn vector_search() {
+    let server = Server::new().await;
+    let index = index_with_documents_user_provided(&server, &SIMPLE_SEARCH_DOCUMENTS_VEC).await;
+
+    // exhaustive and tree searches return the same scores
+    for vector_search in [
+        json!({"exhaustive": true}),
+        json!({"exhaustive": false}),
+        json!({"exhaustive": false, "searchK": 1000}),
+    ] {
+        let (response, code) = index
+            .search_post(json!({"vector": [1.0, 0.0], "hybrid": {"embedder": "default", "semanticRatio": 1.0}, "vectorSearch": vector_search, "showRankingScore": true}))
+            .await;
+        snapshot!(code, @"200 OK");
+        snapshot!(response["hits"], @r###"[{"title":"Captain Marvel","desc":"a Shazam ersatz","id":"3","_rankingScore":0.7773500680923462},{"title":"Captain Planet","desc":"He's not part of the Marvel Cinematic Universe","id":"2","_rankingScore":0.7236068248748779},{"title":"Shazam!","desc":"a Captain Marvel ersatz","id":"1","_rankingScore":0.6581138968467712}]"###);
+    }
+
+    let (response, code) = index
+        .search_post(json!({"vector": [1.0, 0.0], "hybrid": {"embedder": "default", "semanticRatio": 1.0}, "vectorSearch": {"searchK": 0}}))
+        .await;
+    snapshot!(code, @"400 Bad Request");
+    snapshot!(response, @r###"
+    {
+      "message": "Invalid value at `.vectorSearch.searchK`: expected a positive integer, but found `0`",
+      "code": "invalid_search_vector_search",
+      "type": "invalid_request",
+      "link": "https://docs.meilisearch.com/errors#invalid_search_vector_search"
+    }
+    "###);
+
+    let (response, code) = index
+        .search_post(json!({"vector": [1.0, 0.0], "hybrid": {"embedder": "default", "semanticRatio": 1.0}, "vectorSearch": {"exhaustive": "doggo"}}))
+        .await;
+    snapshot!(code, @"400 Bad Request");
+    snapshot!(response, @r###"
+    {
+      "message": "Invalid value type at `.vectorSearch.exhaustive`: expected a boolean, but found a string: `\"doggo\"`",
+      "code": "invalid_search_vector_search",
+      "type": "invalid_request",
+      "link": "https://docs.meilisearch.com/errors#invalid_search_vector_search"
+    }
+    "###);
+
+    let (response, code) = index
+        .search_post(json!({"vector": [1.0, 0.0], "hybrid": {"embedder": "default", "semanticRatio": 1.0}, "vectorSearch": {"doggo": true}}))
+        .await;
+    snapshot!(code, @"400 Bad Request");
+    snapshot!(response, @r###"
+    {
+      "message": "Unknown field `doggo` inside `.vectorSearch`: expected one of `exhaustive`, `searchK`",
+      "code": "invalid_search_vector_search",
+      "type": "invalid_request",
+      "link": "https://docs.meilisearch.com/errors#invalid_search_vector_search"
+    }
+    "###);
+}
+
 #[actix_rt::test]
 async fn single_document() {
     let server = Server::new().await;
@@ -1,6 +1,7 @@
 // This module contains all the test concerning search. Each particular feature of the search
 // should be tested in its own module to isolate tests and keep the tests readable.
 
+mod autocomplete;
 mod distinct;
 mod errors;
 mod facet_search;
@@ -13,8 +14,10 @@ mod locales;
 mod matching_strategy;
 mod multi;
 mod pagination;
+mod query_syntax;
 mod restrict_searchable;
 mod search_queue;
+mod suggestions;
 
 use meili_snap::{json_string, snapshot};
 
@@ -0,0 +1,235 @@
+use meili_snap::{json_string, snapshot};
+use once_cell::sync::Lazy;
+
+use crate::common::index::Index;
+use crate::common::{Server, Value};
+use crate::json;
+
+async fn index_with_documents<'a>(server: &'a Server, documents: &Value) -> Index<'a> {
+    let index = server.index("test");
+
+    let (task, _status_code) = index.add_documents(documents.clone(), None).await;
+    index.wait_task(task.uid()).await.succeeded();
+    index
+}
+
+static DOCUMENTS: Lazy<Value> = Lazy::new(|| {
+    json!([
+        {
+            "id": "1",
+            "title": "Captain Marvel",
+            "description": "a hero from space",
+        },
+        {
+            "id": "2",
+            "title": "Captain Planet",
+            "description": "a hero from the planet",
+        },
+        {
+            "id": "3",
+            "title": "Shazam",
+            "description": "a Captain Marvel ersatz",
+        },
+        {
+            "id": "4",
+            "title": "Marvel Cinematic Universe",
+            "description": "not a captain",
+        },
+    ])
+});
+
+fn sorted_ids(response: &Value) -> String {
+    let mut ids: Vec<_> = response["hits"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|hit| hit["id"].as_str().unwrap())
+        .collect();
+    ids.sort_unstable();
+    format!("{ids:?}")
+}
+
+#[actix_rt::test]
+async fn required_terms() {
+    let server = Server::new().await;
+    let index = index_with_documents(&server, &DOCUMENTS).await;
+
+    index
+        .search(json!({"q": "+planet captain", "querySyntax": "advanced"}), |response, code| {
+            snapshot!(code, @"200 OK");
+            snapshot!(sorted_ids(&response), @r###"["2"]"###);
+        })
+        .await;
+
+    // with the default syntax, the `+` is only a separator
+    index
+        .search(json!({"q": "+planet captain"}), |response, code| {
+            snapshot!(code, @"200 OK");
+            snapshot!(sorted_ids(&response), @r###"["1", "2", "3", "4"]"###);
+        })
+        .await;
+}
+
+#[actix_rt::test]
+async fn or_groups() {
+    let server = Server::new().await;
+    let index = index_with_documents(&server, &DOCUMENTS).await;
+
+    index
+        .search(json!({"q": "(shazam OR planet)", "querySyntax": "advanced"}), |response, code| {
+            snapshot!(code, @"200 OK");
+            snapshot!(sorted_ids(&response), @r###"["2", "3"]"###);
+        })
+        .await;
+
+    index
+        .search(
+            json!({"q": "+(shazam OR \"hero from space\")", "querySyntax": "advanced"}),
+            |response, code| {
+                snapshot!(code, @"200 OK");
+                snapshot!(sorted_ids(&response), @r###"["1", "3"]"###);
+            },
+        )
+        .await;
+}
+
+#[actix_rt::test]
+async fn field_scoped_terms() {
+    let server = Server::new().await;
+    let index = index_with_documents(&server, &DOCUMENTS).await;
+
+    index
+        .search(json!({"q": "description:captain", "querySyntax": "advanced"}), |response, code| {
+            snapshot!(code, @"200 OK");
+            snapshot!(sorted_ids(&response), @r###"["3", "4"]"###);
+        })
+        .await;
+
+    index
+        .search(
+            json!({"q": "title:(planet OR shazam)", "querySyntax": "advanced"}),
+            |response, code| {
+                snapshot!(code, @"200 OK");
+                snapshot!(sorted_ids(&response), @r###"["2", "3"]"###);
+            },
+        )
+        .await;
+
+    // a prefix that is not a searchable attribute is part of the term
+    index
+        .search(json!({"q": "captain:marvel", "querySyntax": "advanced"}), |response, code| {
+            snapshot!(code, @"200 OK");
+            snapshot!(sorted_ids(&response), @r###"["1", "3"]"###);
+        })
+        .await;
+}
+
+#[actix_rt::test]
+async fn malformed_query() {
+    let server = Server::new().await;
+    let index = index_with_documents(&server, &DOCUMENTS).await;
+
+    index
+        .search(json!({"q": "(captain OR", "querySyntax": "advanced"}), |response, code| {
+            snapshot!(code, @"400 Bad Request");
+            snapshot!(json_string!(response), @r###"
+            {
+              "message": "Invalid advanced query syntax: the parenthesis at position 0 is never closed.",
+              "code": "invalid_search_q",
+              "type": "invalid_request",
+              "link": "https://docs.meilisearch.com/errors#invalid_search_q"
+            }
+            "###);
+        })
+        .await;
+
+    index
+        .search(json!({"q": "captain OR planet", "querySyntax": "advanced"}), |response, code| {
+            snapshot!(code, @"400 Bad Request");
+            snapshot!(json_string!(response), @r###"
+            {
+              "message": "Invalid advanced query syntax: the `OR` at position 8 must be inside a group, such as `(a OR b)`.",
+              "code": "invalid_search_q",
+              "type": "invalid_request",
+              "link": "https://docs.meilisearch.com/errors#invalid_search_q"
+            }
+            "###);
+        })
+        .await;
+}
+
+#[actix_rt::test]
+async fn wildcard_and_regex_terms() {
+    let server = Server::new().await;
+    let index = index_with_documents(&server, &DOCUMENTS).await;
+
+    index
+        .search(json!({"q": "plan* *zam", "querySyntax": "advanced"}), |response, code| {
+            snapshot!(code, @"200 OK");
+            snapshot!(sorted_ids(&response), @r###"["2", "3"]"###);
+        })
+        .await;
+
+    index
+        .search(json!({"q": "+M?rvel", "querySyntax": "advanced"}), |response, code| {
+            snapshot!(code, @"200 OK");
+            snapshot!(sorted_ids(&response), @r###"["1", "3", "4"]"###);
+        })
+        .await;
+
+    index
+        .search(json!({"q": "+/(shaz|plan).*/", "querySyntax": "advanced"}), |response, code| {
+            snapshot!(code, @"200 OK");
+            snapshot!(sorted_ids(&response), @r###"["2", "3"]"###);
+        })
+        .await;
+
+    index
+        .search(json!({"q": "+cap* -/marv.l/", "querySyntax": "advanced"}), |response, code| {
+            snapshot!(code, @"200 OK");
+            snapshot!(sorted_ids(&response), @r###"["2"]"###);
+        })
+        .await;
+
+    // with the default syntax, the wildcards are only separators
+    index
+        .search(json!({"q": "plan*"}), |response, code| {
+            snapshot!(code, @"200 OK");
+            snapshot!(sorted_ids(&response), @r###"["2"]"###);
+        })
+        .await;
+}
+
+#[actix_rt::test]
+async fn invalid_regex_term() {
+    let server = Server::new().await;
+    let index = index_with_documents(&server, &DOCUMENTS).await;
+
+    index
+        .search(json!({"q": "/plan(/", "querySyntax": "advanced"}), |response, code| {
+            snapshot!(code, @"400 Bad Request");
+            snapshot!(response["code"], @r###""invalid_search_q""###);
+            let message = response["message"].as_str().unwrap();
+            assert!(
+                message.starts_with(
+                    "Invalid advanced query syntax: the regular expression `/plan(/` is invalid:"
+                ),
+                "{message}"
+            );
+        })
+        .await;
+
+    index
+        .search(json!({"q": "/plan", "querySyntax": "advanced"}), |response, code| {
+            snapshot!(code, @"400 Bad Request");
+            snapshot!(json_string!(response), @r###"
+            {
+              "message": "Invalid advanced query syntax: the regular expression at position 0 is never closed by a `/`.",
+              "code": "invalid_search_q",
+              "type": "invalid_request",
+              "link": "https://docs.meilisearch.com/errors#invalid_search_q"
+            }
+            "###);
+        })
+        .await;
+}
@@ -0,0 +1,103 @@
+use meili_snap::{json_string, snapshot};
+use once_cell::sync::Lazy;
+
+use crate::common::index::Index;
+use crate::common::{Server, Value};
+use crate::json;
+
+async fn index_with_documents<'a>(server: &'a Server, documents: &Value) -> Index<'a> {
+    let index = server.index("test");
+
+    let (task, _status_code) = index.add_documents(documents.clone(), None).await;
+    index.wait_task(task.uid()).await.succeeded();
+    index
+}
+
+static DOCUMENTS: Lazy<Value> = Lazy::new(|| {
+    json!([
+        {
+            "id": "1",
+            "title": "Captain Marvel",
+        },
+        {
+            "id": "2",
+            "title": "Captain Planet",
+        },
+        {
+            "id": "3",
+            "title": "Shazam",
+            "description": "a Captain Marvel ersatz",
+        },
+        {
+            "id": "4",
+            "title": "Marvel Cinematic Universe",
+        },
+    ])
+});
+
+#[actix_rt::test]
+async fn suggestions_replace_misspelled_words() {
+    let server = Server::new().await;
+    let index = index_with_documents(&server, &DOCUMENTS).await;
+
+    index
+        .search(json!({"q": "captain marvle", "showSuggestions": true}), |response, code| {
+            snapshot!(code, @"200 OK");
+            snapshot!(json_string!(response["suggestions"]), @r###"
+            [
+              "captain marvel"
+            ]
+            "###);
+        })
+        .await;
+
+    index
+        .search(json!({"q": "shazan", "showSuggestions": true}), |response, code| {
+            snapshot!(code, @"200 OK");
+            snapshot!(json_string!(response["suggestions"]), @r###"
+            [
+              "shazam"
+            ]
+            "###);
+        })
+        .await;
+}
+
+#[actix_rt::test]
+async fn suggestions_must_match_documents() {
+    let server = Server::new().await;
+    let index = index_with_documents(&server, &DOCUMENTS).await;
+
+    // the query is already correctly spelled
+    index
+        .search(json!({"q": "captain", "showSuggestions": true}), |response, code| {
+            snapshot!(code, @"200 OK");
+            snapshot!(json_string!(response["suggestions"]), @"[]");
+        })
+        .await;
+
+    // no document contains both `planet` and `universe`
+    index
+        .search(
+            json!({"q": "planot univerce", "matchingStrategy": "all", "showSuggestions": true}),
+            |response, code| {
+                snapshot!(code, @"200 OK");
+                snapshot!(response["estimatedTotalHits"], @"0");
+                snapshot!(json_string!(response["suggestions"]), @"[]");
+            },
+        )
+        .await;
+}
+
+#[actix_rt::test]
+async fn suggestions_are_opt_in() {
+    let server = Server::new().await;
+    let index = index_with_documents(&server, &DOCUMENTS).await;
+
+    index
+        .search(json!({"q": "shazan"}), |response, code| {
+            snapshot!(code, @"200 OK");
+            snapshot!(response.get("suggestions").is_some(), @"false");
+        })
+        .await;
+}
@@ -176,10 +176,35 @@ test_setting_routes!(
         update_verb: put,
         default_value: []
     },
+    {
+        setting: stemming,
+        update_verb: put,
+        default_value: []
+    },
+    {
+        setting: synonym_rules,
+        update_verb: put,
+        default_value: []
+    },
+    {
+        setting: searchable_attributes_weights,
+        update_verb: put,
+        default_value: {}
+    },
+    {
+        setting: ngram_attributes,
+        update_verb: put,
+        default_value: {}
+    },
+    {
+        setting: query_rules,
+        update_verb: put,
+        default_value: []
+    },
     {
         setting: typo_tolerance,
         update_verb: patch,
-        default_value: {"enabled": true, "minWordSizeForTypos": {"oneTypo": 5, "twoTypos": 9}, "disableOnWords": [], "disableOnAttributes": [], "disableOnNumbers": false}
+        default_value: {"enabled": true, "minWordSizeForTypos": {"oneTypo": 5, "twoTypos": 9}, "disableOnWords": [], "disableOnAttributes": [], "disableOnNumbers": false, "perAttribute": {}}
     },
 );
 
@@ -199,7 +224,7 @@ async fn get_settings() {
     let (response, code) = index.settings().await;
     assert_eq!(code, 200);
     let settings = response.as_object().unwrap();
-    assert_eq!(settings.keys().len(), 20);
+    assert_eq!(settings.keys().len(), 25);
     assert_eq!(settings["displayedAttributes"], json!(["*"]));
     assert_eq!(settings["searchableAttributes"], json!(["*"]));
     assert_eq!(settings["filterableAttributes"], json!([]));
@@ -231,6 +256,11 @@ async fn get_settings() {
     assert_eq!(settings["proximityPrecision"], json!("byWord"));
     assert_eq!(settings["searchCutoffMs"], json!(null));
     assert_eq!(settings["prefixSearch"], json!("indexingTime"));
+    assert_eq!(settings["stemming"], json!([]));
+    assert_eq!(settings["synonymRules"], json!([]));
+    assert_eq!(settings["searchableAttributesWeights"], json!({}));
+    assert_eq!(settings["ngramAttributes"], json!({}));
+    assert_eq!(settings["queryRules"], json!([]));
     assert_eq!(settings["facetSearch"], json!(true));
     assert_eq!(settings["embedders"], json!({}));
 }
@@ -309,7 +339,8 @@ async fn secrets_are_hidden_in_settings() {
         },
         "disableOnWords": [],
         "disableOnAttributes": [],
-        "disableOnNumbers": false
+        "disableOnNumbers": false,
+        "perAttribute": {}
       },
       "faceting": {
         "maxValuesPerFacet": 100,
@@ -336,7 +367,12 @@ async fn secrets_are_hidden_in_settings() {
       "searchCutoffMs": null,
       "localizedAttributes": null,
       "facetSearch": true,
-      "prefixSearch": "indexingTime"
+      "prefixSearch": "indexingTime",
+      "stemming": [],
+      "synonymRules": [],
+      "searchableAttributesWeights": {},
+      "ngramAttributes": {},
+      "queryRules": []
     }
     "###);
 
@@ -1,7 +1,13 @@
 mod distinct;
 mod errors;
 mod get_settings;
+mod ngram_attributes_settings;
+mod per_attribute_typo_tolerance_settings;
 mod prefix_search_settings;
 mod proximity_settings;
+mod query_rules_settings;
+mod searchable_attributes_weights_settings;
+mod stemming_settings;
+mod synonym_rules_settings;
 mod tokenizer_customization;
 mod vectors;
@@ -0,0 +1,142 @@
+use meili_snap::{json_string, snapshot};
+use once_cell::sync::Lazy;
+
+use crate::common::Server;
+use crate::json;
+
+static DOCUMENTS: Lazy<crate::common::Value> = Lazy::new(|| {
+    json!([
+        {
+            "id": 1,
+            "name": "adapter",
+            "sku": "AB-94521-X",
+        },
+        {
+            "id": 2,
+            "name": "4521 adapter",
+            "sku": "CD-12",
+        },
+        {
+            "id": 3,
+            "name": "94521 clone",
+            "sku": "EF-34",
+        },
+    ])
+});
+
+#[actix_rt::test]
+async fn substrings_of_the_ngram_attributes_match() {
+    let server = Server::new().await;
+    let index = server.index("test");
+
+    let (response, _code) =
+        index.update_settings(json!({ "searchableAttributes": ["name", "sku"] })).await;
+    index.wait_task(response.uid()).await.succeeded();
+    let (response, _code) = index.add_documents(DOCUMENTS.clone(), None).await;
+    index.wait_task(response.uid()).await.succeeded();
+
+    index
+        .search(json!({"q": "4521", "attributesToRetrieve": ["id"]}), |response, code| {
+            snapshot!(code, @"200 OK");
+            snapshot!(json_string!(response["hits"]), @r###"
+            [
+              {
+                "id": 2
+              }
+            ]
+            "###);
+        })
+        .await;
+
+    let (response, code) =
+        index.update_settings(json!({ "ngramAttributes": { "sku": { "mode": "ngram" } } })).await;
+    assert_eq!("202", code.as_str(), "{:?}", response);
+    index.wait_task(response.uid()).await.succeeded();
+
+    let (response, code) = index.settings().await;
+    snapshot!(code, @"200 OK");
+    snapshot!(json_string!(response["ngramAttributes"]), @r###"
+    {
+      "sku": {
+        "mode": "ngram",
+        "minLength": 3,
+        "maxMatches": 50
+      }
+    }
+    "###);
+
+    // the full word matches are ranked first and the n-grams are only searched in the sku
+    index
+        .search(json!({"q": "4521", "attributesToRetrieve": ["id"]}), |response, code| {
+            snapshot!(code, @"200 OK");
+            snapshot!(json_string!(response["hits"]), @r###"
+            [
+              {
+                "id": 2
+              },
+              {
+                "id": 1
+              }
+            ]
+            "###);
+        })
+        .await;
+
+    let (response, _code) = index
+        .update_settings(json!({ "ngramAttributes": { "sku": { "mode": "edgeNgram" } } }))
+        .await;
+    index.wait_task(response.uid()).await.succeeded();
+
+    index
+        .search(json!({"q": "945 adapter", "attributesToRetrieve": ["id"]}), |response, code| {
+            snapshot!(code, @"200 OK");
+            snapshot!(json_string!(response["hits"]), @r###"
+            [
+              {
+                "id": 1
+              }
+            ]
+            "###);
+        })
+        .await;
+
+    let (response, _code) = index.update_settings(json!({ "ngramAttributes": null })).await;
+    index.wait_task(response.uid()).await.succeeded();
+
+    let (response, code) = index.settings().await;
+    snapshot!(code, @"200 OK");
+    snapshot!(json_string!(response["ngramAttributes"]), @"{}");
+}
+
+#[actix_rt::test]
+async fn invalid_ngram_attributes() {
+    let server = Server::new_shared();
+    let index = server.unique_index();
+
+    let (response, code) =
+        index.update_settings(json!({ "ngramAttributes": { "sku": { "mode": "infix" } } })).await;
+    snapshot!(code, @"400 Bad Request");
+    snapshot!(json_string!(response), @r###"
+    {
+      "message": "Unknown value `infix` at `.ngramAttributes.sku.mode`: expected one of `ngram`, `edgeNgram`",
+      "code": "invalid_settings_ngram_attributes",
+      "type": "invalid_request",
+      "link": "https://docs.meilisearch.com/errors#invalid_settings_ngram_attributes"
+    }
+    "###);
+
+    let (response, code) = index
+        .update_settings(
+            json!({ "ngramAttributes": { "sku": { "mode": "ngram", "minLength": 1 } } }),
+        )
+        .await;
+    snapshot!(code, @"400 Bad Request");
+    snapshot!(json_string!(response), @r###"
+    {
+      "message": "`ngramAttributes` setting is invalid. The `minLength` of `sku` must be at least 2, found 1.",
+      "code": "invalid_settings_ngram_attributes",
+      "type": "invalid_request",
+      "link": "https://docs.meilisearch.com/errors#invalid_settings_ngram_attributes"
+    }
+    "###);
+}
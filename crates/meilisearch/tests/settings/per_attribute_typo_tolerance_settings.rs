@@ -0,0 +1,170 @@
+use meili_snap::{json_string, snapshot};
+use once_cell::sync::Lazy;
+
+use crate::common::Server;
+use crate::json;
+
+static DOCUMENTS: Lazy<crate::common::Value> = Lazy::new(|| {
+    json!([
+        {
+            "id": 1,
+            "name": "house",
+            "sku": "ab12",
+        },
+        {
+            "id": 2,
+            "name": "garden",
+            "sku": "house",
+        },
+        {
+            "id": 3,
+            "name": "garage",
+            "sku": "hou",
+        },
+    ])
+});
+
+#[actix_rt::test]
+async fn typo_tolerance_and_prefix_search_per_attribute() {
+    let server = Server::new().await;
+    let index = server.index("test");
+
+    let (response, _code) =
+        index.update_settings(json!({ "searchableAttributes": ["name", "sku"] })).await;
+    index.wait_task(response.uid()).await.succeeded();
+    let (response, _code) = index.add_documents(DOCUMENTS.clone(), None).await;
+    index.wait_task(response.uid()).await.succeeded();
+
+    index
+        .search(json!({"q": "housr", "attributesToRetrieve": ["id"]}), |response, code| {
+            snapshot!(code, @"200 OK");
+            snapshot!(json_string!(response["hits"]), @r###"
+            [
+              {
+                "id": 1
+              },
+              {
+                "id": 2
+              }
+            ]
+            "###);
+        })
+        .await;
+
+    let (response, code) = index
+        .update_settings_typo_tolerance(json!({
+            "perAttribute": {
+                "name": { "minWordSizeForTypos": { "oneTypo": 3 } },
+                "sku": { "enabled": false, "prefixSearch": "disabled" }
+            }
+        }))
+        .await;
+    assert_eq!("202", code.as_str(), "{:?}", response);
+    index.wait_task(response.uid()).await.succeeded();
+
+    let (response, code) = index.settings().await;
+    snapshot!(code, @"200 OK");
+    snapshot!(json_string!(response["typoTolerance"]), @r###"
+    {
+      "enabled": true,
+      "minWordSizeForTypos": {
+        "oneTypo": 5,
+        "twoTypos": 9
+      },
+      "disableOnWords": [],
+      "disableOnAttributes": [],
+      "disableOnNumbers": false,
+      "perAttribute": {
+        "name": {
+          "minWordSizeForTypos": {
+            "oneTypo": 3
+          }
+        },
+        "sku": {
+          "enabled": false,
+          "prefixSearch": "disabled"
+        }
+      }
+    }
+    "###);
+
+    // no typos on the sku
+    index
+        .search(json!({"q": "housr", "attributesToRetrieve": ["id"]}), |response, code| {
+            snapshot!(code, @"200 OK");
+            snapshot!(json_string!(response["hits"]), @r###"
+            [
+              {
+                "id": 1
+              }
+            ]
+            "###);
+        })
+        .await;
+
+    // no prefixes on the sku
+    index
+        .search(json!({"q": "hou", "attributesToRetrieve": ["id"]}), |response, code| {
+            snapshot!(code, @"200 OK");
+            snapshot!(json_string!(response["hits"]), @r###"
+            [
+              {
+                "id": 1
+              },
+              {
+                "id": 3
+              }
+            ]
+            "###);
+        })
+        .await;
+
+    // typos from 3 letters on the name
+    index
+        .search(json!({"q": "hose", "attributesToRetrieve": ["id"]}), |response, code| {
+            snapshot!(code, @"200 OK");
+            snapshot!(json_string!(response["hits"]), @r###"
+            [
+              {
+                "id": 1
+              }
+            ]
+            "###);
+        })
+        .await;
+
+    let (response, _code) =
+        index.update_settings_typo_tolerance(json!({ "perAttribute": null })).await;
+    index.wait_task(response.uid()).await.succeeded();
+
+    let (response, code) = index.settings().await;
+    snapshot!(code, @"200 OK");
+    snapshot!(json_string!(response["typoTolerance"]["perAttribute"]), @"{}");
+}
+
+#[actix_rt::test]
+async fn invalid_per_attribute_typo_tolerance() {
+    let server = Server::new_shared();
+    let index = server.unique_index();
+
+    let (response, code) = index
+        .update_settings_typo_tolerance(json!({ "perAttribute": { "sku": { "typos": false } } }))
+        .await;
+    snapshot!(code, @"400 Bad Request");
+    snapshot!(json_string!(response), @r###"
+    {
+      "message": "Unknown field `typos` inside `.perAttribute.sku`: expected one of `enabled`, `minWordSizeForTypos`, `prefixSearch`",
+      "code": "invalid_settings_typo_tolerance",
+      "type": "invalid_request",
+      "link": "https://docs.meilisearch.com/errors#invalid_settings_typo_tolerance"
+    }
+    "###);
+
+    let (response, code) = index
+        .update_settings_typo_tolerance(json!({
+            "perAttribute": { "name": { "minWordSizeForTypos": { "oneTypo": 6, "twoTypos": 3 } } }
+        }))
+        .await;
+    snapshot!(code, @"400 Bad Request");
+    snapshot!(response["code"], @r###""invalid_settings_typo_tolerance""###);
+}
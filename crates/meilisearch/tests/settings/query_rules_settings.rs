@@ -0,0 +1,216 @@
+use meili_snap::{json_string, snapshot};
+use once_cell::sync::Lazy;
+
+use crate::common::Server;
+use crate::json;
+
+static DOCUMENTS: Lazy<crate::common::Value> = Lazy::new(|| {
+    json!([
+        {
+            "id": 1,
+            "title": "black friday deal on a tv",
+            "on_sale": true,
+        },
+        {
+            "id": 2,
+            "title": "black friday laptop",
+            "on_sale": false,
+        },
+        {
+            "id": 3,
+            "title": "television stand",
+            "on_sale": true,
+        },
+    ])
+});
+
+#[actix_rt::test]
+async fn query_rules_alter_the_matching_searches() {
+    let server = Server::new().await;
+    let index = server.index("test");
+
+    let (response, _code) =
+        index.update_settings(json!({ "filterableAttributes": ["on_sale"] })).await;
+    index.wait_task(response.uid()).await.succeeded();
+    let (response, _code) = index.add_documents(DOCUMENTS.clone(), None).await;
+    index.wait_task(response.uid()).await.succeeded();
+
+    let (response, code) = index
+        .update_settings(json!({
+            "queryRules": [
+                { "match": "contains", "pattern": "Black Friday", "filter": "on_sale = true" },
+                {
+                    "match": "exact",
+                    "pattern": "returns policy",
+                    "userData": { "redirect": "https://example.com/returns" }
+                },
+                { "match": "regex", "pattern": "^tvs?$", "query": "television" },
+            ]
+        }))
+        .await;
+    assert_eq!("202", code.as_str(), "{:?}", response);
+    index.wait_task(response.uid()).await.succeeded();
+
+    let (response, code) = index.settings().await;
+    snapshot!(code, @"200 OK");
+    snapshot!(json_string!(response["queryRules"]), @r###"
+    [
+      {
+        "match": "contains",
+        "pattern": "Black Friday",
+        "filter": "on_sale = true"
+      },
+      {
+        "match": "exact",
+        "pattern": "returns policy",
+        "userData": {
+          "redirect": "https://example.com/returns"
+        }
+      },
+      {
+        "match": "regex",
+        "pattern": "^tvs?$",
+        "query": "television"
+      }
+    ]
+    "###);
+
+    // the filter of the rule is added to the filter of the search
+    index
+        .search(json!({"q": "BLACK  friday", "attributesToRetrieve": ["id"]}), |response, code| {
+            snapshot!(code, @"200 OK");
+            snapshot!(json_string!(response["hits"]), @r###"
+            [
+              {
+                "id": 1
+              }
+            ]
+            "###);
+            snapshot!(response["userData"], @"null");
+        })
+        .await;
+
+    index
+        .search(json!({"q": "returns policy", "attributesToRetrieve": ["id"]}), |response, code| {
+            snapshot!(code, @"200 OK");
+            snapshot!(json_string!(response["hits"]), @"[]");
+            snapshot!(json_string!(response["userData"]), @r###"
+            [
+              {
+                "redirect": "https://example.com/returns"
+              }
+            ]
+            "###);
+        })
+        .await;
+
+    // the query is rewritten but the response keeps the query of the user
+    index
+        .search(json!({"q": "tv", "attributesToRetrieve": ["id"]}), |response, code| {
+            snapshot!(code, @"200 OK");
+            snapshot!(json_string!(response["hits"]), @r###"
+            [
+              {
+                "id": 3
+              }
+            ]
+            "###);
+            snapshot!(response["query"], @r###""tv""###);
+        })
+        .await;
+
+    // the rules are applied to the queries of a federated search too
+    let (response, code) = server
+        .multi_search(json!({"federation": {}, "queries": [
+            {"indexUid": "test", "q": "black friday", "attributesToRetrieve": ["id"]},
+            {"indexUid": "test", "q": "returns policy"},
+        ]}))
+        .await;
+    snapshot!(code, @"200 OK");
+    snapshot!(json_string!(response["hits"]), @r###"
+    [
+      {
+        "id": 1,
+        "_federation": {
+          "indexUid": "test",
+          "queriesPosition": 0,
+          "weightedRankingScore": 1.0
+        }
+      }
+    ]
+    "###);
+    snapshot!(json_string!(response["userData"]), @r###"
+    [
+      {
+        "redirect": "https://example.com/returns"
+      }
+    ]
+    "###);
+
+    let (response, _code) = index.update_settings(json!({ "queryRules": null })).await;
+    index.wait_task(response.uid()).await.succeeded();
+
+    let (response, code) = index.settings().await;
+    snapshot!(code, @"200 OK");
+    snapshot!(json_string!(response["queryRules"]), @"[]");
+}
+
+#[actix_rt::test]
+async fn invalid_query_rules() {
+    let server = Server::new_shared();
+    let index = server.unique_index();
+
+    let (response, code) = index
+        .update_settings(json!({
+            "queryRules": [{ "match": "prefix", "pattern": "black", "filter": "on_sale = true" }]
+        }))
+        .await;
+    snapshot!(code, @"400 Bad Request");
+    snapshot!(json_string!(response), @r###"
+    {
+      "message": "Unknown value `prefix` at `.queryRules[0].match`: expected one of `exact`, `contains`, `regex`",
+      "code": "invalid_settings_query_rules",
+      "type": "invalid_request",
+      "link": "https://docs.meilisearch.com/errors#invalid_settings_query_rules"
+    }
+    "###);
+
+    let (response, code) = index
+        .update_settings(json!({
+            "queryRules": [
+                { "match": "exact", "pattern": "black friday", "filter": "on_sale = true" },
+                { "match": "contains", "pattern": "returns" },
+            ]
+        }))
+        .await;
+    snapshot!(code, @"400 Bad Request");
+    snapshot!(json_string!(response), @r###"
+    {
+      "message": "`queryRules` setting is invalid. The rule at index 1 has no consequence, it must define at least one of `query`, `filter`, `sort` or `userData`.",
+      "code": "invalid_settings_query_rules",
+      "type": "invalid_request",
+      "link": "https://docs.meilisearch.com/errors#invalid_settings_query_rules"
+    }
+    "###);
+
+    let (response, code) = index
+        .update_settings(json!({
+            "queryRules": [{ "match": "regex", "pattern": "black (friday", "query": "black friday" }]
+        }))
+        .await;
+    snapshot!(code, @"400 Bad Request");
+    snapshot!(response["code"], @r###""invalid_settings_query_rules""###);
+
+    let (response, code) = index
+        .update_settings(json!({
+            "queryRules": [{ "match": "contains", "pattern": "cheap", "sort": ["price:asc"] }]
+        }))
+        .await;
+    snapshot!(code, @"202 Accepted");
+    let response = index.wait_task(response.uid()).await.failed();
+    snapshot!(response["error"]["code"], @r###""invalid_settings_query_rules""###);
+    assert!(response["error"]["message"]
+        .as_str()
+        .unwrap()
+        .ends_with("The rule at index 0 sorts on `price`, which is not a sortable attribute."));
+}
@@ -0,0 +1,138 @@
+use meili_snap::{json_string, snapshot};
+use once_cell::sync::Lazy;
+
+use crate::common::Server;
+use crate::json;
+
+static DOCUMENTS: Lazy<crate::common::Value> = Lazy::new(|| {
+    json!([
+        {
+            "id": 1,
+            "title": "the fox",
+            "description": "",
+            "tags": "",
+        },
+        {
+            "id": 2,
+            "title": "",
+            "description": "the fox",
+            "tags": "",
+        },
+        {
+            "id": 3,
+            "title": "",
+            "description": "",
+            "tags": "the fox",
+        },
+    ])
+});
+
+#[actix_rt::test]
+async fn weights_replace_the_order_of_the_searchable_attributes() {
+    let server = Server::new().await;
+    let index = server.index("test");
+
+    let (response, _code) = index
+        .update_settings(json!({ "searchableAttributes": ["title", "description", "tags"] }))
+        .await;
+    index.wait_task(response.uid()).await.succeeded();
+    let (response, _code) = index.add_documents(DOCUMENTS.clone(), None).await;
+    index.wait_task(response.uid()).await.succeeded();
+
+    index
+        .search(json!({"q": "fox", "attributesToRetrieve": ["id"]}), |response, code| {
+            snapshot!(code, @"200 OK");
+            snapshot!(json_string!(response["hits"]), @r###"
+            [
+              {
+                "id": 1
+              },
+              {
+                "id": 2
+              },
+              {
+                "id": 3
+              }
+            ]
+            "###);
+        })
+        .await;
+
+    let (response, code) = index
+        .update_settings(json!({ "searchableAttributesWeights": { "tags": 3, "description": 3 } }))
+        .await;
+    assert_eq!("202", code.as_str(), "{:?}", response);
+    index.wait_task(response.uid()).await.succeeded();
+
+    let (response, code) = index.settings().await;
+    snapshot!(code, @"200 OK");
+    snapshot!(json_string!(response["searchableAttributesWeights"]), @r###"
+    {
+      "description": 3,
+      "tags": 3
+    }
+    "###);
+
+    // the attributes with the same weight are equally important
+    index
+        .search(json!({"q": "fox", "attributesToRetrieve": ["id"]}), |response, code| {
+            snapshot!(code, @"200 OK");
+            snapshot!(json_string!(response["hits"]), @r###"
+            [
+              {
+                "id": 2
+              },
+              {
+                "id": 3
+              },
+              {
+                "id": 1
+              }
+            ]
+            "###);
+        })
+        .await;
+
+    // the boosts of the query replace the weights of the settings
+    index
+        .search(
+            json!({"q": "fox", "attributesToSearchOn": ["title^2", "tags"], "attributesToRetrieve": ["id"]}),
+            |response, code| {
+                snapshot!(code, @"200 OK");
+                snapshot!(json_string!(response["hits"]), @r###"
+                [
+                  {
+                    "id": 1
+                  },
+                  {
+                    "id": 3
+                  }
+                ]
+                "###);
+            },
+        )
+        .await;
+
+    let (response, _code) = index.delete_settings().await;
+    index.wait_task(response.uid()).await.succeeded();
+    let (response, _code) = index.settings().await;
+    snapshot!(json_string!(response["searchableAttributesWeights"]), @"{}");
+}
+
+#[actix_rt::test]
+async fn invalid_searchable_attributes_weights() {
+    let server = Server::new_shared();
+    let index = server.unique_index();
+
+    let (response, code) =
+        index.update_settings(json!({ "searchableAttributesWeights": { "title": "high" } })).await;
+    snapshot!(code, @"400 Bad Request");
+    snapshot!(json_string!(response), @r###"
+    {
+      "message": "Invalid value type at `.searchableAttributesWeights.title`: expected a positive integer, but found a string: `\"high\"`",
+      "code": "invalid_settings_searchable_attributes_weights",
+      "type": "invalid_request",
+      "link": "https://docs.meilisearch.com/errors#invalid_settings_searchable_attributes_weights"
+    }
+    "###);
+}
@@ -0,0 +1,148 @@
+use meili_snap::{json_string, snapshot};
+use once_cell::sync::Lazy;
+
+use crate::common::Server;
+use crate::json;
+
+static DOCUMENTS: Lazy<crate::common::Value> = Lazy::new(|| {
+    json!([
+        {
+            "id": 1,
+            "title": "The runners are running",
+        },
+        {
+            "id": 2,
+            "title": "She runs every morning",
+        },
+        {
+            "id": 3,
+            "title": "A quiet walk",
+        },
+    ])
+});
+
+#[actix_rt::test]
+async fn add_docs_and_enable_stemming() {
+    let server = Server::new().await;
+    let index = server.index("test");
+
+    let (response, _code) = index.add_documents(DOCUMENTS.clone(), None).await;
+    index.wait_task(response.uid()).await.succeeded();
+
+    // without stemming only the exact word matches
+    index
+        .search(json!({"q": "running", "attributesToRetrieve": ["id"]}), |response, code| {
+            snapshot!(code, @"200 OK");
+            snapshot!(json_string!(response["hits"]), @r###"
+            [
+              {
+                "id": 1
+              }
+            ]
+            "###);
+        })
+        .await;
+
+    let (response, code) = index.update_settings(json!({ "stemming": ["eng"] })).await;
+    assert_eq!("202", code.as_str(), "{:?}", response);
+    index.wait_task(response.uid()).await.succeeded();
+
+    let (response, code) = index.settings().await;
+    snapshot!(code, @"200 OK");
+    snapshot!(json_string!(response["stemming"]), @r###"
+    [
+      "eng"
+    ]
+    "###);
+
+    // the words sharing the stem of `running` now match, after the exact word
+    index
+        .search(json!({"q": "running", "attributesToRetrieve": ["id"]}), |response, code| {
+            snapshot!(code, @"200 OK");
+            snapshot!(json_string!(response["hits"]), @r###"
+            [
+              {
+                "id": 1
+              },
+              {
+                "id": 2
+              }
+            ]
+            "###);
+        })
+        .await;
+
+    let (response, code) = index.update_settings(json!({ "stemming": null })).await;
+    assert_eq!("202", code.as_str(), "{:?}", response);
+    index.wait_task(response.uid()).await.succeeded();
+
+    index
+        .search(json!({"q": "running", "attributesToRetrieve": ["id"]}), |response, code| {
+            snapshot!(code, @"200 OK");
+            snapshot!(json_string!(response["hits"]), @r###"
+            [
+              {
+                "id": 1
+              }
+            ]
+            "###);
+        })
+        .await;
+}
+
+#[actix_rt::test]
+async fn enable_stemming_and_add_docs() {
+    let server = Server::new().await;
+    let index = server.index("test");
+
+    let (response, code) = index.update_settings(json!({ "stemming": ["eng"] })).await;
+    assert_eq!("202", code.as_str(), "{:?}", response);
+    index.wait_task(response.uid()).await.succeeded();
+
+    let (response, _code) = index.add_documents(DOCUMENTS.clone(), None).await;
+    index.wait_task(response.uid()).await.succeeded();
+
+    index
+        .search(json!({"q": "runs", "attributesToRetrieve": ["id"]}), |response, code| {
+            snapshot!(code, @"200 OK");
+            snapshot!(json_string!(response["hits"]), @r###"
+            [
+              {
+                "id": 2
+              },
+              {
+                "id": 1
+              }
+            ]
+            "###);
+        })
+        .await;
+}
+
+#[actix_rt::test]
+async fn unsupported_stemming_locale() {
+    let server = Server::new_shared();
+    let index = server.unique_index();
+
+    let (response, code) = index.update_settings(json!({ "stemming": ["jpn"] })).await;
+    snapshot!(code, @"400 Bad Request");
+    snapshot!(json_string!(response), @r###"
+    {
+      "message": "`stemming` setting is invalid. The locale `jpn` cannot be stemmed. Supported locales are: `ara, dan, deu, ell, eng, fin, fra, hun, ita, nld, nob, por, ron, rus, spa, swe, tam, tur`.",
+      "code": "invalid_settings_stemming",
+      "type": "invalid_request",
+      "link": "https://docs.meilisearch.com/errors#invalid_settings_stemming"
+    }
+    "###);
+
+    let (response, code) = index.update_settings(json!({ "stemming": "eng" })).await;
+    snapshot!(code, @"400 Bad Request");
+    snapshot!(json_string!(response), @r###"
+    {
+      "message": "Invalid value type at `.stemming`: expected an array, but found a string: `\"eng\"`",
+      "code": "invalid_settings_stemming",
+      "type": "invalid_request",
+      "link": "https://docs.meilisearch.com/errors#invalid_settings_stemming"
+    }
+    "###);
+}
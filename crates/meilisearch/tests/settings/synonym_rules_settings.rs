@@ -0,0 +1,155 @@
+use meili_snap::{json_string, snapshot};
+use once_cell::sync::Lazy;
+
+use crate::common::Server;
+use crate::json;
+
+static DOCUMENTS: Lazy<crate::common::Value> = Lazy::new(|| {
+    json!([
+        {
+            "id": 1,
+            "title": "couch for sale",
+        },
+        {
+            "id": 2,
+            "title": "sofa for sale",
+        },
+        {
+            "id": 3,
+            "title": "iphone for sale",
+        },
+        {
+            "id": 4,
+            "title": "apple phone for sale",
+        },
+    ])
+});
+
+#[actix_rt::test]
+async fn one_way_rules_and_equivalence_groups() {
+    let server = Server::new().await;
+    let index = server.index("test");
+
+    let (response, _code) = index.add_documents(DOCUMENTS.clone(), None).await;
+    index.wait_task(response.uid()).await.succeeded();
+
+    let (response, code) = index
+        .update_settings(json!({
+            "synonymRules": [
+                { "input": "iphone", "synonyms": ["apple phone"] },
+                { "synonyms": ["couch", "sofa"], "weight": 0.5 },
+            ]
+        }))
+        .await;
+    assert_eq!("202", code.as_str(), "{:?}", response);
+    index.wait_task(response.uid()).await.succeeded();
+
+    let (response, code) = index.settings().await;
+    snapshot!(code, @"200 OK");
+    snapshot!(json_string!(response["synonymRules"]), @r###"
+    [
+      {
+        "input": "iphone",
+        "synonyms": [
+          "apple phone"
+        ],
+        "weight": 1.0
+      },
+      {
+        "synonyms": [
+          "couch",
+          "sofa"
+        ],
+        "weight": 0.5
+      }
+    ]
+    "###);
+
+    // a one-way rule expands its input into the synonyms
+    index
+        .search(json!({"q": "iphone", "attributesToRetrieve": ["id"]}), |response, code| {
+            snapshot!(code, @"200 OK");
+            snapshot!(json_string!(response["hits"]), @r###"
+            [
+              {
+                "id": 3
+              },
+              {
+                "id": 4
+              }
+            ]
+            "###);
+        })
+        .await;
+
+    // but not the synonyms into the input
+    index
+        .search(json!({"q": "apple phone", "attributesToRetrieve": ["id"]}), |response, code| {
+            snapshot!(code, @"200 OK");
+            snapshot!(json_string!(response["hits"]), @r###"
+            [
+              {
+                "id": 4
+              }
+            ]
+            "###);
+        })
+        .await;
+
+    // the down-weighted synonyms are ranked after the original word
+    index
+        .search(
+            json!({"q": "sofa", "attributesToRetrieve": ["id"], "showRankingScoreDetails": true}),
+            |response, code| {
+                snapshot!(code, @"200 OK");
+                snapshot!(json_string!(response["hits"].as_array().unwrap().iter().map(|hit| &hit["id"]).collect::<Vec<_>>()), @r###"
+                [
+                  2,
+                  1
+                ]
+                "###);
+                snapshot!(json_string!(response["hits"][1]["_rankingScoreDetails"]["typo"]), @r###"
+                {
+                  "order": 1,
+                  "typoCount": 1,
+                  "maxTypoCount": 1,
+                  "score": 0.5
+                }
+                "###);
+            },
+        )
+        .await;
+}
+
+#[actix_rt::test]
+async fn invalid_synonym_rules() {
+    let server = Server::new_shared();
+    let index = server.unique_index();
+
+    let (response, code) = index
+        .update_settings(
+            json!({ "synonymRules": [{ "synonyms": ["couch", "sofa"], "weight": 2.0 }] }),
+        )
+        .await;
+    snapshot!(code, @"400 Bad Request");
+    snapshot!(json_string!(response), @r###"
+    {
+      "message": "Invalid value at `.synonymRules[0].weight`: the `weight` of a synonym rule must be greater than `0.0` and lower or equal to `1.0`, but found `2`",
+      "code": "invalid_settings_synonym_rules",
+      "type": "invalid_request",
+      "link": "https://docs.meilisearch.com/errors#invalid_settings_synonym_rules"
+    }
+    "###);
+
+    let (response, code) =
+        index.update_settings(json!({ "synonymRules": [{ "synonyms": ["couch"] }] })).await;
+    snapshot!(code, @"400 Bad Request");
+    snapshot!(json_string!(response), @r###"
+    {
+      "message": "`synonymRules` setting is invalid. The rule at index 0 has no `input` and less than two `synonyms`, an equivalence group needs at least two words.",
+      "code": "invalid_settings_synonym_rules",
+      "type": "invalid_request",
+      "link": "https://docs.meilisearch.com/errors#invalid_settings_synonym_rules"
+    }
+    "###);
+}
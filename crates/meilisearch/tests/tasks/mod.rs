@@ -48,6 +48,251 @@ async fn get_task_status() {
     // TODO check response format, as per #48
 }
 
+#[actix_rt::test]
+async fn get_task_priority() {
+    let server = Server::new().await;
+    let index = server.index("test");
+
+    let documents = r#"[{ "id": 1, "content": "foobar" }]"#;
+    let (task, code) = index
+        .raw_add_documents(
+            documents,
+            vec![("Content-Type", "application/json"), ("TaskPriority", "high")],
+            "",
+        )
+        .await;
+    snapshot!(code, @"202 Accepted");
+    let response = index.wait_task(task.uid()).await.succeeded();
+    snapshot!(response["priority"], @r###""high""###);
+
+    // the tasks of normal priority don't display it
+    let (task, _code) = index.add_documents(json!([{ "id": 2, "content": "foobar" }]), None).await;
+    let response = index.wait_task(task.uid()).await.succeeded();
+    snapshot!(response["priority"], @"null");
+
+    let (response, code) = index
+        .raw_add_documents(
+            documents,
+            vec![("Content-Type", "application/json"), ("TaskPriority", "urgent")],
+            "",
+        )
+        .await;
+    snapshot!(code, @"400 Bad Request");
+    snapshot!(meili_snap::json_string!(response), @r###"
+    {
+      "message": "`urgent` is not a valid task priority. Available priorities are `low`, `normal`, `high`.",
+      "code": "bad_request",
+      "type": "invalid_request",
+      "link": "https://docs.meilisearch.com/errors#bad_request"
+    }
+    "###);
+}
+
+#[actix_rt::test]
+async fn task_priority_is_capped_by_the_api_key() {
+    let mut server = Server::new_auth().await;
+    server.use_admin_key("MASTER_KEY").await;
+    let (response, code) = server
+        .add_api_key(json!({
+            "actions": ["documents.add", "indexes.create", "tasks.get"],
+            "indexes": ["test"],
+            "expiresAt": null,
+            "taskPriority": "low",
+        }))
+        .await;
+    snapshot!(code, @"201 Created");
+    server.use_api_key(response["key"].as_str().unwrap());
+    let index = server.index("test");
+
+    let documents = r#"[{ "id": 1, "content": "foobar" }]"#;
+    let (response, code) = index
+        .raw_add_documents(
+            documents,
+            vec![("Content-Type", "application/json"), ("TaskPriority", "high")],
+            "",
+        )
+        .await;
+    snapshot!(code, @"400 Bad Request");
+    snapshot!(meili_snap::json_string!(response), @r###"
+    {
+      "message": "The task priority cannot be `high` as the API key used registers tasks with a `low` priority at most.",
+      "code": "bad_request",
+      "type": "invalid_request",
+      "link": "https://docs.meilisearch.com/errors#bad_request"
+    }
+    "###);
+
+    let (task, code) = index
+        .raw_add_documents(
+            documents,
+            vec![("Content-Type", "application/json"), ("TaskPriority", "low")],
+            "",
+        )
+        .await;
+    snapshot!(code, @"202 Accepted");
+    let response = index.wait_task(task.uid()).await.succeeded();
+    snapshot!(response["priority"], @r###""low""###);
+}
+
+#[actix_rt::test]
+async fn get_scheduled_task() {
+    let server = Server::new().await;
+    let index = server.index("test");
+
+    let documents = r#"[{ "id": 1, "content": "foobar" }]"#;
+    let (task, code) = index
+        .raw_add_documents(
+            documents,
+            vec![("Content-Type", "application/json"), ("RunAfter", "2100-01-01T00:00:00Z")],
+            "",
+        )
+        .await;
+    snapshot!(code, @"202 Accepted");
+    snapshot!(task["runAfter"], @r###""2100-01-01T00:00:00Z""###);
+
+    let (response, code) = server.tasks_filter("afterRunAfter=2099-12-31T00:00:00Z").await;
+    snapshot!(code, @"200 OK");
+    let results = response["results"].as_array().unwrap();
+    assert_eq!(results.len(), 1);
+    snapshot!(results[0]["status"], @r###""enqueued""###);
+    snapshot!(results[0]["runAfter"], @r###""2100-01-01T00:00:00Z""###);
+
+    // a scheduled task is canceled like any enqueued task
+    let (response, _code) = server.cancel_tasks(&format!("uids={}", task.uid())).await;
+    server.wait_task(response.uid()).await.succeeded();
+    let (response, _code) = index.get_task(task.uid()).await;
+    snapshot!(response["status"], @r###""canceled""###);
+
+    let (response, code) = index
+        .raw_add_documents(
+            documents,
+            vec![("Content-Type", "application/json"), ("RunAfter", "midnight")],
+            "",
+        )
+        .await;
+    snapshot!(code, @"400 Bad Request");
+    snapshot!(meili_snap::json_string!(response), @r###"
+    {
+      "message": "RunAfter must be a valid RFC 3339 date, found `midnight`.",
+      "code": "invalid_task_run_after",
+      "type": "invalid_request",
+      "link": "https://docs.meilisearch.com/errors#invalid_task_run_after"
+    }
+    "###);
+}
+
+#[actix_rt::test]
+async fn register_task_group() {
+    let server = Server::new().await;
+    let index = server.index("test");
+
+    let (task, _code) = index.add_documents(json!([{ "id": 1 }, { "id": 2 }]), Some("id")).await;
+    index.wait_task(task.uid()).await.succeeded();
+
+    // the addition fails, so the deletion is rolled back
+    let (response, code) = index
+        .register_task_group(json!({ "operations": [
+            { "deleteDocuments": [1] },
+            { "addDocuments": [{ "name": "no id" }] },
+        ]}))
+        .await;
+    snapshot!(code, @"202 Accepted");
+    let tasks = response.as_array().unwrap();
+    assert_eq!(tasks.len(), 2);
+    assert_eq!(tasks[0]["groupUid"], tasks[0]["taskUid"]);
+    assert_eq!(tasks[1]["groupUid"], tasks[0]["taskUid"]);
+    let uids: Vec<_> = tasks.iter().map(|task| task["taskUid"].as_u64().unwrap()).collect();
+    let task = index.wait_task(uids[1]).await.failed();
+    snapshot!(task["error"]["code"], @r###""task_group_failed""###);
+    let (task, _code) = index.get_task(uids[0]).await;
+    snapshot!(task["status"], @r###""failed""###);
+    let (_document, code) = index.get_document(1, None).await;
+    snapshot!(code, @"200 OK");
+
+    let (response, code) = index
+        .register_task_group(json!({ "operations": [
+            { "clearDocuments": true },
+            { "deleteDocumentsByFilter": "id >" },
+        ]}))
+        .await;
+    snapshot!(code, @"400 Bad Request");
+    snapshot!(response["code"], @r###""invalid_document_filter""###);
+
+    let (response, code) = index
+        .register_task_group(json!({ "operations": [
+            { "clearDocuments": true },
+            { "updateDocuments": [{ "id": 3 }], "primaryKey": "id" },
+        ]}))
+        .await;
+    snapshot!(code, @"202 Accepted");
+    let uids: Vec<_> =
+        response.as_array().unwrap().iter().map(|task| task["taskUid"].as_u64().unwrap()).collect();
+    let last = index.wait_task(uids[1]).await.succeeded();
+    let (first, _code) = index.get_task(uids[0]).await;
+    assert_eq!(first["batchUid"], last["batchUid"]);
+    let (response, _code) = index.get_all_documents_raw("").await;
+    snapshot!(response["results"], @r###"[{"id":3}]"###);
+
+    let (response, code) = index
+        .register_task_group(json!({ "operations": [
+            { "deleteDocuments": [3], "clearDocuments": true },
+        ]}))
+        .await;
+    snapshot!(code, @"400 Bad Request");
+    snapshot!(meili_snap::json_string!(response), @r###"
+    {
+      "message": "Invalid operation at position 0: exactly one of `addDocuments`, `updateDocuments`, `deleteDocuments`, `deleteDocumentsByFilter` or `clearDocuments` must be specified.",
+      "code": "invalid_task_group_operations",
+      "type": "invalid_request",
+      "link": "https://docs.meilisearch.com/errors#invalid_task_group_operations"
+    }
+    "###);
+}
+
+#[actix_rt::test]
+async fn retry_tasks() {
+    let server = Server::new().await;
+    let index = server.index("test");
+
+    let (response, code) = server.retry_tasks("").await;
+    snapshot!(code, @"400 Bad Request");
+    snapshot!(response["code"], @r###""missing_task_filters""###);
+
+    // deleting an index that does not exist fails
+    let (task, _code) = index.delete().await;
+    let failed = index.wait_task(task.uid()).await.failed();
+
+    // the failed task is enqueued again as a new task
+    let (response, code) = server.retry_tasks(&format!("uids={}", failed.uid())).await;
+    snapshot!(code, @"202 Accepted");
+    let tasks = response.as_array().unwrap();
+    assert_eq!(tasks.len(), 1);
+    assert_eq!(tasks[0]["type"], "indexDeletion");
+    assert_eq!(tasks[0]["status"], "enqueued");
+    let retried = tasks[0]["taskUid"].as_u64().unwrap();
+    assert_ne!(retried, failed.uid());
+    index.wait_task(retried).await.failed();
+
+    // the tasks that did not fail are not retried
+    let (response, code) = server.retry_tasks("statuses=succeeded").await;
+    snapshot!(code, @"202 Accepted");
+    snapshot!(response, @"[]");
+
+    // the documents of a document addition that failed because of a permanent error are deleted
+    let (task, _code) = index.add_documents(json!([{ "name": "kefir" }]), None).await;
+    let failed = index.wait_task(task.uid()).await.failed();
+    let (response, code) = server.retry_tasks(&format!("uids={}", failed.uid())).await;
+    snapshot!(code, @"400 Bad Request");
+    snapshot!(meili_snap::json_string!(response), @r###"
+    {
+      "message": "Task `2` cannot be retried because its documents were deleted. Only the document additions that failed because of a transient error keep their documents.",
+      "code": "task_not_retriable",
+      "type": "invalid_request",
+      "link": "https://docs.meilisearch.com/errors#task_not_retriable"
+    }
+    "###);
+}
+
 #[actix_rt::test]
 async fn list_tasks() {
     let server = Server::new().await;
@@ -1063,3 +1308,68 @@ async fn test_summarized_dump_creation() {
     }
     "###);
 }
+
+#[actix_web::test]
+async fn pause_and_resume_the_task_queue() {
+    let server = Server::new().await;
+
+    let (response, code) = server.pause_tasks("indexUids=doggos").await;
+    snapshot!(code, @"200 OK");
+    snapshot!(meili_snap::json_string!(response), @r###"
+    {
+      "paused": false,
+      "pausedIndexes": [
+        "doggos"
+      ]
+    }
+    "###);
+
+    // the tasks of the paused index are enqueued but not processed
+    let (doggos, _) = server.index("doggos").create(None).await;
+    let (catto, _) = server.index("catto").create(None).await;
+    server.wait_task(catto.uid()).await.succeeded();
+    let (task, _) = server.get_task(doggos.uid()).await;
+    assert_eq!(task["status"], "enqueued");
+
+    let (response, _) = server.service.get("/health").await;
+    snapshot!(meili_snap::json_string!(response), @r###"
+    {
+      "status": "available",
+      "queue": {
+        "paused": false,
+        "numberOfPausedIndexes": 1
+      }
+    }
+    "###);
+    let (response, _) = server.stats().await;
+    snapshot!(meili_snap::json_string!(response["queue"]), @r###"
+    {
+      "paused": false,
+      "pausedIndexes": [
+        "doggos"
+      ]
+    }
+    "###);
+
+    // pausing the whole queue stops all the indexes
+    let (response, code) = server.pause_tasks("").await;
+    snapshot!(code, @"200 OK");
+    snapshot!(response["paused"], @"true");
+    let (pingu, _) = server.index("pingu").create(None).await;
+
+    // resuming the whole queue also resumes the paused indexes
+    let (response, code) = server.resume_tasks("indexUids=*").await;
+    snapshot!(code, @"200 OK");
+    snapshot!(meili_snap::json_string!(response), @r###"
+    {
+      "paused": false,
+      "pausedIndexes": []
+    }
+    "###);
+    server.wait_task(doggos.uid()).await.succeeded();
+    server.wait_task(pingu.uid()).await.succeeded();
+
+    let (response, code) = server.resume_tasks("indexUids=doggo%20s").await;
+    snapshot!(code, @"400 Bad Request");
+    snapshot!(response["code"], @r###""invalid_index_uid""###);
+}
@@ -158,6 +158,10 @@ async fn check_the_index_scheduler(server: &Server) {
             "surname": 1
           }
         }
+      },
+      "queue": {
+        "paused": false,
+        "pausedIndexes": []
       }
     }
     "###);
@@ -244,6 +248,10 @@ async fn check_the_index_scheduler(server: &Server) {
             "surname": 1
           }
         }
+      },
+      "queue": {
+        "paused": false,
+        "pausedIndexes": []
       }
     }
     "###);
@@ -1,11 +1,13 @@
 use std::collections::BTreeMap;
+use std::time::Duration;
 
+use byte_unit::{Byte, Unit};
 use meili_snap::{json_string, snapshot};
 use reqwest::IntoUrl;
 use wiremock::matchers::{method, path};
 use wiremock::{Mock, MockServer, Request, ResponseTemplate};
 
-use crate::common::Value;
+use crate::common::{default_settings, Server, Value};
 use crate::json;
 use crate::vector::{get_server_vector, GetAllDocumentsOptions};
 
@@ -334,6 +336,136 @@ async fn create_mock_raw() -> (MockServer, Value) {
     (mock_server, embedder_settings)
 }
 
+async fn create_mock_fragments() -> (MockServer, Value) {
+    let mock_server = MockServer::start().await;
+
+    let input_to_embedding: BTreeMap<_, _> = vec![
+        // image or text -> embedding
+        ("kefir.jpg", [0.0, 0.0, 1.0]),
+        ("intel.jpg", [1.0, 0.0, 0.0]),
+        ("a dog", [0.1, 0.0, 1.0]),
+        ("a cat", [1.0, 0.0, 0.1]),
+    ]
+    // turn into btree
+    .into_iter()
+    .collect();
+
+    Mock::given(method("POST"))
+        .and(path("/"))
+        .respond_with(move |req: &Request| {
+            let req: serde_json::Value = req.body_json().unwrap();
+            let input = match req["input"]["type"].as_str() {
+                Some("image_url") => req["input"]["image_url"].as_str(),
+                Some("text") => req["input"]["text"].as_str(),
+                _ => {
+                    return ResponseTemplate::new(400).set_body_json(json!({
+                      "error": format!("Invalid input: {}", req["input"])
+                    }));
+                }
+            };
+            let embedding =
+                input.and_then(|input| input_to_embedding.get(input)).unwrap_or(&[99., 99., 99.]);
+            ResponseTemplate::new(200).set_body_json(json!({ "data": embedding }))
+        })
+        .mount(&mock_server)
+        .await;
+    let url = mock_server.uri();
+
+    let embedder_settings = json!({
+        "source": "rest",
+        "url": url,
+        "dimensions": 3,
+        "request": { "input": "{{text}}" },
+        "response": {
+          "data": "{{embedding}}"
+        },
+        "indexingFragment": { "type": "image_url", "image_url": "{{doc.image}}" },
+        "searchFragment": { "type": "text", "text": "{{q}}" },
+    });
+
+    (mock_server, embedder_settings)
+}
+
+async fn create_mock_chunks() -> (MockServer, Value) {
+    let mock_server = MockServer::start().await;
+
+    let text_to_embedding: BTreeMap<_, _> = vec![
+        // text -> embedding
+        ("kefir", [1.0, 0.0, 0.0]),
+        ("intel", [0.0, 1.0, 0.0]),
+        ("max", [0.0, 0.0, 1.0]),
+    ]
+    // turn into btree
+    .into_iter()
+    .collect();
+
+    Mock::given(method("POST"))
+        .and(path("/"))
+        .respond_with(move |req: &Request| {
+            let text: String = req.body_json().unwrap();
+            match text_to_embedding.get(text.as_str()) {
+                Some(embedding) => {
+                    ResponseTemplate::new(200).set_body_json(json!({ "data": embedding }))
+                }
+                None => ResponseTemplate::new(404)
+                    .set_body_json(json!({"error": "text not found", "text": text})),
+            }
+        })
+        .mount(&mock_server)
+        .await;
+    let url = mock_server.uri();
+
+    let embedder_settings = json!({
+        "source": "rest",
+        "url": url,
+        "dimensions": 3,
+        "request": "{{text}}",
+        "response": {
+          "data": "{{embedding}}"
+        },
+        "documentTemplate": "{{doc.name}}",
+        "chunking": { "strategy": "paragraphs" },
+    });
+
+    (mock_server, embedder_settings)
+}
+
+async fn create_mock_reranker() -> (MockServer, Value) {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("POST"))
+        .and(path("/rerank"))
+        .respond_with(move |req: &Request| {
+            let req: serde_json::Value = req.body_json().unwrap();
+            let Some(documents) = req["documents"].as_array() else {
+                return ResponseTemplate::new(400)
+                    .set_body_json(json!({ "error": "missing documents" }));
+            };
+            // intel is always the most relevant document
+            let results: Vec<_> = documents
+                .iter()
+                .map(|document| {
+                    let relevance = if document.as_str() == Some("intel") { 1.0 } else { 0.5 };
+                    serde_json::json!({ "relevance": relevance })
+                })
+                .collect();
+            ResponseTemplate::new(200).set_body_json(json!({ "results": results }))
+        })
+        .mount(&mock_server)
+        .await;
+    let url = format!("{}/rerank", mock_server.uri());
+
+    let reranker_settings = json!({
+        "source": "rest",
+        "url": url,
+        "request": { "query": "{{query}}", "documents": ["{{text}}", "{{..}}"] },
+        "response": { "results": [{ "relevance": "{{score}}" }, "{{..}}"] },
+        "maxCandidates": 10,
+    });
+
+    (mock_server, reranker_settings)
+}
+
 pub async fn post<T: IntoUrl>(url: T, text: &str) -> reqwest::Result<reqwest::Response> {
     reqwest::Client::builder().build()?.post(url).json(&json!(text)).send().await
 }
@@ -2111,3 +2243,321 @@ async fn searchable_reindex() {
     }
     "###);
 }
+
+#[actix_rt::test]
+async fn server_fragments() {
+    let (_mock, setting) = create_mock_fragments().await;
+    let server = get_server_vector().await;
+    let index = server.index("doggo");
+
+    let (response, code) = index
+        .update_settings(json!({
+          "embedders": {
+              "rest": setting,
+          },
+        }))
+        .await;
+    snapshot!(code, @"202 Accepted");
+    let task = server.wait_task(response.uid()).await;
+    snapshot!(task["status"], @r###""succeeded""###);
+    let documents = json!([
+      {"id": 0, "name": "kefir", "image": "kefir.jpg"},
+      {"id": 1, "name": "intel", "image": "intel.jpg"},
+    ]);
+    let (value, code) = index.add_documents(documents, None).await;
+    snapshot!(code, @"202 Accepted");
+    let task = index.wait_task(value.uid()).await;
+    snapshot!(task["status"], @r###""succeeded""###);
+
+    let (response, code) = index
+        .search_post(json!({"q": "a dog", "hybrid": {"semanticRatio": 1.0, "embedder": "rest"}}))
+        .await;
+    snapshot!(code, @"200 OK");
+    snapshot!(json_string!(response["hits"]), @r###"
+    [
+      {
+        "id": 0,
+        "name": "kefir",
+        "image": "kefir.jpg"
+      },
+      {
+        "id": 1,
+        "name": "intel",
+        "image": "intel.jpg"
+      }
+    ]
+    "###);
+
+    let (response, code) = index
+        .search_post(json!({"q": "a cat", "hybrid": {"semanticRatio": 1.0, "embedder": "rest"}}))
+        .await;
+    snapshot!(code, @"200 OK");
+    snapshot!(json_string!(response["hits"]), @r###"
+    [
+      {
+        "id": 1,
+        "name": "intel",
+        "image": "intel.jpg"
+      },
+      {
+        "id": 0,
+        "name": "kefir",
+        "image": "kefir.jpg"
+      }
+    ]
+    "###);
+}
+
+#[actix_rt::test]
+async fn server_reranks_hybrid_results() {
+    let (_mock, mut setting) = create_mock().await;
+    let (_reranker_mock, reranker_setting) = create_mock_reranker().await;
+    setting["reranker"] = reranker_setting.0;
+    let server = get_server_vector().await;
+    let index = server.index("doggo");
+
+    let (response, code) = index
+        .update_settings(json!({
+          "embedders": {
+              "rest": setting,
+          },
+        }))
+        .await;
+    snapshot!(code, @"202 Accepted");
+    let task = server.wait_task(response.uid()).await;
+    snapshot!(task["status"], @r###""succeeded""###);
+    let documents = json!([
+      {"id": 0, "name": "kefir"},
+      {"id": 1, "name": "intel"},
+    ]);
+    let (value, code) = index.add_documents(documents, None).await;
+    snapshot!(code, @"202 Accepted");
+    let task = index.wait_task(value.uid()).await;
+    snapshot!(task["status"], @r###""succeeded""###);
+
+    let (response, code) = index
+        .search_post(json!({
+            "q": "kefir",
+            "hybrid": {"semanticRatio": 0.5, "embedder": "rest"},
+            "showRankingScoreDetails": true,
+            "attributesToRetrieve": ["name"],
+        }))
+        .await;
+    snapshot!(code, @"200 OK");
+    snapshot!(json_string!(response["hits"][0]["name"]), @r###""intel""###);
+    snapshot!(json_string!(response["hits"][1]["name"]), @r###""kefir""###);
+    snapshot!(json_string!(response["hits"][0]["_rankingScoreDetails"]["rerank"]), @r###"
+    {
+      "order": 0,
+      "score": 1.0
+    }
+    "###);
+}
+
+#[actix_rt::test]
+async fn bad_reranker_settings() {
+    let (_mock, mut setting) = create_mock().await;
+    let server = get_server_vector().await;
+    let index = server.index("doggo");
+
+    setting["reranker"] = serde_json::json!({
+        "source": "rest",
+        "url": "http://localhost:7777",
+        "request": { "documents": ["{{text}}", "{{..}}"] },
+        "response": { "results": ["{{score}}", "{{..}}"] },
+    });
+    let (response, code) = index.update_settings(json!({ "embedders": { "rest": setting } })).await;
+    snapshot!(code, @"400 Bad Request");
+    snapshot!(response["message"], @r###""`.embedders.rest.reranker`: in `request`: missing `{{query}}` placeholder""###);
+
+    setting["reranker"] = serde_json::json!({
+        "source": "huggingFace",
+        "maxCandidates": 1000,
+    });
+    let (response, code) = index.update_settings(json!({ "embedders": { "rest": setting } })).await;
+    snapshot!(code, @"400 Bad Request");
+    snapshot!(response["message"], @r###""`.embedders.rest.reranker`: `maxCandidates` cannot exceed 100, found 1000""###);
+}
+
+#[actix_rt::test]
+async fn server_chunks_documents() {
+    let (_mock, setting) = create_mock_chunks().await;
+    let server = get_server_vector().await;
+    let index = server.index("doggo");
+
+    let (response, code) = index
+        .update_settings(json!({
+          "embedders": {
+              "rest": setting,
+          },
+        }))
+        .await;
+    snapshot!(code, @"202 Accepted");
+    let task = server.wait_task(response.uid()).await;
+    snapshot!(task["status"], @r###""succeeded""###);
+    let documents = json!([
+      {"id": 0, "name": "kefir\n\nintel"},
+      {"id": 1, "name": "max"},
+    ]);
+    let (value, code) = index.add_documents(documents, None).await;
+    snapshot!(code, @"202 Accepted");
+    let task = index.wait_task(value.uid()).await;
+    snapshot!(task["status"], @r###""succeeded""###);
+
+    let (documents, _code) = index
+        .get_all_documents(GetAllDocumentsOptions { retrieve_vectors: true, ..Default::default() })
+        .await;
+    snapshot!(json_string!(documents["results"][0]["_vectors"]["rest"]["embeddings"]), @r###"
+    [
+      [
+        1.0,
+        0.0,
+        0.0
+      ],
+      [
+        0.0,
+        1.0,
+        0.0
+      ]
+    ]
+    "###);
+
+    // the document is ranked by its best chunk
+    let (response, code) = index
+        .search_post(json!({
+            "vector": [0.0, 1.0, 0.0],
+            "hybrid": {"semanticRatio": 1.0, "embedder": "rest"},
+            "showRankingScoreDetails": true,
+            "attributesToRetrieve": ["id"],
+        }))
+        .await;
+    snapshot!(code, @"200 OK");
+    snapshot!(json_string!(response["hits"][0]["id"]), @"0");
+    snapshot!(json_string!(response["hits"][0]["_rankingScoreDetails"]["vectorSort"]["chunk"]), @"1");
+    snapshot!(json_string!(response["hits"][1]["id"]), @"1");
+    snapshot!(json_string!(response["hits"][1]["_rankingScoreDetails"]["vectorSort"]["chunk"]), @"0");
+}
+
+#[actix_rt::test]
+async fn bad_chunking_settings() {
+    let (_mock, mut setting) = create_mock().await;
+    let server = get_server_vector().await;
+    let index = server.index("doggo");
+
+    setting["chunking"] =
+        serde_json::json!({ "strategy": "tokens", "maxTokens": 10, "overlap": 10 });
+    let (response, code) = index.update_settings(json!({ "embedders": { "rest": setting } })).await;
+    snapshot!(code, @"400 Bad Request");
+    snapshot!(response["message"], @r###""`.embedders.rest.chunking`: `overlap` must be smaller than `maxTokens`, found overlap 10 for maxTokens 10""###);
+
+    let (response, code) = index
+        .update_settings(json!({ "embedders": { "manual": {
+            "source": "userProvided",
+            "dimensions": 3,
+            "chunking": { "strategy": "paragraphs" },
+        } } }))
+        .await;
+    snapshot!(code, @"400 Bad Request");
+    snapshot!(response["message"], @r###""`.embedders.manual`: Field `chunking` unavailable for source `userProvided`.\n  - note: `chunking` is available for sources: `openAi`, `huggingFace`, `ollama`, `rest`\n  - note: available fields for source `userProvided`: `source`, `dimensions`, `distribution`, `binaryQuantized`, `reranker`""###);
+}
+
+/// Waits until the writer thread of the embedding cache stored the given number of embeddings.
+async fn wait_embedding_cache_entries(server: &Server, count: u64) {
+    for _ in 0..100 {
+        let (stats, code) = server.get_embedding_cache().await;
+        assert_eq!(code, 200, "{stats}");
+        if stats["entries"].as_u64().unwrap() >= count {
+            return;
+        }
+        tokio::time::sleep(Duration::from_millis(100)).await;
+    }
+    panic!("The embeddings were never stored in the embedding cache");
+}
+
+#[actix_rt::test]
+async fn persistent_embedding_cache() {
+    let (mock, setting) = create_mock().await;
+    let temp = tempfile::tempdir().unwrap();
+    let mut options = default_settings(temp.path());
+    options.experimental_persistent_embedding_cache_size =
+        Byte::from_u64_with_unit(100, Unit::MiB).unwrap();
+    let server = Server::new_with_options(options).await.unwrap();
+
+    let documents = json!([
+      {"id": 0, "name": "kefir"},
+      {"id": 1, "name": "intel"},
+    ]);
+    for uid in ["doggo", "doggo_copy"] {
+        let index = server.index(uid);
+        let (response, code) = index
+            .update_settings(json!({
+              "embedders": {
+                  "rest": setting,
+              },
+            }))
+            .await;
+        snapshot!(code, @"202 Accepted");
+        let task = server.wait_task(response.uid()).await;
+        snapshot!(task["status"], @r###""succeeded""###);
+        let (value, code) = index.add_documents(documents.clone(), None).await;
+        snapshot!(code, @"202 Accepted");
+        let task = index.wait_task(value.uid()).await;
+        snapshot!(task["status"], @r###""succeeded""###);
+        // the embeddings are written to the cache by a writer thread, after the task succeeded
+        wait_embedding_cache_entries(&server, 2).await;
+    }
+
+    // the documents of the second index were embedded from the cache
+    snapshot!(mock.received_requests().await.unwrap().len(), @"2");
+    let (stats, code) = server.get_embedding_cache().await;
+    snapshot!(code, @"200 OK");
+    snapshot!(json_string!(stats, { ".sizeBytes" => "[size]" }), @r###"
+    {
+      "entries": 2,
+      "sizeBytes": "[size]",
+      "hits": 2,
+      "misses": 2
+    }
+    "###);
+
+    let (documents, _code) = server
+        .index("doggo_copy")
+        .get_all_documents(GetAllDocumentsOptions { retrieve_vectors: true, ..Default::default() })
+        .await;
+    snapshot!(json_string!(documents["results"][1]["_vectors"]["rest"]["embeddings"]), @r###"
+    [
+      [
+        1.0,
+        1.0,
+        1.0
+      ]
+    ]
+    "###);
+
+    let (stats, code) = server.delete_embedding_cache().await;
+    snapshot!(code, @"200 OK");
+    snapshot!(json_string!(stats, { ".sizeBytes" => "[size]" }), @r###"
+    {
+      "entries": 0,
+      "sizeBytes": "[size]",
+      "hits": 2,
+      "misses": 2
+    }
+    "###);
+}
+
+#[actix_rt::test]
+async fn persistent_embedding_cache_disabled() {
+    let server = get_server_vector().await;
+
+    let (response, code) = server.get_embedding_cache().await;
+    snapshot!(code, @"400 Bad Request");
+    snapshot!(response, @r###"
+    {
+      "message": "Getting the embedding cache stats requires enabling the persistent embedding cache with the `--experimental-persistent-embedding-cache-size` option",
+      "code": "feature_not_enabled",
+      "type": "invalid_request",
+      "link": "https://docs.meilisearch.com/errors#feature_not_enabled"
+    }
+    "###);
+}
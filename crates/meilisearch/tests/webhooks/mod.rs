@@ -0,0 +1,356 @@
+//! To test the deliveries, we need to spawn a new server with a URL listening for
+//! post requests. The webhook handle starts a server, forwards all the received
+//! requests into a channel and answers with the planned status codes.
+
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use actix_web::http::StatusCode;
+use actix_web::web::{Bytes, Data};
+use actix_web::{post, App, HttpRequest, HttpResponse, HttpServer};
+use meili_snap::{json_string, snapshot};
+use tokio::sync::mpsc;
+
+use crate::common::{Server, Value};
+use crate::json;
+
+struct ReceivedRequest {
+    custom_header: Option<String>,
+    signature: Option<String>,
+    body: Vec<u8>,
+}
+
+struct WebhookState {
+    sender: mpsc::UnboundedSender<ReceivedRequest>,
+    /// The status codes to answer with, `200 OK` once they have all been used.
+    planned_statuses: Mutex<VecDeque<u16>>,
+}
+
+#[post("/")]
+async fn forward_request(req: HttpRequest, state: Data<WebhookState>, body: Bytes) -> HttpResponse {
+    let header = |name: &str| req.headers().get(name).map(|v| v.to_str().unwrap().to_string());
+    let status = state.planned_statuses.lock().unwrap().pop_front().unwrap_or(200);
+
+    state
+        .sender
+        .send(ReceivedRequest {
+            custom_header: header("x-custom"),
+            signature: header("x-meilisearch-signature"),
+            body: body.to_vec(),
+        })
+        .unwrap();
+    HttpResponse::new(StatusCode::from_u16(status).unwrap())
+}
+
+struct WebhookHandle {
+    pub server_handle: tokio::task::JoinHandle<Result<(), std::io::Error>>,
+    pub url: String,
+    pub receiver: mpsc::UnboundedReceiver<ReceivedRequest>,
+}
+
+async fn create_webhook_server(planned_statuses: Vec<u16>) -> WebhookHandle {
+    let (sender, receiver) = mpsc::unbounded_channel();
+    let state =
+        Arc::new(WebhookState { sender, planned_statuses: Mutex::new(planned_statuses.into()) });
+
+    // By listening on the port 0, the system will give us any available port.
+    let server = HttpServer::new(move || {
+        App::new().service(forward_request).app_data(Data::from(state.clone()))
+    })
+    .bind(("127.0.0.1", 0))
+    .unwrap();
+    let (ip, scheme) = server.addrs_with_scheme()[0];
+    let url = format!("{scheme}://{ip}/");
+
+    let server_handle = tokio::spawn(server.run());
+    WebhookHandle { server_handle, url, receiver }
+}
+
+/// Waits until the delivery thread logged the given number of deliveries.
+async fn wait_deliveries(server: &Server, uuid: &str, count: usize) -> Value {
+    for _ in 0..100 {
+        let (response, code) = server.get_webhook_deliveries(uuid).await;
+        assert_eq!(code, 200, "{response}");
+        if response["results"].as_array().unwrap().len() >= count {
+            return response;
+        }
+        tokio::time::sleep(Duration::from_millis(100)).await;
+    }
+    panic!("The webhook deliveries were never logged");
+}
+
+#[actix_rt::test]
+async fn create_update_and_delete_webhooks() {
+    let server = Server::new().await;
+
+    let (response, code) = server
+        .create_webhook(json!({
+            "url": "https://example.com/hook",
+            "headers": { "Authorization": "Bearer token" },
+            "filter": { "indexUids": ["movies", "products_*"], "statuses": ["failed"] },
+            "signingSecret": "secret",
+        }))
+        .await;
+    snapshot!(code, @"201 Created");
+    snapshot!(json_string!(response, { ".uuid" => "[uuid]", ".createdAt" => "[date]", ".updatedAt" => "[date]" }), @r###"
+    {
+      "uuid": "[uuid]",
+      "url": "https://example.com/hook",
+      "headers": {
+        "Authorization": "XXX..."
+      },
+      "filter": {
+        "indexUids": [
+          "movies",
+          "products_*"
+        ],
+        "types": null,
+        "statuses": [
+          "failed"
+        ]
+      },
+      "hasSigningSecret": true,
+      "createdAt": "[date]",
+      "updatedAt": "[date]"
+    }
+    "###);
+    let uuid = response["uuid"].as_str().unwrap().to_string();
+
+    let (response, code) = server
+        .patch_webhook(
+            &uuid,
+            json!({ "filter": { "types": ["documentAdditionOrUpdate"] }, "signingSecret": null }),
+        )
+        .await;
+    snapshot!(code, @"200 OK");
+    snapshot!(json_string!(response, { ".uuid" => "[uuid]", ".createdAt" => "[date]", ".updatedAt" => "[date]" }), @r###"
+    {
+      "uuid": "[uuid]",
+      "url": "https://example.com/hook",
+      "headers": {
+        "Authorization": "XXX..."
+      },
+      "filter": {
+        "indexUids": null,
+        "types": [
+          "documentAdditionOrUpdate"
+        ],
+        "statuses": null
+      },
+      "hasSigningSecret": false,
+      "createdAt": "[date]",
+      "updatedAt": "[date]"
+    }
+    "###);
+
+    let (response, code) = server.get_webhooks().await;
+    snapshot!(code, @"200 OK");
+    assert_eq!(response["results"].as_array().unwrap().len(), 1);
+    assert_eq!(response["results"][0]["uuid"], uuid.as_str());
+
+    let (_response, code) = server.delete_webhook(&uuid).await;
+    snapshot!(code, @"204 No Content");
+
+    let (response, code) = server.get_webhook(&uuid).await;
+    snapshot!(code, @"404 Not Found");
+    snapshot!(json_string!(response, { ".message" => "[message]" }), @r###"
+    {
+      "message": "[message]",
+      "code": "webhook_not_found",
+      "type": "invalid_request",
+      "link": "https://docs.meilisearch.com/errors#webhook_not_found"
+    }
+    "###);
+
+    let (response, code) = server.get_webhooks().await;
+    snapshot!(code, @"200 OK");
+    snapshot!(json_string!(response), @r###"
+    {
+      "results": []
+    }
+    "###);
+}
+
+#[actix_rt::test]
+async fn webhook_errors() {
+    let server = Server::new().await;
+
+    let (response, code) = server.create_webhook(json!({ "headers": {} })).await;
+    snapshot!(code, @"400 Bad Request");
+    snapshot!(json_string!(response), @r###"
+    {
+      "message": "Missing field `url`",
+      "code": "missing_webhook_url",
+      "type": "invalid_request",
+      "link": "https://docs.meilisearch.com/errors#missing_webhook_url"
+    }
+    "###);
+
+    let (response, code) = server.create_webhook(json!({ "url": "ftp://example.com" })).await;
+    snapshot!(code, @"400 Bad Request");
+    snapshot!(json_string!(response), @r###"
+    {
+      "message": "Invalid `.url` (`ftp://example.com`): the scheme must be `http` or `https`.",
+      "code": "invalid_webhook_url",
+      "type": "invalid_request",
+      "link": "https://docs.meilisearch.com/errors#invalid_webhook_url"
+    }
+    "###);
+
+    let (response, code) = server
+        .create_webhook(json!({ "url": "https://example.com", "headers": { "in valid": "value" } }))
+        .await;
+    snapshot!(code, @"400 Bad Request");
+    snapshot!(json_string!(response), @r###"
+    {
+      "message": "Invalid `.headers`: `in valid` is not a valid header name.",
+      "code": "invalid_webhook_headers",
+      "type": "invalid_request",
+      "link": "https://docs.meilisearch.com/errors#invalid_webhook_headers"
+    }
+    "###);
+
+    let (response, code) = server
+        .create_webhook(json!({ "url": "https://example.com", "filter": { "statuses": ["done"] } }))
+        .await;
+    snapshot!(code, @"400 Bad Request");
+    snapshot!(json_string!(response), @r###"
+    {
+      "message": "Invalid value in `.filter.statuses`: `done` is not a valid task status. Available statuses are `enqueued`, `processing`, `succeeded`, `failed`, `canceled`.",
+      "code": "invalid_webhook_filter",
+      "type": "invalid_request",
+      "link": "https://docs.meilisearch.com/errors#invalid_webhook_filter"
+    }
+    "###);
+
+    let (response, code) = server.get_webhook("not-a-uuid").await;
+    snapshot!(code, @"404 Not Found");
+    snapshot!(json_string!(response), @r###"
+    {
+      "message": "Webhook `not-a-uuid` not found.",
+      "code": "webhook_not_found",
+      "type": "invalid_request",
+      "link": "https://docs.meilisearch.com/errors#webhook_not_found"
+    }
+    "###);
+}
+
+#[actix_rt::test]
+async fn deliver_the_filtered_tasks_with_a_signature() {
+    let WebhookHandle { server_handle, url, mut receiver } = create_webhook_server(vec![]).await;
+    let server = Server::new().await;
+
+    let (response, code) = server
+        .create_webhook(json!({
+            "url": url,
+            "headers": { "X-Custom": "custom value" },
+            "filter": { "indexUids": ["doggos"] },
+            "signingSecret": "secret",
+        }))
+        .await;
+    assert_eq!(code, 201, "{response}");
+    let uuid = response["uuid"].as_str().unwrap().to_string();
+
+    let (task, _code) = server.index("catto").add_documents(json!([{ "id": 1 }]), None).await;
+    server.wait_task(task.uid()).await.succeeded();
+    let (task, _code) = server.index("doggos").add_documents(json!([{ "id": 1 }]), None).await;
+    server.wait_task(task.uid()).await.succeeded();
+
+    // Only the task of the `doggos` index matches the filter.
+    let request = receiver.recv().await.unwrap();
+    assert_eq!(request.custom_header.as_deref(), Some("custom value"));
+    assert_eq!(
+        request.signature.as_deref(),
+        Some(index_scheduler::sign_webhook_payload("secret", &request.body).as_str())
+    );
+    let payload = String::from_utf8(request.body).unwrap();
+    let tasks: Vec<serde_json::Value> =
+        payload.lines().map(|line| serde_json::from_str(line).unwrap()).collect();
+    assert_eq!(tasks.len(), 1, "{payload}");
+    assert_eq!(tasks[0]["uid"], task.uid());
+    assert_eq!(tasks[0]["indexUid"], "doggos");
+
+    let response = wait_deliveries(&server, &uuid, 1).await;
+    snapshot!(json_string!(response, { ".results[].webhookUuid" => "[uuid]", ".results[].taskUids" => "[uids]", ".results[].enqueuedAt" => "[date]", ".results[].finishedAt" => "[date]" }), @r###"
+    {
+      "results": [
+        {
+          "uid": 0,
+          "webhookUuid": "[uuid]",
+          "taskUids": "[uids]",
+          "status": "succeeded",
+          "attempts": 1,
+          "statusCode": 200,
+          "error": null,
+          "enqueuedAt": "[date]",
+          "finishedAt": "[date]"
+        }
+      ],
+      "offset": 0,
+      "limit": 20,
+      "total": 1
+    }
+    "###);
+
+    server_handle.abort();
+}
+
+#[actix_rt::test]
+async fn retry_the_failed_deliveries() {
+    // The webhook fails once with a server error, then rejects the payload.
+    let WebhookHandle { server_handle, url, mut receiver } =
+        create_webhook_server(vec![503, 200, 400]).await;
+    let server = Server::new().await;
+
+    let (response, code) = server.create_webhook(json!({ "url": url })).await;
+    assert_eq!(code, 201, "{response}");
+    let uuid = response["uuid"].as_str().unwrap().to_string();
+
+    let (task, _code) = server.index("doggos").create(None).await;
+    server.wait_task(task.uid()).await.succeeded();
+    // The failed request and its retry
+    receiver.recv().await.unwrap();
+    receiver.recv().await.unwrap();
+    wait_deliveries(&server, &uuid, 1).await;
+
+    // Client errors are not retried
+    let (task, _code) = server.index("doggos").delete().await;
+    server.wait_task(task.uid()).await.succeeded();
+    receiver.recv().await.unwrap();
+
+    let response = wait_deliveries(&server, &uuid, 2).await;
+    snapshot!(json_string!(response, { ".results[].webhookUuid" => "[uuid]", ".results[].taskUids" => "[uids]", ".results[].enqueuedAt" => "[date]", ".results[].finishedAt" => "[date]" }), @r###"
+    {
+      "results": [
+        {
+          "uid": 1,
+          "webhookUuid": "[uuid]",
+          "taskUids": "[uids]",
+          "status": "failed",
+          "attempts": 1,
+          "statusCode": 400,
+          "error": "The webhook responded with the status code `400`.",
+          "enqueuedAt": "[date]",
+          "finishedAt": "[date]"
+        },
+        {
+          "uid": 0,
+          "webhookUuid": "[uuid]",
+          "taskUids": "[uids]",
+          "status": "succeeded",
+          "attempts": 2,
+          "statusCode": 200,
+          "error": null,
+          "enqueuedAt": "[date]",
+          "finishedAt": "[date]"
+        }
+      ],
+      "offset": 0,
+      "limit": 20,
+      "total": 2
+    }
+    "###);
+
+    server_handle.abort();
+}
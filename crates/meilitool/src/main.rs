@@ -1,5 +1,5 @@
 use std::fs::{read_dir, read_to_string, remove_file, File};
-use std::io::{BufWriter, Write as _};
+use std::io::{BufRead as _, BufReader, BufWriter, Write as _};
 use std::path::PathBuf;
 use std::time::Instant;
 
@@ -7,6 +7,7 @@ use anyhow::{bail, Context};
 use clap::{Parser, Subcommand, ValueEnum};
 use dump::{DumpWriter, IndexMetadata};
 use file_store::FileStore;
+use flate2::read::GzDecoder;
 use meilisearch_auth::{open_auth_store_env, AuthController};
 use meilisearch_types::batches::Batch;
 use meilisearch_types::heed::types::{Bytes, SerdeJson, Str};
@@ -17,7 +18,7 @@ use meilisearch_types::milli::constants::RESERVED_VECTORS_FIELD_NAME;
 use meilisearch_types::milli::documents::{obkv_to_object, DocumentsBatchReader};
 use meilisearch_types::milli::vector::parsed_vectors::{ExplicitVectors, VectorOrArrayOfVectors};
 use meilisearch_types::milli::{obkv_to_json, BEU32};
-use meilisearch_types::tasks::{Status, Task};
+use meilisearch_types::tasks::{Kind, Status, Task};
 use meilisearch_types::versioning::{get_version, parse_version};
 use meilisearch_types::Index;
 use serde_json::Value::Object;
@@ -137,6 +138,33 @@ enum Command {
         #[arg(long, value_delimiter = ',')]
         index_part: Vec<IndexPart>,
     },
+
+    /// Exports the tasks archived by Meilisearch in NDJSON format to stdout.
+    ///
+    /// Meilisearch archives the tasks and batches before deleting them from the task queue when
+    /// it's launched with `--experimental-tasks-archive-dir`. This command reads the archives from
+    /// this directory and doesn't need the database, it can be executed while Meilisearch is running.
+    ExportArchivedTasks {
+        /// The directory in which Meilisearch archives the tasks.
+        #[arg(long)]
+        archive_dir: PathBuf,
+
+        /// Export the archived batches instead of the archived tasks.
+        #[arg(long)]
+        batches: bool,
+
+        /// Only export the tasks or batches of these indexes.
+        #[arg(long, value_delimiter = ',')]
+        index_uids: Vec<String>,
+
+        /// Only export the tasks or batches with these statuses, e.g. `failed,canceled`.
+        #[arg(long, value_delimiter = ',')]
+        statuses: Vec<Status>,
+
+        /// Only export the tasks or batches of these types, e.g. `documentAdditionOrUpdate`.
+        #[arg(long, value_delimiter = ',')]
+        types: Vec<Kind>,
+    },
 }
 
 #[derive(Clone, ValueEnum)]
@@ -148,6 +176,13 @@ enum IndexPart {
 fn main() -> anyhow::Result<()> {
     let Cli { db_path, command } = Cli::parse();
 
+    // The archives are read from their own directory, there is no database to check.
+    if let Command::ExportArchivedTasks { archive_dir, batches, index_uids, statuses, types } =
+        command
+    {
+        return export_archived_tasks(archive_dir, batches, &index_uids, &statuses, &types);
+    }
+
     let detected_version = get_version(&db_path).context("While checking the version file")?;
 
     match command {
@@ -166,6 +201,7 @@ fn main() -> anyhow::Result<()> {
         Command::HairDryer { index_name, index_part } => {
             hair_dryer(db_path, &index_name, &index_part)
         }
+        Command::ExportArchivedTasks { .. } => unreachable!("handled before checking the version"),
     }
 }
 
@@ -688,3 +724,99 @@ fn hair_dryer(
 
     Ok(())
 }
+
+/// Writes the archived tasks, or batches, that match the filters to stdout, in the order in which
+/// they were archived.
+fn export_archived_tasks(
+    archive_dir: PathBuf,
+    batches: bool,
+    index_uids: &[String],
+    statuses: &[Status],
+    types: &[Kind],
+) -> anyhow::Result<()> {
+    let prefix = if batches { "batches-" } else { "tasks-" };
+    let entries = read_dir(&archive_dir).with_context(|| {
+        format!("While trying to read the content of {:?}", archive_dir.display())
+    })?;
+
+    // The archives are named `{prefix}{first_uid}-{last_uid}-{timestamp}[-{suffix}].ndjson.gz`,
+    // the uids restart from zero when the database is recreated so we sort them by date first.
+    let mut archives = Vec::new();
+    for result in entries {
+        let path = result?.path();
+        let Some(name) = path.file_name().and_then(|name| name.to_str()) else { continue };
+        let Some(name) = name.strip_prefix(prefix).and_then(|n| n.strip_suffix(".ndjson.gz"))
+        else {
+            continue;
+        };
+        let parts: Result<Vec<u128>, _> = name.split('-').map(|part| part.parse()).collect();
+        let (first_uid, timestamp, suffix) = match parts.as_deref() {
+            Ok([first_uid, _last_uid, timestamp]) => (*first_uid, *timestamp, 0),
+            Ok([first_uid, _last_uid, timestamp, suffix]) => (*first_uid, *timestamp, *suffix),
+            _ => {
+                eprintln!("Ignoring {:?}, it is not a valid archive name", path.display());
+                continue;
+            }
+        };
+        archives.push(((timestamp, suffix, first_uid), path));
+    }
+    archives.sort_unstable();
+
+    let statuses: Vec<_> = statuses.iter().map(|status| status.to_string()).collect();
+    let types: Vec<_> = types.iter().map(|kind| kind.to_string()).collect();
+
+    let matches = |entry: &serde_json::Value, filter: &[String], field: ArchivedField| {
+        filter.is_empty() || filter.iter().any(|value| field.matches(entry, batches, value))
+    };
+
+    let mut count = 0usize;
+    let mut stdout = BufWriter::new(std::io::stdout());
+    for (_, path) in archives {
+        let file = File::open(&path)
+            .with_context(|| format!("While trying to open {:?}", path.display()))?;
+        let reader = BufReader::new(GzDecoder::new(file));
+        for line in reader.lines() {
+            let line = line.with_context(|| format!("While reading {:?}", path.display()))?;
+            let entry: serde_json::Value = serde_json::from_str(&line)
+                .with_context(|| format!("While parsing an entry of {:?}", path.display()))?;
+
+            if matches(&entry, index_uids, ArchivedField::IndexUid)
+                && matches(&entry, &statuses, ArchivedField::Status)
+                && matches(&entry, &types, ArchivedField::Type)
+            {
+                stdout.write_all(line.as_bytes())?;
+                stdout.write_all(b"\n")?;
+                count += 1;
+            }
+        }
+    }
+    stdout.flush()?;
+
+    eprintln!("Exported {count} archived {}", if batches { "batches" } else { "tasks" });
+
+    Ok(())
+}
+
+/// The fields of the archived tasks and batches on which they can be filtered.
+#[derive(Clone, Copy)]
+enum ArchivedField {
+    IndexUid,
+    Status,
+    Type,
+}
+
+impl ArchivedField {
+    /// Whether the archived task has this value, or any task of the archived batch.
+    fn matches(self, entry: &serde_json::Value, batch: bool, value: &str) -> bool {
+        let (task_field, batch_stat) = match self {
+            ArchivedField::IndexUid => ("indexUid", "indexUids"),
+            ArchivedField::Status => ("status", "status"),
+            ArchivedField::Type => ("type", "types"),
+        };
+        if batch {
+            entry["stats"][batch_stat].as_object().is_some_and(|stat| stat.contains_key(value))
+        } else {
+            entry[task_field].as_str() == Some(value)
+        }
+    }
+}
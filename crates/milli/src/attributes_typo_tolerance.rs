@@ -0,0 +1,65 @@
+use std::collections::BTreeMap;
+
+use heed::types::{SerdeJson, Str};
+use heed::{RoTxn, RwTxn};
+use serde::{Deserialize, Serialize};
+
+use crate::index::{main_key, PrefixSearch};
+use crate::Index;
+
+/// The typo tolerance and prefix search rules of a searchable attribute.
+///
+/// The rules that are not defined fall back to the index-wide ones.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct AttributeTypoTolerance {
+    pub enabled: Option<bool>,
+    pub min_word_len_one_typo: Option<u8>,
+    pub min_word_len_two_typos: Option<u8>,
+    pub prefix_search: Option<PrefixSearch>,
+}
+
+impl Index {
+    /// The typo tolerance and prefix search rules overriding the index-wide ones, by attribute.
+    pub fn attributes_typo_tolerance(
+        &self,
+        rtxn: &RoTxn<'_>,
+    ) -> heed::Result<BTreeMap<String, AttributeTypoTolerance>> {
+        Ok(self
+            .main
+            .remap_types::<Str, SerdeJson<_>>()
+            .get(rtxn, main_key::ATTRIBUTES_TYPO_TOLERANCE)?
+            .unwrap_or_default())
+    }
+
+    pub(crate) fn put_attributes_typo_tolerance(
+        &self,
+        wtxn: &mut RwTxn<'_>,
+        rules: &BTreeMap<String, AttributeTypoTolerance>,
+    ) -> heed::Result<()> {
+        self.main.remap_types::<Str, SerdeJson<_>>().put(
+            wtxn,
+            main_key::ATTRIBUTES_TYPO_TOLERANCE,
+            rules,
+        )
+    }
+
+    pub(crate) fn delete_attributes_typo_tolerance(
+        &self,
+        wtxn: &mut RwTxn<'_>,
+    ) -> heed::Result<bool> {
+        self.main.remap_key_type::<Str>().delete(wtxn, main_key::ATTRIBUTES_TYPO_TOLERANCE)
+    }
+}
+
+/// The rules of the most specific attribute the field belongs to, if any.
+pub fn attribute_typo_tolerance<'a>(
+    rules: &'a BTreeMap<String, AttributeTypoTolerance>,
+    field: &str,
+) -> Option<&'a AttributeTypoTolerance> {
+    rules
+        .iter()
+        .filter(|(attribute, _)| crate::is_faceted_by(field, attribute))
+        .max_by_key(|(attribute, _)| attribute.len())
+        .map(|(_, rules)| rules)
+}
@@ -251,6 +251,8 @@ and can not be more than 511 bytes.", .document_id.to_string()
         valid_fields: BTreeSet<String>,
         hidden_fields: bool,
     },
+    #[error("Invalid advanced query syntax: {0}.")]
+    InvalidAdvancedQuery(String),
     #[error("An LMDB environment is already opened")]
     EnvAlreadyOpened,
     #[error("You must specify where `sort` is listed in the rankingRules setting to use the sort parameter at search time.")]
@@ -279,6 +281,16 @@ and can not be more than 511 bytes.", .document_id.to_string()
     UnknownInternalDocumentId { document_id: DocumentId },
     #[error("`minWordSizeForTypos` setting is invalid. `oneTypo` and `twoTypos` fields should be between `0` and `255`, and `twoTypos` should be greater or equals to `oneTypo` but found `oneTypo: {0}` and twoTypos: {1}`.")]
     InvalidMinTypoWordLenSetting(u8, u8),
+    #[error("`stemming` setting is invalid. The locale `{locale}` cannot be stemmed. Supported locales are: `{}`.",
+        crate::stemming::supported_stemming_languages().collect::<Vec<_>>().join(", "),
+    )]
+    UnsupportedStemmingLocale { locale: String },
+    #[error("`synonymRules` setting is invalid. The rule at index {rule_index} {reason}.")]
+    InvalidSynonymRule { rule_index: usize, reason: String },
+    #[error("`ngramAttributes` setting is invalid. The `minLength` of `{attribute}` must be at least {min}, found {min_length}.")]
+    InvalidNgramMinLength { attribute: String, min_length: u8, min: u8 },
+    #[error("`queryRules` setting is invalid. The rule at index {rule_index} {reason}.")]
+    InvalidQueryRule { rule_index: usize, reason: String },
     #[error(transparent)]
     VectorEmbeddingError(#[from] crate::vector::Error),
     #[error(transparent)]
@@ -387,8 +399,10 @@ and can not be more than 511 bytes.", .document_id.to_string()
     DocumentEditionRuntimeError(Box<EvalAltResult>),
     #[error("Document edition runtime error encountered while compiling the function: {0}")]
     DocumentEditionCompilationError(rhai::ParseError),
-    #[error("{0}")]
-    DocumentEmbeddingError(String),
+    /// `transient` is set when the embedding failed because of a temporary failure of the embedder,
+    /// such as an unreachable server, and processing the documents again later may succeed.
+    #[error("{message}")]
+    DocumentEmbeddingError { message: String, transient: bool },
 }
 
 impl From<crate::vector::Error> for Error {
@@ -1,6 +1,6 @@
 //! The fieldids weights map is in charge of storing linking the searchable fields with their weights.
 
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 
 use serde::{Deserialize, Serialize};
 
@@ -33,6 +33,24 @@ impl FieldidsWeightsMap {
         }
     }
 
+    /// Create the map from the boosts of the searchable fields, the higher the boost the more
+    /// important the field, and return it along with the highest weight of the map.
+    ///
+    /// The most boosted fields are given a weight of 0 and the weights grow as the boosts
+    /// decrease. The fields without a boost are given the weight of the least boosted field.
+    pub fn from_boosts(boosts: Vec<(FieldId, Option<Weight>)>) -> (Self, Weight) {
+        let max_boost = boosts.iter().filter_map(|(_, boost)| *boost).max().unwrap_or_default();
+        let min_boost = boosts.iter().filter_map(|(_, boost)| *boost).min().unwrap_or_default();
+        let max_weight = max_boost - min_boost;
+
+        let map = boosts
+            .into_iter()
+            .map(|(fid, boost)| (fid, boost.map_or(max_weight, |boost| max_boost - boost)))
+            .collect();
+
+        (FieldidsWeightsMap { map }, max_weight)
+    }
+
     /// Removes a field id from the map, returning the associated weight previously in the map.
     pub fn remove(&mut self, fid: FieldId) -> Option<Weight> {
         self.map.remove(&fid)
@@ -48,3 +66,67 @@ impl FieldidsWeightsMap {
         self.map.keys().copied()
     }
 }
+
+/// The boost defined for a searchable field by the `searchableAttributesWeights` setting.
+///
+/// The boost of the most specific attribute the field belongs to is used, `1` by default.
+pub fn searchable_attribute_boost(boosts: &BTreeMap<String, Weight>, field: &str) -> Weight {
+    boosts
+        .iter()
+        .filter(|(attribute, _)| crate::is_faceted_by(field, attribute))
+        .max_by_key(|(attribute, _)| attribute.len())
+        .map_or(DEFAULT_ATTRIBUTE_BOOST, |(_, boost)| *boost)
+}
+
+/// The boost of a searchable attribute when it is not explicitly specified.
+pub const DEFAULT_ATTRIBUTE_BOOST: Weight = 1;
+
+/// Splits an attribute of the `attributesToSearchOn` search parameter from its boost,
+/// e.g. `title^3` is the `title` attribute with a boost of `3`.
+///
+/// The attribute is returned as-is when what follows the last `^` is not a valid boost.
+pub fn split_attribute_boost(attribute: &str) -> (&str, Option<Weight>) {
+    match attribute.rsplit_once('^') {
+        Some((name, boost)) => match boost.parse() {
+            Ok(boost) => (name, Some(boost)),
+            Err(_) => (attribute, None),
+        },
+        None => (attribute, None),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn split_boosts() {
+        assert_eq!(split_attribute_boost("title"), ("title", None));
+        assert_eq!(split_attribute_boost("title^3"), ("title", Some(3)));
+        assert_eq!(split_attribute_boost("a^b^2"), ("a^b", Some(2)));
+        assert_eq!(split_attribute_boost("title^"), ("title^", None));
+        assert_eq!(split_attribute_boost("title^-1"), ("title^-1", None));
+    }
+
+    #[test]
+    fn weights_from_boosts() {
+        let (map, max_weight) = FieldidsWeightsMap::from_boosts(vec![
+            (0, Some(3)),
+            (1, Some(1)),
+            (2, Some(2)),
+            (3, None),
+        ]);
+        assert_eq!(max_weight, 2);
+        assert_eq!(map.weight(0), Some(0));
+        assert_eq!(map.weight(1), Some(2));
+        assert_eq!(map.weight(2), Some(1));
+        assert_eq!(map.weight(3), Some(2));
+
+        let boosts = BTreeMap::from([("tags".to_string(), 2), ("tags.main".to_string(), 5)]);
+        assert_eq!(searchable_attribute_boost(&boosts, "title"), 1);
+        assert_eq!(searchable_attribute_boost(&boosts, "tags"), 2);
+        assert_eq!(searchable_attribute_boost(&boosts, "tags.other"), 2);
+        assert_eq!(searchable_attribute_boost(&boosts, "tags.main"), 5);
+        assert_eq!(searchable_attribute_boost(&boosts, "tagsother"), 1);
+    }
+}
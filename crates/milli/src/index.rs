@@ -59,8 +59,12 @@ pub mod main_key {
     pub const DICTIONARY_KEY: &str = "dictionary";
     pub const SYNONYMS_KEY: &str = "synonyms";
     pub const USER_DEFINED_SYNONYMS_KEY: &str = "user-defined-synonyms";
+    pub const WEIGHTED_SYNONYMS_KEY: &str = "weighted-synonyms";
+    pub const USER_DEFINED_SYNONYM_RULES_KEY: &str = "user-defined-synonym-rules";
     pub const WORDS_FST_KEY: &str = "words-fst";
     pub const WORDS_PREFIXES_FST_KEY: &str = "words-prefixes-fst";
+    pub const STEMS_FST_KEY: &str = "stems-fst";
+    pub const NGRAMS_FST_KEY: &str = "ngrams-fst";
     pub const CREATED_AT_KEY: &str = "created-at";
     pub const UPDATED_AT_KEY: &str = "updated-at";
     pub const AUTHORIZE_TYPOS: &str = "authorize-typos";
@@ -79,6 +83,12 @@ pub mod main_key {
     pub const PREFIX_SEARCH: &str = "prefix_search";
     pub const DOCUMENTS_STATS: &str = "documents_stats";
     pub const DISABLED_TYPOS_TERMS: &str = "disabled_typos_terms";
+    pub const STEMMING: &str = "stemming";
+    pub const SEARCHABLE_ATTRIBUTES_WEIGHTS: &str = "searchable_attributes_weights";
+    pub const ATTRIBUTES_TYPO_TOLERANCE: &str = "attributes_typo_tolerance";
+    pub const NGRAM_ATTRIBUTES: &str = "ngram_attributes";
+    pub const QUERY_RULES: &str = "query_rules";
+    pub const QUERY_RULES_REGEXES: &str = "query-rules-regexes";
 }
 
 pub mod db_name {
@@ -600,6 +610,39 @@ impl Index {
         self.main.remap_key_type::<Str>().delete(wtxn, main_key::FIELDIDS_WEIGHTS_MAP_KEY)
     }
 
+    /// Writes the user defined weights of the searchable attributes, the higher the more important.
+    pub(crate) fn put_searchable_attributes_weights(
+        &self,
+        wtxn: &mut RwTxn<'_>,
+        weights: &BTreeMap<String, Weight>,
+    ) -> heed::Result<()> {
+        self.main.remap_types::<Str, SerdeJson<_>>().put(
+            wtxn,
+            main_key::SEARCHABLE_ATTRIBUTES_WEIGHTS,
+            weights,
+        )
+    }
+
+    /// Get the user defined weights of the searchable attributes, the higher the more important.
+    pub fn searchable_attributes_weights(
+        &self,
+        rtxn: &RoTxn<'_>,
+    ) -> heed::Result<BTreeMap<String, Weight>> {
+        Ok(self
+            .main
+            .remap_types::<Str, SerdeJson<_>>()
+            .get(rtxn, main_key::SEARCHABLE_ATTRIBUTES_WEIGHTS)?
+            .unwrap_or_default())
+    }
+
+    /// Delete the user defined weights of the searchable attributes
+    pub(crate) fn delete_searchable_attributes_weights(
+        &self,
+        wtxn: &mut RwTxn<'_>,
+    ) -> heed::Result<bool> {
+        self.main.remap_key_type::<Str>().delete(wtxn, main_key::SEARCHABLE_ATTRIBUTES_WEIGHTS)
+    }
+
     pub fn max_searchable_attribute_weight(&self, rtxn: &RoTxn<'_>) -> Result<Option<Weight>> {
         let user_defined_searchable_fields = self.user_defined_searchable_fields(rtxn)?;
         if let Some(user_defined_searchable_fields) = user_defined_searchable_fields {
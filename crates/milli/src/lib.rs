@@ -10,6 +10,7 @@ pub mod documents;
 
 mod asc_desc;
 mod attribute_patterns;
+pub mod attributes_typo_tolerance;
 mod criterion;
 pub mod database_stats;
 pub mod disabled_typos_terms;
@@ -21,11 +22,15 @@ mod filterable_attributes_rules;
 pub mod heed_codec;
 pub mod index;
 mod localized_attributes_rules;
+pub mod ngram_attributes;
 pub mod order_by_map;
 pub mod prompt;
 pub mod proximity;
+pub mod query_rules;
 pub mod score_details;
 mod search;
+pub mod stemming;
+pub mod synonym_rules;
 mod thread_pool_no_abort;
 pub mod update;
 pub mod vector;
@@ -48,7 +53,7 @@ use fxhash::{FxHasher32, FxHasher64};
 pub use grenad::CompressionType;
 pub use search::new::{
     execute_search, filtered_universe, DefaultSearchLogger, GeoSortStrategy, SearchContext,
-    SearchLogger, VisualSearchLogger,
+    SearchLogger, VectorSearchParams, VisualSearchLogger,
 };
 use serde_json::Value;
 pub use thread_pool_no_abort::{PanicCatched, ThreadPoolNoAbort, ThreadPoolNoAbortBuilder};
@@ -76,11 +81,13 @@ pub use self::heed_codec::{
 };
 pub use self::index::Index;
 pub use self::localized_attributes_rules::LocalizedAttributesRule;
+pub use self::search::autocomplete::{Autocomplete, Completion};
 pub use self::search::facet::{FacetValueHit, SearchForFacetValues};
 pub use self::search::similar::Similar;
 pub use self::search::{
     FacetDistribution, Filter, FormatOptions, MatchBounds, MatcherBuilder, MatchingWords, OrderBy,
-    Search, SearchResult, SemanticSearch, TermsMatchingStrategy, DEFAULT_VALUES_PER_FACET,
+    QuerySyntax, Search, SearchResult, SemanticSearch, TermsMatchingStrategy,
+    DEFAULT_VALUES_PER_FACET,
 };
 pub use self::update::ChannelCongestion;
 
@@ -0,0 +1,182 @@
+use std::borrow::Cow;
+use std::collections::BTreeMap;
+
+use heed::types::{Bytes, SerdeJson, Str};
+use heed::{RoTxn, RwTxn};
+use serde::{Deserialize, Serialize};
+
+use crate::index::main_key;
+use crate::{FieldId, Index, Result};
+
+/// The minimum length, in characters, of the n-grams when it is not defined.
+pub const DEFAULT_NGRAM_MIN_LENGTH: u8 = 3;
+
+/// The minimum length, in characters, that can be given to the n-grams.
+pub const MIN_NGRAM_LENGTH: u8 = 2;
+
+/// The maximum number of words matched by a part of a word when it is not defined.
+pub const DEFAULT_NGRAM_MAX_MATCHES: u16 = 50;
+
+/// The words longer than this number of characters are not split into n-grams.
+pub const MAX_NGRAM_WORD_LENGTH: usize = 32;
+
+/// The separator between the n-gram and the word in the keys of the n-grams FST.
+const NGRAMS_FST_SEPARATOR: u8 = 0;
+
+/// The parts of the words of an attribute that can be searched on their own.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum NgramMode {
+    /// Every part of the words, e.g. `452` in `94521`.
+    Ngram,
+    /// The beginnings of the words only, e.g. `945` in `94521`.
+    EdgeNgram,
+}
+
+/// How the words of an attribute are split into n-grams.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct NgramAttribute {
+    pub mode: NgramMode,
+    pub min_length: u8,
+    /// The maximum number of words matched by a part of a word, the other words are ignored.
+    #[serde(default = "default_ngram_max_matches")]
+    pub max_matches: u16,
+}
+
+fn default_ngram_max_matches() -> u16 {
+    DEFAULT_NGRAM_MAX_MATCHES
+}
+
+impl NgramAttribute {
+    /// Returns the keys of the n-grams FST for a word of the attribute.
+    ///
+    /// The word itself is not one of its n-grams as it is found through the words FST.
+    pub fn ngrams_fst_keys<'a>(&self, word: &'a str) -> impl Iterator<Item = Vec<u8>> + 'a {
+        let boundaries: Vec<_> = word.char_indices().map(|(i, _)| i).chain([word.len()]).collect();
+        let chars = boundaries.len() - 1;
+        let min_length = self.min_length.max(MIN_NGRAM_LENGTH) as usize;
+        let max_start = match self.mode {
+            _ if chars > MAX_NGRAM_WORD_LENGTH => 0,
+            NgramMode::Ngram => chars.saturating_sub(min_length) + 1,
+            NgramMode::EdgeNgram => 1,
+        };
+
+        (0..max_start).flat_map(move |start| {
+            let boundaries = boundaries.clone();
+            (start + min_length..=chars).filter(move |end| end - start < chars).map(move |end| {
+                let ngram = &word[boundaries[start]..boundaries[end]];
+                let mut key = Vec::with_capacity(ngram.len() + word.len() + 1);
+                key.extend_from_slice(ngram.as_bytes());
+                key.push(NGRAMS_FST_SEPARATOR);
+                key.extend_from_slice(word.as_bytes());
+                key
+            })
+        })
+    }
+}
+
+/// Returns the prefix shared by the keys of all the words containing this n-gram in the n-grams FST.
+pub fn ngrams_fst_prefix(ngram: &str) -> String {
+    let separator = NGRAMS_FST_SEPARATOR as char;
+    format!("{ngram}{separator}")
+}
+
+/// The rule of the most specific attribute the field belongs to, if any.
+pub fn ngram_attribute<'a>(
+    rules: &'a BTreeMap<String, NgramAttribute>,
+    field: &str,
+) -> Option<&'a NgramAttribute> {
+    rules
+        .iter()
+        .filter(|(attribute, _)| crate::is_faceted_by(field, attribute))
+        .max_by_key(|(attribute, _)| attribute.len())
+        .map(|(_, rule)| rule)
+}
+
+/// The rules of the fields belonging to an attribute of the `ngramAttributes` setting.
+pub fn ngram_fields_rules<'a>(
+    rules: &BTreeMap<String, NgramAttribute>,
+    fields: impl IntoIterator<Item = (FieldId, &'a str)>,
+) -> BTreeMap<FieldId, NgramAttribute> {
+    if rules.is_empty() {
+        return BTreeMap::new();
+    }
+    fields
+        .into_iter()
+        .filter_map(|(fid, name)| Some((fid, *ngram_attribute(rules, name)?)))
+        .collect()
+}
+
+impl Index {
+    /// The attributes whose words are split into n-grams, along with the way they are split.
+    pub fn ngram_attributes(
+        &self,
+        rtxn: &RoTxn<'_>,
+    ) -> heed::Result<BTreeMap<String, NgramAttribute>> {
+        Ok(self
+            .main
+            .remap_types::<Str, SerdeJson<_>>()
+            .get(rtxn, main_key::NGRAM_ATTRIBUTES)?
+            .unwrap_or_default())
+    }
+
+    pub(crate) fn put_ngram_attributes(
+        &self,
+        wtxn: &mut RwTxn<'_>,
+        rules: &BTreeMap<String, NgramAttribute>,
+    ) -> heed::Result<()> {
+        self.main.remap_types::<Str, SerdeJson<_>>().put(wtxn, main_key::NGRAM_ATTRIBUTES, rules)
+    }
+
+    pub(crate) fn delete_ngram_attributes(&self, wtxn: &mut RwTxn<'_>) -> heed::Result<bool> {
+        self.main.remap_key_type::<Str>().delete(wtxn, main_key::NGRAM_ATTRIBUTES)
+    }
+
+    pub(crate) fn put_ngrams_fst<A: AsRef<[u8]>>(
+        &self,
+        wtxn: &mut RwTxn<'_>,
+        fst: &fst::Set<A>,
+    ) -> heed::Result<()> {
+        self.main.remap_types::<Str, Bytes>().put(
+            wtxn,
+            main_key::NGRAMS_FST_KEY,
+            fst.as_fst().as_bytes(),
+        )
+    }
+
+    /// Returns the FST mapping the n-grams of the words of the n-gram attributes to these words.
+    ///
+    /// Its keys are made of the n-gram and the word, separated by a `\0`.
+    pub fn ngrams_fst<'t>(&self, rtxn: &'t RoTxn<'_>) -> Result<fst::Set<Cow<'t, [u8]>>> {
+        match self.main.remap_types::<Str, Bytes>().get(rtxn, main_key::NGRAMS_FST_KEY)? {
+            Some(bytes) => Ok(fst::Set::new(bytes)?.map_data(Cow::Borrowed)?),
+            None => Ok(fst::Set::default().map_data(Cow::Owned)?),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ngrams(mode: NgramMode, min_length: u8, word: &str) -> Vec<String> {
+        let rule = NgramAttribute { mode, min_length, max_matches: DEFAULT_NGRAM_MAX_MATCHES };
+        let prefix_len = |key: &[u8]| key.iter().position(|b| *b == NGRAMS_FST_SEPARATOR).unwrap();
+        rule.ngrams_fst_keys(word)
+            .map(|key| String::from_utf8(key[..prefix_len(&key)].to_vec()).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn split_words_into_ngrams() {
+        assert_eq!(
+            ngrams(NgramMode::Ngram, 3, "94521"),
+            ["945", "9452", "452", "4521", "521"].map(String::from)
+        );
+        assert_eq!(ngrams(NgramMode::EdgeNgram, 3, "94521"), ["945", "9452"].map(String::from));
+        assert_eq!(ngrams(NgramMode::Ngram, 2, "ça"), Vec::<String>::new());
+        assert_eq!(ngrams(NgramMode::Ngram, 2, "çaé"), ["ça", "aé"].map(String::from));
+        assert!(ngrams(NgramMode::Ngram, 3, &"a".repeat(MAX_NGRAM_WORD_LENGTH + 1)).is_empty());
+    }
+}
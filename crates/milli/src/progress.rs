@@ -45,6 +45,13 @@ impl Progress {
         steps.push((step_type, Box::new(sub_progress), now));
     }
 
+    /// Returns the name of the current step of type `P`, if the progress went through such a step.
+    pub fn current_step_of<P: Step>(&self) -> Option<Cow<'static, str>> {
+        let inner = self.steps.read().unwrap();
+        let step_type = TypeId::of::<P>();
+        inner.steps.iter().find(|(id, _, _)| *id == step_type).map(|(_, step, _)| step.name())
+    }
+
     // TODO: This code should be in meilisearch_types but cannot because milli can't depend on meilisearch_types
     pub fn as_progress_view(&self) -> ProgressView {
         let inner = self.steps.read().unwrap();
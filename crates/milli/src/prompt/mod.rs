@@ -18,23 +18,36 @@ use self::context::Context;
 use self::document::Document;
 use crate::fields_ids_map::metadata::FieldIdMapWithMetadata;
 use crate::update::del_add::DelAdd;
+use crate::vector::chunking::ChunkingSettings;
+use crate::vector::json_template::JsonTemplate;
+use crate::vector::EmbedderOptions;
 use crate::GlobalFieldsIdsMap;
 
 pub struct Prompt {
-    template: liquid::Template,
+    template: PromptTemplate,
     template_text: String,
     max_bytes: Option<NonZeroUsize>,
+    chunking: Option<ChunkingSettings>,
+}
+
+enum PromptTemplate {
+    /// Renders documents to a text.
+    Text(liquid::Template),
+    /// Renders documents to a JSON fragment, for embedders accepting multimodal inputs.
+    Fragment(JsonTemplate),
 }
 
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct PromptData {
     pub template: String,
     pub max_bytes: Option<NonZeroUsize>,
+    #[serde(default)]
+    pub chunking: Option<ChunkingSettings>,
 }
 
 impl From<Prompt> for PromptData {
     fn from(value: Prompt) -> Self {
-        Self { template: value.template_text, max_bytes: value.max_bytes }
+        Self { template: value.template_text, max_bytes: value.max_bytes, chunking: value.chunking }
     }
 }
 
@@ -42,18 +55,20 @@ impl TryFrom<PromptData> for Prompt {
     type Error = NewPromptError;
 
     fn try_from(value: PromptData) -> Result<Self, Self::Error> {
-        Prompt::new(value.template, value.max_bytes)
+        Ok(Prompt::new(value.template, value.max_bytes)?.with_chunking(value.chunking))
     }
 }
 
 impl Clone for Prompt {
     fn clone(&self) -> Self {
         let template_text = self.template_text.clone();
-        Self {
-            template: new_template(&template_text).unwrap(),
-            template_text,
-            max_bytes: self.max_bytes,
-        }
+        let template = match &self.template {
+            PromptTemplate::Text(_) => PromptTemplate::Text(new_template(&template_text).unwrap()),
+            PromptTemplate::Fragment(fragment) => {
+                PromptTemplate::Fragment(JsonTemplate::new(fragment.template().clone()).unwrap())
+            }
+        };
+        Self { template, template_text, max_bytes: self.max_bytes, chunking: self.chunking.clone() }
     }
 }
 
@@ -80,37 +95,113 @@ pub fn default_max_bytes() -> NonZeroUsize {
 impl Default for Prompt {
     fn default() -> Self {
         Self {
-            template: default_template(),
+            template: PromptTemplate::Text(default_template()),
             template_text: default_template_text().into(),
             max_bytes: Some(default_max_bytes()),
+            chunking: None,
         }
     }
 }
 
 impl Default for PromptData {
     fn default() -> Self {
-        Self { template: default_template_text().into(), max_bytes: Some(default_max_bytes()) }
+        Self {
+            template: default_template_text().into(),
+            max_bytes: Some(default_max_bytes()),
+            chunking: None,
+        }
     }
 }
 
 impl Prompt {
     pub fn new(template: String, max_bytes: Option<NonZeroUsize>) -> Result<Self, NewPromptError> {
-        let this = Self {
-            template: liquid::ParserBuilder::with_stdlib()
-                .build()
-                .unwrap()
-                .parse(&template)
-                .map_err(NewPromptError::cannot_parse_template)?,
+        let parsed = liquid::ParserBuilder::with_stdlib()
+            .build()
+            .unwrap()
+            .parse(&template)
+            .map_err(NewPromptError::cannot_parse_template)?;
+
+        // render template with special object that's OK with `doc.*` and `fields.*`
+        parsed
+            .render(&template_checker::TemplateChecker)
+            .map_err(NewPromptError::invalid_fields_in_template)?;
+
+        Ok(Self {
+            template: PromptTemplate::Text(parsed),
             template_text: template,
             max_bytes,
-        };
+            chunking: None,
+        })
+    }
 
-        // render template with special object that's OK with `doc.*` and `fields.*`
-        this.template
+    /// Splits the texts rendered by this prompt into chunks embedded separately.
+    ///
+    /// With chunking, `max_bytes` applies to each chunk rather than to the whole rendered text.
+    pub fn with_chunking(mut self, chunking: Option<ChunkingSettings>) -> Self {
+        self.chunking = chunking;
+        self
+    }
+
+    /// Creates a prompt that renders documents to a JSON fragment rather than to a text.
+    ///
+    /// Each string of the fragment is a liquid template with access to the same `doc.*` and `fields.*` as
+    /// a regular document template. Rendered fragments are never truncated.
+    pub fn from_fragment(fragment: serde_json::Value) -> Result<Self, NewPromptError> {
+        let template_text = fragment.to_string();
+        let fragment =
+            JsonTemplate::new(fragment).map_err(NewPromptError::cannot_parse_template)?;
+
+        fragment
             .render(&template_checker::TemplateChecker)
             .map_err(NewPromptError::invalid_fields_in_template)?;
 
-        Ok(this)
+        Ok(Self {
+            template: PromptTemplate::Fragment(fragment),
+            template_text,
+            max_bytes: None,
+            chunking: None,
+        })
+    }
+
+    /// Creates the prompt used to render documents for the embedder with the passed options.
+    ///
+    /// This is the document template, unless the embedder embeds JSON fragments.
+    pub fn for_embedder(
+        data: PromptData,
+        embedder_options: &EmbedderOptions,
+    ) -> Result<Self, NewPromptError> {
+        match embedder_options.indexing_fragment() {
+            Some(fragment) => Self::from_fragment(fragment.clone()),
+            None => data.try_into(),
+        }
+    }
+
+    /// Whether this prompt renders JSON fragments instead of texts.
+    pub fn is_fragment(&self) -> bool {
+        matches!(self.template, PromptTemplate::Fragment(_))
+    }
+
+    /// Whether the texts rendered by this prompt are split into several chunks.
+    pub fn is_chunked(&self) -> bool {
+        self.chunking.is_some() && !self.is_fragment()
+    }
+
+    /// Returns the chunks of a text rendered by this prompt, each of them to be embedded separately.
+    ///
+    /// Without chunking, the rendered text is the only chunk.
+    pub fn chunks<'t>(&self, rendered: &'t str) -> Vec<&'t str> {
+        match &self.chunking {
+            Some(chunking) if !self.is_fragment() => {
+                let mut chunks = chunking.chunk(rendered);
+                if let Some(max_bytes) = self.max_bytes {
+                    for chunk in chunks.iter_mut() {
+                        *chunk = truncated(chunk, max_bytes.get());
+                    }
+                }
+                chunks
+            }
+            _ => vec![rendered],
+        }
     }
 
     pub fn render_document<
@@ -126,11 +217,23 @@ impl Prompt {
         let document = ParseableDocument::new(document, doc_alloc);
         let fields = BorrowedFields::new(&document, field_id_map, doc_alloc);
         let context = Context::new(&document, &fields);
+        let template = match &self.template {
+            PromptTemplate::Text(template) => template,
+            PromptTemplate::Fragment(fragment) => {
+                let rendered = fragment.render(&context).map_err(|liquid_error| {
+                    RenderPromptError::missing_context_with_external_docid(
+                        external_docid.to_owned(),
+                        liquid_error,
+                    )
+                })?;
+                return Ok(doc_alloc.alloc_str(&rendered.to_string()));
+            }
+        };
         let mut rendered = bumpalo::collections::Vec::with_capacity_in(
             self.max_bytes.unwrap_or_else(default_max_bytes).get(),
             doc_alloc,
         );
-        self.template.render_to(&mut rendered, &context).map_err(|liquid_error| {
+        template.render_to(&mut rendered, &context).map_err(|liquid_error| {
             RenderPromptError::missing_context_with_external_docid(
                 external_docid.to_owned(),
                 liquid_error,
@@ -150,9 +253,16 @@ impl Prompt {
         let fields = OwnedFields::new(&document, field_id_map);
         let context = Context::new(&document, &fields);
 
-        let mut rendered =
-            self.template.render(&context).map_err(RenderPromptError::missing_context)?;
-        if let Some(max_bytes) = self.max_bytes {
+        let mut rendered = match &self.template {
+            PromptTemplate::Text(template) => {
+                template.render(&context).map_err(RenderPromptError::missing_context)?
+            }
+            PromptTemplate::Fragment(fragment) => {
+                fragment.render(&context).map_err(RenderPromptError::missing_context)?.to_string()
+            }
+        };
+        // chunked texts are truncated chunk by chunk
+        if let (Some(max_bytes), false) = (self.max_bytes, self.is_chunked()) {
             truncate(&mut rendered, max_bytes.get());
         }
         Ok(rendered)
@@ -160,23 +270,31 @@ impl Prompt {
 }
 
 fn truncate(s: &mut String, max_bytes: usize) {
+    let len = truncated(s, max_bytes).len();
+    s.truncate(len);
+}
+
+fn truncated(s: &str, max_bytes: usize) -> &str {
     if max_bytes >= s.len() {
-        return;
+        return s;
     }
     for i in (0..=max_bytes).rev() {
         if s.is_char_boundary(i) {
-            s.truncate(i);
-            break;
+            return &s[..i];
         }
     }
+    ""
 }
 
 #[cfg(test)]
 mod test {
+    use std::num::NonZeroUsize;
+
     use super::Prompt;
     use crate::error::FaultSource;
     use crate::prompt::error::{NewPromptError, NewPromptErrorKind};
     use crate::prompt::truncate;
+    use crate::vector::chunking::{ChunkingSettings, ChunkingStrategy};
 
     #[test]
     fn default_template() {
@@ -235,6 +353,27 @@ mod test {
         .unwrap();
     }
 
+    #[test]
+    fn fragment_ok() {
+        let prompt = Prompt::from_fragment(serde_json::json!({
+            "type": "image_url",
+            "image_url": "{{doc.image}}"
+        }))
+        .unwrap();
+        assert!(prompt.is_fragment());
+    }
+
+    #[test]
+    fn fragment_missing_doc() {
+        assert!(matches!(
+            Prompt::from_fragment(serde_json::json!({ "image_url": "{{image}}" })),
+            Err(NewPromptError {
+                kind: NewPromptErrorKind::InvalidFieldsInTemplate(_),
+                fault: FaultSource::User
+            })
+        ));
+    }
+
     #[test]
     fn template_fields_invalid() {
         assert!(matches!(
@@ -247,6 +386,22 @@ mod test {
         ));
     }
 
+    #[test]
+    fn chunks() {
+        let text = "kefir is a good doggo\n\nintel";
+        let prompt = Prompt::new("{{doc.text}}".into(), NonZeroUsize::new(8)).unwrap();
+        assert!(!prompt.is_chunked());
+        assert_eq!(prompt.chunks(text), vec![text]);
+
+        let prompt = prompt.with_chunking(Some(ChunkingSettings {
+            strategy: ChunkingStrategy::Paragraphs,
+            max_tokens: None,
+            overlap: None,
+        }));
+        assert!(prompt.is_chunked());
+        assert_eq!(prompt.chunks(text), vec!["kefir is", "intel"]);
+    }
+
     // todo: test truncation
     #[test]
     fn template_truncation() {
@@ -0,0 +1,345 @@
+use std::str::FromStr;
+
+use deserr::Deserr;
+use heed::types::{Bytes, SerdeJson, Str};
+use heed::{RoTxn, RwTxn};
+use regex_automata::dfa::{dense, sparse, Automaton as _};
+use regex_automata::Input;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use utoipa::ToSchema;
+
+use crate::index::{db_name, main_key};
+use crate::{AscDesc, Filter, Index, InternalError, Result, SerializationError};
+
+/// The maximum size in bytes of the automaton of a regular expression pattern.
+const PATTERN_REGEX_SIZE_LIMIT: usize = 2 * 1024 * 1024;
+
+/// A rule of the `queryRules` setting.
+///
+/// When the normalized query of a search matches the `pattern` of the rule, the search is altered
+/// by the consequences of the rule: its query can be rewritten, a filter and sort criteria can be
+/// added and the `userData` of the rule is attached to the response.
+#[derive(Serialize, Deserialize, PartialEq, Eq, Clone, Debug, Deserr, ToSchema)]
+#[serde(deny_unknown_fields, rename_all = "camelCase")]
+#[deserr(rename_all = camelCase, deny_unknown_fields)]
+pub struct QueryRule {
+    /// How the `pattern` is matched against the query.
+    #[serde(rename = "match")]
+    #[deserr(rename = "match")]
+    #[schema(value_type = QueryRuleMatch, example = json!("contains"))]
+    pub match_kind: QueryRuleMatch,
+    /// The words, or the regular expression, matched against the lowercased query.
+    #[schema(value_type = String, example = json!("black friday"))]
+    pub pattern: String,
+    /// The query searched instead of the query of the search.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[deserr(default)]
+    #[schema(value_type = Option<String>, example = json!("discount"))]
+    pub query: Option<String>,
+    /// A filter expression added to the filter of the search.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[deserr(default)]
+    #[schema(value_type = Option<String>, example = json!("on_sale = true"))]
+    pub filter: Option<String>,
+    /// Sort criteria applied before the sort criteria of the search.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[deserr(default)]
+    #[schema(value_type = Option<Vec<String>>, example = json!(["price:asc"]))]
+    pub sort: Option<Vec<String>>,
+    /// Any JSON value returned in the `userData` of the responses to the matching searches.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[deserr(default)]
+    #[schema(value_type = Option<Object>, example = json!({ "redirect": "https://example.com/returns" }))]
+    pub user_data: Option<Value>,
+}
+
+/// How the pattern of a query rule is matched against the query.
+#[derive(Serialize, Deserialize, PartialEq, Eq, Clone, Copy, Debug, Deserr, ToSchema)]
+#[serde(rename_all = "camelCase")]
+#[deserr(rename_all = camelCase)]
+pub enum QueryRuleMatch {
+    /// The query is made of the words of the pattern.
+    Exact,
+    /// The query contains the words of the pattern, in this order.
+    Contains,
+    /// The regular expression of the pattern matches the query.
+    Regex,
+}
+
+impl QueryRule {
+    /// Returns an error message if the rule can never match or alter a search.
+    pub fn validate(&self) -> Result<(), String> {
+        if self.pattern.trim().is_empty() {
+            return Err("has an empty `pattern`".to_string());
+        }
+        if self.match_kind == QueryRuleMatch::Regex {
+            if let Err(error) = compile_pattern_regex(&self.pattern) {
+                return Err(format!("has an invalid regular expression as `pattern`: {error}"));
+            }
+        }
+        if let Some(filter) = &self.filter {
+            if let Err(error) = Filter::from_str(filter) {
+                return Err(format!("has an invalid `filter`: {error}"));
+            }
+        }
+        for sort in self.sort.iter().flatten() {
+            if let Err(error) = AscDesc::from_str(sort) {
+                return Err(format!("has an invalid `sort`: {error}"));
+            }
+        }
+        if self.query.is_none()
+            && self.filter.is_none()
+            && self.sort.is_none()
+            && self.user_data.is_none()
+        {
+            return Err(
+                "has no consequence, it must define at least one of `query`, `filter`, `sort` or `userData`"
+                    .to_string(),
+            );
+        }
+        Ok(())
+    }
+}
+
+/// Compiles the regular expression of a pattern into an automaton finding it anywhere in the query.
+fn compile_pattern_regex(pattern: &str) -> Result<sparse::DFA<Vec<u8>>, String> {
+    // the word boundaries are only supported in ASCII queries, the others never match them
+    let config = dense::Config::new()
+        .unicode_word_boundary(true)
+        .dfa_size_limit(Some(PATTERN_REGEX_SIZE_LIMIT))
+        .determinize_size_limit(Some(PATTERN_REGEX_SIZE_LIMIT));
+    let dfa = dense::Builder::new()
+        .configure(config)
+        .build(pattern)
+        .map_err(|error| error.to_string())?;
+    dfa.to_sparse().map_err(|error| error.to_string())
+}
+
+/// A query rule along with the automaton of its regular expression, that is compiled
+/// once when the rules are stored.
+pub struct QueryRuleMatcher<T> {
+    pub rule: QueryRule,
+    regex: Option<PatternRegex<T>>,
+}
+
+/// The automaton of a regular expression, read from the stored bytes or compiled again when
+/// these bytes cannot be read.
+enum PatternRegex<T> {
+    Stored(sparse::DFA<T>),
+    Compiled(sparse::DFA<Vec<u8>>),
+}
+
+impl<T: AsRef<[u8]>> PatternRegex<T> {
+    fn is_match(&self, input: &Input) -> bool {
+        let found = match self {
+            PatternRegex::Stored(dfa) => dfa.try_search_fwd(input),
+            PatternRegex::Compiled(dfa) => dfa.try_search_fwd(input),
+        };
+        found.is_ok_and(|found| found.is_some())
+    }
+}
+
+impl<T: AsRef<[u8]>> QueryRuleMatcher<T> {
+    /// Whether the rule applies to a query normalized with [`normalize_query`].
+    pub fn matches(&self, normalized_query: &str) -> bool {
+        match self.rule.match_kind {
+            QueryRuleMatch::Exact => normalize_query(&self.rule.pattern) == normalized_query,
+            QueryRuleMatch::Contains => {
+                let pattern = normalize_query(&self.rule.pattern);
+                // the pattern must match whole words of the query
+                format!(" {normalized_query} ").contains(&format!(" {pattern} "))
+            }
+            QueryRuleMatch::Regex => self
+                .regex
+                .as_ref()
+                .is_some_and(|regex| regex.is_match(&Input::new(normalized_query).earliest(true))),
+        }
+    }
+}
+
+impl QueryRuleMatcher<Vec<u8>> {
+    /// Compiles the regular expression of the rule, a rule whose pattern is invalid never matches.
+    pub fn compile(rule: QueryRule) -> Self {
+        let regex = match rule.match_kind {
+            QueryRuleMatch::Regex => {
+                compile_pattern_regex(&rule.pattern).ok().map(PatternRegex::Stored)
+            }
+            QueryRuleMatch::Exact | QueryRuleMatch::Contains => None,
+        };
+        QueryRuleMatcher { rule, regex }
+    }
+}
+
+/// Lowercases the query and collapses its whitespaces, as the query rules expect it.
+pub fn normalize_query(query: &str) -> String {
+    query.split_whitespace().map(str::to_lowercase).collect::<Vec<_>>().join(" ")
+}
+
+impl Index {
+    /// The rules of the `queryRules` setting, in the order they are evaluated.
+    pub fn query_rules(&self, rtxn: &RoTxn<'_>) -> heed::Result<Vec<QueryRule>> {
+        Ok(self
+            .main
+            .remap_types::<Str, SerdeJson<Vec<QueryRule>>>()
+            .get(rtxn, main_key::QUERY_RULES)?
+            .unwrap_or_default())
+    }
+
+    /// The rules of the `queryRules` setting along with the automatons of their regular expressions.
+    ///
+    /// An automaton that cannot be read, e.g. because it was stored by a machine of another
+    /// endianness, is compiled again from the pattern of its rule.
+    pub fn query_rule_matchers<'t>(
+        &self,
+        rtxn: &'t RoTxn<'_>,
+    ) -> Result<Vec<QueryRuleMatcher<&'t [u8]>>> {
+        let rules = self.query_rules(rtxn)?;
+        let mut regexes = self
+            .main
+            .remap_types::<Str, Bytes>()
+            .get(rtxn, main_key::QUERY_RULES_REGEXES)?
+            .unwrap_or_default();
+
+        let decoding_error = || {
+            InternalError::Serialization(SerializationError::Decoding {
+                db_name: Some(db_name::MAIN),
+            })
+        };
+
+        let mut matchers = Vec::with_capacity(rules.len());
+        for rule in rules {
+            // the automatons are stored one after the other, each prefixed by its length
+            let mut regex = None;
+            if let Some((length, rest)) = regexes.split_first_chunk::<4>() {
+                let length = u32::from_be_bytes(*length) as usize;
+                let bytes = rest.get(..length).ok_or_else(decoding_error)?;
+                if !bytes.is_empty() {
+                    regex = match sparse::DFA::from_bytes(bytes) {
+                        Ok((dfa, _)) => Some(PatternRegex::Stored(dfa)),
+                        Err(error) => {
+                            tracing::warn!(
+                                pattern = %rule.pattern,
+                                %error,
+                                "the stored automaton of a query rule cannot be read, compiling it again"
+                            );
+                            compile_pattern_regex(&rule.pattern).ok().map(PatternRegex::Compiled)
+                        }
+                    };
+                }
+                regexes = &rest[length..];
+            }
+            matchers.push(QueryRuleMatcher { rule, regex });
+        }
+        Ok(matchers)
+    }
+
+    pub(crate) fn put_query_rules(
+        &self,
+        wtxn: &mut RwTxn<'_>,
+        rules: &[QueryRule],
+    ) -> heed::Result<()> {
+        self.main.remap_types::<Str, SerdeJson<&[QueryRule]>>().put(
+            wtxn,
+            main_key::QUERY_RULES,
+            &rules,
+        )?;
+
+        let mut regexes = Vec::new();
+        for rule in rules {
+            let bytes = match rule.match_kind {
+                QueryRuleMatch::Regex => compile_pattern_regex(&rule.pattern)
+                    .map(|regex| regex.to_bytes_native_endian())
+                    .unwrap_or_default(),
+                QueryRuleMatch::Exact | QueryRuleMatch::Contains => Vec::new(),
+            };
+            regexes.extend_from_slice(&(bytes.len() as u32).to_be_bytes());
+            regexes.extend_from_slice(&bytes);
+        }
+        self.main.remap_types::<Str, Bytes>().put(wtxn, main_key::QUERY_RULES_REGEXES, &regexes)
+    }
+
+    pub(crate) fn delete_query_rules(&self, wtxn: &mut RwTxn<'_>) -> heed::Result<bool> {
+        self.main.remap_key_type::<Str>().delete(wtxn, main_key::QUERY_RULES_REGEXES)?;
+        self.main.remap_key_type::<Str>().delete(wtxn, main_key::QUERY_RULES)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::index::tests::TempIndex;
+
+    fn rule(match_kind: QueryRuleMatch, pattern: &str) -> QueryRule {
+        QueryRule {
+            match_kind,
+            pattern: pattern.to_string(),
+            query: None,
+            filter: Some("on_sale = true".to_string()),
+            sort: None,
+            user_data: None,
+        }
+    }
+
+    #[test]
+    fn match_normalized_queries() {
+        let query = normalize_query("  Black   FRIDAY deals ");
+        assert_eq!(query, "black friday deals");
+
+        let matches = |match_kind, pattern| {
+            QueryRuleMatcher::compile(rule(match_kind, pattern)).matches(&query)
+        };
+        assert!(!matches(QueryRuleMatch::Exact, "black friday"));
+        assert!(matches(QueryRuleMatch::Exact, "Black Friday deals"));
+        assert!(matches(QueryRuleMatch::Contains, "Black Friday"));
+        assert!(matches(QueryRuleMatch::Contains, "deals"));
+        assert!(!matches(QueryRuleMatch::Contains, "black fri"));
+        assert!(matches(QueryRuleMatch::Regex, "^black (friday|monday)"));
+        assert!(matches(QueryRuleMatch::Regex, "fri(day)?"));
+        assert!(!matches(QueryRuleMatch::Regex, "^friday"));
+    }
+
+    #[test]
+    fn compile_unreadable_stored_regexes_again() {
+        let index = TempIndex::new();
+        index
+            .update_settings(|s| {
+                s.set_query_rules(vec![rule(QueryRuleMatch::Regex, "^black (friday|monday)")])
+            })
+            .unwrap();
+
+        let mut wtxn = index.write_txn().unwrap();
+        let mut regexes = 4u32.to_be_bytes().to_vec();
+        regexes.extend_from_slice(b"oops");
+        index
+            .main
+            .remap_types::<Str, Bytes>()
+            .put(&mut wtxn, main_key::QUERY_RULES_REGEXES, &regexes)
+            .unwrap();
+        wtxn.commit().unwrap();
+
+        let rtxn = index.read_txn().unwrap();
+        let matchers = index.query_rule_matchers(&rtxn).unwrap();
+        assert!(matchers[0].matches("black friday deals"));
+        assert!(!matchers[0].matches("friday"));
+    }
+
+    #[test]
+    fn validate_rules() {
+        assert!(rule(QueryRuleMatch::Contains, "black friday").validate().is_ok());
+        assert!(rule(QueryRuleMatch::Contains, " ").validate().is_err());
+        assert!(rule(QueryRuleMatch::Regex, "black (friday").validate().is_err());
+
+        let mut invalid_filter = rule(QueryRuleMatch::Exact, "black friday");
+        invalid_filter.filter = Some("on_sale =".to_string());
+        assert!(invalid_filter.validate().is_err());
+
+        let mut invalid_sort = rule(QueryRuleMatch::Exact, "black friday");
+        invalid_sort.sort = Some(vec!["price:up".to_string()]);
+        assert!(invalid_sort.validate().is_err());
+
+        let mut no_consequence = rule(QueryRuleMatch::Exact, "black friday");
+        no_consequence.filter = None;
+        assert!(no_consequence.validate().is_err());
+    }
+}
@@ -9,6 +9,7 @@ use crate::distance_between_two_points;
 pub enum ScoreDetails {
     Words(Words),
     Typo(Typo),
+    SynonymWeight(Rank),
     Proximity(Rank),
     Fid(Rank),
     Position(Rank),
@@ -17,6 +18,7 @@ pub enum ScoreDetails {
     Sort(Sort),
     Vector(Vector),
     GeoSort(GeoSort),
+    Rerank(Rerank),
 
     /// Returned when we don't have the time to finish applying all the subsequent ranking-rules
     Skipped,
@@ -54,6 +56,7 @@ impl ScoreDetails {
         match self {
             ScoreDetails::Words(details) => Some(details.rank()),
             ScoreDetails::Typo(details) => Some(details.rank()),
+            ScoreDetails::SynonymWeight(details) => Some(*details),
             ScoreDetails::Proximity(details) => Some(*details),
             ScoreDetails::Fid(details) => Some(*details),
             ScoreDetails::Position(details) => Some(*details),
@@ -62,6 +65,7 @@ impl ScoreDetails {
             ScoreDetails::Sort(_) => None,
             ScoreDetails::GeoSort(_) => None,
             ScoreDetails::Vector(_) => None,
+            ScoreDetails::Rerank(_) => None,
             ScoreDetails::Skipped => Some(Rank { rank: 0, max_rank: 1 }),
         }
     }
@@ -124,6 +128,7 @@ impl ScoreDetails {
         match self {
             ScoreDetails::Words(w) => RankOrValue::Rank(w.rank()),
             ScoreDetails::Typo(t) => RankOrValue::Rank(t.rank()),
+            ScoreDetails::SynonymWeight(s) => RankOrValue::Rank(*s),
             ScoreDetails::Proximity(p) => RankOrValue::Rank(*p),
             ScoreDetails::Fid(f) => RankOrValue::Rank(*f),
             ScoreDetails::Position(p) => RankOrValue::Rank(*p),
@@ -134,6 +139,7 @@ impl ScoreDetails {
             ScoreDetails::Vector(vector) => {
                 RankOrValue::Score(vector.similarity.as_ref().map(|s| *s as f64).unwrap_or(0.0f64))
             }
+            ScoreDetails::Rerank(rerank) => RankOrValue::Score(rerank.score as f64),
             ScoreDetails::Skipped => RankOrValue::Rank(Rank { rank: 0, max_rank: 1 }),
         }
     }
@@ -170,6 +176,14 @@ impl ScoreDetails {
                     details_map.insert("typo".into(), typo_details);
                     order += 1;
                 }
+                ScoreDetails::SynonymWeight(synonym_weight) => {
+                    let synonym_weight_details = serde_json::json!({
+                        "order": order,
+                        "score": synonym_weight.local_score(),
+                    });
+                    details_map.insert("synonymWeight".into(), synonym_weight_details);
+                    order += 1;
+                }
                 ScoreDetails::Proximity(proximity) => {
                     let proximity_details = serde_json::json!({
                         "order": order,
@@ -284,13 +298,24 @@ impl ScoreDetails {
                 ScoreDetails::Vector(s) => {
                     let similarity = s.similarity.as_ref();
 
-                    let details = serde_json::json!({
+                    let mut details = serde_json::json!({
                         "order": order,
                         "similarity": similarity,
                     });
+                    if let Some(chunk) = s.chunk {
+                        details["chunk"] = chunk.into();
+                    }
                     details_map.insert("vectorSort".into(), details);
                     order += 1;
                 }
+                ScoreDetails::Rerank(rerank) => {
+                    let details = serde_json::json!({
+                        "order": order,
+                        "score": rerank.score,
+                    });
+                    details_map.insert("rerank".into(), details);
+                    order += 1;
+                }
                 ScoreDetails::Skipped => {
                     details_map
                         .insert("skipped".to_string(), serde_json::json!({ "order": order }));
@@ -543,6 +568,14 @@ impl PartialOrd for GeoSort {
 #[derive(Debug, Clone, PartialEq, PartialOrd)]
 pub struct Vector {
     pub similarity: Option<f32>,
+    /// Index of the chunk of the document that is the most similar, for embedders that chunk documents
+    pub chunk: Option<usize>,
+}
+
+/// The score given by a reranker, always placed before the other details of a reranked document.
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd)]
+pub struct Rerank {
+    pub score: f32,
 }
 
 impl GeoSort {
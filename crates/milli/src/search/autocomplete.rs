@@ -0,0 +1,291 @@
+use std::cmp::Reverse;
+
+use charabia::{TokenKind, TokenizerBuilder};
+use fst::automaton::{Automaton, Str};
+use fst::{IntoStreamer, Streamer};
+
+use crate::search::new::{SearchContext, Word};
+use crate::{filtered_universe, Filter, Index, Result};
+
+/// The maximum number of tokens of the query that are taken into account.
+const MAX_QUERY_TOKENS: usize = 10;
+
+/// The maximum number of words starting with the last word of the query that are looked up.
+const MAX_LOOKED_UP_WORDS: usize = 1_000;
+
+/// A completion of a query along with the number of documents containing it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Completion {
+    pub value: String,
+    pub count: u64,
+}
+
+/// Completes the last word of a query with the words of the index starting with it, the most
+/// frequent ones first.
+///
+/// The words preceding the last one restrict the documents taken into account and the
+/// completion must directly follow the previous word, which makes it possible to complete
+/// phrases. It doesn't rank any document and is therefore much cheaper than a search.
+pub struct Autocomplete<'a> {
+    query: String,
+    // this should be linked to the String in the query
+    filter: Option<Filter<'a>>,
+    searchable_attributes: Option<&'a [String]>,
+    limit: usize,
+    rtxn: &'a heed::RoTxn<'a>,
+    index: &'a Index,
+}
+
+impl<'a> Autocomplete<'a> {
+    pub fn new(query: String, limit: usize, index: &'a Index, rtxn: &'a heed::RoTxn<'a>) -> Self {
+        Self { query, filter: None, searchable_attributes: None, limit, rtxn, index }
+    }
+
+    pub fn filter(&mut self, filter: Filter<'a>) -> &mut Self {
+        self.filter = Some(filter);
+        self
+    }
+
+    pub fn searchable_attributes(&mut self, searchable: &'a [String]) -> &mut Self {
+        self.searchable_attributes = Some(searchable);
+        self
+    }
+
+    pub fn execute(&self) -> Result<Vec<Completion>> {
+        let mut ctx = SearchContext::new(self.index, self.rtxn)?;
+        if let Some(searchable_attributes) = self.searchable_attributes {
+            ctx.attributes_to_search_on(searchable_attributes)?;
+        }
+
+        let tokens = self.tokenize()?;
+        let Some(((prefix, _), preceding)) = tokens.split_last() else {
+            return Ok(Vec::new());
+        };
+
+        if self.limit == 0 {
+            return Ok(Vec::new());
+        }
+
+        // The documents must contain every word preceding the prefix.
+        let mut universe = filtered_universe(self.index, self.rtxn, &self.filter)?;
+        for (word, kind) in preceding {
+            if matches!(kind, TokenKind::StopWord) {
+                continue;
+            }
+            let word = Word::Original(ctx.word_interner.insert(word.clone()));
+            universe = ctx.word_docids(Some(&universe), word)?.unwrap_or_default();
+            if universe.is_empty() {
+                return Ok(Vec::new());
+            }
+        }
+
+        // When the prefix has been indexed we can quickly make sure that at least one
+        // document of the universe contains a word starting with it.
+        if self.index.words_prefixes_fst(self.rtxn)?.contains(prefix) {
+            let interned = ctx.word_interner.insert(prefix.clone());
+            let docids = ctx.word_prefix_docids(Some(&universe), Word::Original(interned))?;
+            if docids.is_none_or(|docids| docids.is_empty()) {
+                return Ok(Vec::new());
+            }
+        }
+
+        let previous_word = match preceding.last() {
+            Some((word, TokenKind::Word)) => Some(ctx.word_interner.insert(word.clone())),
+            _ => None,
+        };
+
+        // Only the first `limit` words starting with the prefix and found in the universe are
+        // completions, as each of them costs a database lookup.
+        let words_fst = ctx.get_words_fst()?;
+        let mut stream = words_fst.search(Str::new(prefix).starts_with()).into_stream();
+        let mut completions = Vec::new();
+        let mut looked_up_words = 0;
+        while let Some(word) = stream.next() {
+            if completions.len() >= self.limit || looked_up_words >= MAX_LOOKED_UP_WORDS {
+                break;
+            }
+            let Ok(word) = std::str::from_utf8(word) else { continue };
+            looked_up_words += 1;
+            let interned = ctx.word_interner.insert(word.to_string());
+            let docids = match previous_word {
+                Some(previous_word) => {
+                    let pair_docids = ctx.get_db_word_pair_proximity_docids(
+                        Some(&universe),
+                        previous_word,
+                        interned,
+                        1,
+                    )?;
+                    // unlike the word docids, the pairs are not restricted to the searched fields
+                    match pair_docids {
+                        Some(pair_docids) if ctx.restricted_fids.is_some() => ctx
+                            .word_docids(Some(&pair_docids), Word::Original(interned))?
+                            .map(|docids| docids & pair_docids),
+                        pair_docids => pair_docids,
+                    }
+                }
+                None => ctx.word_docids(Some(&universe), Word::Original(interned))?,
+            };
+
+            let count = docids.map_or(0, |docids| docids.intersection_len(&universe));
+            if count > 0 {
+                completions.push(Reverse((count, Reverse(word.to_string()))));
+            }
+        }
+
+        // the most frequent completions first, then in alphabetical order
+        completions.sort_unstable_by(|left, right| right.cmp(left));
+
+        let mut beginning = String::new();
+        for (word, _) in preceding {
+            beginning.push_str(word);
+            beginning.push(' ');
+        }
+
+        Ok(completions
+            .into_iter()
+            .map(|Reverse((count, Reverse(word)))| Completion {
+                value: format!("{beginning}{word}"),
+                count,
+            })
+            .collect())
+    }
+
+    /// Returns the normalized words and stop words of the query.
+    fn tokenize(&self) -> Result<Vec<(String, TokenKind)>> {
+        let mut tokbuilder = TokenizerBuilder::new();
+        let stop_words = self.index.stop_words(self.rtxn)?;
+        if let Some(ref stop_words) = stop_words {
+            tokbuilder.stop_words(stop_words);
+        }
+
+        let separators = self.index.allowed_separators(self.rtxn)?;
+        let separators: Option<Vec<_>> =
+            separators.as_ref().map(|x| x.iter().map(String::as_str).collect());
+        if let Some(ref separators) = separators {
+            tokbuilder.separators(separators);
+        }
+
+        let dictionary = self.index.dictionary(self.rtxn)?;
+        let dictionary: Option<Vec<_>> =
+            dictionary.as_ref().map(|x| x.iter().map(String::as_str).collect());
+        if let Some(ref dictionary) = dictionary {
+            tokbuilder.words_dict(dictionary);
+        }
+
+        let tokenizer = tokbuilder.build();
+        let mut tokens: Vec<_> = tokenizer
+            .tokenize(&self.query)
+            .filter(|token| matches!(token.kind, TokenKind::Word | TokenKind::StopWord))
+            .filter(|token| !token.lemma().is_empty())
+            .map(|token| (token.lemma().to_string(), token.kind))
+            .collect();
+
+        // Only the end of a long query is relevant to complete its last word.
+        if tokens.len() > MAX_QUERY_TOKENS {
+            tokens.drain(..tokens.len() - MAX_QUERY_TOKENS);
+        }
+
+        Ok(tokens)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use big_s::S;
+
+    use super::*;
+    use crate::index::tests::TempIndex;
+
+    #[test]
+    fn complete_words_and_phrases() {
+        let index = TempIndex::new();
+        index
+            .add_documents(documents!([
+                { "id": 0, "title": "the quick brown fox", "genre": "animal" },
+                { "id": 1, "title": "quick brown dogs", "genre": "animal" },
+                { "id": 2, "title": "brown bread and quiche", "genre": "food" },
+                { "id": 3, "title": "a quiet quiche", "genre": "food" },
+            ]))
+            .unwrap();
+
+        let rtxn = index.read_txn().unwrap();
+        let completions = Autocomplete::new(S("qui"), 10, &index, &rtxn).execute().unwrap();
+        let values: Vec<_> = completions.iter().map(|c| (c.value.as_str(), c.count)).collect();
+        assert_eq!(values, [("quiche", 2), ("quick", 2), ("quiet", 1)]);
+
+        let completions = Autocomplete::new(S("qui"), 1, &index, &rtxn).execute().unwrap();
+        assert_eq!(completions.len(), 1);
+
+        // the completion must directly follow the previous word
+        let completions = Autocomplete::new(S("quick br"), 10, &index, &rtxn).execute().unwrap();
+        let values: Vec<_> = completions.iter().map(|c| (c.value.as_str(), c.count)).collect();
+        assert_eq!(values, [("quick brown", 2)]);
+
+        let completions = Autocomplete::new(S("brown b"), 10, &index, &rtxn).execute().unwrap();
+        let values: Vec<_> = completions.iter().map(|c| (c.value.as_str(), c.count)).collect();
+        assert_eq!(values, [("brown bread", 1)]);
+
+        let completions = Autocomplete::new(S("zzz"), 10, &index, &rtxn).execute().unwrap();
+        assert!(completions.is_empty());
+    }
+
+    #[test]
+    fn stop_after_limit_completions() {
+        let index = TempIndex::new();
+        // the words after the first `limit` completions are not looked up, even the most frequent one
+        let letter = |n: u32| char::from(b'a' + (n % 26) as u8);
+        let mut documents: Vec<_> = (0..2_000)
+            .map(|id| {
+                let title = format!("word{}{}{}", letter(id / 676), letter(id / 26), letter(id));
+                serde_json::json!({ "id": id, "title": title })
+            })
+            .collect();
+        documents.push(serde_json::json!({ "id": 2_000, "title": "wordy" }));
+        documents.push(serde_json::json!({ "id": 2_001, "title": "wordy" }));
+        index.add_documents(documents!(documents)).unwrap();
+
+        let rtxn = index.read_txn().unwrap();
+        let completions = Autocomplete::new(S("wor"), 2, &index, &rtxn).execute().unwrap();
+        let values: Vec<_> = completions.iter().map(|c| (c.value.as_str(), c.count)).collect();
+        assert_eq!(values, [("wordaaa", 1), ("wordaab", 1)]);
+    }
+
+    #[test]
+    fn complete_with_filter_and_searchable_attributes() {
+        let index = TempIndex::new();
+        index
+            .update_settings(|s| {
+                s.set_filterable_fields(vec![crate::FilterableAttributesRule::Field(S("genre"))]);
+            })
+            .unwrap();
+        index
+            .add_documents(documents!([
+                { "id": 0, "title": "quick fox", "genre": "animal", "description": "quiet" },
+                { "id": 1, "title": "quick quiche", "genre": "food", "description": "quick fish" },
+            ]))
+            .unwrap();
+
+        let rtxn = index.read_txn().unwrap();
+        let filter = Filter::from_str("genre = food").unwrap().unwrap();
+        let completions =
+            Autocomplete::new(S("qui"), 10, &index, &rtxn).filter(filter).execute().unwrap();
+        let values: Vec<_> = completions.iter().map(|c| (c.value.as_str(), c.count)).collect();
+        assert_eq!(values, [("quiche", 1), ("quick", 1)]);
+
+        let attributes = [S("title")];
+        let completions = Autocomplete::new(S("qui"), 10, &index, &rtxn)
+            .searchable_attributes(&attributes)
+            .execute()
+            .unwrap();
+        let values: Vec<_> = completions.iter().map(|c| (c.value.as_str(), c.count)).collect();
+        assert_eq!(values, [("quick", 2), ("quiche", 1)]);
+
+        // the phrases are only completed in the searchable attributes too
+        let completions = Autocomplete::new(S("quick f"), 10, &index, &rtxn)
+            .searchable_attributes(&attributes)
+            .execute()
+            .unwrap();
+        let values: Vec<_> = completions.iter().map(|c| (c.value.as_str(), c.count)).collect();
+        assert_eq!(values, [("quick fox", 1)]);
+    }
+}
@@ -185,3 +185,182 @@ impl<A: Automaton> Automaton for Complement<A> {
         ComplementState(self.0.accept(&state.0, byte))
     }
 }
+
+/// The maximum number of bytes and wildcards of a pattern accepted by `Wildcard`.
+pub const MAX_WILDCARD_PATTERN_LEN: usize = 63;
+
+#[derive(Clone, Copy, Debug)]
+enum WildcardToken {
+    /// A byte of a literal character.
+    Byte(u8),
+    /// The first byte of any character, matched by `?`.
+    AnyFirstByte,
+    /// The remaining bytes of the character matched by `?`.
+    AnyContinuationBytes,
+    /// Any sequence of characters, matched by `*`.
+    AnySequence,
+}
+
+/// An automaton that matches the words matching a pattern, in which `*` stands for any sequence of
+/// characters and `?` for exactly one character.
+#[derive(Clone, Debug)]
+pub struct Wildcard {
+    tokens: Vec<WildcardToken>,
+}
+
+impl Wildcard {
+    /// Returns `None` if the pattern is longer than `MAX_WILDCARD_PATTERN_LEN`.
+    pub fn new(pattern: &str) -> Option<Self> {
+        let mut tokens = Vec::new();
+        for c in pattern.chars() {
+            match c {
+                '*' if matches!(tokens.last(), Some(WildcardToken::AnySequence)) => (),
+                '*' => tokens.push(WildcardToken::AnySequence),
+                '?' => tokens
+                    .extend([WildcardToken::AnyFirstByte, WildcardToken::AnyContinuationBytes]),
+                c => tokens.extend(c.encode_utf8(&mut [0; 4]).bytes().map(WildcardToken::Byte)),
+            }
+        }
+        (tokens.len() <= MAX_WILDCARD_PATTERN_LEN).then_some(Self { tokens })
+    }
+
+    /// Adds to the state the positions that can be skipped without consuming any byte.
+    fn skip_optional_tokens(&self, mut state: u64) -> u64 {
+        for (position, token) in self.tokens.iter().enumerate() {
+            let is_optional =
+                matches!(token, WildcardToken::AnySequence | WildcardToken::AnyContinuationBytes);
+            if is_optional && state & (1 << position) != 0 {
+                state |= 1 << (position + 1);
+            }
+        }
+        state
+    }
+}
+
+/// The `Automaton` state of a `Wildcard` is the set of the positions reached in the pattern.
+impl Automaton for Wildcard {
+    type State = u64;
+
+    fn start(&self) -> u64 {
+        self.skip_optional_tokens(1)
+    }
+    fn is_match(&self, state: &u64) -> bool {
+        state & (1 << self.tokens.len()) != 0
+    }
+    fn can_match(&self, state: &u64) -> bool {
+        *state != 0
+    }
+    fn accept(&self, state: &u64, byte: u8) -> u64 {
+        let is_continuation_byte = byte & 0b1100_0000 == 0b1000_0000;
+        let mut next = 0;
+        for (position, token) in self.tokens.iter().enumerate() {
+            if state & (1 << position) == 0 {
+                continue;
+            }
+            next |= match *token {
+                WildcardToken::Byte(expected) if expected == byte => 1 << (position + 1),
+                WildcardToken::AnyFirstByte if !is_continuation_byte => 1 << (position + 1),
+                WildcardToken::AnyContinuationBytes if is_continuation_byte => 1 << position,
+                WildcardToken::AnySequence => 1 << position,
+                _ => 0,
+            };
+        }
+        self.skip_optional_tokens(next)
+    }
+}
+
+/// The maximum size in bytes of the automaton built by `Regex`.
+const REGEX_SIZE_LIMIT: usize = 2 * 1024 * 1024;
+
+/// An automaton that matches the words entirely matched by a regular expression.
+#[derive(Clone, Debug)]
+pub struct Regex {
+    dfa: regex_automata::dfa::dense::DFA<Vec<u32>>,
+    start: regex_automata::util::primitives::StateID,
+}
+
+impl Regex {
+    /// Returns a description of the error if the regular expression is invalid, or if its
+    /// automaton would be too large.
+    pub fn new(pattern: &str) -> Result<Self, String> {
+        use regex_automata::dfa::{dense, Automaton as _, StartKind};
+        use regex_automata::{Anchored, Input};
+
+        let config = dense::Config::new()
+            .start_kind(StartKind::Anchored)
+            .dfa_size_limit(Some(REGEX_SIZE_LIMIT))
+            .determinize_size_limit(Some(REGEX_SIZE_LIMIT));
+        let dfa = dense::Builder::new()
+            .configure(config)
+            .build(&format!("(?:{pattern})$"))
+            .map_err(|error| error.to_string())?;
+        let start = dfa
+            .start_state_forward(&Input::new("").anchored(Anchored::Yes))
+            .map_err(|error| error.to_string())?;
+        Ok(Self { dfa, start })
+    }
+}
+
+impl Automaton for Regex {
+    type State = regex_automata::util::primitives::StateID;
+
+    fn start(&self) -> Self::State {
+        self.start
+    }
+    fn is_match(&self, state: &Self::State) -> bool {
+        use regex_automata::dfa::Automaton as _;
+        self.dfa.is_match_state(self.dfa.next_eoi_state(*state))
+    }
+    fn can_match(&self, state: &Self::State) -> bool {
+        use regex_automata::dfa::Automaton as _;
+        !self.dfa.is_dead_state(*state)
+    }
+    fn accept(&self, state: &Self::State, byte: u8) -> Self::State {
+        use regex_automata::dfa::Automaton as _;
+        self.dfa.next_state(*state, byte)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use fst::{IntoStreamer, Set, Streamer};
+
+    use super::*;
+
+    fn search(automaton: impl Automaton) -> Vec<String> {
+        let words = ["cat", "catalog", "chat", "coat", "dog", "hat", "hôtel", "hotel"];
+        let mut words: Vec<_> = words.to_vec();
+        words.sort_unstable();
+        let set = Set::from_iter(words).unwrap();
+        let mut stream = set.search(automaton).into_stream();
+        let mut matches = Vec::new();
+        while let Some(word) = stream.next() {
+            matches.push(String::from_utf8(word.to_vec()).unwrap());
+        }
+        matches
+    }
+
+    #[test]
+    fn wildcard() {
+        let wildcard = |pattern| search(Wildcard::new(pattern).unwrap());
+
+        assert_eq!(wildcard("cat*"), ["cat", "catalog"]);
+        assert_eq!(wildcard("*at"), ["cat", "chat", "coat", "hat"]);
+        assert_eq!(wildcard("c?at"), ["chat", "coat"]);
+        assert_eq!(wildcard("h?tel"), ["hotel", "hôtel"]);
+        assert_eq!(wildcard("*a*o*"), ["catalog"]);
+        assert_eq!(wildcard("dog"), ["dog"]);
+        assert!(Wildcard::new(&"a".repeat(MAX_WILDCARD_PATTERN_LEN + 1)).is_none());
+    }
+
+    #[test]
+    fn regex() {
+        let regex = |pattern| search(Regex::new(pattern).unwrap());
+
+        assert_eq!(regex("c.*t"), ["cat", "chat", "coat"]);
+        assert_eq!(regex("(d|h)o.*"), ["dog", "hotel"]);
+        assert_eq!(regex("h.tel"), ["hotel", "hôtel"]);
+        assert_eq!(regex("ca"), Vec::<String>::new());
+        assert!(Regex::new("ca(").is_err());
+    }
+}
@@ -1,11 +1,15 @@
+use std::cell::RefCell;
 use std::cmp::Ordering;
+use std::sync::RwLock;
 
+use bumpalo::Bump;
 use itertools::Itertools;
 use roaring::RoaringBitmap;
 
-use crate::score_details::{ScoreDetails, ScoreValue, ScoringStrategy};
-use crate::search::SemanticSearch;
-use crate::{MatchingWords, Result, Search, SearchResult};
+use crate::score_details::{self, ScoreDetails, ScoreValue, ScoringStrategy};
+use crate::search::{RerankSearch, SemanticSearch};
+use crate::update::new::document::DocumentFromDb;
+use crate::{GlobalFieldsIdsMap, MatchingWords, Result, Search, SearchResult};
 
 struct ScoreWithRatioResult {
     matching_words: MatchingWords,
@@ -13,6 +17,7 @@ struct ScoreWithRatioResult {
     document_scores: Vec<(u32, ScoreWithRatio)>,
     degraded: bool,
     used_negative_operator: bool,
+    suggestions: Vec<String>,
 }
 
 type ScoreWithRatio = (Vec<ScoreDetails>, f32);
@@ -82,22 +87,24 @@ impl ScoreWithRatioResult {
             document_scores,
             degraded: results.degraded,
             used_negative_operator: results.used_negative_operator,
+            suggestions: results.suggestions,
         }
     }
 
+    /// Merges the results, also returning the ids of the documents that come from the semantic results.
     #[tracing::instrument(level = "trace", skip_all, target = "search::hybrid")]
     fn merge(
         vector_results: Self,
         keyword_results: Self,
         from: usize,
         length: usize,
-    ) -> (SearchResult, u32) {
+    ) -> (SearchResult, RoaringBitmap) {
         #[derive(Clone, Copy)]
         enum ResultSource {
             Semantic,
             Keyword,
         }
-        let mut semantic_hit_count = 0;
+        let mut semantic_hits = RoaringBitmap::new();
 
         let mut documents_ids = Vec::with_capacity(
             vector_results.document_scores.len() + keyword_results.document_scores.len(),
@@ -129,7 +136,7 @@ impl ScoreWithRatioResult {
             .take(length)
         {
             if let ResultSource::Semantic = source {
-                semantic_hit_count += 1;
+                semantic_hits.insert(docid);
             }
             documents_ids.push(docid);
             // TODO: pass both scores to documents_score in some way?
@@ -145,8 +152,10 @@ impl ScoreWithRatioResult {
                 degraded: vector_results.degraded | keyword_results.degraded,
                 used_negative_operator: vector_results.used_negative_operator
                     | keyword_results.used_negative_operator,
+                // only the keyword search computes suggestions
+                suggestions: keyword_results.suggestions,
             },
-            semantic_hit_count,
+            semantic_hits,
         )
     }
 }
@@ -165,6 +174,7 @@ impl Search<'_> {
             distinct: self.distinct.clone(),
             searchable_attributes: self.searchable_attributes,
             geo_param: self.geo_param,
+            query_syntax: self.query_syntax,
             terms_matching_strategy: self.terms_matching_strategy,
             scoring_strategy: ScoringStrategy::Detailed,
             words_limit: self.words_limit,
@@ -172,25 +182,31 @@ impl Search<'_> {
             rtxn: self.rtxn,
             index: self.index,
             semantic: self.semantic.clone(),
+            rerank: None,
             time_budget: self.time_budget.clone(),
             ranking_score_threshold: self.ranking_score_threshold,
             locales: self.locales.clone(),
+            vector_search: self.vector_search,
+            suggestions: self.suggestions,
         };
 
         let semantic = search.semantic.take();
         let keyword_results = search.execute()?;
 
+        // no vector search against placeholder search
+        let Some(query) = search.query.take() else {
+            return Ok(return_keyword_results(self.limit, self.offset, keyword_results));
+        };
+
         // completely skip semantic search if the results of the keyword search are good enough
         if self.results_good_enough(&keyword_results, semantic_ratio) {
+            let keyword_results = self.rerank_results(&query, keyword_results)?;
             return Ok(return_keyword_results(self.limit, self.offset, keyword_results));
         }
 
-        // no vector search against placeholder search
-        let Some(query) = search.query.take() else {
-            return Ok(return_keyword_results(self.limit, self.offset, keyword_results));
-        };
         // no embedder, no semantic search
         let Some(SemanticSearch { vector, embedder_name, embedder, quantized }) = semantic else {
+            let keyword_results = self.rerank_results(&query, keyword_results)?;
             return Ok(return_keyword_results(self.limit, self.offset, keyword_results));
         };
 
@@ -207,6 +223,7 @@ impl Search<'_> {
                     Ok(embedding) => embedding,
                     Err(error) => {
                         tracing::error!(error=%error, "Embedding failed");
+                        let keyword_results = self.rerank_results(&query, keyword_results)?;
                         return Ok(return_keyword_results(
                             self.limit,
                             self.offset,
@@ -226,12 +243,110 @@ impl Search<'_> {
         let keyword_results = ScoreWithRatioResult::new(keyword_results, 1.0 - semantic_ratio);
         let vector_results = ScoreWithRatioResult::new(vector_results, semantic_ratio);
 
-        let (merge_results, semantic_hit_count) =
-            ScoreWithRatioResult::merge(vector_results, keyword_results, self.offset, self.limit);
+        let (merge_results, semantic_hits) = if self.rerank.is_some() {
+            // the reranker can move any of the first candidates to the current page
+            let (merge_results, semantic_hits) = ScoreWithRatioResult::merge(
+                vector_results,
+                keyword_results,
+                0,
+                self.offset + self.limit,
+            );
+            let merge_results = self.rerank_results(&query, merge_results)?;
+            (paginate(self.limit, self.offset, merge_results), semantic_hits)
+        } else {
+            ScoreWithRatioResult::merge(vector_results, keyword_results, self.offset, self.limit)
+        };
         assert!(merge_results.documents_ids.len() <= self.limit);
+        let semantic_hit_count = merge_results
+            .documents_ids
+            .iter()
+            .filter(|docid| semantic_hits.contains(**docid))
+            .count() as u32;
         Ok((merge_results, Some(semantic_hit_count)))
     }
 
+    /// Reorders the first candidates of the results by their reranker score, if a reranker was set.
+    ///
+    /// The reranked candidates get their reranker score as first score details. Candidates after
+    /// the `maxCandidates` of the reranker keep their position.
+    ///
+    /// If the reranker fails, the results are returned unchanged.
+    fn rerank_results(&self, query: &str, mut results: SearchResult) -> Result<SearchResult> {
+        let Some(RerankSearch { reranker, prompt }) = &self.rerank else {
+            return Ok(results);
+        };
+        let candidate_count = reranker.max_candidates().min(results.documents_ids.len());
+        if candidate_count == 0 {
+            return Ok(results);
+        }
+
+        let span = tracing::trace_span!(target: "search::hybrid", "rerank", candidate_count);
+        let _entered = span.enter();
+
+        let candidates = results.documents_ids[..candidate_count].to_vec();
+        let fields_ids_map = self.index.fields_ids_map(self.rtxn)?;
+        let fields_ids_map_with_metadata =
+            RwLock::new(self.index.fields_ids_map_with_metadata(self.rtxn)?);
+        let global_fields_ids_map =
+            RefCell::new(GlobalFieldsIdsMap::new(&fields_ids_map_with_metadata));
+        let external_ids: Vec<String> = self
+            .index
+            .external_id_of(self.rtxn, candidates.iter().copied())?
+            .into_iter()
+            .collect::<Result<_>>()?;
+
+        let doc_alloc = Bump::new();
+        let mut texts = Vec::with_capacity(candidate_count);
+        for (&docid, external_id) in candidates.iter().zip(&external_ids) {
+            let Some(document) =
+                DocumentFromDb::new(docid, self.rtxn, self.index, &fields_ids_map)?
+            else {
+                // deleted documents are never part of the results
+                return Err(
+                    crate::UserError::UnknownInternalDocumentId { document_id: docid }.into()
+                );
+            };
+            match prompt.render_document(external_id, document, &global_fields_ids_map, &doc_alloc)
+            {
+                Ok(text) => texts.push(text),
+                Err(error) => {
+                    tracing::error!(error=%error, "Rendering a document for reranking failed");
+                    return Ok(results);
+                }
+            }
+        }
+
+        let deadline = std::time::Instant::now() + reranker.timeout();
+        let scores = match reranker.rerank(query, &texts, Some(deadline)) {
+            Ok(scores) => scores,
+            Err(error) => {
+                tracing::error!(error=%error, "Reranking failed");
+                return Ok(results);
+            }
+        };
+
+        let mut reranked: Vec<_> = results
+            .documents_ids
+            .drain(..candidate_count)
+            .zip(results.document_scores.drain(..candidate_count))
+            .zip(scores)
+            .map(|((docid, mut details), score)| {
+                details.insert(0, ScoreDetails::Rerank(score_details::Rerank { score }));
+                (docid, details, score)
+            })
+            .collect();
+        // stable sort, so that candidates with equal scores keep their hybrid order
+        reranked.sort_by(|(_, _, left), (_, _, right)| right.total_cmp(left));
+
+        let (mut documents_ids, mut document_scores): (Vec<_>, Vec<_>) =
+            reranked.into_iter().map(|(docid, details, _)| (docid, details)).unzip();
+        documents_ids.append(&mut results.documents_ids);
+        document_scores.append(&mut results.document_scores);
+        results.documents_ids = documents_ids;
+        results.document_scores = document_scores;
+        Ok(results)
+    }
+
     fn results_good_enough(&self, keyword_results: &SearchResult, semantic_ratio: f32) -> bool {
         // A result is good enough if its keyword score is > 0.9 with a semantic ratio of 0.5 => 0.9 * 0.5
         const GOOD_ENOUGH_SCORE: f64 = 0.45;
@@ -254,6 +369,14 @@ impl Search<'_> {
 }
 
 fn return_keyword_results(
+    limit: usize,
+    offset: usize,
+    results: SearchResult,
+) -> (SearchResult, Option<u32>) {
+    (paginate(limit, offset, results), Some(0))
+}
+
+fn paginate(
     limit: usize,
     offset: usize,
     SearchResult {
@@ -263,8 +386,9 @@ fn return_keyword_results(
         mut document_scores,
         degraded,
         used_negative_operator,
+        suggestions,
     }: SearchResult,
-) -> (SearchResult, Option<u32>) {
+) -> SearchResult {
     let (documents_ids, document_scores) = if offset >= documents_ids.len() ||
     // technically redudant because documents_ids.len() == document_scores.len(),
     // defensive programming
@@ -281,15 +405,13 @@ fn return_keyword_results(
         document_scores.truncate(limit);
         (documents_ids, document_scores)
     };
-    (
-        SearchResult {
-            matching_words,
-            candidates,
-            documents_ids,
-            document_scores,
-            degraded,
-            used_negative_operator,
-        },
-        Some(0),
-    )
+    SearchResult {
+        matching_words,
+        candidates,
+        documents_ids,
+        document_scores,
+        degraded,
+        used_negative_operator,
+        suggestions,
+    }
 }
@@ -8,9 +8,14 @@ use roaring::bitmap::RoaringBitmap;
 
 pub use self::facet::{FacetDistribution, Filter, OrderBy, DEFAULT_VALUES_PER_FACET};
 pub use self::new::matches::{FormatOptions, MatchBounds, MatcherBuilder, MatchingWords};
-use self::new::{execute_vector_search, PartialSearchResult, VectorStoreStats};
+use self::new::{
+    execute_vector_search, suggest_queries, PartialSearchResult, VectorStoreStats,
+    MAX_CANDIDATES_FOR_SUGGESTIONS,
+};
 use crate::filterable_attributes_rules::{filtered_matching_patterns, matching_features};
+use crate::prompt::Prompt;
 use crate::score_details::{ScoreDetails, ScoringStrategy};
+use crate::vector::rerank::Reranker;
 use crate::vector::Embedder;
 use crate::{
     execute_search, filtered_universe, AscDesc, DefaultSearchLogger, DocumentId, Error, Index,
@@ -22,6 +27,7 @@ static LEVDIST0: Lazy<LevBuilder> = Lazy::new(|| LevBuilder::new(0, true));
 static LEVDIST1: Lazy<LevBuilder> = Lazy::new(|| LevBuilder::new(1, true));
 static LEVDIST2: Lazy<LevBuilder> = Lazy::new(|| LevBuilder::new(2, true));
 
+pub mod autocomplete;
 pub mod facet;
 mod fst_utils;
 pub mod hybrid;
@@ -36,6 +42,13 @@ pub struct SemanticSearch {
     quantized: bool,
 }
 
+#[derive(Clone)]
+pub struct RerankSearch {
+    reranker: Arc<Reranker>,
+    /// Template used to render the candidates before sending them to the reranker
+    prompt: Arc<Prompt>,
+}
+
 pub struct Search<'a> {
     query: Option<String>,
     // this should be linked to the String in the query
@@ -46,6 +59,7 @@ pub struct Search<'a> {
     distinct: Option<String>,
     searchable_attributes: Option<&'a [String]>,
     geo_param: new::GeoSortParameter,
+    query_syntax: QuerySyntax,
     terms_matching_strategy: TermsMatchingStrategy,
     scoring_strategy: ScoringStrategy,
     words_limit: usize,
@@ -53,9 +67,12 @@ pub struct Search<'a> {
     rtxn: &'a heed::RoTxn<'a>,
     index: &'a Index,
     semantic: Option<SemanticSearch>,
+    rerank: Option<RerankSearch>,
     time_budget: TimeBudget,
     ranking_score_threshold: Option<f64>,
     locales: Option<Vec<Language>>,
+    vector_search: new::VectorSearchParams,
+    suggestions: usize,
 }
 
 impl<'a> Search<'a> {
@@ -69,6 +86,7 @@ impl<'a> Search<'a> {
             distinct: None,
             searchable_attributes: None,
             geo_param: new::GeoSortParameter::default(),
+            query_syntax: QuerySyntax::default(),
             terms_matching_strategy: TermsMatchingStrategy::default(),
             scoring_strategy: Default::default(),
             exhaustive_number_hits: false,
@@ -76,9 +94,12 @@ impl<'a> Search<'a> {
             rtxn,
             index,
             semantic: None,
+            rerank: None,
             locales: None,
             time_budget: TimeBudget::max(),
             ranking_score_threshold: None,
+            vector_search: Default::default(),
+            suggestions: 0,
         }
     }
 
@@ -98,6 +119,14 @@ impl<'a> Search<'a> {
         self
     }
 
+    /// Reorders the best candidates of a hybrid search with the passed reranker.
+    ///
+    /// Has no effect outside of hybrid searches.
+    pub fn rerank(&mut self, reranker: Arc<Reranker>, prompt: Arc<Prompt>) -> &mut Search<'a> {
+        self.rerank = Some(RerankSearch { reranker, prompt });
+        self
+    }
+
     pub fn offset(&mut self, offset: usize) -> &mut Search<'a> {
         self.offset = offset;
         self
@@ -123,6 +152,11 @@ impl<'a> Search<'a> {
         self
     }
 
+    pub fn query_syntax(&mut self, value: QuerySyntax) -> &mut Search<'a> {
+        self.query_syntax = value;
+        self
+    }
+
     pub fn terms_matching_strategy(&mut self, value: TermsMatchingStrategy) -> &mut Search<'a> {
         self.terms_matching_strategy = value;
         self
@@ -177,6 +211,18 @@ impl<'a> Search<'a> {
         self
     }
 
+    /// Tunes the vector search performed by semantic and hybrid searches.
+    pub fn vector_search(&mut self, params: new::VectorSearchParams) -> &mut Search<'a> {
+        self.vector_search = params;
+        self
+    }
+
+    /// Computes up to `limit` spelling suggestions when the query matches few documents.
+    pub fn suggestions(&mut self, limit: usize) -> &mut Search<'a> {
+        self.suggestions = limit;
+        self
+    }
+
     pub fn execute_for_candidates(&self, has_vector_search: bool) -> Result<RoaringBitmap> {
         if has_vector_search {
             let ctx = SearchContext::new(self.index, self.rtxn)?;
@@ -222,6 +268,7 @@ impl<'a> Search<'a> {
         }
 
         let universe = filtered_universe(ctx.index, ctx.txn, &self.filter)?;
+        let suggestions_universe = (self.suggestions > 0).then(|| universe.clone());
         let PartialSearchResult {
             located_query_terms,
             candidates,
@@ -244,6 +291,7 @@ impl<'a> Search<'a> {
                     embedder_name,
                     embedder,
                     *quantized,
+                    self.vector_search,
                     self.time_budget.clone(),
                     self.ranking_score_threshold,
                 )?
@@ -251,6 +299,7 @@ impl<'a> Search<'a> {
             _ => execute_search(
                 &mut ctx,
                 self.query.as_deref(),
+                self.query_syntax,
                 self.terms_matching_strategy,
                 self.scoring_strategy,
                 self.exhaustive_number_hits,
@@ -275,6 +324,15 @@ impl<'a> Search<'a> {
             tracing::debug!("Vector store stats: total_time={total_time:.02?}, total_queries={total_queries}, total_results={total_results}");
         }
 
+        let suggestions = match (&located_query_terms, suggestions_universe) {
+            (Some(located_query_terms), Some(universe))
+                if candidates.len() <= MAX_CANDIDATES_FOR_SUGGESTIONS =>
+            {
+                suggest_queries(&mut ctx, located_query_terms, &universe, self.suggestions)?
+            }
+            _ => Vec::new(),
+        };
+
         // consume context and located_query_terms to build MatchingWords.
         let matching_words = match located_query_terms {
             Some(located_query_terms) => MatchingWords::new(ctx, located_query_terms),
@@ -288,6 +346,7 @@ impl<'a> Search<'a> {
             documents_ids,
             degraded,
             used_negative_operator,
+            suggestions,
         })
     }
 }
@@ -303,6 +362,7 @@ impl fmt::Debug for Search<'_> {
             distinct,
             searchable_attributes,
             geo_param: _,
+            query_syntax,
             terms_matching_strategy,
             scoring_strategy,
             words_limit,
@@ -310,9 +370,12 @@ impl fmt::Debug for Search<'_> {
             rtxn: _,
             index: _,
             semantic,
+            rerank,
             time_budget,
             ranking_score_threshold,
             locales,
+            vector_search,
+            suggestions,
         } = self;
         f.debug_struct("Search")
             .field("query", query)
@@ -323,6 +386,7 @@ impl fmt::Debug for Search<'_> {
             .field("sort_criteria", sort_criteria)
             .field("distinct", distinct)
             .field("searchable_attributes", searchable_attributes)
+            .field("query_syntax", query_syntax)
             .field("terms_matching_strategy", terms_matching_strategy)
             .field("scoring_strategy", scoring_strategy)
             .field("exhaustive_number_hits", exhaustive_number_hits)
@@ -331,9 +395,12 @@ impl fmt::Debug for Search<'_> {
                 "semantic.embedder_name",
                 &semantic.as_ref().map(|semantic| &semantic.embedder_name),
             )
+            .field("rerank", &rerank.as_ref().map(|rerank| &rerank.reranker))
             .field("time_budget", time_budget)
             .field("ranking_score_threshold", ranking_score_threshold)
             .field("locales", locales)
+            .field("vector_search", vector_search)
+            .field("suggestions", suggestions)
             .finish()
     }
 }
@@ -346,6 +413,8 @@ pub struct SearchResult {
     pub document_scores: Vec<Vec<ScoreDetails>>,
     pub degraded: bool,
     pub used_negative_operator: bool,
+    /// Alternative queries, only computed when requested and when the query matches few documents
+    pub suggestions: Vec<String>,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -364,6 +433,15 @@ impl Default for TermsMatchingStrategy {
     }
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum QuerySyntax {
+    // words, phrases and negative operators
+    #[default]
+    Simple,
+    // also required terms, groups of alternatives, field-scoped terms, wildcard patterns and regular expressions
+    Advanced,
+}
+
 fn get_first(s: &str) -> &str {
     match s.chars().next() {
         Some(c) => &s[..c.len_utf8()],
@@ -12,6 +12,7 @@ use super::interner::Interned;
 use super::Word;
 use crate::heed_codec::{BytesDecodeOwned, StrBEU16Codec};
 use crate::proximity::ProximityPrecision;
+use crate::synonym_rules::WeightedSynonyms;
 use crate::update::MergeCboRoaringBitmaps;
 use crate::{
     CboRoaringBitmapCodec, CboRoaringBitmapLenCodec, Result, SearchContext, U8StrStrCodec,
@@ -32,6 +33,7 @@ pub struct DatabaseCache<'ctx> {
         FxHashMap<(u8, Interned<String>, Interned<String>), Option<Cow<'ctx, [u8]>>>,
     pub word_docids: FxHashMap<Interned<String>, Option<Cow<'ctx, [u8]>>>,
     pub exact_word_docids: FxHashMap<Interned<String>, Option<Cow<'ctx, [u8]>>>,
+    pub ngram_word_docids: FxHashMap<Interned<String>, Option<Cow<'ctx, [u8]>>>,
     pub word_prefix_docids: FxHashMap<Interned<String>, Option<Cow<'ctx, [u8]>>>,
     pub exact_word_prefix_docids: FxHashMap<Interned<String>, Option<Cow<'ctx, [u8]>>>,
 
@@ -45,6 +47,8 @@ pub struct DatabaseCache<'ctx> {
     pub word_prefix_fid_docids: FxHashMap<(Interned<String>, u16), Option<Cow<'ctx, [u8]>>>,
     pub word_fids: FxHashMap<Interned<String>, Vec<u16>>,
     pub word_prefix_fids: FxHashMap<Interned<String>, Vec<u16>>,
+
+    pub weighted_synonyms: Option<WeightedSynonyms>,
 }
 impl<'ctx> DatabaseCache<'ctx> {
     fn get_value<'v, K1, KC>(
@@ -180,6 +184,24 @@ impl<'ctx> SearchContext<'ctx> {
         }
     }
 
+    fn get_weighted_synonyms(&mut self) -> Result<&WeightedSynonyms> {
+        if self.db_cache.weighted_synonyms.is_none() {
+            self.db_cache.weighted_synonyms = Some(self.index.weighted_synonyms(self.txn)?);
+        }
+        Ok(self.db_cache.weighted_synonyms.get_or_insert_with(Default::default))
+    }
+
+    /// Returns the synonyms of the sequence of words expanded from the synonym rules, with their
+    /// weight.
+    pub fn weighted_synonyms_of(&mut self, words: &[String]) -> Result<Vec<(Vec<String>, f32)>> {
+        Ok(self.get_weighted_synonyms()?.get(words).cloned().unwrap_or_default())
+    }
+
+    /// Returns `true` if a synonym rule has a weight lower than `1.0`.
+    pub fn has_down_weighted_synonyms(&mut self) -> Result<bool> {
+        Ok(self.get_weighted_synonyms()?.values().flatten().any(|(_, weight)| *weight < 1.0))
+    }
+
     pub fn word_docids(
         &mut self,
         universe: Option<&RoaringBitmap>,
@@ -201,6 +223,7 @@ impl<'ctx> SearchContext<'ctx> {
                 })
             }
             Word::Derived(word) => self.get_db_word_docids(universe, word),
+            Word::Ngram(word) => self.get_db_ngram_word_docids(universe, word),
         }
     }
 
@@ -237,6 +260,26 @@ impl<'ctx> SearchContext<'ctx> {
         }
     }
 
+    /// Retrieve or insert the documents containing the given word in the n-gram attributes.
+    fn get_db_ngram_word_docids(
+        &mut self,
+        universe: Option<&RoaringBitmap>,
+        word: Interned<String>,
+    ) -> Result<Option<RoaringBitmap>> {
+        let interned = self.word_interner.get(word).as_str();
+        let keys: Vec<_> = self.ngram_fids.keys().map(|fid| (interned, *fid)).collect();
+
+        DatabaseCache::get_value_from_keys(
+            self.txn,
+            word,
+            &keys[..],
+            &mut self.db_cache.ngram_word_docids,
+            self.index.word_fid_docids.remap_data_type::<Bytes>(),
+            universe,
+            MergeCboRoaringBitmaps,
+        )
+    }
+
     fn get_db_exact_word_docids(
         &mut self,
         universe: Option<&RoaringBitmap>,
@@ -289,7 +332,9 @@ impl<'ctx> SearchContext<'ctx> {
                     }
                 })
             }
-            Word::Derived(prefix) => self.get_db_word_prefix_docids(universe, prefix),
+            Word::Derived(prefix) | Word::Ngram(prefix) => {
+                self.get_db_word_prefix_docids(universe, prefix)
+            }
         }
     }
 
@@ -0,0 +1,166 @@
+use crate::attributes_typo_tolerance::{attribute_typo_tolerance, AttributeTypoTolerance};
+use crate::index::PrefixSearch;
+use crate::{FieldId, Index, Result};
+
+/// The derivations of a query word that a field tolerates.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WordTolerance {
+    pub max_typos: u8,
+    pub prefix_search: bool,
+}
+
+/// The typo tolerance and prefix search rules of a searchable field.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FieldTolerance {
+    pub typos: bool,
+    pub min_word_len_one_typo: u8,
+    pub min_word_len_two_typos: u8,
+    pub prefix_search: bool,
+}
+
+impl FieldTolerance {
+    /// The derivations of the given query word tolerated by the field.
+    pub fn word_tolerance(&self, word: &str) -> WordTolerance {
+        let max_typos = if !self.typos || word.len() < self.min_word_len_one_typo as usize {
+            0
+        } else if word.len() < self.min_word_len_two_typos as usize {
+            1
+        } else {
+            2
+        };
+
+        WordTolerance { max_typos, prefix_search: self.prefix_search }
+    }
+
+    fn overridden_by(self, rules: &AttributeTypoTolerance) -> Self {
+        let AttributeTypoTolerance {
+            enabled,
+            min_word_len_one_typo,
+            min_word_len_two_typos,
+            prefix_search,
+        } = *rules;
+
+        Self {
+            typos: enabled.unwrap_or(self.typos),
+            min_word_len_one_typo: min_word_len_one_typo.unwrap_or(self.min_word_len_one_typo),
+            min_word_len_two_typos: min_word_len_two_typos.unwrap_or(self.min_word_len_two_typos),
+            // There are no prefix databases to rely on when prefix search is disabled index-wide
+            prefix_search: self.prefix_search && prefix_search != Some(PrefixSearch::Disabled),
+        }
+    }
+}
+
+/// The typo tolerance and prefix search rules of the searched fields.
+///
+/// The rules can be overridden by attribute, in which case the derivations of a query term that
+/// are not tolerated by all the searched fields must only be searched in the ones tolerating them.
+#[derive(Debug, Clone)]
+pub struct FieldsTolerance {
+    /// The rules shared by all the searched fields when they don't differ.
+    shared: FieldTolerance,
+    /// The rules of each searched field, empty when they don't differ.
+    fields: Vec<(FieldId, FieldTolerance)>,
+}
+
+impl FieldsTolerance {
+    pub fn new<'a>(
+        index: &Index,
+        rtxn: &heed::RoTxn<'_>,
+        searchable_fields: impl IntoIterator<Item = (&'a str, FieldId)>,
+    ) -> Result<Self> {
+        let global = FieldTolerance {
+            typos: index.authorize_typos(rtxn)?,
+            min_word_len_one_typo: index.min_word_len_one_typo(rtxn)?,
+            min_word_len_two_typos: index.min_word_len_two_typos(rtxn)?,
+            prefix_search: index.prefix_search(rtxn)?.unwrap_or_default() != PrefixSearch::Disabled,
+        };
+
+        let rules = index.attributes_typo_tolerance(rtxn)?;
+        let mut this = Self { shared: global, fields: Vec::new() };
+        if !rules.is_empty() {
+            // The typos are already disabled on the exact attributes by the way they are indexed
+            let exact_attributes_ids = index.exact_attributes_ids(rtxn)?;
+            this.fields = searchable_fields
+                .into_iter()
+                .map(|(name, fid)| {
+                    let mut field = global;
+                    field.typos &= !exact_attributes_ids.contains(&fid);
+                    match attribute_typo_tolerance(&rules, name) {
+                        Some(rules) => (fid, field.overridden_by(rules)),
+                        None => (fid, field),
+                    }
+                })
+                .collect();
+            this.share_if_identical();
+        }
+
+        Ok(this)
+    }
+
+    /// Only keep the rules of the given searched fields.
+    pub fn restrict_to(&mut self, fids: &[FieldId]) {
+        self.fields.retain(|(fid, _)| fids.contains(fid));
+        self.share_if_identical();
+    }
+
+    fn share_if_identical(&mut self) {
+        if let Some((_, first)) = self.fields.first() {
+            if self.fields.iter().all(|(_, rules)| rules == first) {
+                self.shared = *first;
+                self.fields.clear();
+            }
+        }
+    }
+
+    /// Whether all the searched fields share the same rules.
+    pub fn is_uniform(&self) -> bool {
+        self.fields.is_empty()
+    }
+
+    /// The fields having their own rules, empty when the rules are uniform.
+    pub fn fields(&self) -> &[(FieldId, FieldTolerance)] {
+        &self.fields
+    }
+
+    /// The rules of the given field.
+    pub fn field(&self, fid: FieldId) -> FieldTolerance {
+        self.fields.iter().find(|(id, _)| *id == fid).map_or(self.shared, |(_, rules)| *rules)
+    }
+
+    /// Whether at least one of the searched fields tolerates prefixes.
+    pub fn prefix_search_allowed(&self) -> bool {
+        if self.is_uniform() {
+            self.shared.prefix_search
+        } else {
+            self.fields.iter().any(|(_, rules)| rules.prefix_search)
+        }
+    }
+
+    /// The derivations of the word tolerated by at least one of the searched fields.
+    pub fn most_tolerant(&self, word: &str) -> WordTolerance {
+        self.fold(word, |a, b| WordTolerance {
+            max_typos: a.max_typos.max(b.max_typos),
+            prefix_search: a.prefix_search || b.prefix_search,
+        })
+    }
+
+    /// The derivations of the word tolerated by all the searched fields.
+    pub fn least_tolerant(&self, word: &str) -> WordTolerance {
+        self.fold(word, |a, b| WordTolerance {
+            max_typos: a.max_typos.min(b.max_typos),
+            prefix_search: a.prefix_search && b.prefix_search,
+        })
+    }
+
+    fn fold(
+        &self,
+        word: &str,
+        f: impl Fn(WordTolerance, WordTolerance) -> WordTolerance,
+    ) -> WordTolerance {
+        self.fields
+            .iter()
+            .map(|(_, rules)| rules.word_tolerance(word))
+            .reduce(f)
+            .unwrap_or_else(|| self.shared.word_tolerance(word))
+    }
+}
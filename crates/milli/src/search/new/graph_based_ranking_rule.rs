@@ -46,7 +46,7 @@ use super::logger::SearchLogger;
 use super::query_graph::QueryNode;
 use super::ranking_rule_graph::{
     ConditionDocIdsCache, DeadEndsCache, ExactnessGraph, FidGraph, PositionGraph, ProximityGraph,
-    RankingRuleGraph, RankingRuleGraphTrait, TypoGraph, WordsGraph,
+    RankingRuleGraph, RankingRuleGraphTrait, SynonymWeightGraph, TypoGraph, WordsGraph,
 };
 use super::small_bitmap::SmallBitmap;
 use super::{QueryGraph, RankingRule, RankingRuleOutput, SearchContext};
@@ -85,6 +85,12 @@ impl GraphBasedRankingRule<TypoGraph> {
         Self::new_with_id("typo".to_owned(), terms_matching_strategy)
     }
 }
+pub type SynonymWeight = GraphBasedRankingRule<SynonymWeightGraph>;
+impl GraphBasedRankingRule<SynonymWeightGraph> {
+    pub fn new(terms_matching_strategy: Option<TermsMatchingStrategy>) -> Self {
+        Self::new_with_id("synonymWeight".to_owned(), terms_matching_strategy)
+    }
+}
 pub type Exactness = GraphBasedRankingRule<ExactnessGraph>;
 impl GraphBasedRankingRule<ExactnessGraph> {
     pub fn new() -> Self {
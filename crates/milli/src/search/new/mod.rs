@@ -1,6 +1,7 @@
 mod bucket_sort;
 mod db_cache;
 mod distinct;
+mod fields_tolerance;
 mod geo_sort;
 mod graph_based_ranking_rule;
 mod interner;
@@ -13,6 +14,7 @@ mod ranking_rule_graph;
 mod ranking_rules;
 mod resolve_query_graph;
 mod small_bitmap;
+mod suggestions;
 
 mod exact_attribute;
 mod sort;
@@ -21,7 +23,7 @@ mod vector_sort;
 #[cfg(test)]
 mod tests;
 
-use std::collections::HashSet;
+use std::collections::{BTreeMap, HashSet};
 use std::ops::AddAssign;
 use std::time::Duration;
 
@@ -29,21 +31,27 @@ use bucket_sort::{bucket_sort, BucketSortOutput};
 use charabia::{Language, TokenizerBuilder};
 use db_cache::DatabaseCache;
 use exact_attribute::ExactAttribute;
-use graph_based_ranking_rule::{Exactness, Fid, Position, Proximity, Typo};
+use fields_tolerance::FieldsTolerance;
+use graph_based_ranking_rule::{Exactness, Fid, Position, Proximity, SynonymWeight, Typo};
 use heed::RoTxn;
 use interner::{DedupInterner, Interner};
 pub use logger::visual::VisualSearchLogger;
 pub use logger::{DefaultSearchLogger, SearchLogger};
 use query_graph::{QueryGraph, QueryNode};
 use query_term::{
-    located_query_terms_from_tokens, ExtractedTokens, LocatedQueryTerm, Phrase, QueryTerm,
+    located_query_terms_from_advanced_query, located_query_terms_from_tokens,
+    ExtractedAdvancedTokens, ExtractedTokens, LocatedQueryTerm, Phrase, QueryTerm, QueryTermGroup,
+    QueryTermSubset,
 };
 use ranking_rules::{
     BoxRankingRule, PlaceholderQuery, RankingRule, RankingRuleOutput, RankingRuleQueryTrait,
 };
-use resolve_query_graph::{compute_query_graph_docids, PhraseDocIdsCache};
+use resolve_query_graph::{
+    compute_query_graph_docids, compute_query_term_subset_docids_within_field_id, PhraseDocIdsCache,
+};
 use roaring::RoaringBitmap;
 use sort::Sort;
+pub(crate) use suggestions::{suggest_queries, MAX_CANDIDATES_FOR_SUGGESTIONS};
 
 use self::distinct::facet_string_values;
 use self::geo_sort::GeoSort;
@@ -52,16 +60,20 @@ pub use self::geo_sort::Strategy as GeoSortStrategy;
 use self::graph_based_ranking_rule::Words;
 use self::interner::Interned;
 use self::vector_sort::VectorSort;
+pub use self::vector_sort::{VectorSearchParams, EXHAUSTIVE_SEARCH_MAX_CANDIDATES};
 use crate::attribute_patterns::{match_pattern, PatternMatch};
 use crate::constants::RESERVED_GEO_FIELD_NAME;
-use crate::index::PrefixSearch;
+use crate::fieldids_weights_map::{
+    searchable_attribute_boost, split_attribute_boost, DEFAULT_ATTRIBUTE_BOOST,
+};
 use crate::localized_attributes_rules::LocalizedFieldIds;
+use crate::ngram_attributes::ngram_attribute;
 use crate::score_details::{ScoreDetails, ScoringStrategy};
 use crate::search::new::distinct::apply_distinct_rule;
 use crate::vector::Embedder;
 use crate::{
-    AscDesc, DocumentId, FieldId, Filter, Index, Member, Result, TermsMatchingStrategy, TimeBudget,
-    UserError, Weight,
+    AscDesc, DocumentId, FieldId, FieldidsWeightsMap, Filter, Index, Member, QuerySyntax, Result,
+    TermsMatchingStrategy, TimeBudget, UserError, Weight,
 };
 
 /// A structure used throughout the execution of a search query.
@@ -74,13 +86,49 @@ pub struct SearchContext<'ctx> {
     pub term_interner: Interner<QueryTerm>,
     pub phrase_docids: PhraseDocIdsCache,
     pub restricted_fids: Option<RestrictedFids>,
-    pub prefix_search: PrefixSearch,
+    /// The weights of the searchable fields, the lower the more important.
+    pub fields_weights: FieldidsWeightsMap,
+    /// The highest weight of the searchable fields, if any.
+    pub max_field_weight: Option<Weight>,
+    /// The typo tolerance and prefix search rules of the searched fields.
+    pub fields_tolerance: FieldsTolerance,
+    /// The searched fields whose words are split into n-grams, with the maximum number of words
+    /// matched by a part of a word in these fields.
+    pub ngram_fids: BTreeMap<FieldId, u16>,
     pub vector_store_stats: Option<VectorStoreStats>,
 }
 
 impl<'ctx> SearchContext<'ctx> {
     pub fn new(index: &'ctx Index, txn: &'ctx RoTxn<'ctx>) -> Result<Self> {
         let searchable_fids = index.searchable_fields_and_weights(txn)?;
+
+        // The user defined weights replace the ones deduced from the order of the searchable attributes
+        let boosts = index.searchable_attributes_weights(txn)?;
+        let (fields_weights, max_field_weight) = if boosts.is_empty() {
+            (index.fieldids_weights_map(txn)?, index.max_searchable_attribute_weight(txn)?)
+        } else {
+            let boosts = searchable_fids
+                .iter()
+                .map(|(name, fid, _)| (*fid, Some(searchable_attribute_boost(&boosts, name))))
+                .collect();
+            let (fields_weights, max_field_weight) = FieldidsWeightsMap::from_boosts(boosts);
+            (fields_weights, Some(max_field_weight))
+        };
+
+        let fields_tolerance = FieldsTolerance::new(
+            index,
+            txn,
+            searchable_fids.iter().map(|(name, fid, _)| (name.as_ref(), *fid)),
+        )?;
+
+        let ngram_attributes = index.ngram_attributes(txn)?;
+        let ngram_fids = searchable_fids
+            .iter()
+            .filter_map(|(name, fid, _)| {
+                ngram_attribute(&ngram_attributes, name).map(|rule| (*fid, rule.max_matches))
+            })
+            .collect();
+
         let exact_attributes_ids = index.exact_attributes_ids(txn)?;
 
         let mut exact = Vec::new();
@@ -93,8 +141,6 @@ impl<'ctx> SearchContext<'ctx> {
             }
         }
 
-        let prefix_search = index.prefix_search(txn)?.unwrap_or_default();
-
         Ok(Self {
             index,
             txn,
@@ -104,13 +150,16 @@ impl<'ctx> SearchContext<'ctx> {
             term_interner: <_>::default(),
             phrase_docids: <_>::default(),
             restricted_fids: None,
-            prefix_search,
+            fields_weights,
+            max_field_weight,
+            fields_tolerance,
+            ngram_fids,
             vector_store_stats: None,
         })
     }
 
     pub fn is_prefix_search_allowed(&self) -> bool {
-        self.prefix_search != PrefixSearch::Disabled
+        self.fields_tolerance.prefix_search_allowed()
     }
 
     pub fn attributes_to_search_on(
@@ -122,9 +171,16 @@ impl<'ctx> SearchContext<'ctx> {
         let exact_attributes_ids = self.index.exact_attributes_ids(self.txn)?;
 
         let mut universal_wildcard = false;
+        let mut boosted = false;
+        let mut boosts: Vec<(FieldId, Weight)> = Vec::new();
 
         let mut restricted_fids = RestrictedFids::default();
         for field_name in attributes_to_search_on {
+            // The attribute can be followed by its boost, e.g. `title^3`
+            let (field_name, boost) = split_attribute_boost(field_name);
+            boosted |= boost.is_some();
+            let boost = boost.unwrap_or(DEFAULT_ATTRIBUTE_BOOST);
+
             if field_name == "*" {
                 universal_wildcard = true;
                 // we cannot early exit as we want to returns error in case of unknown fields
@@ -142,6 +198,7 @@ impl<'ctx> SearchContext<'ctx> {
 
                 if !matching_searchable_weights.is_empty() {
                     for (_name, fid, weight) in matching_searchable_weights {
+                        boosts.push((*fid, boost));
                         if exact_attributes_ids.contains(fid) {
                             restricted_fids.exact.push((*fid, *weight));
                         } else {
@@ -174,6 +231,7 @@ impl<'ctx> SearchContext<'ctx> {
                 }
             };
 
+            boosts.push((fid, boost));
             if exact_attributes_ids.contains(&fid) {
                 restricted_fids.exact.push((fid, weight));
             } else {
@@ -181,9 +239,42 @@ impl<'ctx> SearchContext<'ctx> {
             };
         }
 
+        // The boosts of the query replace the weights of the searchable fields, the fields that
+        // are not searched on are considered the least important ones.
+        if boosted {
+            let boosts = searchable_fields_weights
+                .iter()
+                .map(|(_name, fid, _weight)| {
+                    let boost = boosts
+                        .iter()
+                        .filter(|(id, _)| id == fid)
+                        .map(|(_, boost)| *boost)
+                        .max()
+                        .or(universal_wildcard.then_some(DEFAULT_ATTRIBUTE_BOOST));
+                    (*fid, boost)
+                })
+                .collect();
+            let (fields_weights, max_field_weight) = FieldidsWeightsMap::from_boosts(boosts);
+            for (fid, weight) in
+                restricted_fids.exact.iter_mut().chain(&mut restricted_fids.tolerant)
+            {
+                *weight = fields_weights.weight(*fid).unwrap_or(max_field_weight);
+            }
+            self.fields_weights = fields_weights;
+            self.max_field_weight = Some(max_field_weight);
+        }
+
         if universal_wildcard {
             self.restricted_fids = None;
         } else {
+            let fids: Vec<_> = restricted_fids
+                .exact
+                .iter()
+                .chain(&restricted_fids.tolerant)
+                .map(|(fid, _)| *fid)
+                .collect();
+            self.fields_tolerance.restrict_to(&fids);
+            self.ngram_fids.retain(|fid, _| fids.contains(fid));
             self.restricted_fids = Some(restricted_fids);
         }
 
@@ -214,6 +305,8 @@ impl AddAssign for VectorStoreStats {
 pub enum Word {
     Original(Interned<String>),
     Derived(Interned<String>),
+    /// A word containing the original one, only searched in the n-gram attributes.
+    Ngram(Interned<String>),
 }
 
 impl Word {
@@ -221,6 +314,7 @@ impl Word {
         match self {
             Word::Original(word) => *word,
             Word::Derived(word) => *word,
+            Word::Ngram(word) => *word,
         }
     }
 }
@@ -315,6 +409,23 @@ fn resolve_negative_phrases(
     Ok(negative_bitmap)
 }
 
+/// Returns the documents of the universe in which a term of the group matches within the field.
+#[tracing::instrument(level = "trace", skip_all, target = "search::query")]
+fn resolve_field_scoped_group(
+    ctx: &mut SearchContext<'_>,
+    universe: &RoaringBitmap,
+    group: &QueryTermGroup,
+    fid: FieldId,
+) -> Result<RoaringBitmap> {
+    let mut docids = RoaringBitmap::new();
+    for term in &group.alternatives {
+        let term = QueryTermSubset::full(term.value);
+        docids |=
+            compute_query_term_subset_docids_within_field_id(ctx, Some(universe), &term, fid)?;
+    }
+    Ok(docids & universe)
+}
+
 /// Return the list of initialised ranking rules to be used for a placeholder search.
 fn get_ranking_rules_for_placeholder_search<'ctx>(
     ctx: &SearchContext<'ctx>,
@@ -377,6 +488,7 @@ fn get_ranking_rules_for_vector<'ctx>(
     embedder_name: &str,
     embedder: &Embedder,
     quantized: bool,
+    vector_search_params: VectorSearchParams,
 ) -> Result<Vec<BoxRankingRule<'ctx, PlaceholderQuery>>> {
     // query graph search
 
@@ -405,6 +517,7 @@ fn get_ranking_rules_for_vector<'ctx>(
                         embedder_name,
                         embedder,
                         quantized,
+                        vector_search_params,
                     )?;
                     ranking_rules.push(Box::new(vector_sort));
                     vector = true;
@@ -446,7 +559,7 @@ fn get_ranking_rules_for_vector<'ctx>(
 
 /// Return the list of initialised ranking rules to be used for a query graph search.
 fn get_ranking_rules_for_query_graph_search<'ctx>(
-    ctx: &SearchContext<'ctx>,
+    ctx: &mut SearchContext<'ctx>,
     sort_criteria: &Option<Vec<AscDesc>>,
     geo_param: geo_sort::Parameter,
     terms_matching_strategy: TermsMatchingStrategy,
@@ -550,6 +663,12 @@ fn get_ranking_rules_for_query_graph_search<'ctx>(
             }
         }
     }
+
+    // Without the typo ranking rule, the synonyms with a weight lower than `1.0` must still be
+    // ranked below the other derivations of the terms
+    if !typo && ctx.has_down_weighted_synonyms()? {
+        ranking_rules.push(Box::new(SynonymWeight::new(None)));
+    }
     Ok(ranking_rules)
 }
 
@@ -635,6 +754,7 @@ pub fn execute_vector_search(
     embedder_name: &str,
     embedder: &Embedder,
     quantized: bool,
+    vector_search_params: VectorSearchParams,
     time_budget: TimeBudget,
     ranking_score_threshold: Option<f64>,
 ) -> Result<PartialSearchResult> {
@@ -651,6 +771,7 @@ pub fn execute_vector_search(
         embedder_name,
         embedder,
         quantized,
+        vector_search_params,
     )?;
 
     let mut placeholder_search_logger = logger::DefaultSearchLogger;
@@ -686,6 +807,7 @@ pub fn execute_vector_search(
 pub fn execute_search(
     ctx: &mut SearchContext<'_>,
     query: Option<&str>,
+    query_syntax: QuerySyntax,
     terms_matching_strategy: TermsMatchingStrategy,
     scoring_strategy: ScoringStrategy,
     exhaustive_number_hits: bool,
@@ -706,7 +828,7 @@ pub fn execute_search(
 
     let mut used_negative_operator = false;
     let mut located_query_terms = None;
-    let query_terms = if let Some(query) = query {
+    let term_groups = if let Some(query) = query {
         let span = tracing::trace_span!(target: "search::tokens", "tokenizer_builder");
         let entered = span.enter();
 
@@ -774,13 +896,25 @@ pub fn execute_search(
         let tokenizer = tokbuilder.build();
         drop(entered);
 
-        let span = tracing::trace_span!(target: "search::tokens", "tokenize");
-        let entered = span.enter();
-        let tokens = tokenizer.tokenize(query);
-        drop(entered);
-
-        let ExtractedTokens { query_terms, negative_words, negative_phrases } =
-            located_query_terms_from_tokens(ctx, tokens, words_limit)?;
+        let (term_groups, negative_words, negative_phrases) = match query_syntax {
+            QuerySyntax::Simple => {
+                let span = tracing::trace_span!(target: "search::tokens", "tokenize");
+                let entered = span.enter();
+                let tokens = tokenizer.tokenize(query);
+                drop(entered);
+
+                let ExtractedTokens { query_terms, negative_words, negative_phrases } =
+                    located_query_terms_from_tokens(ctx, tokens, words_limit)?;
+                let term_groups: Vec<QueryTermGroup> =
+                    query_terms.into_iter().map(QueryTermGroup::from).collect();
+                (term_groups, negative_words, negative_phrases)
+            }
+            QuerySyntax::Advanced => {
+                let ExtractedAdvancedTokens { term_groups, negative_words, negative_phrases } =
+                    located_query_terms_from_advanced_query(ctx, query, &tokenizer, words_limit)?;
+                (term_groups, negative_words, negative_phrases)
+            }
+        };
         used_negative_operator = !negative_words.is_empty() || !negative_phrases.is_empty();
 
         let ignored_documents = resolve_negative_words(ctx, Some(&universe), &negative_words)?;
@@ -789,18 +923,24 @@ pub fn execute_search(
         universe -= ignored_documents;
         universe -= ignored_phrases;
 
-        if query_terms.is_empty() {
+        for group in &term_groups {
+            if let Some(fid) = group.field_id {
+                universe = resolve_field_scoped_group(ctx, &universe, group, fid)?;
+            }
+        }
+
+        if term_groups.is_empty() {
             // Do a placeholder search instead
             None
         } else {
-            Some(query_terms)
+            Some(term_groups)
         }
     } else {
         None
     };
 
-    let bucket_sort_output = if let Some(query_terms) = query_terms {
-        let (graph, new_located_query_terms) = QueryGraph::from_query(ctx, &query_terms)?;
+    let bucket_sort_output = if let Some(term_groups) = term_groups {
+        let (graph, new_located_query_terms) = QueryGraph::from_query(ctx, &term_groups)?;
         located_query_terms = Some(new_located_query_terms);
 
         let ranking_rules = get_ranking_rules_for_query_graph_search(
@@ -7,7 +7,8 @@ use roaring::RoaringBitmap;
 
 use super::interner::{FixedSizeInterner, Interned};
 use super::query_term::{
-    self, number_of_typos_allowed, LocatedQueryTerm, LocatedQueryTermSubset, QueryTermSubset,
+    self, number_of_typos_allowed, LocatedQueryTerm, LocatedQueryTermSubset, QueryTermGroup,
+    QueryTermSubset,
 };
 use super::small_bitmap::SmallBitmap;
 use super::SearchContext;
@@ -92,12 +93,15 @@ pub struct QueryGraph {
 impl QueryGraph {
     /// Build the query graph from the parsed user search query, return an updated list of the located query terms
     /// which contains ngrams.
+    ///
+    /// The alternatives of a group are parallel nodes of the graph, that share the same term id.
     pub fn from_query(
         ctx: &mut SearchContext<'_>,
-        // The terms here must be consecutive
-        terms: &[LocatedQueryTerm],
+        // The groups here must be consecutive
+        groups: &[QueryTermGroup],
     ) -> Result<(QueryGraph, Vec<LocatedQueryTerm>)> {
-        let mut new_located_query_terms = terms.to_vec();
+        let mut new_located_query_terms: Vec<LocatedQueryTerm> =
+            groups.iter().flat_map(|group| group.alternatives.iter().cloned()).collect();
 
         let nbr_typos = number_of_typos_allowed(ctx)?;
 
@@ -109,23 +113,30 @@ impl QueryGraph {
         let (mut prev2, mut prev1, mut prev0): (Vec<u16>, Vec<u16>, Vec<u16>) =
             (vec![], vec![], vec![root_node]);
 
-        let original_terms_len = terms.len();
-        for term_idx in 0..original_terms_len {
+        let original_groups_len = groups.len();
+        for term_idx in 0..original_groups_len {
             let mut new_nodes = vec![];
 
-            let new_node_idx = add_node(
-                &mut nodes_data,
-                QueryNodeData::Term(LocatedQueryTermSubset {
-                    term_subset: QueryTermSubset::full(terms[term_idx].value),
-                    positions: terms[term_idx].positions.clone(),
-                    term_ids: term_idx as u8..=term_idx as u8,
-                }),
-            );
-            new_nodes.push(new_node_idx);
+            let group = &groups[term_idx];
+            for term in &group.alternatives {
+                let mut term_subset = QueryTermSubset::full(term.value);
+                if group.required {
+                    term_subset.make_mandatory();
+                }
+                let new_node_idx = add_node(
+                    &mut nodes_data,
+                    QueryNodeData::Term(LocatedQueryTermSubset {
+                        term_subset,
+                        positions: term.positions.clone(),
+                        term_ids: term_idx as u8..=term_idx as u8,
+                    }),
+                );
+                new_nodes.push(new_node_idx);
+            }
 
             if !prev1.is_empty() {
                 if let Some(ngram) =
-                    query_term::make_ngram(ctx, &terms[term_idx - 1..=term_idx], &nbr_typos)?
+                    make_ngram_of_groups(ctx, &groups[term_idx - 1..=term_idx], &nbr_typos)?
                 {
                     new_located_query_terms.push(ngram.clone());
                     let ngram_idx = add_node(
@@ -141,7 +152,7 @@ impl QueryGraph {
             }
             if !prev2.is_empty() {
                 if let Some(ngram) =
-                    query_term::make_ngram(ctx, &terms[term_idx - 2..=term_idx], &nbr_typos)?
+                    make_ngram_of_groups(ctx, &groups[term_idx - 2..=term_idx], &nbr_typos)?
                 {
                     new_located_query_terms.push(ngram.clone());
                     let ngram_idx = add_node(
@@ -414,6 +425,25 @@ impl QueryGraph {
     }
 }
 
+/// Only groups made of a single term that is not scoped to a field are merged into ngrams.
+fn make_ngram_of_groups(
+    ctx: &mut SearchContext<'_>,
+    groups: &[QueryTermGroup],
+    number_of_typos_allowed: &impl Fn(&str) -> u8,
+) -> Result<Option<LocatedQueryTerm>> {
+    let terms: Option<Vec<LocatedQueryTerm>> = groups
+        .iter()
+        .map(|group| match group.alternatives.as_slice() {
+            [term] if group.field_id.is_none() => Some(term.clone()),
+            _ => None,
+        })
+        .collect();
+    match terms {
+        Some(terms) => query_term::make_ngram(ctx, &terms, number_of_typos_allowed),
+        None => Ok(None),
+    }
+}
+
 fn add_node(nodes_data: &mut Vec<QueryNodeData>, node_data: QueryNodeData) -> u16 {
     let new_node_idx = nodes_data.len() as u16;
     nodes_data.push(node_data);
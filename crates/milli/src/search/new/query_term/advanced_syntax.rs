@@ -0,0 +1,415 @@
+//! Parsing of the search queries written with the advanced query syntax.
+//!
+//! On top of the words, `"quoted phrases"` and `-negations` of the default syntax, the advanced syntax supports:
+//! - `+word` or `+"a phrase"`: a required term, that must match whatever the terms matching strategy
+//! - `(word OR "a phrase" OR other)`: a group of alternatives, any of them matching at this place of the query
+//! - `field:word`, `field:"a phrase"` or `field:(a OR b)`: a term that must match within the given field,
+//!   the prefix is only a field when it is a searchable attribute so that `10:30` or URLs remain plain terms
+//! - `te*`, `*fir` or `ke?ir`: a wildcard pattern, in which `*` stands for any sequence of characters and `?` for one character
+//! - `/k[ea]fir/`: a regular expression, that must match the entire word
+//!
+//! The query is split into clauses before being tokenized, so that the operators are never mistaken for separators.
+//! The text of each alternative is then tokenized like the rest of the query,
+//! except for the patterns and regular expressions that are expanded into the words of the index.
+
+use std::fmt;
+
+/// A term of the query, along with its operators.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Clause<'q> {
+    pub modifier: Modifier,
+    /// The field the term is scoped to, if any.
+    pub field: Option<&'q str>,
+    /// The alternatives of a group, or a single alternative for a simple term.
+    pub alternatives: Vec<Alternative<'q>>,
+    /// Whether the clause ends the query, in which case its last word can be a prefix.
+    pub ends_query: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Modifier {
+    None,
+    /// `+`, the clause must match
+    Required,
+    /// `-`, the clause must not match
+    Negated,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Alternative<'q> {
+    Word(&'q str),
+    Phrase(&'q str),
+    /// A word containing `*` or `?` wildcards.
+    Wildcard(&'q str),
+    /// The regular expression between the slashes of `/regex/`.
+    Regex(&'q str),
+}
+
+/// A malformed advanced query, with the byte offset of the error in the query.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SyntaxError {
+    pub offset: usize,
+    pub kind: SyntaxErrorKind,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SyntaxErrorKind {
+    UnclosedQuote,
+    UnclosedRegex,
+    UnclosedParenthesis,
+    UnexpectedClosingParenthesis,
+    NestedGroup,
+    EmptyGroup,
+    MissingOr,
+    DanglingOr,
+    OrOutsideGroup,
+    DanglingOperator(char),
+    NegatedField,
+}
+
+impl fmt::Display for SyntaxError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let Self { offset, kind } = self;
+        match kind {
+            SyntaxErrorKind::UnclosedQuote => write!(f, "the quote at position {offset} is never closed"),
+            SyntaxErrorKind::UnclosedRegex => {
+                write!(f, "the regular expression at position {offset} is never closed by a `/`")
+            }
+            SyntaxErrorKind::UnclosedParenthesis => {
+                write!(f, "the parenthesis at position {offset} is never closed")
+            }
+            SyntaxErrorKind::UnexpectedClosingParenthesis => {
+                write!(f, "unexpected `)` at position {offset}, no group is open")
+            }
+            SyntaxErrorKind::NestedGroup => {
+                write!(f, "groups cannot be nested, found `(` at position {offset}")
+            }
+            SyntaxErrorKind::EmptyGroup => write!(f, "the group at position {offset} is empty"),
+            SyntaxErrorKind::MissingOr => write!(
+                f,
+                "the alternatives of a group must be separated by `OR`, found a term at position {offset}"
+            ),
+            SyntaxErrorKind::DanglingOr => {
+                write!(f, "the `OR` at position {offset} must be between two alternatives")
+            }
+            SyntaxErrorKind::OrOutsideGroup => write!(
+                f,
+                "the `OR` at position {offset} must be inside a group, such as `(a OR b)`"
+            ),
+            SyntaxErrorKind::DanglingOperator(operator) => write!(
+                f,
+                "the `{operator}` at position {offset} must be immediately followed by a term"
+            ),
+            SyntaxErrorKind::NegatedField => write!(
+                f,
+                "the field-scoped term at position {offset} cannot be negated"
+            ),
+        }
+    }
+}
+
+/// Splits an advanced query into its clauses, `is_field` tells whether the prefix of a `prefix:term` is a field.
+pub fn parse_advanced_query(
+    query: &str,
+    is_field: impl Fn(&str) -> bool,
+) -> Result<Vec<Clause<'_>>, SyntaxError> {
+    let mut parser = Parser { query, offset: 0, is_field };
+    let mut clauses = Vec::new();
+    while let Some(clause) = parser.clause()? {
+        clauses.push(clause);
+    }
+    Ok(clauses)
+}
+
+struct Parser<'q, F> {
+    query: &'q str,
+    offset: usize,
+    is_field: F,
+}
+
+impl<'q, F: Fn(&str) -> bool> Parser<'q, F> {
+    fn rest(&self) -> &'q str {
+        &self.query[self.offset..]
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.rest().chars().next()
+    }
+
+    fn skip_whitespace(&mut self) {
+        let rest = self.rest();
+        self.offset += rest.len() - rest.trim_start().len();
+    }
+
+    fn error(&self, kind: SyntaxErrorKind) -> SyntaxError {
+        SyntaxError { offset: self.offset, kind }
+    }
+
+    fn clause(&mut self) -> Result<Option<Clause<'q>>, SyntaxError> {
+        self.skip_whitespace();
+        let Some(c) = self.peek() else { return Ok(None) };
+
+        let modifier = match c {
+            '+' => Modifier::Required,
+            '-' => Modifier::Negated,
+            _ => Modifier::None,
+        };
+        if modifier != Modifier::None {
+            let operator_offset = self.offset;
+            self.offset += 1;
+            if self.peek().is_none_or(char::is_whitespace) {
+                return Err(SyntaxError {
+                    offset: operator_offset,
+                    kind: SyntaxErrorKind::DanglingOperator(c),
+                });
+            }
+        }
+
+        let clause_offset = self.offset;
+        let field = self.field();
+        if field.is_some() && modifier == Modifier::Negated {
+            return Err(SyntaxError { offset: clause_offset, kind: SyntaxErrorKind::NegatedField });
+        }
+
+        let alternatives = match self.peek() {
+            Some('(') => self.group()?,
+            Some(')') => return Err(self.error(SyntaxErrorKind::UnexpectedClosingParenthesis)),
+            _ => {
+                let alternative_offset = self.offset;
+                let alternative = self.alternative()?;
+                if alternative == Alternative::Word("OR") {
+                    return Err(SyntaxError {
+                        offset: alternative_offset,
+                        kind: SyntaxErrorKind::OrOutsideGroup,
+                    });
+                }
+                vec![alternative]
+            }
+        };
+
+        Ok(Some(Clause { modifier, field, alternatives, ends_query: self.rest().is_empty() }))
+    }
+
+    /// Consumes the `field:` prefix of a clause, only when it is a field immediately followed by a term.
+    fn field(&mut self) -> Option<&'q str> {
+        let rest = self.rest();
+        let end = rest.find(|c: char| c.is_whitespace() || matches!(c, '"' | '(' | ')' | ':'))?;
+        let (field, after) = rest.split_at(end);
+        let term = after.strip_prefix(':')?;
+        if field.is_empty()
+            || field.starts_with('/')
+            || term.is_empty()
+            || term.starts_with(char::is_whitespace)
+            || !(self.is_field)(field)
+        {
+            return None;
+        }
+        self.offset += end + 1;
+        Some(field)
+    }
+
+    /// Parses `(a OR "b c" OR d)`.
+    fn group(&mut self) -> Result<Vec<Alternative<'q>>, SyntaxError> {
+        let group_offset = self.offset;
+        // skip the opening parenthesis
+        self.offset += 1;
+
+        let mut alternatives = Vec::new();
+        let mut expects_alternative = true;
+        let mut last_or_offset = None;
+        loop {
+            self.skip_whitespace();
+            let offset = self.offset;
+            match self.peek() {
+                None => {
+                    return Err(SyntaxError {
+                        offset: group_offset,
+                        kind: SyntaxErrorKind::UnclosedParenthesis,
+                    })
+                }
+                Some(')') => {
+                    self.offset += 1;
+                    break;
+                }
+                Some('(') => return Err(self.error(SyntaxErrorKind::NestedGroup)),
+                Some(_) => {
+                    let alternative = self.alternative()?;
+                    let is_or = alternative == Alternative::Word("OR");
+                    match (is_or, expects_alternative) {
+                        (true, true) => {
+                            return Err(SyntaxError { offset, kind: SyntaxErrorKind::DanglingOr })
+                        }
+                        (true, false) => last_or_offset = Some(offset),
+                        (false, true) => alternatives.push(alternative),
+                        (false, false) => {
+                            return Err(SyntaxError { offset, kind: SyntaxErrorKind::MissingOr })
+                        }
+                    }
+                    expects_alternative = is_or;
+                }
+            }
+        }
+
+        if alternatives.is_empty() {
+            return Err(SyntaxError { offset: group_offset, kind: SyntaxErrorKind::EmptyGroup });
+        }
+        if expects_alternative {
+            let offset = last_or_offset.unwrap_or(group_offset);
+            return Err(SyntaxError { offset, kind: SyntaxErrorKind::DanglingOr });
+        }
+        Ok(alternatives)
+    }
+
+    /// Parses a single word, a wildcard pattern, a quoted phrase or a regular expression.
+    fn alternative(&mut self) -> Result<Alternative<'q>, SyntaxError> {
+        let rest = self.rest();
+        if let Some(phrase) = rest.strip_prefix('"') {
+            let Some(end) = phrase.find('"') else {
+                return Err(self.error(SyntaxErrorKind::UnclosedQuote));
+            };
+            self.offset += end + 2;
+            return Ok(Alternative::Phrase(&phrase[..end]));
+        }
+        if let Some(regex) = rest.strip_prefix('/') {
+            // a slash can be part of the regular expression when escaped by a backslash
+            let mut escaped = false;
+            let Some(end) = regex.find(|c: char| {
+                let is_end = c == '/' && !escaped;
+                escaped = c == '\\' && !escaped;
+                is_end
+            }) else {
+                return Err(self.error(SyntaxErrorKind::UnclosedRegex));
+            };
+            self.offset += end + 2;
+            return Ok(Alternative::Regex(&regex[..end]));
+        }
+
+        let end = rest
+            .find(|c: char| c.is_whitespace() || matches!(c, '"' | '(' | ')'))
+            .unwrap_or(rest.len());
+        self.offset += end;
+        let word = &rest[..end];
+        // a word made of wildcards only would match any word
+        if word.contains(['*', '?']) && !word.chars().all(|c| matches!(c, '*' | '?')) {
+            Ok(Alternative::Wildcard(word))
+        } else {
+            Ok(Alternative::Word(word))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(query: &str) -> Result<Vec<Clause<'_>>, SyntaxError> {
+        parse_advanced_query(query, |field| field == "title")
+    }
+
+    fn clause<'q>(
+        modifier: Modifier,
+        field: Option<&'q str>,
+        alternatives: Vec<Alternative<'q>>,
+        ends_query: bool,
+    ) -> Clause<'q> {
+        Clause { modifier, field, alternatives, ends_query }
+    }
+
+    #[test]
+    fn parse_operators() {
+        use Alternative::{Phrase, Word};
+
+        assert_eq!(
+            parse(r#"+kefir -"bad dog" title:(intel OR "good boy") doggo"#).unwrap(),
+            vec![
+                clause(Modifier::Required, None, vec![Word("kefir")], false),
+                clause(Modifier::Negated, None, vec![Phrase("bad dog")], false),
+                clause(
+                    Modifier::None,
+                    Some("title"),
+                    vec![Word("intel"), Phrase("good boy")],
+                    false
+                ),
+                clause(Modifier::None, None, vec![Word("doggo")], true),
+            ]
+        );
+        // a colon that is not followed by a term does not scope anything
+        assert_eq!(
+            parse("title: kefir ").unwrap(),
+            vec![
+                clause(Modifier::None, None, vec![Word("title:")], false),
+                clause(Modifier::None, None, vec![Word("kefir")], false),
+            ]
+        );
+        // a prefix that is not a field is part of a plain term
+        assert_eq!(
+            parse("genre:kefir 10:30 https://kefir.dog").unwrap(),
+            vec![
+                clause(Modifier::None, None, vec![Word("genre:kefir")], false),
+                clause(Modifier::None, None, vec![Word("10:30")], false),
+                clause(Modifier::None, None, vec![Word("https://kefir.dog")], true),
+            ]
+        );
+    }
+
+    #[test]
+    fn parse_patterns() {
+        use Alternative::{Regex, Wildcard, Word};
+
+        assert_eq!(
+            parse(r"te* title:(*fir OR ke?ir) /k[ea]f\/ir/ ? doggo").unwrap(),
+            vec![
+                clause(Modifier::None, None, vec![Wildcard("te*")], false),
+                clause(
+                    Modifier::None,
+                    Some("title"),
+                    vec![Wildcard("*fir"), Wildcard("ke?ir")],
+                    false
+                ),
+                clause(Modifier::None, None, vec![Regex(r"k[ea]f\/ir")], false),
+                clause(Modifier::None, None, vec![Word("?")], false),
+                clause(Modifier::None, None, vec![Word("doggo")], true),
+            ]
+        );
+        // the colons of a regular expression do not scope it to a field
+        assert_eq!(
+            parse("-/a:b c/").unwrap(),
+            vec![clause(Modifier::Negated, None, vec![Regex("a:b c")], true)]
+        );
+    }
+
+    #[test]
+    fn syntax_errors() {
+        let error = |query| parse(query).unwrap_err().to_string();
+
+        assert_eq!(error(r#"kefir "good boy"#), "the quote at position 6 is never closed");
+        assert_eq!(
+            error(r"kefir /intel\/"),
+            "the regular expression at position 6 is never closed by a `/`"
+        );
+        assert_eq!(error("(cat OR dog"), "the parenthesis at position 0 is never closed");
+        assert_eq!(error("kefir)"), "unexpected `)` at position 5, no group is open");
+        assert_eq!(error("(cat OR (dog))"), "groups cannot be nested, found `(` at position 8");
+        assert_eq!(error("+()"), "the group at position 1 is empty");
+        assert_eq!(
+            error("(cat dog)"),
+            "the alternatives of a group must be separated by `OR`, found a term at position 5"
+        );
+        assert_eq!(error("(cat OR)"), "the `OR` at position 5 must be between two alternatives");
+        assert_eq!(error("(OR cat)"), "the `OR` at position 1 must be between two alternatives");
+        // `OR` is only an operator when written in uppercase
+        assert_eq!(
+            error("(cat or dog)"),
+            "the alternatives of a group must be separated by `OR`, found a term at position 5"
+        );
+        assert_eq!(
+            error("cat OR dog"),
+            "the `OR` at position 4 must be inside a group, such as `(a OR b)`"
+        );
+        assert_eq!(
+            error("kefir + intel"),
+            "the `+` at position 6 must be immediately followed by a term"
+        );
+        assert_eq!(error("-title:kefir"), "the field-scoped term at position 1 cannot be negated");
+    }
+}
@@ -1,20 +1,22 @@
 use std::borrow::Cow;
 use std::cmp::Ordering;
-use std::collections::BTreeSet;
+use std::collections::{BTreeMap, BTreeSet};
 use std::ops::ControlFlow;
 
+use charabia::Language;
 use fst::automaton::Str;
 use fst::{IntoStreamer, Streamer};
 use heed::types::DecodeIgnore;
 use itertools::{merge_join_by, EitherOrBoth};
 
 use super::{OneTypoTerm, Phrase, QueryTerm, ZeroTypoTerm};
+use crate::ngram_attributes::ngrams_fst_prefix;
 use crate::search::fst_utils::{Complement, Intersection, StartsWith, Union};
 use crate::search::new::interner::{DedupInterner, Interned};
 use crate::search::new::query_term::{Lazy, TwoTypoTerm};
 use crate::search::new::{limits, SearchContext};
 use crate::search::{build_dfa, get_first};
-use crate::{Result, MAX_WORD_LENGTH};
+use crate::{stemming, Result, MAX_WORD_LENGTH};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum NumberOfTypos {
@@ -111,6 +113,197 @@ fn find_one_typo_derivations(
     Ok(())
 }
 
+fn find_stem_derivations(
+    ctx: &mut SearchContext<'_>,
+    word_interned: Interned<String>,
+    language: Language,
+    mut visit: impl FnMut(Interned<String>) -> Result<ControlFlow<()>>,
+) -> Result<()> {
+    let word = ctx.word_interner.get(word_interned).to_owned();
+    let word = word.as_str();
+
+    let Some(stem) = stemming::stem(language, word) else { return Ok(()) };
+    let Some(prefix) = stemming::stems_fst_prefix(language, &stem) else { return Ok(()) };
+
+    // words that are their own stem are not in the stems FST
+    if ctx.index.contains_word(ctx.txn, &stem)? {
+        let stem = ctx.word_interner.insert(stem);
+        if visit(stem)?.is_break() {
+            return Ok(());
+        }
+    }
+
+    let fst = ctx.index.stems_fst(ctx.txn)?;
+    let mut stream = fst.search(StartsWith(Str::new(&prefix))).into_stream();
+    while let Some(key) = stream.next() {
+        let derived_word = std::str::from_utf8(&key[prefix.len()..])?;
+        if derived_word == word {
+            continue;
+        }
+        let derived_word = ctx.word_interner.insert(derived_word.to_owned());
+        if visit(derived_word)?.is_break() {
+            break;
+        }
+    }
+    Ok(())
+}
+
+fn find_stems(
+    ctx: &mut SearchContext<'_>,
+    word_interned: Interned<String>,
+    stemming_language: Option<Language>,
+) -> Result<BTreeSet<Interned<String>>> {
+    let mut stems = BTreeSet::new();
+    if let Some(language) = stemming_language {
+        find_stem_derivations(ctx, word_interned, language, |derived_word| {
+            if stems.len() < limits::MAX_STEM_COUNT {
+                stems.insert(derived_word);
+                Ok(ControlFlow::Continue(()))
+            } else {
+                Ok(ControlFlow::Break(()))
+            }
+        })?;
+    }
+    Ok(stems)
+}
+
+/// Returns the typo cost with which the synonyms of a rule of the given weight are ranked.
+///
+/// Every tenth of weight below `1.0` costs one typo: the synonyms of a rule with a weight of `0.9`
+/// are ranked as if they were 1 typo away from the original word, and the ones of a rule with a
+/// weight of `0.5` as if they were 5 typos away from it.
+pub(crate) fn synonym_weight_cost(weight: f32) -> u8 {
+    ((1.0 - weight) * 10.0).round().clamp(1.0, 10.0) as u8
+}
+
+/// Returns the synonyms of the sequence of words with their typo cost.
+///
+/// These are the synonyms of the `synonyms` setting and of the synonym rules with a weight of
+/// `1.0`, which cost nothing, or only the synonyms of the rules with a lower weight if
+/// `down_weighted` is `true`.
+fn find_synonyms_with_cost(
+    ctx: &mut SearchContext<'_>,
+    words: &[String],
+    down_weighted: bool,
+) -> Result<Vec<(Interned<Phrase>, u8)>> {
+    let mut synonyms = Vec::new();
+    if !down_weighted {
+        let synonyms_setting = ctx.index.synonyms(ctx.txn)?.remove(words).unwrap_or_default();
+        synonyms.extend(synonyms_setting.into_iter().map(|synonym| (synonym, 0)));
+    }
+    let weighted_synonyms = ctx.weighted_synonyms_of(words)?;
+    synonyms.extend(
+        weighted_synonyms
+            .into_iter()
+            .filter(|(_, weight)| (*weight < 1.0) == down_weighted)
+            .map(|(synonym, weight)| (synonym, synonym_weight_cost(weight))),
+    );
+
+    let mut synonym_word_count = 0;
+    Ok(synonyms
+        .into_iter()
+        .take(limits::MAX_SYNONYM_PHRASE_COUNT)
+        .filter_map(|(words, cost)| {
+            if synonym_word_count + words.len() > limits::MAX_SYNONYM_WORD_COUNT {
+                return None;
+            }
+            synonym_word_count += words.len();
+            let words = words.into_iter().map(|w| Some(ctx.word_interner.insert(w))).collect();
+            Some((ctx.phrase_interner.insert(Phrase { words }), cost))
+        })
+        .collect())
+}
+
+/// Returns the synonyms of the sequence of words.
+///
+/// These are the synonyms of the `synonyms` setting and of the synonym rules with a weight of
+/// `1.0`.
+pub(super) fn find_synonyms(
+    ctx: &mut SearchContext<'_>,
+    words: &[String],
+) -> Result<BTreeSet<Interned<Phrase>>> {
+    Ok(find_synonyms_with_cost(ctx, words, false)?
+        .into_iter()
+        .map(|(synonym, _)| synonym)
+        .collect())
+}
+
+/// Returns the down-weighted synonyms of the original word of the term, or of its ngram words,
+/// with their typo cost.
+///
+/// The lowest cost is kept for a synonym of both the original word and the ngram words.
+fn find_down_weighted_synonyms(
+    ctx: &mut SearchContext<'_>,
+    original: Interned<String>,
+    ngram_words: Option<Vec<Interned<String>>>,
+) -> Result<BTreeMap<Interned<Phrase>, u8>> {
+    let original = ctx.word_interner.get(original).to_owned();
+    let mut synonyms = find_synonyms_with_cost(ctx, &[original], true)?;
+    if let Some(ngram_words) = ngram_words {
+        let words: Vec<_> =
+            ngram_words.into_iter().map(|w| ctx.word_interner.get(w).to_owned()).collect();
+        synonyms.extend(find_synonyms_with_cost(ctx, &words, true)?);
+    }
+
+    let mut synonyms_cost = BTreeMap::new();
+    for (synonym, cost) in synonyms {
+        synonyms_cost
+            .entry(synonym)
+            .and_modify(|current: &mut u8| *current = (*current).min(cost))
+            .or_insert(cost);
+    }
+    Ok(synonyms_cost)
+}
+
+/// Returns the words of the n-gram attributes containing the given word.
+///
+/// At most the greatest `maxMatches` of the searched n-gram attributes are returned, in the
+/// lexicographic order of the words.
+fn find_ngram_matches(
+    ctx: &mut SearchContext<'_>,
+    word_interned: Interned<String>,
+) -> Result<BTreeSet<Interned<String>>> {
+    let mut ngram_matches = BTreeSet::new();
+    let Some(max_matches) = ctx.ngram_fids.values().max().map(|max| *max as usize) else {
+        return Ok(ngram_matches);
+    };
+
+    let prefix = ngrams_fst_prefix(ctx.word_interner.get(word_interned));
+    let fst = ctx.index.ngrams_fst(ctx.txn)?;
+    let mut stream = fst.search(StartsWith(Str::new(&prefix))).into_stream();
+    while let Some(key) = stream.next() {
+        if ngram_matches.len() >= max_matches {
+            tracing::debug!(
+                word = %ctx.word_interner.get(word_interned),
+                max_matches,
+                "the words matching a part of the word are truncated"
+            );
+            break;
+        }
+        let derived_word = std::str::from_utf8(&key[prefix.len()..])?;
+        ngram_matches.insert(ctx.word_interner.insert(derived_word.to_owned()));
+    }
+    Ok(ngram_matches)
+}
+
+/// Returns the language in which the word of the query must be stemmed, if any.
+///
+/// The language is the one detected by the tokenizer, or enforced by the locales of the search
+/// and the `localizedAttributes` setting. When it is unknown, the only stemmed language is used.
+fn stemming_language(
+    ctx: &SearchContext<'_>,
+    language: Option<Language>,
+) -> Result<Option<Language>> {
+    let Some(stemming) = ctx.index.stemming(ctx.txn)? else { return Ok(None) };
+    Ok(match language {
+        Some(language) => stemming.contains(&language).then_some(language),
+        None => match stemming.as_slice() {
+            [language] => Some(*language),
+            _ => None,
+        },
+    })
+}
+
 fn find_one_two_typo_derivations(
     word_interned: Interned<String>,
     is_prefix: bool,
@@ -170,6 +363,7 @@ pub fn partially_initialized_term_from_word(
     max_typo: u8,
     is_prefix: bool,
     is_ngram: bool,
+    language: Option<Language>,
 ) -> Result<QueryTerm> {
     let word_interned = ctx.word_interner.insert(word.to_owned());
 
@@ -180,6 +374,7 @@ pub fn partially_initialized_term_from_word(
                 ngram_words: None,
                 is_prefix: false,
                 max_levenshtein_distance: 0,
+                stemming_language: None,
                 zero_typo: <_>::default(),
                 one_typo: Lazy::Init(<_>::default()),
                 two_typo: Lazy::Init(<_>::default()),
@@ -220,23 +415,7 @@ pub fn partially_initialized_term_from_word(
             }
         })?;
     }
-    let synonyms = ctx.index.synonyms(ctx.txn)?;
-    let mut synonym_word_count = 0;
-    let synonyms = synonyms
-        .get(&vec![word.to_owned()])
-        .cloned()
-        .unwrap_or_default()
-        .into_iter()
-        .take(limits::MAX_SYNONYM_PHRASE_COUNT)
-        .filter_map(|words| {
-            if synonym_word_count + words.len() > limits::MAX_SYNONYM_WORD_COUNT {
-                return None;
-            }
-            synonym_word_count += words.len();
-            let words = words.into_iter().map(|w| Some(ctx.word_interner.insert(w))).collect();
-            Some(ctx.phrase_interner.insert(Phrase { words }))
-        })
-        .collect();
+    let synonyms = find_synonyms(ctx, &[word.to_owned()])?;
     let zero_typo =
         ZeroTypoTerm { phrase: None, exact: zero_typo, prefix_of, synonyms, use_prefix_db };
 
@@ -245,12 +424,46 @@ pub fn partially_initialized_term_from_word(
         ngram_words: None,
         max_levenshtein_distance: max_typo,
         is_prefix,
+        stemming_language: if is_ngram { None } else { stemming_language(ctx, language)? },
         zero_typo,
         one_typo: Lazy::Uninit,
         two_typo: Lazy::Uninit,
     })
 }
 
+/// Builds a term matching the words of the index accepted by the automaton of a wildcard pattern
+/// or a regular expression.
+///
+/// The matching words are all considered as zero-typo derivations of the pattern.
+pub fn term_from_pattern(
+    ctx: &mut SearchContext<'_>,
+    pattern: &str,
+    automaton: impl fst::Automaton,
+) -> Result<QueryTerm> {
+    let fst = ctx.get_words_fst()?;
+    let mut stream = fst.search(automaton).into_stream();
+
+    let mut matching_words = BTreeSet::new();
+    while let Some(word) = stream.next() {
+        if matching_words.len() >= limits::MAX_PATTERN_EXPANSION_COUNT {
+            break;
+        }
+        let word = std::str::from_utf8(word)?;
+        matching_words.insert(ctx.word_interner.insert(word.to_owned()));
+    }
+
+    Ok(QueryTerm {
+        original: ctx.word_interner.insert(pattern.to_owned()),
+        ngram_words: None,
+        max_levenshtein_distance: 0,
+        is_prefix: false,
+        stemming_language: None,
+        zero_typo: ZeroTypoTerm { prefix_of: matching_words, ..Default::default() },
+        one_typo: Lazy::Init(<_>::default()),
+        two_typo: Lazy::Init(<_>::default()),
+    })
+}
+
 fn find_split_words(ctx: &mut SearchContext<'_>, word: &str) -> Result<Option<Interned<Phrase>>> {
     if let Some((l, r)) = split_best_frequency(ctx, word)? {
         Ok(Some(ctx.phrase_interner.insert(Phrase { words: vec![Some(l), Some(r)] })))
@@ -266,14 +479,18 @@ impl Interned<QueryTerm> {
         let allows_split_words = self_mut.allows_split_words();
         let QueryTerm {
             original,
+            ngram_words,
             is_prefix,
+            stemming_language,
             one_typo,
             max_levenshtein_distance: max_nbr_typos,
             ..
         } = self_mut;
 
         let original = *original;
+        let ngram_words = ngram_words.clone();
         let is_prefix = *is_prefix;
+        let stemming_language = *stemming_language;
         // let original_str = ctx.word_interner.get(*original).to_owned();
         if one_typo.is_init() {
             return Ok(());
@@ -298,6 +515,10 @@ impl Interned<QueryTerm> {
             None
         };
 
+        let stems = find_stems(ctx, original, stemming_language)?;
+        let synonyms = find_down_weighted_synonyms(ctx, original, ngram_words)?;
+        let ngram_matches = find_ngram_matches(ctx, original)?;
+
         let self_mut = ctx.term_interner.get_mut(self);
 
         // Only add the split words to the derivations if:
@@ -315,7 +536,8 @@ impl Interned<QueryTerm> {
         } else {
             split_words
         };
-        let one_typo = OneTypoTerm { split_words, one_typo: one_typo_words };
+        let one_typo =
+            OneTypoTerm { split_words, one_typo: one_typo_words, stems, synonyms, ngram_matches };
 
         self_mut.one_typo = Lazy::Init(one_typo);
 
@@ -325,12 +547,17 @@ impl Interned<QueryTerm> {
         let self_mut = ctx.term_interner.get_mut(self);
         let QueryTerm {
             original,
+            ngram_words,
             is_prefix,
+            stemming_language,
             two_typo,
             max_levenshtein_distance: max_nbr_typos,
             ..
         } = self_mut;
-        let original_str = ctx.word_interner.get(*original).to_owned();
+        let original = *original;
+        let original_str = ctx.word_interner.get(original).to_owned();
+        let ngram_words = ngram_words.clone();
+        let stemming_language = *stemming_language;
         if two_typo.is_init() {
             return Ok(());
         }
@@ -339,7 +566,7 @@ impl Interned<QueryTerm> {
 
         if *max_nbr_typos > 0 {
             find_one_two_typo_derivations(
-                *original,
+                original,
                 *is_prefix,
                 ctx.index.words_fst(ctx.txn)?,
                 &mut ctx.word_interner,
@@ -368,9 +595,13 @@ impl Interned<QueryTerm> {
         }
 
         let split_words = find_split_words(ctx, original_str.as_str())?;
+        let stems = find_stems(ctx, original, stemming_language)?;
+        let synonyms = find_down_weighted_synonyms(ctx, original, ngram_words)?;
+        let ngram_matches = find_ngram_matches(ctx, original)?;
         let self_mut = ctx.term_interner.get_mut(self);
 
-        let one_typo = OneTypoTerm { one_typo: one_typo_words, split_words };
+        let one_typo =
+            OneTypoTerm { one_typo: one_typo_words, split_words, stems, synonyms, ngram_matches };
 
         let two_typo = TwoTypoTerm { two_typos: two_typo_words };
 
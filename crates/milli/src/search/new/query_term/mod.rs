@@ -1,22 +1,28 @@
+mod advanced_syntax;
 mod compute_derivations;
 mod ntypo_subset;
 mod parse_query;
 mod phrase;
 
-use std::collections::BTreeSet;
+use std::collections::{BTreeMap, BTreeSet};
 use std::iter::FromIterator;
 use std::ops::RangeInclusive;
 
+use charabia::Language;
 use either::Either;
+use levenshtein_automata::Distance;
 pub use ntypo_subset::NTypoTermSubset;
 pub use parse_query::{
-    located_query_terms_from_tokens, make_ngram, number_of_typos_allowed, ExtractedTokens,
+    located_query_terms_from_advanced_query, located_query_terms_from_tokens, make_ngram,
+    number_of_typos_allowed, ExtractedAdvancedTokens, ExtractedTokens,
 };
 pub use phrase::Phrase;
 
+use super::fields_tolerance::WordTolerance;
 use super::interner::{DedupInterner, Interned};
 use super::{limits, SearchContext, Word};
-use crate::Result;
+use crate::search::build_dfa;
+use crate::{FieldId, Result};
 
 /// A set of word derivations attached to a location in the search query.
 #[derive(Clone, PartialEq, Eq, Hash)]
@@ -46,6 +52,8 @@ pub struct QueryTerm {
     ngram_words: Option<Vec<Interned<String>>>,
     max_levenshtein_distance: u8,
     is_prefix: bool,
+    /// The language in which the word is stemmed, if any
+    stemming_language: Option<Language>,
     zero_typo: ZeroTypoTerm,
     // May not be computed yet
     one_typo: Lazy<OneTypoTerm>,
@@ -73,6 +81,14 @@ struct OneTypoTerm {
     split_words: Option<Interned<Phrase>>,
     /// Words that are 1 typo away from the original word
     one_typo: BTreeSet<Interned<String>>,
+    /// Words sharing the stem of the original word, ranked as if they were 1 typo away from it
+    stems: BTreeSet<Interned<String>>,
+    /// Synonyms of the original word with a weight lower than `1.0`, with the typo cost of their
+    /// weight
+    synonyms: BTreeMap<Interned<Phrase>, u8>,
+    /// Words of the n-gram attributes containing the original word, ranked as if they were 1 typo
+    /// away from it
+    ngram_matches: BTreeSet<Interned<String>>,
 }
 #[derive(Default, Clone, PartialEq, Eq, Hash)]
 struct TwoTypoTerm {
@@ -260,18 +276,24 @@ impl QueryTermSubset {
 
         match &self.one_typo_subset {
             NTypoTermSubset::All => {
-                let Lazy::Init(OneTypoTerm { split_words: _, one_typo }) = &original.one_typo
+                let Lazy::Init(OneTypoTerm { one_typo, stems, ngram_matches, .. }) =
+                    &original.one_typo
                 else {
                     panic!()
                 };
-                result.extend(one_typo.iter().copied().map(Word::Derived))
+                result.extend(one_typo.iter().copied().map(Word::Derived));
+                result.extend(stems.iter().copied().map(Word::Derived));
+                result.extend(ngram_matches.iter().copied().map(Word::Ngram));
             }
             NTypoTermSubset::Subset { words, phrases: _ } => {
-                let Lazy::Init(OneTypoTerm { split_words: _, one_typo }) = &original.one_typo
+                let Lazy::Init(OneTypoTerm { one_typo, stems, ngram_matches, .. }) =
+                    &original.one_typo
                 else {
                     panic!()
                 };
                 result.extend(one_typo.intersection(words).copied().map(Word::Derived));
+                result.extend(stems.intersection(words).copied().map(Word::Derived));
+                result.extend(ngram_matches.intersection(words).copied().map(Word::Ngram));
             }
             NTypoTermSubset::Nothing => {}
         };
@@ -305,14 +327,15 @@ impl QueryTermSubset {
 
         match &self.one_typo_subset {
             NTypoTermSubset::All => {
-                let Lazy::Init(OneTypoTerm { split_words, one_typo: _ }) = &original.one_typo
+                let Lazy::Init(OneTypoTerm { split_words, synonyms, .. }) = &original.one_typo
                 else {
                     panic!();
                 };
                 result.extend(split_words.iter().copied());
+                result.extend(synonyms.keys().copied());
             }
             NTypoTermSubset::Subset { phrases, .. } => {
-                let Lazy::Init(OneTypoTerm { split_words, one_typo: _ }) = &original.one_typo
+                let Lazy::Init(OneTypoTerm { split_words, synonyms, .. }) = &original.one_typo
                 else {
                     panic!();
                 };
@@ -321,6 +344,7 @@ impl QueryTermSubset {
                         result.insert(*split_words);
                     }
                 }
+                result.extend(synonyms.keys().filter(|synonym| phrases.contains(synonym)).copied());
             }
             NTypoTermSubset::Nothing => {}
         }
@@ -390,6 +414,77 @@ impl QueryTermSubset {
             }
         }
     }
+
+    /// Only keep the derivations of the term tolerated by a field.
+    ///
+    /// The typos are removed when the field tolerates fewer of them, but not the split words,
+    /// stems and n-gram matches that are ranked as typos. When the field doesn't tolerate
+    /// prefixes, only the typos of the whole word are kept.
+    pub fn restrict_tolerance(
+        &mut self,
+        ctx: &mut SearchContext<'_>,
+        tolerance: WordTolerance,
+    ) -> Result<()> {
+        let original = ctx.term_interner.get(self.original);
+        let max_typos = tolerance.max_typos.min(original.max_levenshtein_distance);
+        let without_prefix = original.is_prefix && !tolerance.prefix_search;
+        if max_typos == original.max_levenshtein_distance && !without_prefix {
+            return Ok(());
+        }
+
+        if without_prefix {
+            let ZeroTypoTerm { phrase, exact, prefix_of: _, synonyms, use_prefix_db: _ } =
+                &original.zero_typo;
+            self.zero_typo_subset.intersect(&NTypoTermSubset::Subset {
+                words: exact.iter().copied().collect(),
+                phrases: phrase.iter().chain(synonyms).copied().collect(),
+            });
+        }
+
+        if self.one_typo_subset.is_empty() && self.two_typo_subset.is_empty() {
+            return Ok(());
+        }
+        self.original.compute_fully_if_needed(ctx)?;
+
+        let original = ctx.term_interner.get(self.original);
+        let dfa = without_prefix
+            .then(|| build_dfa(ctx.word_interner.get(original.original), max_typos, false));
+        let tolerated = |word: &Interned<String>, nbr_typos: u8| {
+            nbr_typos <= max_typos
+                && dfa.as_ref().is_none_or(|dfa| {
+                    let distance = dfa.eval(ctx.word_interner.get(*word));
+                    matches!(distance, Distance::Exact(d) if d <= nbr_typos)
+                })
+        };
+
+        if let Lazy::Init(OneTypoTerm { split_words, one_typo, stems, synonyms, ngram_matches }) =
+            &original.one_typo
+        {
+            self.one_typo_subset.intersect(&NTypoTermSubset::Subset {
+                words: one_typo
+                    .iter()
+                    .filter(|w| tolerated(w, 1))
+                    .chain(stems)
+                    .chain(ngram_matches)
+                    .copied()
+                    .collect(),
+                phrases: split_words.iter().chain(synonyms.keys()).copied().collect(),
+            });
+        }
+        if let Lazy::Init(TwoTypoTerm { two_typos }) = &original.two_typo {
+            self.two_typo_subset.intersect(&NTypoTermSubset::Subset {
+                words: two_typos.iter().filter(|w| tolerated(w, 2)).copied().collect(),
+                phrases: BTreeSet::new(),
+            });
+        }
+
+        Ok(())
+    }
+    /// The original word of the term, or the concatenation of its words for an ngram.
+    pub fn original_word<'ctx>(&self, ctx: &'ctx SearchContext<'_>) -> &'ctx str {
+        let original = ctx.term_interner.get(self.original);
+        ctx.word_interner.get(original.original)
+    }
     pub fn clear_zero_typo_subset(&mut self) {
         self.zero_typo_subset = NTypoTermSubset::Nothing;
     }
@@ -399,6 +494,49 @@ impl QueryTermSubset {
     pub fn clear_two_typo_subset(&mut self) {
         self.two_typo_subset = NTypoTermSubset::Nothing;
     }
+    /// The synonyms of the subset with a weight lower than `1.0`, grouped by their typo cost.
+    pub fn down_weighted_synonyms(
+        &self,
+        ctx: &mut SearchContext<'_>,
+    ) -> Result<BTreeMap<u8, BTreeSet<Interned<Phrase>>>> {
+        let mut synonyms_by_cost = BTreeMap::<u8, BTreeSet<_>>::new();
+        if self.one_typo_subset.is_empty() {
+            return Ok(synonyms_by_cost);
+        }
+        self.original.compute_fully_if_needed(ctx)?;
+
+        let original = ctx.term_interner.get(self.original);
+        let Lazy::Init(OneTypoTerm { synonyms, .. }) = &original.one_typo else { panic!() };
+        for (synonym, cost) in synonyms {
+            if self.one_typo_subset.contains_phrase(*synonym) {
+                synonyms_by_cost.entry(*cost).or_default().insert(*synonym);
+            }
+        }
+        Ok(synonyms_by_cost)
+    }
+    /// Removes the synonyms with a weight lower than `1.0` from the subset.
+    pub fn remove_down_weighted_synonyms(&mut self, ctx: &SearchContext<'_>) {
+        let original = ctx.term_interner.get(self.original);
+        let Lazy::Init(OneTypoTerm { split_words, one_typo, stems, synonyms, ngram_matches }) =
+            &original.one_typo
+        else {
+            return;
+        };
+        if synonyms.is_empty() {
+            return;
+        }
+        self.one_typo_subset.intersect(&NTypoTermSubset::Subset {
+            words: one_typo.iter().chain(stems).chain(ngram_matches).copied().collect(),
+            phrases: split_words.iter().copied().collect(),
+        });
+    }
+    /// Keeps only the given synonyms with a weight lower than `1.0` in the subset.
+    pub fn keep_only_down_weighted_synonyms(&mut self, synonyms: BTreeSet<Interned<Phrase>>) {
+        self.clear_zero_typo_subset();
+        self.clear_two_typo_subset();
+        self.one_typo_subset
+            .intersect(&NTypoTermSubset::Subset { words: BTreeSet::new(), phrases: synonyms });
+    }
     pub fn description(&self, ctx: &SearchContext<'_>) -> String {
         let t = ctx.term_interner.get(self.original);
         ctx.word_interner.get(t.original).to_owned()
@@ -417,8 +555,12 @@ impl ZeroTypoTerm {
 }
 impl OneTypoTerm {
     fn is_empty(&self) -> bool {
-        let OneTypoTerm { split_words, one_typo } = self;
-        one_typo.is_empty() && split_words.is_none()
+        let OneTypoTerm { split_words, one_typo, stems, synonyms, ngram_matches } = self;
+        one_typo.is_empty()
+            && split_words.is_none()
+            && stems.is_empty()
+            && synonyms.is_empty()
+            && ngram_matches.is_empty()
     }
 }
 impl TwoTypoTerm {
@@ -470,6 +612,25 @@ impl LocatedQueryTerm {
     }
 }
 
+/// Query terms that can match at the same place of the user's search query.
+///
+/// Outside of the advanced query syntax, a group contains a single term and has no constraints.
+#[derive(Clone)]
+pub struct QueryTermGroup {
+    /// The alternatives of the group, any of them can match
+    pub alternatives: Vec<LocatedQueryTerm>,
+    /// `true` if the group cannot be deleted through the term matching strategy
+    pub required: bool,
+    /// The field the group must match within, if any
+    pub field_id: Option<FieldId>,
+}
+
+impl From<LocatedQueryTerm> for QueryTermGroup {
+    fn from(term: LocatedQueryTerm) -> Self {
+        Self { alternatives: vec![term], required: false, field_id: None }
+    }
+}
+
 impl QueryTerm {
     pub fn is_cached_prefix(&self) -> bool {
         self.zero_typo.use_prefix_db.is_some()
@@ -485,6 +646,25 @@ impl QueryTerm {
         self.zero_typo.phrase
     }
 
+    /// The original word, if it is in the index, and the words derived from it with typos,
+    /// along with their number of typos.
+    ///
+    /// Returns `None` for phrases and ngrams. The derivations that are not computed yet are ignored.
+    pub fn typo_derivations(&self) -> Option<Vec<(Interned<String>, u8)>> {
+        if self.zero_typo.phrase.is_some() || self.ngram_words.is_some() {
+            return None;
+        }
+
+        let mut words: Vec<_> = self.zero_typo.exact.iter().map(|word| (*word, 0)).collect();
+        if let Lazy::Init(OneTypoTerm { one_typo, .. }) = &self.one_typo {
+            words.extend(one_typo.iter().map(|word| (*word, 1)));
+        }
+        if let Lazy::Init(TwoTypoTerm { two_typos }) = &self.two_typo {
+            words.extend(two_typos.iter().map(|word| (*word, 2)));
+        }
+        Some(words)
+    }
+
     pub fn all_computed_derivations(&self) -> (Vec<Interned<String>>, Vec<Interned<Phrase>>) {
         let mut words = BTreeSet::new();
         let mut phrases = BTreeSet::new();
@@ -496,9 +676,14 @@ impl QueryTerm {
         phrases.extend(phrase.iter().copied());
         phrases.extend(synonyms.iter().copied());
 
-        if let Lazy::Init(OneTypoTerm { split_words, one_typo }) = &self.one_typo {
+        if let Lazy::Init(OneTypoTerm { split_words, one_typo, stems, synonyms, ngram_matches }) =
+            &self.one_typo
+        {
             words.extend(one_typo.iter().copied());
+            words.extend(stems.iter().copied());
+            words.extend(ngram_matches.iter().copied());
             phrases.extend(split_words.iter().copied());
+            phrases.extend(synonyms.keys().copied());
         };
 
         if let Lazy::Init(TwoTypoTerm { two_typos }) = &self.two_typo {
@@ -1,13 +1,17 @@
 use std::collections::BTreeSet;
 
 use charabia::normalizer::NormalizedTokenIter;
-use charabia::{SeparatorKind, TokenKind};
-
-use super::compute_derivations::partially_initialized_term_from_word;
-use super::{LocatedQueryTerm, ZeroTypoTerm};
+use charabia::{SeparatorKind, TokenKind, Tokenizer};
+
+use super::advanced_syntax::{parse_advanced_query, Alternative, Clause, Modifier};
+use super::compute_derivations::{
+    find_synonyms, partially_initialized_term_from_word, term_from_pattern,
+};
+use super::{LocatedQueryTerm, QueryTermGroup, ZeroTypoTerm};
+use crate::search::fst_utils::{Regex, Wildcard, MAX_WILDCARD_PATTERN_LEN};
 use crate::search::new::query_term::{Lazy, Phrase, QueryTerm};
 use crate::search::new::Word;
-use crate::{Result, SearchContext, MAX_WORD_LENGTH};
+use crate::{Result, SearchContext, UserError, MAX_WORD_LENGTH};
 
 #[derive(Clone)]
 /// Extraction of the content of a query.
@@ -80,6 +84,7 @@ pub fn located_query_terms_from_tokens(
                                 nbr_typos(word),
                                 false,
                                 false,
+                                token.language,
                             )?;
                             let located_term = LocatedQueryTerm {
                                 value: ctx.term_interner.push(term),
@@ -97,6 +102,7 @@ pub fn located_query_terms_from_tokens(
                         nbr_typos(word),
                         allow_prefix_search,
                         false,
+                        token.language,
                     )?;
                     let located_term = LocatedQueryTerm {
                         value: ctx.term_interner.push(term),
@@ -191,25 +197,181 @@ pub fn located_query_terms_from_tokens(
     Ok(ExtractedTokens { query_terms, negative_words, negative_phrases })
 }
 
+/// Extraction of the content of a query written with the advanced syntax.
+pub struct ExtractedAdvancedTokens {
+    /// The groups of terms to search for in the database, one per clause of the query.
+    pub term_groups: Vec<QueryTermGroup>,
+    /// The words that must not appear in the results.
+    pub negative_words: Vec<Word>,
+    /// The phrases that must not appear in the results.
+    pub negative_phrases: Vec<LocatedQueryTerm>,
+}
+
+/// Convert a search query written with the advanced syntax into groups of located query terms.
+///
+/// The text of each term is tokenized separately, after the operators have been parsed.
+#[tracing::instrument(level = "trace", skip_all, target = "search::query")]
+pub fn located_query_terms_from_advanced_query(
+    ctx: &mut SearchContext<'_>,
+    query: &str,
+    tokenizer: &Tokenizer<'_>,
+    words_limit: Option<usize>,
+) -> Result<ExtractedAdvancedTokens> {
+    let searchable_fields = ctx.index.searchable_fields_and_weights(ctx.txn)?;
+    let clauses = parse_advanced_query(query, |field| {
+        searchable_fields.iter().any(|(name, _, _)| name == field)
+    })
+    .map_err(|error| UserError::InvalidAdvancedQuery(error.to_string()))?;
+
+    let nbr_typos = number_of_typos_allowed(ctx)?;
+    let allow_prefix_search = ctx.is_prefix_search_allowed();
+    let parts_limit = words_limit.unwrap_or(usize::MAX);
+
+    let mut term_groups = Vec::new();
+    let mut negative_words = Vec::new();
+    let mut negative_phrases = Vec::new();
+    let mut position = 0u16;
+
+    for Clause { modifier, field, alternatives, ends_query } in clauses {
+        if term_groups.len() >= parts_limit {
+            break;
+        }
+        // the parser only returns the searchable fields
+        let field_id = field.and_then(|field| {
+            searchable_fields.iter().find(|(name, _, _)| name == field).map(|(_, fid, _)| *fid)
+        });
+
+        let mut located_terms = Vec::new();
+        let last_alternative = alternatives.len() - 1;
+        for (index, alternative) in alternatives.into_iter().enumerate() {
+            // patterns are expanded into the words of the index instead of being tokenized
+            if let Some(term) = pattern_term(ctx, alternative)? {
+                if modifier == Modifier::Negated {
+                    let (words, _) = term.all_computed_derivations();
+                    negative_words.extend(words.into_iter().map(Word::Original));
+                } else {
+                    located_terms.push(LocatedQueryTerm {
+                        value: ctx.term_interner.push(term),
+                        positions: position..=position,
+                    });
+                }
+                continue;
+            }
+            let (text, quoted) = match alternative {
+                Alternative::Phrase(phrase) => (phrase, true),
+                Alternative::Word(text)
+                | Alternative::Wildcard(text)
+                | Alternative::Regex(text) => (text, false),
+            };
+            let tokens: Vec<_> = tokenizer
+                .tokenize(text)
+                .filter(|token| {
+                    !token.lemma().is_empty()
+                        && matches!(token.kind, TokenKind::Word | TokenKind::StopWord)
+                })
+                .take(super::limits::MAX_TOKEN_COUNT)
+                .collect();
+
+            match tokens.as_slice() {
+                // a word that is not quoted has the derivations of a regular query term
+                [token] if !quoted && token.kind == TokenKind::Word => {
+                    let word = token.lemma();
+                    if modifier == Modifier::Negated {
+                        let word = Word::Original(ctx.word_interner.insert(word.to_string()));
+                        negative_words.push(word);
+                        continue;
+                    }
+                    let is_prefix = allow_prefix_search && ends_query && index == last_alternative;
+                    let term = partially_initialized_term_from_word(
+                        ctx,
+                        word,
+                        nbr_typos(word),
+                        is_prefix,
+                        false,
+                        token.language,
+                    )?;
+                    located_terms.push(LocatedQueryTerm {
+                        value: ctx.term_interner.push(term),
+                        positions: position..=position,
+                    });
+                }
+                // quoted texts, and words split by the tokenizer, must match as phrases
+                tokens => {
+                    let mut phrase = PhraseBuilder::empty();
+                    for (offset, token) in tokens.iter().enumerate() {
+                        phrase.push_word(ctx, token, position.saturating_add(offset as u16));
+                    }
+                    let Some(located_term) = phrase.build(ctx) else { continue };
+                    if modifier == Modifier::Negated {
+                        negative_phrases.push(located_term);
+                    } else {
+                        located_terms.push(located_term);
+                    }
+                }
+            }
+        }
+
+        let Some(end) = located_terms.iter().map(|term| *term.positions.end()).max() else {
+            continue;
+        };
+        position = end.saturating_add(1);
+        term_groups.push(QueryTermGroup {
+            alternatives: located_terms,
+            required: modifier == Modifier::Required || field_id.is_some(),
+            field_id,
+        });
+    }
+
+    Ok(ExtractedAdvancedTokens { term_groups, negative_words, negative_phrases })
+}
+
+/// Expands a wildcard pattern or a regular expression into a term matching the words of the index,
+/// returns `None` for the other alternatives.
+///
+/// The words of the index are normalized, so the wildcard patterns are lowercased
+/// but the regular expressions are used as written.
+fn pattern_term(
+    ctx: &mut SearchContext<'_>,
+    alternative: Alternative<'_>,
+) -> Result<Option<QueryTerm>> {
+    let term = match alternative {
+        Alternative::Wildcard(pattern) => {
+            let pattern = pattern.to_lowercase();
+            let Some(automaton) = Wildcard::new(&pattern) else {
+                return Err(UserError::InvalidAdvancedQuery(format!(
+                    "the wildcard pattern `{pattern}` is too long, it must be at most {MAX_WILDCARD_PATTERN_LEN} bytes long"
+                ))
+                .into());
+            };
+            term_from_pattern(ctx, &pattern, automaton)?
+        }
+        Alternative::Regex(regex) => {
+            let automaton = Regex::new(regex).map_err(|error| {
+                UserError::InvalidAdvancedQuery(format!(
+                    "the regular expression `/{regex}/` is invalid: {error}"
+                ))
+            })?;
+            term_from_pattern(ctx, &format!("/{regex}/"), automaton)?
+        }
+        Alternative::Word(_) | Alternative::Phrase(_) => return Ok(None),
+    };
+    Ok(Some(term))
+}
+
 pub fn number_of_typos_allowed<'ctx>(
     ctx: &SearchContext<'ctx>,
 ) -> Result<impl Fn(&str) -> u8 + 'ctx> {
-    let authorize_typos = ctx.index.authorize_typos(ctx.txn)?;
-    let min_len_one_typo = ctx.index.min_word_len_one_typo(ctx.txn)?;
-    let min_len_two_typos = ctx.index.min_word_len_two_typos(ctx.txn)?;
+    // The typos tolerated by at least one field are derived, the fields tolerating
+    // fewer typos only keep the derivations they tolerate when they are resolved.
+    let fields_tolerance = ctx.fields_tolerance.clone();
 
     let exact_words = ctx.index.exact_words(ctx.txn)?;
 
     Ok(Box::new(move |word: &str| {
-        if !authorize_typos
-            || word.len() < min_len_one_typo as usize
-            || exact_words.as_ref().is_some_and(|fst| fst.contains(word))
-        {
+        if exact_words.as_ref().is_some_and(|fst| fst.contains(word)) {
             0
-        } else if word.len() < min_len_two_typos as usize {
-            1
         } else {
-            2
+            fields_tolerance.most_tolerant(word).max_typos
         }
     }))
 }
@@ -254,24 +416,24 @@ pub fn make_ngram(
     let max_nbr_typos =
         number_of_typos_allowed(ngram_str.as_str()).saturating_sub(terms.len() as u8 - 1);
 
-    let mut term =
-        partially_initialized_term_from_word(ctx, &ngram_str, max_nbr_typos, is_prefix, true)?;
+    let mut term = partially_initialized_term_from_word(
+        ctx,
+        &ngram_str,
+        max_nbr_typos,
+        is_prefix,
+        true,
+        None,
+    )?;
 
     // Now add the synonyms
-    let index_synonyms = ctx.index.synonyms(ctx.txn)?;
-
-    term.zero_typo.synonyms.extend(
-        index_synonyms.get(&words).cloned().unwrap_or_default().into_iter().map(|words| {
-            let words = words.into_iter().map(|w| Some(ctx.word_interner.insert(w))).collect();
-            ctx.phrase_interner.insert(Phrase { words })
-        }),
-    );
+    term.zero_typo.synonyms.extend(find_synonyms(ctx, &words)?);
 
     let term = QueryTerm {
         original: ngram_str_interned,
         ngram_words: Some(words_interned),
         is_prefix,
         max_levenshtein_distance: max_nbr_typos,
+        stemming_language: None,
         zero_typo: term.zero_typo,
         one_typo: Lazy::Uninit,
         two_typo: Lazy::Uninit,
@@ -330,6 +492,7 @@ impl PhraseBuilder {
                     ngram_words: None,
                     max_levenshtein_distance: 0,
                     is_prefix: false,
+                    stemming_language: None,
                     zero_typo: ZeroTypoTerm {
                         phrase: Some(phrase),
                         exact: None,
@@ -75,11 +75,10 @@ impl RankingRuleGraphTrait for FidGraph {
             all_fields.extend(fields);
         }
 
-        let weights_map = ctx.index.fieldids_weights_map(ctx.txn)?;
-
         let mut edges = vec![];
         for fid in all_fields.iter().copied() {
-            let weight = weights_map
+            let weight = ctx
+                .fields_weights
                 .weight(fid)
                 .ok_or(InternalError::FieldidsWeightsMapMissingEntry { key: fid })?;
             if weight > current_max_weight {
@@ -92,9 +91,7 @@ impl RankingRuleGraphTrait for FidGraph {
         }
 
         // always lookup the max_fid if we don't already and add an artificial condition for max scoring
-        let max_weight = ctx.index.max_searchable_attribute_weight(ctx.txn)?;
-
-        if let Some(max_weight) = max_weight {
+        if let Some(max_weight) = ctx.max_field_weight {
             if current_max_weight < max_weight {
                 edges.push((
                     max_weight as u32 * term.term_ids.len() as u32, // TODO improve the fid score i.e. fid^10.
@@ -18,6 +18,8 @@ mod fid;
 mod position;
 /// Implementation of the `proximity` ranking rule
 mod proximity;
+/// Implementation of the implicit ranking rule of the synonym weights
+mod synonym_weight;
 /// Implementation of the `typo` ranking rule
 mod typo;
 /// Implementation of the `words` ranking rule
@@ -34,6 +36,7 @@ pub use fid::{FidCondition, FidGraph};
 pub use position::{PositionCondition, PositionGraph};
 pub use proximity::{ProximityCondition, ProximityGraph};
 use roaring::RoaringBitmap;
+pub use synonym_weight::{SynonymWeightCondition, SynonymWeightGraph};
 pub use typo::{TypoCondition, TypoGraph};
 pub use words::{WordsCondition, WordsGraph};
 
@@ -0,0 +1,70 @@
+use roaring::RoaringBitmap;
+
+use super::{ComputedCondition, RankingRuleGraphTrait};
+use crate::score_details::{Rank, ScoreDetails};
+use crate::search::new::interner::{DedupInterner, Interned};
+use crate::search::new::query_term::LocatedQueryTermSubset;
+use crate::search::new::resolve_query_graph::compute_query_term_subset_docids;
+use crate::search::new::SearchContext;
+use crate::Result;
+
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub struct SynonymWeightCondition {
+    term: LocatedQueryTermSubset,
+    cost: u8,
+}
+
+/// Ranks the synonyms with a weight lower than `1.0` below the other derivations of the terms,
+/// when the `typo` ranking rule doesn't already do it.
+pub enum SynonymWeightGraph {}
+
+impl RankingRuleGraphTrait for SynonymWeightGraph {
+    type Condition = SynonymWeightCondition;
+
+    #[tracing::instrument(level = "trace", skip_all, target = "search::synonym_weight")]
+    fn resolve_condition(
+        ctx: &mut SearchContext<'_>,
+        condition: &Self::Condition,
+        universe: &RoaringBitmap,
+    ) -> Result<ComputedCondition> {
+        let SynonymWeightCondition { term, .. } = condition;
+        let docids = compute_query_term_subset_docids(ctx, Some(universe), &term.term_subset)?;
+
+        Ok(ComputedCondition {
+            docids,
+            universe_len: universe.len(),
+            start_term_subset: None,
+            end_term_subset: term.clone(),
+        })
+    }
+
+    #[tracing::instrument(level = "trace", skip_all, target = "search::synonym_weight")]
+    fn build_edges(
+        ctx: &mut SearchContext<'_>,
+        conditions_interner: &mut DedupInterner<Self::Condition>,
+        _from: Option<&LocatedQueryTermSubset>,
+        to_term: &LocatedQueryTermSubset,
+    ) -> Result<Vec<(u32, Interned<Self::Condition>)>> {
+        let down_weighted_synonyms = to_term.term_subset.down_weighted_synonyms(ctx)?;
+
+        let mut term = to_term.clone();
+        term.term_subset.remove_down_weighted_synonyms(ctx);
+        let mut edges =
+            vec![(0, conditions_interner.insert(SynonymWeightCondition { term, cost: 0 }))];
+
+        for (cost, synonyms) in down_weighted_synonyms {
+            let mut term = to_term.clone();
+            term.term_subset.keep_only_down_weighted_synonyms(synonyms);
+            edges.push((
+                cost as u32,
+                conditions_interner.insert(SynonymWeightCondition { term, cost }),
+            ));
+        }
+        Ok(edges)
+    }
+
+    #[tracing::instrument(level = "trace", skip_all, target = "search::synonym_weight")]
+    fn rank_to_score(rank: Rank) -> ScoreDetails {
+        ScoreDetails::SynonymWeight(rank)
+    }
+}
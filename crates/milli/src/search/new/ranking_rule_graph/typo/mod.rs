@@ -51,6 +51,7 @@ impl RankingRuleGraphTrait for TypoGraph {
         // 2-gram -> equivalent to 1 typo
         // 3-gram -> equivalent to 2 typos
         let base_cost = if term.term_ids.len() == 1 { 0 } else { term.term_ids.len() as u32 };
+        let down_weighted_synonyms = term.term_subset.down_weighted_synonyms(ctx)?;
 
         for nbr_typos in 0..=term.term_subset.max_typo_cost(ctx) {
             let mut term = term.clone();
@@ -62,6 +63,7 @@ impl RankingRuleGraphTrait for TypoGraph {
                 1 => {
                     term.term_subset.clear_zero_typo_subset();
                     term.term_subset.clear_two_typo_subset();
+                    term.term_subset.remove_down_weighted_synonyms(ctx);
                 }
                 2 => {
                     term.term_subset.clear_zero_typo_subset();
@@ -75,6 +77,16 @@ impl RankingRuleGraphTrait for TypoGraph {
                 conditions_interner.insert(TypoCondition { term, nbr_typos }),
             ));
         }
+
+        // The synonyms with a weight lower than `1.0` are ranked with the typo cost of their weight
+        for (nbr_typos, synonyms) in down_weighted_synonyms {
+            let mut term = term.clone();
+            term.term_subset.keep_only_down_weighted_synonyms(synonyms);
+            edges.push((
+                nbr_typos as u32 + base_cost,
+                conditions_interner.insert(TypoCondition { term, nbr_typos }),
+            ));
+        }
         Ok(edges)
     }
 
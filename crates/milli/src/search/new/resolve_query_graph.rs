@@ -34,6 +34,40 @@ pub fn compute_query_term_subset_docids(
     ctx: &mut SearchContext<'_>,
     universe: Option<&RoaringBitmap>,
     term: &QueryTermSubset,
+) -> Result<RoaringBitmap> {
+    if ctx.fields_tolerance.is_uniform() {
+        return compute_tolerated_query_term_subset_docids(ctx, universe, term);
+    }
+
+    // The derivations tolerated by all the searched fields are searched in all of them at once,
+    // the other ones are only searched in the fields tolerating them.
+    let word = term.original_word(ctx).to_owned();
+    let least_tolerant = ctx.fields_tolerance.least_tolerant(&word);
+    let more_tolerant_fids: Vec<_> = ctx
+        .fields_tolerance
+        .fields()
+        .iter()
+        .filter(|(_, rules)| rules.word_tolerance(&word) != least_tolerant)
+        .map(|(fid, _)| *fid)
+        .collect();
+
+    let mut tolerated = term.clone();
+    tolerated.restrict_tolerance(ctx, least_tolerant)?;
+    let mut docids = compute_tolerated_query_term_subset_docids(ctx, universe, &tolerated)?;
+    for fid in more_tolerant_fids {
+        docids |= compute_query_term_subset_docids_within_field_id(ctx, universe, term, fid)?;
+    }
+
+    match universe {
+        Some(universe) => Ok(docids & universe),
+        None => Ok(docids),
+    }
+}
+
+fn compute_tolerated_query_term_subset_docids(
+    ctx: &mut SearchContext<'_>,
+    universe: Option<&RoaringBitmap>,
+    term: &QueryTermSubset,
 ) -> Result<RoaringBitmap> {
     let mut docids = RoaringBitmap::new();
     // TODO use the MultiOps trait to do large intersections
@@ -64,8 +98,24 @@ pub fn compute_query_term_subset_docids_within_field_id(
     term: &QueryTermSubset,
     fid: u16,
 ) -> Result<RoaringBitmap> {
+    let restricted;
+    let term = if ctx.fields_tolerance.is_uniform() {
+        term
+    } else {
+        let tolerance = ctx.fields_tolerance.field(fid).word_tolerance(term.original_word(ctx));
+        restricted = {
+            let mut term = term.clone();
+            term.restrict_tolerance(ctx, tolerance)?;
+            term
+        };
+        &restricted
+    };
+
     let mut docids = RoaringBitmap::new();
     for word in term.all_single_words_except_prefix_db(ctx)? {
+        if matches!(word, Word::Ngram(_)) && !ctx.ngram_fids.contains_key(&fid) {
+            continue;
+        }
         if let Some(word_fid_docids) = ctx.get_db_word_fid_docids(universe, word.interned(), fid)? {
             docids |= word_fid_docids;
         }
@@ -0,0 +1,111 @@
+//! Spelling suggestions, alternative queries built from the typo derivations of the query words.
+
+use std::cmp::Reverse;
+
+use roaring::RoaringBitmap;
+
+use super::query_term::LocatedQueryTerm;
+use super::{SearchContext, Word};
+use crate::Result;
+
+/// The maximum number of candidates of a search for which suggestions are computed.
+pub const MAX_CANDIDATES_FOR_SUGGESTIONS: u64 = 10;
+/// The maximum number of words that can replace a single word of the query.
+const MAX_ALTERNATIVES_PER_WORD: usize = 5;
+/// The maximum number of partial suggestions kept while the words of the query are combined.
+const MAX_PARTIAL_SUGGESTIONS: usize = 10;
+
+/// Builds up to `limit` alternative queries, in which each word of the query is replaced by itself
+/// or by one of its typo derivations.
+///
+/// The suggestions are ranked by the number of documents of the universe containing all their words,
+/// the suggestions matching no document and the original query are never returned.
+/// The words of the query that match nothing, even with typos, are left out of the suggestions.
+pub fn suggest_queries(
+    ctx: &mut SearchContext<'_>,
+    located_query_terms: &[LocatedQueryTerm],
+    universe: &RoaringBitmap,
+    limit: usize,
+) -> Result<Vec<String>> {
+    let mut original_words = Vec::new();
+    // for each word of the query, the words that can replace it along with their documents
+    let mut alternatives_by_word: Vec<Vec<(String, RoaringBitmap)>> = Vec::new();
+    let mut last_position = None;
+
+    for LocatedQueryTerm { value, positions } in located_query_terms {
+        // only keep one alternative of the advanced query groups, and skip the ngrams
+        if positions.start() != positions.end() || last_position == Some(*positions.start()) {
+            continue;
+        }
+        last_position = Some(*positions.start());
+
+        value.compute_fully_if_needed(ctx)?;
+        let term = ctx.term_interner.get(*value);
+        match term.typo_derivations() {
+            Some(derivations) => {
+                original_words.push(term.original_word(ctx));
+                let mut alternatives = Vec::new();
+                for (word, typos) in derivations {
+                    let docids = ctx.word_docids(Some(universe), Word::Original(word))?;
+                    if let Some(docids) = docids.filter(|docids| !docids.is_empty()) {
+                        alternatives.push((typos, ctx.word_interner.get(word).clone(), docids));
+                    }
+                }
+                alternatives.sort_by_key(|(typos, _, docids)| (Reverse(docids.len()), *typos));
+                alternatives.truncate(MAX_ALTERNATIVES_PER_WORD);
+                if !alternatives.is_empty() {
+                    alternatives_by_word.push(
+                        alternatives.into_iter().map(|(_, word, docids)| (word, docids)).collect(),
+                    );
+                }
+            }
+            None => {
+                let Some(phrase) = term.original_phrase() else { continue };
+                let text = format!("\"{}\"", phrase.description(ctx));
+                original_words.push(text.clone());
+                let docids = ctx.get_phrase_docids(phrase)? & universe;
+                if !docids.is_empty() {
+                    alternatives_by_word.push(vec![(text, docids)]);
+                }
+            }
+        }
+    }
+
+    if alternatives_by_word.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    // each partial suggestion is the index of the alternative chosen for each word
+    let mut partial_suggestions = vec![(Vec::new(), universe.clone())];
+    for alternatives in &alternatives_by_word {
+        let mut next_suggestions = Vec::new();
+        for (choices, docids) in &partial_suggestions {
+            for (index, (_, alternative_docids)) in alternatives.iter().enumerate() {
+                let docids = docids & alternative_docids;
+                if !docids.is_empty() {
+                    let mut choices = choices.clone();
+                    choices.push(index);
+                    next_suggestions.push((choices, docids));
+                }
+            }
+        }
+        // the sort is stable, the alternatives with fewer typos stay first
+        next_suggestions.sort_by_key(|(_, docids)| Reverse(docids.len()));
+        next_suggestions.truncate(MAX_PARTIAL_SUGGESTIONS);
+        partial_suggestions = next_suggestions;
+    }
+
+    let original_query = original_words.join(" ");
+    Ok(partial_suggestions
+        .into_iter()
+        .map(|(choices, _)| {
+            let words = choices
+                .into_iter()
+                .zip(&alternatives_by_word)
+                .map(|(choice, alternatives)| alternatives[choice].0.as_str());
+            words.collect::<Vec<_>>().join(" ")
+        })
+        .filter(|suggestion| *suggestion != original_query)
+        .take(limit)
+        .collect())
+}
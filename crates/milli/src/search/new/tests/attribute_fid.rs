@@ -157,3 +157,67 @@ fn test_attribute_fid_ngrams() {
     let document_ids_scores: Vec<_> = documents_ids.iter().zip(document_scores).collect();
     insta::assert_snapshot!(format!("{document_ids_scores:#?}"));
 }
+
+#[test]
+fn test_attribute_fid_weights() {
+    let index = TempIndex::new();
+    index
+        .update_settings(|s| {
+            s.set_primary_key("id".to_owned());
+            s.set_searchable_fields(vec![
+                "title".to_owned(),
+                "description".to_owned(),
+                "plot".to_owned(),
+            ]);
+            s.set_criteria(vec![Criterion::Attribute]);
+        })
+        .unwrap();
+    index
+        .add_documents(documents!([
+            { "id": 0, "title": "", "description": "", "plot": "fox" },
+            { "id": 1, "title": "", "description": "fox", "plot": "" },
+            { "id": 2, "title": "fox", "description": "", "plot": "" },
+        ]))
+        .unwrap();
+
+    let search = |attributes: Option<&[String]>| {
+        let txn = index.read_txn().unwrap();
+        let mut s = Search::new(&txn, &index);
+        s.query("fox");
+        if let Some(attributes) = attributes {
+            s.searchable_attributes(attributes);
+        }
+        s.execute().unwrap().documents_ids
+    };
+
+    // the weights are deduced from the order of the searchable attributes
+    assert_eq!(search(None), vec![2, 1, 0]);
+
+    index
+        .update_settings(|s| {
+            s.set_searchable_attributes_weights(
+                [("plot".to_owned(), 3), ("description".to_owned(), 2)].into(),
+            );
+        })
+        .unwrap();
+    assert_eq!(search(None), vec![0, 1, 2]);
+
+    // attributes with the same weight are equally important
+    index
+        .update_settings(|s| {
+            s.set_searchable_attributes_weights(
+                [("plot".to_owned(), 2), ("title".to_owned(), 2)].into(),
+            );
+        })
+        .unwrap();
+    assert_eq!(search(None), vec![0, 2, 1]);
+
+    // the boosts of the query replace the weights of the settings
+    let attributes = ["title".to_owned(), "description^2".to_owned()];
+    assert_eq!(search(Some(&attributes)), vec![1, 2]);
+    let attributes = ["title".to_owned(), "description".to_owned()];
+    assert_eq!(search(Some(&attributes)), vec![2, 1]);
+
+    index.update_settings(|s| s.reset_searchable_attributes_weights()).unwrap();
+    assert_eq!(search(None), vec![2, 1, 0]);
+}
@@ -16,11 +16,16 @@ This module tests the following properties:
 12. Prefix tolerance costs nothing according to the typo ranking rule
 13. Split words cost 1 typo according to the typo ranking rule
 14. Synonyms cost nothing according to the typo ranking rule
+15. The typo tolerance and prefix search can be overridden by attribute
+16. The words of the n-gram attributes containing a query word cost 1 typo
 */
 
 use std::collections::BTreeMap;
 
+use crate::attributes_typo_tolerance::AttributeTypoTolerance;
 use crate::index::tests::TempIndex;
+use crate::index::PrefixSearch;
+use crate::ngram_attributes::{NgramAttribute, NgramMode, DEFAULT_NGRAM_MAX_MATCHES};
 use crate::search::new::tests::collect_field_values;
 use crate::{Criterion, Search, SearchResult, TermsMatchingStrategy};
 
@@ -635,3 +640,177 @@ fn test_typo_synonyms() {
     ]
     "###);
 }
+
+#[test]
+fn test_typo_tolerance_per_attribute() {
+    let index = TempIndex::new();
+    index
+        .update_settings(|s| {
+            s.set_primary_key("id".to_owned());
+            s.set_searchable_fields(vec!["name".to_owned(), "sku".to_owned()]);
+        })
+        .unwrap();
+    index
+        .add_documents(documents!([
+            { "id": 0, "name": "house", "sku": "ab12" },
+            { "id": 1, "name": "garden", "sku": "house" },
+            { "id": 2, "name": "garage", "sku": "hou" },
+            { "id": 3, "name": "houses", "sku": "cd34" },
+            { "id": 4, "name": "toy", "sku": "houses" },
+        ]))
+        .unwrap();
+
+    let search = |query: &str| {
+        let txn = index.read_txn().unwrap();
+        let mut s = Search::new(&txn, &index);
+        s.terms_matching_strategy(TermsMatchingStrategy::All);
+        s.query(query);
+        let mut documents_ids = s.execute().unwrap().documents_ids;
+        documents_ids.sort_unstable();
+        documents_ids
+    };
+
+    assert_eq!(search("housr"), vec![0, 1, 3, 4]);
+    assert_eq!(search("hose"), Vec::<u32>::new());
+    assert_eq!(search("hou"), vec![0, 1, 2, 3, 4]);
+
+    index
+        .update_settings(|s| {
+            s.set_attributes_typo_tolerance(BTreeMap::from([
+                (
+                    "name".to_owned(),
+                    AttributeTypoTolerance { min_word_len_one_typo: Some(3), ..Default::default() },
+                ),
+                (
+                    "sku".to_owned(),
+                    AttributeTypoTolerance {
+                        enabled: Some(false),
+                        prefix_search: Some(PrefixSearch::Disabled),
+                        ..Default::default()
+                    },
+                ),
+            ]));
+        })
+        .unwrap();
+
+    // no typos and no prefixes on the sku
+    assert_eq!(search("housr"), vec![0, 3]);
+    assert_eq!(search("hou"), vec![0, 2, 3]);
+    assert_eq!(search("house"), vec![0, 1, 3]);
+    // typos from 3 letters on the name
+    assert_eq!(search("hose"), vec![0, 3]);
+
+    index
+        .update_settings(|s| {
+            s.set_attributes_typo_tolerance(BTreeMap::from([(
+                "name".to_owned(),
+                AttributeTypoTolerance {
+                    prefix_search: Some(PrefixSearch::Disabled),
+                    ..Default::default()
+                },
+            )]));
+        })
+        .unwrap();
+
+    // only the typos of the whole word are tolerated when prefixes are not
+    assert_eq!(search("housx"), vec![0, 1, 4]);
+
+    index.update_settings(|s| s.reset_attributes_typo_tolerance()).unwrap();
+    assert_eq!(search("housr"), vec![0, 1, 3, 4]);
+    assert_eq!(search("hose"), Vec::<u32>::new());
+}
+
+#[test]
+fn test_ngram_attributes() {
+    let index = TempIndex::new();
+    index
+        .update_settings(|s| {
+            s.set_primary_key("id".to_owned());
+            s.set_searchable_fields(vec!["name".to_owned(), "sku".to_owned()]);
+        })
+        .unwrap();
+    index
+        .add_documents(documents!([
+            { "id": 0, "name": "adapter", "sku": "AB-94521-X" },
+            { "id": 1, "name": "4521 adapter", "sku": "CD-12" },
+            { "id": 2, "name": "94521 clone", "sku": "EF-34" },
+        ]))
+        .unwrap();
+
+    let search = |query: &str| {
+        let txn = index.read_txn().unwrap();
+        let mut s = Search::new(&txn, &index);
+        s.terms_matching_strategy(TermsMatchingStrategy::All);
+        s.query(query);
+        s.execute().unwrap().documents_ids
+    };
+
+    assert_eq!(search("4521"), vec![1]);
+
+    index
+        .update_settings(|s| {
+            s.set_ngram_attributes(BTreeMap::from([(
+                "sku".to_owned(),
+                NgramAttribute {
+                    mode: NgramMode::Ngram,
+                    min_length: 3,
+                    max_matches: DEFAULT_NGRAM_MAX_MATCHES,
+                },
+            )]));
+        })
+        .unwrap();
+
+    // the full word matches are ranked first and the n-grams are only searched in the sku
+    assert_eq!(search("4521"), vec![1, 0]);
+    assert_eq!(search("452 adapter"), vec![0]);
+    assert_eq!(search("45 adapter"), Vec::<u32>::new());
+
+    // the n-grams of the new documents are indexed too
+    index.add_documents(documents!([{ "id": 3, "name": "cable", "sku": "GH-145210" }])).unwrap();
+    assert_eq!(search("4521"), vec![1, 0, 3]);
+
+    // a word already in the document is split as soon as it appears in the sku
+    assert_eq!(search("lon"), Vec::<u32>::new());
+    index
+        .add_documents(documents!([{ "id": 2, "name": "94521 clone", "sku": "clone-34" }]))
+        .unwrap();
+    assert_eq!(search("lon"), vec![2]);
+
+    // the n-grams of the deleted words are removed
+    index.delete_document("3");
+    assert_eq!(search("4521"), vec![1, 0]);
+    let txn = index.read_txn().unwrap();
+    let ngrams_fst = index.ngrams_fst(&txn).unwrap();
+    assert!(!ngrams_fst.stream().into_strs().unwrap().iter().any(|key| key.ends_with("145210")));
+    drop(txn);
+    index.add_documents(documents!([{ "id": 3, "name": "cable", "sku": "GH-145210" }])).unwrap();
+
+    index
+        .update_settings(|s| {
+            s.set_ngram_attributes(BTreeMap::from([(
+                "sku".to_owned(),
+                NgramAttribute {
+                    mode: NgramMode::EdgeNgram,
+                    min_length: 3,
+                    max_matches: DEFAULT_NGRAM_MAX_MATCHES,
+                },
+            )]));
+        })
+        .unwrap();
+    assert_eq!(search("4521"), vec![1]);
+    assert_eq!(search("1452 cable"), vec![3]);
+
+    // the words matched by a part of a word are truncated to the `maxMatches` of the attribute
+    index
+        .update_settings(|s| {
+            s.set_ngram_attributes(BTreeMap::from([(
+                "sku".to_owned(),
+                NgramAttribute { mode: NgramMode::Ngram, min_length: 3, max_matches: 1 },
+            )]));
+        })
+        .unwrap();
+    assert_eq!(search("4521"), vec![1, 3]);
+
+    index.update_settings(|s| s.reset_ngram_attributes()).unwrap();
+    assert_eq!(search("4521"), vec![1]);
+}
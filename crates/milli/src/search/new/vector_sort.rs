@@ -1,4 +1,5 @@
 use std::iter::FromIterator;
+use std::num::NonZeroUsize;
 use std::time::Instant;
 
 use roaring::RoaringBitmap;
@@ -9,18 +10,38 @@ use crate::score_details::{self, ScoreDetails};
 use crate::vector::{ArroyWrapper, DistributionShift, Embedder};
 use crate::{DocumentId, Result, SearchContext, SearchLogger};
 
+/// Above this number of candidates, the vector store is searched using its trees rather than by
+/// comparing the target to the vector of every candidate.
+pub const EXHAUSTIVE_SEARCH_MAX_CANDIDATES: u64 = 10_000;
+
+/// Per-query parameters trading the latency of the vector search for its recall.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct VectorSearchParams {
+    /// Whether to compare the target to the vector of every candidate instead of searching the trees.
+    ///
+    /// Defaults to exhaustive when there are at most [`EXHAUSTIVE_SEARCH_MAX_CANDIDATES`] candidates.
+    /// The binary quantized embedders are always searched using the trees.
+    pub exhaustive: Option<bool>,
+    /// The number of tree nodes to inspect when searching the trees.
+    pub search_k: Option<NonZeroUsize>,
+}
+
 pub struct VectorSort<Q: RankingRuleQueryTrait> {
     query: Option<Q>,
     target: Vec<f32>,
     vector_candidates: RoaringBitmap,
-    cached_sorted_docids: std::vec::IntoIter<(DocumentId, f32)>,
+    cached_sorted_docids: std::vec::IntoIter<(DocumentId, f32, usize)>,
     limit: usize,
     distribution_shift: Option<DistributionShift>,
     embedder_index: u8,
     quantized: bool,
+    /// Whether the documents are split into chunks, whose index is then reported in the score details
+    chunked: bool,
+    params: VectorSearchParams,
 }
 
 impl<Q: RankingRuleQueryTrait> VectorSort<Q> {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         ctx: &SearchContext<'_>,
         target: Vec<f32>,
@@ -29,12 +50,19 @@ impl<Q: RankingRuleQueryTrait> VectorSort<Q> {
         embedder_name: &str,
         embedder: &Embedder,
         quantized: bool,
+        params: VectorSearchParams,
     ) -> Result<Self> {
         let embedder_index = ctx
             .index
             .embedder_category_id
             .get(ctx.txn, embedder_name)?
             .ok_or_else(|| crate::UserError::InvalidSearchEmbedder(embedder_name.to_owned()))?;
+        let chunked = ctx
+            .index
+            .embedding_configs(ctx.txn)?
+            .into_iter()
+            .find(|config| config.name == embedder_name)
+            .is_some_and(|config| config.config.prompt.chunking.is_some());
 
         Ok(Self {
             query: None,
@@ -45,6 +73,8 @@ impl<Q: RankingRuleQueryTrait> VectorSort<Q> {
             distribution_shift: embedder.distribution(),
             embedder_index,
             quantized,
+            chunked,
+            params,
         })
     }
 
@@ -57,7 +87,25 @@ impl<Q: RankingRuleQueryTrait> VectorSort<Q> {
 
         let before = Instant::now();
         let reader = ArroyWrapper::new(ctx.index.vector_arroy, self.embedder_index, self.quantized);
-        let results = reader.nns_by_vector(ctx.txn, target, self.limit, Some(vector_candidates))?;
+        // the trees are built for the whole index, so a search restricted to a few candidates
+        // can miss most of them, while comparing a few vectors directly is cheap.
+        // the distances of the binary quantized vectors can only be computed by arroy.
+        let exhaustive = !self.quantized
+            && self
+                .params
+                .exhaustive
+                .unwrap_or(vector_candidates.len() <= EXHAUSTIVE_SEARCH_MAX_CANDIDATES);
+        let results = if exhaustive {
+            reader.nns_by_vector_exhaustive(ctx.txn, target, vector_candidates)?
+        } else {
+            reader.nns_by_vector(
+                ctx.txn,
+                target,
+                self.limit,
+                self.params.search_k,
+                Some(vector_candidates),
+            )?
+        };
         self.cached_sorted_docids = results.into_iter();
         *ctx.vector_store_stats.get_or_insert_default() += VectorStoreStats {
             total_time: before.elapsed(),
@@ -105,11 +153,15 @@ impl<'ctx, Q: RankingRuleQueryTrait> RankingRule<'ctx, Q> for VectorSort<Q> {
             return Ok(Some(RankingRuleOutput {
                 query,
                 candidates: universe.clone(),
-                score: ScoreDetails::Vector(score_details::Vector { similarity: None }),
+                score: ScoreDetails::Vector(score_details::Vector {
+                    similarity: None,
+                    chunk: None,
+                }),
             }));
         }
 
-        for (docid, distance) in self.cached_sorted_docids.by_ref() {
+        // the results are sorted by distance, so the first occurrence of a document is its best chunk
+        for (docid, distance, chunk) in self.cached_sorted_docids.by_ref() {
             if vector_candidates.contains(docid) {
                 let score = 1.0 - distance;
                 let score = self
@@ -119,7 +171,10 @@ impl<'ctx, Q: RankingRuleQueryTrait> RankingRule<'ctx, Q> for VectorSort<Q> {
                 return Ok(Some(RankingRuleOutput {
                     query,
                     candidates: RoaringBitmap::from_iter([docid]),
-                    score: ScoreDetails::Vector(score_details::Vector { similarity: Some(score) }),
+                    score: ScoreDetails::Vector(score_details::Vector {
+                        similarity: Some(score),
+                        chunk: self.chunked.then_some(chunk),
+                    }),
                 }));
             }
         }
@@ -135,7 +190,10 @@ impl<'ctx, Q: RankingRuleQueryTrait> RankingRule<'ctx, Q> for VectorSort<Q> {
             return Ok(Some(RankingRuleOutput {
                 query,
                 candidates: universe.clone(),
-                score: ScoreDetails::Vector(score_details::Vector { similarity: None }),
+                score: ScoreDetails::Vector(score_details::Vector {
+                    similarity: None,
+                    chunk: None,
+                }),
             }));
         }
 
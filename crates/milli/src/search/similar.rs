@@ -101,8 +101,10 @@ impl<'a> Similar<'a> {
                 .map(|distribution| distribution.shift(score))
                 .unwrap_or(score);
 
-            let score_details =
-                vec![ScoreDetails::Vector(score_details::Vector { similarity: Some(score) })];
+            let score_details = vec![ScoreDetails::Vector(score_details::Vector {
+                similarity: Some(score),
+                chunk: None,
+            })];
 
             let score = ScoreDetails::global_score(score_details.iter());
 
@@ -127,6 +129,7 @@ impl<'a> Similar<'a> {
             document_scores,
             degraded: false,
             used_negative_operator: false,
+            suggestions: Vec::new(),
         })
     }
 }
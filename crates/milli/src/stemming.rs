@@ -0,0 +1,218 @@
+use std::borrow::Cow;
+use std::collections::HashMap;
+
+use charabia::Language;
+use heed::types::{Bytes, DecodeIgnore, SerdeJson, Str};
+use heed::{RoTxn, RwTxn};
+use rust_stemmers::{Algorithm, Stemmer};
+
+use crate::attribute_patterns::PatternMatch;
+use crate::index::main_key;
+use crate::{FieldId, Index, Result};
+
+/// The languages that can be stemmed, with the code prefixing their entries in the stems FST.
+const STEMMING_LANGUAGES: &[(Language, Algorithm, &str)] = &[
+    (Language::Ara, Algorithm::Arabic, "ara"),
+    (Language::Dan, Algorithm::Danish, "dan"),
+    (Language::Deu, Algorithm::German, "deu"),
+    (Language::Ell, Algorithm::Greek, "ell"),
+    (Language::Eng, Algorithm::English, "eng"),
+    (Language::Fin, Algorithm::Finnish, "fin"),
+    (Language::Fra, Algorithm::French, "fra"),
+    (Language::Hun, Algorithm::Hungarian, "hun"),
+    (Language::Ita, Algorithm::Italian, "ita"),
+    (Language::Nld, Algorithm::Dutch, "nld"),
+    (Language::Nob, Algorithm::Norwegian, "nob"),
+    (Language::Por, Algorithm::Portuguese, "por"),
+    (Language::Ron, Algorithm::Romanian, "ron"),
+    (Language::Rus, Algorithm::Russian, "rus"),
+    (Language::Spa, Algorithm::Spanish, "spa"),
+    (Language::Swe, Algorithm::Swedish, "swe"),
+    (Language::Tam, Algorithm::Tamil, "tam"),
+    (Language::Tur, Algorithm::Turkish, "tur"),
+];
+
+/// The separator between the language code, the stem and the word in the keys of the stems FST.
+const STEMS_FST_SEPARATOR: u8 = 0;
+
+fn stemming_language(language: Language) -> Option<(Algorithm, &'static str)> {
+    STEMMING_LANGUAGES
+        .iter()
+        .find(|(stemmed, _, _)| *stemmed == language)
+        .map(|(_, algorithm, code)| (*algorithm, *code))
+}
+
+/// Returns `true` if the words of the language can be stemmed.
+pub fn is_stemming_supported(language: Language) -> bool {
+    stemming_language(language).is_some()
+}
+
+/// The codes of the languages that can be stemmed.
+pub fn supported_stemming_languages() -> impl Iterator<Item = &'static str> {
+    STEMMING_LANGUAGES.iter().map(|(_, _, code)| *code)
+}
+
+/// Returns the stem of the word in the language, if it can be stemmed and differs from the word.
+pub fn stem(language: Language, word: &str) -> Option<String> {
+    let (algorithm, _) = stemming_language(language)?;
+    let stem = Stemmer::create(algorithm).stem(word);
+    (stem != word).then(|| stem.into_owned())
+}
+
+/// Returns the prefix shared by the keys of all the words with this stem in the stems FST.
+pub fn stems_fst_prefix(language: Language, stem: &str) -> Option<String> {
+    let (_, code) = stemming_language(language)?;
+    let separator = STEMS_FST_SEPARATOR as char;
+    Some(format!("{code}{separator}{stem}{separator}"))
+}
+
+/// The stemmers of the languages enabled with the `stemming` setting.
+pub struct Stemmers {
+    stemmers: Vec<(Language, &'static str, Stemmer)>,
+    /// The languages of the fields matching a rule of the `localizedAttributes` setting, the words
+    /// of the other fields are stemmed in all the enabled languages.
+    fields_languages: HashMap<FieldId, Vec<Language>>,
+}
+
+impl Stemmers {
+    pub fn new(index: &Index, rtxn: &RoTxn<'_>) -> Result<Self> {
+        let languages = index.stemming(rtxn)?.unwrap_or_default();
+        let stemmers = languages
+            .iter()
+            .filter_map(|language| {
+                let (algorithm, code) = stemming_language(*language)?;
+                Some((*language, code, Stemmer::create(algorithm)))
+            })
+            .collect();
+
+        let mut fields_languages = HashMap::new();
+        if let Some(rules) = index.localized_attributes_rules(rtxn)? {
+            let fields_ids_map = index.fields_ids_map(rtxn)?;
+            for (fid, name) in fields_ids_map.iter() {
+                let rule = rules.iter().find(|rule| rule.match_str(name) == PatternMatch::Match);
+                if let Some(rule) = rule.filter(|rule| !rule.locales.is_empty()) {
+                    fields_languages.insert(fid, rule.locales.clone());
+                }
+            }
+        }
+
+        Ok(Self { stemmers, fields_languages })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.stemmers.is_empty()
+    }
+
+    /// Whether the words are stemmed in the languages of the fields they belong to.
+    pub fn is_localized(&self) -> bool {
+        !self.fields_languages.is_empty()
+    }
+
+    /// Returns the keys of the stems FST for a word of the index.
+    ///
+    /// There is one key by language in which the word is not its own stem, words that are already
+    /// stems are found through the words FST. The languages are the ones of the fields the word
+    /// belongs to in `txn`, all the enabled languages when one of them has no localized attributes.
+    pub fn stems_fst_keys(
+        &self,
+        index: &Index,
+        txn: &RoTxn<'_>,
+        word: &str,
+    ) -> Result<Vec<Vec<u8>>> {
+        let mut languages = None;
+        if self.is_localized() {
+            let mut word_languages = Vec::new();
+            for fid in word_fids(index, txn, word)? {
+                match self.fields_languages.get(&fid) {
+                    Some(field_languages) => word_languages.extend_from_slice(field_languages),
+                    None => {
+                        word_languages.clear();
+                        break;
+                    }
+                }
+            }
+            if !word_languages.is_empty() {
+                languages = Some(word_languages);
+            }
+        }
+
+        let stemmers = self.stemmers.iter().filter(|(language, _, _)| {
+            languages.as_ref().is_none_or(|languages| languages.contains(language))
+        });
+        Ok(stemmers
+            .filter_map(|(_, code, stemmer)| {
+                let stem = stemmer.stem(word);
+                if stem == word {
+                    return None;
+                }
+                let mut key = Vec::with_capacity(code.len() + stem.len() + word.len() + 2);
+                key.extend_from_slice(code.as_bytes());
+                key.push(STEMS_FST_SEPARATOR);
+                key.extend_from_slice(stem.as_bytes());
+                key.push(STEMS_FST_SEPARATOR);
+                key.extend_from_slice(word.as_bytes());
+                Some(key)
+            })
+            .collect())
+    }
+}
+
+/// Returns the fields the word belongs to, according to the word fid docids database.
+fn word_fids(index: &Index, txn: &RoTxn<'_>, word: &str) -> Result<Vec<FieldId>> {
+    let mut prefix = word.as_bytes().to_vec();
+    prefix.push(0);
+    let database = index.word_fid_docids.remap_types::<Bytes, DecodeIgnore>();
+    let mut fids = Vec::new();
+    for result in database.prefix_iter(txn, &prefix)? {
+        let (key, ()) = result?;
+        if let Some(fid) = key[prefix.len()..].first_chunk::<2>() {
+            fids.push(FieldId::from_be_bytes(*fid));
+        }
+    }
+    Ok(fids)
+}
+
+impl Index {
+    /// The languages in which the words are stemmed, at indexing and search time.
+    pub fn stemming(&self, txn: &RoTxn<'_>) -> heed::Result<Option<Vec<Language>>> {
+        self.main.remap_types::<Str, SerdeJson<Vec<Language>>>().get(txn, main_key::STEMMING)
+    }
+
+    pub(crate) fn put_stemming(
+        &self,
+        txn: &mut RwTxn<'_>,
+        languages: &[Language],
+    ) -> heed::Result<()> {
+        self.main.remap_types::<Str, SerdeJson<&[Language]>>().put(
+            txn,
+            main_key::STEMMING,
+            &languages,
+        )
+    }
+
+    pub(crate) fn delete_stemming(&self, txn: &mut RwTxn<'_>) -> heed::Result<bool> {
+        self.main.remap_key_type::<Str>().delete(txn, main_key::STEMMING)
+    }
+
+    pub(crate) fn put_stems_fst<A: AsRef<[u8]>>(
+        &self,
+        txn: &mut RwTxn<'_>,
+        fst: &fst::Set<A>,
+    ) -> heed::Result<()> {
+        self.main.remap_types::<Str, Bytes>().put(
+            txn,
+            main_key::STEMS_FST_KEY,
+            fst.as_fst().as_bytes(),
+        )
+    }
+
+    /// Returns the FST mapping the stems of the indexed words to these words.
+    ///
+    /// Its keys are made of the code of the language, the stem and the word, separated by a `\0`.
+    pub fn stems_fst<'t>(&self, rtxn: &'t RoTxn<'_>) -> Result<fst::Set<Cow<'t, [u8]>>> {
+        match self.main.remap_types::<Str, Bytes>().get(rtxn, main_key::STEMS_FST_KEY)? {
+            Some(bytes) => Ok(fst::Set::new(bytes)?.map_data(Cow::Borrowed)?),
+            None => Ok(fst::Set::default().map_data(Cow::Owned)?),
+        }
+    }
+}
@@ -0,0 +1,207 @@
+use std::collections::HashMap;
+use std::convert::Infallible;
+
+use deserr::{DeserializeError, Deserr, ErrorKind, ValuePointerRef};
+use heed::types::{SerdeBincode, SerdeJson, Str};
+use heed::{RoTxn, RwTxn};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::index::main_key;
+use crate::Index;
+
+/// The synonyms of each normalized sequence of words, with their weight.
+pub type WeightedSynonyms = HashMap<Vec<String>, Vec<(Vec<String>, f32)>>;
+
+/// A rule of the `synonymRules` setting.
+///
+/// With an `input`, the rule is one-way: the input is expanded into the synonyms but the synonyms
+/// are not expanded into the input. Without an `input`, the synonyms form a group of words that
+/// are all equivalent to each other.
+#[derive(Serialize, Deserialize, PartialEq, Eq, Clone, Debug, Deserr, ToSchema)]
+#[serde(deny_unknown_fields, rename_all = "camelCase")]
+#[deserr(rename_all = camelCase, deny_unknown_fields)]
+pub struct SynonymRule {
+    /// The words expanded into the synonyms, for a one-way rule.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[deserr(default)]
+    #[schema(value_type = Option<String>, example = json!("iphone"))]
+    pub input: Option<String>,
+    /// The synonyms of the input, or the words of the equivalence group.
+    #[schema(value_type = Vec<String>, example = json!(["apple phone"]))]
+    pub synonyms: Vec<String>,
+    /// The weight of the synonyms, the lower the weight, the lower the documents matching the
+    /// synonyms are ranked below the documents matching the original words.
+    #[serde(default)]
+    #[deserr(default)]
+    #[schema(value_type = f32, example = json!(0.8))]
+    pub weight: SynonymWeight,
+}
+
+impl SynonymRule {
+    /// Returns an error message if the rule cannot expand any word.
+    pub fn validate(&self) -> Result<(), String> {
+        match &self.input {
+            Some(input) if input.trim().is_empty() => Err("has an empty `input`".to_string()),
+            Some(_) if self.synonyms.is_empty() => Err("has no `synonyms`".to_string()),
+            None if self.synonyms.len() < 2 => Err(
+                "has no `input` and less than two `synonyms`, an equivalence group needs at least two words"
+                    .to_string(),
+            ),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// The weight of the synonyms of a rule, greater than `0.0` and lower or equal to `1.0`.
+#[derive(Serialize, Deserialize, PartialEq, Clone, Copy, Debug)]
+#[serde(transparent)]
+pub struct SynonymWeight(f32);
+
+/// The weight is never `NaN` as it is checked when deserialized.
+impl Eq for SynonymWeight {}
+
+impl SynonymWeight {
+    pub fn get(&self) -> f32 {
+        self.0
+    }
+}
+
+impl Default for SynonymWeight {
+    fn default() -> Self {
+        Self(1.0)
+    }
+}
+
+impl<E: DeserializeError> Deserr<E> for SynonymWeight {
+    fn deserialize_from_value<V: deserr::IntoValue>(
+        value: deserr::Value<V>,
+        location: ValuePointerRef,
+    ) -> Result<Self, E> {
+        let weight = f32::deserialize_from_value(value, location)?;
+        if weight > 0.0 && weight <= 1.0 {
+            Ok(Self(weight))
+        } else {
+            Err(deserr::take_cf_content(E::error::<Infallible>(
+                None,
+                ErrorKind::Unexpected {
+                    msg: format!("the `weight` of a synonym rule must be greater than `0.0` and lower or equal to `1.0`, but found `{weight}`"),
+                },
+                location,
+            )))
+        }
+    }
+}
+
+/// Expands the rules into the synonyms of each sequence of words normalized by `normalize`.
+///
+/// When a synonym is produced by several rules, its greatest weight is kept.
+pub(crate) fn expand_synonym_rules(
+    rules: &[SynonymRule],
+    normalize: impl Fn(&str) -> Vec<String>,
+) -> WeightedSynonyms {
+    let mut expanded = WeightedSynonyms::new();
+    for SynonymRule { input, synonyms, weight } in rules {
+        let synonyms: Vec<_> = synonyms
+            .iter()
+            .map(|synonym| normalize(synonym))
+            .filter(|synonym| !synonym.is_empty())
+            .collect();
+        let inputs = match input {
+            Some(input) => vec![normalize(input)],
+            None => synonyms.clone(),
+        };
+
+        for input in inputs.into_iter().filter(|input| !input.is_empty()) {
+            let entry = expanded.entry(input).or_default();
+            entry.extend(synonyms.iter().cloned().map(|synonym| (synonym, weight.get())));
+        }
+    }
+
+    for (input, synonyms) in expanded.iter_mut() {
+        synonyms.retain(|(synonym, _)| synonym != input);
+        synonyms.sort_unstable_by(|(left, left_weight), (right, right_weight)| {
+            left.cmp(right).then(right_weight.total_cmp(left_weight))
+        });
+        synonyms.dedup_by(|(left, _), (right, _)| left == right);
+    }
+    expanded.retain(|_, synonyms| !synonyms.is_empty());
+
+    expanded
+}
+
+impl Index {
+    /// The rules of the `synonymRules` setting, as defined by the user.
+    pub fn synonym_rules(&self, rtxn: &RoTxn<'_>) -> heed::Result<Vec<SynonymRule>> {
+        Ok(self
+            .main
+            .remap_types::<Str, SerdeJson<Vec<SynonymRule>>>()
+            .get(rtxn, main_key::USER_DEFINED_SYNONYM_RULES_KEY)?
+            .unwrap_or_default())
+    }
+
+    /// The normalized synonyms expanded from the synonym rules.
+    pub fn weighted_synonyms(&self, rtxn: &RoTxn<'_>) -> heed::Result<WeightedSynonyms> {
+        Ok(self
+            .main
+            .remap_types::<Str, SerdeBincode<WeightedSynonyms>>()
+            .get(rtxn, main_key::WEIGHTED_SYNONYMS_KEY)?
+            .unwrap_or_default())
+    }
+
+    pub(crate) fn put_synonym_rules(
+        &self,
+        wtxn: &mut RwTxn<'_>,
+        weighted_synonyms: &WeightedSynonyms,
+        synonym_rules: &[SynonymRule],
+    ) -> heed::Result<()> {
+        self.main.remap_types::<Str, SerdeBincode<WeightedSynonyms>>().put(
+            wtxn,
+            main_key::WEIGHTED_SYNONYMS_KEY,
+            weighted_synonyms,
+        )?;
+        self.main.remap_types::<Str, SerdeJson<&[SynonymRule]>>().put(
+            wtxn,
+            main_key::USER_DEFINED_SYNONYM_RULES_KEY,
+            &synonym_rules,
+        )
+    }
+
+    pub(crate) fn delete_synonym_rules(&self, wtxn: &mut RwTxn<'_>) -> heed::Result<bool> {
+        self.main.remap_key_type::<Str>().delete(wtxn, main_key::WEIGHTED_SYNONYMS_KEY)?;
+        self.main.remap_key_type::<Str>().delete(wtxn, main_key::USER_DEFINED_SYNONYM_RULES_KEY)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn normalize(text: &str) -> Vec<String> {
+        text.split_whitespace().map(str::to_lowercase).collect()
+    }
+
+    fn rule(input: Option<&str>, synonyms: &[&str], weight: f32) -> SynonymRule {
+        SynonymRule {
+            input: input.map(ToString::to_string),
+            synonyms: synonyms.iter().map(ToString::to_string).collect(),
+            weight: SynonymWeight(weight),
+        }
+    }
+
+    #[test]
+    fn expand_one_way_and_equivalence_rules() {
+        let rules = [
+            rule(Some("iPhone"), &["Apple phone"], 1.0),
+            rule(None, &["couch", "sofa", "settee"], 0.5),
+            rule(Some("couch"), &["sofa"], 0.8),
+        ];
+        let expanded = expand_synonym_rules(&rules, normalize);
+
+        let words = |text: &str| normalize(text);
+        assert_eq!(expanded[&words("iphone")], vec![(words("apple phone"), 1.0)]);
+        assert!(!expanded.contains_key(&words("apple phone")));
+        assert_eq!(expanded[&words("couch")], vec![(words("settee"), 0.5), (words("sofa"), 0.8)]);
+        assert_eq!(expanded[&words("sofa")], vec![(words("couch"), 0.5), (words("settee"), 0.5)]);
+    }
+}
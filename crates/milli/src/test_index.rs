@@ -64,7 +64,7 @@ impl TempIndex {
     ) -> Result<(), crate::error::Error> {
         let local_pool;
         let indexer_config = &self.indexer_config;
-        let pool = match &indexer_config.thread_pool {
+        let pool = match indexer_config.thread_pool.as_deref() {
             Some(pool) => pool,
             None => {
                 local_pool = ThreadPoolNoAbortBuilder::new().build().unwrap();
@@ -155,7 +155,7 @@ impl TempIndex {
     ) -> Result<(), crate::error::Error> {
         let local_pool;
         let indexer_config = &self.indexer_config;
-        let pool = match &indexer_config.thread_pool {
+        let pool = match indexer_config.thread_pool.as_deref() {
             Some(pool) => pool,
             None => {
                 local_pool = ThreadPoolNoAbortBuilder::new().build().unwrap();
@@ -233,7 +233,7 @@ fn aborting_indexation() {
 
     let local_pool;
     let indexer_config = &index.indexer_config;
-    let pool = match &indexer_config.thread_pool {
+    let pool = match indexer_config.thread_pool.as_deref() {
         Some(pool) => pool,
         None => {
             local_pool = ThreadPoolNoAbortBuilder::new().build().unwrap();
@@ -1118,6 +1118,7 @@ fn bug_3021_fourth() {
         mut documents_ids,
         degraded: _,
         used_negative_operator: _,
+        suggestions: _,
     } = search.execute().unwrap();
     let primary_key_id = index.fields_ids_map(&rtxn).unwrap().id("primary_key").unwrap();
     documents_ids.sort_unstable();
@@ -22,6 +22,7 @@ use crate::update::del_add::{DelAdd, KvReaderDelAdd, KvWriterDelAdd};
 use crate::update::settings::InnerIndexSettingsDiff;
 use crate::vector::error::{EmbedErrorKind, PossibleEmbeddingMistakes, UnusedVectorsDistribution};
 use crate::vector::parsed_vectors::{ParsedVectorsDiff, VectorState};
+use crate::vector::persistent_cache::EmbedderCache;
 use crate::vector::settings::ReindexAction;
 use crate::vector::{Embedder, Embedding};
 use crate::{try_split_array_at, DocumentId, FieldId, Result, ThreadPoolNoAbort};
@@ -40,6 +41,8 @@ pub struct ExtractedVectorPoints {
     // embedder
     pub embedder_name: String,
     pub embedder: Arc<Embedder>,
+    pub document_cache: Option<EmbedderCache>,
+    pub prompt: Arc<Prompt>,
     pub add_to_user_provided: RoaringBitmap,
     pub remove_from_user_provided: RoaringBitmap,
 }
@@ -168,7 +171,10 @@ impl ManualEmbedderErrors {
                     );
                 }
 
-                Err(crate::Error::UserError(crate::UserError::DocumentEmbeddingError(msg)))
+                Err(crate::Error::UserError(crate::UserError::DocumentEmbeddingError {
+                    message: msg,
+                    transient: false,
+                }))
             }
             None => Ok(()),
         }
@@ -444,7 +450,7 @@ pub fn extract_vector_points<R: io::Read + io::Seek>(
     for EmbedderVectorExtractor {
         embedder_name,
         embedder,
-        prompt: _,
+        prompt,
         prompts_writer,
         remove_vectors_writer,
         action,
@@ -462,11 +468,15 @@ pub fn extract_vector_points<R: io::Read + io::Seek>(
                 Default::default()
             };
 
+        let document_cache =
+            settings_diff.new.embedding_configs.document_cache(&embedder_name).cloned();
         results.push(ExtractedVectorPoints {
             manual_vectors: writer_into_reader(manual_vectors_writer)?,
             remove_vectors: writer_into_reader(remove_vectors_writer)?,
             prompts: writer_into_reader(prompts_writer)?,
             embedder,
+            document_cache,
+            prompt,
             embedder_name,
             add_to_user_provided,
             remove_from_user_provided,
@@ -674,12 +684,15 @@ fn compare_vectors(a: &[f32], b: &[f32]) -> Ordering {
     a.iter().copied().map(OrderedFloat).cmp(b.iter().copied().map(OrderedFloat))
 }
 
+#[allow(clippy::too_many_arguments)]
 #[tracing::instrument(level = "trace", skip_all, target = "indexing::extract")]
 pub fn extract_embeddings<R: io::Read + io::Seek>(
     // docid, prompt
     prompt_reader: grenad::Reader<R>,
     indexer: GrenadParameters,
     embedder: Arc<Embedder>,
+    document_cache: Option<&EmbedderCache>,
+    prompt: &Prompt,
     embedder_name: &str,
     possible_embedding_mistakes: &PossibleEmbeddingMistakes,
     unused_vectors_distribution: &UnusedVectorsDistribution,
@@ -689,11 +702,11 @@ pub fn extract_embeddings<R: io::Read + io::Seek>(
     let n_vectors_per_chunk = embedder.prompt_count_in_chunk_hint(); // number of vectors in a single chunk
 
     // docid, state with embedding
-    let mut state_writer = create_writer(
+    let mut state_writer = EmbeddingsWriter::new(create_writer(
         indexer.chunk_compression_type,
         indexer.chunk_compression_level,
         tempfile::tempfile()?,
-    );
+    ));
 
     let mut chunks = Vec::with_capacity(n_chunks);
     let mut current_chunk = Vec::with_capacity(n_vectors_per_chunk);
@@ -704,38 +717,42 @@ pub fn extract_embeddings<R: io::Read + io::Seek>(
     while let Some((key, value)) = cursor.move_on_next()? {
         let docid = key.try_into().map(DocumentId::from_be_bytes).unwrap();
         // SAFETY: precondition, the grenad value was saved from a string
-        let prompt = unsafe { std::str::from_utf8_unchecked(value) };
-        if current_chunk.len() == current_chunk.capacity() {
-            chunks.push(std::mem::replace(
-                &mut current_chunk,
-                Vec::with_capacity(n_vectors_per_chunk),
-            ));
-            chunks_ids.push(std::mem::replace(
-                &mut current_chunk_ids,
-                Vec::with_capacity(n_vectors_per_chunk),
-            ));
-        };
-        current_chunk.push(prompt.to_owned());
-        current_chunk_ids.push(docid);
-
-        if chunks.len() == chunks.capacity() {
-            let chunked_embeds = embed_chunks(
-                &embedder,
-                std::mem::replace(&mut chunks, Vec::with_capacity(n_chunks)),
-                embedder_name,
-                possible_embedding_mistakes,
-                unused_vectors_distribution,
-                request_threads,
-            )?;
-
-            for (docid, embeddings) in chunks_ids
-                .iter()
-                .flat_map(|docids| docids.iter())
-                .zip(chunked_embeds.iter().flat_map(|embeds| embeds.iter()))
-            {
-                state_writer.insert(docid.to_be_bytes(), cast_slice(embeddings))?;
+        let rendered = unsafe { std::str::from_utf8_unchecked(value) };
+        // each text chunk of the document is embedded separately
+        for text in prompt.chunks(rendered) {
+            if current_chunk.len() == current_chunk.capacity() {
+                chunks.push(std::mem::replace(
+                    &mut current_chunk,
+                    Vec::with_capacity(n_vectors_per_chunk),
+                ));
+                chunks_ids.push(std::mem::replace(
+                    &mut current_chunk_ids,
+                    Vec::with_capacity(n_vectors_per_chunk),
+                ));
+            };
+            current_chunk.push(text.to_owned());
+            current_chunk_ids.push(docid);
+
+            if chunks.len() == chunks.capacity() {
+                let chunked_embeds = embed_chunks(
+                    &embedder,
+                    document_cache,
+                    std::mem::replace(&mut chunks, Vec::with_capacity(n_chunks)),
+                    embedder_name,
+                    possible_embedding_mistakes,
+                    unused_vectors_distribution,
+                    request_threads,
+                )?;
+
+                for (docid, embeddings) in chunks_ids
+                    .iter()
+                    .flat_map(|docids| docids.iter())
+                    .zip(chunked_embeds.iter().flat_map(|embeds| embeds.iter()))
+                {
+                    state_writer.insert(*docid, embeddings)?;
+                }
+                chunks_ids.clear();
             }
-            chunks_ids.clear();
         }
     }
 
@@ -743,6 +760,7 @@ pub fn extract_embeddings<R: io::Read + io::Seek>(
     if !chunks.is_empty() {
         let chunked_embeds = embed_chunks(
             &embedder,
+            document_cache,
             std::mem::take(&mut chunks),
             embedder_name,
             possible_embedding_mistakes,
@@ -754,13 +772,14 @@ pub fn extract_embeddings<R: io::Read + io::Seek>(
             .flat_map(|docids| docids.iter())
             .zip(chunked_embeds.iter().flat_map(|embeds| embeds.iter()))
         {
-            state_writer.insert(docid.to_be_bytes(), cast_slice(embeddings))?;
+            state_writer.insert(*docid, embeddings)?;
         }
     }
 
     if !current_chunk.is_empty() {
         let embeds = embed_chunks(
             &embedder,
+            document_cache,
             vec![std::mem::take(&mut current_chunk)],
             embedder_name,
             possible_embedding_mistakes,
@@ -770,23 +789,63 @@ pub fn extract_embeddings<R: io::Read + io::Seek>(
 
         if let Some(embeds) = embeds.first() {
             for (docid, embeddings) in current_chunk_ids.iter().zip(embeds.iter()) {
-                state_writer.insert(docid.to_be_bytes(), cast_slice(embeddings))?;
+                state_writer.insert(*docid, embeddings)?;
             }
         }
     }
 
-    writer_into_reader(state_writer)
+    state_writer.into_reader()
+}
+
+/// Writes the embeddings of the documents, the embeddings of the chunks of a document being written together.
+///
+/// Expects the chunks of a document to be inserted contiguously.
+struct EmbeddingsWriter {
+    writer: Writer<BufWriter<File>>,
+    current: Option<(DocumentId, Embedding)>,
+}
+
+impl EmbeddingsWriter {
+    fn new(writer: Writer<BufWriter<File>>) -> Self {
+        Self { writer, current: None }
+    }
+
+    fn insert(&mut self, docid: DocumentId, embedding: &[f32]) -> Result<()> {
+        match &mut self.current {
+            Some((current_docid, embeddings)) if *current_docid == docid => {
+                embeddings.extend_from_slice(embedding)
+            }
+            current => {
+                if let Some((docid, embeddings)) = current.replace((docid, embedding.to_vec())) {
+                    self.writer.insert(docid.to_be_bytes(), cast_slice(&embeddings))?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn into_reader(mut self) -> Result<grenad::Reader<BufReader<File>>> {
+        if let Some((docid, embeddings)) = self.current.take() {
+            self.writer.insert(docid.to_be_bytes(), cast_slice(&embeddings))?;
+        }
+        writer_into_reader(self.writer)
+    }
 }
 
 fn embed_chunks(
     embedder: &Embedder,
+    document_cache: Option<&EmbedderCache>,
     text_chunks: Vec<Vec<String>>,
     embedder_name: &str,
     possible_embedding_mistakes: &PossibleEmbeddingMistakes,
     unused_vectors_distribution: &UnusedVectorsDistribution,
     request_threads: &ThreadPoolNoAbort,
 ) -> Result<Vec<Vec<Embedding>>> {
-    match embedder.embed_index(text_chunks, request_threads) {
+    let embeddings = match document_cache {
+        Some(cache) => cache.embed_index(embedder, text_chunks, request_threads),
+        None => embedder.embed_index(text_chunks, request_threads),
+    };
+    match embeddings {
         Ok(chunks) => Ok(chunks),
         Err(error) => {
             if let FaultSource::Bug = error.fault {
@@ -826,7 +885,10 @@ fn embed_chunks(
                     }
                 }
 
-                Err(crate::Error::UserError(crate::UserError::DocumentEmbeddingError(msg)))
+                Err(crate::Error::UserError(crate::UserError::DocumentEmbeddingError {
+                    message: msg,
+                    transient: error.kind.is_transient(),
+                }))
             }
         }
     }
@@ -260,6 +260,8 @@ fn send_original_documents_data(
                         prompts,
                         embedder_name,
                         embedder,
+                        document_cache,
+                        prompt,
                         add_to_user_provided,
                         remove_from_user_provided,
                     } in extracted_vectors
@@ -268,6 +270,8 @@ fn send_original_documents_data(
                             prompts,
                             indexer,
                             embedder.clone(),
+                            document_cache.as_ref(),
+                            &prompt,
                             &embedder_name,
                             &possible_embedding_mistakes,
                             &unused_vectors_distribution,
@@ -229,8 +229,8 @@ where
             crate::vector::error::PossibleEmbeddingMistakes::new(&field_distribution);
 
         let backup_pool;
-        let pool = match self.indexer_config.thread_pool {
-            Some(ref pool) => pool,
+        let pool = match self.indexer_config.thread_pool.as_deref() {
+            Some(pool) => pool,
             None => {
                 // We initialize a backup pool with the default
                 // settings if none have already been set.
@@ -2776,6 +2776,7 @@ mod tests {
                         dimensions: Setting::Set(3),
                         document_template: Setting::NotSet,
                         document_template_max_bytes: Setting::NotSet,
+                        chunking: Setting::NotSet,
                         url: Setting::NotSet,
                         request: Setting::NotSet,
                         response: Setting::NotSet,
@@ -2784,6 +2785,9 @@ mod tests {
                         search_embedder: Setting::NotSet,
                         indexing_embedder: Setting::NotSet,
                         binary_quantized: Setting::NotSet,
+                        indexing_fragment: Setting::NotSet,
+                        search_fragment: Setting::NotSet,
+                        reranker: Setting::NotSet,
                     }),
                 );
                 settings.set_embedder_settings(embedders);
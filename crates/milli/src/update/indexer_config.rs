@@ -1,9 +1,12 @@
+use std::sync::Arc;
+
 use grenad::CompressionType;
 
 use super::GrenadParameters;
 use crate::thread_pool_no_abort::ThreadPoolNoAbort;
+use crate::vector::persistent_cache::PersistentEmbeddingCache;
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct IndexerConfig {
     pub log_every_n: Option<usize>,
     pub max_nb_chunks: Option<usize>,
@@ -11,9 +14,11 @@ pub struct IndexerConfig {
     pub max_memory: Option<usize>,
     pub chunk_compression_type: CompressionType,
     pub chunk_compression_level: Option<u32>,
-    pub thread_pool: Option<ThreadPoolNoAbort>,
+    pub thread_pool: Option<Arc<ThreadPoolNoAbort>>,
     pub max_positions_per_attributes: Option<u32>,
     pub skip_index_budget: bool,
+    /// Persistent cache of the embeddings of the documents, shared by all the indexes.
+    pub embedding_cache: Option<Arc<PersistentEmbeddingCache>>,
 }
 
 impl IndexerConfig {
@@ -39,6 +44,7 @@ impl Default for IndexerConfig {
             thread_pool: None,
             max_positions_per_attributes: None,
             skip_index_budget: false,
+            embedding_cache: None,
         }
     }
 }
@@ -937,15 +937,6 @@ impl EmbeddingSender<'_, '_> {
     ) -> crate::Result<()> {
         self.0.set_vectors(docid, embedder_id, &embeddings[..])
     }
-
-    pub fn set_vector(
-        &self,
-        docid: DocumentId,
-        embedder_id: u8,
-        embedding: Embedding,
-    ) -> crate::Result<()> {
-        self.0.set_vectors(docid, embedder_id, &[embedding])
-    }
 }
 
 #[derive(Clone, Copy)]
@@ -3,11 +3,13 @@ use std::cell::RefCell;
 use bumpalo::collections::Vec as BVec;
 use bumpalo::Bump;
 use hashbrown::{DefaultHashBuilder, HashMap};
+use itertools::Itertools as _;
 
 use super::cache::DelAddRoaringBitmap;
 use crate::error::FaultSource;
 use crate::prompt::Prompt;
 use crate::update::new::channel::EmbeddingSender;
+use crate::update::new::indexer::check_must_stop_processing;
 use crate::update::new::indexer::document_changes::{DocumentChangeContext, Extractor};
 use crate::update::new::thread_local::MostlySend;
 use crate::update::new::vector_document::VectorDocument;
@@ -15,25 +17,28 @@ use crate::update::new::DocumentChange;
 use crate::vector::error::{
     EmbedErrorKind, PossibleEmbeddingMistakes, UnusedVectorsDistributionBump,
 };
+use crate::vector::persistent_cache::EmbedderCache;
 use crate::vector::{Embedder, Embedding, EmbeddingConfigs};
 use crate::{DocumentId, FieldDistribution, InternalError, Result, ThreadPoolNoAbort, UserError};
 
-pub struct EmbeddingExtractor<'a, 'b> {
+pub struct EmbeddingExtractor<'a, 'b, MSP> {
     embedders: &'a EmbeddingConfigs,
     sender: EmbeddingSender<'a, 'b>,
     possible_embedding_mistakes: PossibleEmbeddingMistakes,
     threads: &'a ThreadPoolNoAbort,
+    must_stop_processing: &'a MSP,
 }
 
-impl<'a, 'b> EmbeddingExtractor<'a, 'b> {
+impl<'a, 'b, MSP> EmbeddingExtractor<'a, 'b, MSP> {
     pub fn new(
         embedders: &'a EmbeddingConfigs,
         sender: EmbeddingSender<'a, 'b>,
         field_distribution: &'a FieldDistribution,
         threads: &'a ThreadPoolNoAbort,
+        must_stop_processing: &'a MSP,
     ) -> Self {
         let possible_embedding_mistakes = PossibleEmbeddingMistakes::new(field_distribution);
-        Self { embedders, sender, threads, possible_embedding_mistakes }
+        Self { embedders, sender, threads, possible_embedding_mistakes, must_stop_processing }
     }
 }
 
@@ -43,7 +48,10 @@ pub struct EmbeddingExtractorData<'extractor>(
 
 unsafe impl MostlySend for EmbeddingExtractorData<'_> {}
 
-impl<'extractor> Extractor<'extractor> for EmbeddingExtractor<'_, '_> {
+impl<'extractor, MSP> Extractor<'extractor> for EmbeddingExtractor<'_, '_, MSP>
+where
+    MSP: Fn() -> bool + Sync,
+{
     type Data = RefCell<EmbeddingExtractorData<'extractor>>;
 
     fn init_data<'doc>(&'doc self, extractor_alloc: &'extractor Bump) -> crate::Result<Self::Data> {
@@ -70,6 +78,7 @@ impl<'extractor> Extractor<'extractor> for EmbeddingExtractor<'_, '_> {
                 )?;
             all_chunks.push(Chunks::new(
                 embedder,
+                self.embedders.document_cache(embedder_name),
                 embedder_id,
                 embedder_name,
                 prompt,
@@ -77,6 +86,7 @@ impl<'extractor> Extractor<'extractor> for EmbeddingExtractor<'_, '_> {
                 &self.possible_embedding_mistakes,
                 self.threads,
                 self.sender,
+                self.must_stop_processing,
                 &context.doc_alloc,
             ))
         }
@@ -268,11 +278,14 @@ impl<'extractor> Extractor<'extractor> for EmbeddingExtractor<'_, '_> {
 // Currently this is the case as:
 // 1. BVec are inside of the bumaplo
 // 2. All other fields are either trivial (u8) or references.
-struct Chunks<'a, 'b, 'extractor> {
+struct Chunks<'a, 'b, 'extractor, MSP> {
     texts: BVec<'a, &'a str>,
     ids: BVec<'a, DocumentId>,
+    /// Number of texts embedded together
+    capacity: usize,
 
     embedder: &'a Embedder,
+    document_cache: Option<&'a EmbedderCache>,
     embedder_id: u8,
     embedder_name: &'a str,
     dimensions: usize,
@@ -281,13 +294,18 @@ struct Chunks<'a, 'b, 'extractor> {
     user_provided: &'a RefCell<EmbeddingExtractorData<'extractor>>,
     threads: &'a ThreadPoolNoAbort,
     sender: EmbeddingSender<'a, 'b>,
+    must_stop_processing: &'a MSP,
     has_manual_generation: Option<&'a str>,
 }
 
-impl<'a, 'b, 'extractor> Chunks<'a, 'b, 'extractor> {
+impl<'a, 'b, 'extractor, MSP> Chunks<'a, 'b, 'extractor, MSP>
+where
+    MSP: Fn() -> bool + Sync,
+{
     #[allow(clippy::too_many_arguments)]
     pub fn new(
         embedder: &'a Embedder,
+        document_cache: Option<&'a EmbedderCache>,
         embedder_id: u8,
         embedder_name: &'a str,
         prompt: &'a Prompt,
@@ -295,6 +313,7 @@ impl<'a, 'b, 'extractor> Chunks<'a, 'b, 'extractor> {
         possible_embedding_mistakes: &'a PossibleEmbeddingMistakes,
         threads: &'a ThreadPoolNoAbort,
         sender: EmbeddingSender<'a, 'b>,
+        must_stop_processing: &'a MSP,
         doc_alloc: &'a Bump,
     ) -> Self {
         let capacity = embedder.prompt_count_in_chunk_hint() * embedder.chunk_count_hint();
@@ -304,11 +323,14 @@ impl<'a, 'b, 'extractor> Chunks<'a, 'b, 'extractor> {
         Self {
             texts,
             ids,
+            capacity,
             embedder,
+            document_cache,
             prompt,
             possible_embedding_mistakes,
             threads,
             sender,
+            must_stop_processing,
             embedder_id,
             embedder_name,
             user_provided,
@@ -329,24 +351,30 @@ impl<'a, 'b, 'extractor> Chunks<'a, 'b, 'extractor> {
             self.has_manual_generation.get_or_insert(external_docid);
         }
 
-        if self.texts.len() < self.texts.capacity() {
-            self.texts.push(rendered);
-            self.ids.push(docid);
-            return Ok(());
+        // all the chunks of a document are embedded together, so that its vectors are all sent at once
+        let chunks = self.prompt.chunks(rendered);
+        if !self.texts.is_empty() && self.texts.len() + chunks.len() > self.capacity {
+            Self::embed_chunks(
+                &mut self.texts,
+                &mut self.ids,
+                self.embedder,
+                self.document_cache,
+                self.embedder_id,
+                self.embedder_name,
+                self.possible_embedding_mistakes,
+                unused_vectors_distribution,
+                self.threads,
+                self.sender,
+                self.must_stop_processing,
+                self.has_manual_generation.take(),
+            )?;
         }
 
-        Self::embed_chunks(
-            &mut self.texts,
-            &mut self.ids,
-            self.embedder,
-            self.embedder_id,
-            self.embedder_name,
-            self.possible_embedding_mistakes,
-            unused_vectors_distribution,
-            self.threads,
-            self.sender,
-            self.has_manual_generation.take(),
-        )
+        for chunk in chunks {
+            self.texts.push(chunk);
+            self.ids.push(docid);
+        }
+        Ok(())
     }
 
     pub fn drain(
@@ -357,12 +385,14 @@ impl<'a, 'b, 'extractor> Chunks<'a, 'b, 'extractor> {
             &mut self.texts,
             &mut self.ids,
             self.embedder,
+            self.document_cache,
             self.embedder_id,
             self.embedder_name,
             self.possible_embedding_mistakes,
             unused_vectors_distribution,
             self.threads,
             self.sender,
+            self.must_stop_processing,
             self.has_manual_generation,
         );
         // optimization: don't run bvec dtors as they only contain bumpalo allocated stuff
@@ -375,14 +405,19 @@ impl<'a, 'b, 'extractor> Chunks<'a, 'b, 'extractor> {
         texts: &mut BVec<'a, &'a str>,
         ids: &mut BVec<'a, DocumentId>,
         embedder: &Embedder,
+        document_cache: Option<&EmbedderCache>,
         embedder_id: u8,
         embedder_name: &str,
         possible_embedding_mistakes: &PossibleEmbeddingMistakes,
         unused_vectors_distribution: &UnusedVectorsDistributionBump,
         threads: &ThreadPoolNoAbort,
         sender: EmbeddingSender<'a, 'b>,
+        must_stop_processing: &MSP,
         has_manual_generation: Option<&'a str>,
     ) -> Result<()> {
+        // embedding a chunk can take a while, the indexation can be canceled between two chunks
+        check_must_stop_processing(must_stop_processing)?;
+
         if let Some(external_docid) = has_manual_generation {
             let mut msg = format!(
                 r"While embedding documents for embedder `{embedder_name}`: no vectors provided for document `{}`{}",
@@ -418,13 +453,24 @@ impl<'a, 'b, 'extractor> Chunks<'a, 'b, 'extractor> {
                 );
             }
 
-            return Err(crate::Error::UserError(crate::UserError::DocumentEmbeddingError(msg)));
+            return Err(crate::Error::UserError(crate::UserError::DocumentEmbeddingError {
+                message: msg,
+                transient: false,
+            }));
         }
 
-        let res = match embedder.embed_index_ref(texts.as_slice(), threads) {
+        let embeddings = match document_cache {
+            Some(cache) => cache.embed_index_ref(embedder, texts.as_slice(), threads),
+            None => embedder.embed_index_ref(texts.as_slice(), threads),
+        };
+        let res = match embeddings {
             Ok(embeddings) => {
-                for (docid, embedding) in ids.into_iter().zip(embeddings) {
-                    sender.set_vector(*docid, embedder_id, embedding).unwrap();
+                // the chunks of a document are contiguous
+                for (docid, embeddings) in
+                    &ids.iter().zip(embeddings).chunk_by(|(docid, _)| **docid)
+                {
+                    let embeddings = embeddings.map(|(_, embedding)| embedding).collect();
+                    sender.set_vectors(docid, embedder_id, embeddings).unwrap();
                 }
                 Ok(())
             }
@@ -467,7 +513,10 @@ impl<'a, 'b, 'extractor> Chunks<'a, 'b, 'extractor> {
                         }
                     }
 
-                    Err(crate::Error::UserError(crate::UserError::DocumentEmbeddingError(msg)))
+                    Err(crate::Error::UserError(crate::UserError::DocumentEmbeddingError {
+                        message: msg,
+                        transient: error.kind.is_transient(),
+                    }))
                 }
             }
         };
@@ -13,11 +13,15 @@ use super::super::thread_local::{FullySend, ThreadLocal};
 use super::super::FacetFieldIdsDelta;
 use super::document_changes::{extract, DocumentChanges, IndexingContext};
 use crate::index::IndexEmbeddingConfig;
+use crate::ngram_attributes::ngram_fields_rules;
 use crate::progress::MergingWordCache;
 use crate::proximity::ProximityPrecision;
 use crate::update::new::extract::EmbeddingExtractor;
 use crate::update::new::merger::merge_and_send_rtree;
-use crate::update::new::{merge_and_send_docids, merge_and_send_facet_docids, FacetDatabases};
+use crate::update::new::{
+    merge_and_send_docids, merge_and_send_facet_docids, merge_and_send_word_fid_docids,
+    FacetDatabases, NgramWordsDelta,
+};
 use crate::vector::EmbeddingConfigs;
 use crate::{Result, ThreadPoolNoAbort, ThreadPoolNoAbortBuilder};
 
@@ -34,7 +38,7 @@ pub(super) fn extract_all<'pl, 'extractor, DC, MSP>(
     mut index_embeddings: Vec<IndexEmbeddingConfig>,
     document_ids: &mut RoaringBitmap,
     modified_docids: &mut RoaringBitmap,
-) -> Result<(FacetFieldIdsDelta, Vec<IndexEmbeddingConfig>)>
+) -> Result<(FacetFieldIdsDelta, NgramWordsDelta, Vec<IndexEmbeddingConfig>)>
 where
     DC: DocumentChanges<'pl>,
     MSP: Fn() -> bool + Sync,
@@ -79,6 +83,7 @@ where
     }
 
     let facet_field_ids_delta;
+    let ngram_words_delta;
 
     {
         let caches = {
@@ -149,10 +154,22 @@ where
             let _entered = span.enter();
             indexing_context.progress.update_progress(MergingWordCache::WordFieldIdDocids);
 
-            merge_and_send_docids(
+            // the fields of the documents are all known once the words have been extracted
+            let ngram_attributes = index.ngram_attributes(&rtxn)?;
+            let ngram_fields_rules = {
+                let fields_ids_map = indexing_context.new_fields_ids_map.read().unwrap();
+                ngram_fields_rules(
+                    &ngram_attributes,
+                    fields_ids_map.iter().map(|(fid, name, _)| (fid, name)),
+                )
+            };
+
+            ngram_words_delta = merge_and_send_word_fid_docids(
                 word_fid_docids,
                 index.word_fid_docids.remap_types(),
                 index,
+                ngram_fields_rules,
+                indexing_context.grenad_parameters.max_memory_by_thread(),
                 extractor_sender.docids::<WordFidDocids>(),
                 &indexing_context.must_stop_processing,
             )?;
@@ -246,6 +263,7 @@ where
             embedding_sender,
             field_distribution,
             request_threads(),
+            indexing_context.must_stop_processing,
         );
         let mut datastore = ThreadLocal::with_capacity(rayon::current_num_threads());
         {
@@ -309,7 +327,7 @@ where
     indexing_context.progress.update_progress(IndexingStep::WaitingForDatabaseWrites);
     finished_extraction.store(true, std::sync::atomic::Ordering::Relaxed);
 
-    Result::Ok((facet_field_ids_delta, index_embeddings))
+    Result::Ok((facet_field_ids_delta, ngram_words_delta, index_embeddings))
 }
 
 fn request_threads() -> &'static ThreadPoolNoAbort {
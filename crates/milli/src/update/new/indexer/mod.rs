@@ -9,7 +9,10 @@ pub use document_operation::{DocumentOperation, PayloadStats};
 use hashbrown::HashMap;
 use heed::RwTxn;
 pub use partial_dump::PartialDump;
-pub use post_processing::recompute_word_fst_from_word_docids_database;
+pub use post_processing::{
+    recompute_ngrams_fst_from_word_fid_docids, recompute_stems_fst_from_words_fst,
+    recompute_word_fst_from_word_docids_database,
+};
 pub use update_by_function::UpdateByFunction;
 pub use write::ChannelCongestion;
 use write::{build_vectors, update_index, write_to_db};
@@ -37,6 +40,20 @@ mod write;
 
 static LOG_MEMORY_METRICS_ONCE: Once = Once::new();
 
+/// A cancellation checkpoint, returns an error if the indexation must be stopped.
+///
+/// The long running steps call it between their units of work so that a canceled
+/// indexation stops in a bounded time.
+pub(crate) fn check_must_stop_processing<MSP>(must_stop_processing: &MSP) -> Result<()>
+where
+    MSP: Fn() -> bool,
+{
+    if must_stop_processing() {
+        return Err(InternalError::AbortedIndexation.into());
+    }
+    Ok(())
+}
+
 /// This is the main function of this crate.
 ///
 /// Give it the output of the [`Indexer::document_changes`] method and it will execute it in the [`rayon::ThreadPool`].
@@ -194,7 +211,9 @@ where
 
         indexing_context.progress.update_progress(IndexingStep::WaitingForExtractors);
 
-        let (facet_field_ids_delta, index_embeddings) = extractor_handle.join().unwrap()?;
+        let (facet_field_ids_delta, ngram_words_delta, index_embeddings) =
+            extractor_handle.join().unwrap()?;
+        check_must_stop_processing(indexing_context.must_stop_processing)?;
 
         indexing_context.progress.update_progress(IndexingStep::WritingEmbeddingsToDatabase);
 
@@ -211,12 +230,16 @@ where
         })
         .unwrap()?;
 
+        check_must_stop_processing(indexing_context.must_stop_processing)?;
+
         post_processing::post_process(
             indexing_context,
             wtxn,
             global_fields_ids_map,
             facet_field_ids_delta,
+            ngram_words_delta,
         )?;
+        check_must_stop_processing(indexing_context.must_stop_processing)?;
 
         indexing_context.progress.update_progress(IndexingStep::Finalizing);
 
@@ -1,25 +1,31 @@
 use std::cmp::Ordering;
 
+use fst::Streamer;
 use heed::types::{Bytes, DecodeIgnore, Str};
 use heed::RwTxn;
 use itertools::{merge_join_by, EitherOrBoth};
 
+use super::check_must_stop_processing;
 use super::document_changes::IndexingContext;
 use crate::facet::FacetType;
 use crate::index::main_key::{WORDS_FST_KEY, WORDS_PREFIXES_FST_KEY};
+use crate::ngram_attributes::ngram_fields_rules;
 use crate::progress::Progress;
+use crate::stemming::Stemmers;
 use crate::update::del_add::DelAdd;
 use crate::update::facet::new_incremental::FacetsUpdateIncremental;
 use crate::update::facet::{FACET_GROUP_SIZE, FACET_MAX_GROUP_SIZE, FACET_MIN_LEVEL_SIZE};
 use crate::update::new::facet_search_builder::FacetSearchBuilder;
 use crate::update::new::merger::FacetFieldIdDelta;
+use crate::update::new::ngrams_fst_builder::NgramsFstBuilder;
+use crate::update::new::stems_fst_builder::StemsFstBuilder;
 use crate::update::new::steps::{IndexingStep, PostProcessingFacets, PostProcessingWords};
 use crate::update::new::word_fst_builder::{PrefixData, PrefixDelta, WordFstBuilder};
 use crate::update::new::words_prefix_docids::{
     compute_exact_word_prefix_docids, compute_word_prefix_docids, compute_word_prefix_fid_docids,
     compute_word_prefix_position_docids,
 };
-use crate::update::new::FacetFieldIdsDelta;
+use crate::update::new::{FacetFieldIdsDelta, NgramWordsDelta};
 use crate::update::{FacetsUpdateBulk, GrenadParameters};
 use crate::{GlobalFieldsIdsMap, Index, Result};
 
@@ -28,11 +34,13 @@ pub(super) fn post_process<MSP>(
     wtxn: &mut RwTxn<'_>,
     mut global_fields_ids_map: GlobalFieldsIdsMap<'_>,
     facet_field_ids_delta: FacetFieldIdsDelta,
+    ngram_words_delta: NgramWordsDelta,
 ) -> Result<()>
 where
     MSP: Fn() -> bool + Sync,
 {
     let index = indexing_context.index;
+    let must_stop_processing = indexing_context.must_stop_processing;
     indexing_context.progress.update_progress(IndexingStep::PostProcessingFacets);
     compute_facet_level_database(
         index,
@@ -40,50 +48,103 @@ where
         facet_field_ids_delta,
         &mut global_fields_ids_map,
         indexing_context.progress,
+        must_stop_processing,
+    )?;
+    compute_facet_search_database(
+        index,
+        wtxn,
+        global_fields_ids_map,
+        indexing_context.progress,
+        must_stop_processing,
     )?;
-    compute_facet_search_database(index, wtxn, global_fields_ids_map, indexing_context.progress)?;
     indexing_context.progress.update_progress(IndexingStep::PostProcessingWords);
-    if let Some(prefix_delta) = compute_word_fst(index, wtxn, indexing_context.progress)? {
+    let prefix_delta =
+        compute_word_fst(index, wtxn, indexing_context.progress, must_stop_processing)?;
+    compute_ngrams_fst(
+        index,
+        wtxn,
+        ngram_words_delta,
+        indexing_context.progress,
+        must_stop_processing,
+    )?;
+    if let Some(prefix_delta) = prefix_delta {
         compute_prefix_database(
             index,
             wtxn,
             prefix_delta,
             indexing_context.grenad_parameters,
             indexing_context.progress,
+            must_stop_processing,
         )?;
     };
     Ok(())
 }
 
 #[tracing::instrument(level = "trace", skip_all, target = "indexing::prefix")]
-fn compute_prefix_database(
+fn compute_prefix_database<MSP>(
     index: &Index,
     wtxn: &mut RwTxn,
     prefix_delta: PrefixDelta,
     grenad_parameters: &GrenadParameters,
     progress: &Progress,
-) -> Result<()> {
+    must_stop_processing: &MSP,
+) -> Result<()>
+where
+    MSP: Fn() -> bool + Sync,
+{
     let PrefixDelta { modified, deleted } = prefix_delta;
 
     progress.update_progress(PostProcessingWords::WordPrefixDocids);
-    compute_word_prefix_docids(wtxn, index, &modified, &deleted, grenad_parameters)?;
+    compute_word_prefix_docids(
+        wtxn,
+        index,
+        &modified,
+        &deleted,
+        grenad_parameters,
+        must_stop_processing,
+    )?;
 
     progress.update_progress(PostProcessingWords::ExactWordPrefixDocids);
-    compute_exact_word_prefix_docids(wtxn, index, &modified, &deleted, grenad_parameters)?;
+    compute_exact_word_prefix_docids(
+        wtxn,
+        index,
+        &modified,
+        &deleted,
+        grenad_parameters,
+        must_stop_processing,
+    )?;
 
     progress.update_progress(PostProcessingWords::WordPrefixFieldIdDocids);
-    compute_word_prefix_fid_docids(wtxn, index, &modified, &deleted, grenad_parameters)?;
+    compute_word_prefix_fid_docids(
+        wtxn,
+        index,
+        &modified,
+        &deleted,
+        grenad_parameters,
+        must_stop_processing,
+    )?;
 
     progress.update_progress(PostProcessingWords::WordPrefixPositionDocids);
-    compute_word_prefix_position_docids(wtxn, index, &modified, &deleted, grenad_parameters)
+    compute_word_prefix_position_docids(
+        wtxn,
+        index,
+        &modified,
+        &deleted,
+        grenad_parameters,
+        must_stop_processing,
+    )
 }
 
 #[tracing::instrument(level = "trace", skip_all, target = "indexing")]
-fn compute_word_fst(
+fn compute_word_fst<MSP>(
     index: &Index,
     wtxn: &mut RwTxn,
     progress: &Progress,
-) -> Result<Option<PrefixDelta>> {
+    must_stop_processing: &MSP,
+) -> Result<Option<PrefixDelta>>
+where
+    MSP: Fn() -> bool,
+{
     let rtxn = index.read_txn()?;
     progress.update_progress(PostProcessingWords::WordFst);
 
@@ -91,6 +152,10 @@ fn compute_word_fst(
     let mut word_fst_builder = WordFstBuilder::new(&words_fst)?;
     let prefix_settings = index.prefix_settings(&rtxn)?;
     word_fst_builder.with_prefix_settings(prefix_settings);
+    let stems_fst = index.stems_fst(&rtxn)?;
+    let stemmers = Stemmers::new(index, wtxn)?;
+    let mut stems_fst_builder =
+        (!stemmers.is_empty()).then(|| StemsFstBuilder::new(index, stemmers, &stems_fst));
 
     let previous_words = index.word_docids.iter(&rtxn)?.remap_data_type::<Bytes>();
     let current_words = index.word_docids.iter(wtxn)?.remap_data_type::<Bytes>();
@@ -98,25 +163,39 @@ fn compute_word_fst(
         (Ok((l, _)), Ok((r, _))) => l.cmp(r),
         (Err(_), _) | (_, Err(_)) => Ordering::Equal,
     }) {
+        check_must_stop_processing(must_stop_processing)?;
         match eob {
             EitherOrBoth::Both(lhs, rhs) => {
                 let (word, lhs_bytes) = lhs?;
                 let (_, rhs_bytes) = rhs?;
                 if lhs_bytes != rhs_bytes {
                     word_fst_builder.register_word(DelAdd::Addition, word.as_ref())?;
+                    if let Some(stems_fst_builder) = &mut stems_fst_builder {
+                        stems_fst_builder.register_updated_word(word.as_ref(), &rtxn, wtxn)?;
+                    }
                 }
             }
             EitherOrBoth::Left(result) => {
                 let (word, _) = result?;
                 word_fst_builder.register_word(DelAdd::Deletion, word.as_ref())?;
+                if let Some(stems_fst_builder) = &mut stems_fst_builder {
+                    stems_fst_builder.register_word(DelAdd::Deletion, word.as_ref(), &rtxn)?;
+                }
             }
             EitherOrBoth::Right(result) => {
                 let (word, _) = result?;
                 word_fst_builder.register_word(DelAdd::Addition, word.as_ref())?;
+                if let Some(stems_fst_builder) = &mut stems_fst_builder {
+                    stems_fst_builder.register_word(DelAdd::Addition, word.as_ref(), wtxn)?;
+                }
             }
         }
     }
 
+    if let Some(stems_fst_builder) = stems_fst_builder {
+        index.put_stems_fst(wtxn, &stems_fst_builder.build()?)?;
+    }
+
     let (word_fst_mmap, prefix_data) = word_fst_builder.build(index, &rtxn)?;
     index.main.remap_types::<Str, Bytes>().put(wtxn, WORDS_FST_KEY, &word_fst_mmap)?;
     if let Some(PrefixData { prefixes_fst_mmap, prefix_delta }) = prefix_data {
@@ -131,6 +210,34 @@ fn compute_word_fst(
     }
 }
 
+/// Updates the n-grams FST with the words that appeared in or disappeared from the n-gram attributes.
+#[tracing::instrument(level = "trace", skip_all, target = "indexing")]
+fn compute_ngrams_fst<MSP>(
+    index: &Index,
+    wtxn: &mut RwTxn,
+    ngram_words_delta: NgramWordsDelta,
+    progress: &Progress,
+    must_stop_processing: &MSP,
+) -> Result<()>
+where
+    MSP: Fn() -> bool,
+{
+    let NgramWordsDelta { fields_rules, ngrams } = ngram_words_delta;
+    // there is nothing to do without n-gram attributes or when none of their words changed
+    if ngrams.iter().all(NgramsFstBuilder::is_empty) {
+        return Ok(());
+    }
+
+    let rtxn = index.read_txn()?;
+    progress.update_progress(PostProcessingWords::NgramsFst);
+
+    let ngrams_fst_mmap =
+        NgramsFstBuilder::build(ngrams, index, &rtxn, wtxn, &fields_rules, must_stop_processing)?;
+    index.put_ngrams_fst(wtxn, &fst::Set::new(ngrams_fst_mmap)?)?;
+
+    Ok(())
+}
+
 pub fn recompute_word_fst_from_word_docids_database(index: &Index, wtxn: &mut RwTxn) -> Result<()> {
     let fst = fst::Set::default().map_data(std::borrow::Cow::Owned)?;
     let mut word_fst_builder = WordFstBuilder::new(&fst)?;
@@ -145,13 +252,68 @@ pub fn recompute_word_fst_from_word_docids_database(index: &Index, wtxn: &mut Rw
     Ok(())
 }
 
+/// Rebuilds the stems FST from the words FST, for the languages of the `stemming` setting.
+pub fn recompute_stems_fst_from_words_fst(index: &Index, wtxn: &mut RwTxn) -> Result<()> {
+    let stemmers = Stemmers::new(index, wtxn)?;
+    let mut keys = Vec::new();
+    if !stemmers.is_empty() {
+        let words_fst = index.words_fst(wtxn)?;
+        let mut stream = words_fst.stream();
+        while let Some(word) = stream.next() {
+            keys.extend(stemmers.stems_fst_keys(index, wtxn, std::str::from_utf8(word)?)?);
+        }
+    }
+    keys.sort_unstable();
+    keys.dedup();
+    index.put_stems_fst(wtxn, &fst::Set::from_iter(keys)?)?;
+
+    Ok(())
+}
+
+/// Rebuilds the n-grams FST from the words of the attributes of the `ngramAttributes` setting.
+#[tracing::instrument(level = "trace", skip_all, target = "indexing")]
+pub fn recompute_ngrams_fst_from_word_fid_docids<MSP>(
+    index: &Index,
+    wtxn: &mut RwTxn,
+    max_memory: Option<usize>,
+    must_stop_processing: &MSP,
+) -> Result<()>
+where
+    MSP: Fn() -> bool,
+{
+    check_must_stop_processing(must_stop_processing)?;
+    let rules = index.ngram_attributes(wtxn)?;
+    let fields_ids_map = index.fields_ids_map(wtxn)?;
+    let fields_rules = ngram_fields_rules(&rules, fields_ids_map.iter());
+    let mut ngrams_fst_builder = NgramsFstBuilder::new(max_memory);
+    if !fields_rules.is_empty() {
+        let words = index.word_fid_docids.iter(wtxn)?.remap_data_type::<DecodeIgnore>();
+        for result in words {
+            check_must_stop_processing(must_stop_processing)?;
+            let ((word, fid), _) = result?;
+            if let Some(rule) = fields_rules.get(&fid) {
+                ngrams_fst_builder.register_keys(DelAdd::Addition, rule.ngrams_fst_keys(word))?;
+            }
+        }
+    }
+    let ngrams_fst_mmap = ngrams_fst_builder.build_from_scratch(must_stop_processing)?;
+    check_must_stop_processing(must_stop_processing)?;
+    index.put_ngrams_fst(wtxn, &fst::Set::new(ngrams_fst_mmap)?)?;
+
+    Ok(())
+}
+
 #[tracing::instrument(level = "trace", skip_all, target = "indexing::facet_search")]
-fn compute_facet_search_database(
+fn compute_facet_search_database<MSP>(
     index: &Index,
     wtxn: &mut RwTxn,
     global_fields_ids_map: GlobalFieldsIdsMap,
     progress: &Progress,
-) -> Result<()> {
+    must_stop_processing: &MSP,
+) -> Result<()>
+where
+    MSP: Fn() -> bool,
+{
     let rtxn = index.read_txn()?;
     progress.update_progress(PostProcessingFacets::FacetSearch);
 
@@ -186,6 +348,7 @@ fn compute_facet_search_database(
             (Err(_), _) | (_, Err(_)) => Ordering::Equal,
         },
     ) {
+        check_must_stop_processing(must_stop_processing)?;
         match eob {
             EitherOrBoth::Both(lhs, rhs) => {
                 let (_, _) = lhs?;
@@ -206,13 +369,17 @@ fn compute_facet_search_database(
 }
 
 #[tracing::instrument(level = "trace", skip_all, target = "indexing::facet_field_ids")]
-fn compute_facet_level_database(
+fn compute_facet_level_database<MSP>(
     index: &Index,
     wtxn: &mut RwTxn,
     mut facet_field_ids_delta: FacetFieldIdsDelta,
     global_fields_ids_map: &mut GlobalFieldsIdsMap,
     progress: &Progress,
-) -> Result<()> {
+    must_stop_processing: &MSP,
+) -> Result<()>
+where
+    MSP: Fn() -> bool,
+{
     let rtxn = index.read_txn()?;
 
     let filterable_attributes_rules = index.filterable_attributes_rules(&rtxn)?;
@@ -221,6 +388,7 @@ fn compute_facet_level_database(
     deltas.sort_by_key(|(_, delta)| if let FacetFieldIdDelta::Bulk = delta { 0 } else { 1 });
 
     for (fid, delta) in deltas {
+        check_must_stop_processing(must_stop_processing)?;
         // skip field ids that should not be facet leveled
         let Some(metadata) = global_fields_ids_map.metadata(fid) else {
             continue;
@@ -260,6 +428,7 @@ fn compute_facet_level_database(
     deltas.sort_by_key(|(_, delta)| if let FacetFieldIdDelta::Bulk = delta { 0 } else { 1 });
 
     for (fid, delta) in deltas {
+        check_must_stop_processing(must_stop_processing)?;
         let span = tracing::trace_span!(target: "indexing::facet_field_ids", "number");
         let _entered = span.enter();
         match delta {
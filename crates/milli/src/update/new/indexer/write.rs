@@ -7,6 +7,7 @@ use rand::SeedableRng as _;
 use time::OffsetDateTime;
 
 use super::super::channel::*;
+use super::check_must_stop_processing;
 use crate::database_stats::DatabaseStats;
 use crate::documents::PrimaryKey;
 use crate::fields_ids_map::metadata::FieldIdMapWithMetadata;
@@ -118,6 +119,7 @@ where
     let seed = rand::random();
     let mut rng = rand::rngs::StdRng::seed_from_u64(seed);
     for (_index, (_embedder_name, _embedder, writer, dimensions)) in arroy_writers {
+        check_must_stop_processing(must_stop_processing)?;
         let dimensions = *dimensions;
         writer.build_and_quantize(
             wtxn,
@@ -1,8 +1,9 @@
 use std::cell::RefCell;
+use std::collections::BTreeMap;
 
 use hashbrown::HashMap;
 use heed::types::Bytes;
-use heed::{Database, RoTxn};
+use heed::{BytesDecode, BytesEncode, Database, RoTxn};
 use memmap2::Mmap;
 use rayon::iter::{IntoParallelIterator, ParallelIterator};
 use roaring::RoaringBitmap;
@@ -12,6 +13,10 @@ use super::extract::{
     merge_caches_sorted, transpose_and_freeze_caches, BalancedCaches, DelAddRoaringBitmap,
     FacetKind, GeoExtractorData,
 };
+use super::ngrams_fst_builder::NgramsFstBuilder;
+use crate::heed_codec::StrBEU16Codec;
+use crate::ngram_attributes::NgramAttribute;
+use crate::update::del_add::DelAdd;
 use crate::update::facet::new_incremental::FacetFieldIdChange;
 use crate::{CboRoaringBitmapCodec, FieldId, GeoPoint, Index, InternalError, Result};
 
@@ -90,6 +95,91 @@ where
     })
 }
 
+/// Merges the word fid docids like [`merge_and_send_docids`] and registers the n-grams of the
+/// words that appeared in or disappeared from the fields of the n-gram attributes.
+#[tracing::instrument(level = "trace", skip_all, target = "indexing::merge")]
+pub fn merge_and_send_word_fid_docids<'extractor, MSP>(
+    mut caches: Vec<BalancedCaches<'extractor>>,
+    database: Database<Bytes, Bytes>,
+    index: &Index,
+    ngram_fields_rules: BTreeMap<FieldId, NgramAttribute>,
+    max_memory: Option<usize>,
+    docids_sender: WordDocidsSender<WordFidDocids>,
+    must_stop_processing: &MSP,
+) -> Result<NgramWordsDelta>
+where
+    MSP: Fn() -> bool + Sync,
+{
+    let ngrams = transpose_and_freeze_caches(&mut caches)?
+        .into_par_iter()
+        .map(|frozen| {
+            let mut ngrams = NgramsFstBuilder::new(max_memory);
+            let rtxn = index.read_txn()?;
+            if must_stop_processing() {
+                return Err(InternalError::AbortedIndexation.into());
+            }
+            merge_caches_sorted(frozen, |key, DelAddRoaringBitmap { del, add }| {
+                let current = database.get(&rtxn, key)?;
+                let was_absent = current.is_none();
+                let operation = merge_cbo_bitmaps(current, del, add)?;
+                let presence_changed = match operation {
+                    Operation::Write(_) => was_absent,
+                    Operation::Delete => true,
+                    Operation::Ignore => false,
+                };
+                if presence_changed && !ngram_fields_rules.is_empty() {
+                    let (word, fid) =
+                        StrBEU16Codec::bytes_decode(key).map_err(heed::Error::Decoding)?;
+                    if let Some(rule) = ngram_fields_rules.get(&fid) {
+                        register_ngrams(
+                            &mut ngrams,
+                            &rtxn,
+                            database,
+                            &ngram_fields_rules,
+                            (word, fid, rule),
+                            matches!(operation, Operation::Write(_)),
+                        )?;
+                    }
+                }
+                match operation {
+                    Operation::Write(bitmap) => docids_sender.write(key, &bitmap),
+                    Operation::Delete => docids_sender.delete(key),
+                    Operation::Ignore => Ok(()),
+                }
+            })?;
+            Ok(ngrams)
+        })
+        .collect::<Result<_>>()?;
+
+    Ok(NgramWordsDelta { fields_rules: ngram_fields_rules, ngrams })
+}
+
+/// Registers the n-grams of a word that appeared in or disappeared from an n-gram attribute.
+///
+/// The n-grams of a word that disappeared from an attribute are also the ones of the other
+/// n-gram attributes the word was in. As these attributes may be updated by another thread, the
+/// word is checked once they are all merged.
+fn register_ngrams(
+    ngrams: &mut NgramsFstBuilder,
+    rtxn: &RoTxn,
+    database: Database<Bytes, Bytes>,
+    ngram_fields_rules: &BTreeMap<FieldId, NgramAttribute>,
+    (word, fid, rule): (&str, FieldId, &NgramAttribute),
+    appeared: bool,
+) -> Result<()> {
+    if appeared {
+        return ngrams.register_keys(DelAdd::Addition, rule.ngrams_fst_keys(word));
+    }
+
+    for &other_fid in ngram_fields_rules.keys().filter(|other_fid| **other_fid != fid) {
+        let key = StrBEU16Codec::bytes_encode(&(word, other_fid)).map_err(heed::Error::Encoding)?;
+        if database.get(rtxn, &key)?.is_some() {
+            return ngrams.register_word_to_check(word);
+        }
+    }
+    ngrams.register_keys(DelAdd::Deletion, rule.ngrams_fst_keys(word))
+}
+
 #[tracing::instrument(level = "trace", skip_all, target = "indexing::merge")]
 pub fn merge_and_send_facet_docids<'extractor>(
     mut caches: Vec<BalancedCaches<'extractor>>,
@@ -288,6 +378,16 @@ impl FacetFieldIdsDelta {
     }
 }
 
+/// The n-grams of the words that appeared in or disappeared from the fields of the n-gram
+/// attributes, they are the only ones to update in the n-grams FST.
+#[derive(Default)]
+pub struct NgramWordsDelta {
+    /// The rules of the fields belonging to an n-gram attribute
+    pub fields_rules: BTreeMap<FieldId, NgramAttribute>,
+    /// The n-grams registered by each merging thread
+    pub ngrams: Vec<NgramsFstBuilder>,
+}
+
 enum Operation {
     Write(RoaringBitmap),
     Delete,
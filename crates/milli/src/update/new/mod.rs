@@ -1,8 +1,10 @@
 pub use document_change::{Deletion, DocumentChange, Insertion, Update};
 pub use indexer::ChannelCongestion;
 pub use merger::{
-    merge_and_send_docids, merge_and_send_facet_docids, FacetDatabases, FacetFieldIdsDelta,
+    merge_and_send_docids, merge_and_send_facet_docids, merge_and_send_word_fid_docids,
+    FacetDatabases, FacetFieldIdsDelta, NgramWordsDelta,
 };
+pub use steps::IndexingStep;
 
 use super::del_add::DelAdd;
 use crate::FieldId;
@@ -15,9 +17,11 @@ mod facet_search_builder;
 mod fst_merger_builder;
 pub mod indexer;
 mod merger;
+mod ngrams_fst_builder;
 mod parallel_iterator_ext;
 mod ref_cell_ext;
 pub mod reindex;
+mod stems_fst_builder;
 pub(crate) mod steps;
 pub(crate) mod thread_local;
 pub mod vector_document;
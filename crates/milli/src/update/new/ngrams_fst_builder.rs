@@ -0,0 +1,166 @@
+use std::borrow::Cow;
+use std::collections::BTreeMap;
+use std::fs::File;
+
+use fst::Set;
+use grenad::{MergeFunction, MergerBuilder, Sorter};
+use heed::types::DecodeIgnore;
+use heed::RoTxn;
+use memmap2::Mmap;
+
+use super::fst_merger_builder::FstMergerBuilder;
+use super::indexer::check_must_stop_processing;
+use crate::ngram_attributes::NgramAttribute;
+use crate::update::del_add::DelAdd;
+use crate::update::{create_sorter, MergeIgnoreValues};
+use crate::{FieldId, Index, Result};
+
+/// Applies the n-grams of the words that appeared in or disappeared from the n-gram attributes
+/// to the n-grams FST.
+///
+/// The n-grams are sorted on disk by grenad and merged with the stream of the current FST into a
+/// new FST written to a temporary file.
+pub struct NgramsFstBuilder {
+    ngrams: Sorter<MergeNgramsFstKeys>,
+    words_to_check: Sorter<MergeIgnoreValues>,
+    is_empty: bool,
+}
+
+impl NgramsFstBuilder {
+    pub fn new(max_memory: Option<usize>) -> Self {
+        Self {
+            ngrams: create_sorter(
+                grenad::SortAlgorithm::Unstable,
+                MergeNgramsFstKeys,
+                grenad::CompressionType::None,
+                None,
+                None,
+                max_memory,
+                false,
+            ),
+            words_to_check: create_sorter(
+                grenad::SortAlgorithm::Unstable,
+                MergeIgnoreValues,
+                grenad::CompressionType::None,
+                None,
+                None,
+                max_memory,
+                false,
+            ),
+            is_empty: true,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.is_empty
+    }
+
+    pub fn register_keys(
+        &mut self,
+        deladd: DelAdd,
+        keys: impl Iterator<Item = Vec<u8>>,
+    ) -> Result<()> {
+        for key in keys {
+            self.ngrams.insert(key, [deladd as u8])?;
+            self.is_empty = false;
+        }
+        Ok(())
+    }
+
+    /// Registers a word that disappeared from an n-gram attribute but was in another one, its
+    /// n-grams are only updated once the presence of the word in the attributes is known.
+    pub fn register_word_to_check(&mut self, word: &str) -> Result<()> {
+        self.words_to_check.insert(word, b"")?;
+        self.is_empty = false;
+        Ok(())
+    }
+
+    /// Builds the n-grams FST made of the keys of the current one, with the n-grams registered
+    /// by the builders applied.
+    ///
+    /// The n-grams of the words to check are deleted for the attributes the words were in,
+    /// according to `rtxn`, and added back for the attributes they are in, according to `wtxn`.
+    pub fn build<MSP>(
+        builders: Vec<Self>,
+        index: &Index,
+        rtxn: &RoTxn,
+        wtxn: &RoTxn,
+        fields_rules: &BTreeMap<FieldId, NgramAttribute>,
+        must_stop_processing: &MSP,
+    ) -> Result<Mmap>
+    where
+        MSP: Fn() -> bool,
+    {
+        let mut ngrams = MergerBuilder::new(MergeNgramsFstKeys);
+        let mut words_to_check = MergerBuilder::new(MergeIgnoreValues);
+        for builder in builders {
+            ngrams.extend(builder.ngrams.into_reader_cursors()?);
+            words_to_check.extend(builder.words_to_check.into_reader_cursors()?);
+        }
+
+        let mut checked_words = Self::new(None);
+        let word_fid_docids = index.word_fid_docids.remap_data_type::<DecodeIgnore>();
+        let mut iter = words_to_check.build().into_stream_merger_iter()?;
+        while let Some((word, _)) = iter.next()? {
+            check_must_stop_processing(must_stop_processing)?;
+            let word = std::str::from_utf8(word)?;
+            for (&fid, rule) in fields_rules {
+                if word_fid_docids.get(rtxn, &(word, fid))?.is_some() {
+                    checked_words.register_keys(DelAdd::Deletion, rule.ngrams_fst_keys(word))?;
+                }
+                if word_fid_docids.get(wtxn, &(word, fid))?.is_some() {
+                    checked_words.register_keys(DelAdd::Addition, rule.ngrams_fst_keys(word))?;
+                }
+            }
+        }
+        ngrams.extend(checked_words.ngrams.into_reader_cursors()?);
+
+        let ngrams_fst = index.ngrams_fst(rtxn)?;
+        Self::merge_into_fst(ngrams, Some(&ngrams_fst), must_stop_processing)
+    }
+
+    /// Builds a new n-grams FST made of the registered additions only.
+    pub fn build_from_scratch<MSP>(self, must_stop_processing: &MSP) -> Result<Mmap>
+    where
+        MSP: Fn() -> bool,
+    {
+        let mut ngrams = MergerBuilder::new(MergeNgramsFstKeys);
+        ngrams.extend(self.ngrams.into_reader_cursors()?);
+        Self::merge_into_fst(ngrams, None::<&Set<&[u8]>>, must_stop_processing)
+    }
+
+    fn merge_into_fst<D, MSP>(
+        ngrams: MergerBuilder<File, MergeNgramsFstKeys>,
+        ngrams_fst: Option<&Set<D>>,
+        must_stop_processing: &MSP,
+    ) -> Result<Mmap>
+    where
+        D: AsRef<[u8]>,
+        MSP: Fn() -> bool,
+    {
+        let mut fst_merger_builder = FstMergerBuilder::new(ngrams_fst)?;
+        let mut iter = ngrams.build().into_stream_merger_iter()?;
+        while let Some((key, deladd)) = iter.next()? {
+            check_must_stop_processing(must_stop_processing)?;
+            let deladd = if deladd == &[DelAdd::Addition as u8] {
+                DelAdd::Addition
+            } else {
+                DelAdd::Deletion
+            };
+            fst_merger_builder.register(deladd, key, &mut |_, _, _| Ok(()))?;
+        }
+        fst_merger_builder.build(&mut |_, _, _| Ok(()))
+    }
+}
+
+/// Keeps the additions over the deletions, as the n-grams of a word that is still in one of the
+/// n-gram attributes are both deleted and added back.
+struct MergeNgramsFstKeys;
+
+impl MergeFunction for MergeNgramsFstKeys {
+    type Error = crate::Error;
+
+    fn merge<'a>(&self, _key: &[u8], values: &[Cow<'a, [u8]>]) -> Result<Cow<'a, [u8]>> {
+        Ok(values.iter().max().unwrap().clone())
+    }
+}
@@ -0,0 +1,62 @@
+use std::borrow::Cow;
+
+use fst::{Set, SetBuilder};
+use heed::RoTxn;
+
+use crate::stemming::Stemmers;
+use crate::update::del_add::DelAdd;
+use crate::{Index, Result};
+
+/// Applies the words added to and deleted from the words FST to the stems FST.
+pub struct StemsFstBuilder<'a> {
+    index: &'a Index,
+    stemmers: Stemmers,
+    stems_fst: &'a Set<Cow<'a, [u8]>>,
+    additions: Vec<Vec<u8>>,
+    deletions: Vec<Vec<u8>>,
+}
+
+impl<'a> StemsFstBuilder<'a> {
+    pub fn new(index: &'a Index, stemmers: Stemmers, stems_fst: &'a Set<Cow<'a, [u8]>>) -> Self {
+        Self { index, stemmers, stems_fst, additions: Vec::new(), deletions: Vec::new() }
+    }
+
+    /// Registers a word added to or deleted from the index, `txn` being the transaction in
+    /// which the word is in the index.
+    pub fn register_word(&mut self, deladd: DelAdd, word: &[u8], txn: &RoTxn) -> Result<()> {
+        let word = std::str::from_utf8(word)?;
+        let keys = match deladd {
+            DelAdd::Deletion => &mut self.deletions,
+            DelAdd::Addition => &mut self.additions,
+        };
+        keys.extend(self.stemmers.stems_fst_keys(self.index, txn, word)?);
+        Ok(())
+    }
+
+    /// Registers a word whose documents changed, its stems change when it appeared in or
+    /// disappeared from a field of the `localizedAttributes` setting.
+    pub fn register_updated_word(&mut self, word: &[u8], rtxn: &RoTxn, wtxn: &RoTxn) -> Result<()> {
+        if self.stemmers.is_localized() {
+            self.register_word(DelAdd::Deletion, word, rtxn)?;
+            self.register_word(DelAdd::Addition, word, wtxn)?;
+        }
+        Ok(())
+    }
+
+    pub fn build(self) -> Result<Set<Vec<u8>>> {
+        let Self { index: _, stemmers: _, stems_fst, mut additions, mut deletions } = self;
+        additions.sort_unstable();
+        additions.dedup();
+        deletions.sort_unstable();
+        deletions.dedup();
+        let additions = Set::from_iter(additions)?;
+        let deletions = Set::from_iter(deletions)?;
+
+        // the keys of an updated word can be both deleted and added back
+        let difference = stems_fst.op().add(&deletions).difference();
+        let mut builder = SetBuilder::memory();
+        builder
+            .extend_stream(fst::set::OpBuilder::new().add(difference).add(&additions).union())?;
+        Ok(builder.into_set())
+    }
+}
@@ -34,6 +34,7 @@ make_enum_progress! {
 make_enum_progress! {
     pub enum PostProcessingWords {
         WordFst,
+        NgramsFst,
         WordPrefixDocids,
         ExactWordPrefixDocids,
         WordPrefixFieldIdDocids,
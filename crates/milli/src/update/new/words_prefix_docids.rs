@@ -10,6 +10,7 @@ use roaring::MultiOps;
 use tempfile::spooled_tempfile;
 use thread_local::ThreadLocal;
 
+use super::indexer::check_must_stop_processing;
 use super::ref_cell_ext::RefCellExt as _;
 use crate::heed_codec::StrBEU16Codec;
 use crate::update::GrenadParameters;
@@ -34,22 +35,30 @@ impl WordPrefixDocids {
         }
     }
 
-    fn execute(
+    fn execute<MSP>(
         self,
         wtxn: &mut heed::RwTxn,
         prefix_to_compute: &BTreeSet<Prefix>,
         prefix_to_delete: &BTreeSet<Prefix>,
-    ) -> Result<()> {
+        must_stop_processing: &MSP,
+    ) -> Result<()>
+    where
+        MSP: Fn() -> bool + Sync,
+    {
         delete_prefixes(wtxn, &self.prefix_database, prefix_to_delete)?;
-        self.recompute_modified_prefixes(wtxn, prefix_to_compute)
+        self.recompute_modified_prefixes(wtxn, prefix_to_compute, must_stop_processing)
     }
 
     #[tracing::instrument(level = "trace", skip_all, target = "indexing::prefix")]
-    fn recompute_modified_prefixes(
+    fn recompute_modified_prefixes<MSP>(
         &self,
         wtxn: &mut RwTxn,
         prefixes: &BTreeSet<Prefix>,
-    ) -> Result<()> {
+        must_stop_processing: &MSP,
+    ) -> Result<()>
+    where
+        MSP: Fn() -> bool + Sync,
+    {
         // We fetch the docids associated to the newly added word prefix fst only.
         // And collect the CboRoaringBitmaps pointers in an HashMap.
         let frozen = FrozenPrefixBitmaps::from_prefixes(self.database, wtxn, prefixes)?;
@@ -58,6 +67,7 @@ impl WordPrefixDocids {
         // of them and *serialize* them into files. There is one file by CPU.
         let local_entries = ThreadLocal::with_capacity(rayon::current_num_threads());
         prefixes.into_par_iter().map(AsRef::as_ref).try_for_each(|prefix| {
+            check_must_stop_processing(must_stop_processing)?;
             let refcell = local_entries.get_or(|| {
                 let file = BufWriter::new(spooled_tempfile(
                     self.max_memory_by_thread.unwrap_or(usize::MAX),
@@ -78,7 +88,9 @@ impl WordPrefixDocids {
             buffer.clear();
             CboRoaringBitmapCodec::serialize_into_vec(&output, buffer);
             index.push(PrefixEntry { prefix, serialized_length: buffer.len() });
-            file.write_all(buffer)
+            file.write_all(buffer)?;
+
+            Result::Ok(())
         })?;
 
         drop(frozen);
@@ -170,22 +182,30 @@ impl WordPrefixIntegerDocids {
         }
     }
 
-    fn execute(
+    fn execute<MSP>(
         self,
         wtxn: &mut heed::RwTxn,
         prefix_to_compute: &BTreeSet<Prefix>,
         prefix_to_delete: &BTreeSet<Prefix>,
-    ) -> Result<()> {
+        must_stop_processing: &MSP,
+    ) -> Result<()>
+    where
+        MSP: Fn() -> bool + Sync,
+    {
         delete_prefixes(wtxn, &self.prefix_database, prefix_to_delete)?;
-        self.recompute_modified_prefixes(wtxn, prefix_to_compute)
+        self.recompute_modified_prefixes(wtxn, prefix_to_compute, must_stop_processing)
     }
 
     #[tracing::instrument(level = "trace", skip_all, target = "indexing::prefix")]
-    fn recompute_modified_prefixes(
+    fn recompute_modified_prefixes<MSP>(
         &self,
         wtxn: &mut RwTxn,
         prefixes: &BTreeSet<Prefix>,
-    ) -> Result<()> {
+        must_stop_processing: &MSP,
+    ) -> Result<()>
+    where
+        MSP: Fn() -> bool + Sync,
+    {
         // We fetch the docids associated to the newly added word prefix fst only.
         // And collect the CboRoaringBitmaps pointers in an HashMap.
         let frozen = FrozenPrefixIntegerBitmaps::from_prefixes(self.database, wtxn, prefixes)?;
@@ -194,6 +214,7 @@ impl WordPrefixIntegerDocids {
         // of them and *serialize* them into files. There is one file by CPU.
         let local_entries = ThreadLocal::with_capacity(rayon::current_num_threads());
         prefixes.into_par_iter().map(AsRef::as_ref).try_for_each(|prefix| {
+            check_must_stop_processing(must_stop_processing)?;
             let refcell = local_entries.get_or(|| {
                 let file = BufWriter::new(spooled_tempfile(
                     self.max_memory_by_thread.unwrap_or(usize::MAX),
@@ -323,65 +344,81 @@ fn delete_prefixes(
 }
 
 #[tracing::instrument(level = "trace", skip_all, target = "indexing::prefix")]
-pub fn compute_word_prefix_docids(
+pub fn compute_word_prefix_docids<MSP>(
     wtxn: &mut RwTxn,
     index: &Index,
     prefix_to_compute: &BTreeSet<Prefix>,
     prefix_to_delete: &BTreeSet<Prefix>,
     grenad_parameters: &GrenadParameters,
-) -> Result<()> {
+    must_stop_processing: &MSP,
+) -> Result<()>
+where
+    MSP: Fn() -> bool + Sync,
+{
     WordPrefixDocids::new(
         index.word_docids.remap_key_type(),
         index.word_prefix_docids.remap_key_type(),
         grenad_parameters,
     )
-    .execute(wtxn, prefix_to_compute, prefix_to_delete)
+    .execute(wtxn, prefix_to_compute, prefix_to_delete, must_stop_processing)
 }
 
 #[tracing::instrument(level = "trace", skip_all, target = "indexing::prefix")]
-pub fn compute_exact_word_prefix_docids(
+pub fn compute_exact_word_prefix_docids<MSP>(
     wtxn: &mut RwTxn,
     index: &Index,
     prefix_to_compute: &BTreeSet<Prefix>,
     prefix_to_delete: &BTreeSet<Prefix>,
     grenad_parameters: &GrenadParameters,
-) -> Result<()> {
+    must_stop_processing: &MSP,
+) -> Result<()>
+where
+    MSP: Fn() -> bool + Sync,
+{
     WordPrefixDocids::new(
         index.exact_word_docids.remap_key_type(),
         index.exact_word_prefix_docids.remap_key_type(),
         grenad_parameters,
     )
-    .execute(wtxn, prefix_to_compute, prefix_to_delete)
+    .execute(wtxn, prefix_to_compute, prefix_to_delete, must_stop_processing)
 }
 
 #[tracing::instrument(level = "trace", skip_all, target = "indexing::prefix")]
-pub fn compute_word_prefix_fid_docids(
+pub fn compute_word_prefix_fid_docids<MSP>(
     wtxn: &mut RwTxn,
     index: &Index,
     prefix_to_compute: &BTreeSet<Prefix>,
     prefix_to_delete: &BTreeSet<Prefix>,
     grenad_parameters: &GrenadParameters,
-) -> Result<()> {
+    must_stop_processing: &MSP,
+) -> Result<()>
+where
+    MSP: Fn() -> bool + Sync,
+{
     WordPrefixIntegerDocids::new(
         index.word_fid_docids.remap_key_type(),
         index.word_prefix_fid_docids.remap_key_type(),
         grenad_parameters,
     )
-    .execute(wtxn, prefix_to_compute, prefix_to_delete)
+    .execute(wtxn, prefix_to_compute, prefix_to_delete, must_stop_processing)
 }
 
 #[tracing::instrument(level = "trace", skip_all, target = "indexing::prefix")]
-pub fn compute_word_prefix_position_docids(
+pub fn compute_word_prefix_position_docids<MSP>(
     wtxn: &mut RwTxn,
     index: &Index,
     prefix_to_compute: &BTreeSet<Prefix>,
     prefix_to_delete: &BTreeSet<Prefix>,
     grenad_parameters: &GrenadParameters,
-) -> Result<()> {
+    must_stop_processing: &MSP,
+) -> Result<()>
+where
+    MSP: Fn() -> bool + Sync,
+{
     WordPrefixIntegerDocids::new(
         index.word_position_docids.remap_key_type(),
         index.word_prefix_position_docids.remap_key_type(),
         grenad_parameters,
     )
-    .execute(wtxn, prefix_to_compute, prefix_to_delete)
+    .execute(wtxn, prefix_to_compute, prefix_to_delete, must_stop_processing)
 }
@@ -1,10 +1,10 @@
 use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
-use std::convert::TryInto;
 use std::num::NonZeroUsize;
 use std::result::Result as StdResult;
+use std::str::FromStr;
 use std::sync::Arc;
 
-use charabia::{Normalize, Tokenizer, TokenizerBuilder};
+use charabia::{Language, Normalize, Tokenizer, TokenizerBuilder};
 use deserr::{DeserializeError, Deserr};
 use itertools::{merge_join_by, EitherOrBoth, Itertools};
 use roaring::RoaringBitmap;
@@ -15,6 +15,7 @@ use super::del_add::{DelAdd, DelAddOperation};
 use super::index_documents::{IndexDocumentsConfig, Transform};
 use super::IndexerConfig;
 use crate::attribute_patterns::PatternMatch;
+use crate::attributes_typo_tolerance::AttributeTypoTolerance;
 use crate::constants::RESERVED_GEO_FIELD_NAME;
 use crate::criterion::Criterion;
 use crate::disabled_typos_terms::DisabledTyposTerms;
@@ -25,17 +26,26 @@ use crate::index::{
     IndexEmbeddingConfig, PrefixSearch, DEFAULT_MIN_WORD_LEN_ONE_TYPO,
     DEFAULT_MIN_WORD_LEN_TWO_TYPOS,
 };
+use crate::ngram_attributes::NgramAttribute;
 use crate::order_by_map::OrderByMap;
-use crate::prompt::default_max_bytes;
+use crate::prompt::{default_max_bytes, Prompt};
 use crate::proximity::ProximityPrecision;
+use crate::query_rules::QueryRule;
+use crate::synonym_rules::{expand_synonym_rules, SynonymRule};
 use crate::update::index_documents::IndexDocumentsMethod;
+use crate::update::new::indexer::{
+    recompute_ngrams_fst_from_word_fid_docids, recompute_stems_fst_from_words_fst,
+};
 use crate::update::{IndexDocuments, UpdateIndexingStep};
 use crate::vector::settings::{
     EmbedderAction, EmbedderSource, EmbeddingSettings, NestingContext, ReindexAction,
     SubEmbeddingSettings, WriteBackToDocuments,
 };
 use crate::vector::{Embedder, EmbeddingConfig, EmbeddingConfigs};
-use crate::{FieldId, FilterableAttributesRule, Index, LocalizedAttributesRule, Result};
+use crate::{
+    is_faceted, AscDesc, FieldId, FilterableAttributesRule, Index, LocalizedAttributesRule, Member,
+    Result, Weight,
+};
 
 #[derive(Debug, Clone, PartialEq, Eq, Copy)]
 pub enum Setting<T> {
@@ -168,6 +178,7 @@ pub struct Settings<'a, 't, 'i> {
     dictionary: Setting<BTreeSet<String>>,
     distinct_field: Setting<String>,
     synonyms: Setting<BTreeMap<String, Vec<String>>>,
+    synonym_rules: Setting<Vec<SynonymRule>>,
     primary_key: Setting<String>,
     authorize_typos: Setting<bool>,
     disable_on_numbers: Setting<bool>,
@@ -185,6 +196,11 @@ pub struct Settings<'a, 't, 'i> {
     localized_attributes_rules: Setting<Vec<LocalizedAttributesRule>>,
     prefix_search: Setting<PrefixSearch>,
     facet_search: Setting<bool>,
+    stemming: Setting<Vec<Language>>,
+    searchable_attributes_weights: Setting<BTreeMap<String, Weight>>,
+    attributes_typo_tolerance: Setting<BTreeMap<String, AttributeTypoTolerance>>,
+    ngram_attributes: Setting<BTreeMap<String, NgramAttribute>>,
+    query_rules: Setting<Vec<QueryRule>>,
 }
 
 impl<'a, 't, 'i> Settings<'a, 't, 'i> {
@@ -207,6 +223,7 @@ impl<'a, 't, 'i> Settings<'a, 't, 'i> {
             dictionary: Setting::NotSet,
             distinct_field: Setting::NotSet,
             synonyms: Setting::NotSet,
+            synonym_rules: Setting::NotSet,
             primary_key: Setting::NotSet,
             authorize_typos: Setting::NotSet,
             disable_on_numbers: Setting::NotSet,
@@ -223,6 +240,11 @@ impl<'a, 't, 'i> Settings<'a, 't, 'i> {
             localized_attributes_rules: Setting::NotSet,
             prefix_search: Setting::NotSet,
             facet_search: Setting::NotSet,
+            stemming: Setting::NotSet,
+            searchable_attributes_weights: Setting::NotSet,
+            attributes_typo_tolerance: Setting::NotSet,
+            ngram_attributes: Setting::NotSet,
+            query_rules: Setting::NotSet,
             indexer_config,
         }
     }
@@ -325,6 +347,14 @@ impl<'a, 't, 'i> Settings<'a, 't, 'i> {
         self.synonyms = if synonyms.is_empty() { Setting::Reset } else { Setting::Set(synonyms) }
     }
 
+    pub fn reset_synonym_rules(&mut self) {
+        self.synonym_rules = Setting::Reset;
+    }
+
+    pub fn set_synonym_rules(&mut self, rules: Vec<SynonymRule>) {
+        self.synonym_rules = if rules.is_empty() { Setting::Reset } else { Setting::Set(rules) }
+    }
+
     pub fn reset_primary_key(&mut self) {
         self.primary_key = Setting::Reset;
     }
@@ -453,6 +483,51 @@ impl<'a, 't, 'i> Settings<'a, 't, 'i> {
         self.facet_search = Setting::Reset;
     }
 
+    pub fn set_stemming(&mut self, value: Vec<Language>) {
+        self.stemming = Setting::Set(value);
+    }
+
+    pub fn reset_stemming(&mut self) {
+        self.stemming = Setting::Reset;
+    }
+
+    pub fn set_searchable_attributes_weights(&mut self, weights: BTreeMap<String, Weight>) {
+        self.searchable_attributes_weights =
+            if weights.is_empty() { Setting::Reset } else { Setting::Set(weights) }
+    }
+
+    pub fn reset_searchable_attributes_weights(&mut self) {
+        self.searchable_attributes_weights = Setting::Reset;
+    }
+
+    pub fn set_attributes_typo_tolerance(
+        &mut self,
+        rules: BTreeMap<String, AttributeTypoTolerance>,
+    ) {
+        self.attributes_typo_tolerance =
+            if rules.is_empty() { Setting::Reset } else { Setting::Set(rules) }
+    }
+
+    pub fn reset_attributes_typo_tolerance(&mut self) {
+        self.attributes_typo_tolerance = Setting::Reset;
+    }
+
+    pub fn set_ngram_attributes(&mut self, rules: BTreeMap<String, NgramAttribute>) {
+        self.ngram_attributes = if rules.is_empty() { Setting::Reset } else { Setting::Set(rules) }
+    }
+
+    pub fn reset_ngram_attributes(&mut self) {
+        self.ngram_attributes = Setting::Reset;
+    }
+
+    pub fn set_query_rules(&mut self, rules: Vec<QueryRule>) {
+        self.query_rules = if rules.is_empty() { Setting::Reset } else { Setting::Set(rules) }
+    }
+
+    pub fn reset_query_rules(&mut self) {
+        self.query_rules = Setting::Reset;
+    }
+
     #[tracing::instrument(
         level = "trace"
         skip(self, progress_callback, should_abort, settings_diff),
@@ -606,6 +681,9 @@ impl<'a, 't, 'i> Settings<'a, 't, 'i> {
         if changes && self.synonyms == Setting::NotSet {
             self.synonyms = Setting::Set(self.index.user_defined_synonyms(self.wtxn)?);
         }
+        if changes && self.synonym_rules == Setting::NotSet {
+            self.synonym_rules = Setting::Set(self.index.synonym_rules(self.wtxn)?);
+        }
 
         Ok(changes)
     }
@@ -631,6 +709,9 @@ impl<'a, 't, 'i> Settings<'a, 't, 'i> {
         if changes && self.synonyms == Setting::NotSet {
             self.synonyms = Setting::Set(self.index.user_defined_synonyms(self.wtxn)?);
         }
+        if changes && self.synonym_rules == Setting::NotSet {
+            self.synonym_rules = Setting::Set(self.index.synonym_rules(self.wtxn)?);
+        }
 
         Ok(changes)
     }
@@ -656,48 +737,55 @@ impl<'a, 't, 'i> Settings<'a, 't, 'i> {
         if changes && self.synonyms == Setting::NotSet {
             self.synonyms = Setting::Set(self.index.user_defined_synonyms(self.wtxn)?);
         }
+        if changes && self.synonym_rules == Setting::NotSet {
+            self.synonym_rules = Setting::Set(self.index.synonym_rules(self.wtxn)?);
+        }
 
         Ok(changes)
     }
 
     fn update_synonyms(&mut self) -> Result<bool> {
-        match self.synonyms {
-            Setting::Set(ref user_synonyms) => {
-                fn normalize(tokenizer: &Tokenizer<'_>, text: &str) -> Vec<String> {
-                    tokenizer
-                        .tokenize(text)
-                        .filter_map(|token| {
-                            if token.is_word() && !token.lemma().is_empty() {
-                                Some(token.lemma().to_string())
-                            } else {
-                                None
-                            }
-                        })
-                        .collect::<Vec<_>>()
-                }
+        if self.synonyms == Setting::NotSet && self.synonym_rules == Setting::NotSet {
+            return Ok(false);
+        }
 
-                let mut builder = TokenizerBuilder::new();
-                let stop_words = self.index.stop_words(self.wtxn)?;
-                if let Some(ref stop_words) = stop_words {
-                    builder.stop_words(stop_words);
-                }
+        fn normalize(tokenizer: &Tokenizer<'_>, text: &str) -> Vec<String> {
+            tokenizer
+                .tokenize(text)
+                .filter_map(|token| {
+                    if token.is_word() && !token.lemma().is_empty() {
+                        Some(token.lemma().to_string())
+                    } else {
+                        None
+                    }
+                })
+                .collect::<Vec<_>>()
+        }
 
-                let separators = self.index.allowed_separators(self.wtxn)?;
-                let separators: Option<Vec<_>> =
-                    separators.as_ref().map(|x| x.iter().map(String::as_str).collect());
-                if let Some(ref separators) = separators {
-                    builder.separators(separators);
-                }
+        let mut builder = TokenizerBuilder::new();
+        let stop_words = self.index.stop_words(self.wtxn)?;
+        if let Some(ref stop_words) = stop_words {
+            builder.stop_words(stop_words);
+        }
 
-                let dictionary = self.index.dictionary(self.wtxn)?;
-                let dictionary: Option<Vec<_>> =
-                    dictionary.as_ref().map(|x| x.iter().map(String::as_str).collect());
-                if let Some(ref dictionary) = dictionary {
-                    builder.words_dict(dictionary);
-                }
+        let separators = self.index.allowed_separators(self.wtxn)?;
+        let separators: Option<Vec<_>> =
+            separators.as_ref().map(|x| x.iter().map(String::as_str).collect());
+        if let Some(ref separators) = separators {
+            builder.separators(separators);
+        }
 
-                let tokenizer = builder.build();
+        let dictionary = self.index.dictionary(self.wtxn)?;
+        let dictionary: Option<Vec<_>> =
+            dictionary.as_ref().map(|x| x.iter().map(String::as_str).collect());
+        if let Some(ref dictionary) = dictionary {
+            builder.words_dict(dictionary);
+        }
+
+        let tokenizer = builder.build();
 
+        let synonyms_changed = match self.synonyms {
+            Setting::Set(ref user_synonyms) => {
                 let mut new_synonyms = HashMap::new();
                 for (word, synonyms) in user_synonyms {
                     // Normalize both the word and associated synonyms.
@@ -726,14 +814,35 @@ impl<'a, 't, 'i> Settings<'a, 't, 'i> {
 
                 if new_synonyms != old_synonyms {
                     self.index.put_synonyms(self.wtxn, &new_synonyms, user_synonyms)?;
-                    Ok(true)
+                    true
                 } else {
-                    Ok(false)
+                    false
                 }
             }
-            Setting::Reset => Ok(self.index.delete_synonyms(self.wtxn)?),
-            Setting::NotSet => Ok(false),
-        }
+            Setting::Reset => self.index.delete_synonyms(self.wtxn)?,
+            Setting::NotSet => false,
+        };
+
+        let synonym_rules_changed = match self.synonym_rules {
+            Setting::Set(ref rules) => {
+                let weighted_synonyms =
+                    expand_synonym_rules(rules, |text| normalize(&tokenizer, text));
+
+                let old_weighted_synonyms = self.index.weighted_synonyms(self.wtxn)?;
+                let old_rules = self.index.synonym_rules(self.wtxn)?;
+
+                if weighted_synonyms != old_weighted_synonyms || *rules != old_rules {
+                    self.index.put_synonym_rules(self.wtxn, &weighted_synonyms, rules)?;
+                    true
+                } else {
+                    false
+                }
+            }
+            Setting::Reset => self.index.delete_synonym_rules(self.wtxn)?,
+            Setting::NotSet => false,
+        };
+
+        Ok(synonyms_changed || synonym_rules_changed)
     }
 
     fn update_exact_attributes(&mut self) -> Result<bool> {
@@ -1005,6 +1114,26 @@ impl<'a, 't, 'i> Settings<'a, 't, 'i> {
         Ok(changed)
     }
 
+    fn update_stemming(&mut self) -> Result<bool> {
+        let changed = match &mut self.stemming {
+            Setting::Set(new) => {
+                new.sort_unstable();
+                new.dedup();
+                let old = self.index.stemming(self.wtxn)?;
+                if old.as_deref() == Some(new.as_slice()) {
+                    false
+                } else {
+                    self.index.put_stemming(self.wtxn, new)?;
+                    old.is_some_and(|old| !old.is_empty()) || !new.is_empty()
+                }
+            }
+            Setting::Reset => self.index.delete_stemming(self.wtxn)?,
+            Setting::NotSet => false,
+        };
+
+        Ok(changed)
+    }
+
     fn update_facet_search(&mut self) -> Result<bool> {
         let changed = match self.facet_search {
             Setting::Set(new) => {
@@ -1222,6 +1351,109 @@ impl<'a, 't, 'i> Settings<'a, 't, 'i> {
         Ok(changed)
     }
 
+    fn update_searchable_attributes_weights(&mut self) -> Result<bool> {
+        let changed = match &self.searchable_attributes_weights {
+            Setting::Set(new) => {
+                let old = self.index.searchable_attributes_weights(self.wtxn)?;
+                if &old == new {
+                    false
+                } else {
+                    self.index.put_searchable_attributes_weights(self.wtxn, new)?;
+                    true
+                }
+            }
+            Setting::Reset => self.index.delete_searchable_attributes_weights(self.wtxn)?,
+            Setting::NotSet => false,
+        };
+
+        Ok(changed)
+    }
+
+    fn update_attributes_typo_tolerance(&mut self) -> Result<bool> {
+        let changed = match &self.attributes_typo_tolerance {
+            Setting::Set(new) => {
+                let old = self.index.attributes_typo_tolerance(self.wtxn)?;
+                if &old == new {
+                    false
+                } else {
+                    self.index.put_attributes_typo_tolerance(self.wtxn, new)?;
+                    true
+                }
+            }
+            Setting::Reset => self.index.delete_attributes_typo_tolerance(self.wtxn)?,
+            Setting::NotSet => false,
+        };
+
+        Ok(changed)
+    }
+
+    fn update_ngram_attributes(&mut self) -> Result<bool> {
+        let changed = match &self.ngram_attributes {
+            Setting::Set(new) => {
+                let old = self.index.ngram_attributes(self.wtxn)?;
+                if &old == new {
+                    false
+                } else {
+                    self.index.put_ngram_attributes(self.wtxn, new)?;
+                    true
+                }
+            }
+            Setting::Reset => self.index.delete_ngram_attributes(self.wtxn)?,
+            Setting::NotSet => false,
+        };
+
+        Ok(changed)
+    }
+
+    fn update_query_rules(&mut self) -> Result<bool> {
+        let changed = match &self.query_rules {
+            Setting::Set(new) => {
+                let old = self.index.query_rules(self.wtxn)?;
+                if &old == new {
+                    false
+                } else {
+                    self.index.put_query_rules(self.wtxn, new)?;
+                    true
+                }
+            }
+            Setting::Reset => self.index.delete_query_rules(self.wtxn)?,
+            Setting::NotSet => false,
+        };
+
+        Ok(changed)
+    }
+
+    /// The query rules must only sort on sortable attributes, whichever of them changed.
+    fn validate_query_rules_sort(&mut self) -> Result<()> {
+        if matches!(self.query_rules, Setting::NotSet)
+            && matches!(self.sortable_fields, Setting::NotSet)
+        {
+            return Ok(());
+        }
+
+        let sortable_fields = self.index.sortable_fields(self.wtxn)?;
+        for (rule_index, rule) in self.index.query_rules(self.wtxn)?.into_iter().enumerate() {
+            for sort in rule.sort.iter().flatten() {
+                // the syntax of the sort criteria is validated along with the rest of the rule
+                let Ok(asc_desc) = AscDesc::from_str(sort) else { continue };
+                let (field, sortable) = match asc_desc.member() {
+                    Member::Field(field) => (field.as_str(), is_faceted(field, &sortable_fields)),
+                    Member::Geo(_) => {
+                        (RESERVED_GEO_FIELD_NAME, sortable_fields.contains(RESERVED_GEO_FIELD_NAME))
+                    }
+                };
+                if !sortable {
+                    return Err(UserError::InvalidQueryRule {
+                        rule_index,
+                        reason: format!("sorts on `{field}`, which is not a sortable attribute"),
+                    }
+                    .into());
+                }
+            }
+        }
+        Ok(())
+    }
+
     fn update_localized_attributes_rules(&mut self) -> Result<()> {
         match &self.localized_attributes_rules {
             Setting::Set(new) => {
@@ -1260,10 +1492,16 @@ impl<'a, 't, 'i> Settings<'a, 't, 'i> {
         self.update_sort_facet_values_by()?;
         self.update_pagination_max_total_hits()?;
         self.update_search_cutoff()?;
+        self.update_searchable_attributes_weights()?;
+        self.update_attributes_typo_tolerance()?;
+        let stemming_changed = self.update_stemming()?;
+        let ngram_attributes_changed = self.update_ngram_attributes()?;
+        self.update_query_rules()?;
 
         // could trigger re-indexing
         self.update_filterable()?;
         self.update_sortable()?;
+        self.validate_query_rules_sort()?;
         self.update_stop_words()?;
         self.update_non_separator_tokens()?;
         self.update_separator_tokens()?;
@@ -1281,6 +1519,12 @@ impl<'a, 't, 'i> Settings<'a, 't, 'i> {
 
         let mut new_inner_settings = InnerIndexSettings::from_index(self.index, self.wtxn, None)?;
         new_inner_settings.recompute_searchables(self.wtxn, self.index)?;
+        if let Some(cache) = &self.indexer_config.embedding_cache {
+            let document_caches = cache.for_embedders(&self.index.embedding_configs(self.wtxn)?);
+            new_inner_settings.embedding_configs =
+                std::mem::take(&mut new_inner_settings.embedding_configs)
+                    .with_document_caches(document_caches);
+        }
 
         let primary_key_id = self
             .index
@@ -1295,10 +1539,26 @@ impl<'a, 't, 'i> Settings<'a, 't, 'i> {
             settings_update_only,
         );
 
-        if inner_settings_diff.any_reindexing_needed() {
+        let reindexing_needed = inner_settings_diff.any_reindexing_needed();
+        if reindexing_needed {
             self.reindex(&progress_callback, &should_abort, inner_settings_diff)?;
         }
 
+        // the stems FST only depends on the indexed words and the stemmed languages
+        if stemming_changed || reindexing_needed {
+            recompute_stems_fst_from_words_fst(self.index, self.wtxn)?;
+        }
+
+        // the n-grams FST only depends on the indexed words and the n-gram attributes
+        if ngram_attributes_changed || reindexing_needed {
+            recompute_ngrams_fst_from_word_fid_docids(
+                self.index,
+                self.wtxn,
+                self.indexer_config.max_memory,
+                &should_abort,
+            )?;
+        }
+
         Ok(())
     }
 }
@@ -1668,10 +1928,12 @@ fn embedders(embedding_configs: Vec<IndexEmbeddingConfig>) -> Result<EmbeddingCo
         .map(
             |IndexEmbeddingConfig {
                  name,
-                 config: EmbeddingConfig { embedder_options, prompt, quantized },
+                 config: EmbeddingConfig { embedder_options, prompt, quantized, .. },
                  ..
              }| {
-                let prompt = Arc::new(prompt.try_into().map_err(crate::Error::from)?);
+                let prompt = Arc::new(
+                    Prompt::for_embedder(prompt, &embedder_options).map_err(crate::Error::from)?,
+                );
 
                 let embedder = Arc::new(
                     // cache_cap: no cache needed for indexing purposes
@@ -1717,6 +1979,64 @@ fn validate_prompt(
     }
 }
 
+fn validate_fragments(
+    name: &str,
+    indexing_fragment: &Setting<serde_json::Value>,
+    search_fragment: &Setting<serde_json::Value>,
+) -> Result<()> {
+    if let Some(indexing_fragment) = indexing_fragment.as_ref().set() {
+        crate::prompt::Prompt::from_fragment(indexing_fragment.to_owned())
+            .map_err(|inner| UserError::InvalidPromptForEmbeddings(name.to_owned(), inner))?;
+    }
+    if let Some(search_fragment) = search_fragment.as_ref().set() {
+        crate::vector::json_template::JsonTemplate::new(search_fragment.to_owned()).map_err(
+            |error| {
+                crate::UserError::VectorEmbeddingError(
+                    crate::vector::error::NewEmbedderError::rest_could_not_parse_template(format!(
+                        "in `searchFragment`: {error}"
+                    ))
+                    .into(),
+                )
+            },
+        )?;
+    }
+    Ok(())
+}
+
+fn validate_chunking(
+    name: &str,
+    chunking: &Setting<crate::vector::chunking::ChunkingSettings>,
+    indexing_fragment: &Setting<serde_json::Value>,
+) -> Result<()> {
+    if let Some(chunking) = chunking.as_ref().set() {
+        if indexing_fragment.as_ref().set().is_some() {
+            return Err(UserError::InvalidSettingsEmbedder {
+                embedder_name: name.to_owned(),
+                message: "`chunking` cannot be used with an `indexingFragment`".to_string(),
+            }
+            .into());
+        }
+        chunking.validate().map_err(|message| UserError::InvalidSettingsEmbedder {
+            embedder_name: format!("{name}.chunking"),
+            message,
+        })?;
+    }
+    Ok(())
+}
+
+fn validate_reranker(
+    name: &str,
+    reranker: &Setting<crate::vector::rerank::RerankerSettings>,
+) -> Result<()> {
+    if let Some(reranker) = reranker.as_ref().set() {
+        reranker.validate().map_err(|message| UserError::InvalidSettingsEmbedder {
+            embedder_name: format!("{name}.reranker"),
+            message,
+        })?;
+    }
+    Ok(())
+}
+
 pub fn validate_embedding_settings(
     settings: Setting<EmbeddingSettings>,
     name: &str,
@@ -1731,6 +2051,7 @@ pub fn validate_embedding_settings(
         dimensions,
         document_template,
         document_template_max_bytes,
+        chunking,
         url,
         request,
         response,
@@ -1739,9 +2060,15 @@ pub fn validate_embedding_settings(
         distribution,
         headers,
         binary_quantized: binary_quantize,
+        indexing_fragment,
+        search_fragment,
+        reranker,
     } = settings;
 
     let document_template = validate_prompt(name, document_template, document_template_max_bytes)?;
+    validate_fragments(name, &indexing_fragment, &search_fragment)?;
+    validate_chunking(name, &chunking, &indexing_fragment)?;
+    validate_reranker(name, &reranker)?;
 
     if let Some(0) = dimensions.set() {
         return Err(crate::error::UserError::InvalidSettingsDimensions {
@@ -1778,6 +2105,7 @@ pub fn validate_embedding_settings(
             dimensions,
             document_template,
             document_template_max_bytes,
+            chunking,
             url,
             request,
             response,
@@ -1786,6 +2114,9 @@ pub fn validate_embedding_settings(
             distribution,
             headers,
             binary_quantized: binary_quantize,
+            indexing_fragment,
+            search_fragment,
+            reranker,
         }));
     };
     EmbeddingSettings::check_settings(
@@ -1802,11 +2133,15 @@ pub fn validate_embedding_settings(
         &response,
         &document_template,
         &document_template_max_bytes,
+        &chunking,
         &headers,
+        &indexing_fragment,
+        &search_fragment,
         &search_embedder,
         &indexing_embedder,
         &binary_quantize,
         &distribution,
+        &reranker,
     )?;
     match inferred_source {
         EmbedderSource::OpenAi => {
@@ -1880,11 +2215,18 @@ pub fn validate_embedding_settings(
                         &embedder.response,
                         &embedder.document_template,
                         &embedder.document_template_max_bytes,
+                        // chunking is not available for sub-embedders
+                        &Setting::NotSet,
                         &embedder.headers,
+                        // fragments are not available for sub-embedders
+                        &Setting::NotSet,
+                        &Setting::NotSet,
                         &search_embedder,
                         &indexing_embedder,
                         &embedder.binary_quantized,
                         &embedder.distribution,
+                        // rerankers are not available for sub-embedders
+                        &Setting::NotSet,
                     )?;
                 } else {
                     return Err(UserError::MissingSourceForNested {
@@ -1935,11 +2277,18 @@ pub fn validate_embedding_settings(
                         &embedder.response,
                         &embedder.document_template,
                         &embedder.document_template_max_bytes,
+                        // chunking is not available for sub-embedders
+                        &Setting::NotSet,
                         &embedder.headers,
+                        // fragments are not available for sub-embedders
+                        &Setting::NotSet,
+                        &Setting::NotSet,
                         &search_embedder,
                         &indexing_embedder,
                         &embedder.binary_quantized,
                         &embedder.distribution,
+                        // rerankers are not available for sub-embedders
+                        &Setting::NotSet,
                     )?;
                 } else {
                     return Err(UserError::MissingSourceForNested {
@@ -1962,6 +2311,7 @@ pub fn validate_embedding_settings(
         dimensions,
         document_template,
         document_template_max_bytes,
+        chunking,
         url,
         request,
         response,
@@ -1970,6 +2320,9 @@ pub fn validate_embedding_settings(
         distribution,
         headers,
         binary_quantized: binary_quantize,
+        indexing_fragment,
+        search_fragment,
+        reranker,
     }))
 }
 
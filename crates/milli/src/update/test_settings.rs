@@ -584,6 +584,190 @@ fn set_and_reset_synonyms() {
     assert!(result.documents_ids.is_empty());
 }
 
+#[test]
+fn set_and_reset_synonym_rules() {
+    let index = TempIndex::new();
+
+    index
+        .add_documents(documents!([
+            { "id": 0, "text": "iphone for sale" },
+            { "id": 1, "text": "apple phone for sale" },
+            { "id": 2, "text": "couch for sale" },
+            { "id": 3, "text": "sofa for sale" },
+        ]))
+        .unwrap();
+
+    let rules: Vec<SynonymRule> = serde_json::from_value(serde_json::json!([
+        { "input": "iphone", "synonyms": ["apple phone"] },
+        { "synonyms": ["couch", "sofa"], "weight": 0.5 },
+    ]))
+    .unwrap();
+    index
+        .update_settings(|settings| {
+            settings.set_synonym_rules(rules.clone());
+        })
+        .unwrap();
+
+    let rtxn = index.read_txn().unwrap();
+    assert_eq!(index.synonym_rules(&rtxn).unwrap(), rules);
+    // one-way rules are not expanded from the synonyms to the input
+    let result = index.search(&rtxn).query("iphone").execute().unwrap();
+    assert_eq!(result.documents_ids, vec![0, 1]);
+    let result = index.search(&rtxn).query("apple phone").execute().unwrap();
+    assert_eq!(result.documents_ids, vec![1]);
+    // the words of an equivalence group are synonyms of each other, the original word first
+    let result = index.search(&rtxn).query("couch").execute().unwrap();
+    assert_eq!(result.documents_ids, vec![2, 3]);
+    let result = index.search(&rtxn).query("sofa").execute().unwrap();
+    assert_eq!(result.documents_ids, vec![3, 2]);
+    drop(rtxn);
+
+    index
+        .update_settings(|settings| {
+            settings.reset_synonym_rules();
+        })
+        .unwrap();
+
+    let rtxn = index.read_txn().unwrap();
+    assert!(index.synonym_rules(&rtxn).unwrap().is_empty());
+    assert!(index.weighted_synonyms(&rtxn).unwrap().is_empty());
+    let result = index.search(&rtxn).query("sofa").execute().unwrap();
+    assert_eq!(result.documents_ids, vec![3]);
+}
+
+#[test]
+fn synonym_rules_weights_rank_the_synonyms() {
+    let index = TempIndex::new();
+
+    index
+        .add_documents(documents!([
+            { "id": 0, "text": "settee for sale" },
+            { "id": 1, "text": "sofa for sale" },
+            { "id": 2, "text": "divan for sale" },
+            { "id": 3, "text": "couch for sale" },
+        ]))
+        .unwrap();
+
+    let rules: Vec<SynonymRule> = serde_json::from_value(serde_json::json!([
+        { "input": "couch", "synonyms": ["divan"] },
+        { "input": "couch", "synonyms": ["sofa"], "weight": 0.8 },
+        { "input": "couch", "synonyms": ["settee"], "weight": 0.5 },
+    ]))
+    .unwrap();
+    index
+        .update_settings(|settings| {
+            settings.set_synonym_rules(rules);
+        })
+        .unwrap();
+
+    // the lower the weight, the lower the synonym is ranked
+    let rtxn = index.read_txn().unwrap();
+    let result = index.search(&rtxn).query("couch").execute().unwrap();
+    assert_eq!(result.documents_ids, vec![3, 2, 1, 0]);
+    drop(rtxn);
+
+    // even without the typo ranking rule
+    index
+        .update_settings(|settings| {
+            settings.set_criteria(vec![
+                Criterion::Words,
+                Criterion::Proximity,
+                Criterion::Attribute,
+                Criterion::Exactness,
+            ]);
+        })
+        .unwrap();
+
+    let rtxn = index.read_txn().unwrap();
+    let result = index.search(&rtxn).query("couch").execute().unwrap();
+    assert_eq!(result.documents_ids, vec![3, 2, 1, 0]);
+}
+
+#[test]
+fn set_and_reset_stemming() {
+    let index = TempIndex::new();
+
+    index
+        .add_documents(documents!([
+            { "id": 0, "text": "the runners are running" },
+            { "id": 1, "text": "she runs every morning" },
+            { "id": 2, "text": "a quiet walk" },
+        ]))
+        .unwrap();
+
+    let rtxn = index.read_txn().unwrap();
+    let result = index.search(&rtxn).query("running").execute().unwrap();
+    assert_eq!(result.documents_ids, vec![0]);
+    drop(rtxn);
+
+    // Stemming the existing documents
+    index
+        .update_settings(|settings| {
+            settings.set_stemming(vec![Language::Eng]);
+        })
+        .unwrap();
+
+    let rtxn = index.read_txn().unwrap();
+    assert_eq!(index.stemming(&rtxn).unwrap(), Some(vec![Language::Eng]));
+    // the exact match is ranked before the stem match
+    let result = index.search(&rtxn).query("running").execute().unwrap();
+    assert_eq!(result.documents_ids, vec![0, 1]);
+    drop(rtxn);
+
+    // Stemming the new documents, a word that is already a stem is found too
+    index.add_documents(documents!([{ "id": 3, "text": "we will run" }])).unwrap();
+
+    let rtxn = index.read_txn().unwrap();
+    let result = index.search(&rtxn).query("running").execute().unwrap();
+    assert_eq!(result.documents_ids, vec![0, 1, 3]);
+    drop(rtxn);
+
+    // Reset the stemming
+    index
+        .update_settings(|settings| {
+            settings.reset_stemming();
+        })
+        .unwrap();
+
+    let rtxn = index.read_txn().unwrap();
+    assert_eq!(index.stemming(&rtxn).unwrap(), None);
+    assert!(index.stems_fst(&rtxn).unwrap().is_empty());
+    let result = index.search(&rtxn).query("running").execute().unwrap();
+    assert_eq!(result.documents_ids, vec![0]);
+}
+
+#[test]
+fn stem_words_in_the_languages_of_their_field() {
+    let index = TempIndex::new();
+
+    index
+        .update_settings(|settings| {
+            settings.set_stemming(vec![Language::Eng, Language::Fra]);
+            settings.set_localized_attributes_rules(vec![LocalizedAttributesRule::new(
+                vec![S("text_fr")],
+                vec![Language::Fra],
+            )]);
+        })
+        .unwrap();
+    index
+        .add_documents(documents!([
+            { "id": 0, "text": "the runners" },
+            { "id": 1, "text_fr": "les coureurs" },
+        ]))
+        .unwrap();
+
+    let rtxn = index.read_txn().unwrap();
+    let keys = index.stems_fst(&rtxn).unwrap().stream().into_strs().unwrap();
+    let has_key = |code: &str, word: &str| {
+        keys.iter()
+            .any(|key| key.starts_with(&format!("{code}\0")) && key.ends_with(&format!("\0{word}")))
+    };
+    // the words of a localized field are only stemmed in its languages, the others in all of them
+    assert!(has_key("eng", "runners"));
+    assert!(has_key("fra", "coureurs"));
+    assert!(!has_key("eng", "coureurs"));
+}
+
 #[test]
 fn thai_synonyms() {
     let index = TempIndex::new();
@@ -881,6 +1065,7 @@ fn test_correct_settings_init() {
                 dictionary,
                 distinct_field,
                 synonyms,
+                synonym_rules,
                 primary_key,
                 authorize_typos,
                 min_word_len_two_typos,
@@ -897,6 +1082,11 @@ fn test_correct_settings_init() {
                 prefix_search,
                 facet_search,
                 disable_on_numbers,
+                stemming,
+                searchable_attributes_weights,
+                attributes_typo_tolerance,
+                ngram_attributes,
+                query_rules,
             } = settings;
             assert!(matches!(searchable_fields, Setting::NotSet));
             assert!(matches!(displayed_fields, Setting::NotSet));
@@ -909,6 +1099,7 @@ fn test_correct_settings_init() {
             assert!(matches!(dictionary, Setting::NotSet));
             assert!(matches!(distinct_field, Setting::NotSet));
             assert!(matches!(synonyms, Setting::NotSet));
+            assert!(matches!(synonym_rules, Setting::NotSet));
             assert!(matches!(primary_key, Setting::NotSet));
             assert!(matches!(authorize_typos, Setting::NotSet));
             assert!(matches!(min_word_len_two_typos, Setting::NotSet));
@@ -925,6 +1116,11 @@ fn test_correct_settings_init() {
             assert!(matches!(prefix_search, Setting::NotSet));
             assert!(matches!(facet_search, Setting::NotSet));
             assert!(matches!(disable_on_numbers, Setting::NotSet));
+            assert!(matches!(stemming, Setting::NotSet));
+            assert!(matches!(searchable_attributes_weights, Setting::NotSet));
+            assert!(matches!(attributes_typo_tolerance, Setting::NotSet));
+            assert!(matches!(ngram_attributes, Setting::NotSet));
+            assert!(matches!(query_rules, Setting::NotSet));
         })
         .unwrap();
 }
@@ -0,0 +1,204 @@
+//! Chunking splits the text rendered from a document into several parts that are embedded separately.
+//!
+//! A chunked document is stored with one vector per chunk, and is ranked by its chunk closest to the query.
+
+use deserr::Deserr;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+/// Number of tokens in a chunk when `maxTokens` is not specified.
+pub const DEFAULT_MAX_TOKENS: usize = 256;
+
+/// Maximum number of chunks of a document, further chunks are ignored.
+///
+/// This is the maximum number of vectors that can be stored for a single document.
+pub const MAX_CHUNKS: usize = u8::MAX as usize;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Deserr, ToSchema)]
+#[serde(deny_unknown_fields, rename_all = "camelCase")]
+#[deserr(rename_all = camelCase, deny_unknown_fields)]
+pub struct ChunkingSettings {
+    /// How the rendered texts are split into chunks.
+    ///
+    /// - `tokens` splits texts into windows of `maxTokens` tokens, consecutive windows sharing `overlap` tokens
+    /// - `paragraphs` splits texts on blank lines, paragraphs longer than `maxTokens` are further split as with `tokens`
+    pub strategy: ChunkingStrategy,
+    /// The maximum number of tokens of a chunk, where tokens are the whitespace-separated words of the text.
+    ///
+    /// Defaults to 256.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[deserr(default)]
+    pub max_tokens: Option<usize>,
+    /// The number of tokens repeated at the start of a chunk from the end of the previous chunk.
+    ///
+    /// Defaults to 0, must be smaller than `maxTokens`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[deserr(default)]
+    pub overlap: Option<usize>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Deserr, ToSchema)]
+#[serde(deny_unknown_fields, rename_all = "camelCase")]
+#[deserr(rename_all = camelCase, deny_unknown_fields)]
+pub enum ChunkingStrategy {
+    Tokens,
+    Paragraphs,
+}
+
+impl ChunkingSettings {
+    pub fn max_tokens(&self) -> usize {
+        self.max_tokens.unwrap_or(DEFAULT_MAX_TOKENS)
+    }
+
+    pub fn overlap(&self) -> usize {
+        self.overlap.unwrap_or_default()
+    }
+
+    /// Checks that the settings describe a valid chunking, returning an error message otherwise.
+    pub fn validate(&self) -> Result<(), String> {
+        if self.max_tokens == Some(0) {
+            return Err("`maxTokens` cannot be zero".to_string());
+        }
+        let (max_tokens, overlap) = (self.max_tokens(), self.overlap());
+        if overlap >= max_tokens {
+            return Err(format!(
+                "`overlap` must be smaller than `maxTokens`, found overlap {overlap} for maxTokens {max_tokens}"
+            ));
+        }
+        Ok(())
+    }
+
+    /// Splits the text into chunks, that are slices of the original text.
+    ///
+    /// Always returns at least one chunk, and at most [`MAX_CHUNKS`].
+    pub fn chunk<'t>(&self, text: &'t str) -> Vec<&'t str> {
+        let mut chunks = Vec::new();
+        match self.strategy {
+            ChunkingStrategy::Tokens => self.chunk_tokens(text, &mut chunks),
+            ChunkingStrategy::Paragraphs => {
+                for paragraph in text.split("\n\n") {
+                    self.chunk_tokens(paragraph, &mut chunks);
+                    if chunks.len() >= MAX_CHUNKS {
+                        break;
+                    }
+                }
+            }
+        }
+
+        if chunks.is_empty() {
+            // texts without any token are embedded as-is, like when chunking is disabled
+            chunks.push(text);
+        }
+        chunks.truncate(MAX_CHUNKS);
+        chunks
+    }
+
+    fn chunk_tokens<'t>(&self, text: &'t str, chunks: &mut Vec<&'t str>) {
+        let tokens = token_spans(text);
+        let max_tokens = self.max_tokens();
+        // validated to be at least 1
+        let step = max_tokens.saturating_sub(self.overlap()).max(1);
+
+        let mut start = 0;
+        while start < tokens.len() && chunks.len() < MAX_CHUNKS {
+            let end = (start + max_tokens).min(tokens.len());
+            chunks.push(&text[tokens[start].0..tokens[end - 1].1]);
+            if end == tokens.len() {
+                break;
+            }
+            start += step;
+        }
+    }
+}
+
+/// Returns the byte ranges of the whitespace-separated tokens of the text.
+fn token_spans(text: &str) -> Vec<(usize, usize)> {
+    let mut spans = Vec::new();
+    let mut token_start = None;
+    for (index, c) in text.char_indices() {
+        match (c.is_whitespace(), token_start) {
+            (true, Some(start)) => {
+                spans.push((start, index));
+                token_start = None;
+            }
+            (false, None) => token_start = Some(index),
+            _ => {}
+        }
+    }
+    if let Some(start) = token_start {
+        spans.push((start, text.len()));
+    }
+    spans
+}
+
+#[cfg(test)]
+mod test {
+    use super::{ChunkingSettings, ChunkingStrategy, MAX_CHUNKS};
+
+    fn settings(
+        strategy: ChunkingStrategy,
+        max_tokens: usize,
+        overlap: Option<usize>,
+    ) -> ChunkingSettings {
+        ChunkingSettings { strategy, max_tokens: Some(max_tokens), overlap }
+    }
+
+    #[test]
+    fn chunk_by_tokens() {
+        let chunking = settings(ChunkingStrategy::Tokens, 3, None);
+        assert_eq!(
+            chunking.chunk("the quick  brown fox\njumps over the lazy dog"),
+            vec!["the quick  brown", "fox\njumps over", "the lazy dog"]
+        );
+        assert_eq!(chunking.chunk("  kefir  "), vec!["kefir"]);
+    }
+
+    #[test]
+    fn chunk_by_tokens_with_overlap() {
+        let chunking = settings(ChunkingStrategy::Tokens, 3, Some(1));
+        assert_eq!(
+            chunking.chunk("the quick brown fox jumps over the lazy dog"),
+            vec!["the quick brown", "brown fox jumps", "jumps over the", "the lazy dog"]
+        );
+        // the last window is not repeated when it is fully contained in the previous one
+        assert_eq!(chunking.chunk("the quick brown"), vec!["the quick brown"]);
+    }
+
+    #[test]
+    fn chunk_by_paragraphs() {
+        let chunking = settings(ChunkingStrategy::Paragraphs, 4, None);
+        assert_eq!(
+            chunking.chunk(
+                "title: Intel\n\noverview: a good doggo\n\n\n\nbreed: a very good boy, the best"
+            ),
+            vec!["title: Intel", "overview: a good doggo", "breed: a very good", "boy, the best"]
+        );
+    }
+
+    #[test]
+    fn chunk_empty_text() {
+        let chunking = settings(ChunkingStrategy::Paragraphs, 4, None);
+        assert_eq!(chunking.chunk(""), vec![""]);
+        assert_eq!(chunking.chunk(" \n\n "), vec![" \n\n "]);
+    }
+
+    #[test]
+    fn chunk_count_is_capped() {
+        let chunking = settings(ChunkingStrategy::Tokens, 1, None);
+        let text = "doggo ".repeat(MAX_CHUNKS * 2);
+        assert_eq!(chunking.chunk(&text).len(), MAX_CHUNKS);
+    }
+
+    #[test]
+    fn validate_settings() {
+        settings(ChunkingStrategy::Tokens, 3, Some(2)).validate().unwrap();
+        assert_eq!(
+            settings(ChunkingStrategy::Tokens, 0, None).validate().unwrap_err(),
+            "`maxTokens` cannot be zero"
+        );
+        assert_eq!(
+            settings(ChunkingStrategy::Tokens, 3, Some(3)).validate().unwrap_err(),
+            "`overlap` must be smaller than `maxTokens`, found overlap 3 for maxTokens 3"
+        );
+    }
+}
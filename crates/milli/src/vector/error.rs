@@ -95,6 +95,14 @@ pub enum EmbedErrorKind {
     RestNetwork(ureq::Transport),
     #[error("error extracting embeddings from the response:\n  - {0}")]
     RestExtractionError(String),
+    #[error("could not read a rendered fragment as JSON:\n  - {0}")]
+    RestFragmentDeserialization(serde_json::Error),
+    #[error("could not render the search fragment:\n  - {0}")]
+    RestSearchFragmentRender(liquid::Error),
+    #[error("expected a response containing {0} scores, got only {1}")]
+    RestResponseScoreCount(usize, usize),
+    #[error("error extracting scores from the response:\n  - {0}")]
+    RestScoreExtractionError(String),
     #[error("was expecting embeddings of dimension `{0}`, got embeddings of dimensions `{1}`")]
     UnexpectedDimension(usize, usize),
     #[error("no embedding was produced")]
@@ -110,6 +118,18 @@ fn option_info(info: Option<&str>, prefix: &str) -> String {
     }
 }
 
+impl EmbedErrorKind {
+    /// Whether the embedding server failed temporarily, so that embedding the same documents later may succeed.
+    pub fn is_transient(&self) -> bool {
+        matches!(
+            self,
+            EmbedErrorKind::RestNetwork(_)
+                | EmbedErrorKind::RestTooManyRequests(_)
+                | EmbedErrorKind::RestInternalServerError(..)
+        )
+    }
+}
+
 impl EmbedError {
     pub fn tokenize(inner: Box<dyn std::error::Error + Send + Sync>) -> Self {
         Self { kind: EmbedErrorKind::Tokenize(inner), fault: FaultSource::Runtime }
@@ -210,6 +230,25 @@ impl EmbedError {
     pub(crate) fn rest_extraction_error(error: String) -> EmbedError {
         Self { kind: EmbedErrorKind::RestExtractionError(error), fault: FaultSource::Runtime }
     }
+
+    pub(crate) fn rest_fragment_deserialization(error: serde_json::Error) -> EmbedError {
+        Self { kind: EmbedErrorKind::RestFragmentDeserialization(error), fault: FaultSource::Bug }
+    }
+
+    pub(crate) fn rest_search_fragment_render(error: liquid::Error) -> EmbedError {
+        Self { kind: EmbedErrorKind::RestSearchFragmentRender(error), fault: FaultSource::User }
+    }
+
+    pub(crate) fn rest_response_score_count(expected: usize, got: usize) -> EmbedError {
+        Self {
+            kind: EmbedErrorKind::RestResponseScoreCount(expected, got),
+            fault: FaultSource::Runtime,
+        }
+    }
+
+    pub(crate) fn rest_score_extraction_error(error: String) -> EmbedError {
+        Self { kind: EmbedErrorKind::RestScoreExtractionError(error), fault: FaultSource::Runtime }
+    }
 }
 
 #[derive(Debug, thiserror::Error)]
@@ -712,6 +712,88 @@ impl ValueTemplate {
     }
 }
 
+/// A JSON value where each string is a liquid template.
+///
+/// Rendering the template renders each of its strings against the same liquid context,
+/// leaving the structure of the value, its keys and its non-string values untouched.
+#[derive(Debug)]
+pub struct JsonTemplate {
+    value: Value,
+    // templates of the strings of `value`, in depth-first order
+    templates: Vec<liquid::Template>,
+}
+
+impl JsonTemplate {
+    /// Parses each string of `value` as a liquid template.
+    pub fn new(value: Value) -> Result<Self, liquid::Error> {
+        let parser = liquid::ParserBuilder::with_stdlib().build().unwrap();
+        let mut templates = Vec::new();
+        Self::parse_strings(&value, &parser, &mut templates)?;
+        Ok(Self { value, templates })
+    }
+
+    fn parse_strings(
+        value: &Value,
+        parser: &liquid::Parser,
+        templates: &mut Vec<liquid::Template>,
+    ) -> Result<(), liquid::Error> {
+        match value {
+            Value::String(s) => templates.push(parser.parse(s)?),
+            Value::Array(values) => {
+                for value in values {
+                    Self::parse_strings(value, parser, templates)?;
+                }
+            }
+            Value::Object(object) => {
+                for value in object.values() {
+                    Self::parse_strings(value, parser, templates)?;
+                }
+            }
+            Value::Null | Value::Bool(_) | Value::Number(_) => {}
+        }
+        Ok(())
+    }
+
+    /// Renders the template against the passed liquid context.
+    pub fn render(&self, context: &dyn liquid::ObjectView) -> Result<Value, liquid::Error> {
+        let mut templates = self.templates.iter();
+        Self::render_value(&self.value, &mut templates, context)
+    }
+
+    fn render_value<'t>(
+        value: &Value,
+        templates: &mut impl Iterator<Item = &'t liquid::Template>,
+        context: &dyn liquid::ObjectView,
+    ) -> Result<Value, liquid::Error> {
+        Ok(match value {
+            Value::String(_) => {
+                // unwrap: there is exactly one template per string, in the same order
+                Value::String(templates.next().unwrap().render(context)?)
+            }
+            Value::Array(values) => Value::Array(
+                values
+                    .iter()
+                    .map(|value| Self::render_value(value, templates, context))
+                    .collect::<Result<_, _>>()?,
+            ),
+            Value::Object(object) => Value::Object(
+                object
+                    .iter()
+                    .map(|(key, value)| {
+                        Ok((key.clone(), Self::render_value(value, templates, context)?))
+                    })
+                    .collect::<Result<Map<_, _>, liquid::Error>>()?,
+            ),
+            value @ (Value::Null | Value::Bool(_) | Value::Number(_)) => value.clone(),
+        })
+    }
+
+    /// The JSON value this template was created from.
+    pub fn template(&self) -> &Value {
+        &self.value
+    }
+}
+
 fn inject_value(rendered: &mut Value, injection_path: &Vec<PathComponent>, injected_value: Value) {
     let mut current_value = rendered;
     for injection_component in injection_path {
@@ -838,7 +920,7 @@ impl<T> ExtractionResultErrorContext<T> for Result<T, ExtractionErrorKind> {
 mod test {
     use serde_json::{json, Value};
 
-    use super::{PathComponent, TemplateParsingError, ValueTemplate};
+    use super::{JsonTemplate, PathComponent, TemplateParsingError, ValueTemplate};
 
     fn new_template(template: Value) -> Result<ValueTemplate, TemplateParsingError> {
         ValueTemplate::new(template, "{{text}}", "{{..}}")
@@ -967,4 +1049,34 @@ mod test {
         let extracted_values: Vec<Value> = basic.extract(rendered).unwrap();
         assert_eq!(extracted_values, injected_values);
     }
+
+    #[test]
+    fn json_template_renders_strings() {
+        let template = JsonTemplate::new(json!({
+            "type": "image_url",
+            "image_url": { "url": "{{doc.image}}", "detail": "low" },
+            "tags": ["{{doc.title}}", 42, null, true]
+        }))
+        .unwrap();
+
+        let rendered = template
+            .render(&liquid::object!({
+                "doc": { "image": "https://example.com/cat.jpg", "title": "a cat" }
+            }))
+            .unwrap();
+
+        assert_eq!(
+            rendered,
+            json!({
+                "type": "image_url",
+                "image_url": { "url": "https://example.com/cat.jpg", "detail": "low" },
+                "tags": ["a cat", 42, null, true]
+            })
+        );
+    }
+
+    #[test]
+    fn json_template_syntax_error() {
+        assert!(JsonTemplate::new(json!({ "text": "{{q" })).is_err());
+    }
 }
@@ -17,6 +17,7 @@ use crate::progress::Progress;
 use crate::prompt::{Prompt, PromptData};
 use crate::ThreadPoolNoAbort;
 
+pub mod chunking;
 pub mod composite;
 pub mod error;
 pub mod hf;
@@ -24,6 +25,8 @@ pub mod json_template;
 pub mod manual;
 pub mod openai;
 pub mod parsed_vectors;
+pub mod persistent_cache;
+pub mod rerank;
 pub mod settings;
 
 pub mod ollama;
@@ -358,17 +361,25 @@ impl ArroyWrapper {
         Ok(results)
     }
 
+    /// Returns the items nearest to the vector, sorted by distance.
+    ///
+    /// Along with its distance, each item comes with the index of its vector that is at that distance.
+    /// An item with several vectors can appear several times.
+    ///
+    /// `search_k` is the number of tree nodes inspected by the search, defaulting to arroy's own
+    /// `limit * number of trees`. Raising it improves recall at the cost of latency.
     pub fn nns_by_vector(
         &self,
         rtxn: &RoTxn,
         vector: &[f32],
         limit: usize,
+        search_k: Option<NonZeroUsize>,
         filter: Option<&RoaringBitmap>,
-    ) -> Result<Vec<(ItemId, f32)>, arroy::Error> {
+    ) -> Result<Vec<(ItemId, f32, usize)>, arroy::Error> {
         if self.quantized {
-            self._nns_by_vector(rtxn, self.quantized_db(), vector, limit, filter)
+            self._nns_by_vector(rtxn, self.quantized_db(), vector, limit, search_k, filter)
         } else {
-            self._nns_by_vector(rtxn, self.angular_db(), vector, limit, filter)
+            self._nns_by_vector(rtxn, self.angular_db(), vector, limit, search_k, filter)
         }
     }
 
@@ -378,21 +389,71 @@ impl ArroyWrapper {
         db: arroy::Database<D>,
         vector: &[f32],
         limit: usize,
+        search_k: Option<NonZeroUsize>,
         filter: Option<&RoaringBitmap>,
-    ) -> Result<Vec<(ItemId, f32)>, arroy::Error> {
+    ) -> Result<Vec<(ItemId, f32, usize)>, arroy::Error> {
         let mut results = Vec::new();
 
-        for reader in self.readers(rtxn, db) {
+        for (vector_index, reader) in self.readers(rtxn, db).enumerate() {
             let reader = reader?;
             let mut searcher = reader.nns(limit);
             if let Some(filter) = filter {
                 searcher.candidates(filter);
             }
+            if let Some(search_k) = search_k {
+                searcher.search_k(search_k);
+            }
 
-            results.append(&mut searcher.by_vector(rtxn, vector)?);
+            results.extend(
+                searcher
+                    .by_vector(rtxn, vector)?
+                    .into_iter()
+                    .map(|(item, distance)| (item, distance, vector_index)),
+            );
         }
 
-        results.sort_unstable_by_key(|(_, distance)| OrderedFloat(*distance));
+        results.sort_unstable_by_key(|(_, distance, _)| OrderedFloat(*distance));
+
+        Ok(results)
+    }
+
+    /// Returns all the candidates sorted by their distance to the vector, without using the trees.
+    ///
+    /// Every vector of the candidates is read and compared to the target, so the results are exact,
+    /// unlike [`Self::nns_by_vector`] that can miss neighbors when the candidates are few and scattered.
+    /// The distances are the cosine distances arroy computes for non-quantized embedders.
+    /// The distances of binary quantized embedders are not the cosine distances of their stored
+    /// vectors, so these embedders are searched with the trees, like [`Self::nns_by_vector`] does.
+    pub fn nns_by_vector_exhaustive(
+        &self,
+        rtxn: &RoTxn,
+        vector: &[f32],
+        candidates: &RoaringBitmap,
+    ) -> Result<Vec<(ItemId, f32, usize)>, arroy::Error> {
+        if self.quantized {
+            let limit = candidates.len() as usize;
+            return self._nns_by_vector(
+                rtxn,
+                self.quantized_db(),
+                vector,
+                limit,
+                None,
+                Some(candidates),
+            );
+        }
+
+        let mut results = Vec::new();
+
+        for (vector_index, reader) in self.readers(rtxn, self.angular_db()).enumerate() {
+            let reader = reader?;
+            for item in candidates {
+                if let Some(item_vector) = reader.item_vector(rtxn, item)? {
+                    results.push((item, cosine_distance(vector, &item_vector), vector_index));
+                }
+            }
+        }
+
+        results.sort_unstable_by_key(|(_, distance, _)| OrderedFloat(*distance));
 
         Ok(results)
     }
@@ -606,6 +667,9 @@ pub struct EmbeddingConfig {
     pub prompt: PromptData,
     /// If this embedder is binary quantized
     pub quantized: Option<bool>,
+    /// Reranker applied after hybrid searches using this embedder
+    #[serde(default)]
+    pub reranker: Option<rerank::RerankerSettings>,
     // TODO: add metrics and anything needed
 }
 
@@ -619,29 +683,45 @@ impl EmbeddingConfig {
 ///
 /// Each configuration is mapped to a name.
 #[derive(Clone, Default)]
-pub struct EmbeddingConfigs(HashMap<String, (Arc<Embedder>, Arc<Prompt>, bool)>);
+pub struct EmbeddingConfigs {
+    embedders: HashMap<String, (Arc<Embedder>, Arc<Prompt>, bool)>,
+    document_caches: HashMap<String, persistent_cache::EmbedderCache>,
+}
 
 impl EmbeddingConfigs {
     /// Create the map from its internal component.s
     pub fn new(data: HashMap<String, (Arc<Embedder>, Arc<Prompt>, bool)>) -> Self {
-        Self(data)
+        Self { embedders: data, document_caches: HashMap::new() }
+    }
+
+    /// Uses the persistent cache when embedding the documents, for the embedders with a cache.
+    pub fn with_document_caches(
+        self,
+        document_caches: HashMap<String, persistent_cache::EmbedderCache>,
+    ) -> Self {
+        Self { document_caches, ..self }
     }
 
     pub fn contains(&self, name: &str) -> bool {
-        self.0.contains_key(name)
+        self.embedders.contains_key(name)
     }
 
     /// Get an embedder configuration and template from its name.
     pub fn get(&self, name: &str) -> Option<(Arc<Embedder>, Arc<Prompt>, bool)> {
-        self.0.get(name).cloned()
+        self.embedders.get(name).cloned()
+    }
+
+    /// Get the persistent cache of the embeddings of the documents for an embedder, if any.
+    pub fn document_cache(&self, name: &str) -> Option<&persistent_cache::EmbedderCache> {
+        self.document_caches.get(name)
     }
 
     pub fn inner_as_ref(&self) -> &HashMap<String, (Arc<Embedder>, Arc<Prompt>, bool)> {
-        &self.0
+        &self.embedders
     }
 
     pub fn into_inner(self) -> HashMap<String, (Arc<Embedder>, Arc<Prompt>, bool)> {
-        self.0
+        self.embedders
     }
 }
 
@@ -652,7 +732,7 @@ impl IntoIterator for EmbeddingConfigs {
         std::collections::hash_map::IntoIter<String, (Arc<Embedder>, Arc<Prompt>, bool)>;
 
     fn into_iter(self) -> Self::IntoIter {
-        self.0.into_iter()
+        self.embedders.into_iter()
     }
 }
 
@@ -673,6 +753,20 @@ impl Default for EmbedderOptions {
     }
 }
 
+impl EmbedderOptions {
+    /// The JSON fragment rendered from documents in place of the document template, if any.
+    pub fn indexing_fragment(&self) -> Option<&serde_json::Value> {
+        match self {
+            EmbedderOptions::Rest(options) => options.indexing_fragment.as_ref(),
+            EmbedderOptions::HuggingFace(_)
+            | EmbedderOptions::OpenAi(_)
+            | EmbedderOptions::Ollama(_)
+            | EmbedderOptions::UserProvided(_)
+            | EmbedderOptions::Composite(_) => None,
+        }
+    }
+}
+
 impl Embedder {
     /// Spawns a new embedder built from its options.
     pub fn new(
@@ -726,10 +820,7 @@ impl Embedder {
                 embedder.embed(&[text], deadline)?.pop().ok_or_else(EmbedError::missing_embedding)
             }
             Embedder::UserProvided(embedder) => embedder.embed_one(text),
-            Embedder::Rest(embedder) => embedder
-                .embed_ref(&[text], deadline)?
-                .pop()
-                .ok_or_else(EmbedError::missing_embedding),
+            Embedder::Rest(embedder) => embedder.embed_search(text, deadline),
             Embedder::Composite(embedder) => embedder.search.embed_one(text, deadline),
         }?;
 
@@ -974,6 +1065,22 @@ pub const fn is_cuda_enabled() -> bool {
     cfg!(feature = "cuda")
 }
 
+/// The cosine distance as returned by arroy, between 0 (same direction) and 1 (opposite directions).
+fn cosine_distance(a: &[f32], b: &[f32]) -> f32 {
+    let (mut dot, mut norm_a, mut norm_b) = (0.0, 0.0, 0.0);
+    for (x, y) in a.iter().zip(b) {
+        dot += x * y;
+        norm_a += x * x;
+        norm_b += y * y;
+    }
+    let norms = (norm_a * norm_b).sqrt();
+    if norms <= f32::EPSILON {
+        return 0.0;
+    }
+    let cosine = (dot / norms).clamp(-1.0, 1.0);
+    (1.0 - cosine) / 2.0
+}
+
 pub fn arroy_db_range_for_embedder(embedder_id: u8) -> impl Iterator<Item = u16> {
     let embedder_id = (embedder_id as u16) << 8;
 
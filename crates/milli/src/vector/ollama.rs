@@ -70,6 +70,8 @@ impl EmbedderOptions {
             request,
             response,
             headers: Default::default(),
+            indexing_fragment: None,
+            search_fragment: None,
         })
     }
 }
@@ -200,6 +200,8 @@ impl Embedder {
                     ]
                 }),
                 headers: Default::default(),
+                indexing_fragment: None,
+                search_fragment: None,
             },
             cache_cap,
             super::rest::ConfigurationSource::OpenAi,
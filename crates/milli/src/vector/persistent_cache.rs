@@ -0,0 +1,484 @@
+//! A persistent cache of the embeddings computed when indexing documents.
+//!
+//! Reindexing the documents, e.g. after a settings change, renders the same texts again for most of them.
+//! The cache is keyed by both the embedder options and the rendered text, so that an unchanged document is
+//! not sent again to the embedder as long as the embedder options don't change.
+//!
+//! The extractors only read from the cache: the embeddings they compute and the entries they hit are sent
+//! to a single writer thread, which evicts the least recently used entries when the cache is almost full.
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, OnceLock};
+
+use crossbeam_channel::{Receiver, Sender, TrySendError};
+use heed::types::Bytes;
+use heed::{Database, Env, EnvOpenOptions, MdbError, RwTxn, WithoutTls};
+
+use super::error::EmbedError;
+use super::{Embedder, EmbedderOptions, Embedding};
+use crate::index::IndexEmbeddingConfig;
+use crate::{ThreadPoolNoAbort, BEU64};
+
+/// The number of writes waiting for the writer thread, the next ones are dropped.
+const MAX_PENDING_WRITES: usize = 10_000;
+/// The maximum number of writes applied in a single write transaction.
+const MAX_WRITES_PER_TXN: usize = 1_000;
+
+/// The cache of the embeddings of the documents, shared by all the indexes.
+///
+/// The LMDB environment is only opened on first use, so that creating the cache never touches the disk.
+pub struct PersistentEmbeddingCache {
+    path: PathBuf,
+    map_size: usize,
+    store: OnceLock<Option<Store>>,
+    hits: AtomicU64,
+    misses: AtomicU64,
+}
+
+struct Store {
+    databases: Databases,
+    writes: Sender<CacheWrite>,
+}
+
+#[derive(Clone)]
+struct Databases {
+    env: Env<WithoutTls>,
+    map_size: u64,
+    embeddings: Database<Bytes, Bytes>,
+    /// The last use of each entry.
+    last_uses: Database<Bytes, BEU64>,
+    /// The entries by last use, the first one is the least recently used.
+    entries_by_use: Database<BEU64, Bytes>,
+}
+
+/// A change to the cache, applied by the writer thread.
+enum CacheWrite {
+    Put {
+        key: [u8; 16],
+        value: Vec<u8>,
+    },
+    /// Marks an entry as recently used.
+    Touch {
+        key: [u8; 16],
+    },
+}
+
+/// Statistics about the persistent embedding cache.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PersistentEmbeddingCacheStats {
+    /// Number of embeddings in the cache.
+    pub entries: u64,
+    /// Size used by the cache on disk, in bytes.
+    pub size: u64,
+    /// Number of texts whose embedding was retrieved from the cache since the start of the engine.
+    pub hits: u64,
+    /// Number of texts that had to be embedded since the start of the engine.
+    pub misses: u64,
+}
+
+impl std::fmt::Debug for PersistentEmbeddingCache {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PersistentEmbeddingCache")
+            .field("path", &self.path)
+            .field("map_size", &self.map_size)
+            .finish_non_exhaustive()
+    }
+}
+
+impl PersistentEmbeddingCache {
+    pub fn new(path: PathBuf, map_size: usize) -> Self {
+        Self {
+            path,
+            map_size,
+            store: OnceLock::new(),
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+        }
+    }
+
+    /// The part of the cache dedicated to the embedder with the given options.
+    pub fn for_embedder(self: &Arc<Self>, options: &EmbedderOptions) -> EmbedderCache {
+        // serializing rather than hashing the options keeps the key stable across versions of the engine
+        let mut options = serde_json::to_value(options).unwrap_or_default();
+        // the api key doesn't change the embeddings and must not be derivable from the cache
+        remove_api_keys(&mut options);
+        let options = serde_json::to_vec(&options).unwrap_or_default();
+        EmbedderCache { cache: self.clone(), embedder_key: fxhash::hash64(&options) }
+    }
+
+    /// The parts of the cache dedicated to each of the embedders of an index.
+    pub fn for_embedders(
+        self: &Arc<Self>,
+        embedding_configs: &[IndexEmbeddingConfig],
+    ) -> HashMap<String, EmbedderCache> {
+        embedding_configs
+            .iter()
+            .map(|config| (config.name.clone(), self.for_embedder(&config.config.embedder_options)))
+            .collect()
+    }
+
+    pub fn stats(&self) -> crate::Result<PersistentEmbeddingCacheStats> {
+        let mut stats = PersistentEmbeddingCacheStats {
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+            ..Default::default()
+        };
+        if let Some(Store { databases, .. }) = self.store() {
+            let rtxn = databases.env.read_txn()?;
+            stats.entries = databases.embeddings.len(&rtxn)?;
+            stats.size = databases.env.non_free_pages_size()?;
+        }
+        Ok(stats)
+    }
+
+    /// Removes all the embeddings from the cache.
+    pub fn purge(&self) -> crate::Result<()> {
+        if let Some(Store { databases, .. }) = self.store() {
+            let mut wtxn = databases.env.write_txn()?;
+            databases.embeddings.clear(&mut wtxn)?;
+            databases.last_uses.clear(&mut wtxn)?;
+            databases.entries_by_use.clear(&mut wtxn)?;
+            wtxn.commit()?;
+        }
+        Ok(())
+    }
+
+    fn store(&self) -> Option<&Store> {
+        self.store
+            .get_or_init(|| match Store::open(&self.path, self.map_size) {
+                Ok(store) => Some(store),
+                Err(error) => {
+                    tracing::warn!(%error, path = %self.path.display(), "could not open the embedding cache, disabling it");
+                    None
+                }
+            })
+            .as_ref()
+    }
+
+    fn get_many(&self, embedder_key: u64, texts: &[&str]) -> heed::Result<Vec<Option<Embedding>>> {
+        let Some(store) = self.store() else {
+            return Ok(vec![None; texts.len()]);
+        };
+        let rtxn = store.databases.env.read_txn()?;
+        texts
+            .iter()
+            .map(|text| {
+                let key = entry_key(embedder_key, text);
+                let value = store.databases.embeddings.get(&rtxn, &key)?;
+                let embedding = value.and_then(|value| decode_entry(value, text));
+                if embedding.is_some() {
+                    store.send(CacheWrite::Touch { key });
+                }
+                Ok(embedding)
+            })
+            .collect()
+    }
+
+    fn put_many<'t>(
+        &self,
+        embedder_key: u64,
+        entries: impl Iterator<Item = (&'t str, &'t Embedding)>,
+    ) {
+        let Some(store) = self.store() else {
+            return;
+        };
+        for (text, embedding) in entries {
+            let mut value = Vec::new();
+            encode_entry(text, embedding, &mut value);
+            store.send(CacheWrite::Put { key: entry_key(embedder_key, text), value });
+        }
+    }
+}
+
+impl Store {
+    fn open(path: &std::path::Path, map_size: usize) -> heed::Result<Self> {
+        std::fs::create_dir_all(path)?;
+        // LMDB requires the map size to be a multiple of the page size
+        let map_size = map_size / page_size::get() * page_size::get();
+        let env = unsafe {
+            let env_options = EnvOpenOptions::new();
+            let mut env_options = env_options.read_txn_without_tls();
+            env_options.max_dbs(3).map_size(map_size).open(path)
+        }?;
+        let mut wtxn = env.write_txn()?;
+        let embeddings = env.create_database(&mut wtxn, Some("embeddings"))?;
+        let last_uses = env.create_database(&mut wtxn, Some("last-uses"))?;
+        let entries_by_use = env.create_database(&mut wtxn, Some("entries-by-use"))?;
+        wtxn.commit()?;
+
+        let databases =
+            Databases { env, map_size: map_size as u64, embeddings, last_uses, entries_by_use };
+        let (writes, receiver) = crossbeam_channel::bounded(MAX_PENDING_WRITES);
+        let writer = databases.clone();
+        std::thread::Builder::new()
+            .name(String::from("embedding-cache-writer"))
+            .spawn(move || writer.write_loop(receiver))?;
+        Ok(Self { databases, writes })
+    }
+
+    /// Sends the write to the writer thread, the cache is best-effort so the write is dropped
+    /// rather than slowing the indexing down when the writer thread is late.
+    fn send(&self, write: CacheWrite) {
+        if let Err(TrySendError::Disconnected(_)) = self.writes.try_send(write) {
+            tracing::warn!("the embedding cache writer thread stopped, the cache is not updated");
+        }
+    }
+}
+
+impl Databases {
+    fn write_loop(self, receiver: Receiver<CacheWrite>) {
+        let mut next_use = match self.next_use() {
+            Ok(next_use) => next_use,
+            Err(error) => {
+                tracing::warn!(%error, "could not read the embedding cache, disabling the writes");
+                return;
+            }
+        };
+        while let Ok(write) = receiver.recv() {
+            let writes: Vec<_> = std::iter::once(write)
+                .chain(receiver.try_iter().take(MAX_WRITES_PER_TXN))
+                .collect();
+            if let Err(error) = self.apply_writes(&writes, &mut next_use) {
+                tracing::warn!(%error, "could not write to the embedding cache");
+            }
+        }
+    }
+
+    fn next_use(&self) -> heed::Result<u64> {
+        let rtxn = self.env.read_txn()?;
+        Ok(self.entries_by_use.last(&rtxn)?.map_or(0, |(last_use, _)| last_use + 1))
+    }
+
+    /// Applies the writes, evicting the least recently used entries when the cache is almost full.
+    fn apply_writes(&self, writes: &[CacheWrite], next_use: &mut u64) -> heed::Result<()> {
+        if self.env.non_free_pages_size()? > self.map_size / 4 * 3 {
+            self.evict_fraction(4)?;
+        }
+        match self.try_apply_writes(writes, next_use) {
+            // the pages freed by the eviction are only reusable once it is committed
+            Err(heed::Error::Mdb(MdbError::MapFull)) => {
+                self.evict_fraction(2)?;
+                self.try_apply_writes(writes, next_use)
+            }
+            result => result,
+        }
+    }
+
+    fn try_apply_writes(&self, writes: &[CacheWrite], next_use: &mut u64) -> heed::Result<()> {
+        let mut wtxn = self.env.write_txn()?;
+        for write in writes {
+            match write {
+                CacheWrite::Put { key, value } => {
+                    self.embeddings.put(&mut wtxn, key, value)?;
+                    self.mark_used(&mut wtxn, key, next_use)?;
+                }
+                CacheWrite::Touch { key } => {
+                    // the entry may have been evicted or purged since it was read
+                    if self.last_uses.get(&wtxn, key)?.is_some() {
+                        self.mark_used(&mut wtxn, key, next_use)?;
+                    }
+                }
+            }
+        }
+        wtxn.commit()
+    }
+
+    fn mark_used(&self, wtxn: &mut RwTxn, key: &[u8], next_use: &mut u64) -> heed::Result<()> {
+        if let Some(last_use) = self.last_uses.get(wtxn, key)? {
+            self.entries_by_use.delete(wtxn, &last_use)?;
+        }
+        self.last_uses.put(wtxn, key, next_use)?;
+        self.entries_by_use.put(wtxn, next_use, key)?;
+        *next_use += 1;
+        Ok(())
+    }
+
+    /// Evicts `1 / denominator` of the entries, the least recently used first.
+    fn evict_fraction(&self, denominator: u64) -> heed::Result<()> {
+        let mut wtxn = self.env.write_txn()?;
+        let count = self.entries_by_use.len(&wtxn)?.div_ceil(denominator);
+        self.evict_least_recently_used(&mut wtxn, count)?;
+        wtxn.commit()
+    }
+
+    fn evict_least_recently_used(&self, wtxn: &mut RwTxn, count: u64) -> heed::Result<()> {
+        let evicted: Vec<(u64, Vec<u8>)> = self
+            .entries_by_use
+            .iter(wtxn)?
+            .take(count as usize)
+            .map(|result| result.map(|(last_use, key)| (last_use, key.to_vec())))
+            .collect::<heed::Result<_>>()?;
+        for (last_use, key) in evicted {
+            self.entries_by_use.delete(wtxn, &last_use)?;
+            self.last_uses.delete(wtxn, &key)?;
+            self.embeddings.delete(wtxn, &key)?;
+        }
+        Ok(())
+    }
+}
+
+/// The part of the persistent cache dedicated to a single embedder.
+#[derive(Debug, Clone)]
+pub struct EmbedderCache {
+    cache: Arc<PersistentEmbeddingCache>,
+    embedder_key: u64,
+}
+
+impl EmbedderCache {
+    /// Embeds the texts, only calling the embedder for the texts that are not in the cache.
+    ///
+    /// Failing to access the cache is not an error, the texts are embedded as if the cache was empty.
+    pub fn embed_index_ref(
+        &self,
+        embedder: &Embedder,
+        texts: &[&str],
+        threads: &ThreadPoolNoAbort,
+    ) -> Result<Vec<Embedding>, EmbedError> {
+        let mut embeddings =
+            self.cache.get_many(self.embedder_key, texts).unwrap_or_else(|error| {
+                tracing::warn!(%error, "could not read from the embedding cache");
+                vec![None; texts.len()]
+            });
+
+        let missing: Vec<&str> = texts
+            .iter()
+            .zip(&embeddings)
+            .filter_map(|(text, embedding)| embedding.is_none().then_some(*text))
+            .collect();
+        self.cache.hits.fetch_add((texts.len() - missing.len()) as u64, Ordering::Relaxed);
+        self.cache.misses.fetch_add(missing.len() as u64, Ordering::Relaxed);
+
+        if !missing.is_empty() {
+            let computed = embedder.embed_index_ref(&missing, threads)?;
+            self.cache.put_many(self.embedder_key, missing.iter().copied().zip(&computed));
+            let mut computed = computed.into_iter();
+            for embedding in embeddings.iter_mut().filter(|embedding| embedding.is_none()) {
+                *embedding = computed.next();
+            }
+        }
+
+        Ok(embeddings.into_iter().flatten().collect())
+    }
+
+    /// Owning variant of [`Self::embed_index_ref`], keeping the texts grouped as in the input.
+    pub fn embed_index(
+        &self,
+        embedder: &Embedder,
+        text_chunks: Vec<Vec<String>>,
+        threads: &ThreadPoolNoAbort,
+    ) -> Result<Vec<Vec<Embedding>>, EmbedError> {
+        let texts: Vec<&str> = text_chunks.iter().flatten().map(String::as_str).collect();
+        let mut embeddings = self.embed_index_ref(embedder, &texts, threads)?.into_iter();
+        Ok(text_chunks
+            .iter()
+            .map(|chunk| embeddings.by_ref().take(chunk.len()).collect())
+            .collect())
+    }
+}
+
+/// Removes the `api_key` fields of the embedder options, including the ones of the nested embedders.
+fn remove_api_keys(options: &mut serde_json::Value) {
+    match options {
+        serde_json::Value::Object(object) => {
+            object.remove("api_key");
+            object.values_mut().for_each(remove_api_keys);
+        }
+        serde_json::Value::Array(array) => array.iter_mut().for_each(remove_api_keys),
+        _ => (),
+    }
+}
+
+/// `[embedder key][text hash]`, both big-endian.
+fn entry_key(embedder_key: u64, text: &str) -> [u8; 16] {
+    let mut key = [0; 16];
+    key[..8].copy_from_slice(&embedder_key.to_be_bytes());
+    key[8..].copy_from_slice(&fxhash::hash64(text).to_be_bytes());
+    key
+}
+
+/// `[text length: u32][text][embedding: f32...]`, all little-endian.
+///
+/// The text is stored so that a collision of the text hashes is never mistaken for a hit.
+fn encode_entry(text: &str, embedding: &[f32], buffer: &mut Vec<u8>) {
+    buffer.clear();
+    buffer.extend_from_slice(&(text.len() as u32).to_le_bytes());
+    buffer.extend_from_slice(text.as_bytes());
+    for value in embedding {
+        buffer.extend_from_slice(&value.to_le_bytes());
+    }
+}
+
+fn decode_entry(value: &[u8], text: &str) -> Option<Embedding> {
+    let (len, value) = value.split_first_chunk::<4>()?;
+    let len = u32::from_le_bytes(*len) as usize;
+    let (stored_text, embedding) = value.split_at_checked(len)?;
+    if stored_text != text.as_bytes() {
+        return None;
+    }
+    let embedding = embedding.chunks_exact(std::mem::size_of::<f32>());
+    Some(embedding.map(|bytes| f32::from_le_bytes(bytes.try_into().unwrap())).collect())
+}
+
+#[cfg(test)]
+mod test {
+    use super::{decode_entry, encode_entry, entry_key, remove_api_keys, CacheWrite, Store};
+
+    #[test]
+    fn entry_roundtrip() {
+        let mut buffer = Vec::new();
+        encode_entry("kefir", &[0.5, -1.0, 2.25], &mut buffer);
+        assert_eq!(decode_entry(&buffer, "kefir"), Some(vec![0.5, -1.0, 2.25]));
+        // a different text with the same hash is a miss
+        assert_eq!(decode_entry(&buffer, "intel"), None);
+        assert_eq!(decode_entry(&buffer[..2], "kefir"), None);
+    }
+
+    #[test]
+    fn evicts_the_least_recently_used_entries() {
+        let dir = tempfile::tempdir().unwrap();
+        let Store { databases, .. } = Store::open(dir.path(), 10 * 1024 * 1024).unwrap();
+
+        let put = |text: &str| {
+            let mut value = Vec::new();
+            encode_entry(text, &[1.0], &mut value);
+            CacheWrite::Put { key: entry_key(0, text), value }
+        };
+        let mut next_use = 0;
+        databases.apply_writes(&[put("kefir"), put("intel"), put("bobby")], &mut next_use).unwrap();
+        databases
+            .apply_writes(&[CacheWrite::Touch { key: entry_key(0, "kefir") }], &mut next_use)
+            .unwrap();
+
+        let mut wtxn = databases.env.write_txn().unwrap();
+        databases.evict_least_recently_used(&mut wtxn, 2).unwrap();
+        let remaining: Vec<_> = ["kefir", "intel", "bobby"]
+            .into_iter()
+            .filter(|text| databases.embeddings.get(&wtxn, &entry_key(0, text)).unwrap().is_some())
+            .collect();
+        assert_eq!(remaining, ["kefir"]);
+        assert_eq!(databases.last_uses.len(&wtxn).unwrap(), 1);
+        assert_eq!(databases.entries_by_use.len(&wtxn).unwrap(), 1);
+    }
+
+    #[test]
+    fn api_keys_are_not_part_of_the_key() {
+        let mut options = serde_json::json!({
+            "Composite": {
+                "search": { "OpenAi": { "api_key": "secret", "dimensions": 2 } },
+                "index": { "Rest": { "api_key": "secret", "url": "http://localhost" } },
+            }
+        });
+        remove_api_keys(&mut options);
+        assert_eq!(
+            options,
+            serde_json::json!({
+                "Composite": {
+                    "search": { "OpenAi": { "dimensions": 2 } },
+                    "index": { "Rest": { "url": "http://localhost" } },
+                }
+            })
+        );
+    }
+}
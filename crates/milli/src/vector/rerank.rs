@@ -0,0 +1,630 @@
+//! Rerankers reorder the best candidates of a hybrid search by scoring each of them against the query.
+//!
+//! Unlike embedders, rerankers see the query and the document together, which makes them more accurate
+//! but much slower: they are only applied to a capped number of candidates.
+
+use std::collections::BTreeMap;
+use std::time::{Duration, Instant};
+
+use candle_core::Tensor;
+use candle_nn::{Linear, Module as _, VarBuilder};
+use candle_transformers::models::bert::{BertModel, Config, DTYPE};
+use deserr::Deserr;
+use hf_hub::api::sync::Api;
+use hf_hub::{Repo, RepoType};
+use serde::{Deserialize, Serialize};
+use tokenizers::Tokenizer;
+use utoipa::ToSchema;
+
+use super::error::{EmbedError, NewEmbedderError};
+use super::json_template::ValueTemplate;
+use super::rest::{
+    check_response, ConfigurationSource, Retry, REPEAT_PLACEHOLDER, REQUEST_PLACEHOLDER,
+};
+
+/// Number of candidates sent to the reranker when `maxCandidates` is not specified.
+pub const DEFAULT_MAX_CANDIDATES: usize = 20;
+/// Maximum value of `maxCandidates`, to bound the latency of a reranked search.
+pub const MAX_CANDIDATES_LIMIT: usize = 100;
+/// Time in milliseconds given to a remote reranker when `timeoutMs` is not specified.
+pub const DEFAULT_TIMEOUT_MS: u64 = 3_000;
+
+/// Placeholder of the request of a REST reranker that is replaced by the query.
+pub const QUERY_PLACEHOLDER: &str = "{{query}}";
+/// Placeholder of the response of a REST reranker where the scores are read.
+pub const SCORE_PLACEHOLDER: &str = "{{score}}";
+
+const DEFAULT_MODEL: &str = "cross-encoder/ms-marco-MiniLM-L-6-v2";
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Deserr, ToSchema)]
+#[serde(deny_unknown_fields, rename_all = "camelCase")]
+#[deserr(rename_all = camelCase, deny_unknown_fields)]
+pub struct RerankerSettings {
+    /// The source of the reranker.
+    ///
+    /// - `huggingFace` runs a cross-encoder model locally
+    /// - `rest` sends the query and the documents to a remote reranking API
+    pub source: RerankerSource,
+    /// The name of the cross-encoder model to use.
+    ///
+    /// Available for source `huggingFace`, defaults to `cross-encoder/ms-marco-MiniLM-L-6-v2`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[deserr(default)]
+    pub model: Option<String>,
+    /// The revision (commit SHA1) of the model to use.
+    ///
+    /// Available for source `huggingFace`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[deserr(default)]
+    pub revision: Option<String>,
+    /// URL to reach the remote reranker.
+    ///
+    /// Mandatory for source `rest`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[deserr(default)]
+    pub url: Option<String>,
+    /// The API key to pass to the remote reranker while making requests.
+    ///
+    /// Available for source `rest`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[deserr(default)]
+    pub api_key: Option<String>,
+    /// Template request to send to the remote reranker.
+    ///
+    /// Must contain `{{query}}`, and `{{text}}` repeated with `{{..}}` for the documents.
+    /// Mandatory for source `rest`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[deserr(default)]
+    #[schema(value_type = Option<serde_json::Value>)]
+    pub request: Option<serde_json::Value>,
+    /// Template response indicating where to find the scores in the response from the remote reranker.
+    ///
+    /// Must contain `{{score}}` repeated with `{{..}}`. The scores must be in the same order as the documents of the request.
+    /// Mandatory for source `rest`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[deserr(default)]
+    #[schema(value_type = Option<serde_json::Value>)]
+    pub response: Option<serde_json::Value>,
+    /// Additional headers to send to the remote reranker.
+    ///
+    /// Available for source `rest`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[deserr(default)]
+    pub headers: Option<BTreeMap<String, String>>,
+    /// The maximum number of candidates that are reranked, defaults to 20, cannot exceed 100.
+    ///
+    /// Candidates ranked after this limit keep the order of the hybrid search.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[deserr(default)]
+    pub max_candidates: Option<usize>,
+    /// The maximum time in milliseconds to wait for the remote reranker, defaults to 3000.
+    ///
+    /// Past this time the candidates keep the order of the hybrid search. Available for source `rest`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[deserr(default)]
+    pub timeout_ms: Option<u64>,
+}
+
+impl std::hash::Hash for RerankerSettings {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.source.hash(state);
+        self.model.hash(state);
+        self.revision.hash(state);
+        self.url.hash(state);
+        self.api_key.hash(state);
+        self.max_candidates.hash(state);
+        self.timeout_ms.hash(state);
+        // skip hashing the request, response and headers
+    }
+}
+
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq, Serialize, Deserialize, Deserr, ToSchema)]
+#[serde(deny_unknown_fields, rename_all = "camelCase")]
+#[deserr(rename_all = camelCase, deny_unknown_fields)]
+pub enum RerankerSource {
+    HuggingFace,
+    Rest,
+}
+
+impl std::fmt::Display for RerankerSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let s = match self {
+            RerankerSource::HuggingFace => "huggingFace",
+            RerankerSource::Rest => "rest",
+        };
+        f.write_str(s)
+    }
+}
+
+impl RerankerSettings {
+    /// Checks that the settings describe a valid reranker, returning an error message otherwise.
+    ///
+    /// This does not download models nor reach remote rerankers.
+    pub fn validate(&self) -> Result<(), String> {
+        let RerankerSettings {
+            source,
+            model,
+            revision,
+            url,
+            api_key,
+            request,
+            response,
+            headers,
+            max_candidates,
+            timeout_ms,
+        } = self;
+
+        let unavailable =
+            |field: &str| format!("Field `{field}` unavailable for reranker source `{source}`.");
+        let missing = |field: &str| {
+            format!("Missing field `{field}` (note: this field is mandatory for reranker source `{source}`)")
+        };
+
+        match source {
+            RerankerSource::HuggingFace => {
+                for (field, is_set) in [
+                    ("url", url.is_some()),
+                    ("apiKey", api_key.is_some()),
+                    ("request", request.is_some()),
+                    ("response", response.is_some()),
+                    ("headers", headers.is_some()),
+                    ("timeoutMs", timeout_ms.is_some()),
+                ] {
+                    if is_set {
+                        return Err(unavailable(field));
+                    }
+                }
+            }
+            RerankerSource::Rest => {
+                for (field, is_set) in
+                    [("model", model.is_some()), ("revision", revision.is_some())]
+                {
+                    if is_set {
+                        return Err(unavailable(field));
+                    }
+                }
+                let Some(url) = url else { return Err(missing("url")) };
+                let Some(request) = request else { return Err(missing("request")) };
+                let Some(response) = response else { return Err(missing("response")) };
+                url::Url::parse(url)
+                    .map_err(|error| format!("`url`: could not parse `{url}`: {error}"))?;
+                RerankRequest::new(request.clone())?;
+                RerankResponse::new(response.clone())?;
+            }
+        }
+
+        if *timeout_ms == Some(0) {
+            return Err("`timeoutMs` cannot be zero".to_string());
+        }
+
+        match max_candidates {
+            Some(0) => Err("`maxCandidates` cannot be zero".to_string()),
+            Some(max_candidates) if *max_candidates > MAX_CANDIDATES_LIMIT => Err(format!(
+                "`maxCandidates` cannot exceed {MAX_CANDIDATES_LIMIT}, found {max_candidates}"
+            )),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// A reranker, ready to score documents against queries.
+pub struct Reranker {
+    kind: RerankerKind,
+    max_candidates: usize,
+    timeout: Duration,
+}
+
+enum RerankerKind {
+    HuggingFace(CrossEncoder),
+    Rest(RestReranker),
+}
+
+impl std::fmt::Debug for Reranker {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let source = match &self.kind {
+            RerankerKind::HuggingFace(_) => RerankerSource::HuggingFace,
+            RerankerKind::Rest(_) => RerankerSource::Rest,
+        };
+        f.debug_struct("Reranker")
+            .field("source", &source)
+            .field("max_candidates", &self.max_candidates)
+            .field("timeout", &self.timeout)
+            .finish()
+    }
+}
+
+impl Reranker {
+    /// Builds a reranker from its settings, downloading the model if needed.
+    pub fn new(settings: RerankerSettings) -> Result<Self, NewEmbedderError> {
+        let max_candidates = settings.max_candidates.unwrap_or(DEFAULT_MAX_CANDIDATES);
+        let timeout = Duration::from_millis(settings.timeout_ms.unwrap_or(DEFAULT_TIMEOUT_MS));
+        let kind = match settings.source {
+            RerankerSource::HuggingFace => RerankerKind::HuggingFace(CrossEncoder::new(
+                settings.model.unwrap_or_else(|| DEFAULT_MODEL.to_string()),
+                settings.revision,
+            )?),
+            RerankerSource::Rest => RerankerKind::Rest(RestReranker::new(settings)?),
+        };
+        Ok(Self { kind, max_candidates, timeout })
+    }
+
+    /// The maximum number of candidates that should be reranked.
+    pub fn max_candidates(&self) -> usize {
+        self.max_candidates
+    }
+
+    /// The maximum time to wait for the scores of a remote reranker.
+    pub fn timeout(&self) -> Duration {
+        self.timeout
+    }
+
+    /// Scores each text against the query, higher is more relevant.
+    ///
+    /// The returned scores are between 0.0 and 1.0 for the `huggingFace` source, and are returned as-is by the remote reranker
+    /// for the `rest` source.
+    pub fn rerank(
+        &self,
+        query: &str,
+        texts: &[&str],
+        deadline: Option<Instant>,
+    ) -> Result<Vec<f32>, EmbedError> {
+        match &self.kind {
+            RerankerKind::HuggingFace(cross_encoder) => {
+                texts.iter().map(|text| cross_encoder.score(query, text)).collect()
+            }
+            RerankerKind::Rest(reranker) => reranker.rerank(query, texts, deadline),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct ClassifierConfig {
+    hidden_size: usize,
+    #[serde(default)]
+    id2label: Option<BTreeMap<String, String>>,
+}
+
+/// A BERT model with a sequence classification head, scoring (query, document) pairs.
+struct CrossEncoder {
+    model: BertModel,
+    pooler: Linear,
+    classifier: Linear,
+    tokenizer: Tokenizer,
+}
+
+impl CrossEncoder {
+    fn new(model: String, revision: Option<String>) -> Result<Self, NewEmbedderError> {
+        let device = match candle_core::Device::cuda_if_available(0) {
+            Ok(device) => device,
+            Err(error) => {
+                tracing::warn!("could not initialize CUDA device for Hugging Face reranker, defaulting to CPU: {}", error);
+                candle_core::Device::Cpu
+            }
+        };
+        let repo = match revision {
+            Some(revision) => Repo::with_revision(model.clone(), RepoType::Model, revision),
+            None => Repo::model(model.clone()),
+        };
+        let (config_filename, tokenizer_filename, weights_filename) = {
+            let api = Api::new().map_err(NewEmbedderError::new_api_fail)?;
+            let api = api.repo(repo);
+            let config = api.get("config.json").map_err(NewEmbedderError::api_get)?;
+            let tokenizer = api.get("tokenizer.json").map_err(NewEmbedderError::api_get)?;
+            let weights = api.get("model.safetensors").map_err(NewEmbedderError::api_get)?;
+            (config, tokenizer, weights)
+        };
+
+        let config_text = std::fs::read_to_string(&config_filename)
+            .map_err(|inner| NewEmbedderError::open_config(config_filename.clone(), inner))?;
+        let config: Config = serde_json::from_str(&config_text).map_err(|inner| {
+            NewEmbedderError::deserialize_config(
+                model.clone(),
+                config_text.clone(),
+                config_filename.clone(),
+                inner,
+            )
+        })?;
+        let classifier_config: ClassifierConfig =
+            serde_json::from_str(&config_text).map_err(|inner| {
+                NewEmbedderError::deserialize_config(
+                    model.clone(),
+                    config_text.clone(),
+                    config_filename,
+                    inner,
+                )
+            })?;
+        let tokenizer = Tokenizer::from_file(&tokenizer_filename)
+            .map_err(|inner| NewEmbedderError::open_tokenizer(tokenizer_filename, inner))?;
+
+        let vb = unsafe {
+            VarBuilder::from_mmaped_safetensors(&[weights_filename], DTYPE, &device)
+                .map_err(NewEmbedderError::safetensor_weight)?
+        };
+
+        let hidden_size = classifier_config.hidden_size;
+        let label_count = classifier_config.id2label.map_or(1, |labels| labels.len().max(1));
+
+        // `BertModel::load` looks for the weights under the `bert` prefix when they are not at the root
+        let bert = BertModel::load(vb.clone(), &config).map_err(NewEmbedderError::load_model)?;
+        let pooler = candle_nn::linear(hidden_size, hidden_size, vb.pp("bert.pooler.dense"))
+            .map_err(NewEmbedderError::load_model)?;
+        let classifier = candle_nn::linear(hidden_size, label_count, vb.pp("classifier"))
+            .map_err(NewEmbedderError::load_model)?;
+
+        tracing::debug!(model, "cross-encoder loaded");
+
+        Ok(Self { model: bert, pooler, classifier, tokenizer })
+    }
+
+    fn score(&self, query: &str, text: &str) -> Result<f32, EmbedError> {
+        let tokens = self.tokenizer.encode((query, text), true).map_err(EmbedError::tokenize)?;
+        let len = tokens.get_ids().len().min(512);
+        let token_ids = Tensor::new(&tokens.get_ids()[..len], &self.model.device)
+            .map_err(EmbedError::tensor_shape)?;
+        let token_type_ids = Tensor::new(&tokens.get_type_ids()[..len], &self.model.device)
+            .map_err(EmbedError::tensor_shape)?;
+        let token_ids = Tensor::stack(&[token_ids], 0).map_err(EmbedError::tensor_shape)?;
+        let token_type_ids =
+            Tensor::stack(&[token_type_ids], 0).map_err(EmbedError::tensor_shape)?;
+
+        let hidden_states = self
+            .model
+            .forward(&token_ids, &token_type_ids, None)
+            .map_err(EmbedError::model_forward)?;
+        // classification uses the hidden state of the [CLS] token
+        let cls = hidden_states.get_on_dim(1, 0).map_err(EmbedError::tensor_value)?;
+        let pooled = self
+            .pooler
+            .forward(&cls)
+            .and_then(|pooled| pooled.tanh())
+            .map_err(EmbedError::model_forward)?;
+        let logits = self.classifier.forward(&pooled).map_err(EmbedError::model_forward)?;
+        let logits: Vec<f32> =
+            logits.flatten_all().and_then(|t| t.to_vec1()).map_err(EmbedError::tensor_shape)?;
+        // with several labels, the last one is the "relevant" label
+        let logit = logits.last().copied().ok_or_else(EmbedError::missing_embedding)?;
+
+        Ok(1.0 / (1.0 + (-logit).exp()))
+    }
+}
+
+struct RestReranker {
+    client: ureq::Agent,
+    url: String,
+    bearer: Option<String>,
+    headers: BTreeMap<String, String>,
+    request: RerankRequest,
+    response: RerankResponse,
+}
+
+impl RestReranker {
+    fn new(settings: RerankerSettings) -> Result<Self, NewEmbedderError> {
+        let missing = |field: &str| {
+            NewEmbedderError::rest_could_not_parse_template(format!("missing `{field}`"))
+        };
+        let url = settings.url.ok_or_else(|| missing("url"))?;
+        let request = RerankRequest::new(settings.request.ok_or_else(|| missing("request"))?)
+            .map_err(NewEmbedderError::rest_could_not_parse_template)?;
+        let response = RerankResponse::new(settings.response.ok_or_else(|| missing("response"))?)
+            .map_err(NewEmbedderError::rest_could_not_parse_template)?;
+
+        let client = ureq::AgentBuilder::new()
+            .max_idle_connections(super::REQUEST_PARALLELISM * 2)
+            .max_idle_connections_per_host(super::REQUEST_PARALLELISM * 2)
+            .build();
+
+        Ok(Self {
+            client,
+            url,
+            bearer: settings.api_key.map(|api_key| format!("Bearer {api_key}")),
+            headers: settings.headers.unwrap_or_default(),
+            request,
+            response,
+        })
+    }
+
+    fn rerank(
+        &self,
+        query: &str,
+        texts: &[&str],
+        deadline: Option<Instant>,
+    ) -> Result<Vec<f32>, EmbedError> {
+        let request = self.client.post(&self.url);
+        let request = if let Some(bearer) = &self.bearer {
+            request.set("Authorization", bearer)
+        } else {
+            request
+        };
+        let mut request = request.set("Content-Type", "application/json");
+        for (header, value) in &self.headers {
+            request = request.set(header.as_str(), value.as_str());
+        }
+        if let Some(deadline) = deadline {
+            request = request.timeout(deadline.saturating_duration_since(Instant::now()));
+        }
+
+        let body = self.request.inject(query, texts);
+        let response = check_response(request.send_json(&body), ConfigurationSource::User)
+            .map_err(Retry::into_error)?;
+        let response: serde_json::Value =
+            response.into_json().map_err(EmbedError::rest_response_deserialization)?;
+
+        let scores = self.response.extract_scores(response)?;
+        if scores.len() != texts.len() {
+            return Err(EmbedError::rest_response_score_count(texts.len(), scores.len()));
+        }
+        Ok(scores)
+    }
+}
+
+struct RerankRequest {
+    template: ValueTemplate,
+}
+
+impl RerankRequest {
+    fn new(template: serde_json::Value) -> Result<Self, String> {
+        if !contains_string(&template, QUERY_PLACEHOLDER) {
+            return Err(format!("in `request`: missing `{QUERY_PLACEHOLDER}` placeholder"));
+        }
+        let template = ValueTemplate::new(template, REQUEST_PLACEHOLDER, REPEAT_PLACEHOLDER)
+            .map_err(|error| {
+                error.error_message("request", REQUEST_PLACEHOLDER, REPEAT_PLACEHOLDER)
+            })?;
+        if !template.has_array_value() {
+            return Err(format!(
+                "in `request`: `{REQUEST_PLACEHOLDER}` must be repeated with `{REPEAT_PLACEHOLDER}` to send all the documents"
+            ));
+        }
+        Ok(Self { template })
+    }
+
+    fn inject(&self, query: &str, texts: &[&str]) -> serde_json::Value {
+        // unwrap: we never rerank an empty list of texts
+        let mut body =
+            self.template.inject(texts.iter().map(|text| serde_json::json!(text))).unwrap();
+        inject_query(&mut body, query);
+        body
+    }
+}
+
+struct RerankResponse {
+    template: ValueTemplate,
+}
+
+impl RerankResponse {
+    fn new(template: serde_json::Value) -> Result<Self, String> {
+        let template = ValueTemplate::new(template, SCORE_PLACEHOLDER, REPEAT_PLACEHOLDER)
+            .map_err(|error| {
+                error.error_message("response", SCORE_PLACEHOLDER, REPEAT_PLACEHOLDER)
+            })?;
+        if !template.has_array_value() {
+            return Err(format!(
+                "in `response`: `{SCORE_PLACEHOLDER}` must be repeated with `{REPEAT_PLACEHOLDER}` to read the score of all the documents"
+            ));
+        }
+        Ok(Self { template })
+    }
+
+    fn extract_scores(&self, response: serde_json::Value) -> Result<Vec<f32>, EmbedError> {
+        self.template.extract(response).map_err(|error| {
+            EmbedError::rest_score_extraction_error(error.error_message(
+                "response",
+                SCORE_PLACEHOLDER,
+                "a number",
+            ))
+        })
+    }
+}
+
+fn contains_string(value: &serde_json::Value, needle: &str) -> bool {
+    match value {
+        serde_json::Value::String(s) => s == needle,
+        serde_json::Value::Array(values) => values.iter().any(|v| contains_string(v, needle)),
+        serde_json::Value::Object(object) => object.values().any(|v| contains_string(v, needle)),
+        serde_json::Value::Null | serde_json::Value::Bool(_) | serde_json::Value::Number(_) => {
+            false
+        }
+    }
+}
+
+fn inject_query(value: &mut serde_json::Value, query: &str) {
+    match value {
+        serde_json::Value::String(s) if s == QUERY_PLACEHOLDER => {
+            *value = serde_json::Value::String(query.to_string())
+        }
+        serde_json::Value::Array(values) => {
+            values.iter_mut().for_each(|value| inject_query(value, query))
+        }
+        serde_json::Value::Object(object) => {
+            object.values_mut().for_each(|value| inject_query(value, query))
+        }
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use serde_json::json;
+
+    use super::{RerankRequest, RerankResponse, RerankerSettings, RerankerSource};
+
+    fn rest_settings(request: serde_json::Value, response: serde_json::Value) -> RerankerSettings {
+        RerankerSettings {
+            source: RerankerSource::Rest,
+            model: None,
+            revision: None,
+            url: Some("http://localhost:7777/rerank".into()),
+            api_key: None,
+            request: Some(request),
+            response: Some(response),
+            headers: None,
+            max_candidates: None,
+            timeout_ms: None,
+        }
+    }
+
+    #[test]
+    fn rest_request_injects_query_and_documents() {
+        let request = RerankRequest::new(json!({
+            "query": "{{query}}",
+            "documents": ["{{text}}", "{{..}}"]
+        }))
+        .unwrap();
+        assert_eq!(
+            request.inject("cat", &["kefir", "intel"]),
+            json!({ "query": "cat", "documents": ["kefir", "intel"] })
+        );
+    }
+
+    #[test]
+    fn rest_response_extracts_scores() {
+        let response =
+            RerankResponse::new(json!({ "results": [{ "score": "{{score}}" }, "{{..}}"] }))
+                .unwrap();
+        let scores = response
+            .extract_scores(json!({ "results": [{ "score": 0.5 }, { "score": 0.25 }] }))
+            .unwrap();
+        assert_eq!(scores, vec![0.5, 0.25]);
+    }
+
+    #[test]
+    fn validate_settings() {
+        let valid = rest_settings(
+            json!({ "query": "{{query}}", "documents": ["{{text}}", "{{..}}"] }),
+            json!({ "scores": ["{{score}}", "{{..}}"] }),
+        );
+        valid.validate().unwrap();
+
+        let missing_query = rest_settings(
+            json!({ "documents": ["{{text}}", "{{..}}"] }),
+            json!({ "scores": ["{{score}}", "{{..}}"] }),
+        );
+        assert_eq!(
+            missing_query.validate().unwrap_err(),
+            "in `request`: missing `{{query}}` placeholder"
+        );
+
+        let single_document = rest_settings(
+            json!({ "query": "{{query}}", "document": "{{text}}" }),
+            json!({ "scores": ["{{score}}", "{{..}}"] }),
+        );
+        assert!(single_document.validate().is_err());
+
+        let too_many_candidates = RerankerSettings { max_candidates: Some(1000), ..valid.clone() };
+        assert!(too_many_candidates.validate().is_err());
+
+        let no_timeout = RerankerSettings { timeout_ms: Some(0), ..valid.clone() };
+        assert_eq!(no_timeout.validate().unwrap_err(), "`timeoutMs` cannot be zero");
+
+        let hf_with_url = RerankerSettings {
+            source: RerankerSource::HuggingFace,
+            request: None,
+            response: None,
+            ..valid
+        };
+        assert_eq!(
+            hf_with_url.validate().unwrap_err(),
+            "Field `url` unavailable for reranker source `huggingFace`."
+        );
+    }
+}
@@ -6,9 +6,10 @@ use rand::Rng;
 use rayon::iter::{IntoParallelIterator as _, ParallelIterator as _};
 use rayon::slice::ParallelSlice as _;
 use serde::{Deserialize, Serialize};
+use serde_json::value::RawValue;
 
 use super::error::EmbedErrorKind;
-use super::json_template::ValueTemplate;
+use super::json_template::{JsonTemplate, ValueTemplate};
 use super::{
     DistributionShift, EmbedError, Embedding, EmbeddingCache, NewEmbedderError, REQUEST_PARALLELISM,
 };
@@ -90,6 +91,9 @@ struct EmbedderData {
     request: Request,
     response: Response,
     configuration_source: ConfigurationSource,
+    /// Whether the inputs to embed at indexing time are rendered JSON fragments rather than texts
+    embeds_fragments: bool,
+    search_fragment: Option<JsonTemplate>,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
@@ -101,6 +105,12 @@ pub struct EmbedderOptions {
     pub request: serde_json::Value,
     pub response: serde_json::Value,
     pub headers: BTreeMap<String, String>,
+    /// JSON template rendered from each document and sent in place of the document template.
+    #[serde(default)]
+    pub indexing_fragment: Option<serde_json::Value>,
+    /// JSON template rendered from the query and sent in place of the query text.
+    #[serde(default)]
+    pub search_fragment: Option<serde_json::Value>,
 }
 
 impl std::hash::Hash for EmbedderOptions {
@@ -109,7 +119,7 @@ impl std::hash::Hash for EmbedderOptions {
         self.distribution.hash(state);
         self.dimensions.hash(state);
         self.url.hash(state);
-        // skip hashing the request and response
+        // skip hashing the request, response and fragments
         // collisions in regular usage should be minimal,
         // and the list is limited to 256 values anyway
     }
@@ -139,6 +149,12 @@ impl Embedder {
 
         let request = Request::new(options.request)?;
         let response = Response::new(options.response, &request)?;
+        let search_fragment =
+            options.search_fragment.map(JsonTemplate::new).transpose().map_err(|error| {
+                NewEmbedderError::rest_could_not_parse_template(format!(
+                    "in `searchFragment`: {error}"
+                ))
+            })?;
 
         let data = EmbedderData {
             client,
@@ -148,6 +164,8 @@ impl Embedder {
             response,
             configuration_source,
             headers: options.headers,
+            embeds_fragments: options.indexing_fragment.is_some(),
+            search_fragment,
         };
 
         let dimensions = if let Some(dimensions) = options.dimensions {
@@ -169,7 +187,7 @@ impl Embedder {
         texts: Vec<String>,
         deadline: Option<Instant>,
     ) -> Result<Vec<Embedding>, EmbedError> {
-        embed(&self.data, texts.as_slice(), texts.len(), Some(self.dimensions), deadline)
+        self.embed_ref(texts.as_slice(), deadline)
     }
 
     pub fn embed_ref<S>(
@@ -180,7 +198,42 @@ impl Embedder {
     where
         S: AsRef<str> + Serialize,
     {
-        embed(&self.data, texts, texts.len(), Some(self.dimensions), deadline)
+        if self.data.embeds_fragments {
+            // the texts are fragments rendered by the indexing prompt, send them as JSON values
+            let fragments: Vec<&RawValue> = texts
+                .iter()
+                .map(|text| serde_json::from_str(text.as_ref()))
+                .collect::<Result<_, _>>()
+                .map_err(EmbedError::rest_fragment_deserialization)?;
+            embed(
+                &self.data,
+                fragments.as_slice(),
+                fragments.len(),
+                Some(self.dimensions),
+                deadline,
+            )
+        } else {
+            embed(&self.data, texts, texts.len(), Some(self.dimensions), deadline)
+        }
+    }
+
+    /// Embeds a search query.
+    ///
+    /// If the embedder has a search fragment, the fragment rendered with the query is sent instead of the query.
+    pub fn embed_search(
+        &self,
+        text: &str,
+        deadline: Option<Instant>,
+    ) -> Result<Embedding, EmbedError> {
+        let mut embeddings = match &self.data.search_fragment {
+            Some(fragment) => {
+                let fragment = render_search_fragment(fragment, text)?;
+                embed(&self.data, &[fragment], 1, Some(self.dimensions), deadline)?
+            }
+            None => embed(&self.data, &[text], 1, Some(self.dimensions), deadline)?,
+        };
+        // unwrap: guaranteed that embeddings.len() == 1, otherwise the previous line terminated in error
+        Ok(embeddings.pop().unwrap())
     }
 
     pub fn embed_tokens(
@@ -272,12 +325,25 @@ impl Embedder {
 }
 
 fn infer_dimensions(data: &EmbedderData) -> Result<usize, NewEmbedderError> {
-    let v = embed(data, ["test"].as_slice(), 1, None, None)
-        .map_err(NewEmbedderError::could_not_determine_dimension)?;
+    let v = match &data.search_fragment {
+        Some(fragment) => render_search_fragment(fragment, "test")
+            .and_then(|fragment| embed(data, &[fragment], 1, None, None)),
+        None => embed(data, ["test"].as_slice(), 1, None, None),
+    }
+    .map_err(NewEmbedderError::could_not_determine_dimension)?;
     // unwrap: guaranteed that v.len() == 1, otherwise the previous line terminated in error
     Ok(v.first().unwrap().len())
 }
 
+fn render_search_fragment(
+    fragment: &JsonTemplate,
+    query: &str,
+) -> Result<serde_json::Value, EmbedError> {
+    fragment
+        .render(&liquid::object!({ "q": query }))
+        .map_err(EmbedError::rest_search_fragment_render)
+}
+
 fn embed<S>(
     data: &EmbedderData,
     inputs: &[S],
@@ -344,7 +410,7 @@ where
     })
 }
 
-fn check_response(
+pub(super) fn check_response(
     response: Result<ureq::Response, ureq::Error>,
     configuration_source: ConfigurationSource,
 ) -> Result<ureq::Response, Retry> {
@@ -6,8 +6,10 @@ use roaring::RoaringBitmap;
 use serde::{Deserialize, Serialize};
 use utoipa::ToSchema;
 
+use super::chunking::ChunkingSettings;
 use super::composite::SubEmbedderOptions;
 use super::hf::OverridePooling;
+use super::rerank::RerankerSettings;
 use super::{ollama, openai, DistributionShift, EmbedderOptions};
 use crate::prompt::{default_max_bytes, PromptData};
 use crate::update::Setting;
@@ -203,6 +205,22 @@ pub struct EmbeddingSettings {
     pub document_template_max_bytes: Setting<usize>,
     #[serde(default, skip_serializing_if = "Setting::is_not_set")]
     #[deserr(default)]
+    #[schema(value_type = Option<ChunkingSettings>)]
+    /// Splits rendered texts into several chunks, that are embedded separately.
+    ///
+    /// Documents are then ranked by their chunk closest to the query. When chunking,
+    /// `documentTemplateMaxBytes` applies to each chunk rather than to the whole text.
+    ///
+    /// # Availability
+    ///
+    /// - This parameter is available for source `openAi`, `huggingFace`, `ollama` and `rest`, but not for nested embedders
+    ///
+    /// # 🔄 Reindexing
+    ///
+    /// - 🏗️ Changing the value of this parameter always regenerates embeddings
+    pub chunking: Setting<ChunkingSettings>,
+    #[serde(default, skip_serializing_if = "Setting::is_not_set")]
+    #[deserr(default)]
     #[schema(value_type = Option<String>)]
     /// URL to reach the remote embedder.
     ///
@@ -266,6 +284,37 @@ pub struct EmbeddingSettings {
     ///
     /// - 🌱 Changing the value of this parameter never regenerates embeddings
     pub headers: Setting<BTreeMap<String, String>>,
+    #[serde(default, skip_serializing_if = "Setting::is_not_set")]
+    #[deserr(default)]
+    #[schema(value_type = Option<serde_json::Value>)]
+    /// A JSON value whose strings are liquid templates, used to render documents to a multimodal fragment.
+    ///
+    /// When set, Meilisearch renders this fragment for each document and puts it in place of `{{text}}` in the request,
+    /// instead of the text rendered by `documentTemplate`. This allows sending e.g. images to the embedder.
+    ///
+    /// # Availability
+    ///
+    /// - This parameter is available for source `rest`
+    ///
+    /// # 🔄 Reindexing
+    ///
+    /// - 🏗️ When modified, embeddings are regenerated for documents whose rendering through the fragment produces a different value.
+    pub indexing_fragment: Setting<serde_json::Value>,
+    #[serde(default, skip_serializing_if = "Setting::is_not_set")]
+    #[deserr(default)]
+    #[schema(value_type = Option<serde_json::Value>)]
+    /// A JSON value whose strings are liquid templates, used to render search queries to a multimodal fragment.
+    ///
+    /// The query is available as `{{q}}`. When set, the rendered fragment is put in place of `{{text}}` in the request.
+    ///
+    /// # Availability
+    ///
+    /// - This parameter is available for source `rest`
+    ///
+    /// # 🔄 Reindexing
+    ///
+    /// - 🌱 Changing the value of this parameter never regenerates embeddings
+    pub search_fragment: Setting<serde_json::Value>,
 
     #[serde(default, skip_serializing_if = "Setting::is_not_set")]
     #[deserr(default)]
@@ -290,6 +339,22 @@ pub struct EmbeddingSettings {
     ///
     /// - 🌱 Changing the value of this parameter never regenerates embeddings
     pub distribution: Setting<DistributionShift>,
+
+    #[serde(default, skip_serializing_if = "Setting::is_not_set")]
+    #[deserr(default)]
+    #[schema(value_type = Option<RerankerSettings>)]
+    /// Reranker applied to the best candidates of hybrid searches using this embedder.
+    ///
+    /// Documents are rendered with the `documentTemplate` before being scored against the query.
+    ///
+    /// # Availability
+    ///
+    /// - This parameter is available for all embedders, but not for nested embedders
+    ///
+    /// # 🔄 Reindexing
+    ///
+    /// - 🌱 Changing the value of this parameter never regenerates embeddings
+    pub reranker: Setting<RerankerSettings>,
 }
 
 #[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq, Deserr, ToSchema)]
@@ -629,7 +694,11 @@ impl SettingsDiff {
                     mut distribution,
                     mut headers,
                     mut document_template_max_bytes,
+                    mut chunking,
                     binary_quantized: mut binary_quantize,
+                    mut indexing_fragment,
+                    mut search_fragment,
+                    mut reranker,
                 } = old;
 
                 let EmbeddingSettings {
@@ -648,7 +717,11 @@ impl SettingsDiff {
                     distribution: new_distribution,
                     headers: new_headers,
                     document_template_max_bytes: new_document_template_max_bytes,
+                    chunking: new_chunking,
                     binary_quantized: new_binary_quantize,
+                    indexing_fragment: new_indexing_fragment,
+                    search_fragment: new_search_fragment,
+                    reranker: new_reranker,
                 } = new;
 
                 if matches!(binary_quantize, Setting::Set(true))
@@ -675,6 +748,8 @@ impl SettingsDiff {
                     &mut request,
                     &mut response,
                     &mut headers,
+                    &mut indexing_fragment,
+                    &mut search_fragment,
                     new_source,
                     new_model,
                     new_revision,
@@ -687,8 +762,21 @@ impl SettingsDiff {
                     new_request,
                     new_response,
                     new_headers,
+                    new_indexing_fragment,
+                    new_search_fragment,
                 );
 
+                // embedders without a document template cannot chunk it
+                if matches!(
+                    source,
+                    Setting::Set(EmbedderSource::UserProvided | EmbedderSource::Composite)
+                ) {
+                    chunking = Setting::NotSet;
+                }
+                if chunking.apply(new_chunking) {
+                    ReindexAction::push_action(&mut reindex_action, ReindexAction::FullReindex);
+                }
+
                 let binary_quantize_changed = binary_quantize.apply(new_binary_quantize);
 
                 // changes to the *search* embedder never triggers any reindexing
@@ -700,6 +788,8 @@ impl SettingsDiff {
                 )?;
 
                 distribution.apply(new_distribution);
+                // changes to the reranker only affect search
+                reranker.apply(new_reranker);
 
                 let updated_settings = EmbeddingSettings {
                     source,
@@ -717,7 +807,11 @@ impl SettingsDiff {
                     distribution,
                     headers,
                     document_template_max_bytes,
+                    chunking,
                     binary_quantized: binary_quantize,
+                    indexing_fragment,
+                    search_fragment,
+                    reranker,
                 };
 
                 match reindex_action {
@@ -805,6 +899,9 @@ impl SettingsDiff {
                     &mut request,
                     &mut response,
                     &mut headers,
+                    // fragments are not available for sub-embedders
+                    &mut Setting::NotSet,
+                    &mut Setting::NotSet,
                     new_source,
                     new_model,
                     new_revision,
@@ -817,6 +914,8 @@ impl SettingsDiff {
                     new_request,
                     new_response,
                     new_headers,
+                    Setting::NotSet,
+                    Setting::NotSet,
                 );
 
                 // update phony settings, it is always an error to have them set.
@@ -866,6 +965,8 @@ impl SettingsDiff {
         request: &mut Setting<serde_json::Value>,
         response: &mut Setting<serde_json::Value>,
         headers: &mut Setting<BTreeMap<String, String>>,
+        indexing_fragment: &mut Setting<serde_json::Value>,
+        search_fragment: &mut Setting<serde_json::Value>,
         new_source: Setting<EmbedderSource>,
         new_model: Setting<String>,
         new_revision: Setting<String>,
@@ -878,6 +979,8 @@ impl SettingsDiff {
         new_request: Setting<serde_json::Value>,
         new_response: Setting<serde_json::Value>,
         new_headers: Setting<BTreeMap<String, String>>,
+        new_indexing_fragment: Setting<serde_json::Value>,
+        new_search_fragment: Setting<serde_json::Value>,
     ) {
         // **Warning**: do not use short-circuiting || here, we want all these operations applied
         if source.apply(new_source) {
@@ -895,6 +998,8 @@ impl SettingsDiff {
                 document_template,
                 document_template_max_bytes,
                 headers,
+                indexing_fragment,
+                search_fragment,
                 // send dummy values, the source cannot recursively be composite
                 &mut Setting::NotSet,
                 &mut Setting::NotSet,
@@ -953,8 +1058,13 @@ impl SettingsDiff {
             }
         }
 
+        if indexing_fragment.apply(new_indexing_fragment) {
+            ReindexAction::push_action(reindex_action, ReindexAction::RegeneratePrompts);
+        }
+
         api_key.apply(new_api_key);
         headers.apply(new_headers);
+        search_fragment.apply(new_search_fragment);
     }
 }
 
@@ -981,6 +1091,8 @@ fn apply_default_for_source(
     document_template: &mut Setting<String>,
     document_template_max_bytes: &mut Setting<usize>,
     headers: &mut Setting<BTreeMap<String, String>>,
+    indexing_fragment: &mut Setting<serde_json::Value>,
+    search_fragment: &mut Setting<serde_json::Value>,
     search_embedder: &mut Setting<SubEmbeddingSettings>,
     indexing_embedder: &mut Setting<SubEmbeddingSettings>,
 ) {
@@ -994,6 +1106,8 @@ fn apply_default_for_source(
             *request = Setting::NotSet;
             *response = Setting::NotSet;
             *headers = Setting::NotSet;
+            *indexing_fragment = Setting::NotSet;
+            *search_fragment = Setting::NotSet;
             *search_embedder = Setting::NotSet;
             *indexing_embedder = Setting::NotSet;
         }
@@ -1006,6 +1120,8 @@ fn apply_default_for_source(
             *request = Setting::NotSet;
             *response = Setting::NotSet;
             *headers = Setting::NotSet;
+            *indexing_fragment = Setting::NotSet;
+            *search_fragment = Setting::NotSet;
             *search_embedder = Setting::NotSet;
             *indexing_embedder = Setting::NotSet;
         }
@@ -1018,6 +1134,8 @@ fn apply_default_for_source(
             *request = Setting::NotSet;
             *response = Setting::NotSet;
             *headers = Setting::NotSet;
+            *indexing_fragment = Setting::NotSet;
+            *search_fragment = Setting::NotSet;
             *search_embedder = Setting::NotSet;
             *indexing_embedder = Setting::NotSet;
         }
@@ -1030,6 +1148,8 @@ fn apply_default_for_source(
             *request = Setting::Reset;
             *response = Setting::Reset;
             *headers = Setting::Reset;
+            *indexing_fragment = Setting::NotSet;
+            *search_fragment = Setting::NotSet;
             *search_embedder = Setting::NotSet;
             *indexing_embedder = Setting::NotSet;
         }
@@ -1044,6 +1164,8 @@ fn apply_default_for_source(
             *document_template = Setting::NotSet;
             *document_template_max_bytes = Setting::NotSet;
             *headers = Setting::NotSet;
+            *indexing_fragment = Setting::NotSet;
+            *search_fragment = Setting::NotSet;
             *search_embedder = Setting::NotSet;
             *indexing_embedder = Setting::NotSet;
         }
@@ -1058,6 +1180,8 @@ fn apply_default_for_source(
             *document_template = Setting::NotSet;
             *document_template_max_bytes = Setting::NotSet;
             *headers = Setting::NotSet;
+            *indexing_fragment = Setting::NotSet;
+            *search_fragment = Setting::NotSet;
             *search_embedder = Setting::Reset;
             *indexing_embedder = Setting::Reset;
         }
@@ -1118,14 +1242,18 @@ pub enum MetaEmbeddingSetting {
     Dimensions,
     DocumentTemplate,
     DocumentTemplateMaxBytes,
+    Chunking,
     Url,
     Request,
     Response,
     Headers,
+    IndexingFragment,
+    SearchFragment,
     SearchEmbedder,
     IndexingEmbedder,
     Distribution,
     BinaryQuantized,
+    Reranker,
 }
 
 impl MetaEmbeddingSetting {
@@ -1140,14 +1268,18 @@ impl MetaEmbeddingSetting {
             Dimensions => "dimensions",
             DocumentTemplate => "documentTemplate",
             DocumentTemplateMaxBytes => "documentTemplateMaxBytes",
+            Chunking => "chunking",
             Url => "url",
             Request => "request",
             Response => "response",
             Headers => "headers",
+            IndexingFragment => "indexingFragment",
+            SearchFragment => "searchFragment",
             SearchEmbedder => "searchEmbedder",
             IndexingEmbedder => "indexingEmbedder",
             Distribution => "distribution",
             BinaryQuantized => "binaryQuantized",
+            Reranker => "reranker",
         }
     }
 }
@@ -1168,11 +1300,15 @@ impl EmbeddingSettings {
         response: &Setting<serde_json::Value>,
         document_template: &Setting<String>,
         document_template_max_bytes: &Setting<usize>,
+        chunking: &Setting<ChunkingSettings>,
         headers: &Setting<BTreeMap<String, String>>,
+        indexing_fragment: &Setting<serde_json::Value>,
+        search_fragment: &Setting<serde_json::Value>,
         search_embedder: &Setting<SubEmbeddingSettings>,
         indexing_embedder: &Setting<SubEmbeddingSettings>,
         binary_quantized: &Setting<bool>,
         distribution: &Setting<DistributionShift>,
+        reranker: &Setting<RerankerSettings>,
     ) -> Result<(), UserError> {
         Self::check_setting(embedder_name, source, MetaEmbeddingSetting::Model, context, model)?;
         Self::check_setting(
@@ -1226,6 +1362,13 @@ impl EmbeddingSettings {
             context,
             document_template_max_bytes,
         )?;
+        Self::check_setting(
+            embedder_name,
+            source,
+            MetaEmbeddingSetting::Chunking,
+            context,
+            chunking,
+        )?;
         Self::check_setting(
             embedder_name,
             source,
@@ -1233,6 +1376,20 @@ impl EmbeddingSettings {
             context,
             headers,
         )?;
+        Self::check_setting(
+            embedder_name,
+            source,
+            MetaEmbeddingSetting::IndexingFragment,
+            context,
+            indexing_fragment,
+        )?;
+        Self::check_setting(
+            embedder_name,
+            source,
+            MetaEmbeddingSetting::SearchFragment,
+            context,
+            search_fragment,
+        )?;
         Self::check_setting(
             embedder_name,
             source,
@@ -1260,6 +1417,13 @@ impl EmbeddingSettings {
             MetaEmbeddingSetting::Distribution,
             context,
             distribution,
+        )?;
+        Self::check_setting(
+            embedder_name,
+            source,
+            MetaEmbeddingSetting::Reranker,
+            context,
+            reranker,
         )
     }
 
@@ -1320,9 +1484,13 @@ impl EmbeddingSettings {
         use MetaEmbeddingSetting::*;
         use NestingContext::*;
         match (source, field, context) {
-            (_, Distribution | BinaryQuantized, NotNested) => FieldStatus::Allowed,
-            (_, Distribution | BinaryQuantized, _) => FieldStatus::Disallowed,
+            (_, Distribution | BinaryQuantized | Reranker, NotNested) => FieldStatus::Allowed,
+            (_, Distribution | BinaryQuantized | Reranker, _) => FieldStatus::Disallowed,
             (_, DocumentTemplate | DocumentTemplateMaxBytes, Search) => FieldStatus::Disallowed,
+            (OpenAi | HuggingFace | Ollama | Rest, Chunking, NotNested) => FieldStatus::Allowed,
+            (_, Chunking, _) => FieldStatus::Disallowed,
+            (Rest, IndexingFragment | SearchFragment, NotNested) => FieldStatus::Allowed,
+            (_, IndexingFragment | SearchFragment, _) => FieldStatus::Disallowed,
             (
                 OpenAi,
                 Source
@@ -1521,6 +1689,7 @@ impl EmbeddingSettings {
             dimensions: Setting::NotSet,
             document_template,
             document_template_max_bytes,
+            chunking: Setting::NotSet,
             url: Setting::NotSet,
             request: Setting::NotSet,
             response: Setting::NotSet,
@@ -1529,6 +1698,9 @@ impl EmbeddingSettings {
             indexing_embedder: Setting::NotSet,
             distribution: Setting::some_or_not_set(distribution),
             binary_quantized: Setting::some_or_not_set(quantized),
+            indexing_fragment: Setting::NotSet,
+            search_fragment: Setting::NotSet,
+            reranker: Setting::NotSet,
         }
     }
 
@@ -1553,6 +1725,7 @@ impl EmbeddingSettings {
             dimensions: Setting::some_or_not_set(dimensions),
             document_template,
             document_template_max_bytes,
+            chunking: Setting::NotSet,
             url: Setting::some_or_not_set(url),
             request: Setting::NotSet,
             response: Setting::NotSet,
@@ -1561,6 +1734,9 @@ impl EmbeddingSettings {
             indexing_embedder: Setting::NotSet,
             distribution: Setting::some_or_not_set(distribution),
             binary_quantized: Setting::some_or_not_set(quantized),
+            indexing_fragment: Setting::NotSet,
+            search_fragment: Setting::NotSet,
+            reranker: Setting::NotSet,
         }
     }
 
@@ -1585,6 +1761,7 @@ impl EmbeddingSettings {
             dimensions: Setting::some_or_not_set(dimensions),
             document_template,
             document_template_max_bytes,
+            chunking: Setting::NotSet,
             url: Setting::some_or_not_set(url),
             request: Setting::NotSet,
             response: Setting::NotSet,
@@ -1593,6 +1770,9 @@ impl EmbeddingSettings {
             indexing_embedder: Setting::NotSet,
             distribution: Setting::some_or_not_set(distribution),
             binary_quantized: Setting::some_or_not_set(quantized),
+            indexing_fragment: Setting::NotSet,
+            search_fragment: Setting::NotSet,
+            reranker: Setting::NotSet,
         }
     }
 
@@ -1609,6 +1789,7 @@ impl EmbeddingSettings {
             dimensions: Setting::Set(dimensions),
             document_template: Setting::NotSet,
             document_template_max_bytes: Setting::NotSet,
+            chunking: Setting::NotSet,
             url: Setting::NotSet,
             request: Setting::NotSet,
             response: Setting::NotSet,
@@ -1617,6 +1798,9 @@ impl EmbeddingSettings {
             indexing_embedder: Setting::NotSet,
             distribution: Setting::some_or_not_set(distribution),
             binary_quantized: Setting::some_or_not_set(quantized),
+            indexing_fragment: Setting::NotSet,
+            search_fragment: Setting::NotSet,
+            reranker: Setting::NotSet,
         }
     }
 
@@ -1629,6 +1813,8 @@ impl EmbeddingSettings {
             response,
             distribution,
             headers,
+            indexing_fragment,
+            search_fragment,
         }: super::rest::EmbedderOptions,
         document_template: Setting<String>,
         document_template_max_bytes: Setting<usize>,
@@ -1643,6 +1829,7 @@ impl EmbeddingSettings {
             dimensions: Setting::some_or_not_set(dimensions),
             document_template,
             document_template_max_bytes,
+            chunking: Setting::NotSet,
             url: Setting::Set(url),
             request: Setting::Set(request),
             response: Setting::Set(response),
@@ -1651,16 +1838,20 @@ impl EmbeddingSettings {
             search_embedder: Setting::NotSet,
             indexing_embedder: Setting::NotSet,
             binary_quantized: Setting::some_or_not_set(quantized),
+            indexing_fragment: Setting::some_or_not_set(indexing_fragment),
+            search_fragment: Setting::some_or_not_set(search_fragment),
+            reranker: Setting::NotSet,
         }
     }
 }
 
 impl From<EmbeddingConfig> for EmbeddingSettings {
     fn from(value: EmbeddingConfig) -> Self {
-        let EmbeddingConfig { embedder_options, prompt, quantized } = value;
+        let EmbeddingConfig { embedder_options, mut prompt, quantized, reranker } = value;
+        let chunking = Setting::some_or_not_set(prompt.chunking.take());
         let document_template_max_bytes =
             Setting::Set(prompt.max_bytes.unwrap_or(default_max_bytes()).get());
-        match embedder_options {
+        let mut this = match embedder_options {
             super::EmbedderOptions::HuggingFace(options) => Self::from_hugging_face(
                 options,
                 Setting::Set(prompt.template),
@@ -1701,10 +1892,14 @@ impl From<EmbeddingConfig> for EmbeddingSettings {
                 binary_quantized: Setting::some_or_not_set(quantized),
                 document_template: Setting::NotSet,
                 document_template_max_bytes: Setting::NotSet,
+                chunking: Setting::NotSet,
                 url: Setting::NotSet,
                 request: Setting::NotSet,
                 response: Setting::NotSet,
                 headers: Setting::NotSet,
+                indexing_fragment: Setting::NotSet,
+                search_fragment: Setting::NotSet,
+                reranker: Setting::NotSet,
                 distribution: Setting::some_or_not_set(search.distribution()),
                 search_embedder: Setting::Set(SubEmbeddingSettings::from_options(
                     search,
@@ -1717,7 +1912,10 @@ impl From<EmbeddingConfig> for EmbeddingSettings {
                     document_template_max_bytes,
                 )),
             },
-        }
+        };
+        this.reranker = Setting::some_or_not_set(reranker);
+        this.chunking = chunking;
+        this
     }
 }
 
@@ -1773,6 +1971,7 @@ impl From<EmbeddingSettings> for SubEmbeddingSettings {
             dimensions,
             document_template,
             document_template_max_bytes,
+            chunking: _,
             url,
             request,
             response,
@@ -1781,6 +1980,9 @@ impl From<EmbeddingSettings> for SubEmbeddingSettings {
             search_embedder: _,
             indexing_embedder: _,
             distribution: _,
+            indexing_fragment: _,
+            search_fragment: _,
+            reranker: _,
         } = value;
         Self {
             source,
@@ -1815,6 +2017,7 @@ impl From<EmbeddingSettings> for EmbeddingConfig {
             dimensions,
             document_template,
             document_template_max_bytes,
+            chunking,
             url,
             request,
             response,
@@ -1823,9 +2026,13 @@ impl From<EmbeddingSettings> for EmbeddingConfig {
             binary_quantized,
             search_embedder,
             mut indexing_embedder,
+            indexing_fragment,
+            search_fragment,
+            reranker,
         } = value;
 
         this.quantized = binary_quantized.set();
+        this.reranker = reranker.set();
         if let Some((template, document_template_max_bytes)) =
             match (document_template, &mut indexing_embedder) {
                 (Setting::Set(template), _) => Some((template, document_template_max_bytes)),
@@ -1846,7 +2053,8 @@ impl From<EmbeddingSettings> for EmbeddingConfig {
                 .and_then(NonZeroUsize::new)
                 .unwrap_or(default_max_bytes());
 
-            this.prompt = PromptData { template, max_bytes: Some(max_bytes) }
+            this.prompt =
+                PromptData { template, max_bytes: Some(max_bytes), chunking: chunking.set() }
         }
 
         if let Some(source) = source.set() {
@@ -1872,6 +2080,8 @@ impl From<EmbeddingSettings> for EmbeddingConfig {
                     headers,
                     dimensions,
                     distribution,
+                    indexing_fragment,
+                    search_fragment,
                 )
                 .into(),
                 EmbedderSource::Composite => {
@@ -1937,6 +2147,8 @@ impl SubEmbedderOptions {
                 headers,
                 dimensions,
                 distribution,
+                Setting::NotSet,
+                Setting::NotSet,
             ),
             EmbedderSource::Composite => panic!("nested composite embedders"),
         }
@@ -2006,6 +2218,8 @@ impl SubEmbedderOptions {
         headers: Setting<BTreeMap<String, String>>,
         dimensions: Setting<usize>,
         distribution: Setting<DistributionShift>,
+        indexing_fragment: Setting<serde_json::Value>,
+        search_fragment: Setting<serde_json::Value>,
     ) -> Self {
         Self::Rest(super::rest::EmbedderOptions {
             api_key: api_key.set(),
@@ -2015,6 +2229,8 @@ impl SubEmbedderOptions {
             response,
             distribution: distribution.set(),
             headers: headers.set().unwrap_or_default(),
+            indexing_fragment: indexing_fragment.set(),
+            search_fragment: search_fragment.set(),
         })
     }
     fn ollama(